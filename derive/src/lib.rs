@@ -0,0 +1,316 @@
+//! Derive macros for the `dsp-chain` crate.
+//!
+//! These are re-exported by `dsp` when its `derive` feature is enabled and should be used via
+//! those re-exports, as the generated code refers to items within `dsp`.
+
+#![forbid(unsafe_code)]
+#![deny(missing_docs)]
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Ident, Type};
+
+/// Implement `Node<F>` for an enum of nodes by delegating every method to the node held by the
+/// current variant.
+///
+/// Each variant must hold exactly one field, the type of which implements `Node<F>`. This allows
+/// a **Graph** to hold several kinds of node without boxing them as trait objects, so that calls
+/// to the nodes are dispatched by a `match` that the compiler may inline.
+///
+/// For each type that is held by only one variant, `From<T>` and `NodeVariant<T>` are also
+/// implemented for the enum, so that nodes may be added via `Graph::add_node_typed` and accessed
+/// as their own type via `Graph::node_as`.
+///
+/// ```ignore
+/// use dsp::NodeEnum;
+/// use dsp::nodes::{Expander, PhaseMeter};
+///
+/// #[derive(NodeEnum)]
+/// enum DspNode {
+///     Expander(Expander),
+///     PhaseMeter(PhaseMeter),
+/// }
+/// ```
+#[proc_macro_derive(NodeEnum)]
+pub fn derive_node_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    node_enum(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// A variant holding a single node.
+struct Variant {
+    /// The pattern binding the node as `node`, e.g. `Name::Variant(ref node)`.
+    pattern: TokenStream2,
+    pattern_mut: TokenStream2,
+    /// The expression constructing the variant from `node`.
+    construct: TokenStream2,
+    ty: Type,
+}
+
+fn node_enum(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`NodeEnum` may only be derived for enums",
+            ))
+        }
+    };
+    if data.variants.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`NodeEnum` requires at least one variant",
+        ));
+    }
+
+    let mut variants = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let variant = match variant.fields {
+            Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => Variant {
+                pattern: quote!(#name::#ident(ref node)),
+                pattern_mut: quote!(#name::#ident(ref mut node)),
+                construct: quote!(#name::#ident(node)),
+                ty: fields.unnamed[0].ty.clone(),
+            },
+            Fields::Named(ref fields) if fields.named.len() == 1 => {
+                let field = &fields.named[0].ident;
+                Variant {
+                    pattern: quote!(#name::#ident { #field: ref node }),
+                    pattern_mut: quote!(#name::#ident { #field: ref mut node }),
+                    construct: quote!(#name::#ident { #field: node }),
+                    ty: fields.named[0].ty.clone(),
+                }
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    variant,
+                    "each variant of a `NodeEnum` must hold exactly one node",
+                ))
+            }
+        };
+        variants.push(variant);
+    }
+
+    let node_impl = node_impl(&input, &variants);
+    let variant_impls = variant_impls(&input, &variants);
+    Ok(quote! {
+        #node_impl
+        #variant_impls
+    })
+}
+
+/// The implementation of `Node<F>`, delegating each method to the current variant.
+fn node_impl(input: &DeriveInput, variants: &[Variant]) -> TokenStream2 {
+    let name = &input.ident;
+    let frame: Ident = parse_quote!(__F);
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!(#frame));
+    {
+        let where_clause = generics.make_where_clause();
+        where_clause
+            .predicates
+            .push(parse_quote!(#frame: ::dsp::Frame));
+        for variant in variants {
+            let ty = &variant.ty;
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: ::dsp::Node<#frame>));
+        }
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    // Delegate a method taking `&self` or `&mut self`, given its name and remaining arguments.
+    // The call is fully qualified as nodes often have inherent methods of the same name.
+    let delegate = |method: TokenStream2, args: TokenStream2, mutable: bool| {
+        let arms = variants.iter().map(|variant| {
+            let pattern = if mutable {
+                &variant.pattern_mut
+            } else {
+                &variant.pattern
+            };
+            quote!(#pattern => ::dsp::Node::<#frame>::#method(node, #args),)
+        });
+        quote! {
+            match *self {
+                #(#arms)*
+            }
+        }
+    };
+
+    let audio_requested = delegate(quote!(audio_requested), quote!(buffer, sample_hz), true);
+    let dry = delegate(quote!(dry), quote!(), false);
+    let wet = delegate(quote!(wet), quote!(), false);
+    let latency = delegate(quote!(latency), quote!(), false);
+    let buffer_format = delegate(quote!(buffer_format), quote!(), false);
+    let audio_requested_planar = delegate(
+        quote!(audio_requested_planar),
+        quote!(buffer, sample_hz),
+        true,
+    );
+    let is_silent = delegate(quote!(is_silent), quote!(), false);
+    let tail_frames = delegate(quote!(tail_frames), quote!(), false);
+    let param_changes = delegate(quote!(param_changes), quote!(changes), true);
+    let analysis_values = delegate(quote!(analysis_values), quote!(publish), false);
+    let handle_event = delegate(quote!(handle_event), quote!(event), true);
+    let handle_message = delegate(quote!(handle_message), quote!(message), true);
+    let bus_layout = delegate(quote!(bus_layout), quote!(), false);
+    let set_bus_layout = delegate(quote!(set_bus_layout), quote!(layout), true);
+    let separate_io = delegate(quote!(separate_io), quote!(), false);
+    let process = delegate(quote!(process), quote!(inputs, output, sample_hz), true);
+    let channels_changed = delegate(quote!(channels_changed), quote!(channels), true);
+    let update_tempo = delegate(quote!(update_tempo), quote!(tempo), true);
+    let finish_loading = delegate(quote!(finish_loading), quote!(), true);
+    let held_notes = delegate(quote!(held_notes), quote!(notes), false);
+    let always_render = delegate(quote!(always_render), quote!(), false);
+
+    quote! {
+        impl #impl_generics ::dsp::Node<#frame> for #name #ty_generics #where_clause {
+            #[inline]
+            fn audio_requested(&mut self, buffer: &mut [#frame], sample_hz: f64) {
+                #audio_requested
+            }
+            #[inline]
+            fn dry(&self) -> <#frame::Sample as ::dsp::Sample>::Float {
+                #dry
+            }
+            #[inline]
+            fn wet(&self) -> <#frame::Sample as ::dsp::Sample>::Float {
+                #wet
+            }
+            #[inline]
+            fn latency(&self) -> usize {
+                #latency
+            }
+            #[inline]
+            fn buffer_format(&self) -> ::dsp::BufferFormat {
+                #buffer_format
+            }
+            #[inline]
+            fn audio_requested_planar(
+                &mut self,
+                buffer: ::dsp::Planar<#frame::Sample>,
+                sample_hz: f64,
+            ) {
+                #audio_requested_planar
+            }
+            #[inline]
+            fn is_silent(&self) -> bool {
+                #is_silent
+            }
+            #[inline]
+            fn tail_frames(&self) -> usize {
+                #tail_frames
+            }
+            #[inline]
+            fn param_changes(&mut self, changes: &mut ::std::vec::Vec<::dsp::ParamChange>) {
+                #param_changes
+            }
+            #[inline]
+            fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+                #analysis_values
+            }
+            #[inline]
+            fn handle_event(&mut self, event: &::dsp::event::Event) {
+                #handle_event
+            }
+            #[inline]
+            fn handle_message(&mut self, message: &dyn ::std::any::Any) {
+                #handle_message
+            }
+            #[inline]
+            fn bus_layout(&self) -> ::dsp::BusLayout {
+                #bus_layout
+            }
+            #[inline]
+            fn set_bus_layout(&mut self, layout: &::dsp::BusLayout) -> bool {
+                #set_bus_layout
+            }
+            #[inline]
+            fn separate_io(&self) -> bool {
+                #separate_io
+            }
+            #[inline]
+            fn process(&mut self, inputs: &[&[#frame]], output: &mut [#frame], sample_hz: f64) {
+                #process
+            }
+            #[inline]
+            fn channels_changed(&mut self, channels: usize) {
+                #channels_changed
+            }
+            #[inline]
+            fn update_tempo(&mut self, tempo: &::dsp::Tempo) {
+                #update_tempo
+            }
+            #[inline]
+            fn finish_loading(&mut self) -> bool {
+                #finish_loading
+            }
+            #[inline]
+            fn held_notes(&self, notes: &mut ::std::vec::Vec<::dsp::event::Event>) {
+                #held_notes
+            }
+            #[inline]
+            fn always_render(&self) -> bool {
+                #always_render
+            }
+        }
+    }
+}
+
+/// `From<T>` and `NodeVariant<T>` for the type held by each variant, skipping any type held by
+/// more than one variant as the conversion would be ambiguous.
+fn variant_impls(input: &DeriveInput, variants: &[Variant]) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let key = |ty: &Type| quote!(#ty).to_string();
+    let impls = variants
+        .iter()
+        .filter(|variant| {
+            let ty = key(&variant.ty);
+            variants.iter().filter(|v| key(&v.ty) == ty).count() == 1
+        })
+        .map(|variant| {
+            let ty = &variant.ty;
+            let pattern = &variant.pattern;
+            let pattern_mut = &variant.pattern_mut;
+            let other = if variants.len() > 1 {
+                quote!(_ => None,)
+            } else {
+                quote!()
+            };
+            let construct = &variant.construct;
+            quote! {
+                impl #impl_generics ::std::convert::From<#ty> for #name #ty_generics #where_clause {
+                    fn from(node: #ty) -> Self {
+                        #construct
+                    }
+                }
+
+                impl #impl_generics ::dsp::NodeVariant<#ty> for #name #ty_generics #where_clause {
+                    fn as_variant(&self) -> ::std::option::Option<&#ty> {
+                        match *self {
+                            #pattern => Some(node),
+                            #other
+                        }
+                    }
+
+                    fn as_variant_mut(&mut self) -> ::std::option::Option<&mut #ty> {
+                        match *self {
+                            #pattern_mut => Some(node),
+                            #other
+                        }
+                    }
+                }
+            }
+        });
+    quote!(#(#impls)*)
+}
@@ -2,7 +2,8 @@
 //! oscillators.
 
 use dasp::slice::ToFrameSliceMut;
-use dsp::{Frame, FromSample, Graph, Node, Sample, Walker};
+use dsp::raw::Walker;
+use dsp::{Frame, FromSample, Graph, Node, Sample};
 
 use portaudio as pa;
 
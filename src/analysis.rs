@@ -0,0 +1,194 @@
+//! Offline analysis of signals and of the output of **Node**s, with export of the resulting
+//! tracks to CSV and JSON.
+//!
+//! An **Analyser** runs a signal, a **Node** or a whole **Graph** over its input block by block,
+//! measuring the meter, loudness, spectrum and pitch tracks of the result. The resulting
+//! **Analysis** may be written to any `std::io::Write` implementation, for use within batch audio
+//! QC pipelines.
+
+use self::loudness::LoudnessMeter;
+use crate::node::Node;
+use dasp::{Frame, Sample};
+
+mod export;
+pub(crate) mod fft;
+mod loudness;
+pub(crate) mod pitch;
+
+/// The lowest level reported in decibels, substituted for silence.
+pub const MIN_DB: f32 = -144.0;
+
+/// Settings for analysing a signal block by block.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Analyser {
+    /// The sample rate of the analysed signal.
+    pub sample_hz: f64,
+    /// The number of frames in each analysed block.
+    ///
+    /// The spectrum of each block is measured with an FFT of the next power of two.
+    pub block_size: usize,
+    /// Whether to measure the spectrum of each block.
+    pub spectrum: bool,
+    /// The lowest fundamental frequency considered by pitch detection.
+    ///
+    /// Blocks must span at least two periods of this frequency for pitch to be detected.
+    pub min_pitch_hz: f64,
+    /// The highest fundamental frequency considered by pitch detection.
+    pub max_pitch_hz: f64,
+}
+
+/// The tracks measured by an **Analyser**, with one **AnalysisBlock** for each block of the
+/// signal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    /// The sample rate of the analysed signal.
+    pub sample_hz: f64,
+    /// The number of frames in each analysed block.
+    pub block_size: usize,
+    /// The measurements of each block in order.
+    pub blocks: Vec<AnalysisBlock>,
+}
+
+/// The measurements of a single block of the analysed signal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnalysisBlock {
+    /// The time at the start of the block in seconds.
+    pub time_secs: f64,
+    /// The peak absolute sample value across all channels in dBFS.
+    pub peak_db: f32,
+    /// The RMS level across all channels in dBFS.
+    pub rms_db: f32,
+    /// The momentary (400ms) K-weighted loudness at the end of the block in LUFS.
+    pub loudness_lufs: f32,
+    /// The fundamental frequency of the block's mono sum, if a periodic component was found.
+    pub pitch_hz: Option<f32>,
+    /// The magnitude of each frequency bin of the block's mono sum in dBFS, from DC to the
+    /// Nyquist frequency.
+    ///
+    /// Empty unless the spectrum was measured.
+    pub spectrum_db: Vec<f32>,
+}
+
+impl Analyser {
+    /// An analyser for signals at the given sample rate, with blocks of 2048 frames.
+    pub fn new(sample_hz: f64) -> Self {
+        Analyser {
+            sample_hz,
+            block_size: 2048,
+            spectrum: true,
+            min_pitch_hz: 50.0,
+            max_pitch_hz: 2000.0,
+        }
+    }
+
+    /// Analyse the given signal.
+    pub fn analyse<F>(&self, signal: &[F]) -> Analysis
+    where
+        F: Frame,
+    {
+        let mut state = State::new(self);
+        for block in signal.chunks(self.block_size.max(1)) {
+            state.analyse_block(self, block);
+        }
+        state.analysis
+    }
+
+    /// Analyse the output of the given node when `input` is passed through it block by block.
+    ///
+    /// As the **Graph** is itself a **Node**, this may also be used to analyse a whole graph.
+    pub fn analyse_node<F, N>(&self, node: &mut N, input: &[F]) -> Analysis
+    where
+        F: Frame,
+        N: Node<F>,
+    {
+        let mut state = State::new(self);
+        let mut buffer = Vec::with_capacity(self.block_size);
+        for block in input.chunks(self.block_size.max(1)) {
+            buffer.clear();
+            buffer.extend_from_slice(block);
+            node.audio_requested(&mut buffer, self.sample_hz);
+            state.analyse_block(self, &buffer);
+        }
+        state.analysis
+    }
+}
+
+/// The running state of an analysis.
+struct State {
+    analysis: Analysis,
+    loudness: LoudnessMeter,
+    frames: u64,
+    mono: Vec<f64>,
+    magnitudes: Vec<f64>,
+}
+
+impl State {
+    fn new(analyser: &Analyser) -> Self {
+        State {
+            analysis: Analysis {
+                sample_hz: analyser.sample_hz,
+                block_size: analyser.block_size,
+                blocks: Vec::new(),
+            },
+            loudness: LoudnessMeter::new(analyser.sample_hz),
+            frames: 0,
+            mono: Vec::new(),
+            magnitudes: Vec::new(),
+        }
+    }
+
+    fn analyse_block<F>(&mut self, analyser: &Analyser, block: &[F])
+    where
+        F: Frame,
+    {
+        let to_f64 = |s: F::Sample| s.to_float_sample().to_sample::<f64>();
+
+        let mut peak = 0.0f64;
+        let mut sum_squares = 0.0;
+        self.mono.clear();
+        for frame in block {
+            let mut mono = 0.0;
+            for s in frame.channels().map(to_f64) {
+                peak = peak.max(s.abs());
+                sum_squares += s * s;
+                mono += s;
+            }
+            self.mono.push(mono / F::CHANNELS as f64);
+        }
+        let num_samples = (block.len() * F::CHANNELS).max(1) as f64;
+
+        let samples = block
+            .iter()
+            .flat_map(|frame| frame.channels().map(to_f64).enumerate());
+        let loudness = self.loudness.process(samples, block.len());
+
+        let pitch_hz = pitch::detect_pitch(
+            &self.mono,
+            analyser.sample_hz,
+            analyser.min_pitch_hz,
+            analyser.max_pitch_hz,
+        );
+
+        let mut spectrum_db = Vec::new();
+        if analyser.spectrum {
+            let size = analyser.block_size.max(1).next_power_of_two();
+            fft::magnitude_spectrum(&self.mono, size, &mut self.magnitudes);
+            spectrum_db.extend(self.magnitudes.iter().map(|&m| to_db(m)));
+        }
+
+        self.analysis.blocks.push(AnalysisBlock {
+            time_secs: self.frames as f64 / analyser.sample_hz,
+            peak_db: to_db(peak),
+            rms_db: to_db((sum_squares / num_samples).sqrt()),
+            loudness_lufs: (loudness as f32).max(MIN_DB),
+            pitch_hz: pitch_hz.map(|hz| hz as f32),
+            spectrum_db,
+        });
+        self.frames += block.len() as u64;
+    }
+}
+
+/// Convert the given amplitude to decibels, no lower than `MIN_DB`.
+pub(crate) fn to_db(amp: f64) -> f32 {
+    ((20.0 * amp.log10()) as f32).max(MIN_DB)
+}
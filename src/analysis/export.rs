@@ -0,0 +1,111 @@
+//! Export of analysis tracks to CSV and JSON.
+
+use super::Analysis;
+use std::io::{self, Write};
+
+impl Analysis {
+    /// The frequency in hz at the centre of each bin of the measured spectra.
+    pub fn spectrum_frequencies(&self) -> impl Iterator<Item = f64> {
+        let size = self.block_size.max(1).next_power_of_two();
+        let bin_hz = self.sample_hz / size as f64;
+        (0..size / 2 + 1).map(move |bin| bin as f64 * bin_hz)
+    }
+
+    /// Write the meter, loudness and pitch tracks as CSV with one row per block.
+    ///
+    /// The columns are `time_secs,peak_db,rms_db,loudness_lufs,pitch_hz`. The pitch is left
+    /// empty for blocks in which none was detected.
+    pub fn write_csv<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writeln!(writer, "time_secs,peak_db,rms_db,loudness_lufs,pitch_hz")?;
+        for block in &self.blocks {
+            write!(
+                writer,
+                "{},{},{},{},",
+                block.time_secs, block.peak_db, block.rms_db, block.loudness_lufs
+            )?;
+            if let Some(hz) = block.pitch_hz {
+                write!(writer, "{}", hz)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Write the spectrum track as CSV with one row per block.
+    ///
+    /// The first column is `time_secs`, followed by the magnitude in dBFS of each bin, headed by
+    /// the bin's frequency in hz.
+    pub fn write_spectrum_csv<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        write!(writer, "time_secs")?;
+        for hz in self.spectrum_frequencies() {
+            write!(writer, ",{}", hz)?;
+        }
+        writeln!(writer)?;
+        for block in &self.blocks {
+            write!(writer, "{}", block.time_secs)?;
+            for db in &block.spectrum_db {
+                write!(writer, ",{}", db)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Write all tracks as a single JSON object.
+    ///
+    /// The object holds the `sample_hz` and `block_size` of the analysis along with a `blocks`
+    /// array, with one object per block holding each of the fields of **AnalysisBlock**. Pitches
+    /// that were not detected are written as `null`.
+    pub fn write_json<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        write!(
+            writer,
+            "{{\"sample_hz\":{},\"block_size\":{},\"blocks\":[",
+            json_number(self.sample_hz),
+            self.block_size
+        )?;
+        for (i, block) in self.blocks.iter().enumerate() {
+            if i > 0 {
+                write!(writer, ",")?;
+            }
+            write!(
+                writer,
+                "{{\"time_secs\":{},\"peak_db\":{},\"rms_db\":{},\"loudness_lufs\":{},\"pitch_hz\":",
+                json_number(block.time_secs),
+                json_number(block.peak_db.into()),
+                json_number(block.rms_db.into()),
+                json_number(block.loudness_lufs.into()),
+            )?;
+            match block.pitch_hz {
+                Some(hz) => write!(writer, "{}", json_number(hz.into()))?,
+                None => write!(writer, "null")?,
+            }
+            write!(writer, ",\"spectrum_db\":[")?;
+            for (j, &db) in block.spectrum_db.iter().enumerate() {
+                if j > 0 {
+                    write!(writer, ",")?;
+                }
+                write!(writer, "{}", json_number(db.into()))?;
+            }
+            write!(writer, "]}}")?;
+        }
+        writeln!(writer, "]}}")
+    }
+}
+
+/// Format the number for JSON, which cannot represent non-finite values.
+fn json_number(n: f64) -> String {
+    if n.is_finite() {
+        format!("{}", n)
+    } else {
+        "null".to_string()
+    }
+}
@@ -0,0 +1,89 @@
+//! A small radix-2 FFT for spectral analysis.
+
+use std::f64::consts::PI;
+
+/// Transform the given complex signal in place.
+///
+/// **Panics** if the length of `re` is not a power of two or does not match the length of `im`.
+pub(crate) fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    assert!(n.is_power_of_two(), "the FFT size must be a power of two");
+    assert_eq!(n, im.len());
+
+    // Reorder the input by bit-reversed index.
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    // Combine the transforms of increasing size.
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_im, w_re) = (angle * k as f64).sin_cos();
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Write the magnitude of each bin of the Hann-windowed `signal`, zero-padded to `size` frames,
+/// into `magnitudes`.
+///
+/// The magnitudes are scaled so that a full-scale sine wave centred on a bin has a magnitude of
+/// `1.0`. `size / 2 + 1` magnitudes are written, from DC up to the Nyquist frequency.
+pub(crate) fn magnitude_spectrum(signal: &[f64], size: usize, magnitudes: &mut Vec<f64>) {
+    let mut re = Vec::new();
+    let mut im = Vec::new();
+    magnitude_spectrum_with(signal, size, &mut re, &mut im, magnitudes);
+}
+
+/// The same as `magnitude_spectrum`, but re-uses the given buffers for the transform so that
+/// nothing is allocated once they have grown to `size`.
+pub(crate) fn magnitude_spectrum_with(
+    signal: &[f64],
+    size: usize,
+    re: &mut Vec<f64>,
+    im: &mut Vec<f64>,
+    magnitudes: &mut Vec<f64>,
+) {
+    re.clear();
+    re.resize(size, 0.0);
+    im.clear();
+    im.resize(size, 0.0);
+    let len = signal.len().min(size);
+    for (i, (&s, r)) in signal.iter().zip(re.iter_mut()).enumerate() {
+        let window = 0.5 - 0.5 * (2.0 * PI * i as f64 / len as f64).cos();
+        *r = s * window;
+    }
+    fft(re, im);
+
+    // The Hann window has a coherent gain of one half.
+    let scale = 4.0 / len.max(1) as f64;
+    magnitudes.clear();
+    magnitudes.extend(
+        re.iter()
+            .zip(im.iter())
+            .take(size / 2 + 1)
+            .map(|(r, i)| (r * r + i * i).sqrt() * scale),
+    );
+}
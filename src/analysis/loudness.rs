@@ -0,0 +1,59 @@
+//! Momentary loudness measurement following ITU-R BS.1770.
+
+use crate::nodes::filter::{Biquad, Coefficients};
+use std::collections::VecDeque;
+
+/// The length of the momentary loudness window in seconds.
+const MOMENTARY_WINDOW_SECS: f64 = 0.4;
+
+/// Measures the K-weighted loudness of a signal over a sliding 400ms window.
+#[derive(Clone, Debug)]
+pub(crate) struct LoudnessMeter {
+    shelf: Biquad,
+    high_pass: Biquad,
+    window_frames: usize,
+    /// The K-weighted energy and length in frames of each block within the window.
+    blocks: VecDeque<(f64, usize)>,
+    energy: f64,
+    frames: usize,
+}
+
+impl LoudnessMeter {
+    /// A meter for signals at the given sample rate.
+    pub fn new(sample_hz: f64) -> Self {
+        LoudnessMeter {
+            shelf: Biquad::new(Coefficients::k_weighting_shelf(sample_hz)),
+            high_pass: Biquad::new(Coefficients::k_weighting_high_pass(sample_hz)),
+            window_frames: (sample_hz * MOMENTARY_WINDOW_SECS) as usize,
+            blocks: VecDeque::new(),
+            energy: 0.0,
+            frames: 0,
+        }
+    }
+
+    /// Add a block of `frames` frames, given as the samples of each channel in turn, returning
+    /// the momentary loudness in LUFS at the end of the block.
+    pub fn process<I>(&mut self, channels: I, frames: usize) -> f64
+    where
+        I: IntoIterator<Item = (usize, f64)>,
+    {
+        let mut energy = 0.0;
+        for (channel, sample) in channels {
+            let y = self.shelf.process_sample(channel, sample);
+            let y = self.high_pass.process_sample(channel, y);
+            energy += y * y;
+        }
+        self.blocks.push_back((energy, frames));
+        self.energy += energy;
+        self.frames += frames;
+        while let Some(&(energy, frames)) = self.blocks.front() {
+            if self.frames - frames < self.window_frames {
+                break;
+            }
+            self.blocks.pop_front();
+            self.energy -= energy;
+            self.frames -= frames;
+        }
+        -0.691 + 10.0 * (self.energy.max(0.0) / self.frames.max(1) as f64).log10()
+    }
+}
@@ -0,0 +1,55 @@
+//! Monophonic pitch detection.
+
+/// The threshold below which the cumulative mean normalised difference of a lag is considered
+/// periodic.
+const THRESHOLD: f64 = 0.15;
+
+/// Estimate the fundamental frequency of the given signal in hz using the YIN algorithm.
+///
+/// Only frequencies between `min_hz` and `max_hz` are considered. Returns `None` if no periodic
+/// component is found, or if the signal is too short to contain two periods of `min_hz`.
+pub(crate) fn detect_pitch(
+    signal: &[f64],
+    sample_hz: f64,
+    min_hz: f64,
+    max_hz: f64,
+) -> Option<f64> {
+    let min_lag = ((sample_hz / max_hz).floor() as usize).max(2);
+    let max_lag = (sample_hz / min_hz).ceil() as usize;
+    let window = signal.len() / 2;
+    if max_lag + 1 > window || min_lag >= max_lag {
+        return None;
+    }
+
+    // The cumulative mean normalised difference of each lag.
+    let mut cmnd = vec![1.0; max_lag + 2];
+    let mut running_sum = 0.0;
+    for lag in 1..cmnd.len() {
+        let difference: f64 = (0..window)
+            .map(|j| signal[j] - signal[j + lag])
+            .map(|d| d * d)
+            .sum();
+        running_sum += difference;
+        cmnd[lag] = if running_sum > 0.0 {
+            difference * lag as f64 / running_sum
+        } else {
+            1.0
+        };
+    }
+
+    // Find the first dip below the threshold, then follow it down to its minimum.
+    let mut lag = (min_lag..=max_lag).find(|&lag| cmnd[lag] < THRESHOLD)?;
+    while lag < max_lag && cmnd[lag + 1] < cmnd[lag] {
+        lag += 1;
+    }
+
+    // Refine the lag by fitting a parabola through its neighbours.
+    let (a, b, c) = (cmnd[lag - 1], cmnd[lag], cmnd[lag + 1]);
+    let denominator = a - 2.0 * b + c;
+    let offset = if denominator.abs() > f64::EPSILON {
+        0.5 * (a - c) / denominator
+    } else {
+        0.0
+    };
+    Some(sample_hz / (lag as f64 + offset))
+}
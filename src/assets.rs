@@ -0,0 +1,305 @@
+//! Audio data shared between nodes.
+//!
+//! A [**SamplePool**](./struct.SamplePool.html) holds reference-counted, immutable
+//! [**SampleBuffer**](./struct.SampleBuffer.html)s by name, so that any number of sampler or drum
+//! nodes may play the same audio without duplicating it in memory. Buffers may be loaded on a
+//! background thread and are evicted according to the pool's
+//! [**EvictionPolicy**](./enum.EvictionPolicy.html) once no node refers to them.
+
+use crate::nodes::filter::from_f64;
+use crate::offline::{self, wav};
+use dasp::Frame;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+
+/// Immutable audio data, shared via `Arc`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SampleBuffer<F> {
+    /// The frames of audio.
+    pub frames: Vec<F>,
+    /// The sample rate at which the audio was recorded.
+    pub sample_hz: f64,
+}
+
+/// How a **SamplePool** decides which buffers to evict.
+///
+/// Buffers that are still referenced outside of the pool are never evicted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Keep all buffers until they are removed explicitly.
+    Never,
+    /// Evict buffers as soon as they are no longer referenced outside of the pool.
+    Unreferenced,
+    /// Evict the least recently used unreferenced buffers while the pool holds more than the
+    /// given number of frames.
+    MaxFrames(usize),
+}
+
+/// A pool of named **SampleBuffer**s shared between nodes.
+///
+/// Cloning a pool yields another handle to the same buffers, so that buffers may be loaded on
+/// other threads.
+#[derive(Clone, Debug)]
+pub struct SamplePool<F> {
+    inner: Arc<Mutex<Inner<F>>>,
+}
+
+#[derive(Debug)]
+struct Inner<F> {
+    entries: Vec<Entry<F>>,
+    policy: EvictionPolicy,
+    /// Incremented on each access, to order entries by the time they were last used.
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Entry<F> {
+    name: String,
+    buffer: Arc<SampleBuffer<F>>,
+    last_used: u64,
+}
+
+impl<F> SampleBuffer<F>
+where
+    F: Frame,
+{
+    /// A buffer holding the given frames recorded at the given sample rate.
+    pub fn new(frames: Vec<F>, sample_hz: f64) -> Self {
+        SampleBuffer { frames, sample_hz }
+    }
+
+    /// Decode a RIFF WAVE file, mapping its channels onto the channels of `F` cyclically.
+    pub fn from_wav<P>(path: P) -> Result<Self, offline::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let decoded = wav::read(BufReader::new(File::open(path)?))?;
+        let channels = decoded.channels;
+        let frames = decoded
+            .samples
+            .chunks_exact(channels)
+            .map(|frame| F::from_fn(|ch| from_f64(frame[ch % channels] as f64)))
+            .collect();
+        Ok(SampleBuffer::new(frames, decoded.sample_hz as f64))
+    }
+
+    /// The number of frames in the buffer.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether the buffer holds no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+impl<F> SamplePool<F>
+where
+    F: Frame,
+{
+    /// An empty pool that evicts buffers according to the given policy.
+    pub fn new(policy: EvictionPolicy) -> Self {
+        let inner = Inner {
+            entries: Vec::new(),
+            policy,
+            clock: 0,
+        };
+        SamplePool {
+            inner: Arc::new(Mutex::new(inner)),
+        }
+    }
+
+    /// The policy by which buffers are evicted.
+    pub fn policy(&self) -> EvictionPolicy {
+        self.lock().policy
+    }
+
+    /// Set the policy by which buffers are evicted, evicting any buffers that it rules out.
+    pub fn set_policy(&self, policy: EvictionPolicy) {
+        let mut inner = self.lock();
+        inner.policy = policy;
+        inner.evict();
+    }
+
+    /// Add a buffer under the given name, replacing any buffer with the same name.
+    ///
+    /// Returns the shared buffer, which should be held by the nodes that play it.
+    pub fn insert(&self, name: &str, buffer: SampleBuffer<F>) -> Arc<SampleBuffer<F>> {
+        let buffer = Arc::new(buffer);
+        let mut inner = self.lock();
+        let last_used = inner.tick();
+        inner.entries.retain(|entry| entry.name != name);
+        inner.entries.push(Entry {
+            name: name.to_string(),
+            buffer: buffer.clone(),
+            last_used,
+        });
+        inner.evict();
+        buffer
+    }
+
+    /// The buffer with the given name, if it is loaded.
+    pub fn get(&self, name: &str) -> Option<Arc<SampleBuffer<F>>> {
+        let mut inner = self.lock();
+        let now = inner.tick();
+        inner
+            .entries
+            .iter_mut()
+            .find(|entry| entry.name == name)
+            .map(|entry| {
+                entry.last_used = now;
+                entry.buffer.clone()
+            })
+    }
+
+    /// The buffer with the given name, loading it via `load` on the current thread if it is not
+    /// loaded.
+    pub fn get_or_load<L, E>(&self, name: &str, load: L) -> Result<Arc<SampleBuffer<F>>, E>
+    where
+        L: FnOnce() -> Result<SampleBuffer<F>, E>,
+    {
+        match self.get(name) {
+            Some(buffer) => Ok(buffer),
+            None => load().map(|buffer| self.insert(name, buffer)),
+        }
+    }
+
+    /// Load a buffer via `load` on a background thread, adding it to the pool under the given
+    /// name once loaded.
+    ///
+    /// If a buffer with the given name is already loaded, it is returned without calling `load`.
+    pub fn load_in_background<L, E>(
+        &self,
+        name: &str,
+        load: L,
+    ) -> JoinHandle<Result<Arc<SampleBuffer<F>>, E>>
+    where
+        F: Send + Sync + 'static,
+        L: FnOnce() -> Result<SampleBuffer<F>, E> + Send + 'static,
+        E: Send + 'static,
+    {
+        let pool = self.clone();
+        let name = name.to_string();
+        thread::spawn(move || pool.get_or_load(&name, load))
+    }
+
+    /// Decode the given RIFF WAVE file on a background thread, adding it to the pool under its
+    /// path once loaded.
+    pub fn load_wav_in_background<P>(
+        &self,
+        path: P,
+    ) -> JoinHandle<Result<Arc<SampleBuffer<F>>, offline::Error>>
+    where
+        F: Send + Sync + 'static,
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let name = path.to_string_lossy().into_owned();
+        self.load_in_background(&name, move || SampleBuffer::from_wav(path))
+    }
+
+    /// Whether a buffer with the given name is loaded.
+    pub fn contains(&self, name: &str) -> bool {
+        self.lock().entries.iter().any(|entry| entry.name == name)
+    }
+
+    /// Remove the buffer with the given name from the pool.
+    ///
+    /// Nodes that hold the buffer keep it alive until they drop it.
+    pub fn remove(&self, name: &str) -> bool {
+        let mut inner = self.lock();
+        let len = inner.entries.len();
+        inner.entries.retain(|entry| entry.name != name);
+        inner.entries.len() != len
+    }
+
+    /// Evict the buffers ruled out by the pool's policy, returning the number evicted.
+    ///
+    /// This happens automatically whenever a buffer is added, but buffers that were referenced
+    /// at that time are only evicted by a later call once their last reference is dropped.
+    pub fn evict(&self) -> usize {
+        self.lock().evict()
+    }
+
+    /// The number of loaded buffers.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Whether no buffers are loaded.
+    pub fn is_empty(&self) -> bool {
+        self.lock().entries.is_empty()
+    }
+
+    /// The total number of frames over all loaded buffers.
+    pub fn total_frames(&self) -> usize {
+        self.lock().total_frames()
+    }
+
+    /// Lock the pool's state, recovering from a poisoned lock as the state is consistent between
+    /// all operations.
+    fn lock(&self) -> MutexGuard<'_, Inner<F>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<F> Default for SamplePool<F>
+where
+    F: Frame,
+{
+    fn default() -> Self {
+        SamplePool::new(EvictionPolicy::Never)
+    }
+}
+
+impl<F> Inner<F> {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn total_frames(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|entry| entry.buffer.frames.len())
+            .sum()
+    }
+
+    fn evict(&mut self) -> usize {
+        let len = self.entries.len();
+        match self.policy {
+            EvictionPolicy::Never => (),
+            EvictionPolicy::Unreferenced => {
+                self.entries.retain(|entry| !entry.is_unreferenced());
+            }
+            EvictionPolicy::MaxFrames(max_frames) => {
+                let mut total_frames = self.total_frames();
+                while total_frames > max_frames {
+                    let lru = self
+                        .entries
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, entry)| entry.is_unreferenced())
+                        .min_by_key(|(_, entry)| entry.last_used)
+                        .map(|(i, _)| i);
+                    match lru {
+                        Some(i) => total_frames -= self.entries.remove(i).buffer.frames.len(),
+                        None => break,
+                    }
+                }
+            }
+        }
+        len - self.entries.len()
+    }
+}
+
+impl<F> Entry<F> {
+    /// Whether the pool holds the only reference to the buffer.
+    fn is_unreferenced(&self) -> bool {
+        Arc::strong_count(&self.buffer) == 1
+    }
+}
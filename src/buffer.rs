@@ -0,0 +1,399 @@
+//! Buffer layouts and utilities for nodes that process audio in something other than interleaved
+//! **Frame** slices.
+
+use crate::{Frame, Sample};
+
+/// The layout in which a **Node** receives its buffer when audio is requested.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BufferFormat {
+    /// A slice of **Frame**s, where the samples of each channel are interleaved. This is the
+    /// default and requires no conversion.
+    #[default]
+    Interleaved,
+    /// A slice of samples where all samples of the first channel are followed by all samples of
+    /// the second channel and so on. Many algorithms (e.g. convolution or SIMD-heavy filters) are
+    /// significantly faster on planar data.
+    Planar,
+}
+
+/// A mutable view of a buffer of samples in planar layout.
+///
+/// All samples of the first channel are followed by all samples of the second channel and so on.
+#[derive(Debug)]
+pub struct Planar<'a, S> {
+    samples: &'a mut [S],
+    channels: usize,
+}
+
+impl<'a, S> Planar<'a, S> {
+    /// View the given samples as a planar buffer with the given number of channels.
+    ///
+    /// **Panics** if `channels` is `0` or if the number of samples is not a multiple of it.
+    pub fn new(samples: &'a mut [S], channels: usize) -> Self {
+        assert!(
+            channels > 0,
+            "a planar buffer must have at least one channel"
+        );
+        assert_eq!(
+            samples.len() % channels,
+            0,
+            "the number of samples must be a multiple of the number of channels"
+        );
+        Planar { samples, channels }
+    }
+
+    /// The number of channels in the buffer.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The number of frames (i.e. samples per channel) in the buffer.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    /// The samples of the channel at the given index.
+    ///
+    /// **Panics** if `channel` is out of range.
+    pub fn channel(&self, channel: usize) -> &[S] {
+        let frames = self.frames();
+        &self.samples[channel * frames..(channel + 1) * frames]
+    }
+
+    /// The samples of the channel at the given index.
+    ///
+    /// **Panics** if `channel` is out of range.
+    pub fn channel_mut(&mut self, channel: usize) -> &mut [S] {
+        let frames = self.frames();
+        &mut self.samples[channel * frames..(channel + 1) * frames]
+    }
+
+    /// An iterator yielding the samples of each channel in order.
+    pub fn iter(&self) -> std::slice::Chunks<'_, S> {
+        let frames = std::cmp::max(self.frames(), 1);
+        self.samples.chunks(frames)
+    }
+
+    /// An iterator yielding mutable access to the samples of each channel in order.
+    pub fn iter_mut(&mut self) -> std::slice::ChunksMut<'_, S> {
+        let frames = std::cmp::max(self.frames(), 1);
+        self.samples.chunks_mut(frames)
+    }
+
+    /// All samples in the buffer.
+    pub fn as_slice(&self) -> &[S] {
+        self.samples
+    }
+
+    /// All samples in the buffer.
+    pub fn as_mut_slice(&mut self) -> &mut [S] {
+        self.samples
+    }
+}
+
+/// A mutable view of a buffer of interleaved samples whose number of channels is only known at
+/// runtime, e.g. the buffer of an audio device.
+///
+/// This is the runtime-sized counterpart to a slice of **Frame**s, where each frame is a stride of
+/// `channels` samples.
+#[derive(Debug)]
+pub struct DynFrames<'a, S> {
+    samples: &'a mut [S],
+    channels: usize,
+}
+
+impl<'a, S> DynFrames<'a, S> {
+    /// View the given interleaved samples as frames with the given number of channels.
+    ///
+    /// **Panics** if `channels` is `0` or if the number of samples is not a multiple of it.
+    pub fn new(samples: &'a mut [S], channels: usize) -> Self {
+        assert!(channels > 0, "a frame must have at least one channel");
+        assert_eq!(
+            samples.len() % channels,
+            0,
+            "the number of samples must be a multiple of the number of channels"
+        );
+        DynFrames { samples, channels }
+    }
+
+    /// The number of channels in each frame.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The number of frames in the buffer.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    /// The samples of the frame at the given index.
+    ///
+    /// **Panics** if `frame` is out of range.
+    pub fn frame(&self, frame: usize) -> &[S] {
+        &self.samples[frame * self.channels..(frame + 1) * self.channels]
+    }
+
+    /// The samples of the frame at the given index.
+    ///
+    /// **Panics** if `frame` is out of range.
+    pub fn frame_mut(&mut self, frame: usize) -> &mut [S] {
+        &mut self.samples[frame * self.channels..(frame + 1) * self.channels]
+    }
+
+    /// An iterator yielding the samples of each frame in order.
+    pub fn iter(&self) -> std::slice::Chunks<'_, S> {
+        self.samples.chunks(self.channels)
+    }
+
+    /// An iterator yielding mutable access to the samples of each frame in order.
+    pub fn iter_mut(&mut self) -> std::slice::ChunksMut<'_, S> {
+        self.samples.chunks_mut(self.channels)
+    }
+
+    /// All samples in the buffer.
+    pub fn as_slice(&self) -> &[S] {
+        self.samples
+    }
+
+    /// All samples in the buffer.
+    pub fn as_mut_slice(&mut self) -> &mut [S] {
+        self.samples
+    }
+}
+
+/// A handle to a region of a **Scratch** arena.
+///
+/// Handles are invalidated when the arena is cleared, after which using them panics rather than
+/// aliasing a region handed out since.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ScratchHandle {
+    index: usize,
+    generation: u32,
+}
+
+/// An arena of frames that is partitioned into regions, e.g. one per port or band of a node.
+///
+/// All regions share a single allocation. Once enough capacity has been reserved (typically when
+/// the buffer size changes), regions can be handed out and cleared on the audio thread without
+/// allocating.
+#[derive(Clone, Debug, Default)]
+pub struct Scratch<F> {
+    frames: Vec<F>,
+    /// The start and end of each region within `frames`.
+    regions: Vec<(usize, usize)>,
+    generation: u32,
+}
+
+impl<F> Scratch<F>
+where
+    F: Frame,
+{
+    /// An empty arena with no capacity.
+    pub fn new() -> Self {
+        Scratch {
+            frames: Vec::new(),
+            regions: Vec::new(),
+            generation: 0,
+        }
+    }
+
+    /// An empty arena with capacity for `regions` regions of `frames` frames each.
+    pub fn with_capacity(frames: usize, regions: usize) -> Self {
+        let mut scratch = Self::new();
+        scratch.reserve(frames, regions);
+        scratch
+    }
+
+    /// Reserve capacity for at least `regions` more regions of `frames` frames each.
+    pub fn reserve(&mut self, frames: usize, regions: usize) {
+        self.frames.reserve(frames * regions);
+        self.regions.reserve(regions);
+    }
+
+    /// Remove all regions, invalidating all handles.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.regions.clear();
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// The number of regions in the arena.
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Whether the arena holds no regions.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Add a region of `frames` frames set to equilibrium, allocating if there is not enough
+    /// capacity.
+    pub fn alloc(&mut self, frames: usize) -> ScratchHandle {
+        let start = self.frames.len();
+        self.frames.resize(start + frames, F::EQUILIBRIUM);
+        self.regions.push((start, start + frames));
+        ScratchHandle {
+            index: self.regions.len() - 1,
+            generation: self.generation,
+        }
+    }
+
+    /// Add a region of `frames` frames set to equilibrium.
+    ///
+    /// Returns `None` rather than allocating if there is not enough capacity.
+    pub fn try_alloc(&mut self, frames: usize) -> Option<ScratchHandle> {
+        let fits = self.frames.capacity() - self.frames.len() >= frames
+            && self.regions.capacity() > self.regions.len();
+        if fits {
+            Some(self.alloc(frames))
+        } else {
+            None
+        }
+    }
+
+    /// The region for the given handle, or `None` if the handle was invalidated.
+    pub fn get(&self, handle: ScratchHandle) -> Option<&[F]> {
+        let (start, end) = self.region(handle)?;
+        Some(&self.frames[start..end])
+    }
+
+    /// The region for the given handle, or `None` if the handle was invalidated.
+    pub fn get_mut(&mut self, handle: ScratchHandle) -> Option<&mut [F]> {
+        let (start, end) = self.region(handle)?;
+        Some(&mut self.frames[start..end])
+    }
+
+    /// The regions for two different handles at once.
+    ///
+    /// **Panics** if either handle was invalidated or if both refer to the same region.
+    pub fn get_pair_mut(&mut self, a: ScratchHandle, b: ScratchHandle) -> (&mut [F], &mut [F]) {
+        assert_ne!(
+            a.index, b.index,
+            "the handles must refer to different regions"
+        );
+        let (a_start, a_end) = self.region(a).expect("the scratch handle was invalidated");
+        let (b_start, b_end) = self.region(b).expect("the scratch handle was invalidated");
+        if a_start < b_start {
+            let (low, high) = self.frames.split_at_mut(b_start);
+            (&mut low[a_start..a_end], &mut high[..b_end - b_start])
+        } else {
+            let (low, high) = self.frames.split_at_mut(a_start);
+            (&mut high[..a_end - a_start], &mut low[b_start..b_end])
+        }
+    }
+
+    /// An iterator yielding each region in the order in which they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &[F]> {
+        let frames = &self.frames;
+        self.regions
+            .iter()
+            .map(move |&(start, end)| &frames[start..end])
+    }
+
+    /// An iterator yielding mutable access to each region in the order in which they were added.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut [F]> {
+        let mut rest = &mut self.frames[..];
+        let mut offset = 0;
+        self.regions.iter().map(move |&(start, end)| {
+            let (_, tail) = std::mem::take(&mut rest).split_at_mut(start - offset);
+            let (region, tail) = tail.split_at_mut(end - start);
+            rest = tail;
+            offset = end;
+            region
+        })
+    }
+
+    /// The range of the region for the given handle if it is still valid.
+    fn region(&self, handle: ScratchHandle) -> Option<(usize, usize)> {
+        if handle.generation != self.generation {
+            return None;
+        }
+        self.regions.get(handle.index).cloned()
+    }
+}
+
+impl<F> std::ops::Index<ScratchHandle> for Scratch<F>
+where
+    F: Frame,
+{
+    type Output = [F];
+    fn index(&self, handle: ScratchHandle) -> &[F] {
+        self.get(handle)
+            .expect("the scratch handle was invalidated")
+    }
+}
+
+impl<F> std::ops::IndexMut<ScratchHandle> for Scratch<F>
+where
+    F: Frame,
+{
+    fn index_mut(&mut self, handle: ScratchHandle) -> &mut [F] {
+        self.get_mut(handle)
+            .expect("the scratch handle was invalidated")
+    }
+}
+
+/// Copy the runtime-sized `dyn_frames` into the fixed-size `frames`.
+///
+/// Channels beyond those of `F` are dropped and channels missing from `dyn_frames` are set to
+/// equilibrium.
+///
+/// **Panics** if the number of frames differs.
+pub fn from_dyn_frames<F>(dyn_frames: &DynFrames<F::Sample>, frames: &mut [F])
+where
+    F: Frame,
+{
+    assert_eq!(dyn_frames.frames(), frames.len());
+    for (frame, dyn_frame) in frames.iter_mut().zip(dyn_frames.iter()) {
+        *frame = F::from_fn(|ch| dyn_frame.get(ch).cloned().unwrap_or(Sample::EQUILIBRIUM));
+    }
+}
+
+/// Copy the fixed-size `frames` into the runtime-sized `dyn_frames`.
+///
+/// Channels beyond those of `dyn_frames` are dropped and channels missing from `F` are set to
+/// equilibrium.
+///
+/// **Panics** if the number of frames differs.
+pub fn to_dyn_frames<F>(frames: &[F], dyn_frames: &mut DynFrames<F::Sample>)
+where
+    F: Frame,
+{
+    assert_eq!(dyn_frames.frames(), frames.len());
+    for (frame, dyn_frame) in frames.iter().zip(dyn_frames.iter_mut()) {
+        for (ch, sample) in dyn_frame.iter_mut().enumerate() {
+            *sample = frame.channel(ch).cloned().unwrap_or(Sample::EQUILIBRIUM);
+        }
+    }
+}
+
+/// Write the interleaved `frames` to `planar` in planar layout.
+///
+/// **Panics** if `planar` does not contain exactly `F::CHANNELS` samples per frame.
+pub fn deinterleave<F>(frames: &[F], planar: &mut [F::Sample])
+where
+    F: Frame,
+{
+    assert_eq!(frames.len() * F::CHANNELS, planar.len());
+    let len = frames.len();
+    for (i, frame) in frames.iter().enumerate() {
+        for (ch, sample) in frame.channels().enumerate() {
+            planar[ch * len + i] = sample;
+        }
+    }
+}
+
+/// Write the samples in planar layout to the interleaved `frames`.
+///
+/// **Panics** if `planar` does not contain exactly `F::CHANNELS` samples per frame.
+pub fn interleave<F>(planar: &[F::Sample], frames: &mut [F])
+where
+    F: Frame,
+{
+    assert_eq!(frames.len() * F::CHANNELS, planar.len());
+    let len = frames.len();
+    for (i, frame) in frames.iter_mut().enumerate() {
+        *frame = F::from_fn(|ch| planar[ch * len + i]);
+    }
+}
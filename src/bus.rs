@@ -0,0 +1,167 @@
+//! Descriptions of the buses through which a processor exchanges audio with its host.
+
+use crate::node::Node;
+use dasp::Frame;
+
+/// The role of a bus within a **BusLayout**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BusKind {
+    /// The main input, e.g. the signal processed by an effect.
+    MainInput,
+    /// The main output.
+    MainOutput,
+    /// An auxiliary input that controls processing without being heard directly, e.g. the key
+    /// signal of a compressor.
+    Sidechain,
+    /// An auxiliary output, e.g. a separate output for each drum of a drum machine.
+    AuxOutput,
+}
+
+/// A single bus of a **BusLayout**.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bus {
+    /// The name under which the bus is presented to the user.
+    pub name: String,
+    /// The role of the bus.
+    pub kind: BusKind,
+    /// The number of channels carried by the bus.
+    pub channels: usize,
+}
+
+/// The buses through which a processor exchanges audio with its host, e.g. a plugin host or an
+/// audio backend.
+///
+/// A processor exposes its current layout via `Node::bus_layout`. Hosts negotiate a layout by
+/// proposing the layouts that they support in order of preference via `negotiate`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BusLayout {
+    /// The buses in the order in which they are presented, inputs before outputs.
+    pub buses: Vec<Bus>,
+}
+
+impl BusLayout {
+    /// A layout with no buses.
+    pub fn new() -> Self {
+        BusLayout { buses: Vec::new() }
+    }
+
+    /// A layout with a main input and a main output of the given numbers of channels, as used by
+    /// most effects.
+    pub fn effect(input_channels: usize, output_channels: usize) -> Self {
+        BusLayout::new()
+            .with_bus("Input", BusKind::MainInput, input_channels)
+            .with_bus("Output", BusKind::MainOutput, output_channels)
+    }
+
+    /// A layout with only a main output of the given number of channels, as used by
+    /// instruments and generators.
+    pub fn instrument(output_channels: usize) -> Self {
+        BusLayout::new().with_bus("Output", BusKind::MainOutput, output_channels)
+    }
+
+    /// Add a bus, placing inputs before outputs.
+    pub fn with_bus(mut self, name: &str, kind: BusKind, channels: usize) -> Self {
+        let bus = Bus {
+            name: name.to_string(),
+            kind,
+            channels,
+        };
+        let index = match kind {
+            BusKind::MainInput | BusKind::Sidechain => {
+                self.buses.iter().take_while(|bus| bus.is_input()).count()
+            }
+            BusKind::MainOutput | BusKind::AuxOutput => self.buses.len(),
+        };
+        self.buses.insert(index, bus);
+        self
+    }
+
+    /// Add a sidechain input with the given name and number of channels.
+    pub fn with_sidechain(self, name: &str, channels: usize) -> Self {
+        self.with_bus(name, BusKind::Sidechain, channels)
+    }
+
+    /// Add an auxiliary output with the given name and number of channels.
+    pub fn with_aux_output(self, name: &str, channels: usize) -> Self {
+        self.with_bus(name, BusKind::AuxOutput, channels)
+    }
+
+    /// The main input, if any.
+    pub fn main_input(&self) -> Option<&Bus> {
+        self.buses.iter().find(|bus| bus.kind == BusKind::MainInput)
+    }
+
+    /// The main output, if any.
+    pub fn main_output(&self) -> Option<&Bus> {
+        self.buses
+            .iter()
+            .find(|bus| bus.kind == BusKind::MainOutput)
+    }
+
+    /// All sidechain inputs in order.
+    pub fn sidechains(&self) -> impl Iterator<Item = &Bus> {
+        self.buses_of(BusKind::Sidechain)
+    }
+
+    /// All auxiliary outputs in order.
+    pub fn aux_outputs(&self) -> impl Iterator<Item = &Bus> {
+        self.buses_of(BusKind::AuxOutput)
+    }
+
+    /// The bus with the given name, if any.
+    pub fn bus(&self, name: &str) -> Option<&Bus> {
+        self.buses.iter().find(|bus| bus.name == name)
+    }
+
+    /// The total number of input channels over all input buses.
+    pub fn input_channels(&self) -> usize {
+        self.buses
+            .iter()
+            .filter(|bus| bus.is_input())
+            .map(|bus| bus.channels)
+            .sum()
+    }
+
+    /// The total number of output channels over all output buses.
+    pub fn output_channels(&self) -> usize {
+        self.buses
+            .iter()
+            .filter(|bus| !bus.is_input())
+            .map(|bus| bus.channels)
+            .sum()
+    }
+
+    /// Whether the layout has at most one main input and exactly one main output.
+    pub fn is_valid(&self) -> bool {
+        let count = |kind| self.buses_of(kind).count();
+        count(BusKind::MainInput) <= 1 && count(BusKind::MainOutput) == 1
+    }
+
+    /// Propose each of the given layouts to the node in order of preference, returning the first
+    /// layout that it accepts.
+    ///
+    /// Returns `None` if the node accepts none of the layouts, in which case it keeps its
+    /// previous layout.
+    pub fn negotiate<'a, F, N>(node: &mut N, proposals: &'a [BusLayout]) -> Option<&'a BusLayout>
+    where
+        F: Frame,
+        N: Node<F> + ?Sized,
+    {
+        proposals.iter().find(|layout| node.set_bus_layout(layout))
+    }
+
+    /// All buses of the given kind in order.
+    fn buses_of(&self, kind: BusKind) -> impl Iterator<Item = &Bus> {
+        self.buses.iter().filter(move |bus| bus.kind == kind)
+    }
+}
+
+impl Bus {
+    /// Whether the bus carries audio into the processor.
+    pub fn is_input(&self) -> bool {
+        match self.kind {
+            BusKind::MainInput | BusKind::Sidechain => true,
+            BusKind::MainOutput | BusKind::AuxOutput => false,
+        }
+    }
+}
@@ -0,0 +1,411 @@
+//! A JSON patch format describing the topology of a **Graph** and the parameters of its nodes.
+//!
+//! A [**GraphDescription**](./struct.GraphDescription.html) lists each node by a type name along
+//! with its parameters, followed by the connections between nodes and the master node. Nodes
+//! describe themselves via the [**Describe**](./trait.Describe.html) trait, and a
+//! [**NodeFactory**](./struct.NodeFactory.html) maps each type name back to a constructor when a
+//! description is loaded. This allows patches to be saved, shared and edited by external tools.
+//! A [**HotReload**](./struct.HotReload.html) reloads edited patches into a running **Graph**,
+//! preserving the state of the nodes that did not change.
+//!
+//! ```json
+//! {
+//!   "nodes": [
+//!     { "type": "oscillator", "params": { "hz": 440 } },
+//!     { "type": "gain", "params": { "amp": 0.5 } }
+//!   ],
+//!   "connections": [{ "source": 0, "destination": 1 }],
+//!   "feedback": [],
+//!   "master": 1
+//! }
+//! ```
+
+use crate::graph::{Graph, NodeIndex, WouldCycle};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::fmt;
+
+pub use self::json::{ParseError, Value};
+pub use self::reload::HotReload;
+
+mod json;
+mod reload;
+
+/// Implemented by nodes that may be saved as part of a **GraphDescription**.
+pub trait Describe {
+    /// The name under which the node's constructor is registered with a **NodeFactory**.
+    fn type_name(&self) -> &str;
+
+    /// The node's parameters, from which the constructor registered for its type name should be
+    /// able to recreate it.
+    ///
+    /// By default, nodes have no parameters.
+    fn params(&self) -> Value {
+        Value::Null
+    }
+}
+
+/// A description of a single node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeDescription {
+    /// The name under which the node's constructor is registered.
+    pub type_name: String,
+    /// The node's parameters.
+    pub params: Value,
+}
+
+/// A description of a connection from the output of one node to the input of another.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionDescription {
+    /// The position of the input node within `GraphDescription::nodes`.
+    pub source: usize,
+    /// The position of the output node within `GraphDescription::nodes`.
+    pub destination: usize,
+    /// Whether the connection is enabled.
+    pub enabled: bool,
+}
+
+/// A description of the topology of a **Graph** and the parameters of its nodes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphDescription {
+    /// The nodes in order of their indices.
+    pub nodes: Vec<NodeDescription>,
+    /// The connections between nodes.
+    pub connections: Vec<ConnectionDescription>,
+    /// The feedback connections between nodes, whose `enabled` field is ignored.
+    pub feedback: Vec<ConnectionDescription>,
+    /// The position of the master node within `nodes`, if any.
+    pub master: Option<usize>,
+}
+
+/// Creates nodes from their descriptions via constructors registered by type name.
+pub struct NodeFactory<N> {
+    constructors: Vec<(String, Box<Constructor<N>>)>,
+}
+
+/// A constructor registered with a **NodeFactory**.
+type Constructor<N> = dyn Fn(&Value) -> Result<N, String> + Send + Sync;
+
+/// An error that occurred while loading a **GraphDescription**.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The document is not valid JSON.
+    Parse(ParseError),
+    /// The document is valid JSON but does not describe a graph.
+    Invalid(&'static str),
+    /// No constructor is registered for the type name of the node at the given position.
+    UnknownType(usize, String),
+    /// The constructor of the node at the given position rejected its parameters.
+    Node(usize, String),
+    /// A connection refers to a node that does not exist.
+    NoNode(usize),
+    /// The connections would create a cycle.
+    WouldCycle,
+}
+
+impl GraphDescription {
+    /// Parse a description from JSON.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let value = Value::parse(json).map_err(Error::Parse)?;
+        let nodes = match value.get("nodes").map(Value::as_array) {
+            Some(Some(nodes)) => nodes,
+            _ => return Err(Error::Invalid("expected an array of nodes")),
+        };
+        let nodes = nodes
+            .iter()
+            .map(|node| {
+                let type_name = node
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .ok_or(Error::Invalid("expected a node type"))?;
+                Ok(NodeDescription {
+                    type_name: type_name.to_string(),
+                    params: node.get("params").cloned().unwrap_or_default(),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        let master = match value.get("master") {
+            None | Some(Value::Null) => None,
+            Some(master) => Some(index(master).ok_or(Error::Invalid("expected a master index"))?),
+        };
+        Ok(GraphDescription {
+            nodes,
+            connections: connections(value.get("connections"))?,
+            feedback: connections(value.get("feedback"))?,
+            master,
+        })
+    }
+
+    /// Write the description as JSON.
+    pub fn to_json(&self) -> String {
+        self.to_value().to_string()
+    }
+
+    /// The description as a JSON value.
+    pub fn to_value(&self) -> Value {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                Value::Object(vec![
+                    ("type".to_string(), node.type_name.clone().into()),
+                    ("params".to_string(), node.params.clone()),
+                ])
+            })
+            .collect();
+        let connections = |connections: &[ConnectionDescription], enabled: bool| {
+            let connections = connections.iter().map(|connection| {
+                let mut members = vec![
+                    ("source".to_string(), connection.source.into()),
+                    ("destination".to_string(), connection.destination.into()),
+                ];
+                if enabled && !connection.enabled {
+                    members.push(("enabled".to_string(), false.into()));
+                }
+                Value::Object(members)
+            });
+            Value::Array(connections.collect())
+        };
+        Value::Object(vec![
+            ("nodes".to_string(), Value::Array(nodes)),
+            (
+                "connections".to_string(),
+                connections(&self.connections, true),
+            ),
+            ("feedback".to_string(), connections(&self.feedback, false)),
+            (
+                "master".to_string(),
+                self.master.map_or(Value::Null, Value::from),
+            ),
+        ])
+    }
+}
+
+impl<N> NodeFactory<N> {
+    /// A factory with no registered constructors.
+    pub fn new() -> Self {
+        NodeFactory {
+            constructors: Vec::new(),
+        }
+    }
+
+    /// Register the constructor for nodes of the given type name, replacing any constructor
+    /// registered under the same name.
+    ///
+    /// The constructor is given the node's parameters and returns an error message if they are
+    /// invalid.
+    pub fn register<C>(&mut self, type_name: &str, constructor: C)
+    where
+        C: Fn(&Value) -> Result<N, String> + Send + Sync + 'static,
+    {
+        self.constructors.retain(|(name, _)| name != type_name);
+        self.constructors
+            .push((type_name.to_string(), Box::new(constructor)));
+    }
+
+    /// The same as `register`, but returns the factory for chaining.
+    pub fn with<C>(mut self, type_name: &str, constructor: C) -> Self
+    where
+        C: Fn(&Value) -> Result<N, String> + Send + Sync + 'static,
+    {
+        self.register(type_name, constructor);
+        self
+    }
+
+    /// Whether a constructor is registered for the given type name.
+    pub fn contains(&self, type_name: &str) -> bool {
+        self.constructors.iter().any(|(name, _)| name == type_name)
+    }
+
+    /// The registered type names in the order in which they were registered.
+    pub fn type_names(&self) -> impl Iterator<Item = &str> {
+        self.constructors.iter().map(|(name, _)| &name[..])
+    }
+
+    /// Create a node from its description.
+    ///
+    /// Returns `None` if no constructor is registered for its type name, otherwise the result of
+    /// the constructor.
+    pub fn create(&self, description: &NodeDescription) -> Option<Result<N, String>> {
+        self.constructors
+            .iter()
+            .find(|(name, _)| *name == description.type_name)
+            .map(|(_, constructor)| constructor(&description.params))
+    }
+}
+
+impl<N> Default for NodeFactory<N> {
+    fn default() -> Self {
+        NodeFactory::new()
+    }
+}
+
+impl<N> fmt::Debug for NodeFactory<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.type_names()).finish()
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Describe the topology of the **Graph** and the parameters of its nodes.
+    pub fn to_description(&self) -> GraphDescription
+    where
+        N: Describe,
+    {
+        let nodes = self
+            .raw_nodes()
+            .iter()
+            .map(|node| NodeDescription {
+                type_name: node.weight.type_name().to_string(),
+                params: node.weight.params(),
+            })
+            .collect();
+        let connections = self
+            .raw_edges()
+            .iter()
+            .map(|edge| ConnectionDescription {
+                source: edge.source().index(),
+                destination: edge.target().index(),
+                enabled: edge.weight.is_enabled(),
+            })
+            .collect();
+        let feedback = self
+            .feedback_connections()
+            .iter()
+            .map(|fb| ConnectionDescription {
+                source: fb.source().index(),
+                destination: fb.destination().index(),
+                enabled: true,
+            })
+            .collect();
+        GraphDescription {
+            nodes,
+            connections,
+            feedback,
+            master: self.master_index().map(|idx| idx.index()),
+        }
+    }
+
+    /// Build a **Graph** from a description, creating each node via the given factory.
+    pub fn from_description(
+        description: &GraphDescription,
+        factory: &NodeFactory<N>,
+    ) -> Result<Self, Error> {
+        let mut graph =
+            Graph::with_capacity_indexed(description.nodes.len(), description.connections.len(), 0);
+        for (i, node) in description.nodes.iter().enumerate() {
+            let node = match factory.create(node) {
+                None => return Err(Error::UnknownType(i, node.type_name.clone())),
+                Some(Err(message)) => return Err(Error::Node(i, message)),
+                Some(Ok(node)) => node,
+            };
+            graph.add_node(node);
+        }
+        let node_index = |i: usize| {
+            if i < description.nodes.len() {
+                Ok(NodeIndex::new(i))
+            } else {
+                Err(Error::NoNode(i))
+            }
+        };
+        let connections = description
+            .connections
+            .iter()
+            .map(|c| Ok((node_index(c.source)?, node_index(c.destination)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let edges = graph.add_connections(connections)?;
+        for (edge, connection) in edges.into_iter().zip(&description.connections) {
+            if !connection.enabled {
+                graph
+                    .set_connection_enabled(edge, false)
+                    .expect("the connection was just added");
+            }
+        }
+        for fb in &description.feedback {
+            let (src, dest) = (node_index(fb.source)?, node_index(fb.destination)?);
+            graph
+                .add_feedback_connection(src, dest)
+                .expect("the nodes were checked");
+        }
+        if let Some(master) = description.master {
+            graph.set_master(Some(node_index(master)?));
+        }
+        Ok(graph)
+    }
+}
+
+impl From<WouldCycle> for Error {
+    fn from(_: WouldCycle) -> Self {
+        Error::WouldCycle
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Parse(ref err) => write!(f, "{}", err),
+            Error::Invalid(reason) => write!(f, "Invalid graph description: {}", reason),
+            Error::UnknownType(i, ref name) => {
+                write!(f, "No constructor for the type {:?} of node {}", name, i)
+            }
+            Error::Node(i, ref message) => write!(f, "Could not create node {}: {}", i, message),
+            Error::NoNode(i) => write!(f, "No node at position {}", i),
+            Error::WouldCycle => write!(f, "The connections would create a cycle"),
+        }
+    }
+}
+
+impl ::std::error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Parse(_) => "Invalid JSON",
+            Error::Invalid(_) => "Invalid graph description",
+            Error::UnknownType(..) => "No constructor for the node type",
+            Error::Node(..) => "Could not create node",
+            Error::NoNode(_) => "No node at the given position",
+            Error::WouldCycle => "The connections would create a cycle",
+        }
+    }
+}
+
+/// Parse an array of connections, treating a missing array as empty.
+fn connections(value: Option<&Value>) -> Result<Vec<ConnectionDescription>, Error> {
+    let connections = match value {
+        None => return Ok(Vec::new()),
+        Some(value) => value
+            .as_array()
+            .ok_or(Error::Invalid("expected an array of connections"))?,
+    };
+    connections
+        .iter()
+        .map(|connection| {
+            let endpoint = |key| {
+                connection.get(key).and_then(index).ok_or(Error::Invalid(
+                    "expected a connection source and destination",
+                ))
+            };
+            Ok(ConnectionDescription {
+                source: endpoint("source")?,
+                destination: endpoint("destination")?,
+                enabled: connection
+                    .get("enabled")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true),
+            })
+        })
+        .collect()
+}
+
+/// The value as a node position, if it is a non-negative integer.
+fn index(value: &Value) -> Option<usize> {
+    value
+        .as_f64()
+        .filter(|n| *n >= 0.0 && n.fract() == 0.0)
+        .map(|n| n as usize)
+}
@@ -0,0 +1,434 @@
+//! Minimal reading and writing of JSON.
+
+use std::fmt::{self, Write};
+
+/// A JSON value, used for the parameters of described nodes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Value {
+    /// `null`.
+    #[default]
+    Null,
+    /// `true` or `false`.
+    Bool(bool),
+    /// A number.
+    Number(f64),
+    /// A string.
+    String(String),
+    /// An array of values.
+    Array(Vec<Value>),
+    /// An object, with its members in the order in which they were written.
+    Object(Vec<(String, Value)>),
+}
+
+/// The error returned when a document is not valid JSON.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The byte offset at which the error was found.
+    pub offset: usize,
+    /// What was wrong at `offset`.
+    pub reason: &'static str,
+}
+
+impl Value {
+    /// Parse a JSON document.
+    pub fn parse(json: &str) -> Result<Value, ParseError> {
+        let mut parser = Parser {
+            json,
+            offset: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.offset != json.len() {
+            return Err(parser.error("unexpected trailing characters"));
+        }
+        Ok(value)
+    }
+
+    /// The member of an object with the given key.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// The value as a boolean, if it is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// The value as a number, if it is one.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// The value as a string, if it is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The value as an array, if it is one.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match *self {
+            Value::Array(ref values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// The members of the value, if it is an object.
+    pub fn as_object(&self) -> Option<&[(String, Value)]> {
+        match *self {
+            Value::Object(ref members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Write the value as compact JSON.
+    ///
+    /// Numbers that cannot be represented in JSON (infinities and NaN) are written as `null`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Value::Number(_) => f.write_str("null"),
+            Value::String(ref s) => write_string(f, s),
+            Value::Array(ref values) => {
+                f.write_char('[')?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(ref members) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid JSON at byte {}: {}", self.offset, self.reason)
+    }
+}
+
+impl ::std::error::Error for ParseError {
+    fn description(&self) -> &str {
+        "Invalid JSON"
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(n: f32) -> Self {
+        Value::Number(n as f64)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Self {
+        Value::Number(n as f64)
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(s: &'a str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+/// Write `s` as a quoted JSON string.
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// The maximum depth to which arrays and objects may be nested, so that parsing a hostile
+/// document cannot overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// A recursive descent parser over a JSON document.
+struct Parser<'a> {
+    json: &'a str,
+    offset: usize,
+    /// The number of arrays and objects enclosing the value being parsed.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, reason: &'static str) -> ParseError {
+        ParseError {
+            offset: self.offset,
+            reason,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.json.as_bytes().get(self.offset).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.offset += 1;
+        }
+    }
+
+    /// Consume `expected` if it is next, after any whitespace.
+    fn eat(&mut self, expected: u8) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.offset += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: u8, reason: &'static str) -> Result<(), ParseError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(reason))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.nested(Self::object),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: Value) -> Result<Value, ParseError> {
+        if self.json[self.offset..].starts_with(literal) {
+            self.offset += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error("expected a value"))
+        }
+    }
+
+    /// Parse an array or object via `parse`, failing if it is nested too deeply.
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Value, ParseError>,
+    ) -> Result<Value, ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let start = self.offset;
+        let invalid = |offset| ParseError {
+            offset,
+            reason: "invalid number",
+        };
+        self.eat_byte(b'-');
+        // The integer part is either a single zero or begins with a non-zero digit.
+        if !self.eat_byte(b'0') && self.digits() == 0 {
+            return Err(invalid(start));
+        }
+        if self.eat_byte(b'.') && self.digits() == 0 {
+            return Err(invalid(start));
+        }
+        if self.eat_byte(b'e') || self.eat_byte(b'E') {
+            if !self.eat_byte(b'+') {
+                self.eat_byte(b'-');
+            }
+            if self.digits() == 0 {
+                return Err(invalid(start));
+            }
+        }
+        self.json[start..self.offset]
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| invalid(start))
+    }
+
+    /// Consume `expected` if it is the very next byte.
+    fn eat_byte(&mut self, expected: u8) -> bool {
+        if self.peek() == Some(expected) {
+            self.offset += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume a run of decimal digits, returning their number.
+    fn digits(&mut self) -> usize {
+        let start = self.offset;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.offset += 1;
+        }
+        self.offset - start
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect(b'"', "expected a string")?;
+        let mut s = String::new();
+        loop {
+            let c = match self.json[self.offset..].chars().next() {
+                Some(c) => c,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.offset += c.len_utf8();
+            match c {
+                '"' => return Ok(s),
+                '\\' => s.push(self.escape()?),
+                c if (c as u32) < 0x20 => return Err(self.error("control character in string")),
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// Parse the remainder of an escape sequence following a backslash.
+    fn escape(&mut self) -> Result<char, ParseError> {
+        let c = self
+            .peek()
+            .ok_or_else(|| self.error("unterminated string"))?;
+        self.offset += 1;
+        let c = match c {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex4()?;
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    // A surrogate pair encodes a character outside of the basic multilingual
+                    // plane.
+                    if !self.json[self.offset..].starts_with("\\u") {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    self.offset += 2;
+                    let low = self.hex4()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    high
+                };
+                return std::char::from_u32(code).ok_or_else(|| self.error("invalid escape"));
+            }
+            _ => return Err(self.error("invalid escape")),
+        };
+        Ok(c)
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .peek()
+                .and_then(|b| (b as char).to_digit(16))
+                .ok_or_else(|| self.error("invalid escape"))?;
+            code = code * 16 + digit;
+            self.offset += 1;
+        }
+        Ok(code)
+    }
+
+    fn array(&mut self) -> Result<Value, ParseError> {
+        self.expect(b'[', "expected an array")?;
+        let mut values = Vec::new();
+        if self.eat(b']') {
+            return Ok(Value::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            if self.eat(b']') {
+                return Ok(Value::Array(values));
+            }
+            self.expect(b',', "expected ',' or ']'")?;
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, ParseError> {
+        self.expect(b'{', "expected an object")?;
+        let mut members = Vec::new();
+        if self.eat(b'}') {
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(b':', "expected ':'")?;
+            members.push((key, self.value()?));
+            if self.eat(b'}') {
+                return Ok(Value::Object(members));
+            }
+            self.expect(b',', "expected ',' or '}'")?;
+        }
+    }
+}
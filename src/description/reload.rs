@@ -0,0 +1,129 @@
+//! Reloading a **GraphDescription** into a running **Graph**.
+
+use super::{Error, GraphDescription, NodeFactory};
+use crate::graph::{Graph, NodeIndex, SwapHandle};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+/// Reloads patches into a **Graph** that is being rendered via a **GraphSwap**, e.g. whenever a
+/// file watcher reports that the patch file has changed.
+///
+/// Each reload builds a new **Graph** from its description off the audio thread and sends it to
+/// the **GraphSwap**, which takes over at the start of the next request for audio. Nodes whose
+/// description (type name and parameters) is unchanged are carried over from the running graph,
+/// so that their state (e.g. delay lines, envelopes and held notes) is preserved. A description
+/// that fails to load leaves the running graph untouched.
+///
+/// The running graph must have been built from the last description given to the **HotReload**,
+/// and must not be restructured between reloads, so that its nodes may be matched.
+pub struct HotReload<F, N, Ix = usize>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    handle: SwapHandle<F, N, Ix>,
+    factory: NodeFactory<N>,
+    current: GraphDescription,
+}
+
+impl<F, N, Ix> HotReload<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Reload patches via the given handle, creating nodes with the given factory.
+    ///
+    /// `current` is the description of the graph that is currently being rendered.
+    pub fn new(
+        handle: SwapHandle<F, N, Ix>,
+        factory: NodeFactory<N>,
+        current: GraphDescription,
+    ) -> Self {
+        HotReload {
+            handle,
+            factory,
+            current,
+        }
+    }
+
+    /// The description of the graph most recently sent to the **GraphSwap**.
+    pub fn description(&self) -> &GraphDescription {
+        &self.current
+    }
+
+    /// The handle via which graphs are sent to the **GraphSwap**.
+    ///
+    /// Use this to collect the graphs that have been replaced via `SwapHandle::retired`.
+    pub fn handle(&self) -> &SwapHandle<F, N, Ix> {
+        &self.handle
+    }
+
+    /// The factory used to create nodes.
+    pub fn factory(&self) -> &NodeFactory<N> {
+        &self.factory
+    }
+
+    /// The factory used to create nodes, e.g. to register constructors for new node types.
+    pub fn factory_mut(&mut self) -> &mut NodeFactory<N> {
+        &mut self.factory
+    }
+
+    /// Parse the given JSON and reload the described patch.
+    ///
+    /// See [`reload`](./struct.HotReload.html#method.reload).
+    pub fn reload_json(&mut self, json: &str) -> Result<bool, Error> {
+        let description = GraphDescription::from_json(json)?;
+        self.reload(description)
+    }
+
+    /// Build a graph from the given description and send it to replace the running graph at the
+    /// start of the next request for audio, carrying over each node whose description is
+    /// unchanged.
+    ///
+    /// Returns an error without touching the running graph if the description could not be
+    /// loaded. Otherwise returns `false` if the **GraphSwap** no longer exists.
+    pub fn reload(&mut self, description: GraphDescription) -> Result<bool, Error> {
+        let graph = Graph::from_description(&description, &self.factory)?;
+        let preserved = preserved_nodes(&self.current, &description);
+        let sent = self.handle.swap_preserving(graph, preserved);
+        self.current = description;
+        Ok(sent)
+    }
+}
+
+/// Pair each node described by `new` with an unchanged node described by `old`, preferring the
+/// node at the same position.
+fn preserved_nodes<Ix>(
+    old: &GraphDescription,
+    new: &GraphDescription,
+) -> Vec<(NodeIndex<Ix>, NodeIndex<Ix>)>
+where
+    Ix: IndexType,
+{
+    // Match the nodes that are unchanged at the same position first, then any others.
+    let mut matches: Vec<Option<usize>> = new
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| Some(i).filter(|&i| old.nodes.get(i) == Some(node)))
+        .collect();
+    let mut used = vec![false; old.nodes.len()];
+    for &j in matches.iter().flatten() {
+        used[j] = true;
+    }
+    for (node, matched) in new.nodes.iter().zip(&mut matches) {
+        if matched.is_none() {
+            *matched = (0..old.nodes.len()).find(|&j| !used[j] && old.nodes[j] == *node);
+            if let Some(j) = *matched {
+                used[j] = true;
+            }
+        }
+    }
+    matches
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, matched)| matched.map(|j| (NodeIndex::new(i), NodeIndex::new(j))))
+        .collect()
+}
@@ -0,0 +1,10 @@
+//! Higher-level constructs built from several **Graph**s, such as the multi-track **Mixer**.
+//!
+//! Many applications build the same structure on top of dsp-chain: one **Graph** per track, each
+//! with its own fader, summed into a master **Graph**. The **Mixer** provides this structure so
+//! that it need not be rebuilt for each application.
+
+pub use self::mixer::{Mixer, MixerParam, Track, TrackParam, Vca, VcaParam};
+
+mod mixer;
+mod workers;
@@ -0,0 +1,977 @@
+//! A mixer of independent **Graph**s, each with its own gain, pan, mute and solo, summed into a
+//! master **Graph** directly or via group buses, with VCA faders linking the gains of tracks.
+
+use super::workers::{Work, WorkerPool};
+use crate::event::Event;
+use crate::graph::{ExternalKind, Graph, Tempo};
+use crate::node::{Node, ParamChange};
+use crate::{Panning, Volume};
+use daggy::petgraph::graph::IndexType;
+use dasp::{Frame, Sample};
+use std::any::Any;
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+/// The number of parameters of each track and group bus.
+const TRACK_PARAMS: usize = 4;
+
+/// The number of parameters of each VCA.
+const VCA_PARAMS: usize = 2;
+
+/// The number of parameter indices shared by the track, group bus and VCA at each index: those of
+/// the track, followed by those of the group bus, followed by those of the VCA.
+const STRIP_PARAMS: usize = 2 * TRACK_PARAMS + VCA_PARAMS;
+
+/// A parameter of a **Track** or group bus that may be set via `Event::Param`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TrackParam {
+    /// The linear gain of the track.
+    Gain,
+    /// The pan of the track, from `-1.0` (left) to `1.0` (right).
+    Pan,
+    /// Whether the track is muted, where `value >= 0.5` mutes it.
+    Mute,
+    /// Whether the track is soloed, where `value >= 0.5` solos it.
+    Solo,
+}
+
+/// A parameter of a **Vca** that may be set via `Event::Param`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum VcaParam {
+    /// The linear gain of the VCA.
+    Gain,
+    /// Whether the VCA is muted, where `value >= 0.5` mutes it.
+    Mute,
+}
+
+/// The address of a parameter of a **Mixer**, which is set via `Event::Param` with the `param`
+/// given by `index`.
+///
+/// Each track, group bus and VCA is addressed by its index, so that the parameters of any number
+/// of each may be addressed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MixerParam {
+    /// A parameter of the track at the given index.
+    Track(usize, TrackParam),
+    /// A parameter of the group bus at the given index.
+    Group(usize, TrackParam),
+    /// A parameter of the VCA at the given index.
+    Vca(usize, VcaParam),
+}
+
+impl TrackParam {
+    /// Every parameter of a track in the order of their indices.
+    pub const ALL: [TrackParam; TRACK_PARAMS] = [
+        TrackParam::Gain,
+        TrackParam::Pan,
+        TrackParam::Mute,
+        TrackParam::Solo,
+    ];
+}
+
+impl VcaParam {
+    /// Every parameter of a VCA in the order of their indices.
+    pub const ALL: [VcaParam; VCA_PARAMS] = [VcaParam::Gain, VcaParam::Mute];
+}
+
+impl MixerParam {
+    /// The index of the parameter, as set via `Event::Param`.
+    pub fn index(self) -> usize {
+        match self {
+            MixerParam::Track(track, param) => track * STRIP_PARAMS + param as usize,
+            MixerParam::Group(group, param) => group * STRIP_PARAMS + TRACK_PARAMS + param as usize,
+            MixerParam::Vca(vca, param) => vca * STRIP_PARAMS + 2 * TRACK_PARAMS + param as usize,
+        }
+    }
+
+    /// The parameter with the given index, as set via `Event::Param`.
+    pub fn from_index(index: usize) -> Self {
+        let (strip, offset) = (index / STRIP_PARAMS, index % STRIP_PARAMS);
+        match offset {
+            o if o < TRACK_PARAMS => MixerParam::Track(strip, TrackParam::ALL[o]),
+            o if o < 2 * TRACK_PARAMS => {
+                MixerParam::Group(strip, TrackParam::ALL[o - TRACK_PARAMS])
+            }
+            o => MixerParam::Vca(strip, VcaParam::ALL[o - 2 * TRACK_PARAMS]),
+        }
+    }
+}
+
+/// One track or group bus of a **Mixer**: a **Graph** and its fader.
+///
+/// A group bus is a **Track** whose **Graph** receives the sum of its member tracks via an
+/// external input, e.g. to apply effects to a group of drums, and whose output is summed into the
+/// master **Graph**.
+#[derive(Clone)]
+pub struct Track<F, N, Ix = usize>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    name: String,
+    graph: Graph<F, N, Ix>,
+    /// The linear gain applied to the output of the track's **Graph**.
+    pub gain: Volume,
+    /// The pan of the track, from `-1.0` (left) to `1.0` (right), applied with a constant-power
+    /// law when `F` has two channels and ignored otherwise.
+    pub pan: Panning,
+    /// Whether the track is silenced.
+    pub muted: bool,
+    /// Whether the track is soloed, silencing every track that is not soloed.
+    pub soloed: bool,
+    /// The group bus into which the track is summed, or `None` if it is summed into the master.
+    group: Option<usize>,
+    /// The VCAs that control the track.
+    vcas: Vec<usize>,
+    /// The name of the external input that receives the sum of the members of a group bus.
+    input: Option<String>,
+    /// The sum of the members of a group bus during the current request.
+    input_buffer: Vec<F>,
+    /// The output of the track's **Graph** during the current request.
+    buffer: Vec<F>,
+    /// The gain of each channel at the end of the last request, from which the next is ramped.
+    channel_gains: Vec<f32>,
+    /// The gain of each channel at the end of the current request.
+    target_gains: Vec<f32>,
+    /// The value of each parameter as last reported via `Node::param_changes` or set via
+    /// `Event::Param`.
+    reported: [f32; TRACK_PARAMS],
+}
+
+/// A VCA fader of a **Mixer**, scaling the gain of each of its member tracks without passing any
+/// audio of its own.
+///
+/// Moving a VCA moves every member relative to its own fader, so that the balance between the
+/// members is kept. A track may be a member of several VCAs, whose gains multiply.
+#[derive(Clone, Debug)]
+pub struct Vca {
+    name: String,
+    /// The linear gain by which the gain of each member is multiplied, equivalent to offsetting
+    /// the fader of each member by the same number of decibels.
+    pub gain: Volume,
+    /// Whether every member is silenced.
+    pub muted: bool,
+    /// The value of each parameter as last reported via `Node::param_changes` or set via
+    /// `Event::Param`.
+    reported: [f32; VCA_PARAMS],
+}
+
+/// Owns several independent **Graph**s, the tracks, and sums them into a master **Graph** after
+/// applying the gain, pan, mute and solo of each track.
+///
+/// Each request for audio renders every track, including those that are muted so that they keep
+/// time with the others. Tracks may be routed to group buses via `set_track_group`, which sum
+/// their members through a **Graph** and fader of their own. The sum of the audible tracks and
+/// group buses is written to a named external input of the master **Graph**, from which the
+/// output is then rendered. The tracks and group buses may be rendered in parallel via
+/// `set_parallel`, provided that their nodes may be sent to other threads.
+///
+/// VCA faders, added via `add_vca` and assigned via `assign_vca`, link the gains of their member
+/// tracks. Soloing a group bus solos its members, while soloing a member keeps its group bus
+/// audible.
+///
+/// The **Mixer** is itself a **Node**, so it may be rendered directly by the host or nested
+/// within a larger **Graph**. The faders may be automated via `Event::Param`, where `param` is
+/// given by `MixerParam::index`, so that the moves of group buses and
+/// VCAs may be recorded and played back, and smoothed by the **Graph**, like those of any other
+/// parameter. Changes to the gains and pans are ramped over the following request to avoid
+/// clicks. Moves made directly via `track_mut`, `group_mut` or `vca_mut` are reported via
+/// `Node::param_changes`, so that a parent **Graph** notifies the host of them.
+///
+/// The latencies of the tracks are not compensated, so the latency of the **Mixer** is that of
+/// its slowest path through a track, its group bus and the master. Messages are forwarded to the
+/// output node of every track, group bus and the master **Graph**.
+#[derive(Clone)]
+pub struct Mixer<F, N, Ix = usize>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    tracks: Vec<Track<F, N, Ix>>,
+    groups: Vec<Track<F, N, Ix>>,
+    vcas: Vec<Vca>,
+    master: Graph<F, N, Ix>,
+    /// The name of the master **Graph**'s external input that receives the sum of the tracks.
+    master_input: String,
+    /// Renders the tracks across the worker threads, if they are rendered in parallel.
+    ///
+    /// Stored by `set_parallel`, so that only rendering in parallel requires the tracks to be
+    /// `Send`.
+    render_parallel: Option<RenderParallel<F, N, Ix>>,
+    /// The threads across which the tracks and group buses are rendered in parallel.
+    workers: WorkerPool<Track<F, N, Ix>, (usize, f64)>,
+    /// The sum of the tracks during the current request.
+    mix_buffer: Vec<F>,
+}
+
+/// **Panics** unless the given **Graph** has an external input with the given name.
+fn assert_external_input<F, N, Ix>(graph: &Graph<F, N, Ix>, name: &str, what: &str)
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    assert!(
+        graph
+            .external(name)
+            .is_some_and(|external| external.kind == ExternalKind::Input),
+        "the {} graph has no external input named {:?}",
+        what,
+        name
+    );
+}
+
+/// The number of tracks and group buses for which the worker threads are prepared at least, so
+/// that adding a few tracks does not require them to be restarted.
+const MIN_WORKER_CAPACITY: usize = 16;
+
+/// Renders a list of tracks across the worker threads, given the number of frames and the sample
+/// rate.
+type RenderParallel<F, N, Ix> =
+    fn(&mut Vec<Track<F, N, Ix>>, &mut WorkerPool<Track<F, N, Ix>, (usize, f64)>, (usize, f64));
+
+/// Render every track of the given list into its buffer, across the worker threads if
+/// `render_parallel` is given.
+///
+/// The tracks are rendered serially if there are more than the worker threads were prepared
+/// for, as restarting the threads here would spawn them while rendering.
+fn render_tracks<F, N, Ix>(
+    tracks: &mut Vec<Track<F, N, Ix>>,
+    workers: &mut WorkerPool<Track<F, N, Ix>, (usize, f64)>,
+    render_parallel: Option<RenderParallel<F, N, Ix>>,
+    frames: usize,
+    sample_hz: f64,
+) where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    match render_parallel {
+        Some(render) if tracks.len() >= 2 && workers.capacity() >= tracks.len() => {
+            render(tracks, workers, (frames, sample_hz));
+        }
+        _ => {
+            for track in tracks {
+                track.render(frames, sample_hz);
+            }
+        }
+    }
+}
+
+impl Vca {
+    /// The name of the VCA.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<F, N, Ix> Track<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    fn new(name: &str, graph: Graph<F, N, Ix>) -> Self {
+        Track {
+            name: name.to_string(),
+            graph,
+            gain: 1.0,
+            pan: 0.0,
+            muted: false,
+            soloed: false,
+            group: None,
+            vcas: Vec::new(),
+            input: None,
+            input_buffer: Vec::new(),
+            buffer: Vec::new(),
+            channel_gains: Vec::new(),
+            target_gains: Vec::new(),
+            reported: [1.0, 0.0, 0.0, 0.0],
+        }
+    }
+
+    /// The name of the track.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The track's **Graph**.
+    pub fn graph(&self) -> &Graph<F, N, Ix> {
+        &self.graph
+    }
+
+    /// The track's **Graph**, e.g. to add nodes or send events.
+    pub fn graph_mut(&mut self) -> &mut Graph<F, N, Ix> {
+        &mut self.graph
+    }
+
+    /// The output of the track's **Graph** during the last request, before its fader.
+    pub fn buffer(&self) -> &[F] {
+        &self.buffer
+    }
+
+    /// The index of the group bus into which the track is summed, or `None` if it is summed
+    /// directly into the master. This is always `None` for group buses.
+    pub fn group(&self) -> Option<usize> {
+        self.group
+    }
+
+    /// The indices of the VCAs that control the track.
+    pub fn vcas(&self) -> &[usize] {
+        &self.vcas
+    }
+
+    /// Clear the sum of the members of a group bus ahead of the current request.
+    fn clear_input(&mut self, frames: usize) {
+        if self.input_buffer.len() != frames {
+            self.input_buffer.resize(frames, F::EQUILIBRIUM);
+        }
+        dasp::slice::equilibrium(&mut self.input_buffer);
+    }
+
+    /// Render the track's **Graph** into its buffer, first writing the sum of the members of a
+    /// group bus to its external input.
+    fn render(&mut self, frames: usize, sample_hz: f64) {
+        if let Some(input) = &self.input {
+            self.graph.write_external_input(input, &self.input_buffer);
+        }
+        if self.buffer.len() != frames {
+            self.buffer.resize(frames, F::EQUILIBRIUM);
+        }
+        dasp::slice::equilibrium(&mut self.buffer);
+        self.graph.audio_requested(&mut self.buffer, sample_hz);
+    }
+
+    /// The gain of the given channel, given the gain applied on top of the track's fader, which
+    /// is `0.0` if the track is not audible.
+    fn channel_gain(&self, channel: usize, gain: f32) -> f32 {
+        if gain == 0.0 {
+            return 0.0;
+        }
+        let gain = self.gain * gain;
+        if F::CHANNELS != 2 {
+            return gain;
+        }
+        let angle = (self.pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+        let law = if channel == 0 {
+            angle.cos()
+        } else {
+            angle.sin()
+        };
+        // Scaled so that a centred track passes at unity gain.
+        gain * law * SQRT_2
+    }
+
+    /// Sum the track's buffer onto `output` through its fader and the given additional gain,
+    /// ramping from the gains of the last request.
+    fn sum_onto(&mut self, output: &mut [F], gain: f32) {
+        self.target_gains.clear();
+        for ch in 0..F::CHANNELS {
+            let gain = self.channel_gain(ch, gain);
+            self.target_gains.push(gain);
+        }
+        if self.channel_gains.len() != F::CHANNELS {
+            self.channel_gains = self.target_gains.clone();
+        }
+        let (from, to) = (&self.channel_gains, &self.target_gains);
+        if from.iter().chain(to).all(|&gain| gain == 0.0) {
+            return;
+        }
+        let len = output.len() as f32;
+        for (i, (out, frame)) in output.iter_mut().zip(&self.buffer).enumerate() {
+            let x = (i + 1) as f32 / len;
+            *out = F::from_fn(|ch| {
+                let gain = from[ch] + (to[ch] - from[ch]) * x;
+                let s = frame.channel(ch).copied().unwrap_or(F::Sample::EQUILIBRIUM);
+                let out = out.channel(ch).copied().unwrap_or(F::Sample::EQUILIBRIUM);
+                out.add_amp(s.mul_amp(gain.to_sample()).to_signed_sample())
+            });
+        }
+        self.channel_gains.copy_from_slice(&self.target_gains);
+    }
+
+    /// The current value of the given parameter.
+    fn param_value(&self, param: TrackParam) -> f32 {
+        match param {
+            TrackParam::Gain => self.gain,
+            TrackParam::Pan => self.pan,
+            TrackParam::Mute => flag_value(self.muted),
+            TrackParam::Solo => flag_value(self.soloed),
+        }
+    }
+
+    /// Set the given parameter, as set via `Event::Param`.
+    fn set_param(&mut self, param: TrackParam, value: f32) {
+        match param {
+            TrackParam::Gain => self.gain = value,
+            TrackParam::Pan => self.pan = value,
+            TrackParam::Mute => self.muted = value >= 0.5,
+            TrackParam::Solo => self.soloed = value >= 0.5,
+        }
+        self.reported[param as usize] = self.param_value(param);
+    }
+}
+
+impl Vca {
+    /// The current value of the given parameter.
+    fn param_value(&self, param: VcaParam) -> f32 {
+        match param {
+            VcaParam::Gain => self.gain,
+            VcaParam::Mute => flag_value(self.muted),
+        }
+    }
+
+    /// Set the given parameter, as set via `Event::Param`.
+    fn set_param(&mut self, param: VcaParam, value: f32) {
+        match param {
+            VcaParam::Gain => self.gain = value,
+            VcaParam::Mute => self.muted = value >= 0.5,
+        }
+        self.reported[param as usize] = self.param_value(param);
+    }
+}
+
+/// The value of a parameter that is either on or off.
+fn flag_value(on: bool) -> f32 {
+    if on {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl<F, N, Ix> Work<(usize, f64)> for Track<F, N, Ix>
+where
+    F: Frame + Send + 'static,
+    F::Sample: Send,
+    N: Node<F> + Send + 'static,
+    Ix: IndexType + Send,
+{
+    fn work(&mut self, (frames, sample_hz): (usize, f64)) {
+        self.render(frames, sample_hz);
+    }
+}
+
+/// Report the value of the given parameter if it differs from the value last reported.
+fn report_change(
+    reported: &mut f32,
+    value: f32,
+    param: MixerParam,
+    changes: &mut Vec<ParamChange>,
+) {
+    if *reported != value {
+        *reported = value;
+        changes.push(ParamChange {
+            param: param.index(),
+            value,
+        });
+    }
+}
+
+impl<F, N, Ix> Mixer<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// A mixer with no tracks that sums into the external input named `master_input` of the
+    /// given master **Graph**.
+    ///
+    /// **Panics** if the master **Graph** has no external input with the given name.
+    pub fn new(master: Graph<F, N, Ix>, master_input: &str) -> Self {
+        assert_external_input(&master, master_input, "master");
+        Mixer {
+            tracks: Vec::new(),
+            groups: Vec::new(),
+            vcas: Vec::new(),
+            master,
+            master_input: master_input.to_string(),
+            render_parallel: None,
+            workers: WorkerPool::default(),
+            mix_buffer: Vec::new(),
+        }
+    }
+
+    /// Add a track rendering the given **Graph** at unity gain, returning the index of the track.
+    pub fn add_track(&mut self, name: &str, graph: Graph<F, N, Ix>) -> usize {
+        self.tracks.push(Track::new(name, graph));
+        self.tracks.len() - 1
+    }
+
+    /// Remove and return the **Graph** of the track at the given index, or `None` if there is no
+    /// such track.
+    ///
+    /// The tracks after it shift down by one index, along with their parameters.
+    pub fn remove_track(&mut self, index: usize) -> Option<Graph<F, N, Ix>> {
+        if index >= self.tracks.len() {
+            return None;
+        }
+        Some(self.tracks.remove(index).graph)
+    }
+
+    /// All tracks in the order in which they were added.
+    pub fn tracks(&self) -> &[Track<F, N, Ix>] {
+        &self.tracks
+    }
+
+    /// The track at the given index.
+    pub fn track(&self, index: usize) -> Option<&Track<F, N, Ix>> {
+        self.tracks.get(index)
+    }
+
+    /// The track at the given index, e.g. to move its fader.
+    pub fn track_mut(&mut self, index: usize) -> Option<&mut Track<F, N, Ix>> {
+        self.tracks.get_mut(index)
+    }
+
+    /// The index of the track with the given name.
+    pub fn track_index(&self, name: &str) -> Option<usize> {
+        self.tracks.iter().position(|track| track.name == name)
+    }
+
+    /// Add a group bus rendering the given **Graph** at unity gain, returning the index of the
+    /// group bus.
+    ///
+    /// The sum of the member tracks is written to the external input of the **Graph** named
+    /// `input` ahead of each request.
+    ///
+    /// **Panics** if the **Graph** has no external input with the given name.
+    pub fn add_group(&mut self, name: &str, graph: Graph<F, N, Ix>, input: &str) -> usize {
+        assert_external_input(&graph, input, "group");
+        let mut group = Track::new(name, graph);
+        group.input = Some(input.to_string());
+        self.groups.push(group);
+        self.groups.len() - 1
+    }
+
+    /// Remove and return the **Graph** of the group bus at the given index, or `None` if there is
+    /// no such group bus.
+    ///
+    /// The members of the group bus are summed directly into the master once more. The group
+    /// buses after it shift down by one index, along with their parameters.
+    pub fn remove_group(&mut self, index: usize) -> Option<Graph<F, N, Ix>> {
+        if index >= self.groups.len() {
+            return None;
+        }
+        for track in &mut self.tracks {
+            track.group = match track.group {
+                Some(group) if group == index => None,
+                Some(group) if group > index => Some(group - 1),
+                group => group,
+            };
+        }
+        Some(self.groups.remove(index).graph)
+    }
+
+    /// All group buses in the order in which they were added.
+    pub fn groups(&self) -> &[Track<F, N, Ix>] {
+        &self.groups
+    }
+
+    /// The group bus at the given index.
+    pub fn group(&self, index: usize) -> Option<&Track<F, N, Ix>> {
+        self.groups.get(index)
+    }
+
+    /// The group bus at the given index, e.g. to move its fader.
+    pub fn group_mut(&mut self, index: usize) -> Option<&mut Track<F, N, Ix>> {
+        self.groups.get_mut(index)
+    }
+
+    /// The index of the group bus with the given name.
+    pub fn group_index(&self, name: &str) -> Option<usize> {
+        self.groups.iter().position(|group| group.name == name)
+    }
+
+    /// Route the track at the given index into the group bus at the given index, or directly into
+    /// the master if `group` is `None`.
+    ///
+    /// Returns `false` if there is no such track or group bus.
+    pub fn set_track_group(&mut self, track: usize, group: Option<usize>) -> bool {
+        if group.is_some_and(|group| group >= self.groups.len()) {
+            return false;
+        }
+        match self.tracks.get_mut(track) {
+            Some(track) => {
+                track.group = group;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Add a VCA at unity gain with no members, returning the index of the VCA.
+    pub fn add_vca(&mut self, name: &str) -> usize {
+        self.vcas.push(Vca {
+            name: name.to_string(),
+            gain: 1.0,
+            muted: false,
+            reported: [1.0, 0.0],
+        });
+        self.vcas.len() - 1
+    }
+
+    /// Remove and return the VCA at the given index, or `None` if there is no such VCA.
+    ///
+    /// Its members are released from it. The VCAs after it shift down by one index, along with
+    /// their parameters.
+    pub fn remove_vca(&mut self, index: usize) -> Option<Vca> {
+        if index >= self.vcas.len() {
+            return None;
+        }
+        for track in &mut self.tracks {
+            track.vcas.retain(|&vca| vca != index);
+            for vca in &mut track.vcas {
+                if *vca > index {
+                    *vca -= 1;
+                }
+            }
+        }
+        Some(self.vcas.remove(index))
+    }
+
+    /// All VCAs in the order in which they were added.
+    pub fn vcas(&self) -> &[Vca] {
+        &self.vcas
+    }
+
+    /// The VCA at the given index.
+    pub fn vca(&self, index: usize) -> Option<&Vca> {
+        self.vcas.get(index)
+    }
+
+    /// The VCA at the given index, e.g. to move its fader.
+    pub fn vca_mut(&mut self, index: usize) -> Option<&mut Vca> {
+        self.vcas.get_mut(index)
+    }
+
+    /// The index of the VCA with the given name.
+    pub fn vca_index(&self, name: &str) -> Option<usize> {
+        self.vcas.iter().position(|vca| vca.name == name)
+    }
+
+    /// Make the track at the given index a member of the VCA at the given index.
+    ///
+    /// Returns `false` if there is no such track or VCA.
+    pub fn assign_vca(&mut self, track: usize, vca: usize) -> bool {
+        if vca >= self.vcas.len() {
+            return false;
+        }
+        match self.tracks.get_mut(track) {
+            Some(track) => {
+                if !track.vcas.contains(&vca) {
+                    track.vcas.push(vca);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Release the track at the given index from the VCA at the given index.
+    ///
+    /// Returns `false` if the track was not a member of the VCA.
+    pub fn unassign_vca(&mut self, track: usize, vca: usize) -> bool {
+        match self.tracks.get_mut(track) {
+            Some(track) => {
+                let len = track.vcas.len();
+                track.vcas.retain(|&v| v != vca);
+                track.vcas.len() != len
+            }
+            None => false,
+        }
+    }
+
+    /// The gain applied to the track at the given index by its VCAs, which is `0.0` if any of
+    /// them is muted.
+    pub fn vca_gain(&self, index: usize) -> Volume {
+        let track = match self.tracks.get(index) {
+            Some(track) => track,
+            None => return 0.0,
+        };
+        track
+            .vcas
+            .iter()
+            .map(|&vca| &self.vcas[vca])
+            .fold(
+                1.0,
+                |gain, vca| {
+                    if vca.muted {
+                        0.0
+                    } else {
+                        gain * vca.gain
+                    }
+                },
+            )
+    }
+
+    /// The master **Graph**.
+    pub fn master(&self) -> &Graph<F, N, Ix> {
+        &self.master
+    }
+
+    /// The master **Graph**, e.g. to add effects to the sum of the tracks.
+    pub fn master_mut(&mut self) -> &mut Graph<F, N, Ix> {
+        &mut self.master
+    }
+
+    /// Whether any track or group bus is soloed.
+    pub fn any_soloed(&self) -> bool {
+        let mut strips = self.tracks.iter().chain(&self.groups);
+        strips.any(|strip| strip.soloed)
+    }
+
+    /// Whether the track at the given index is heard, given the mute and solo of every track and
+    /// group bus and the mute of its VCAs.
+    pub fn is_audible(&self, index: usize) -> bool {
+        self.track_audible(index, self.any_soloed())
+    }
+
+    /// Whether the group bus at the given index is heard, given the mute and solo of every track
+    /// and group bus.
+    pub fn is_group_audible(&self, index: usize) -> bool {
+        self.group_audible(index, self.any_soloed())
+    }
+
+    /// Whether the track at the given index is heard, given whether any track or group bus is
+    /// soloed.
+    fn track_audible(&self, index: usize, any_soloed: bool) -> bool {
+        let track = match self.tracks.get(index) {
+            Some(track) => track,
+            None => return false,
+        };
+        let group = track.group.map(|group| &self.groups[group]);
+        let soloed = track.soloed || group.is_some_and(|group| group.soloed);
+        let vca_muted = track.vcas.iter().any(|&vca| self.vcas[vca].muted);
+        !track.muted && !vca_muted && (soloed || !any_soloed)
+    }
+
+    /// Whether the group bus at the given index is heard, given whether any track or group bus
+    /// is soloed.
+    fn group_audible(&self, index: usize, any_soloed: bool) -> bool {
+        let group = match self.groups.get(index) {
+            Some(group) => group,
+            None => return false,
+        };
+        let mut members = self
+            .tracks
+            .iter()
+            .filter(|track| track.group == Some(index));
+        let soloed = group.soloed || members.any(|track| track.soloed);
+        !group.muted && (soloed || !any_soloed)
+    }
+
+    /// Whether the tracks are rendered in parallel.
+    pub fn is_parallel(&self) -> bool {
+        self.render_parallel.is_some()
+    }
+
+    /// Sum the rendered tracks through their faders into the inputs of their group buses or the
+    /// mix buffer.
+    fn mix_tracks(&mut self, frames: usize) {
+        if self.mix_buffer.len() != frames {
+            self.mix_buffer.resize(frames, F::EQUILIBRIUM);
+        }
+        dasp::slice::equilibrium(&mut self.mix_buffer);
+        for group in &mut self.groups {
+            group.clear_input(frames);
+        }
+        let any_soloed = self.any_soloed();
+        for i in 0..self.tracks.len() {
+            let gain = if self.track_audible(i, any_soloed) {
+                self.vca_gain(i)
+            } else {
+                0.0
+            };
+            let track = &mut self.tracks[i];
+            let output = match track.group {
+                Some(group) => &mut self.groups[group].input_buffer,
+                None => &mut self.mix_buffer,
+            };
+            track.sum_onto(output, gain);
+        }
+    }
+
+    /// Sum the rendered group buses through their faders into the mix buffer.
+    fn mix_groups(&mut self) {
+        let any_soloed = self.any_soloed();
+        for i in 0..self.groups.len() {
+            let gain = if self.group_audible(i, any_soloed) {
+                1.0
+            } else {
+                0.0
+            };
+            self.groups[i].sum_onto(&mut self.mix_buffer, gain);
+        }
+    }
+}
+
+impl<F, N, Ix> Mixer<F, N, Ix>
+where
+    F: Frame + Send + 'static,
+    F::Sample: Send,
+    N: Node<F> + Send + 'static,
+    Ix: IndexType + Send,
+{
+    /// Set whether the tracks are rendered in parallel across a pool of worker threads, one per
+    /// core, followed by the group buses in parallel.
+    ///
+    /// The worker threads are started here and persist until the tracks are rendered serially
+    /// once more or the **Mixer** is dropped, so no threads are spawned while rendering. They are
+    /// prepared for the current tracks and group buses and a few more; while there are more than
+    /// that, they are rendered serially until `reserve_tracks` is called. A clone of the
+    /// **Mixer** renders serially until this or `reserve_tracks` is called on it.
+    ///
+    /// By default, this is `false` and the tracks are rendered in turn on the calling thread.
+    pub fn set_parallel(&mut self, parallel: bool) {
+        if parallel {
+            self.render_parallel = Some(Self::render_parallel);
+            self.reserve_tracks(0);
+        } else {
+            self.render_parallel = None;
+            self.workers = WorkerPool::default();
+        }
+    }
+
+    /// Prepare the worker threads to render at least `additional` more tracks or group buses in
+    /// parallel than there are now, restarting them if they were prepared for fewer.
+    ///
+    /// Call this before adding many tracks to a parallel **Mixer**, as the threads are never
+    /// restarted while rendering. Does nothing unless the tracks are rendered in parallel.
+    pub fn reserve_tracks(&mut self, additional: usize) {
+        let strips = self.tracks.len().max(self.groups.len()) + additional;
+        if self.is_parallel() && self.workers.capacity() < strips {
+            self.workers = WorkerPool::new(strips.max(MIN_WORKER_CAPACITY));
+        }
+    }
+
+    /// Render every track of the given list across the worker threads.
+    fn render_parallel(
+        tracks: &mut Vec<Track<F, N, Ix>>,
+        workers: &mut WorkerPool<Track<F, N, Ix>, (usize, f64)>,
+        context: (usize, f64),
+    ) {
+        workers.run(tracks, context);
+    }
+}
+
+impl<F, N, Ix> Node<F> for Mixer<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    fn audio_requested(&mut self, output: &mut [F], sample_hz: f64) {
+        let frames = output.len();
+        let render_parallel = self.render_parallel;
+        render_tracks(
+            &mut self.tracks,
+            &mut self.workers,
+            render_parallel,
+            frames,
+            sample_hz,
+        );
+        self.mix_tracks(frames);
+        render_tracks(
+            &mut self.groups,
+            &mut self.workers,
+            render_parallel,
+            frames,
+            sample_hz,
+        );
+        self.mix_groups();
+        self.master
+            .write_external_input(&self.master_input, &self.mix_buffer);
+        self.master.audio_requested(output, sample_hz);
+    }
+
+    /// The latency of the slowest path through a track, its group bus and the master **Graph**.
+    fn latency(&self) -> usize {
+        let group_latency = |group: Option<usize>| match group {
+            Some(group) => Node::latency(&self.groups[group].graph),
+            None => 0,
+        };
+        let tracks = self
+            .tracks
+            .iter()
+            .map(|track| Node::latency(&track.graph) + group_latency(track.group));
+        let groups = self.groups.iter().map(|group| Node::latency(&group.graph));
+        let slowest = tracks.chain(groups).max().unwrap_or(0);
+        slowest + Node::latency(&self.master)
+    }
+
+    fn tail_frames(&self) -> usize {
+        let tail = |strips: &[Track<F, N, Ix>]| {
+            let tails = strips.iter().map(|strip| strip.graph.tail_frames());
+            tails.max().unwrap_or(0)
+        };
+        tail(&self.tracks) + tail(&self.groups) + self.master.tail_frames()
+    }
+
+    fn update_tempo(&mut self, tempo: &Tempo) {
+        for track in self.tracks.iter_mut().chain(&mut self.groups) {
+            track.graph.update_tempo(tempo);
+        }
+        self.master.update_tempo(tempo);
+    }
+
+    fn channels_changed(&mut self, channels: usize) {
+        for track in self.tracks.iter_mut().chain(&mut self.groups) {
+            track.graph.channels_changed(channels);
+        }
+        self.master.channels_changed(channels);
+    }
+
+    /// Reports the moves of faders made directly rather than via `Event::Param`.
+    fn param_changes(&mut self, changes: &mut Vec<ParamChange>) {
+        for (i, track) in self.tracks.iter_mut().enumerate() {
+            for &param in &TrackParam::ALL {
+                let value = track.param_value(param);
+                let reported = &mut track.reported[param as usize];
+                report_change(reported, value, MixerParam::Track(i, param), changes);
+            }
+        }
+        for (i, group) in self.groups.iter_mut().enumerate() {
+            for &param in &TrackParam::ALL {
+                let value = group.param_value(param);
+                let reported = &mut group.reported[param as usize];
+                report_change(reported, value, MixerParam::Group(i, param), changes);
+            }
+        }
+        for (i, vca) in self.vcas.iter_mut().enumerate() {
+            for &param in &VcaParam::ALL {
+                let value = vca.param_value(param);
+                let reported = &mut vca.reported[param as usize];
+                report_change(reported, value, MixerParam::Vca(i, param), changes);
+            }
+        }
+    }
+
+    fn handle_message(&mut self, message: &dyn Any) {
+        for track in self.tracks.iter_mut().chain(&mut self.groups) {
+            track.graph.handle_message(message);
+        }
+        self.master.handle_message(message);
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { param, value } = *event {
+            match MixerParam::from_index(param) {
+                MixerParam::Track(track, param) => {
+                    if let Some(track) = self.tracks.get_mut(track) {
+                        track.set_param(param, value);
+                    }
+                }
+                MixerParam::Group(group, param) => {
+                    if let Some(group) = self.groups.get_mut(group) {
+                        group.set_param(param, value);
+                    }
+                }
+                MixerParam::Vca(vca, param) => {
+                    if let Some(vca) = self.vcas.get_mut(vca) {
+                        vca.set_param(param, value);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! A pool of persistent threads across which the **Mixer** renders its tracks in parallel.
+
+use std::any::Any;
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+
+/// Work that may be carried out on one of the threads of a **WorkerPool**, given some context
+/// shared by every job of a run.
+pub(crate) trait Work<C>: Send + 'static {
+    /// Carry out the work.
+    fn work(&mut self, context: C);
+}
+
+/// A job sent to a worker: the item along with its position among the items of the run.
+type Job<T, C> = (usize, T, C);
+
+/// A finished job returned by a worker, along with the payload of its panic if it panicked.
+type Done<T> = (usize, T, Option<Box<dyn Any + Send>>);
+
+/// Persistent threads that carry out the work of up to `capacity` items at a time.
+///
+/// The threads are started when the pool is created and stopped when it is dropped, so that
+/// running the items spawns no threads. Items are moved to the threads and back via channels
+/// whose buffers are allocated up front, so running them does not allocate either.
+pub(crate) struct WorkerPool<T, C> {
+    jobs: Option<SyncSender<Job<T, C>>>,
+    done: Option<Receiver<Done<T>>>,
+    /// Holds each finished item until every item of the run is done, so that they are returned
+    /// in their original order.
+    slots: Vec<Option<T>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl<T, C> WorkerPool<T, C>
+where
+    T: Work<C>,
+    C: Copy + Send + 'static,
+{
+    /// A pool with a thread per available core, up to `capacity` threads, able to run up to
+    /// `capacity` items at a time.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let cores = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let (jobs, job_receiver) = mpsc::sync_channel::<Job<T, C>>(capacity);
+        let (done_sender, done) = mpsc::sync_channel(capacity);
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let threads = (0..cores.min(capacity))
+            .map(|_| {
+                let jobs = job_receiver.clone();
+                let done = done_sender.clone();
+                thread::spawn(move || loop {
+                    let job = jobs.lock().unwrap_or_else(PoisonError::into_inner).recv();
+                    let (index, mut item, context) = match job {
+                        Ok(job) => job,
+                        Err(_) => return,
+                    };
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| item.work(context)));
+                    if done.send((index, item, result.err())).is_err() {
+                        return;
+                    }
+                })
+            })
+            .collect();
+        WorkerPool {
+            jobs: Some(jobs),
+            done: Some(done),
+            slots: (0..capacity).map(|_| None).collect(),
+            threads,
+        }
+    }
+
+    /// Carry out the work of every item across the threads, blocking until all are done, and
+    /// leave them in `items` in their original order.
+    ///
+    /// If any item panicked, the panic is resumed on the calling thread once every item has been
+    /// returned.
+    ///
+    /// **Panics** if there are more items than the capacity of the pool.
+    pub fn run(&mut self, items: &mut Vec<T>, context: C) {
+        let count = items.len();
+        assert!(
+            count <= self.capacity(),
+            "too many items for the worker pool"
+        );
+        let (jobs, done) = match (&self.jobs, &self.done) {
+            (Some(jobs), Some(done)) => (jobs, done),
+            _ => return,
+        };
+        for (index, item) in items.drain(..).enumerate() {
+            jobs.send((index, item, context))
+                .expect("the worker threads stopped");
+        }
+        let mut panicked = None;
+        for _ in 0..count {
+            let (index, item, panic) = done.recv().expect("the worker threads stopped");
+            self.slots[index] = Some(item);
+            panicked = panicked.or(panic);
+        }
+        items.extend(self.slots[..count].iter_mut().filter_map(Option::take));
+        if let Some(payload) = panicked {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+impl<T, C> WorkerPool<T, C> {
+    /// The number of items that may be run at a time.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+impl<T, C> Clone for WorkerPool<T, C> {
+    /// An empty pool, as threads are not shared between copies.
+    fn clone(&self) -> Self {
+        WorkerPool::default()
+    }
+}
+
+// Implemented manually as the derive would require T: Default and C: Default.
+impl<T, C> Default for WorkerPool<T, C> {
+    /// An empty pool without any threads or capacity.
+    fn default() -> Self {
+        WorkerPool {
+            jobs: None,
+            done: None,
+            slots: Vec::new(),
+            threads: Vec::new(),
+        }
+    }
+}
+
+impl<T, C> Drop for WorkerPool<T, C> {
+    fn drop(&mut self) {
+        // Disconnecting the jobs channel stops every thread once it has finished its last job.
+        self.jobs = None;
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,387 @@
+//! Events such as notes and controller changes that are delivered to nodes while rendering.
+//!
+//! Each node in a **Graph** has its own **EventQueue** with a fixed capacity, so that a dense
+//! stream of events can never cause the audio thread to allocate. What happens to events that
+//! arrive while a queue is full is described by its **OverflowPolicy**.
+//!
+//! Events are timestamped with the transport frame at which they take effect. The **Graph**
+//! renders each node up to the frame of its next event before delivering it, so that events are
+//! not quantised to the buffer size.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+pub mod ump;
+
+/// The number of events that may be queued for a node by default.
+pub const DEFAULT_EVENT_CAPACITY: usize = 256;
+
+/// An event delivered to a node via `Node::handle_event`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A note was pressed.
+    NoteOn {
+        /// The MIDI channel, from `0` to `15`, offset by `16` for each group of a Universal MIDI
+        /// Packet.
+        channel: u8,
+        /// The MIDI note number.
+        note: u8,
+        /// The velocity, from `0.0` to `1.0`.
+        velocity: f32,
+    },
+    /// A note was released.
+    NoteOff {
+        /// The MIDI channel, from `0` to `15`, offset by `16` for each group of a Universal MIDI
+        /// Packet.
+        channel: u8,
+        /// The MIDI note number.
+        note: u8,
+        /// The release velocity, from `0.0` to `1.0`.
+        velocity: f32,
+    },
+    /// A continuous controller changed.
+    ControlChange {
+        /// The MIDI channel, from `0` to `15`, offset by `16` for each group of a Universal MIDI
+        /// Packet.
+        channel: u8,
+        /// The controller number.
+        controller: u8,
+        /// The value of the controller, from `0.0` to `1.0`.
+        value: f32,
+    },
+    /// The pitch bend of a channel changed.
+    PitchBend {
+        /// The MIDI channel, from `0` to `15`, offset by `16` for each group of a Universal MIDI
+        /// Packet.
+        channel: u8,
+        /// The bend, from `-1.0` to `1.0`.
+        value: f32,
+    },
+    /// The pressure (aftertouch) of a channel changed.
+    ChannelPressure {
+        /// The MIDI channel, from `0` to `15`, offset by `16` for each group of a Universal MIDI
+        /// Packet.
+        channel: u8,
+        /// The pressure, from `0.0` to `1.0`.
+        value: f32,
+    },
+    /// The pressure (aftertouch) of a single note changed.
+    NotePressure {
+        /// The MIDI channel, from `0` to `15`, offset by `16` for each group of a Universal MIDI
+        /// Packet.
+        channel: u8,
+        /// The MIDI note number.
+        note: u8,
+        /// The pressure, from `0.0` to `1.0`.
+        value: f32,
+    },
+    /// A controller of a single note changed, as sent by MIDI 2.0 per-note controllers.
+    NoteController {
+        /// The MIDI channel, from `0` to `15`, offset by `16` for each group of a Universal MIDI
+        /// Packet.
+        channel: u8,
+        /// The MIDI note number.
+        note: u8,
+        /// Whether the controller is one of the registered per-note controllers defined by the
+        /// MIDI 2.0 specification, rather than an assignable one.
+        registered: bool,
+        /// The controller number.
+        controller: u8,
+        /// The value of the controller, from `0.0` to `1.0`.
+        value: f32,
+    },
+    /// The pitch bend of a single note changed.
+    NotePitchBend {
+        /// The MIDI channel, from `0` to `15`, offset by `16` for each group of a Universal MIDI
+        /// Packet.
+        channel: u8,
+        /// The MIDI note number.
+        note: u8,
+        /// The bend, from `-1.0` to `1.0`.
+        value: f32,
+    },
+    /// A parameter of the node should be set.
+    Param {
+        /// The index of the parameter.
+        param: usize,
+        /// The new value of the parameter.
+        value: f32,
+    },
+}
+
+/// Describes what an **EventQueue** does with an event that arrives while it is full.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// The oldest queued event is dropped to make room for the new event.
+    DropOldest,
+    /// The new event is dropped. This is the default, as it never reorders or loses note-offs
+    /// that are already queued.
+    #[default]
+    DropNewest,
+    /// A controller or parameter change replaces the value of a queued change to the same
+    /// controller or parameter. Any other event, or a change with no queued counterpart, is
+    /// dropped.
+    CoalesceControls,
+}
+
+/// A queue of events with a fixed capacity, ordered by the frame at which they take effect.
+#[derive(Clone, Debug)]
+pub struct EventQueue {
+    /// Each event along with the transport frame at which it takes effect.
+    events: VecDeque<(u64, Event)>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    /// The number of events dropped since the count was last taken.
+    dropped: usize,
+}
+
+impl EventQueue {
+    /// An empty queue with room for `capacity` events.
+    ///
+    /// The queue's storage is allocated up front, so pushing never allocates.
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        EventQueue {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            policy,
+            dropped: 0,
+        }
+    }
+
+    /// The maximum number of events that may be queued.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// What happens to events that arrive while the queue is full.
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// The number of queued events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether no events are queued.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Whether the queue's storage has been allocated.
+    pub fn is_allocated(&self) -> bool {
+        self.capacity == 0 || self.events.capacity() >= self.capacity
+    }
+
+    /// Queue the given event to take effect as soon as possible, applying the overflow policy if
+    /// the queue is full.
+    ///
+    /// Returns `false` if an event was dropped.
+    pub fn push(&mut self, event: Event) -> bool {
+        self.push_at(0, event)
+    }
+
+    /// Queue the given event to take effect at the given transport frame, applying the overflow
+    /// policy if the queue is full.
+    ///
+    /// Events for the same frame are delivered in the order in which they were queued.
+    ///
+    /// Returns `false` if an event was dropped.
+    pub fn push_at(&mut self, frame: u64, event: Event) -> bool {
+        if !self.is_allocated() {
+            self.events.reserve_exact(self.capacity);
+        }
+        if self.events.len() < self.capacity {
+            self.insert(frame, event);
+            return true;
+        }
+        let policy = self.policy;
+        match policy {
+            OverflowPolicy::DropOldest if self.capacity > 0 => {
+                self.events.pop_front();
+                self.insert(frame, event);
+            }
+            OverflowPolicy::CoalesceControls if self.coalesce(event) => return true,
+            _ => (),
+        }
+        self.dropped += 1;
+        false
+    }
+
+    /// Remove and return the earliest queued event.
+    pub fn pop(&mut self) -> Option<Event> {
+        self.events.pop_front().map(|(_, event)| event)
+    }
+
+    /// Remove and return the earliest queued event if it takes effect before the given frame.
+    pub fn pop_before(&mut self, frame: u64) -> Option<Event> {
+        match self.events.front() {
+            Some(&(event_frame, _)) if event_frame < frame => self.pop(),
+            _ => None,
+        }
+    }
+
+    /// The frame at which the earliest queued event takes effect.
+    pub fn next_frame(&self) -> Option<u64> {
+        self.events.front().map(|&(frame, _)| frame)
+    }
+
+    /// Remove all queued events.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Remove redundant controller and parameter changes that take effect before the given frame,
+    /// keeping only the first and last change to each controller or parameter.
+    ///
+    /// This thins the dense streams of changes sent by high-resolution controllers to at most two
+    /// changes per block, while preserving where each movement started and ended.
+    ///
+    /// Returns the number of changes removed.
+    pub fn coalesce_before(&mut self, frame: u64) -> usize {
+        self.coalesce_within(0..frame)
+    }
+
+    /// Remove redundant controller and parameter changes that take effect within the given range
+    /// of frames, keeping only the first and last change to each controller or parameter within
+    /// the range.
+    ///
+    /// Returns the number of changes removed.
+    pub fn coalesce_within(&mut self, frames: Range<u64>) -> usize {
+        let start = self
+            .events
+            .partition_point(|&(queued, _)| queued < frames.start);
+        let mut end = self
+            .events
+            .partition_point(|&(queued, _)| queued < frames.end);
+        let mut removed = 0;
+        let mut i = start;
+        while i < end {
+            let event = self.events[i].1;
+            let is_target = |&(_, queued): &(u64, Event)| same_target(&queued, &event);
+            let redundant = self.events.range(start..i).any(is_target)
+                && self.events.range(i + 1..end).any(is_target);
+            if redundant {
+                self.events.remove(i);
+                end -= 1;
+                removed += 1;
+            } else {
+                i += 1;
+            }
+        }
+        removed
+    }
+
+    /// The number of events dropped since this was last called, resetting the count.
+    pub fn take_dropped(&mut self) -> usize {
+        std::mem::replace(&mut self.dropped, 0)
+    }
+
+    /// Insert the event after all queued events that take effect at or before `frame`.
+    fn insert(&mut self, frame: u64, event: Event) {
+        let index = self.events.partition_point(|&(queued, _)| queued <= frame);
+        self.events.insert(index, (frame, event));
+    }
+
+    /// Replace the value of a queued change to the same target as `event`.
+    ///
+    /// Returns `false` if `event` is not a change or no change to its target is queued.
+    fn coalesce(&mut self, event: Event) -> bool {
+        let queued = self
+            .events
+            .iter_mut()
+            .rev()
+            .find(|(_, queued)| same_target(queued, &event));
+        match queued {
+            Some((_, queued)) => {
+                *queued = event;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Default for EventQueue {
+    /// An empty queue with room for `DEFAULT_EVENT_CAPACITY` events and the default overflow
+    /// policy.
+    ///
+    /// The queue's storage is not allocated until the first event is pushed.
+    fn default() -> Self {
+        EventQueue {
+            events: VecDeque::new(),
+            capacity: DEFAULT_EVENT_CAPACITY,
+            policy: OverflowPolicy::default(),
+            dropped: 0,
+        }
+    }
+}
+
+/// Whether `a` and `b` are changes to the same controller or parameter.
+fn same_target(a: &Event, b: &Event) -> bool {
+    match (*a, *b) {
+        (
+            Event::ControlChange {
+                channel: a_channel,
+                controller: a_controller,
+                ..
+            },
+            Event::ControlChange {
+                channel: b_channel,
+                controller: b_controller,
+                ..
+            },
+        ) => a_channel == b_channel && a_controller == b_controller,
+        (Event::PitchBend { channel: a, .. }, Event::PitchBend { channel: b, .. }) => a == b,
+        (Event::ChannelPressure { channel: a, .. }, Event::ChannelPressure { channel: b, .. }) => {
+            a == b
+        }
+        (
+            Event::NotePressure {
+                channel: a_channel,
+                note: a_note,
+                ..
+            },
+            Event::NotePressure {
+                channel: b_channel,
+                note: b_note,
+                ..
+            },
+        ) => a_channel == b_channel && a_note == b_note,
+        (
+            Event::NoteController {
+                channel: a_channel,
+                note: a_note,
+                registered: a_registered,
+                controller: a_controller,
+                ..
+            },
+            Event::NoteController {
+                channel: b_channel,
+                note: b_note,
+                registered: b_registered,
+                controller: b_controller,
+                ..
+            },
+        ) => {
+            a_channel == b_channel
+                && a_note == b_note
+                && a_registered == b_registered
+                && a_controller == b_controller
+        }
+        (
+            Event::NotePitchBend {
+                channel: a_channel,
+                note: a_note,
+                ..
+            },
+            Event::NotePitchBend {
+                channel: b_channel,
+                note: b_note,
+                ..
+            },
+        ) => a_channel == b_channel && a_note == b_note,
+        (Event::Param { param: a, .. }, Event::Param { param: b, .. }) => a == b,
+        _ => false,
+    }
+}
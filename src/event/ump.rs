@@ -0,0 +1,190 @@
+//! Decoding of Universal MIDI Packets (UMP), as used by MIDI 2.0 devices and drivers.
+//!
+//! Both MIDI 1.0 and MIDI 2.0 channel voice messages are decoded into **Event**s. MIDI 2.0
+//! messages carry 16-bit velocities and 32-bit controller values, which are normalised to the
+//! range of the corresponding **Event** field. Per-note controllers, per-note pitch bend and
+//! polyphonic pressure are decoded into per-note events, so that MPE-style expression can be
+//! handled alongside MIDI 1.0 controllers. The group of each packet is folded into the channel of
+//! the event as `group * 16 + channel`.
+//!
+//! All other message types (system messages, SysEx, utility messages and so on) are skipped.
+
+use super::Event;
+
+/// The message type of MIDI 1.0 channel voice messages.
+const MIDI1_CHANNEL_VOICE: u32 = 0x2;
+/// The message type of MIDI 2.0 channel voice messages.
+const MIDI2_CHANNEL_VOICE: u32 = 0x4;
+
+/// The number of 32-bit words in a packet, given the first word of the packet.
+pub fn packet_len(word: u32) -> usize {
+    match word >> 28 {
+        0x0 | 0x1 | 0x2 | 0x6 | 0x7 => 1,
+        0x3 | 0x4 | 0x8 | 0x9 | 0xA => 2,
+        0xB | 0xC => 3,
+        _ => 4,
+    }
+}
+
+/// Decode a single packet, returning `None` if it is not a supported channel voice message or
+/// if it is truncated.
+pub fn decode_packet(packet: &[u32]) -> Option<Event> {
+    let word = *packet.first()?;
+    let group = ((word >> 24) & 0xF) as u8;
+    let status = (word >> 20) & 0xF;
+    let channel = group * 16 + ((word >> 16) & 0xF) as u8;
+    let note = ((word >> 8) & 0x7F) as u8;
+    let index = (word & 0xFF) as u8;
+    match word >> 28 {
+        MIDI1_CHANNEL_VOICE => {
+            let data = (word & 0x7F) as u8;
+            decode_midi1(status, channel, note, data)
+        }
+        MIDI2_CHANNEL_VOICE => {
+            let data = *packet.get(1)?;
+            decode_midi2(status, channel, note, index, data)
+        }
+        _ => None,
+    }
+}
+
+/// Decode all supported messages in a stream of packets, skipping any others.
+///
+/// A truncated packet at the end of the stream is ignored.
+pub fn decode(words: &[u32]) -> Decode<'_> {
+    Decode { words }
+}
+
+/// An iterator yielding the events decoded from a stream of Universal MIDI Packets.
+///
+/// See [`decode`](./fn.decode.html).
+#[derive(Clone, Debug)]
+pub struct Decode<'a> {
+    words: &'a [u32],
+}
+
+impl<'a> Iterator for Decode<'a> {
+    type Item = Event;
+    fn next(&mut self) -> Option<Event> {
+        while let Some(&word) = self.words.first() {
+            let len = packet_len(word).min(self.words.len());
+            let (packet, rest) = self.words.split_at(len);
+            self.words = rest;
+            if let Some(event) = decode_packet(packet) {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
+
+/// Decode a MIDI 1.0 channel voice message with the given status nibble and 7-bit data bytes.
+fn decode_midi1(status: u32, channel: u8, data1: u8, data2: u8) -> Option<Event> {
+    let value = data2 as f32 / 127.0;
+    let event = match status {
+        0x8 => Event::NoteOff {
+            channel,
+            note: data1,
+            velocity: value,
+        },
+        // A note-on with a velocity of zero is a note-off in MIDI 1.0.
+        0x9 if data2 == 0 => Event::NoteOff {
+            channel,
+            note: data1,
+            velocity: 0.0,
+        },
+        0x9 => Event::NoteOn {
+            channel,
+            note: data1,
+            velocity: value,
+        },
+        0xA => Event::NotePressure {
+            channel,
+            note: data1,
+            value,
+        },
+        0xB => Event::ControlChange {
+            channel,
+            controller: data1,
+            value,
+        },
+        0xD => Event::ChannelPressure {
+            channel,
+            value: data1 as f32 / 127.0,
+        },
+        0xE => {
+            let bend = (data1 as i32 | (data2 as i32) << 7) - 0x2000;
+            Event::PitchBend {
+                channel,
+                value: (bend as f32 / 0x2000 as f32).max(-1.0),
+            }
+        }
+        _ => return None,
+    };
+    Some(event)
+}
+
+/// Decode a MIDI 2.0 channel voice message with the given status nibble, note (or controller)
+/// number, index (or attribute type) byte and 32-bit data word.
+fn decode_midi2(status: u32, channel: u8, note: u8, index: u8, data: u32) -> Option<Event> {
+    let event = match status {
+        0x0 | 0x1 => Event::NoteController {
+            channel,
+            note,
+            registered: status == 0x0,
+            controller: index,
+            value: unipolar(data),
+        },
+        0x6 => Event::NotePitchBend {
+            channel,
+            note,
+            value: bipolar(data),
+        },
+        0x8 => Event::NoteOff {
+            channel,
+            note,
+            velocity: velocity(data),
+        },
+        0x9 => Event::NoteOn {
+            channel,
+            note,
+            velocity: velocity(data),
+        },
+        0xA => Event::NotePressure {
+            channel,
+            note,
+            value: unipolar(data),
+        },
+        0xB => Event::ControlChange {
+            channel,
+            controller: note,
+            value: unipolar(data),
+        },
+        0xD => Event::ChannelPressure {
+            channel,
+            value: unipolar(data),
+        },
+        0xE => Event::PitchBend {
+            channel,
+            value: bipolar(data),
+        },
+        _ => return None,
+    };
+    Some(event)
+}
+
+/// The 16-bit velocity in the upper half of the data word of a MIDI 2.0 note message.
+fn velocity(data: u32) -> f32 {
+    (data >> 16) as f32 / u16::MAX as f32
+}
+
+/// A 32-bit controller value normalised to the range `0.0` to `1.0`.
+fn unipolar(data: u32) -> f32 {
+    (data as f64 / u32::MAX as f64) as f32
+}
+
+/// A 32-bit value centred on `0x80000000` normalised to the range `-1.0` to `1.0`.
+fn bipolar(data: u32) -> f32 {
+    let centre = 0x8000_0000u32 as f64;
+    ((data as f64 - centre) / centre).max(-1.0) as f32
+}
@@ -7,31 +7,126 @@
 //!
 //! The `Graph` type requires that its nodes implement the [`Node`](../node/trait.Node.html) trait.
 
-use crate::node::Node;
+use self::control::TapState;
+use self::latency::Compensation;
+use self::messages::MessageBus;
+use self::pool::BufferPool;
+use self::ramp::FadingConnection;
+use crate::bus::BusLayout;
+use crate::event::EventQueue;
+use crate::node::{Node, ParamChange};
+use daggy::petgraph::graph::IndexType;
 use daggy::{self, Walker};
 use dasp::{self, Frame, Sample};
+use std::any::Any;
+use std::fmt;
+use std::ops::Range;
+use std::time::Duration;
+
+pub use self::advisor::{BufferAdvice, BufferAdvisor};
+pub use self::aggregate::DeviceOutput;
+pub use self::analysis_bus::AnalysisRoute;
+pub use self::compose::IndexMap;
+pub use self::control::{ControlSource, ControlTap, ControlValue};
+pub use self::determinism::BlockSizeReport;
+pub use self::device::DeviceConfig;
+pub use self::external::{External, ExternalKind};
+pub use self::feedback::FeedbackConnection;
+pub use self::layout::NodeLayout;
+pub use self::lineage::{Ancestors, Descendants};
+pub use self::node_id::NodeId;
+pub use self::notification::{Notification, NotificationReceiver};
+pub use self::panic::PanicPolicy;
+pub use self::params::{ParamHandle, ParamSubscription};
+pub use self::preset::Preset;
+pub use self::smoothing::{ParamSmoothing, SMOOTHING_INTERVAL};
+pub use self::swap::{GraphSwap, SwapHandle};
+pub use self::tempo::{Tempo, TempoMap, TempoPoint, TempoRamp};
+pub use self::transport::CountIn;
+pub use self::typed::{NodeVariant, TypedNodeIndex};
+pub use self::validate::{ValidationReport, Violation};
+pub use self::watchdog::Watchdog;
+
+mod advisor;
+mod aggregate;
+mod analysis_bus;
+mod bypass;
+mod capacity;
+mod chain;
+mod channels;
+mod compose;
+mod control;
+mod determinism;
+mod device;
+mod dot;
+mod dynamic;
+mod events;
+mod external;
+mod feedback;
+mod idle;
+mod latency;
+mod layout;
+mod lineage;
+mod map;
+mod messages;
+mod mix;
+mod monitor;
+mod node_id;
+mod notification;
+mod panic;
+mod params;
+mod pool;
+mod ports;
+mod preset;
+#[cfg(feature = "sampler")]
+mod punch;
+mod ramp;
+mod replace;
+#[cfg(feature = "serde")]
+mod serialization;
+pub(crate) mod silence;
+mod smoothing;
+mod solo;
+mod swap;
+mod tail;
+mod tempo;
+mod transport;
+mod typed;
+mod validate;
+mod watchdog;
 
 /// An alias for our Graph's Node Index.
-pub type NodeIndex = daggy::NodeIndex<usize>;
+pub type NodeIndex<Ix = usize> = daggy::NodeIndex<Ix>;
 /// An alias for our Graph's Edge Index.
-pub type EdgeIndex = daggy::EdgeIndex<usize>;
+pub type EdgeIndex<Ix = usize> = daggy::EdgeIndex<Ix>;
 
 /// An alias for the iterator yielding mutable access to all node weights.
-pub type NodesMut<'a, N> = daggy::NodeWeightsMut<'a, N, usize>;
+pub type NodesMut<'a, N, Ix = usize> = daggy::NodeWeightsMut<'a, N, Ix>;
 
 /// Read only access to a **Graph**'s internal node array.
-pub type RawNodes<'a, N> = daggy::RawNodes<'a, N, usize>;
+pub type RawNodes<'a, N, Ix = usize> = daggy::RawNodes<'a, N, Ix>;
 /// Read only access to a **Graph**'s internal edge array.
-pub type RawEdges<'a, F> = daggy::RawEdges<'a, Connection<F>, usize>;
+pub type RawEdges<'a, F, Ix = usize> = daggy::RawEdges<'a, Connection<F>, Ix>;
 
 /// An iterator yielding indices to recently added connections.
-pub type EdgeIndices = daggy::EdgeIndices<usize>;
+pub type EdgeIndices<Ix = usize> = daggy::EdgeIndices<Ix>;
+
+/// The indices returned by `Graph::insert_between`: the connection into the inserted node, the
+/// inserted node and the connection out of it.
+pub type Insertion<Ix = usize> = (EdgeIndex<Ix>, NodeIndex<Ix>, EdgeIndex<Ix>);
 
 /// An alias for the **Dag** used within our **Graph**.
-pub type Dag<F, N> = daggy::Dag<N, Connection<F>, usize>;
+pub type Dag<F, N, Ix = usize> = daggy::Dag<N, Connection<F>, Ix>;
 
 /// An alias for the **PetGraph** used by our **Graph**'s internal **Dag**.
-pub type PetGraph<F, N> = daggy::PetGraph<N, Connection<F>, usize>;
+pub type PetGraph<F, N, Ix = usize> = daggy::PetGraph<N, Connection<F>, Ix>;
+
+/// A **Graph** using `u32` indices, halving the size of index storage on 64-bit targets.
+pub type Graph32<F, N> = Graph<F, N, u32>;
+/// A **Graph** using `u16` indices, for small graphs on memory constrained targets.
+///
+/// Note that a `Graph16` may contain at most 65,535 nodes and connections.
+pub type Graph16<F, N> = Graph<F, N, u16>;
 
 /// A directed, acyclic DSP graph.
 ///
@@ -74,15 +169,164 @@ pub type PetGraph<F, N> = daggy::PetGraph<N, Connection<F>, usize>;
 /// to shift its index to take its place.
 ///
 /// **Graph** also offers methods for accessing its underlying **Dag** or **PetGraph**.
+///
+/// The `Ix` parameter is the type used to store node and edge indices and is `usize` by default.
+/// The [`Graph32`](./type.Graph32.html) and [`Graph16`](./type.Graph16.html) aliases may be used
+/// to shrink index storage for small graphs.
+#[derive(Clone)]
+pub struct Graph<F, N, Ix = usize>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    dag: Dag<F, N, Ix>,
+    /// Replaced nodes that are being crossfaded out.
+    replaced: Vec<replace::Replaced<N, Ix>>,
+    /// Replaced nodes that have been crossfaded out, waiting to be collected by the host.
+    retired_nodes: Vec<N>,
+    /// Everything else maintained by the **Graph**.
+    state: GraphState<F, Ix>,
+}
+
+/// The state of a **Graph** that does not depend on the type of its nodes, carried over as a
+/// whole when the nodes are mapped to another type.
 #[derive(Clone, Debug)]
-pub struct Graph<F, N> {
-    dag: Dag<F, N>,
+struct GraphState<F, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
     /// The order in which audio will be requested from each node.
-    visit_order: Vec<NodeIndex>,
+    visit_order: Vec<NodeIndex<Ix>>,
     /// The node from which audio will be requested upon a call to `Node::audio_requested`.
-    maybe_master: Option<NodeIndex>,
+    maybe_master: Option<NodeIndex<Ix>>,
+    /// The subset of the visit order that must be rendered to produce the output of
+    /// `render_order_node`.
+    render_order: Vec<NodeIndex<Ix>>,
+    /// The node for which the `render_order` was prepared, or `None` if it must be re-prepared.
+    render_order_node: Option<NodeIndex<Ix>>,
     /// A buffer to re-use when mixing the dry and wet signals when audio is requested.
     dry_buffer: Vec<F>,
+    /// A buffer to re-use when rendering nodes that request a planar buffer.
+    planar_buffer: Vec<F::Sample>,
+    /// The latency of the signal at the output of each node, indexed by node index.
+    path_latencies: Vec<usize>,
+    /// State maintained by the **Graph** for each node, indexed by node index.
+    node_meta: Vec<NodeMeta>,
+    /// How to handle nodes that panic while rendering.
+    panic_policy: PanicPolicy,
+    /// Sends notifications to the host.
+    notifier: notification::Notifier<Ix>,
+    /// A buffer to re-use when collecting parameter changes from nodes.
+    param_changes: Vec<ParamChange>,
+    /// The transport position in frames at which the next request for audio begins.
+    position: u64,
+    /// Whether connections keep the buffer rendered during the previous request.
+    double_buffered: bool,
+    /// Connections that feed a node's output back with a delay of one buffer.
+    feedback: Vec<FeedbackConnection<F, Ix>>,
+    /// Spare buffers from which connection buffers are drawn.
+    pool: BufferPool<F>,
+    /// Whether to panic if the **Graph** allocates while rendering.
+    assert_no_alloc: bool,
+    /// Whether to check the **Graph**'s invariants after each mutation.
+    validate_mutations: bool,
+    /// The maximum node latency allowed in the monitoring path, if monitoring is enabled.
+    monitor_max_latency: Option<usize>,
+    /// The rendered output of each node bypassed by low-latency monitoring.
+    full_quality_outputs: Vec<(NodeIndex<Ix>, Vec<F>)>,
+    /// The number of frames over which nodes are faded in or out of bypass.
+    bypass_fade_frames: usize,
+    /// Whether bypassed nodes keep delaying their input by their latency.
+    bypass_preserves_latency: bool,
+    /// The delay lines of bypassed nodes whose latency is preserved.
+    bypass_delays: Vec<(NodeIndex<Ix>, Compensation<F>)>,
+    /// Taps publishing decimated values of connections and parameters.
+    control_taps: Vec<TapState<Ix>>,
+    /// Whether any node is soloed.
+    any_soloed: bool,
+    /// The number of frames over which added and removed connections are faded in and out.
+    connection_ramp_frames: usize,
+    /// Removed connections that are still being faded out.
+    fading: Vec<FadingConnection<F, Ix>>,
+    /// The number of channels of each frame that carry audio.
+    channels: usize,
+    /// Frames into which audio is rendered for buffers with a runtime number of channels.
+    dyn_buffer: Vec<F>,
+    /// A buffer for each input of a node that processes separately from its inputs.
+    input_buffers: Vec<Vec<F>>,
+    /// The number of input buffers holding the inputs of the node being rendered.
+    num_ports: usize,
+    /// Whether redundant controller and parameter changes are coalesced before being delivered.
+    coalesce_events: bool,
+    /// A queue for each type of message sent to nodes.
+    message_buses: Vec<Box<dyn MessageBus<Ix>>>,
+    /// The buses exposed by the **Graph** when used as a processor.
+    bus_layout: BusLayout,
+    /// The named boundary nodes through which audio enters and leaves the **Graph**.
+    externals: Vec<External<Ix>>,
+    /// The audio passed through each external, in the same order as `externals`.
+    external_buffers: Vec<external::ExternalBuffer<F>>,
+    /// The output of the requested node, kept while external outputs after it are rendered.
+    output_stash: Vec<F>,
+    /// The buffer into which replaced nodes are rendered.
+    replace_buffer: Vec<F>,
+    /// The limits on the time that each node may take to render, if enforced.
+    watchdog: Option<Watchdog>,
+    /// The limits used to recommend a block size, if enabled.
+    buffer_advisor: Option<BufferAdvisor>,
+    /// The render timings and xruns collected for the buffer advisor.
+    advisor_stats: advisor::AdvisorStats,
+    /// Routes from the control values published by nodes to the parameters of other nodes.
+    analysis_routes: Vec<AnalysisRoute<Ix>>,
+    /// The latest control values published by nodes.
+    analysis_values: analysis_bus::AnalysisValues<Ix>,
+    /// The parameters bound to handles held by other threads.
+    param_handles: Vec<params::ParamBinding<Ix>>,
+    /// The id to assign to the next node added.
+    next_node_id: NodeId,
+    /// The current device and the fade around switching devices.
+    device: device::DeviceState,
+    /// The tempo map of the transport, if any.
+    tempo: tempo::TempoState,
+    /// The time in milliseconds over which parameter changes are smoothed by default.
+    default_smoothing_ms: f32,
+    /// Whether the **Graph** has become idle and how long it has been quiet.
+    idle: idle::IdleState,
+}
+
+/// State maintained by the **Graph** alongside each node.
+#[derive(Clone, Debug, Default)]
+struct NodeMeta {
+    /// The raw value of the **NodeId** assigned to the node when it was added.
+    id: u64,
+    /// Whether the node has panicked while rendering and is now bypassed.
+    panicked: bool,
+    /// The range of transport frames outside of which the node is skipped.
+    active_range: Option<Range<u64>>,
+    /// Whether the node output silence during the last request for audio.
+    silent: bool,
+    /// Whether the node has been bypassed via `Graph::set_bypassed`.
+    bypassed: bool,
+    /// The progress of the bypass fade, from `0.0` (processed) to `1.0` (bypassed).
+    bypass_mix: f32,
+    /// Whether the node's output is replaced with silence.
+    muted: bool,
+    /// Whether the node is soloed.
+    soloed: bool,
+    /// Whether the node is soloed or is an ancestor or descendant of a soloed node.
+    in_solo_path: bool,
+    /// The number of frames of the node's tail left to render since its inputs fell silent.
+    tail_remaining: usize,
+    /// Events waiting to be delivered to the node.
+    events: EventQueue,
+    /// The smoothing of the node's parameters.
+    smoothing: smoothing::Smoother,
+    /// The time taken by the node to render during the last request while the watchdog was
+    /// enabled.
+    render_time: Duration,
+    /// The number of consecutive requests in which the node exceeded the watchdog's budget.
+    overruns: usize,
 }
 
 /// Describes a connection between two Nodes within the Graph: *input -> connection -> output*.
@@ -96,20 +340,51 @@ pub struct Connection<F> {
     /// After `Graph::audio_requested_from` is called, this buffer will contain the audio rendered
     /// by the **Connection**'s input node.
     pub buffer: Vec<F>,
+    /// The buffer rendered during the previous request, if the **Graph** is double-buffered.
+    previous: Vec<F>,
+    /// Whether the buffer contains only equilibrium frames.
+    silent: bool,
+    /// Whether the buffer is summed into the output node's input.
+    enabled: bool,
+    /// The gain of the ramp fading in a newly added connection, which is `1.0` once complete.
+    gain: f32,
+    /// Delays the buffer to align it with the slowest path into the output node.
+    compensation: Compensation<F>,
 }
 
 /// The error returned when adding an edge that would create a cycle.
 #[derive(Copy, Clone, Debug)]
 pub struct WouldCycle;
 
+/// The error returned when a **Graph** method is given an index for which there is no node or
+/// connection, or by the `try_` methods that add connections.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RequestError<Ix = usize>
+where
+    Ix: IndexType,
+{
+    /// There is no node for the given index.
+    NoNode(NodeIndex<Ix>),
+    /// There is no connection for the given index.
+    NoConnection(EdgeIndex<Ix>),
+    /// Adding the connection would have caused the graph to cycle.
+    WouldCycle,
+}
+
 /// A walker object for walking over nodes that are inputs to some node.
-pub struct Inputs<F, N> {
-    parents: daggy::Parents<N, Connection<F>, usize>,
+pub struct Inputs<F, N, Ix = usize>
+where
+    Ix: IndexType,
+{
+    parents: daggy::Parents<N, Connection<F>, Ix>,
 }
 
 /// A walker object for walking over nodes that are outputs to some node.
-pub struct Outputs<F, N> {
-    children: daggy::Children<N, Connection<F>, usize>,
+pub struct Outputs<F, N, Ix = usize>
+where
+    Ix: IndexType,
+{
+    children: daggy::Children<N, Connection<F>, Ix>,
 }
 
 /// A walker type for walking over a **Graph**'s nodes in the order in which they will visited when
@@ -134,14 +409,10 @@ where
     /// [`with_capacity`](./struct.Graph.html#method.with_capacity) is recommended if you have a
     /// rough idea of the number of nodes, connections and frames per buffer upon the **Graph**'s
     /// instantiation.
+    ///
+    /// Use `Default::default` to construct a **Graph** with some other index type.
     pub fn new() -> Self {
-        let dag = daggy::Dag::new();
-        Graph {
-            dag: dag,
-            visit_order: Vec::new(),
-            dry_buffer: Vec::new(),
-            maybe_master: None,
-        }
+        Self::default()
     }
 
     /// Constructor for a new dsp Graph with some minimum capacity.
@@ -149,34 +420,59 @@ where
     /// - **nodes** is the capacity for the underlying **Dag**'s node `Vec`.
     /// - **connections** is the capacity for the underlying **Dag**'s edge `Vec`.
     /// - **frames_per_buffer** is the capacity for the **Graph**'s `dry_buffer`, which is used
-    /// for mixing the dry and wet signals when `Node::audio_requested` is called.
+    ///   for mixing the dry and wet signals when `Node::audio_requested` is called.
+    ///
+    /// Use [`with_capacity_indexed`](./struct.Graph.html#method.with_capacity_indexed) to
+    /// construct a **Graph** with some other index type.
     pub fn with_capacity(nodes: usize, connections: usize, frames_per_buffer: usize) -> Self {
-        Graph {
+        Self::with_capacity_indexed(nodes, connections, frames_per_buffer)
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// The same as [`with_capacity`](./struct.Graph.html#method.with_capacity) but for a
+    /// **Graph** with any index type, e.g. `Graph32::with_capacity_indexed(64, 64, 512)`.
+    pub fn with_capacity_indexed(
+        nodes: usize,
+        connections: usize,
+        frames_per_buffer: usize,
+    ) -> Self {
+        let mut graph = Graph {
             dag: daggy::Dag::with_capacity(nodes, connections),
-            visit_order: Vec::with_capacity(nodes),
-            dry_buffer: Vec::with_capacity(frames_per_buffer),
-            maybe_master: None,
-        }
+            ..Self::default()
+        };
+        let state = &mut graph.state;
+        state.visit_order.reserve(nodes);
+        state.path_latencies.reserve(nodes);
+        state.node_meta.reserve(nodes);
+        state.dry_buffer.reserve(frames_per_buffer);
+        state.planar_buffer.reserve(frames_per_buffer * F::CHANNELS);
+        graph
     }
 
     /// A reference to the underlying **Dag**.
-    pub fn dag(&self) -> &Dag<F, N> {
+    pub fn dag(&self) -> &Dag<F, N, Ix> {
         &self.dag
     }
 
     /// Takes ownership of the **Graph** and returns the underlying **Dag**.
-    pub fn into_dag(self) -> Dag<F, N> {
+    pub fn into_dag(self) -> Dag<F, N, Ix> {
         let Graph { dag, .. } = self;
         dag
     }
 
     /// A reference to the internal **Dag**'s underlying **PetGraph**.
-    pub fn pet_graph(&self) -> &PetGraph<F, N> {
+    pub fn pet_graph(&self) -> &PetGraph<F, N, Ix> {
         self.dag.graph()
     }
 
     /// Takes ownership of the **Graph** and returns the internal **Dag**'s underlying **PetGraph**.
-    pub fn into_pet_graph(self) -> PetGraph<F, N> {
+    pub fn into_pet_graph(self) -> PetGraph<F, N, Ix> {
         self.into_dag().into_graph()
     }
 
@@ -194,8 +490,8 @@ where
     ///
     /// **Graph**'s **Node** implementation will request audio from the node at `maybe_master`
     /// when the `Node::audio_requested` method is called.
-    pub fn master_index(&self) -> Option<NodeIndex> {
-        self.maybe_master
+    pub fn master_index(&self) -> Option<NodeIndex<Ix>> {
+        self.state.maybe_master
     }
 
     /// Set the master node for the **Graph**.
@@ -204,7 +500,7 @@ where
     ///
     /// **Graph**'s **Node** implementation will request audio from the node at `maybe_master`
     /// when the `Node::audio_requested` method is called.
-    pub fn set_master(&mut self, maybe_index: Option<NodeIndex>) {
+    pub fn set_master(&mut self, maybe_index: Option<NodeIndex<Ix>>) {
         let maybe_index = maybe_index.and_then(|index| {
             if self.dag.node_weight(index).is_some() {
                 Some(index)
@@ -212,57 +508,100 @@ where
                 None
             }
         });
-        self.maybe_master = maybe_index;
+        self.state.maybe_master = maybe_index;
         self.prepare_visit_order();
     }
 
     /// Add a node to the dsp graph.
     ///
     /// This computes in **O(1)** time.
-    pub fn add_node(&mut self, node: N) -> NodeIndex {
+    pub fn add_node(&mut self, node: N) -> NodeIndex<Ix> {
+        let always_render = node.always_render();
         let idx = self.dag.add_node(node);
+        self.push_node_meta(NodeMeta::default());
+        // A node without connections may be visited at any point, so there's no need to re-sort.
+        self.state.visit_order.push(idx);
+        // Nodes that are always rendered must join the render order regardless.
+        if always_render {
+            self.state.render_order_node = None;
+        }
+        self.debug_validate();
         idx
     }
 
     /// A reference to the node at the given index (or `None` if it doesn't exist).
-    pub fn node(&self, node: NodeIndex) -> Option<&N> {
+    pub fn node(&self, node: NodeIndex<Ix>) -> Option<&N> {
         self.dag.node_weight(node)
     }
 
     /// A mutable reference to the node at the given index (or `None` if it doesn't exist).
-    pub fn node_mut(&mut self, node: NodeIndex) -> Option<&mut N> {
+    pub fn node_mut(&mut self, node: NodeIndex<Ix>) -> Option<&mut N> {
         self.dag.node_weight_mut(node)
     }
 
+    /// Replace the node at the given index with `new_node`, returning the old node.
+    ///
+    /// All inbound and outbound connections (along with their buffers) are left intact, so the
+    /// visit order does not need to be re-prepared. The latency compensation of the connections is
+    /// re-prepared for the new node's latency, as is the render order if only one of the nodes is
+    /// always rendered. If the old node had panicked, the new node is no longer bypassed. The new
+    /// node takes over the old node's **NodeId**.
+    ///
+    /// Returns `None` if there is no node for the given index, in which case `new_node` is dropped.
+    pub fn replace_node(&mut self, idx: NodeIndex<Ix>, new_node: N) -> Option<N> {
+        let node = self.dag.node_weight_mut(idx)?;
+        let old_node = std::mem::replace(node, new_node);
+        self.state.node_meta[idx.index()].panicked = false;
+        if old_node.always_render() != self.dag[idx].always_render() {
+            self.state.render_order_node = None;
+        }
+        self.prepare_compensation();
+        self.debug_validate();
+        Some(old_node)
+    }
+
     /// Read only access to the internal node array.
-    pub fn raw_nodes(&self) -> RawNodes<N> {
+    pub fn raw_nodes(&self) -> RawNodes<N, Ix> {
         self.dag.raw_nodes()
     }
 
     /// An iterator yielding mutable access to all nodes.
     ///
     /// The order in which nodes are yielded matches the order of their indices.
-    pub fn nodes_mut(&mut self) -> NodesMut<N> {
+    pub fn nodes_mut(&mut self) -> NodesMut<N, Ix> {
         self.dag.node_weights_mut()
     }
 
     /// A reference to the connection at the given index (or `None` if it doesn't exist).
-    pub fn connection(&self, edge: EdgeIndex) -> Option<&Connection<F>> {
+    pub fn connection(&self, edge: EdgeIndex<Ix>) -> Option<&Connection<F>> {
         self.dag.edge_weight(edge)
     }
 
     /// Read only access to the internal edge array.
-    pub fn raw_edges(&self) -> RawEdges<F> {
+    pub fn raw_edges(&self) -> RawEdges<F, Ix> {
         self.dag.raw_edges()
     }
 
     /// Index the **Graph** by two `NodeIndex`s at once.
     ///
     /// **Panics** if the indices are equal or if they are out of bounds.
-    pub fn index_twice_mut(&mut self, a: NodeIndex, b: NodeIndex) -> (&mut N, &mut N) {
+    pub fn index_twice_mut(&mut self, a: NodeIndex<Ix>, b: NodeIndex<Ix>) -> (&mut N, &mut N) {
         self.dag.index_twice_mut(a, b)
     }
 
+    /// The same as [`index_twice_mut`](./struct.Graph.html#method.index_twice_mut) but returns
+    /// `None` rather than panicking if the indices are equal or if there is no node for either.
+    pub fn try_index_twice_mut(
+        &mut self,
+        a: NodeIndex<Ix>,
+        b: NodeIndex<Ix>,
+    ) -> Option<(&mut N, &mut N)> {
+        if a == b || self.dag.node_weight(a).is_none() || self.dag.node_weight(b).is_none() {
+            return None;
+        }
+        Some(self.dag.index_twice_mut(a, b))
+    }
+
     /// Remove a node from the dsp graph.
     ///
     /// Resets the master to None if the index matches the current master index.
@@ -270,13 +609,79 @@ where
     /// **Note:** This method may shift (and in turn invalidate) previously returned node indices!
     ///
     /// **Graph** will re-prepare its visit order if some node was removed.
-    pub fn remove_node(&mut self, idx: NodeIndex) -> Option<N> {
-        if self.maybe_master == Some(idx) {
-            self.maybe_master = None;
+    pub fn remove_node(&mut self, idx: NodeIndex<Ix>) -> Option<N> {
+        let node = self.take_node(idx)?;
+        self.prepare_visit_order();
+        Some(node)
+    }
+
+    /// Remove a node from the dsp graph, connecting each of its inputs directly to each of its
+    /// outputs so that removing a node from the middle of a chain does not sever the signal path.
+    ///
+    /// *src -> node -> dest* becomes *src -> dest*
+    ///
+    /// A bridging connection is enabled if both the input and output connections it replaces were
+    /// enabled. Where `src` is already connected to `dest`, no connection is added. Feedback
+    /// connections to or from the node are removed rather than bridged.
+    ///
+    /// Resets the master to None if the index matches the current master index.
+    ///
+    /// **Note:** This method may shift (and in turn invalidate) previously returned node and edge
+    /// indices!
+    ///
+    /// **Graph** will re-prepare its visit order once if some node was removed.
+    pub fn remove_node_bridging(&mut self, idx: NodeIndex<Ix>) -> Option<N> {
+        self.dag.node_weight(idx)?;
+        let inputs: Vec<_> = self
+            .dag
+            .parents(idx)
+            .iter(&self.dag)
+            .map(|(edge, src)| (src, self.dag[edge].enabled))
+            .collect();
+        let outputs: Vec<_> = self
+            .dag
+            .children(idx)
+            .iter(&self.dag)
+            .map(|(edge, dest)| (dest, self.dag[edge].enabled))
+            .collect();
+        let last = NodeIndex::new(self.dag.node_count() - 1);
+        let node = self.take_node(idx)?;
+        // The last node has been shifted into the removed node's index.
+        let shifted = |n: NodeIndex<Ix>| if n == last { idx } else { n };
+        for &(src, src_enabled) in &inputs {
+            for &(dest, dest_enabled) in &outputs {
+                let (src, dest) = (shifted(src), shifted(dest));
+                if self.dag.find_edge(src, dest).is_some() {
+                    continue;
+                }
+                let mut connection = self.new_connection();
+                connection.enabled = src_enabled && dest_enabled;
+                if self.dag.add_edge(src, dest, connection).is_err() {
+                    unreachable!("`dest` cannot reach `src` as `src` was an ancestor of `dest`");
+                }
+            }
         }
-        self.dag.remove_node(idx).map(|node| {
-            self.prepare_visit_order();
-            node
+        self.prepare_visit_order();
+        Some(node)
+    }
+
+    /// Remove a node without re-preparing the visit order.
+    fn take_node(&mut self, idx: NodeIndex<Ix>) -> Option<N> {
+        if self.state.maybe_master == Some(idx) {
+            self.state.maybe_master = None;
+        } else if idx.index() < self.dag.node_count()
+            && self.state.maybe_master == Some(NodeIndex::new(self.dag.node_count() - 1))
+        {
+            // The last node will be shifted into the removed node's index.
+            self.state.maybe_master = Some(idx);
+        }
+        let last = NodeIndex::new(self.dag.node_count().saturating_sub(1));
+        if self.dag.node_weight(idx).is_some() {
+            self.recycle_node_connections(idx);
+        }
+        self.dag.remove_node(idx).inspect(|_| {
+            self.state.node_meta.swap_remove(idx.index());
+            self.remove_node_state(idx, last);
         })
     }
 
@@ -294,21 +699,37 @@ where
     ///
     /// **Panics** if there is no node for either `src` or `dest`.
     ///
-    /// **Panics** if the Graph is at the maximum number of edges for its index.
+    /// **Panics** if the Graph is at the maximum number of edges for its index. See
+    /// [`remaining_connection_capacity`](./struct.Graph.html#method.remaining_connection_capacity).
     pub fn add_connection(
         &mut self,
-        src: NodeIndex,
-        dest: NodeIndex,
-    ) -> Result<EdgeIndex, WouldCycle> {
+        src: NodeIndex<Ix>,
+        dest: NodeIndex<Ix>,
+    ) -> Result<EdgeIndex<Ix>, WouldCycle> {
+        // `Dag::add_edge` only checks for cycles when `src` has inputs and `dest` has outputs, so
+        // connecting a node to itself must be caught here.
+        if src == dest {
+            return Err(WouldCycle);
+        }
+        let connection = self.new_connection();
         self.dag
-            .add_edge(src, dest, Connection { buffer: Vec::new() })
-            .map(|edge| {
-                self.prepare_visit_order();
-                edge
-            })
+            .add_edge(src, dest, connection)
+            .inspect(|_| self.prepare_visit_order())
             .map_err(|_| WouldCycle)
     }
 
+    /// The same as [`add_connection`](./struct.Graph.html#method.add_connection) but returns an
+    /// error rather than panicking if there is no node for either `src` or `dest`.
+    pub fn try_add_connection(
+        &mut self,
+        src: NodeIndex<Ix>,
+        dest: NodeIndex<Ix>,
+    ) -> Result<EdgeIndex<Ix>, RequestError<Ix>> {
+        self.check_node(src)?;
+        self.check_node(dest)?;
+        Ok(self.add_connection(src, dest)?)
+    }
+
     /// The same as [`add_connection`](./struct.Graph.html#method.add_connection) but adds
     /// multiple connections to the **Graph**. Rather than checking for introduced cycles and
     /// re-preparing the visit order after adding each edge, we only do so after **all** edges are
@@ -327,25 +748,51 @@ where
     ///
     /// **Panics** if there is no node for either `src` or `dest`.
     ///
-    /// **Panics** if the Graph is at the maximum number of edges for its index.
-    pub fn add_connections<I>(&mut self, connections: I) -> Result<EdgeIndices, WouldCycle>
+    /// **Panics** if the Graph is at the maximum number of edges for its index. See
+    /// [`remaining_connection_capacity`](./struct.Graph.html#method.remaining_connection_capacity).
+    pub fn add_connections<I>(&mut self, connections: I) -> Result<EdgeIndices<Ix>, WouldCycle>
     where
-        I: ::std::iter::IntoIterator<Item = (NodeIndex, NodeIndex)>,
+        I: ::std::iter::IntoIterator<Item = (NodeIndex<Ix>, NodeIndex<Ix>)>,
     {
-        fn new_connection<F>() -> Connection<F> {
-            Connection { buffer: Vec::new() }
+        let connections: Vec<_> = connections.into_iter().collect();
+        if connections.iter().any(|&(src, dest)| src == dest) {
+            return Err(WouldCycle);
         }
-        self.dag
-            .add_edges(
-                connections
-                    .into_iter()
-                    .map(|(src, dest)| (src, dest, new_connection())),
-            )
-            .map(|edges| {
+        let connections: Vec<_> = connections
+            .into_iter()
+            .map(|(src, dest)| (src, dest, self.new_connection()))
+            .collect();
+        match self.dag.add_edges(connections) {
+            Ok(edges) => {
                 self.prepare_visit_order();
-                edges
-            })
-            .map_err(|_| WouldCycle)
+                Ok(edges)
+            }
+            Err(daggy::WouldCycle(removed)) => {
+                // Return the buffers of the rejected connections to the pool.
+                for mut connection in removed {
+                    self.state.pool.recycle(&mut connection);
+                }
+                Err(WouldCycle)
+            }
+        }
+    }
+
+    /// The same as [`add_connections`](./struct.Graph.html#method.add_connections) but returns an
+    /// error rather than panicking if there is no node for any of the given indices, in which
+    /// case no connections are added.
+    pub fn try_add_connections<I>(
+        &mut self,
+        connections: I,
+    ) -> Result<EdgeIndices<Ix>, RequestError<Ix>>
+    where
+        I: ::std::iter::IntoIterator<Item = (NodeIndex<Ix>, NodeIndex<Ix>)>,
+    {
+        let connections: Vec<_> = connections.into_iter().collect();
+        for &(src, dest) in &connections {
+            self.check_node(src)?;
+            self.check_node(dest)?;
+        }
+        Ok(self.add_connections(connections)?)
     }
 
     /// Find and return the index to the edge that describes the connection where `src` is an input
@@ -353,7 +800,11 @@ where
     ///
     /// Computes in **O(e')** time, where **e'** is the number of edges connected to the nodes `a`
     /// and `b`.
-    pub fn find_connection(&self, src: NodeIndex, dest: NodeIndex) -> Option<EdgeIndex> {
+    pub fn find_connection(
+        &self,
+        src: NodeIndex<Ix>,
+        dest: NodeIndex<Ix>,
+    ) -> Option<EdgeIndex<Ix>> {
         self.dag.find_edge(src, dest)
     }
 
@@ -362,13 +813,56 @@ where
     /// Returns true if an edge was removed, returns false if there was no edge at the given index.
     ///
     /// Re-prepares the visit order if some edge was removed.
-    pub fn remove_edge(&mut self, edge: EdgeIndex) -> bool {
-        if self.dag.remove_edge(edge).is_some() {
+    pub fn remove_edge(&mut self, edge: EdgeIndex<Ix>) -> bool {
+        let removed = self.detach_edge(edge);
+        if removed {
             self.prepare_visit_order();
-            true
-        } else {
-            false
         }
+        removed
+    }
+
+    /// Remove the connection at the given index, fading it out and recycling its buffers, without
+    /// re-preparing the visit order.
+    ///
+    /// Returns `false` if there was no connection at the given index.
+    fn detach_edge(&mut self, edge: EdgeIndex<Ix>) -> bool {
+        let endpoints = self.dag.edge_endpoints(edge);
+        match (endpoints, self.dag.remove_edge(edge)) {
+            (Some((src, dest)), Some(connection)) => {
+                self.fade_out_connection(src, dest, connection);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Enable or disable the connection at the given index without removing it.
+    ///
+    /// A disabled connection keeps its index and buffer, but is ignored when summing the inputs of
+    /// its output node, including when compensating for latency. As the **Graph**'s structure is
+    /// unchanged, toggling a connection does not re-prepare the visit order.
+    pub fn set_connection_enabled(
+        &mut self,
+        edge: EdgeIndex<Ix>,
+        enabled: bool,
+    ) -> Result<(), RequestError<Ix>> {
+        match self.dag.edge_weight_mut(edge) {
+            Some(connection) => {
+                connection.enabled = enabled;
+                Ok(())
+            }
+            None => Err(RequestError::NoConnection(edge)),
+        }
+    }
+
+    /// Whether or not the connection at the given index is enabled.
+    ///
+    /// Returns `false` if there is no connection at the given index.
+    pub fn is_connection_enabled(&self, edge: EdgeIndex<Ix>) -> bool {
+        self.dag
+            .edge_weight(edge)
+            .map(|connection| connection.enabled)
+            .unwrap_or(false)
     }
 
     /// Find and remove any connection between a and b if there is one, whether it is *a -> b* or
@@ -381,7 +875,7 @@ where
     ///
     /// Note: If you have an index to the edge you want to remove,
     /// [`remove_edge`](./struct.Graph.html#method.remove_edge) is a more performant option.
-    pub fn remove_connection(&mut self, a: NodeIndex, b: NodeIndex) -> bool {
+    pub fn remove_connection(&mut self, a: NodeIndex<Ix>, b: NodeIndex<Ix>) -> bool {
         match self
             .dag
             .find_edge(a, b)
@@ -404,15 +898,27 @@ where
     ///
     /// **Panics** if there is no node for the given `dest` index.
     ///
-    /// **Panics** if the Graph is at the maximum number of edges for its index.
-    pub fn add_input(&mut self, src: N, dest: NodeIndex) -> (EdgeIndex, NodeIndex) {
-        let indices = self
-            .dag
-            .add_parent(dest, Connection { buffer: Vec::new() }, src);
+    /// **Panics** if the Graph is at the maximum number of edges for its index. See
+    /// [`remaining_connection_capacity`](./struct.Graph.html#method.remaining_connection_capacity).
+    pub fn add_input(&mut self, src: N, dest: NodeIndex<Ix>) -> (EdgeIndex<Ix>, NodeIndex<Ix>) {
+        let connection = self.new_connection();
+        let indices = self.dag.add_parent(dest, connection, src);
+        self.push_node_meta(NodeMeta::default());
         self.prepare_visit_order();
         indices
     }
 
+    /// The same as [`add_input`](./struct.Graph.html#method.add_input) but returns an error
+    /// rather than panicking if there is no node for the given `dest` index.
+    pub fn try_add_input(
+        &mut self,
+        src: N,
+        dest: NodeIndex<Ix>,
+    ) -> Result<(EdgeIndex<Ix>, NodeIndex<Ix>), RequestError<Ix>> {
+        self.check_node(dest)?;
+        Ok(self.add_input(src, dest))
+    }
+
     /// Add a new node weight to the graph as an output to the wait at the given `src` node index.
     ///
     /// *src -> new edge -> dest*
@@ -425,21 +931,145 @@ where
     ///
     /// **Panics** if there is no node for the given `dest` index.
     ///
-    /// **Panics** if the Graph is at the maximum number of edges for its index.
-    pub fn add_output(&mut self, src: NodeIndex, dest: N) -> (EdgeIndex, NodeIndex) {
-        let indices = self
-            .dag
-            .add_child(src, Connection { buffer: Vec::new() }, dest);
+    /// **Panics** if the Graph is at the maximum number of edges for its index. See
+    /// [`remaining_connection_capacity`](./struct.Graph.html#method.remaining_connection_capacity).
+    pub fn add_output(&mut self, src: NodeIndex<Ix>, dest: N) -> (EdgeIndex<Ix>, NodeIndex<Ix>) {
+        let connection = self.new_connection();
+        let indices = self.dag.add_child(src, connection, dest);
+        self.push_node_meta(NodeMeta::default());
         self.prepare_visit_order();
         indices
     }
 
+    /// Add a series of nodes, each connected as an input to the next.
+    ///
+    /// *nodes[0] -> nodes[1] -> ... -> nodes[n - 1]*
+    ///
+    /// Returns the indices of the new nodes in order, along with the indices of the `n - 1`
+    /// connections between them.
+    ///
+    /// As the new connections cannot create a cycle, none are checked and the visit order is only
+    /// updated once after all nodes are added, making this far more efficient than repeatedly
+    /// calling `add_output` for long serial chains.
+    ///
+    /// **Panics** if the Graph is at the maximum number of nodes or edges for its index.
+    pub fn add_chain<I>(&mut self, nodes: I) -> (Vec<NodeIndex<Ix>>, Vec<EdgeIndex<Ix>>)
+    where
+        I: IntoIterator<Item = N>,
+    {
+        let nodes = nodes.into_iter();
+        let mut node_indices: Vec<NodeIndex<Ix>> = Vec::with_capacity(nodes.size_hint().0);
+        let mut edge_indices = Vec::with_capacity(node_indices.capacity().saturating_sub(1));
+        for node in nodes {
+            let idx = match node_indices.last() {
+                None => self.dag.add_node(node),
+                Some(&prev) => {
+                    let connection = self.new_connection();
+                    let (edge, idx) = self.dag.add_child(prev, connection, node);
+                    edge_indices.push(edge);
+                    idx
+                }
+            };
+            self.push_node_meta(NodeMeta::default());
+            node_indices.push(idx);
+        }
+        if !node_indices.is_empty() {
+            self.prepare_visit_order();
+        }
+        (node_indices, edge_indices)
+    }
+
+    /// Add a set of nodes, each connected as an input to the node at the given `dest` index.
+    ///
+    /// *nodes[i] -> new edge i -> dest*
+    ///
+    /// Returns the indices of the new nodes in order, along with the indices of the connections
+    /// from each of them to `dest`.
+    ///
+    /// As with [`add_chain`](./struct.Graph.html#method.add_chain), the new connections cannot
+    /// create a cycle, so none are checked and the visit order is only updated once.
+    ///
+    /// **Panics** if there is no node for the given `dest` index.
+    ///
+    /// **Panics** if the Graph is at the maximum number of nodes or edges for its index.
+    pub fn add_parallel<I>(
+        &mut self,
+        nodes: I,
+        dest: NodeIndex<Ix>,
+    ) -> (Vec<NodeIndex<Ix>>, Vec<EdgeIndex<Ix>>)
+    where
+        I: IntoIterator<Item = N>,
+    {
+        let nodes = nodes.into_iter();
+        let mut node_indices = Vec::with_capacity(nodes.size_hint().0);
+        let mut edge_indices = Vec::with_capacity(nodes.size_hint().0);
+        for node in nodes {
+            let connection = self.new_connection();
+            let (edge, idx) = self.dag.add_parent(dest, connection, node);
+            self.push_node_meta(NodeMeta::default());
+            node_indices.push(idx);
+            edge_indices.push(edge);
+        }
+        if !node_indices.is_empty() {
+            self.prepare_visit_order();
+        }
+        (node_indices, edge_indices)
+    }
+
+    /// The same as [`add_output`](./struct.Graph.html#method.add_output) but returns an error
+    /// rather than panicking if there is no node for the given `src` index.
+    pub fn try_add_output(
+        &mut self,
+        src: NodeIndex<Ix>,
+        dest: N,
+    ) -> Result<(EdgeIndex<Ix>, NodeIndex<Ix>), RequestError<Ix>> {
+        self.check_node(src)?;
+        Ok(self.add_output(src, dest))
+    }
+
+    /// Insert a new node into the middle of the existing connection at the given index.
+    ///
+    /// *src -> edge -> dest* becomes *src -> new edge -> node -> new edge -> dest*
+    ///
+    /// Returns the indices of the connection from `src` to the new node, the new node and the
+    /// connection from the new node to `dest`.
+    ///
+    /// This is equivalent to removing the connection via `remove_edge` and adding the node and its
+    /// two connections, so the old connection is faded out as the new ones fade in, but only
+    /// re-prepares the visit order once.
+    ///
+    /// **Note:** As the old connection is removed, this may shift (and in turn invalidate) the
+    /// previously returned index of the last connection.
+    ///
+    /// Returns an error if there is no connection for the given index.
+    pub fn insert_between(
+        &mut self,
+        edge: EdgeIndex<Ix>,
+        node: N,
+    ) -> Result<Insertion<Ix>, RequestError<Ix>> {
+        let (src, dest) = match self.dag.edge_endpoints(edge) {
+            Some(endpoints) => endpoints,
+            None => return Err(RequestError::NoConnection(edge)),
+        };
+        self.detach_edge(edge);
+        let connection = self.new_connection();
+        let (src_edge, node_idx) = self.dag.add_child(src, connection, node);
+        self.push_node_meta(NodeMeta::default());
+        let connection = self.new_connection();
+        let dest_edge = match self.dag.add_edge(node_idx, dest, connection) {
+            Ok(dest_edge) => dest_edge,
+            Err(_) => unreachable!("`dest` cannot reach `src` as `src` was an input to `dest`"),
+        };
+        self.prepare_visit_order();
+        Ok((src_edge, node_idx, dest_edge))
+    }
+
     /// A "walker" object that may be used to step through the inputs of the given node.
     ///
     /// Unlike the `Inputs` type, `WalkInputs` does not borrow the `Graph`.
     ///
     /// Can be converted to an iterator using `.iter()`.
-    pub fn inputs(&self, idx: NodeIndex) -> Inputs<F, N> {
+    pub fn inputs(&self, idx: NodeIndex<Ix>) -> Inputs<F, N, Ix> {
         Inputs {
             parents: self.dag.parents(idx),
         }
@@ -450,12 +1080,28 @@ where
     /// Unlike the `Outputs` type, `WalkOutputs` does not borrow the **Graph**.
     ///
     /// Can be converted to an iterator using `.iter()`.
-    pub fn outputs(&self, idx: NodeIndex) -> Outputs<F, N> {
+    pub fn outputs(&self, idx: NodeIndex<Ix>) -> Outputs<F, N, Ix> {
         Outputs {
             children: self.dag.children(idx),
         }
     }
 
+    /// An iterator yielding the index of each node that is a direct input to the given node.
+    ///
+    /// Unlike `inputs`, this borrows the **Graph** and requires no `Walker` import.
+    pub fn input_nodes(&self, idx: NodeIndex<Ix>) -> impl Iterator<Item = NodeIndex<Ix>> + '_ {
+        let mut inputs = self.inputs(idx);
+        std::iter::from_fn(move || inputs.next_node(self))
+    }
+
+    /// An iterator yielding the index of each node to which the given node is a direct input.
+    ///
+    /// Unlike `outputs`, this borrows the **Graph** and requires no `Walker` import.
+    pub fn output_nodes(&self, idx: NodeIndex<Ix>) -> impl Iterator<Item = NodeIndex<Ix>> + '_ {
+        let mut outputs = self.outputs(idx);
+        std::iter::from_fn(move || outputs.next_node(self))
+    }
+
     /// A "walker" type that may be used to step through all node indices in the order in which
     /// they will be visited when audio is requested from the **Graph**.
     pub fn visit_order(&self) -> VisitOrder {
@@ -470,17 +1116,17 @@ where
     /// Unlike the VisitOrder type, VisitOrder does not borrow the **Graph**.
     pub fn visit_order_rev(&self) -> VisitOrderReverse {
         VisitOrderReverse {
-            current_visit_order_idx: self.visit_order.len(),
+            current_visit_order_idx: self.state.visit_order.len(),
         }
     }
 
     /// Remove all incoming connections to the node at the given index.
     ///
     /// Return the number of connections removed.
-    pub fn remove_all_input_connections(&mut self, idx: NodeIndex) -> usize {
+    pub fn remove_all_input_connections(&mut self, idx: NodeIndex<Ix>) -> usize {
         let mut inputs = self.inputs(idx);
         let mut num = 0;
-        while let Some(connection) = inputs.next_edge(&self) {
+        while let Some(connection) = inputs.next_edge(self) {
             self.remove_edge(connection);
             num += 1;
         }
@@ -490,10 +1136,10 @@ where
     /// Remove all outgoing connections from the node at the given index.
     ///
     /// Return the number of connections removed.
-    pub fn remove_all_output_connections(&mut self, idx: NodeIndex) -> usize {
+    pub fn remove_all_output_connections(&mut self, idx: NodeIndex<Ix>) -> usize {
         let mut outputs = self.outputs(idx);
         let mut num = 0;
-        while let Some(connection) = outputs.next_edge(&self) {
+        while let Some(connection) = outputs.next_edge(self) {
             self.remove_edge(connection);
             num += 1;
         }
@@ -506,133 +1152,509 @@ where
     ///
     /// Note: this may shift (and in turn invalidate) previously returned node and edge indices!
     pub fn clear_disconnected(&mut self) -> usize {
+        self.clear_disconnected_with(|_, _| ())
+    }
+
+    /// The same as [`clear_disconnected`](./struct.Graph.html#method.clear_disconnected), but
+    /// passes each removed node to `removed` along with its index, so that any external resources
+    /// associated with it (e.g. file handles or plugin instances) may be released
+    /// deterministically.
+    ///
+    /// Each index is the one that the node had before any nodes were removed.
+    pub fn clear_disconnected_with<G>(&mut self, mut removed: G) -> usize
+    where
+        G: FnMut(NodeIndex<Ix>, N),
+    {
         let mut num_removed = 0;
-        for i in 0..self.dag.node_count() {
+        // Iterate in reverse so that the node shifted into a removed node's index has already
+        // been checked.
+        for i in (0..self.dag.node_count()).rev() {
             let idx = NodeIndex::new(i);
             let num_inputs = self.inputs(idx).count(self);
             let num_outputs = self.outputs(idx).count(self);
             if num_inputs == 0 && num_outputs == 0 {
-                if self.maybe_master == Some(idx) {
-                    self.maybe_master = None;
+                let last = NodeIndex::new(self.dag.node_count() - 1);
+                if self.state.maybe_master == Some(idx) {
+                    self.state.maybe_master = None;
+                } else if self.state.maybe_master == Some(last) {
+                    self.state.maybe_master = Some(idx);
                 }
-                self.dag.remove_node(idx);
+                let node = self.dag.remove_node(idx).expect("no node for index");
+                self.state.node_meta.swap_remove(i);
+                self.remove_node_state(idx, last);
+                removed(idx, node);
                 num_removed += 1;
             }
         }
+        if num_removed > 0 {
+            self.prepare_visit_order();
+        }
         num_removed
     }
 
+    /// Update all state that refers to nodes by index after the node at `idx` was removed and the
+    /// last node was shifted into its place.
+    fn remove_node_state(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        self.remove_node_feedback(idx, last);
+        self.remove_node_monitor(idx, last);
+        self.remove_node_control_taps(idx, last);
+        self.remove_node_fading(idx, last);
+        self.remove_node_messages(idx, last);
+        self.remove_node_externals(idx, last);
+        self.remove_node_replaced(idx, last);
+        self.remove_node_bypass(idx, last);
+        self.remove_node_analysis(idx, last);
+        self.remove_node_param_handles(idx, last);
+    }
+
+    /// The same as [`clear`](./struct.Graph.html#method.clear), but passes each node to `removed`
+    /// along with its index, so that any external resources associated with it (e.g. file handles
+    /// or plugin instances) may be released deterministically.
+    pub fn clear_with<G>(&mut self, mut removed: G)
+    where
+        G: FnMut(NodeIndex<Ix>, N),
+    {
+        for connection in self.dag.edge_weights_mut() {
+            self.state.pool.recycle(connection);
+        }
+        // Remove the nodes from the last index down so that no node is shifted into another's
+        // index.
+        for i in (0..self.dag.node_count()).rev() {
+            let idx = NodeIndex::new(i);
+            if let Some(node) = self.dag.remove_node(idx) {
+                removed(idx, node);
+            }
+        }
+        self.clear();
+    }
+
     /// Clear all dsp nodes.
     pub fn clear(&mut self) {
+        for connection in self.dag.edge_weights_mut() {
+            self.state.pool.recycle(connection);
+        }
         self.dag.clear();
-        self.visit_order.clear();
-        self.maybe_master = None;
+        self.state.node_meta.clear();
+        self.state.feedback.clear();
+        self.state.full_quality_outputs.clear();
+        self.state.control_taps.clear();
+        self.clear_fading_connections();
+        self.clear_messages();
+        self.state.externals.clear();
+        self.state.external_buffers.clear();
+        self.clear_replaced();
+        self.state.bypass_delays.clear();
+        self.clear_analysis();
+        self.state.param_handles.clear();
+        self.state.any_soloed = false;
+        self.state.visit_order.clear();
+        self.state.render_order_node = None;
+        self.state.maybe_master = None;
+        self.debug_validate();
     }
 
     /// Prepare the buffers for all nodes within the Graph.
     pub fn prepare_buffers(&mut self, buffer_size: usize) {
-        // Initialise the dry signal buffer.
-        resize_buffer_to(&mut self.dry_buffer, buffer_size);
+        // Initialise the dry signal buffer and the planar buffer.
+        resize_buffer_to(&mut self.state.dry_buffer, buffer_size);
+        self.state
+            .planar_buffer
+            .resize(buffer_size * F::CHANNELS, F::Sample::EQUILIBRIUM);
 
         // Initialise all connection buffers.
         for connection in self.dag.edge_weights_mut() {
             resize_buffer_to(&mut connection.buffer, buffer_size);
+            if self.state.double_buffered {
+                resize_buffer_to(&mut connection.previous, buffer_size);
+            }
+        }
+        self.state.pool.resize(buffer_size);
+        self.prepare_feedback_buffers(buffer_size);
+        self.prepare_input_buffers(buffer_size);
+        self.prepare_external_buffers(buffer_size);
+        resize_buffer_to(&mut self.state.replace_buffer, buffer_size);
+
+        // Prepare everything else that would otherwise be allocated when audio is requested.
+        self.prepare_compensation();
+        if let Some(out_node) = self.output_node() {
+            if self.state.render_order_node != Some(out_node) {
+                self.prepare_render_order(out_node);
+            }
         }
+
+        self.debug_validate();
     }
 
     /// Request audio from the node at the given index.
     ///
-    /// **Panics** if there is no node for the given index.
-    pub fn audio_requested_from(&mut self, out_node: NodeIndex, output: &mut [F], sample_hz: f64) {
-        // We can only go on if a node actually exists for the given index.
-        if self.node(out_node).is_none() {
-            panic!("No node for the given index");
+    /// **Panics** if there is no node for the given index. See
+    /// [`try_audio_requested_from`](./struct.Graph.html#method.try_audio_requested_from) for a
+    /// non-panicking alternative that is better suited to the audio thread.
+    pub fn audio_requested_from(
+        &mut self,
+        out_node: NodeIndex<Ix>,
+        output: &mut [F],
+        sample_hz: f64,
+    ) {
+        if let Err(err) = self.try_audio_requested_from(out_node, output, sample_hz) {
+            panic!("{}", err);
         }
+    }
+
+    /// Request audio from the node at the given index.
+    ///
+    /// Returns an error without touching `output` if there is no node for the given index.
+    pub fn try_audio_requested_from(
+        &mut self,
+        out_node: NodeIndex<Ix>,
+        output: &mut [F],
+        sample_hz: f64,
+    ) -> Result<(), RequestError<Ix>> {
+        // We can only go on if a node actually exists for the given index.
+        self.check_node(out_node)?;
+        let started = self.start_advisor();
 
         let buffer_size = output.len();
 
-        // Ensure the dry_buffer is the same length as the output buffer.
-        if self.dry_buffer.len() != buffer_size {
-            resize_buffer_to(&mut self.dry_buffer, buffer_size);
+        // Ensure the dry_buffer and all connection buffers are the same length as the output
+        // buffer.
+        if self.state.dry_buffer.len() != buffer_size {
+            self.note_alloc("the buffer size changed");
+            self.prepare_buffers(buffer_size);
         }
 
-        let mut visit_order = self.visit_order();
-        while let Some(node_idx) = visit_order.next(self) {
-            // Set the buffers to equilibrium, ready to sum the inputs of the current node.
-            for i in 0..buffer_size {
-                output[i] = F::EQUILIBRIUM;
-                self.dry_buffer[i] = F::EQUILIBRIUM;
-            }
+        // Ensure there is a path latency slot for every node.
+        if self.state.path_latencies.len() != self.dag.node_count() {
+            self.note_alloc("nodes were added since the buffers were prepared");
+            self.state.path_latencies.resize(self.dag.node_count(), 0);
+        }
 
-            // Walk over each of the input connections to sum their buffers to the output.
-            let mut inputs = self.inputs(node_idx);
-            while let Some(connection_idx) = inputs.next_edge(self) {
-                let connection = &self[connection_idx];
-                // Sum the connection's buffer onto the output.
-                //
-                // We can be certain that `connection`'s buffer is the same size as the
-                // `output` buffer as all connections are visited from their input nodes
-                // (towards the end of the visit_order while loop) before being visited here
-                // by their output nodes.
-                dasp::slice::zip_map_in_place(
-                    output,
-                    &connection.buffer,
-                    |out_frame, con_frame| {
-                        out_frame.zip_map(con_frame, |out_sample, con_sample| {
-                            let out_signed =
-                                out_sample.to_sample::<<F::Sample as Sample>::Signed>();
-                            let con_signed =
-                                con_sample.to_sample::<<F::Sample as Sample>::Signed>();
-                            (out_signed + con_signed).to_sample::<F::Sample>()
-                        })
-                    },
-                );
-            }
+        // The range of transport frames covered by this request.
+        let block = self.state.position..self.state.position + buffer_size as u64;
 
-            // Store the dry signal in the dry buffer for later summing.
-            dasp::slice::write(&mut self.dry_buffer, output);
+        // Only visit the nodes that contribute to the output of `out_node`.
+        if self.state.render_order_node != Some(out_node) {
+            self.note_alloc("the graph was restructured since the buffers were prepared");
+            self.prepare_render_order(out_node);
+        }
+
+        // An idle graph fills the output with silence until something becomes due.
+        if self.render_idle(output) {
+            self.record_render_time(started, buffer_size, sample_hz);
+            return Ok(());
+        }
+        let quiet = self.is_quiet(block.end);
 
-            // Render the audio with the current node and sum the dry and wet signals.
-            let (dry, wet) = {
-                let node = &mut self[node_idx];
+        self.deliver_param_handles();
+
+        let mut stashed = false;
+        for i in 0..self.state.render_order.len() {
+            let node_idx = self.state.render_order[i];
+
+            // Switch nodes whose background load has completed to active.
+            if self.dag[node_idx].finish_loading() {
+                self.state.notifier.send(Notification::NodeLoaded(node_idx));
+            }
+
+            // Thin out the changes within the block and deliver the events that take effect at
+            // its start, along with any messages.
+            self.coalesce_queued_events(node_idx, &block);
+            self.dispatch_events(node_idx, block.start + 1);
+            self.dispatch_messages(node_idx);
+            let silent = if !self.state.node_meta[node_idx.index()].is_active(&block) {
+                // Nodes outside of their active range are skipped entirely.
+                dasp::slice::equilibrium(output);
+                self.state.path_latencies[node_idx.index()] = 0;
+                true
+            } else if self.can_skip_silent(node_idx) && !self.has_events_before(node_idx, block.end)
+            {
+                // Idle nodes with silent inputs are skipped, keeping their last path latency.
+                dasp::slice::equilibrium(output);
+                true
+            } else if self.state.node_meta[node_idx.index()].is_fully_bypassed() {
+                // Bypassed nodes pass their summed input straight through.
+                let max_input_latency = self.sum_inputs(node_idx, output);
+                self.sum_external_input(node_idx, output);
+                let latency = self.delay_bypassed(node_idx, output);
+                self.state.path_latencies[node_idx.index()] = max_input_latency + latency;
+                silence::is_equilibrium(output)
+            } else {
+                self.update_tail(node_idx, buffer_size);
+
+                // Sum the inputs of the current node onto the output, keeping each input
+                // separately for nodes that process separately from their inputs.
+                let max_input_latency = if self.gathers_inputs(node_idx) {
+                    self.gather_inputs(node_idx, output)
+                } else {
+                    self.sum_inputs(node_idx, output)
+                };
+                self.sum_external_input(node_idx, output);
+
+                // Store the dry signal in the dry buffer for later summing. This is skipped for
+                // nodes with no dry signal, which are the majority in long serial chains.
+                let monitor_bypassed = self.is_monitor_bypassed(node_idx);
+                let bypass_fading = self.state.node_meta[node_idx.index()].is_bypass_fading();
+                let has_dry = self.dag[node_idx].dry() != Sample::EQUILIBRIUM
+                    || self.state.panic_policy == PanicPolicy::Dry
+                    || monitor_bypassed
+                    || bypass_fading
+                    || self.replaced_has_dry(node_idx);
+                if has_dry {
+                    dasp::slice::write(&mut self.state.dry_buffer, output);
+                }
+                self.store_replaced_input(node_idx, output);
 
                 // Render our `output` buffer with the current node.
                 // The `output` buffer is now representative of a fully wet signal.
-                node.audio_requested(output, sample_hz);
+                let started = self.start_watchdog();
+                let rendered = self.render_node(node_idx, output, block.start, sample_hz);
+                self.check_watchdog(node_idx, started, buffer_size, sample_hz);
+                if !rendered {
+                    // A bypassed node introduces no latency of its own.
+                    self.state.path_latencies[node_idx.index()] = max_input_latency;
+                } else if monitor_bypassed {
+                    // Monitor the input without the node's latency, keeping the rendered output.
+                    self.bypass_for_monitoring(node_idx, output);
+                    self.state.path_latencies[node_idx.index()] = max_input_latency;
+                    self.collect_param_changes(node_idx);
+                    self.publish_analysis(node_idx);
+                } else {
+                    let (dry, wet) = {
+                        let node = &self.dag[node_idx];
+                        let latency = max_input_latency + node.latency();
+                        self.state.path_latencies[node_idx.index()] = latency;
+                        (node.dry(), node.wet())
+                    };
+                    self.collect_param_changes(node_idx);
+                    self.publish_analysis(node_idx);
+
+                    // Combine the dry and wet signals.
+                    if has_dry {
+                        mix::mix_dry_wet(output, &self.state.dry_buffer, dry, wet);
+                    } else if wet != <F::Sample as Sample>::IDENTITY {
+                        dasp::slice::map_in_place(output, |f| f.scale_amp(wet));
+                    }
+
+                    // Crossfade with the summed input while toggling bypass.
+                    if bypass_fading {
+                        self.apply_bypass_fade(node_idx, output);
+                    }
+                }
+
+                // Crossfade from the node that this node replaced, if any.
+                self.crossfade_replaced(node_idx, output, has_dry);
+                silence::is_equilibrium(output)
+            };
+
+            // Deliver the events of nodes that were not rendered.
+            self.dispatch_events(node_idx, block.end);
 
-                let dry = node.dry();
-                let wet = node.wet();
-                (dry, wet)
+            // Silence the output of muted nodes and of nodes outside of the solo path.
+            let silent = if self.is_audible(node_idx) {
+                silent
+            } else {
+                dasp::slice::equilibrium(output);
+                true
             };
+            self.state.node_meta[node_idx.index()].silent = silent;
 
-            // Combine the dry and wet signals.
-            dasp::slice::zip_map_in_place(output, &self.dry_buffer, |f_wet, f_dry| {
-                f_wet.zip_map(f_dry, |s_wet, s_dry| {
-                    let wet = s_wet.mul_amp(wet);
-                    let dry = s_dry.mul_amp(dry);
-                    wet.add_amp(dry.to_sample())
-                })
-            });
+            // Store the rendered output for any nodes that it is fed back to or fading out of.
+            self.write_feedback(node_idx, output);
+            self.write_fading(node_idx, output);
+            self.write_external_output(node_idx, output);
 
-            // If we've reached our output node, we're done!
+            // If we've reached our output node, we're done, unless external outputs remain to be
+            // rendered, in which case its output is kept until they are.
             if node_idx == out_node {
-                return;
+                if i + 1 == self.state.render_order.len() {
+                    break;
+                }
+                if self.state.output_stash.len() != output.len() {
+                    self.note_alloc("external outputs were added since the buffers were prepared");
+                    resize_buffer_to(&mut self.state.output_stash, output.len());
+                }
+                dasp::slice::write(&mut self.state.output_stash, output);
+                stashed = true;
             }
 
             // Walk over each of the outgoing connections and write the rendered output to them.
+            let double_buffered = self.state.double_buffered;
+            let assert_no_alloc = self.state.assert_no_alloc;
             let mut outputs = self.outputs(node_idx);
             while let Some(connection_idx) = outputs.next_edge(self) {
                 let connection = &mut self.dag[connection_idx];
 
+                // Keep the previous request's output rather than overwriting it.
+                if double_buffered {
+                    std::mem::swap(&mut connection.buffer, &mut connection.previous);
+                }
+
                 // Ensure the buffer matches the target length.
                 if connection.buffer.len() != output.len() {
+                    assert!(
+                        !assert_no_alloc,
+                        "the graph allocated while rendering: a connection was not prepared"
+                    );
                     resize_buffer_to(&mut connection.buffer, output.len());
                 }
 
                 // Write the rendered audio to the outgoing connection buffers.
                 dasp::slice::write(&mut connection.buffer, output);
+                connection.silent = silent;
             }
         }
+
+        if stashed {
+            dasp::slice::write(output, &self.state.output_stash);
+        }
+        self.silence_inactive_channels(output);
+        self.advance_feedback();
+        self.advance_fading();
+        self.update_control_taps(block.start, buffer_size, sample_hz);
+        self.update_idle(quiet, output);
+        self.state.position = block.end;
+        self.record_render_time(started, buffer_size, sample_hz);
+        Ok(())
+    }
+
+    /// Set `output` to the sum of all input connections to the node at the given index.
+    ///
+    /// Returns the latency of the slowest path into the node.
+    fn sum_inputs(&mut self, node_idx: NodeIndex<Ix>, output: &mut [F]) -> usize {
+        let step = self.ramp_step();
+
+        // Find the latency of the slowest path into the current node.
+        let mut max_input_latency = 0;
+        let mut num_inputs = 0;
+        let mut inputs = self.inputs(node_idx);
+        while let Some((connection_idx, input_idx)) = inputs.next(self) {
+            if !self.dag[connection_idx].enabled {
+                continue;
+            }
+            let latency = self.state.path_latencies[input_idx.index()];
+            max_input_latency = std::cmp::max(max_input_latency, latency);
+            num_inputs += 1;
+        }
+
+        if num_inputs == 1 {
+            // A single input needs no summing or latency compensation, so copy it straight to
+            // the output rather than clearing the output and summing onto it.
+            let mut inputs = self.inputs(node_idx);
+            while let Some(connection_idx) = inputs.next_edge(self) {
+                let connection = &mut self.dag[connection_idx];
+                if !connection.enabled {
+                    continue;
+                }
+                // Lines that were compensating a slower sibling keep running so that the change
+                // to no delay is crossfaded.
+                connection.compensation.set_delay(0);
+                if !connection.compensation.is_passthrough() {
+                    let compensation = &mut connection.compensation;
+                    for (out_frame, &con_frame) in output.iter_mut().zip(&connection.buffer) {
+                        *out_frame = compensation.process(con_frame);
+                    }
+                } else if connection.silent {
+                    dasp::slice::equilibrium(output);
+                } else {
+                    dasp::slice::write(output, &connection.buffer);
+                }
+                if connection.gain < 1.0 {
+                    ramp::ramp_in_place(output, &mut connection.gain, step);
+                }
+                break;
+            }
+            self.sum_feedback(node_idx, output);
+            self.sum_fading(node_idx, output);
+            return max_input_latency;
+        }
+
+        // Set the output to equilibrium, ready to sum the inputs of the current node.
+        dasp::slice::equilibrium(output);
+
+        // Walk over each of the input connections to sum their buffers to the output.
+        let mut inputs = self.inputs(node_idx);
+        while let Some((connection_idx, input_idx)) = inputs.next(self) {
+            if !self.dag[connection_idx].enabled {
+                continue;
+            }
+
+            // Delay faster paths so that they are aligned with the slowest.
+            let delay = max_input_latency - self.state.path_latencies[input_idx.index()];
+            if !self.dag[connection_idx].compensation.fits(delay) {
+                self.note_alloc("a node's latency rose since the buffers were prepared");
+            }
+            let Connection {
+                ref buffer,
+                ref mut compensation,
+                ref mut gain,
+                silent,
+                ..
+            } = self.dag[connection_idx];
+            compensation.set_delay(delay);
+
+            // Silent connections add nothing, unless delayed audio is still in flight.
+            if silent && compensation.is_passthrough() {
+                *gain = (*gain + step * output.len() as f32).min(1.0);
+                continue;
+            }
+
+            // Fade in newly added connections.
+            if *gain < 1.0 {
+                ramp::sum_ramped(output, buffer, compensation, gain, step);
+                continue;
+            }
+
+            // Sum the connection's buffer onto the output.
+            //
+            // We can be certain that `connection`'s buffer is the same size as the
+            // `output` buffer as all connections are visited from their input nodes
+            // (towards the end of the visit_order while loop) before being visited here
+            // by their output nodes.
+            if compensation.is_passthrough() {
+                mix::sum_onto(output, buffer);
+            } else {
+                dasp::slice::zip_map_in_place(output, buffer, |out_frame, con_frame| {
+                    let con_frame = compensation.process(con_frame);
+                    out_frame.zip_map(con_frame, |out_sample, con_sample| {
+                        let out_signed = out_sample.to_sample::<<F::Sample as Sample>::Signed>();
+                        let con_signed = con_sample.to_sample::<<F::Sample as Sample>::Signed>();
+                        (out_signed + con_signed).to_sample::<F::Sample>()
+                    })
+                });
+            }
+        }
+
+        // Sum the output fed back from the previous request and of any removed connections that
+        // are fading out.
+        self.sum_feedback(node_idx, output);
+        self.sum_fading(node_idx, output);
+
+        max_input_latency
+    }
+
+    /// Returns an error if there is no node for the given index.
+    fn check_node(&self, idx: NodeIndex<Ix>) -> Result<(), RequestError<Ix>> {
+        match self.dag.node_weight(idx) {
+            Some(_) => Ok(()),
+            None => Err(RequestError::NoNode(idx)),
+        }
+    }
+
+    /// The node from which audio is requested by the **Graph**'s **Node** implementation.
+    ///
+    /// This is the master node if there is one. Otherwise, we'll start from the back of the
+    /// visit_order and use the first node that has no output connections.
+    fn output_node(&self) -> Option<NodeIndex<Ix>> {
+        if self.state.maybe_master.is_some() {
+            return self.state.maybe_master;
+        }
+        let mut visit_order_rev = self.visit_order_rev();
+        while let Some(node) = visit_order_rev.next(self) {
+            if self.inputs(node).count(self) == 0 {
+                return Some(node);
+            }
+        }
+        None
     }
 
     /// Prepare the visit order for the graph in its current state.
@@ -646,96 +1668,356 @@ where
     ///
     /// The user should never have to worry about this, thus the method is private.
     fn prepare_visit_order(&mut self) {
-        self.visit_order = daggy::petgraph::algo::toposort(self.dag.graph());
+        self.state.visit_order = daggy::petgraph::algo::toposort(self.dag.graph());
+        self.state.render_order_node = None;
+        self.prepare_solo_path();
+        self.prepare_compensation();
+        self.prepare_control_taps();
+        self.debug_validate();
+    }
+
+    /// Prepare the order in which nodes are rendered when audio is requested from `out_node`.
+    ///
+    /// This is the visit order filtered to the ancestors of `out_node`, of all external outputs and
+    /// of all nodes that are always rendered (including the sources of any feedback connections
+    /// into them), so that branches that cannot be heard are skipped.
+    /// The result is cached until the **Graph**'s connections change.
+    fn prepare_render_order(&mut self, out_node: NodeIndex<Ix>) {
+        let mut is_ancestor = vec![false; self.dag.node_count()];
+        let mut stack = vec![out_node];
+        stack.extend(self.external_output_nodes());
+        stack.extend(
+            self.dag
+                .raw_nodes()
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| node.weight.always_render())
+                .map(|(i, _)| NodeIndex::new(i)),
+        );
+        while let Some(idx) = stack.pop() {
+            if std::mem::replace(&mut is_ancestor[idx.index()], true) {
+                continue;
+            }
+            let mut inputs = self.inputs(idx);
+            while let Some(input) = inputs.next_node(self) {
+                stack.push(input);
+            }
+            for fb in self.feedback_connections() {
+                if fb.destination() == idx {
+                    stack.push(fb.source());
+                }
+            }
+            stack.extend(self.fading_sources(idx));
+        }
+
+        self.state.render_order.clear();
+        let visit_order = &self.state.visit_order;
+        self.state.render_order.extend(
+            visit_order
+                .iter()
+                .cloned()
+                .filter(|idx| is_ancestor[idx.index()]),
+        );
+        self.state.render_order_node = Some(out_node);
+    }
+}
+
+impl<F, N, Ix> Default for Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    fn default() -> Self {
+        Graph {
+            dag: daggy::Dag::new(),
+            replaced: Vec::new(),
+            retired_nodes: Vec::new(),
+            state: GraphState::default(),
+        }
+    }
+}
+
+impl<F, Ix> Default for GraphState<F, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    fn default() -> Self {
+        GraphState {
+            visit_order: Vec::new(),
+            dry_buffer: Vec::new(),
+            planar_buffer: Vec::new(),
+            maybe_master: None,
+            render_order: Vec::new(),
+            render_order_node: None,
+            path_latencies: Vec::new(),
+            node_meta: Vec::new(),
+            panic_policy: PanicPolicy::default(),
+            notifier: notification::Notifier::default(),
+            param_changes: Vec::new(),
+            position: 0,
+            double_buffered: false,
+            feedback: Vec::new(),
+            pool: BufferPool::new(),
+            assert_no_alloc: false,
+            validate_mutations: cfg!(debug_assertions),
+            monitor_max_latency: None,
+            full_quality_outputs: Vec::new(),
+            bypass_fade_frames: bypass::DEFAULT_BYPASS_FADE_FRAMES,
+            bypass_preserves_latency: false,
+            bypass_delays: Vec::new(),
+            control_taps: Vec::new(),
+            any_soloed: false,
+            connection_ramp_frames: 0,
+            fading: Vec::new(),
+            channels: F::CHANNELS,
+            dyn_buffer: Vec::new(),
+            input_buffers: Vec::new(),
+            num_ports: 0,
+            coalesce_events: false,
+            message_buses: Vec::new(),
+            bus_layout: BusLayout::effect(F::CHANNELS, F::CHANNELS),
+            externals: Vec::new(),
+            external_buffers: Vec::new(),
+            output_stash: Vec::new(),
+            replace_buffer: Vec::new(),
+            watchdog: None,
+            buffer_advisor: None,
+            advisor_stats: advisor::AdvisorStats::default(),
+            analysis_routes: Vec::new(),
+            analysis_values: analysis_bus::AnalysisValues::default(),
+            param_handles: Vec::new(),
+            next_node_id: NodeId::new(0),
+            device: device::DeviceState::default(),
+            tempo: tempo::TempoState::default(),
+            default_smoothing_ms: 0.0,
+            idle: idle::IdleState::default(),
+        }
+    }
+}
+
+// Implemented by hand, as deriving would not bound the samples of the planar buffer.
+impl<F, N, Ix> fmt::Debug for Graph<F, N, Ix>
+where
+    F: Frame + fmt::Debug,
+    F::Sample: fmt::Debug,
+    N: fmt::Debug,
+    Ix: IndexType + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Graph")
+            .field("dag", &self.dag)
+            .field("replaced", &self.replaced)
+            .field("retired_nodes", &self.retired_nodes)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<F> Connection<F>
+where
+    F: Frame,
+{
+    /// A new, empty connection.
+    fn new() -> Self {
+        Connection {
+            buffer: Vec::new(),
+            previous: Vec::new(),
+            silent: false,
+            enabled: true,
+            gain: 1.0,
+            compensation: Compensation::new(),
+        }
+    }
+
+    /// Whether or not the buffer contains only equilibrium frames, in which case it is skipped
+    /// when summing the inputs of the **Connection**'s output node.
+    pub fn is_silent(&self) -> bool {
+        self.silent
+    }
+
+    /// The audio rendered by the **Connection**'s input node during the previous request for
+    /// audio.
+    ///
+    /// This is only maintained while the **Graph** is double-buffered (see
+    /// `Graph::set_double_buffered`) and is empty otherwise.
+    pub fn previous_buffer(&self) -> &[F] {
+        &self.previous
+    }
+
+    /// Whether or not the **Connection** is summed into the input of its output node.
+    ///
+    /// See `Graph::set_connection_enabled`.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
     }
 }
 
-impl<F, N> ::std::ops::Index<NodeIndex> for Graph<F, N> {
+impl<F, N, Ix> ::std::ops::Index<NodeIndex<Ix>> for Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
     type Output = N;
+    /// **Panics** if there is no node for the given index. See
+    /// [`node`](./struct.Graph.html#method.node) for a non-panicking alternative.
     #[inline]
-    fn index<'a>(&'a self, index: NodeIndex) -> &'a N {
+    fn index(&self, index: NodeIndex<Ix>) -> &N {
         &self.dag[index]
     }
 }
 
-impl<F, N> ::std::ops::IndexMut<NodeIndex> for Graph<F, N> {
+impl<F, N, Ix> ::std::ops::IndexMut<NodeIndex<Ix>> for Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    /// **Panics** if there is no node for the given index. See
+    /// [`node_mut`](./struct.Graph.html#method.node_mut) for a non-panicking alternative.
     #[inline]
-    fn index_mut(&mut self, index: NodeIndex) -> &mut N {
+    fn index_mut(&mut self, index: NodeIndex<Ix>) -> &mut N {
         &mut self.dag[index]
     }
 }
 
-impl<F, N> ::std::ops::Index<EdgeIndex> for Graph<F, N> {
+impl<F, N, Ix> ::std::ops::Index<EdgeIndex<Ix>> for Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
     type Output = Connection<F>;
+    /// **Panics** if there is no connection for the given index. See
+    /// [`connection`](./struct.Graph.html#method.connection) for a non-panicking alternative.
     #[inline]
-    fn index<'a>(&'a self, index: EdgeIndex) -> &'a Connection<F> {
+    fn index(&self, index: EdgeIndex<Ix>) -> &Connection<F> {
         &self.dag[index]
     }
 }
 
-impl<F, N> Node<F> for Graph<F, N>
+impl<F, N, Ix> Node<F> for Graph<F, N, Ix>
 where
     F: Frame,
     N: Node<F>,
+    Ix: IndexType,
 {
     fn audio_requested(&mut self, output: &mut [F], sample_hz: f64) {
-        match self.maybe_master {
-            Some(master) => self.audio_requested_from(master, output, sample_hz),
-            None => {
-                // If there is no set master node, we'll start from the back of the visit_order and
-                // use the first node that has no output connections.
-                let mut visit_order_rev = self.visit_order_rev();
-                while let Some(node) = visit_order_rev.next(self) {
-                    if self.inputs(node).count(self) == 0 {
-                        self.audio_requested_from(node, output, sample_hz);
-                        return;
-                    }
-                }
+        if let Some(node) = self.output_node() {
+            self.audio_requested_from(node, output, sample_hz);
+        }
+        self.apply_device_fade(output);
+        // The parent passes its tempo again before the next request, if it still has one.
+        self.state.tempo.parent = None;
+    }
+
+    /// The path latency of the output node as of the last request for audio, or as of the last
+    /// time the **Graph** was restructured or its buffers were prepared.
+    fn latency(&self) -> usize {
+        self.output_node()
+            .and_then(|node| self.state.path_latencies.get(node.index()).cloned())
+            .unwrap_or(0)
+    }
+
+    fn tail_frames(&self) -> usize {
+        self.output_node()
+            .map(|node| self.path_tail_frames(node))
+            .unwrap_or(0)
+    }
+
+    fn channels_changed(&mut self, channels: usize) {
+        self.set_channels(channels.min(F::CHANNELS).max(1));
+    }
+
+    fn update_tempo(&mut self, tempo: &Tempo) {
+        self.state.tempo.parent = Some(*tempo);
+    }
+
+    /// Forwards the message to the output node.
+    fn handle_message(&mut self, message: &dyn Any) {
+        if let Some(node) = self.output_node() {
+            self.dag[node].handle_message(message);
+        }
+    }
+
+    /// Whether any of the **Graph**'s nodes must always be rendered, e.g. a meter within it.
+    fn always_render(&self) -> bool {
+        self.dag
+            .raw_nodes()
+            .iter()
+            .any(|node| node.weight.always_render())
+    }
+
+    fn bus_layout(&self) -> BusLayout {
+        self.state.bus_layout.clone()
+    }
+
+    /// Accepts any valid layout whose buses have between `1` and `F::CHANNELS` channels, setting
+    /// the active channels to those of the main output.
+    fn set_bus_layout(&mut self, layout: &BusLayout) -> bool {
+        let fits = layout
+            .buses
+            .iter()
+            .all(|bus| bus.channels > 0 && bus.channels <= F::CHANNELS);
+        if !fits || !layout.is_valid() {
+            return false;
+        }
+        if let Some(main_output) = layout.main_output() {
+            if main_output.channels != self.state.channels {
+                self.set_channels(main_output.channels);
             }
         }
+        self.state.bus_layout = layout.clone();
+        true
     }
 }
 
-impl<F, N> Walker<Graph<F, N>> for Inputs<F, N> {
-    type Index = usize;
+impl<F, N, Ix> Walker<Graph<F, N, Ix>> for Inputs<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    type Index = Ix;
 
     /// The next (connection, node) input pair to some node in our walk for the given **Graph**.
     #[inline]
-    fn next(&mut self, graph: &Graph<F, N>) -> Option<(EdgeIndex, NodeIndex)> {
+    fn next(&mut self, graph: &Graph<F, N, Ix>) -> Option<(EdgeIndex<Ix>, NodeIndex<Ix>)> {
         self.parents.next(&graph.dag)
     }
 
     /// The next input connection to some node in our walk for the given **Graph**.
     #[inline]
-    fn next_edge(&mut self, graph: &Graph<F, N>) -> Option<EdgeIndex> {
+    fn next_edge(&mut self, graph: &Graph<F, N, Ix>) -> Option<EdgeIndex<Ix>> {
         self.parents.next_edge(&graph.dag)
     }
 
     /// The next input node to some node in our walk for the given **Graph**.
     #[inline]
-    fn next_node(&mut self, graph: &Graph<F, N>) -> Option<NodeIndex> {
+    fn next_node(&mut self, graph: &Graph<F, N, Ix>) -> Option<NodeIndex<Ix>> {
         self.parents.next_node(&graph.dag)
     }
 }
 
-impl<F, N> Walker<Graph<F, N>> for Outputs<F, N> {
-    type Index = usize;
+impl<F, N, Ix> Walker<Graph<F, N, Ix>> for Outputs<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    type Index = Ix;
 
     /// The next (connection, node) output pair from some node in our walk for the given **Graph**.
     #[inline]
-    fn next(&mut self, graph: &Graph<F, N>) -> Option<(EdgeIndex, NodeIndex)> {
+    fn next(&mut self, graph: &Graph<F, N, Ix>) -> Option<(EdgeIndex<Ix>, NodeIndex<Ix>)> {
         self.children.next(&graph.dag)
     }
 
     /// The next output connection from some node in our walk for the given **Graph**.
     #[inline]
-    fn next_edge(&mut self, graph: &Graph<F, N>) -> Option<EdgeIndex> {
+    fn next_edge(&mut self, graph: &Graph<F, N, Ix>) -> Option<EdgeIndex<Ix>> {
         self.children.next_edge(&graph.dag)
     }
 
     /// The next output node from some node in our walk for the given **Graph**.
     #[inline]
-    fn next_node(&mut self, graph: &Graph<F, N>) -> Option<NodeIndex> {
+    fn next_node(&mut self, graph: &Graph<F, N, Ix>) -> Option<NodeIndex<Ix>> {
         self.children.next_node(&graph.dag)
     }
 }
@@ -744,8 +2026,13 @@ impl VisitOrder {
     /// The index of the next node that would be visited during audio requested in our walk of the
     /// given **Graph**'s visit order.
     #[inline]
-    pub fn next<F, N>(&mut self, graph: &Graph<F, N>) -> Option<NodeIndex> {
+    pub fn next<F, N, Ix>(&mut self, graph: &Graph<F, N, Ix>) -> Option<NodeIndex<Ix>>
+    where
+        F: Frame,
+        Ix: IndexType,
+    {
         graph
+            .state
             .visit_order
             .get(self.current_visit_order_idx)
             .map(|&idx| {
@@ -759,13 +2046,18 @@ impl VisitOrderReverse {
     /// The index of the next node that would be visited during audio requested in our walk of the
     /// given **Graph**'s visit order.
     #[inline]
-    pub fn next<F, N>(&mut self, graph: &Graph<F, N>) -> Option<NodeIndex> {
+    pub fn next<F, N, Ix>(&mut self, graph: &Graph<F, N, Ix>) -> Option<NodeIndex<Ix>>
+    where
+        F: Frame,
+        Ix: IndexType,
+    {
         if self.current_visit_order_idx > 0 {
             self.current_visit_order_idx -= 1;
             graph
+                .state
                 .visit_order
                 .get(self.current_visit_order_idx)
-                .map(|&idx| idx)
+                .copied()
         } else {
             None
         }
@@ -796,3 +2088,42 @@ impl ::std::error::Error for WouldCycle {
         "Adding this input would have caused the graph to cycle!"
     }
 }
+
+impl<Ix> ::std::fmt::Display for RequestError<Ix>
+where
+    Ix: IndexType,
+{
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        match *self {
+            RequestError::NoNode(idx) => write!(f, "No node for the given index {}", idx.index()),
+            RequestError::NoConnection(idx) => {
+                write!(f, "No connection for the given index {}", idx.index())
+            }
+            RequestError::WouldCycle => {
+                write!(f, "Adding this input would have caused the graph to cycle")
+            }
+        }
+    }
+}
+
+impl<Ix> ::std::error::Error for RequestError<Ix>
+where
+    Ix: IndexType,
+{
+    fn description(&self) -> &str {
+        match *self {
+            RequestError::NoNode(_) => "No node for the given index",
+            RequestError::NoConnection(_) => "No connection for the given index",
+            RequestError::WouldCycle => "Adding this input would have caused the graph to cycle!",
+        }
+    }
+}
+
+impl<Ix> From<WouldCycle> for RequestError<Ix>
+where
+    Ix: IndexType,
+{
+    fn from(_: WouldCycle) -> Self {
+        RequestError::WouldCycle
+    }
+}
@@ -0,0 +1,167 @@
+//! Recommending a block size from the time taken to render and the history of xruns.
+
+use super::{Graph, Notification};
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::time::Instant;
+
+/// Limits used by the **Graph** to recommend a larger or smaller block size.
+///
+/// See [`set_buffer_advisor`](./struct.Graph.html#method.set_buffer_advisor).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BufferAdvisor {
+    /// The number of requests for audio over which the load is measured before advising.
+    pub window: usize,
+    /// The fraction of the real-time duration of a request for audio above which rendering is
+    /// considered unstable and a larger block size is recommended.
+    pub max_load: f64,
+    /// The fraction of the real-time duration of a request for audio that rendering may take
+    /// after halving the block size. A smaller block size is recommended while the peak load
+    /// stays below half of this.
+    pub target_load: f64,
+    /// The smallest block size that may be recommended.
+    pub min_frames: usize,
+    /// The largest block size that may be recommended.
+    pub max_frames: usize,
+}
+
+/// A block size recommended by the **Graph**'s buffer advisor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BufferAdvice {
+    /// The current block size balances latency and stability.
+    Keep,
+    /// Rendering is close to or beyond its real-time limit, or xruns were reported, so a larger
+    /// block size should be used to avoid dropouts.
+    Increase(usize),
+    /// Rendering has ample headroom, so a smaller block size may be used to reduce latency.
+    Decrease(usize),
+}
+
+/// The statistics collected within the current window.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AdvisorStats {
+    blocks: usize,
+    /// The block size of the last request for audio.
+    frames: usize,
+    /// The largest fraction of the real-time duration of a request taken to render it.
+    peak_load: f64,
+    xruns: usize,
+    advice: Option<BufferAdvice>,
+}
+
+impl Default for BufferAdvisor {
+    fn default() -> Self {
+        BufferAdvisor {
+            window: 256,
+            max_load: 0.7,
+            target_load: 0.5,
+            min_frames: 32,
+            max_frames: 4096,
+        }
+    }
+}
+
+impl BufferAdvisor {
+    /// Advise on the block size of `frames` given the peak load and the number of xruns within
+    /// a window.
+    pub fn advise(&self, frames: usize, peak_load: f64, xruns: usize) -> BufferAdvice {
+        if xruns > 0 || peak_load > self.max_load {
+            let larger = std::cmp::min(frames.saturating_mul(2), self.max_frames);
+            if larger > frames {
+                return BufferAdvice::Increase(larger);
+            }
+        } else if peak_load * 2.0 < self.target_load {
+            let smaller = std::cmp::max(frames / 2, self.min_frames);
+            if smaller < frames {
+                return BufferAdvice::Decrease(smaller);
+            }
+        }
+        BufferAdvice::Keep
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    /// Enable or disable the buffer advisor.
+    ///
+    /// While enabled, the time taken by each request for audio is measured against its real-time
+    /// duration. At the end of every window of `window` requests, the peak load and any xruns
+    /// reported via `report_xrun` are used to recommend a block size, helping to tune latency
+    /// against stability. Whenever the advice changes to a new block size,
+    /// `Notification::BufferSizeAdvised` is emitted so that the host may reopen its audio stream
+    /// with that block size.
+    ///
+    /// Pass `None` to disable the advisor, which is the default.
+    pub fn set_buffer_advisor(&mut self, advisor: Option<BufferAdvisor>) {
+        self.state.buffer_advisor = advisor;
+        self.state.advisor_stats = AdvisorStats::default();
+    }
+
+    /// The buffer advisor's limits, if it is enabled.
+    pub fn buffer_advisor(&self) -> Option<BufferAdvisor> {
+        self.state.buffer_advisor
+    }
+
+    /// The advice given at the end of the last complete window, if any.
+    pub fn buffer_advice(&self) -> Option<BufferAdvice> {
+        self.state.advisor_stats.advice
+    }
+
+    /// Report that the audio backend missed a deadline, e.g. from an underflow callback.
+    ///
+    /// Any xrun within a window causes a larger block size to be recommended.
+    pub fn report_xrun(&mut self) {
+        self.state.advisor_stats.xruns += 1;
+    }
+
+    /// The time at which a request for audio started, if the advisor is enabled.
+    pub(crate) fn start_advisor(&self) -> Option<Instant> {
+        self.state.buffer_advisor.map(|_| Instant::now())
+    }
+
+    /// Record the time taken to render `frames` frames since `started`, advising on the block
+    /// size at the end of each window.
+    pub(crate) fn record_render_time(
+        &mut self,
+        started: Option<Instant>,
+        frames: usize,
+        sample_hz: f64,
+    ) {
+        let (advisor, started) = match (self.state.buffer_advisor, started) {
+            (Some(advisor), Some(started)) if frames > 0 => (advisor, started),
+            _ => return,
+        };
+        let stats = &mut self.state.advisor_stats;
+        if stats.frames != frames {
+            // Measurements of another block size say little about this one.
+            stats.blocks = 0;
+            stats.peak_load = 0.0;
+            stats.frames = frames;
+        }
+        let load = started.elapsed().as_secs_f64() / (frames as f64 / sample_hz);
+        stats.peak_load = stats.peak_load.max(load);
+        stats.blocks += 1;
+        if stats.blocks < advisor.window {
+            return;
+        }
+        let advice = advisor.advise(frames, stats.peak_load, stats.xruns);
+        stats.blocks = 0;
+        stats.peak_load = 0.0;
+        stats.xruns = 0;
+        if stats.advice == Some(advice) {
+            return;
+        }
+        stats.advice = Some(advice);
+        match advice {
+            BufferAdvice::Increase(frames) | BufferAdvice::Decrease(frames) => {
+                self.state
+                    .notifier
+                    .send(Notification::BufferSizeAdvised(frames));
+            }
+            BufferAdvice::Keep => (),
+        }
+    }
+}
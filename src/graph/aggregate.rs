@@ -0,0 +1,344 @@
+//! Driving additional audio devices from the external outputs of one **Graph**, e.g. the main mix
+//! to an interface alongside a cue mix to USB headphones.
+
+use super::{DeviceConfig, ExternalKind, Graph};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::{Frame, Sample};
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The rate at which the measured latency of a **DeviceOutput** follows the number of buffered
+/// frames, per callback.
+const FILL_SMOOTHING: f64 = 0.05;
+
+/// The correction applied to the resampling ratio per unit of relative latency error.
+const DRIFT_GAIN: f64 = 0.002;
+
+/// The largest correction applied to the resampling ratio, enough for the clocks of any two
+/// devices in working order.
+const MAX_DRIFT: f64 = 0.005;
+
+/// Receives the audio of an external output of a **Graph** on the stream of another device,
+/// returned by [`Graph::add_device_output`](./struct.Graph.html#method.add_device_output).
+///
+/// The **Graph** is driven by the callback of one device, its primary stream. Each time audio is
+/// requested, the output of the external output is pushed into a lock-free queue, which the
+/// callback of the other device drains via `read`. As the two devices run from separate clocks,
+/// they drift apart over time; the **DeviceOutput** resamples the audio by a ratio that it
+/// continually adjusts to hold the number of queued frames at its target latency, so that the
+/// queue neither runs dry nor overflows.
+///
+/// The **DeviceOutput** never blocks or allocates, so it may be moved into the callback of the
+/// other device.
+pub struct DeviceOutput<F> {
+    shared: Arc<Ring>,
+    /// The number of channels of the external output.
+    channels: usize,
+    /// The number of **Graph** frames per device frame while the clocks agree.
+    nominal_ratio: f64,
+    /// The number of **Graph** frames per device frame, corrected for drift.
+    ratio: f64,
+    /// The number of queued frames at which the queue is held.
+    target_fill: usize,
+    /// The smoothed number of queued frames.
+    fill: f64,
+    /// The four most recent frames taken from the queue, oldest first, interleaved by channel.
+    history: Vec<f32>,
+    /// The position between the second and third frames of `history` at which the next frame is
+    /// interpolated.
+    phase: f64,
+    /// Whether the queue has filled up to its target since it last ran dry.
+    primed: bool,
+    underruns: usize,
+    frame: PhantomData<fn() -> F>,
+}
+
+/// The **Graph**'s end of the queue to a **DeviceOutput**.
+///
+/// Cloning a **Graph** does not clone its device outputs: the clone's sender is detached, as only
+/// one stream may feed each queue.
+pub(crate) struct DeviceSender {
+    shared: Option<Arc<Ring>>,
+}
+
+/// A single-producer, single-consumer queue of interleaved samples.
+struct Ring {
+    /// The bits of each `f32` sample.
+    samples: Vec<AtomicU32>,
+    channels: usize,
+    /// The number of frames that the queue holds.
+    capacity: usize,
+    /// The total number of frames written and read, wrapping.
+    written: AtomicUsize,
+    read: AtomicUsize,
+    /// The number of frames dropped because the queue was full.
+    dropped: AtomicUsize,
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Send the audio of the external output with the given name to the stream of another
+    /// device.
+    ///
+    /// `sample_hz` is the sample rate at which the **Graph** is rendered, while `device`
+    /// describes the stream of the other device. The returned **DeviceOutput** should be moved
+    /// into that stream's callback and read from each time it is called. Its target latency is
+    /// two buffers of the larger of the two streams' buffer sizes, using the size for which the
+    /// **Graph**'s buffers were last prepared (or 512 frames if they have not been).
+    ///
+    /// Any **DeviceOutput** previously added for the external output is detached and receives
+    /// silence from then on.
+    ///
+    /// Returns `None` if there is no external output with the given name.
+    pub fn add_device_output(
+        &mut self,
+        name: &str,
+        sample_hz: f64,
+        device: DeviceConfig,
+    ) -> Option<DeviceOutput<F>> {
+        let i = self
+            .state
+            .externals
+            .iter()
+            .position(|e| e.name == name && e.kind == ExternalKind::Output)?;
+        let graph_block = match self.state.dry_buffer.len() {
+            0 => 512,
+            len => len,
+        };
+        let nominal_ratio = sample_hz / device.sample_hz;
+        let device_block = (device.buffer_size as f64 * nominal_ratio).ceil() as usize;
+        let target_fill = 2 * std::cmp::max(graph_block, device_block).max(1);
+        let shared = Arc::new(Ring::new(F::CHANNELS, target_fill * 4));
+        self.state.external_buffers[i].device = DeviceSender {
+            shared: Some(shared.clone()),
+        };
+        Some(DeviceOutput {
+            shared,
+            channels: self.state.externals[i].channels.min(F::CHANNELS),
+            nominal_ratio,
+            ratio: nominal_ratio,
+            target_fill,
+            fill: 0.0,
+            history: vec![0.0; 4 * F::CHANNELS],
+            phase: 0.0,
+            primed: false,
+            underruns: 0,
+            frame: PhantomData,
+        })
+    }
+
+    /// Stop sending the audio of the external output with the given name to another device.
+    ///
+    /// Returns `false` if the external output was not sending to a device.
+    pub fn remove_device_output(&mut self, name: &str) -> bool {
+        let buffer = self
+            .state
+            .externals
+            .iter()
+            .zip(&mut self.state.external_buffers)
+            .find(|(e, _)| e.name == name && e.kind == ExternalKind::Output)
+            .map(|(_, buffer)| buffer);
+        match buffer {
+            Some(buffer) => buffer.device.shared.take().is_some(),
+            None => false,
+        }
+    }
+}
+
+impl<F> DeviceOutput<F>
+where
+    F: Frame,
+{
+    /// Fill `buffer` with the next frames of the external output, resampled for the device.
+    ///
+    /// Channels beyond those of the external output are set to equilibrium. Until enough frames
+    /// have been queued to reach the target latency, and whenever the queue runs dry, the buffer
+    /// is filled with silence.
+    pub fn read(&mut self, buffer: &mut [F]) {
+        let available = self.shared.available();
+        self.fill += (available as f64 - self.fill) * FILL_SMOOTHING;
+        if !self.primed {
+            if available < self.target_fill {
+                dasp::slice::equilibrium(buffer);
+                return;
+            }
+            self.primed = true;
+            self.fill = available as f64;
+        }
+        // Consume faster when frames build up and slower when they run low.
+        let error = (self.fill - self.target_fill as f64) / self.target_fill as f64;
+        let correction = (error * DRIFT_GAIN).clamp(-MAX_DRIFT, MAX_DRIFT);
+        self.ratio = self.nominal_ratio * (1.0 + correction);
+
+        let channels = F::CHANNELS;
+        for (i, frame) in buffer.iter_mut().enumerate() {
+            while self.phase >= 1.0 {
+                self.history.rotate_left(channels);
+                let newest = &mut self.history[3 * channels..];
+                if !self.shared.pop(newest) {
+                    self.underruns += 1;
+                    self.primed = false;
+                    dasp::slice::equilibrium(&mut buffer[i..]);
+                    return;
+                }
+                self.phase -= 1.0;
+            }
+            let (history, t, active) = (&self.history, self.phase as f32, self.channels);
+            *frame = F::from_fn(|ch| {
+                if ch >= active {
+                    return F::Sample::EQUILIBRIUM;
+                }
+                let y = |n: usize| history[n * channels + ch];
+                let (y0, y1, y2, y3) = (y(0), y(1), y(2), y(3));
+                let c1 = 0.5 * (y2 - y0);
+                let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+                let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
+                let s = ((c3 * t + c2) * t + c1) * t + y1;
+                s.to_sample::<<F::Sample as Sample>::Float>().to_sample()
+            });
+            self.phase += self.ratio;
+        }
+    }
+
+    /// The number of **Graph** frames consumed per device frame, including the current
+    /// correction for drift between the two clocks.
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    /// The number of frames currently queued, in frames of the **Graph**.
+    pub fn latency_frames(&self) -> usize {
+        self.shared.available()
+    }
+
+    /// The number of queued frames, in frames of the **Graph**, at which the queue is held.
+    pub fn target_latency_frames(&self) -> usize {
+        self.target_fill
+    }
+
+    /// The number of times the queue ran dry, e.g. because the primary stream stalled.
+    pub fn underruns(&self) -> usize {
+        self.underruns
+    }
+
+    /// The number of frames dropped because the queue was full, e.g. because this device's
+    /// stream stalled.
+    pub fn dropped_frames(&self) -> usize {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Whether the **Graph** is still sending to this device output.
+    pub fn is_attached(&self) -> bool {
+        Arc::strong_count(&self.shared) > 1
+    }
+}
+
+impl<F> fmt::Debug for DeviceOutput<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DeviceOutput")
+            .field("channels", &self.channels)
+            .field("ratio", &self.ratio)
+            .field("target_latency_frames", &self.target_fill)
+            .field("latency_frames", &self.shared.available())
+            .field("underruns", &self.underruns)
+            .finish()
+    }
+}
+
+impl DeviceSender {
+    /// A sender that is not connected to any device output.
+    pub fn detached() -> Self {
+        DeviceSender { shared: None }
+    }
+
+    /// Queue the given frames for the device output, if any.
+    pub fn send<F>(&self, frames: &[F])
+    where
+        F: Frame,
+    {
+        if let Some(ref shared) = self.shared {
+            shared.push(frames);
+        }
+    }
+}
+
+impl Clone for DeviceSender {
+    fn clone(&self) -> Self {
+        DeviceSender::detached()
+    }
+}
+
+impl fmt::Debug for DeviceSender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DeviceSender")
+            .field("attached", &self.shared.is_some())
+            .finish()
+    }
+}
+
+impl Ring {
+    fn new(channels: usize, capacity: usize) -> Self {
+        Ring {
+            samples: (0..channels * capacity)
+                .map(|_| AtomicU32::new(0))
+                .collect(),
+            channels,
+            capacity,
+            written: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of frames written but not yet read.
+    fn available(&self) -> usize {
+        let written = self.written.load(Ordering::Acquire);
+        written.wrapping_sub(self.read.load(Ordering::Acquire))
+    }
+
+    /// Write as many of the given frames as fit, dropping the rest.
+    fn push<F>(&self, frames: &[F])
+    where
+        F: Frame,
+    {
+        let written = self.written.load(Ordering::Relaxed);
+        let free = self.capacity - written.wrapping_sub(self.read.load(Ordering::Acquire));
+        let len = std::cmp::min(free, frames.len());
+        for (i, frame) in frames[..len].iter().enumerate() {
+            let start = written.wrapping_add(i) % self.capacity * self.channels;
+            let slots = &self.samples[start..start + self.channels];
+            for (slot, s) in slots.iter().zip(frame.channels()) {
+                let s = s.to_float_sample().to_sample::<f32>();
+                slot.store(s.to_bits(), Ordering::Relaxed);
+            }
+        }
+        self.written
+            .store(written.wrapping_add(len), Ordering::Release);
+        if len < frames.len() {
+            self.dropped
+                .fetch_add(frames.len() - len, Ordering::Relaxed);
+        }
+    }
+
+    /// Read the next frame into `frame`, returning `false` if the queue is empty.
+    fn pop(&self, frame: &mut [f32]) -> bool {
+        let read = self.read.load(Ordering::Relaxed);
+        if self.written.load(Ordering::Acquire) == read {
+            return false;
+        }
+        let start = read % self.capacity * self.channels;
+        let slots = &self.samples[start..start + self.channels];
+        for (s, slot) in frame.iter_mut().zip(slots) {
+            *s = f32::from_bits(slot.load(Ordering::Relaxed));
+        }
+        self.read.store(read.wrapping_add(1), Ordering::Release);
+        true
+    }
+}
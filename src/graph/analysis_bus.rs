@@ -0,0 +1,200 @@
+//! The analysis bus, via which analysis nodes such as meters and pitch or tempo detectors publish
+//! named control values that modulate the parameters of other nodes.
+
+use super::{Graph, GraphState, NodeIndex, RequestError};
+use crate::event::Event;
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+/// Routes a named control value published by one node to a parameter of another.
+///
+/// Each time the source node publishes the value via `Node::analysis_values`, the destination
+/// node is sent `Event::Param` with the value scaled by `scale` and offset by `offset`. A
+/// destination that comes after the source in the visit order receives the value before it is
+/// rendered within the same request for audio, while any other destination receives it within
+/// the next request.
+///
+/// See [`Graph::add_analysis_route`](./struct.Graph.html#method.add_analysis_route).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AnalysisRoute<Ix = usize> {
+    /// The node publishing the value.
+    pub source: NodeIndex<Ix>,
+    /// The name of the value, e.g. `"correlation"`.
+    pub name: &'static str,
+    /// The node whose parameter is modulated.
+    pub destination: NodeIndex<Ix>,
+    /// The index of the modulated parameter.
+    pub param: usize,
+    /// The amount by which the value is multiplied.
+    pub scale: f32,
+    /// The amount added to the scaled value.
+    pub offset: f32,
+}
+
+/// The latest value of each name published by each node.
+#[derive(Clone, Debug)]
+pub(crate) struct AnalysisValues<Ix> {
+    values: Vec<(NodeIndex<Ix>, &'static str, f32)>,
+}
+
+impl<Ix> AnalysisRoute<Ix> {
+    /// Route the value published by `source` under `name` to the parameter `param` of
+    /// `destination`, unscaled.
+    pub fn new(
+        source: NodeIndex<Ix>,
+        name: &'static str,
+        destination: NodeIndex<Ix>,
+        param: usize,
+    ) -> Self {
+        AnalysisRoute {
+            source,
+            name,
+            destination,
+            param,
+            scale: 1.0,
+            offset: 0.0,
+        }
+    }
+
+    /// The same route with the value mapped from the range `from` onto the range `to`, e.g. to
+    /// map a correlation from `-1.0..1.0` onto a width from `0.0..2.0`.
+    pub fn mapped(mut self, from: (f32, f32), to: (f32, f32)) -> Self {
+        let span = from.1 - from.0;
+        self.scale = if span != 0.0 {
+            (to.1 - to.0) / span
+        } else {
+            0.0
+        };
+        self.offset = to.0 - from.0 * self.scale;
+        self
+    }
+}
+
+// Implemented manually as the derive would require `Ix: Default`.
+impl<Ix> Default for AnalysisValues<Ix>
+where
+    Ix: IndexType,
+{
+    fn default() -> Self {
+        AnalysisValues { values: Vec::new() }
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Route a named control value published by one node to a parameter of another.
+    ///
+    /// Routes follow their nodes by index, so removing nodes may invalidate them in the same way
+    /// as any other index. Routes from or to a removed node are removed along with the node.
+    ///
+    /// Returns an error if there is no node for either the source or the destination.
+    pub fn add_analysis_route(&mut self, route: AnalysisRoute<Ix>) -> Result<(), RequestError<Ix>> {
+        self.check_node(route.source)?;
+        self.check_node(route.destination)?;
+        self.state.analysis_routes.push(route);
+        Ok(())
+    }
+
+    /// Remove the route at the given index within `analysis_routes`, returning it if it exists.
+    pub fn remove_analysis_route(&mut self, index: usize) -> Option<AnalysisRoute<Ix>> {
+        if index < self.state.analysis_routes.len() {
+            Some(self.state.analysis_routes.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// All routes from published values to parameters, in the order in which they were added.
+    pub fn analysis_routes(&self) -> &[AnalysisRoute<Ix>] {
+        &self.state.analysis_routes
+    }
+
+    /// The latest value published under the given name by the node at the given index.
+    ///
+    /// Returns `None` if the node has not published a value of that name.
+    pub fn analysis_value(&self, idx: NodeIndex<Ix>, name: &str) -> Option<f32> {
+        self.state
+            .analysis_values
+            .values
+            .iter()
+            .find(|&&(node, n, _)| node == idx && n == name)
+            .map(|&(_, _, value)| value)
+    }
+
+    /// Collect the values published by the node at the given index, sending each routed value
+    /// to its destination.
+    pub(crate) fn publish_analysis(&mut self, idx: NodeIndex<Ix>) {
+        let Graph {
+            ref dag,
+            ref mut state,
+            ..
+        } = *self;
+        let GraphState {
+            ref analysis_routes,
+            ref mut analysis_values,
+            ref mut node_meta,
+            ..
+        } = *state;
+        let mut added = false;
+        dag[idx].analysis_values(&mut |name, value| {
+            let values = &mut analysis_values.values;
+            match values
+                .iter_mut()
+                .find(|(node, n, _)| *node == idx && *n == name)
+            {
+                Some(latest) => latest.2 = value,
+                None => {
+                    values.push((idx, name, value));
+                    added = true;
+                }
+            }
+            let routes = analysis_routes
+                .iter()
+                .filter(|route| route.source == idx && route.name == name);
+            for route in routes {
+                let param = route.param;
+                let value = value * route.scale + route.offset;
+                node_meta[route.destination.index()]
+                    .events
+                    .push(Event::Param { param, value });
+            }
+        });
+        if added {
+            self.note_alloc("a node published an analysis value for the first time");
+        }
+    }
+
+    /// Update the analysis routes and values after the node at `idx` was removed and the last
+    /// node was shifted into its place.
+    pub(crate) fn remove_node_analysis(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        self.state
+            .analysis_routes
+            .retain(|route| route.source != idx && route.destination != idx);
+        for route in &mut self.state.analysis_routes {
+            if route.source == last {
+                route.source = idx;
+            }
+            if route.destination == last {
+                route.destination = idx;
+            }
+        }
+        let values = &mut self.state.analysis_values.values;
+        values.retain(|&(node, _, _)| node != idx);
+        for (node, _, _) in values.iter_mut() {
+            if *node == last {
+                *node = idx;
+            }
+        }
+    }
+
+    /// Remove all analysis routes and values.
+    pub(crate) fn clear_analysis(&mut self) {
+        self.state.analysis_routes.clear();
+        self.state.analysis_values.values.clear();
+    }
+}
@@ -0,0 +1,216 @@
+//! Bypassing of nodes by the **Graph**, with click-free fades when toggling and optional
+//! preservation of the bypassed node's latency.
+
+use super::latency::Compensation;
+use super::{ramp, Graph, NodeIndex, NodeMeta, RequestError};
+use crate::node::Node;
+use crate::slice;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+/// The default number of frames over which a node is faded in or out of bypass.
+pub(crate) const DEFAULT_BYPASS_FADE_FRAMES: usize = 128;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Bypass the node at the given index, or stop bypassing it.
+    ///
+    /// A bypassed node passes its summed input straight through without its `audio_requested`
+    /// method being called and introduces no latency, unless latency is preserved via
+    /// `set_bypass_preserves_latency`. When toggled, the node's output is crossfaded with its
+    /// input over the number of frames set via `set_bypass_fade_frames` to avoid clicks.
+    pub fn set_bypassed(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        bypassed: bool,
+    ) -> Result<(), RequestError<Ix>> {
+        let fade_frames = self.state.bypass_fade_frames;
+        let meta = self
+            .state
+            .node_meta
+            .get_mut(idx.index())
+            .ok_or(RequestError::NoNode(idx))?;
+        let was_bypassed = meta.bypassed;
+        meta.bypassed = bypassed;
+        if fade_frames == 0 {
+            meta.bypass_mix = if bypassed { 1.0 } else { 0.0 };
+        }
+        if bypassed && !was_bypassed {
+            self.prepare_bypass_delay(idx);
+        }
+        Ok(())
+    }
+
+    /// Whether bypassed nodes keep delaying their passed-through input by their latency.
+    ///
+    /// By default, a bypassed node introduces no latency, so toggling bypass on a node that
+    /// reports latency shifts the timing of everything downstream as the **Graph**'s latency
+    /// compensation adjusts. When enabled, the input of a bypassed node is instead delayed by the
+    /// node's reported latency, so that bypass may be toggled without any change in timing. This
+    /// also aligns the dry and processed signals while crossfading in and out of bypass.
+    pub fn set_bypass_preserves_latency(&mut self, preserve: bool) {
+        self.state.bypass_preserves_latency = preserve;
+        if preserve {
+            let bypassed: Vec<_> = (0..self.state.node_meta.len())
+                .filter(|&i| self.state.node_meta[i].bypassed)
+                .map(NodeIndex::new)
+                .collect();
+            for idx in bypassed {
+                self.prepare_bypass_delay(idx);
+            }
+        } else {
+            self.state.bypass_delays.clear();
+        }
+    }
+
+    /// Whether bypassed nodes keep delaying their passed-through input by their latency.
+    ///
+    /// See `set_bypass_preserves_latency`.
+    pub fn bypass_preserves_latency(&self) -> bool {
+        self.state.bypass_preserves_latency
+    }
+
+    /// Whether or not the node at the given index is bypassed.
+    ///
+    /// This is `true` as soon as `set_bypassed` is called, even while the node is fading out.
+    pub fn is_bypassed(&self, idx: NodeIndex<Ix>) -> bool {
+        self.state
+            .node_meta
+            .get(idx.index())
+            .map(|meta| meta.bypassed)
+            .unwrap_or(false)
+    }
+
+    /// Set the number of frames over which nodes are faded in or out of bypass.
+    ///
+    /// By default, this is 128 frames. If `0`, bypass takes effect immediately.
+    pub fn set_bypass_fade_frames(&mut self, frames: usize) {
+        self.state.bypass_fade_frames = frames;
+    }
+
+    /// The number of frames over which nodes are faded in or out of bypass.
+    pub fn bypass_fade_frames(&self) -> usize {
+        self.state.bypass_fade_frames
+    }
+
+    /// Crossfade the node's rendered `output` with its dry input stored in the dry buffer,
+    /// advancing the node's bypass fade.
+    pub(crate) fn apply_bypass_fade(&mut self, idx: NodeIndex<Ix>, output: &mut [F]) {
+        let latency = self.bypass_latency(idx);
+        if let Some(delay) = bypass_delay_mut(&mut self.state.bypass_delays, idx) {
+            delay.set_delay(latency);
+            for frame in &mut self.state.dry_buffer[..output.len()] {
+                *frame = delay.process(*frame);
+            }
+        }
+        let step = 1.0 / self.state.bypass_fade_frames.max(1) as f32;
+        let meta = &mut self.state.node_meta[idx.index()];
+        let target = if meta.bypassed { 1.0 } else { 0.0 };
+        let (frames, from, to) =
+            ramp::advance_gain(&mut meta.bypass_mix, target, step, output.len());
+        let (fading, rest) = output.split_at_mut(frames);
+        let (dry_fading, dry_rest) = self.state.dry_buffer.split_at(frames);
+        slice::crossfade_into(fading, dry_fading, from, to);
+        if target >= 1.0 {
+            slice::write(rest, dry_rest);
+        }
+    }
+
+    /// The latency of the node at the given index while bypassed.
+    ///
+    /// This is the node's reported latency if latency is preserved while bypassed, otherwise `0`.
+    pub(crate) fn bypass_latency(&self, idx: NodeIndex<Ix>) -> usize {
+        if self.state.bypass_preserves_latency {
+            self.dag[idx].latency()
+        } else {
+            0
+        }
+    }
+
+    /// Delay the passed-through `output` of the bypassed node at the given index by its bypass
+    /// latency, returning the latency.
+    pub(crate) fn delay_bypassed(&mut self, idx: NodeIndex<Ix>, output: &mut [F]) -> usize {
+        let latency = self.bypass_latency(idx);
+        if latency == 0 {
+            return 0;
+        }
+        let prepared = self
+            .state
+            .bypass_delays
+            .iter()
+            .any(|(node, delay)| *node == idx && delay.fits(latency));
+        if !prepared {
+            self.note_alloc("the bypass delay was not prepared");
+            self.prepare_bypass_delay(idx);
+        }
+        let delay = bypass_delay_mut(&mut self.state.bypass_delays, idx)
+            .expect("the delay was just prepared");
+        delay.set_delay(latency);
+        for frame in output {
+            *frame = delay.process(*frame);
+        }
+        latency
+    }
+
+    /// Prepare a cleared delay line for bypassing the node at the given index, if latency is
+    /// preserved while bypassed.
+    pub(crate) fn prepare_bypass_delay(&mut self, idx: NodeIndex<Ix>) {
+        let latency = self.bypass_latency(idx);
+        if latency == 0 {
+            return;
+        }
+        match bypass_delay_mut(&mut self.state.bypass_delays, idx) {
+            Some(delay) => {
+                delay.set_delay(latency);
+                delay.clear();
+            }
+            None => {
+                let mut delay = Compensation::new();
+                delay.prepare(latency, latency);
+                self.state.bypass_delays.push((idx, delay));
+            }
+        }
+    }
+
+    /// Update the bypass delays after the node at `idx` was removed and the last node was
+    /// shifted into its place.
+    pub(crate) fn remove_node_bypass(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        self.state.bypass_delays.retain(|(node, _)| *node != idx);
+        for (node, _) in &mut self.state.bypass_delays {
+            if *node == last {
+                *node = idx;
+            }
+        }
+    }
+}
+
+/// The delay line for bypassing the node at the given index, if one has been prepared.
+fn bypass_delay_mut<F, Ix>(
+    delays: &mut [(NodeIndex<Ix>, Compensation<F>)],
+    idx: NodeIndex<Ix>,
+) -> Option<&mut Compensation<F>>
+where
+    Ix: IndexType,
+{
+    delays
+        .iter_mut()
+        .find(|(node, _)| *node == idx)
+        .map(|(_, delay)| delay)
+}
+
+impl NodeMeta {
+    /// Whether the node is bypassed and has finished fading out.
+    pub(crate) fn is_fully_bypassed(&self) -> bool {
+        self.bypassed && self.bypass_mix >= 1.0
+    }
+
+    /// Whether the node is currently fading in or out of bypass.
+    pub(crate) fn is_bypass_fading(&self) -> bool {
+        let target = if self.bypassed { 1.0 } else { 0.0 };
+        self.bypass_mix != target
+    }
+}
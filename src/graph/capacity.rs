@@ -0,0 +1,110 @@
+//! Management of the memory reserved by a **Graph** and the limits imposed by its index type.
+
+use super::{Dag, EdgeIndex, Graph, NodeIndex};
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    /// The maximum number of nodes that the **Graph**'s index type can address.
+    ///
+    /// Adding a node beyond this limit will panic.
+    pub fn max_node_count(&self) -> usize {
+        <Ix as IndexType>::max().index()
+    }
+
+    /// The maximum number of connections that the **Graph**'s index type can address.
+    ///
+    /// Adding a connection beyond this limit will panic.
+    pub fn max_connection_count(&self) -> usize {
+        <Ix as IndexType>::max().index()
+    }
+
+    /// The number of nodes that may still be added before reaching the limit of the **Graph**'s
+    /// index type.
+    pub fn remaining_node_capacity(&self) -> usize {
+        self.max_node_count().saturating_sub(self.dag.node_count())
+    }
+
+    /// The number of connections that may still be added before reaching the limit of the
+    /// **Graph**'s index type.
+    pub fn remaining_connection_capacity(&self) -> usize {
+        self.max_connection_count()
+            .saturating_sub(self.dag.edge_count())
+    }
+
+    /// Reserve capacity for at least `additional` more nodes.
+    ///
+    /// As the underlying **Dag** does not expose its storage, this rebuilds it with the new
+    /// capacity in **O(n + e)** time. All node and edge indices remain stable. This should be
+    /// called while setting up the **Graph** rather than on the audio thread.
+    pub fn reserve_nodes(&mut self, additional: usize) {
+        let nodes = self.dag.node_count() + additional;
+        let edges = self.dag.edge_count();
+        self.rebuild_dag(nodes, edges);
+        self.state.visit_order.reserve(additional);
+        self.state.node_meta.reserve(additional);
+        self.state.path_latencies.reserve(additional);
+    }
+
+    /// Reserve capacity for at least `additional` more connections.
+    ///
+    /// As the underlying **Dag** does not expose its storage, this rebuilds it with the new
+    /// capacity in **O(n + e)** time. All node and edge indices remain stable. This should be
+    /// called while setting up the **Graph** rather than on the audio thread.
+    pub fn reserve_connections(&mut self, additional: usize) {
+        let nodes = self.dag.node_count();
+        let edges = self.dag.edge_count() + additional;
+        self.rebuild_dag(nodes, edges);
+    }
+
+    /// Shrink the memory reserved by the **Graph** as much as possible.
+    ///
+    /// This rebuilds the underlying **Dag** in **O(n + e)** time. All node and edge indices remain
+    /// stable.
+    pub fn shrink_to_fit(&mut self) {
+        let nodes = self.dag.node_count();
+        let edges = self.dag.edge_count();
+        self.rebuild_dag(nodes, edges);
+        self.state.visit_order.shrink_to_fit();
+        self.state.node_meta.shrink_to_fit();
+        self.state.path_latencies.shrink_to_fit();
+        self.state.feedback.shrink_to_fit();
+    }
+
+    /// Move all nodes and edges into a new **Dag** with the given capacity, preserving indices.
+    fn rebuild_dag(&mut self, node_capacity: usize, edge_capacity: usize) {
+        // Remove edges and nodes from the back so that no indices are shifted.
+        let mut edges = Vec::with_capacity(self.dag.edge_count());
+        while let Some(i) = self.dag.edge_count().checked_sub(1) {
+            let edge = &self.dag.raw_edges()[i];
+            let (src, dest) = (edge.source(), edge.target());
+            let weight = self
+                .dag
+                .remove_edge(EdgeIndex::new(i))
+                .expect("no edge for index");
+            edges.push((src, dest, weight));
+        }
+        let mut nodes = Vec::with_capacity(self.dag.node_count());
+        while let Some(i) = self.dag.node_count().checked_sub(1) {
+            let node = self
+                .dag
+                .remove_node(NodeIndex::new(i))
+                .expect("no node for index");
+            nodes.push(node);
+        }
+
+        // Re-add everything in the original order so that every index is restored.
+        let mut dag = Dag::with_capacity(node_capacity, edge_capacity);
+        for node in nodes.into_iter().rev() {
+            dag.add_node(node);
+        }
+        if dag.add_edges(edges.into_iter().rev()).is_err() {
+            unreachable!("the edges of an acyclic graph cannot form a cycle");
+        }
+        self.dag = dag;
+    }
+}
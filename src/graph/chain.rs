@@ -0,0 +1,128 @@
+//! Moving static **Chain**s of nodes into the **Graph** as a series of nodes and freezing a series
+//! of nodes back into a single **Chain** node.
+
+use super::{EdgeIndex, Graph, NodeIndex};
+use crate::node::Node;
+use crate::nodes::{Chain, IntoNodes};
+use daggy::petgraph::graph::IndexType;
+use daggy::Walker;
+use dasp::Frame;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Add each stage of the given chain to the graph as its own node, connected in series via
+    /// [`add_chain`](./struct.Graph.html#method.add_chain).
+    ///
+    /// Returns the indices of the new nodes in order, along with the indices of the connections
+    /// between them.
+    ///
+    /// This lifts a pipeline that was built statically into the graph, where its stages may be
+    /// rewired, bypassed or replaced at runtime.
+    pub fn expand_chain<T>(&mut self, chain: Chain<T>) -> (Vec<NodeIndex<Ix>>, Vec<EdgeIndex<Ix>>)
+    where
+        T: IntoNodes<N>,
+    {
+        let mut nodes = Vec::new();
+        chain.into_stages().into_nodes(&mut nodes);
+        self.add_chain(nodes)
+    }
+
+    /// Replace the given series of nodes with a single **Chain** node that renders them in the
+    /// same order, returning the index of the new node.
+    ///
+    /// Each node in `nodes` must be the only input to the next and the next must be its only
+    /// output. The new node takes the inputs of the first node and the outputs of the last,
+    /// keeping whether each connection is enabled, and becomes the master if any of the nodes
+    /// was the master. This saves the graph from visiting, mixing and buffering each node of a
+    /// section whose topology is frozen, e.g. before shipping a patch that was prototyped
+    /// dynamically.
+    ///
+    /// The bypass, mute, solo and active range state of the nodes along with their pending events
+    /// are discarded.
+    ///
+    /// Returns `None` and leaves the graph unchanged if `nodes` is empty, contains an index
+    /// without a node or a repeated index, does not form such a series, or contains a node with a
+    /// feedback connection.
+    ///
+    /// **Note:** This method may shift (and in turn invalidate) previously returned node indices!
+    pub fn freeze_chain(&mut self, nodes: &[NodeIndex<Ix>]) -> Option<NodeIndex<Ix>>
+    where
+        N: From<Chain<Vec<N>>>,
+    {
+        let (&first, &last) = (nodes.first()?, nodes.last()?);
+        // The position of each node within the chain.
+        let mut positions = vec![None; self.dag.node_count()];
+        for (i, &idx) in nodes.iter().enumerate() {
+            match positions.get_mut(idx.index()) {
+                Some(position @ None) => *position = Some(i),
+                _ => return None,
+            }
+        }
+        for pair in nodes.windows(2) {
+            let mut outputs = self.outputs(pair[0]);
+            let mut inputs = self.inputs(pair[1]);
+            if outputs.next_node(self) != Some(pair[1])
+                || outputs.next_node(self).is_some()
+                || inputs.next_node(self) != Some(pair[0])
+                || inputs.next_node(self).is_some()
+            {
+                return None;
+            }
+        }
+        let in_chain = |idx: NodeIndex<Ix>| positions[idx.index()].is_some();
+        if self
+            .state
+            .feedback
+            .iter()
+            .any(|fb| in_chain(fb.source()) || in_chain(fb.destination()))
+        {
+            return None;
+        }
+
+        let inputs: Vec<_> = self
+            .inputs(first)
+            .iter(self)
+            .map(|(edge, src)| (src, self.dag[edge].enabled))
+            .collect();
+        let outputs: Vec<_> = self
+            .outputs(last)
+            .iter(self)
+            .map(|(edge, dest)| (dest, self.dag[edge].enabled))
+            .collect();
+        let was_master = self.state.maybe_master.is_some_and(in_chain);
+
+        let remove: Vec<bool> = positions.iter().map(Option::is_some).collect();
+        let mut stages: Vec<Option<N>> = nodes.iter().map(|_| None).collect();
+        let map = self.remove_flagged_nodes(&remove, |idx, node| {
+            if let Some(position) = positions[idx.index()] {
+                stages[position] = Some(node);
+            }
+        });
+        let stages = stages
+            .into_iter()
+            .map(|node| node.expect("each node was removed"))
+            .collect();
+
+        let idx = self.add_node(Chain::new(stages).into());
+        let inputs = inputs.into_iter().map(|(src, enabled)| (src, idx, enabled));
+        let outputs = outputs
+            .into_iter()
+            .map(|(dest, enabled)| (idx, dest, enabled));
+        for (src, dest, enabled) in inputs.chain(outputs) {
+            let src = map.node(src).unwrap_or(src);
+            let dest = map.node(dest).unwrap_or(dest);
+            let edge = self
+                .add_connection(src, dest)
+                .expect("the chain formed no cycle with the rest of the graph");
+            self.dag[edge].enabled = enabled;
+        }
+        if was_master {
+            self.state.maybe_master = Some(idx);
+        }
+        Some(idx)
+    }
+}
@@ -0,0 +1,71 @@
+//! Changing the number of active channels at runtime.
+
+use super::Graph;
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::{self, Frame, Sample};
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// The number of channels of each frame that carry audio.
+    ///
+    /// By default, this is `F::CHANNELS`.
+    pub fn channels(&self) -> usize {
+        self.state.channels
+    }
+
+    /// Set the number of channels of each frame that carry audio, e.g. after the host switches
+    /// from a stereo device to a 5.1 device.
+    ///
+    /// This allows a **Graph** whose frame type is wide enough for the largest expected layout to
+    /// be reconfigured rather than reconstructed with a new frame type. Channels from `channels`
+    /// upwards are silenced in the **Graph**'s output.
+    ///
+    /// This should be called at a safe point between requests for audio. All connection, feedback
+    /// and latency compensation buffers are cleared so that no audio rendered for the old layout
+    /// leaks into the new one, and every node is notified via `Node::channels_changed`.
+    ///
+    /// **Panics** if `channels` is `0` or greater than `F::CHANNELS`.
+    pub fn set_channels(&mut self, channels: usize) {
+        assert!(
+            channels > 0 && channels <= F::CHANNELS,
+            "the number of channels must be between 1 and the number of channels per frame"
+        );
+        self.state.channels = channels;
+        self.clear_signal_buffers();
+        for node in self.dag.node_weights_mut() {
+            node.channels_changed(channels);
+        }
+    }
+
+    /// Clear all connection, feedback and latency compensation buffers, along with any fading
+    /// connections.
+    pub(crate) fn clear_signal_buffers(&mut self) {
+        for connection in self.dag.edge_weights_mut() {
+            dasp::slice::equilibrium(&mut connection.buffer);
+            dasp::slice::equilibrium(&mut connection.previous);
+            connection.silent = true;
+            connection.compensation.clear();
+        }
+        self.clear_feedback_buffers();
+        self.clear_fading_connections();
+    }
+
+    /// Silence the inactive channels of the given output.
+    pub(crate) fn silence_inactive_channels(&self, output: &mut [F]) {
+        let channels = self.state.channels;
+        if channels >= F::CHANNELS {
+            return;
+        }
+        dasp::slice::map_in_place(output, |frame| {
+            F::from_fn(|ch| match frame.channel(ch) {
+                Some(&s) if ch < channels => s,
+                _ => F::Sample::EQUILIBRIUM,
+            })
+        });
+    }
+}
@@ -0,0 +1,303 @@
+//! Composing **Graph**s from prebuilt sub-patches, splitting them apart and removing nodes in
+//! batches.
+
+use super::{EdgeIndex, Graph, GraphState, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use daggy::Walker;
+use dasp::Frame;
+
+/// Maps the old indices of nodes and connections to their new indices after a **Graph** was
+/// appended to another, or after nodes were removed from a **Graph** in a batch.
+///
+/// See [`Graph::append`](./struct.Graph.html#method.append) and
+/// [`Graph::remove_nodes`](./struct.Graph.html#method.remove_nodes).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexMap<Ix = usize>
+where
+    Ix: IndexType,
+{
+    nodes: Vec<Option<NodeIndex<Ix>>>,
+    connections: Vec<Option<EdgeIndex<Ix>>>,
+}
+
+impl<Ix> IndexMap<Ix>
+where
+    Ix: IndexType,
+{
+    /// The new index of the node that had the given index, or `None` if it was removed.
+    pub fn node(&self, idx: NodeIndex<Ix>) -> Option<NodeIndex<Ix>> {
+        self.nodes.get(idx.index()).cloned().flatten()
+    }
+
+    /// The new index of the connection that had the given index, or `None` if it was removed.
+    pub fn connection(&self, edge: EdgeIndex<Ix>) -> Option<EdgeIndex<Ix>> {
+        self.connections.get(edge.index()).cloned().flatten()
+    }
+
+    /// The new index of each node, in the order of their old indices, or `None` for each node
+    /// that was removed.
+    pub fn nodes(&self) -> &[Option<NodeIndex<Ix>>] {
+        &self.nodes
+    }
+
+    /// The new index of each connection, in the order of their old indices, or `None` for each
+    /// connection that was removed.
+    pub fn connections(&self) -> &[Option<EdgeIndex<Ix>>] {
+        &self.connections
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Move all nodes and connections of `other` into this graph, returning the new index of
+    /// each of its nodes and connections.
+    ///
+    /// Each node keeps its bypass, mute, solo and active range state along with any pending
+    /// events, and each connection (including feedback connections) is re-created between the
+    /// moved nodes, keeping whether it is enabled. No connections are made between the two
+    /// graphs; use the returned **IndexMap** to connect the appended nodes as required.
+    ///
+    /// Each moved node is assigned a new **NodeId** within this graph, as ids are only unique
+    /// within the graph that assigned them.
+    ///
+    /// The master node of this graph is left unchanged. All other graph-level state of `other`,
+    /// such as its master node, external ports, control taps and settings, is discarded.
+    ///
+    /// The visit order is only re-prepared once.
+    pub fn append(&mut self, other: Graph<F, N, Ix>) -> IndexMap<Ix> {
+        let Graph { dag, state, .. } = other;
+        let GraphState {
+            node_meta,
+            feedback,
+            ..
+        } = state;
+        let (nodes, edges) = dag.into_graph().into_nodes_edges();
+
+        let mut new_nodes = Vec::with_capacity(nodes.len());
+        let mut new_edges = Vec::with_capacity(edges.len());
+        for (node, meta) in nodes.into_iter().zip(node_meta) {
+            new_nodes.push(self.dag.add_node(node.weight));
+            self.push_node_meta(meta);
+        }
+        for edge in edges {
+            let (src, dest) = (
+                new_nodes[edge.source().index()],
+                new_nodes[edge.target().index()],
+            );
+            let mut connection = self.state.pool.connection(self.state.double_buffered);
+            connection.enabled = edge.weight.enabled;
+            let new_edge = match self.dag.add_edge(src, dest, connection) {
+                Ok(new_edge) => new_edge,
+                Err(_) => unreachable!("the connections formed no cycle within `other`"),
+            };
+            new_edges.push(new_edge);
+        }
+        for fb in feedback {
+            let (src, dest) = (
+                new_nodes[fb.source().index()],
+                new_nodes[fb.destination().index()],
+            );
+            self.add_feedback_connection(src, dest)
+                .expect("the nodes were appended");
+        }
+        self.prepare_visit_order();
+        for &idx in &new_nodes {
+            if self.state.node_meta[idx.index()].bypassed {
+                self.prepare_bypass_delay(idx);
+            }
+        }
+        IndexMap {
+            nodes: new_nodes.into_iter().map(Some).collect(),
+            connections: new_edges.into_iter().map(Some).collect(),
+        }
+    }
+
+    /// Remove the given nodes from this graph along with all of their connections, returning
+    /// them as a new graph.
+    ///
+    /// The node at `nodes[i]` becomes the node at index `i` within the new graph, keeping its
+    /// bypass, mute, solo and active range state along with any pending events. Connections
+    /// (including feedback connections) between the given nodes are re-created within the new
+    /// graph, keeping whether they are enabled, while connections to the rest of this graph are
+    /// removed. The new graph has no master node and its buffers are prepared for the current
+    /// buffer size of this graph; all other settings take their defaults.
+    ///
+    /// Indices without a node and repeated indices are ignored.
+    ///
+    /// **Note:** This method may shift (and in turn invalidate) previously returned node indices!
+    pub fn split_off(&mut self, nodes: &[NodeIndex<Ix>]) -> Graph<F, N, Ix> {
+        // The index of each split node within the new graph.
+        let mut new_indices = vec![None; self.dag.node_count()];
+        let mut split = Vec::with_capacity(nodes.len());
+        for &idx in nodes {
+            if let Some(new_idx @ None) = new_indices.get_mut(idx.index()) {
+                *new_idx = Some(NodeIndex::<Ix>::new(split.len()));
+                split.push(idx);
+            }
+        }
+        let new_index = |idx: NodeIndex<Ix>| new_indices[idx.index()];
+
+        let connections: Vec<_> = self
+            .dag
+            .raw_edges()
+            .iter()
+            .filter_map(|edge| {
+                let (src, dest) = (new_index(edge.source())?, new_index(edge.target())?);
+                Some((src, dest, edge.weight.enabled))
+            })
+            .collect();
+        let feedback: Vec<_> = self
+            .state
+            .feedback
+            .iter()
+            .filter_map(|fb| Some((new_index(fb.source())?, new_index(fb.destination())?)))
+            .collect();
+        let metas: Vec<_> = split
+            .iter()
+            .map(|idx| std::mem::take(&mut self.state.node_meta[idx.index()]))
+            .collect();
+
+        // Removing nodes from the highest index down ensures that removing one never shifts
+        // another that is yet to be removed.
+        let mut removal: Vec<_> = split.iter().cloned().enumerate().collect();
+        removal.sort_by_key(|&(_, idx)| std::cmp::Reverse(idx.index()));
+        let mut removed: Vec<Option<N>> = split.iter().map(|_| None).collect();
+        for (i, idx) in removal {
+            removed[i] = self.remove_node(idx);
+        }
+
+        let mut graph = Graph::with_capacity_indexed(
+            split.len(),
+            connections.len(),
+            self.state.dry_buffer.len(),
+        );
+        for (node, meta) in removed.into_iter().zip(metas) {
+            let idx = graph.add_node(node.expect("the node was checked"));
+            graph.state.node_meta[idx.index()] = meta;
+        }
+        let pairs = connections.iter().map(|&(src, dest, _)| (src, dest));
+        let edges = graph
+            .add_connections(pairs)
+            .expect("the connections formed no cycle within this graph");
+        for (edge, &(_, _, enabled)) in edges.into_iter().zip(&connections) {
+            graph.dag[edge].enabled = enabled;
+        }
+        for (src, dest) in feedback {
+            graph
+                .add_feedback_connection(src, dest)
+                .expect("the nodes were split off");
+        }
+        graph
+    }
+
+    /// Remove all of the given nodes along with their connections, re-preparing the visit order
+    /// only once, and return the new index of each remaining node and connection.
+    ///
+    /// Unlike calling `remove_node` for each node, which may shift the index of another node that
+    /// is yet to be removed, each index refers to the node that had that index before any were
+    /// removed. Indices without a node and repeated indices are ignored.
+    ///
+    /// Resets the master to `None` if the master node is removed.
+    pub fn remove_nodes<I>(&mut self, nodes: I) -> IndexMap<Ix>
+    where
+        I: IntoIterator<Item = NodeIndex<Ix>>,
+    {
+        let mut remove = vec![false; self.dag.node_count()];
+        for idx in nodes {
+            if let Some(remove) = remove.get_mut(idx.index()) {
+                *remove = true;
+            }
+        }
+        self.remove_flagged_nodes(&remove, |_, _| ())
+    }
+
+    /// Remove every node for which `keep` returns `false` along with its connections,
+    /// re-preparing the visit order only once, and return the new index of each remaining node
+    /// and connection.
+    ///
+    /// `keep` is called once for each node in the order of their indices.
+    ///
+    /// Resets the master to `None` if the master node is removed.
+    pub fn retain_nodes<P>(&mut self, mut keep: P) -> IndexMap<Ix>
+    where
+        P: FnMut(NodeIndex<Ix>, &N) -> bool,
+    {
+        let remove: Vec<bool> = self
+            .dag
+            .raw_nodes()
+            .iter()
+            .enumerate()
+            .map(|(i, node)| !keep(NodeIndex::new(i), &node.weight))
+            .collect();
+        self.remove_flagged_nodes(&remove, |_, _| ())
+    }
+
+    /// Remove each node whose index is flagged in `remove`, passing it to `removed` along with its
+    /// original index and tracking where the nodes and connections shifted into the indices of
+    /// removed ones end up.
+    pub(crate) fn remove_flagged_nodes<R>(
+        &mut self,
+        remove: &[bool],
+        mut removed: R,
+    ) -> IndexMap<Ix>
+    where
+        R: FnMut(NodeIndex<Ix>, N),
+    {
+        // The original index of the node and connection at each current index.
+        let num_edges = self.dag.edge_count();
+        let mut node_origins: Vec<usize> = (0..self.dag.node_count()).collect();
+        let mut edge_origins: Vec<usize> = (0..num_edges).collect();
+        let mut num_removed = 0;
+        // Iterate in reverse so that the node shifted into a removed node's index is never one
+        // that is yet to be removed.
+        for i in (0..remove.len()).rev().filter(|&i| remove[i]) {
+            let idx = NodeIndex::new(i);
+            // Remove the connections one at a time, as each shifts the last connection into its
+            // index.
+            loop {
+                let edge = match self.inputs(idx).next_edge(self) {
+                    Some(edge) => edge,
+                    None => match self.outputs(idx).next_edge(self) {
+                        Some(edge) => edge,
+                        None => break,
+                    },
+                };
+                let mut connection = self.dag.remove_edge(edge).expect("no connection for index");
+                self.state.pool.recycle(&mut connection);
+                edge_origins.swap_remove(edge.index());
+            }
+            let last = NodeIndex::new(self.dag.node_count() - 1);
+            if self.state.maybe_master == Some(idx) {
+                self.state.maybe_master = None;
+            } else if self.state.maybe_master == Some(last) {
+                self.state.maybe_master = Some(idx);
+            }
+            let node = self.dag.remove_node(idx).expect("no node for index");
+            self.state.node_meta.swap_remove(i);
+            node_origins.swap_remove(i);
+            self.remove_node_state(idx, last);
+            removed(idx, node);
+            num_removed += 1;
+        }
+        if num_removed > 0 {
+            self.prepare_visit_order();
+        }
+
+        let mut map = IndexMap {
+            nodes: vec![None; remove.len()],
+            connections: vec![None; num_edges],
+        };
+        for (i, &origin) in node_origins.iter().enumerate() {
+            map.nodes[origin] = Some(NodeIndex::new(i));
+        }
+        for (i, &origin) in edge_origins.iter().enumerate() {
+            map.connections[origin] = Some(EdgeIndex::new(i));
+        }
+        map
+    }
+}
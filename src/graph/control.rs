@@ -0,0 +1,210 @@
+//! Control taps publishing decimated snapshots of connections and parameters, for systems such as
+//! haptics, lighting or visualisation that follow the audio at a much lower rate.
+
+use super::{EdgeIndex, Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::{Frame, Sample};
+use std::sync::mpsc;
+
+/// The number of values that may be queued for a **ControlTap** before further values are
+/// dropped.
+const CONTROL_TAP_CAPACITY: usize = 256;
+
+/// The signal followed by a control tap.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ControlSource<Ix = usize>
+where
+    Ix: IndexType,
+{
+    /// The peak absolute sample value across all channels of the connection at the given index.
+    Connection(EdgeIndex<Ix>),
+    /// The latest value of a parameter reported by the node at the given index via
+    /// `Node::param_changes`.
+    Param(NodeIndex<Ix>, usize),
+}
+
+/// A single decimated value published by a control tap.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ControlValue {
+    /// The transport position in frames at which the value was taken.
+    pub position: u64,
+    /// The value of the source over the preceding control period.
+    pub value: f32,
+}
+
+/// The receiving end of a control tap, returned by
+/// [`Graph::add_control_tap`](./struct.Graph.html#method.add_control_tap).
+///
+/// The tap is removed from the **Graph** once this is dropped.
+#[derive(Debug)]
+pub struct ControlTap {
+    receiver: mpsc::Receiver<ControlValue>,
+}
+
+/// The state of a control tap maintained by the **Graph**.
+#[derive(Clone, Debug)]
+pub(crate) struct TapState<Ix>
+where
+    Ix: IndexType,
+{
+    source: ControlSource<Ix>,
+    /// The input and output nodes of the followed connection, by which its index is updated as
+    /// other connections are removed.
+    endpoints: Option<(NodeIndex<Ix>, NodeIndex<Ix>)>,
+    rate_hz: f64,
+    /// The number of frames elapsed within the current control period.
+    phase: f64,
+    /// The value accumulated over the current control period.
+    value: Option<f32>,
+    sender: mpsc::SyncSender<ControlValue>,
+}
+
+impl ControlTap {
+    /// Yield all values published since this was last called, without blocking.
+    pub fn try_iter(&self) -> mpsc::TryIter<'_, ControlValue> {
+        self.receiver.try_iter()
+    }
+
+    /// The most recently published value, discarding any older values.
+    pub fn latest(&self) -> Option<ControlValue> {
+        self.receiver.try_iter().last()
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Follow the given source, publishing its value `rate_hz` times per second of rendered audio.
+    ///
+    /// Values are published on the audio thread without blocking or allocating. If the receiver
+    /// falls behind, new values are dropped until it catches up.
+    ///
+    /// The tap keeps following its source as other nodes and connections are removed and their
+    /// indices shift. Taps are removed along with their node or connection, after which the
+    /// **ControlTap** receives no more values.
+    pub fn add_control_tap(&mut self, source: ControlSource<Ix>, rate_hz: f64) -> ControlTap {
+        let (sender, receiver) = mpsc::sync_channel(CONTROL_TAP_CAPACITY);
+        let endpoints = match source {
+            ControlSource::Connection(edge) => self.dag.edge_endpoints(edge),
+            ControlSource::Param(..) => None,
+        };
+        self.state.control_taps.push(TapState {
+            source,
+            endpoints,
+            rate_hz,
+            phase: 0.0,
+            value: None,
+            sender,
+        });
+        ControlTap { receiver }
+    }
+
+    /// The number of control taps whose receivers have not yet been found to be dropped.
+    pub fn control_tap_count(&self) -> usize {
+        self.state.control_taps.len()
+    }
+
+    /// Update the parameter taps of the node at the given index with its collected parameter
+    /// changes.
+    pub(crate) fn update_param_taps(&mut self, idx: NodeIndex<Ix>) {
+        for tap in &mut self.state.control_taps {
+            match tap.source {
+                ControlSource::Param(node, param) if node == idx => {
+                    let latest = self
+                        .state
+                        .param_changes
+                        .iter()
+                        .rev()
+                        .find(|c| c.param == param);
+                    if let Some(change) = latest {
+                        tap.value = Some(change.value);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Advance all control taps over the `len` frames rendered from transport frame `position`,
+    /// publishing a value at the end of each control period.
+    pub(crate) fn update_control_taps(&mut self, position: u64, len: usize, sample_hz: f64) {
+        let dag = &self.dag;
+        self.state.control_taps.retain_mut(|tap| {
+            let period = sample_hz / tap.rate_hz.max(f64::MIN_POSITIVE);
+            let buffer = match tap.source {
+                ControlSource::Connection(edge) => dag.edge_weight(edge).map(|c| &c.buffer[..]),
+                ControlSource::Param(..) => None,
+            };
+            for i in 0..len {
+                if let Some(frame) = buffer.and_then(|buffer| buffer.get(i)) {
+                    let peak = frame.channels().fold(0.0f32, |peak, s| {
+                        peak.max(s.to_float_sample().to_sample::<f32>().abs())
+                    });
+                    tap.value = Some(tap.value.unwrap_or(0.0).max(peak));
+                }
+                tap.phase += 1.0;
+                if tap.phase >= period {
+                    tap.phase -= period;
+                    if let Some(value) = tap.value {
+                        let value = ControlValue {
+                            position: position + i as u64,
+                            value,
+                        };
+                        if let Err(mpsc::TrySendError::Disconnected(_)) = tap.sender.try_send(value)
+                        {
+                            return false;
+                        }
+                    }
+                    // Connection peaks are measured anew each period.
+                    if buffer.is_some() {
+                        tap.value = None;
+                    }
+                }
+            }
+            true
+        });
+    }
+
+    /// Update the control taps after the node at `idx` was removed and the last node was shifted
+    /// into its place.
+    pub(crate) fn remove_node_control_taps(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        self.state
+            .control_taps
+            .retain(|tap| match (tap.source, tap.endpoints) {
+                (ControlSource::Param(node, _), _) => node != idx,
+                (ControlSource::Connection(_), Some((src, dest))) => src != idx && dest != idx,
+                (ControlSource::Connection(_), None) => true,
+            });
+        let shifted = |n: NodeIndex<Ix>| if n == last { idx } else { n };
+        for tap in &mut self.state.control_taps {
+            if let ControlSource::Param(ref mut node, _) = tap.source {
+                *node = shifted(*node);
+            }
+            if let Some((src, dest)) = tap.endpoints {
+                tap.endpoints = Some((shifted(src), shifted(dest)));
+            }
+        }
+    }
+
+    /// Find the current index of each followed connection after the **Graph** was restructured,
+    /// removing the taps of connections that no longer exist.
+    pub(crate) fn prepare_control_taps(&mut self) {
+        let dag = &self.dag;
+        self.state
+            .control_taps
+            .retain_mut(|tap| match tap.endpoints {
+                Some((src, dest)) => match dag.find_edge(src, dest) {
+                    Some(edge) => {
+                        tap.source = ControlSource::Connection(edge);
+                        true
+                    }
+                    None => false,
+                },
+                None => true,
+            });
+    }
+}
@@ -0,0 +1,103 @@
+//! Verification that a **Graph** renders the same output regardless of the buffer size, so that
+//! offline and real-time renders of a session agree.
+
+use super::Graph;
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::{Frame, Sample};
+
+/// The result of rendering a **Graph** with several buffer sizes, returned by
+/// [`Graph::verify_block_sizes`](./struct.Graph.html#method.verify_block_sizes).
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockSizeReport {
+    /// The buffer sizes compared, the first of which is the reference for the others.
+    pub block_sizes: Vec<usize>,
+    /// The number of frames rendered with each buffer size.
+    pub frames: usize,
+    /// The largest absolute difference between a sample rendered with the reference buffer size
+    /// and the same sample rendered with any other.
+    pub max_difference: f32,
+    /// The first frame of the render at which any output differed from the reference, along with
+    /// the buffer size with which it differed, or `None` if every render was bit-identical.
+    pub first_difference: Option<(usize, usize)>,
+}
+
+impl BlockSizeReport {
+    /// Whether every buffer size rendered exactly the same output.
+    pub fn is_bit_identical(&self) -> bool {
+        self.first_difference.is_none()
+    }
+
+    /// Whether every sample differed from the reference by no more than `tolerance`.
+    pub fn is_within(&self, tolerance: f32) -> bool {
+        self.max_difference <= tolerance
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F> + Clone,
+    Ix: IndexType,
+{
+    /// Render `frames` frames from a copy of the **Graph** in its current state with each of the
+    /// given buffer sizes, comparing the output of each with that of the first.
+    ///
+    /// The **Graph** itself is left untouched, and the copies are detached from any
+    /// **ParamHandle**s so that values pending delivery are left for the **Graph**. Events
+    /// scheduled on the **Graph** are rendered by each copy.
+    ///
+    /// The **Graph** renders events, parameter smoothing, coalescing and tempo changes at the same
+    /// frames whatever the buffer size, so differences point to nodes whose output depends on how
+    /// their buffer is divided. Two sources of difference are inherent to the **Graph**:
+    /// feedback connections delay their audio by exactly one buffer, and gain ramps applied to
+    /// connections, bypass and crossfades may differ by rounding error in the last bits.
+    ///
+    /// **Panics** if any buffer size is `0`.
+    pub fn verify_block_sizes(
+        &self,
+        block_sizes: &[usize],
+        frames: usize,
+        sample_hz: f64,
+    ) -> BlockSizeReport {
+        assert!(block_sizes.iter().all(|&size| size > 0));
+        let render = |block_size: usize| {
+            let mut graph = self.clone();
+            graph.state.param_handles.clear();
+            let mut output = vec![F::EQUILIBRIUM; frames];
+            for block in output.chunks_mut(block_size) {
+                graph.audio_requested(block, sample_hz);
+            }
+            output
+        };
+        let mut report = BlockSizeReport {
+            block_sizes: block_sizes.to_vec(),
+            frames,
+            max_difference: 0.0,
+            first_difference: None,
+        };
+        let (&reference_size, others) = match block_sizes.split_first() {
+            Some(split) => split,
+            None => return report,
+        };
+        let reference = render(reference_size);
+        for &block_size in others {
+            let output = render(block_size);
+            for (i, (a, b)) in reference.iter().zip(&output).enumerate() {
+                for (a, b) in a.channels().zip(b.channels()) {
+                    let a = a.to_float_sample().to_sample::<f32>();
+                    let b = b.to_float_sample().to_sample::<f32>();
+                    let difference = (a - b).abs();
+                    report.max_difference = report.max_difference.max(difference);
+                    if a.to_bits() != b.to_bits() {
+                        let first = report.first_difference.get_or_insert((i, block_size));
+                        if i < first.0 {
+                            *first = (i, block_size);
+                        }
+                    }
+                }
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,144 @@
+//! Switching the audio device that drives the **Graph** without reconstructing it, e.g. when
+//! headphones are unplugged and the host falls back to the built-in speakers.
+
+use super::{ramp, Graph};
+use crate::node::Node;
+use crate::slice;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+/// The default number of frames over which the output is faded out before and faded in after a
+/// device switch.
+const DEFAULT_DEVICE_FADE_FRAMES: usize = 256;
+
+/// The stream configuration of the audio device driving a **Graph**.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DeviceConfig {
+    /// The sample rate of the stream in hz.
+    pub sample_hz: f64,
+    /// The number of output channels of the stream.
+    pub channels: usize,
+    /// The number of frames requested by each callback of the stream.
+    pub buffer_size: usize,
+}
+
+/// The state of a device switch.
+#[derive(Clone, Debug)]
+pub(crate) struct DeviceState {
+    /// The configuration of the current device, if any has been set.
+    config: Option<DeviceConfig>,
+    fade_frames: usize,
+    /// The gain applied to the output, moving towards `target`.
+    gain: f32,
+    /// `0.0` while fading out ahead of a switch and `1.0` otherwise.
+    target: f32,
+}
+
+impl Default for DeviceState {
+    fn default() -> Self {
+        DeviceState {
+            config: None,
+            fade_frames: DEFAULT_DEVICE_FADE_FRAMES,
+            gain: 1.0,
+            target: 1.0,
+        }
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Set the number of frames over which the output is faded out by `begin_device_switch` and
+    /// faded back in after `switch_device`.
+    ///
+    /// By default, this is `256`. If `0`, the output is cut and restored immediately.
+    pub fn set_device_fade_frames(&mut self, frames: usize) {
+        self.state.device.fade_frames = frames;
+    }
+
+    /// The number of frames over which the output is faded out and in around a device switch.
+    pub fn device_fade_frames(&self) -> usize {
+        self.state.device.fade_frames
+    }
+
+    /// The configuration of the device most recently passed to `switch_device`, if any.
+    pub fn device(&self) -> Option<DeviceConfig> {
+        self.state.device.config
+    }
+
+    /// Begin fading the output out ahead of a device switch.
+    ///
+    /// A backend that is notified of a device change while the old stream is still running
+    /// should call this and keep rendering into the old stream until
+    /// `is_ready_for_device_switch` returns `true`, then stop the old stream, call
+    /// `switch_device` and start the new one. If the old device has already gone, e.g. because
+    /// it was unplugged, `switch_device` may be called straight away.
+    ///
+    /// The fade is applied to the output rendered via `Node::audio_requested` and
+    /// `audio_requested_dyn`, but not via `audio_requested_from`.
+    pub fn begin_device_switch(&mut self) {
+        self.state.device.target = 0.0;
+        if self.state.device.fade_frames == 0 {
+            self.state.device.gain = 0.0;
+        }
+    }
+
+    /// Whether the output has finished fading out after `begin_device_switch`, so that the old
+    /// stream may be stopped without a click.
+    pub fn is_ready_for_device_switch(&self) -> bool {
+        self.state.device.target == 0.0 && self.state.device.gain == 0.0
+    }
+
+    /// Reconfigure the **Graph** for the stream of a new device and fade its output back in.
+    ///
+    /// The nodes and their state are kept, so playback continues where it left off:
+    ///
+    /// - If the number of channels differs, the **Graph** is reconfigured via `set_channels`
+    ///   (limited to `F::CHANNELS`), notifying every node via `Node::channels_changed`.
+    /// - All connection, feedback and latency compensation buffers are cleared, so that no audio
+    ///   rendered for the old stream is heard on the new one.
+    /// - All buffers are prepared for the new buffer size via `prepare_buffers`, so that the
+    ///   first callback of the new stream does not allocate.
+    ///
+    /// Nodes are expected to adapt to a new sample rate when they are next passed it via
+    /// `Node::audio_requested`, as with any change in sample rate.
+    ///
+    /// This should be called at a safe point between requests for audio, i.e. while neither
+    /// stream is running.
+    pub fn switch_device(&mut self, config: DeviceConfig) {
+        let channels = config.channels.min(F::CHANNELS).max(1);
+        if channels != self.state.channels {
+            self.set_channels(channels);
+        } else {
+            self.clear_signal_buffers();
+        }
+        self.prepare_buffers(config.buffer_size);
+        self.state.device.config = Some(config);
+        self.state.device.gain = if self.state.device.fade_frames == 0 {
+            1.0
+        } else {
+            0.0
+        };
+        self.state.device.target = 1.0;
+    }
+
+    /// Apply the device switch fade to the **Graph**'s output.
+    pub(crate) fn apply_device_fade(&mut self, output: &mut [F]) {
+        let DeviceState {
+            fade_frames,
+            ref mut gain,
+            target,
+            ..
+        } = self.state.device;
+        if *gain == 1.0 && target == 1.0 {
+            return;
+        }
+        let step = 1.0 / fade_frames.max(1) as f32;
+        let (frames, from, to) = ramp::advance_gain(gain, target, step, output.len());
+        slice::apply_ramp(&mut output[..frames], from, to);
+        slice::apply_ramp(&mut output[frames..], *gain, *gain);
+    }
+}
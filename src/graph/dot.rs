@@ -0,0 +1,110 @@
+//! Export of the **Graph** to the Graphviz DOT language for visually debugging routing.
+
+use super::{Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::fmt::{Debug, Write};
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F> + Debug,
+    Ix: IndexType,
+{
+    /// Describe the **Graph** in the Graphviz DOT language.
+    ///
+    /// Each node is labelled with its index and its `Debug` representation, and each connection
+    /// is drawn as an arrow from the input to the output. The node from which audio is requested
+    /// is drawn with a double outline, disabled connections are dashed and feedback connections
+    /// are dotted. Render the result with e.g. `dot -Tsvg graph.dot -o graph.svg`.
+    pub fn to_dot(&self) -> String {
+        self.write_dot(false)
+    }
+
+    /// The same as [`to_dot`](./struct.Graph.html#method.to_dot), but also labels each node with
+    /// its path latency and its state (bypassed, muted, soloed, panicked or silent during the last
+    /// request for audio).
+    pub fn to_dot_with_stats(&self) -> String {
+        self.write_dot(true)
+    }
+
+    fn write_dot(&self, stats: bool) -> String {
+        let mut dot = String::new();
+        // Writing to a `String` never fails.
+        let _ = self.write_dot_to(&mut dot, stats);
+        dot
+    }
+
+    fn write_dot_to(&self, dot: &mut String, stats: bool) -> std::fmt::Result {
+        let out_node = self.output_node();
+        writeln!(dot, "digraph {{")?;
+        writeln!(dot, "    node [shape=box];")?;
+        for (i, node) in self.dag.raw_nodes().iter().enumerate() {
+            let idx = NodeIndex::new(i);
+            let mut label = format!("{}: {:?}", i, node.weight);
+            if stats {
+                self.write_dot_stats(&mut label, idx)?;
+            }
+            write!(dot, "    {} [label=\"{}\"", i, escape(&label))?;
+            if out_node == Some(idx) {
+                write!(dot, ", peripheries=2, style=bold")?;
+            }
+            writeln!(dot, "];")?;
+        }
+        for edge in self.dag.raw_edges() {
+            write!(
+                dot,
+                "    {} -> {}",
+                edge.source().index(),
+                edge.target().index()
+            )?;
+            if !edge.weight.enabled {
+                write!(dot, " [style=dashed]")?;
+            }
+            writeln!(dot, ";")?;
+        }
+        for fb in self.feedback_connections() {
+            writeln!(
+                dot,
+                "    {} -> {} [style=dotted, constraint=false];",
+                fb.source().index(),
+                fb.destination().index()
+            )?;
+        }
+        writeln!(dot, "}}")
+    }
+
+    /// Append the path latency and state of the node at the given index to `label`.
+    fn write_dot_stats(&self, label: &mut String, idx: NodeIndex<Ix>) -> std::fmt::Result {
+        if let Some(latency) = self.path_latency(idx) {
+            write!(label, "\nlatency: {}", latency)?;
+        }
+        let meta = &self.state.node_meta[idx.index()];
+        let states = [
+            (meta.bypassed, "bypassed"),
+            (meta.muted, "muted"),
+            (meta.soloed, "soloed"),
+            (meta.panicked, "panicked"),
+            (meta.silent, "silent"),
+        ];
+        for &(_, state) in states.iter().filter(|(is, _)| *is) {
+            write!(label, "\n{}", state)?;
+        }
+        Ok(())
+    }
+}
+
+/// Escape quotes, backslashes and newlines for use within a quoted DOT string.
+fn escape(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
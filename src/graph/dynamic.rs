@@ -0,0 +1,43 @@
+//! Rendering into buffers whose number of channels is only known at runtime.
+
+use super::Graph;
+use crate::buffer::{from_dyn_frames, to_dyn_frames, DynFrames};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Request audio from the **Graph**'s output node into a buffer whose number of channels is
+    /// only known at runtime, such as the buffer of an audio device.
+    ///
+    /// Choose a frame type wide enough for the largest expected channel layout. Whenever the
+    /// number of channels of `buffer` differs from the **Graph**'s active channels, the
+    /// **Graph** is reconfigured via `set_channels` (limited to `F::CHANNELS`) before rendering,
+    /// so a device change needs no new **Graph**. Channels of `buffer` beyond `F::CHANNELS` are
+    /// set to equilibrium.
+    ///
+    /// The contents of `buffer` are passed to the **Graph** in the same way as for
+    /// `Node::audio_requested`.
+    pub fn audio_requested_dyn(&mut self, mut buffer: DynFrames<F::Sample>, sample_hz: f64) {
+        let channels = std::cmp::min(buffer.channels(), F::CHANNELS);
+        if channels != self.state.channels {
+            self.set_channels(channels);
+        }
+        if self.state.dyn_buffer.len() != buffer.frames() {
+            self.note_alloc("the size of the runtime-channel buffer changed");
+            self.state
+                .dyn_buffer
+                .resize(buffer.frames(), F::EQUILIBRIUM);
+        }
+        let mut frames = std::mem::take(&mut self.state.dyn_buffer);
+        from_dyn_frames(&buffer, &mut frames);
+        self.audio_requested(&mut frames, sample_hz);
+        to_dyn_frames(&frames, &mut buffer);
+        self.state.dyn_buffer = frames;
+    }
+}
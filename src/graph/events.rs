@@ -0,0 +1,179 @@
+//! Delivery of events to nodes via their fixed-capacity event queues.
+
+use super::{replace, Graph, NodeIndex, Notification, RequestError, SMOOTHING_INTERVAL};
+use crate::event::{ump, Event, EventQueue, OverflowPolicy};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::ops::Range;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Queue an event for the node at the given index, to take effect at the start of the next
+    /// request for audio.
+    ///
+    /// See [`schedule_event`](./struct.Graph.html#method.schedule_event) for details.
+    pub fn send_event(&mut self, idx: NodeIndex<Ix>, event: Event) -> Result<(), RequestError<Ix>> {
+        let frame = self.state.position;
+        self.schedule_event(idx, frame, event)
+    }
+
+    /// Queue an event for the node at the given index, to take effect at the given transport
+    /// frame.
+    ///
+    /// When audio is requested, the node is rendered up to the frame of each of its events
+    /// before the event is delivered via `Node::handle_event`, splitting the node's buffer at
+    /// the event boundaries. Events for frames that have already been rendered are delivered at
+    /// the start of the next request. Events are delivered even if the node would otherwise be
+    /// skipped.
+    ///
+    /// If the node's queue is full, its overflow policy is applied and the number of dropped
+    /// events is reported via `Notification::EventsDropped` when the node is next rendered. A
+    /// node's queue is allocated when the first event is sent to it, unless it was prepared via
+    /// `set_event_queue`.
+    pub fn schedule_event(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        frame: u64,
+        event: Event,
+    ) -> Result<(), RequestError<Ix>> {
+        let allocated = self
+            .state
+            .node_meta
+            .get(idx.index())
+            .ok_or(RequestError::NoNode(idx))?
+            .events
+            .is_allocated();
+        if !allocated {
+            self.note_alloc("an event was sent to a node whose event queue was not prepared");
+        }
+        self.state.node_meta[idx.index()]
+            .events
+            .push_at(frame, event);
+        Ok(())
+    }
+
+    /// Decode a stream of Universal MIDI Packets and queue the resulting events for the node at
+    /// the given index, to take effect at the given transport frame.
+    ///
+    /// See the [`ump`](../event/ump/index.html) module for the supported messages.
+    ///
+    /// Returns the number of events queued.
+    pub fn schedule_ump(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        frame: u64,
+        words: &[u32],
+    ) -> Result<usize, RequestError<Ix>> {
+        self.check_node(idx)?;
+        let mut count = 0;
+        for event in ump::decode(words) {
+            self.schedule_event(idx, frame, event)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Replace the event queue of the node at the given index with an empty queue of the given
+    /// capacity and overflow policy, allocating it up front.
+    ///
+    /// Any events that are queued for the node are discarded.
+    pub fn set_event_queue(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<(), RequestError<Ix>> {
+        let meta = self
+            .state
+            .node_meta
+            .get_mut(idx.index())
+            .ok_or(RequestError::NoNode(idx))?;
+        meta.events = EventQueue::new(capacity, policy);
+        Ok(())
+    }
+
+    /// The event queue of the node at the given index.
+    pub fn event_queue(&self, idx: NodeIndex<Ix>) -> Option<&EventQueue> {
+        self.state
+            .node_meta
+            .get(idx.index())
+            .map(|meta| &meta.events)
+    }
+
+    /// Set whether redundant controller and parameter changes are coalesced before being
+    /// delivered.
+    ///
+    /// When enabled, only the first and last change to each controller or parameter within each
+    /// interval of `SMOOTHING_INTERVAL` frames are delivered to each node (see
+    /// `EventQueue::coalesce_within`). This reduces the number of events that nodes must handle
+    /// per block when driven by high-resolution controllers. The intervals are aligned to
+    /// transport frames, so the same changes are delivered regardless of the buffer size,
+    /// provided that they were scheduled before the interval began to render.
+    ///
+    /// By default, this is `false` and every change is delivered.
+    pub fn set_coalesce_events(&mut self, coalesce: bool) {
+        self.state.coalesce_events = coalesce;
+    }
+
+    /// Whether redundant controller and parameter changes are coalesced before being delivered.
+    pub fn coalesces_events(&self) -> bool {
+        self.state.coalesce_events
+    }
+
+    /// Coalesce the changes queued for the node at the given index within each interval that
+    /// begins within the given block, if enabled.
+    pub(crate) fn coalesce_queued_events(&mut self, idx: NodeIndex<Ix>, block: &Range<u64>) {
+        if !self.state.coalesce_events {
+            return;
+        }
+        let interval = SMOOTHING_INTERVAL as u64;
+        let events = &mut self.state.node_meta[idx.index()].events;
+        let mut start = block.start.div_ceil(interval) * interval;
+        while start < block.end {
+            events.coalesce_within(start..start + interval);
+            start += interval;
+        }
+    }
+
+    /// Deliver the events queued for the node at the given index that take effect before the
+    /// given frame, reporting any that were dropped.
+    pub(crate) fn dispatch_events(&mut self, idx: NodeIndex<Ix>, before: u64) {
+        let meta = &mut self.state.node_meta[idx.index()];
+        let node = &mut self.dag[idx];
+        // A node that is being crossfaded out receives the events of its replacement.
+        let mut previous = replace::previous_node_mut(&mut self.replaced, idx);
+        while let Some(event) = meta.events.pop_before(before) {
+            let event = match meta
+                .smoothing
+                .filter(event, self.state.default_smoothing_ms)
+            {
+                Some(event) => event,
+                None => continue,
+            };
+            node.handle_event(&event);
+            if let Some(ref mut previous) = previous {
+                previous.handle_event(&event);
+            }
+        }
+        let dropped = meta.events.take_dropped();
+        if dropped > 0 {
+            self.state
+                .notifier
+                .send(Notification::EventsDropped(idx, dropped));
+        }
+    }
+
+    /// Whether any events queued for the node at the given index take effect before the given
+    /// frame.
+    pub(crate) fn has_events_before(&self, idx: NodeIndex<Ix>, before: u64) -> bool {
+        self.state.node_meta[idx.index()]
+            .events
+            .next_frame()
+            .is_some_and(|frame| frame < before)
+    }
+}
@@ -0,0 +1,238 @@
+//! Named boundary nodes through which the **Graph** exchanges audio with the outside world, e.g.
+//! the physical ports of an audio device or the buses of a plugin.
+
+use super::aggregate::DeviceSender;
+use super::{resize_buffer_to, silence, Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+/// Whether an **External** carries audio into or out of the **Graph**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExternalKind {
+    /// Audio written by the host via `Graph::write_external_input`.
+    Input,
+    /// Audio read by the host via `Graph::external_output_buffer`.
+    Output,
+}
+
+/// A named boundary node of the **Graph**.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct External<Ix = usize> {
+    /// The name by which adapters address the boundary, e.g. `"mic"` or `"cue"`.
+    pub name: String,
+    /// Whether audio flows into or out of the **Graph**.
+    pub kind: ExternalKind,
+    /// The number of channels that the boundary carries.
+    pub channels: usize,
+    /// The index of the boundary node.
+    pub node: NodeIndex<Ix>,
+}
+
+/// The audio passed through an external input or output during the last request.
+#[derive(Clone, Debug)]
+pub(crate) struct ExternalBuffer<F> {
+    frames: Vec<F>,
+    silent: bool,
+    /// The queue to the device to which an external output is sent, if any.
+    pub(crate) device: DeviceSender,
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Add the given node as a named external input with the given number of channels.
+    ///
+    /// The audio written to the input via `write_external_input` is summed onto the node's input
+    /// each time audio is requested, so a node that passes its input straight through makes the
+    /// external audio available to all of its outputs.
+    ///
+    /// **Panics** if there is already an external input or output with the given name.
+    pub fn add_external_input(&mut self, name: &str, channels: usize, node: N) -> NodeIndex<Ix> {
+        self.add_external(name, ExternalKind::Input, channels, node)
+    }
+
+    /// Add the given node as a named external output with the given number of channels.
+    ///
+    /// The output node is rendered whenever audio is requested, along with all of its inputs,
+    /// even if it does not contribute to the output of the node from which audio is requested.
+    /// Its output may be read via `external_output_buffer` afterwards, or sent to the stream of
+    /// another device via `add_device_output`.
+    ///
+    /// **Panics** if there is already an external input or output with the given name.
+    pub fn add_external_output(&mut self, name: &str, channels: usize, node: N) -> NodeIndex<Ix> {
+        self.add_external(name, ExternalKind::Output, channels, node)
+    }
+
+    /// All external inputs and outputs in the order in which they were added.
+    pub fn externals(&self) -> &[External<Ix>] {
+        &self.state.externals
+    }
+
+    /// The external input or output with the given name.
+    pub fn external(&self, name: &str) -> Option<&External<Ix>> {
+        self.state
+            .externals
+            .iter()
+            .find(|external| external.name == name)
+    }
+
+    /// Write the audio for the next request to the external input with the given name.
+    ///
+    /// Returns `false` if there is no external input with the given name.
+    pub fn write_external_input(&mut self, name: &str, frames: &[F]) -> bool {
+        let position = self
+            .state
+            .externals
+            .iter()
+            .position(|e| e.name == name && e.kind == ExternalKind::Input);
+        let i = match position {
+            Some(i) => i,
+            None => return false,
+        };
+        if self.state.external_buffers[i].frames.len() != frames.len() {
+            self.note_alloc("the size of an external input changed");
+            resize_buffer_to(&mut self.state.external_buffers[i].frames, frames.len());
+        }
+        let buffer = &mut self.state.external_buffers[i];
+        dasp::slice::write(&mut buffer.frames, frames);
+        buffer.silent = silence::is_equilibrium(frames);
+        true
+    }
+
+    /// The audio rendered by the external output with the given name during the last request.
+    pub fn external_output_buffer(&self, name: &str) -> Option<&[F]> {
+        self.state
+            .externals
+            .iter()
+            .zip(&self.state.external_buffers)
+            .find(|(e, _)| e.name == name && e.kind == ExternalKind::Output)
+            .map(|(_, buffer)| &buffer.frames[..])
+    }
+
+    fn add_external(
+        &mut self,
+        name: &str,
+        kind: ExternalKind,
+        channels: usize,
+        node: N,
+    ) -> NodeIndex<Ix> {
+        assert!(
+            self.external(name).is_none(),
+            "there is already an external input or output named {:?}",
+            name
+        );
+        let node = self.add_node(node);
+        self.state.externals.push(External {
+            name: name.to_string(),
+            kind,
+            channels,
+            node,
+        });
+        self.state.external_buffers.push(ExternalBuffer {
+            frames: Vec::new(),
+            silent: true,
+            device: DeviceSender::detached(),
+        });
+        self.state.render_order_node = None;
+        node
+    }
+
+    /// The nodes of all external outputs.
+    pub(crate) fn external_output_nodes(&self) -> impl Iterator<Item = NodeIndex<Ix>> + '_ {
+        self.state
+            .externals
+            .iter()
+            .filter(|e| e.kind == ExternalKind::Output)
+            .map(|e| e.node)
+    }
+
+    /// Whether the node at the given index is an external input to which audio was written.
+    pub(crate) fn has_external_input(&self, idx: NodeIndex<Ix>) -> bool {
+        self.state
+            .externals
+            .iter()
+            .zip(&self.state.external_buffers)
+            .any(|(e, buffer)| e.node == idx && e.kind == ExternalKind::Input && !buffer.silent)
+    }
+
+    /// Whether audio that is not silent was written to any external input.
+    pub(crate) fn has_any_external_input(&self) -> bool {
+        self.state
+            .externals
+            .iter()
+            .zip(&self.state.external_buffers)
+            .any(|(e, buffer)| e.kind == ExternalKind::Input && !buffer.silent)
+    }
+
+    /// Sum the audio written to the external input at the given node onto `output`.
+    pub(crate) fn sum_external_input(&self, idx: NodeIndex<Ix>, output: &mut [F]) {
+        let inputs = self
+            .state
+            .externals
+            .iter()
+            .zip(&self.state.external_buffers);
+        for (_, buffer) in inputs.filter(|(e, _)| e.node == idx && e.kind == ExternalKind::Input) {
+            if buffer.silent {
+                continue;
+            }
+            let len = std::cmp::min(output.len(), buffer.frames.len());
+            super::mix::sum_onto(&mut output[..len], &buffer.frames[..len]);
+        }
+    }
+
+    /// Store the rendered output of the node at the given index if it is an external output.
+    pub(crate) fn write_external_output(&mut self, idx: NodeIndex<Ix>, output: &[F]) {
+        let assert_no_alloc = self.state.assert_no_alloc;
+        let outputs = self
+            .state
+            .externals
+            .iter()
+            .zip(&mut self.state.external_buffers);
+        for (_, buffer) in outputs.filter(|(e, _)| e.node == idx && e.kind == ExternalKind::Output)
+        {
+            if buffer.frames.len() != output.len() {
+                assert!(
+                    !assert_no_alloc,
+                    "the graph allocated while rendering: an external output was not prepared"
+                );
+                resize_buffer_to(&mut buffer.frames, output.len());
+            }
+            dasp::slice::write(&mut buffer.frames, output);
+            buffer.device.send(output);
+        }
+    }
+
+    /// Resize the buffers of all external outputs.
+    pub(crate) fn prepare_external_buffers(&mut self, buffer_size: usize) {
+        let outputs = self
+            .state
+            .externals
+            .iter()
+            .zip(&mut self.state.external_buffers);
+        for (_, buffer) in outputs.filter(|(e, _)| e.kind == ExternalKind::Output) {
+            resize_buffer_to(&mut buffer.frames, buffer_size);
+        }
+        resize_buffer_to(&mut self.state.output_stash, buffer_size);
+    }
+
+    /// Update the externals after the node at `idx` was removed and the last node was shifted
+    /// into its place.
+    pub(crate) fn remove_node_externals(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        let mut i = 0;
+        while i < self.state.externals.len() {
+            if self.state.externals[i].node == idx {
+                self.state.externals.remove(i);
+                self.state.external_buffers.remove(i);
+            } else {
+                if self.state.externals[i].node == last {
+                    self.state.externals[i].node = idx;
+                }
+                i += 1;
+            }
+        }
+    }
+}
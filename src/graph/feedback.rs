@@ -0,0 +1,187 @@
+//! Double-buffering of connection outputs and one-block-delay feedback connections.
+
+use super::{mix, resize_buffer_to, Graph, NodeIndex, RequestError};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::{self, Frame};
+
+/// A connection that feeds the output of a node back to one of its ancestors (or to itself).
+///
+/// As the destination node may be rendered before the source node, the destination always reads
+/// the audio rendered by the source during the *previous* request for audio. That is, feedback
+/// connections introduce a delay of exactly one buffer. They are not considered by the **Graph**'s
+/// latency compensation.
+#[derive(Clone, Debug)]
+pub struct FeedbackConnection<F, Ix = usize> {
+    source: NodeIndex<Ix>,
+    destination: NodeIndex<Ix>,
+    /// The audio rendered by the source node during the current request.
+    current: Vec<F>,
+    /// The audio rendered by the source node during the previous request.
+    previous: Vec<F>,
+}
+
+impl<F, Ix> FeedbackConnection<F, Ix>
+where
+    Ix: IndexType,
+{
+    /// The index of the node whose output is fed back.
+    pub fn source(&self) -> NodeIndex<Ix> {
+        self.source
+    }
+
+    /// The index of the node that receives the fed back output.
+    pub fn destination(&self) -> NodeIndex<Ix> {
+        self.destination
+    }
+
+    /// The audio rendered by the source node during the previous request for audio.
+    ///
+    /// This is the buffer that is summed onto the input of the destination node.
+    pub fn buffer(&self) -> &[F] {
+        &self.previous
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Enable or disable double-buffering of connection outputs.
+    ///
+    /// When enabled, each **Connection** keeps the audio rendered during the previous request in
+    /// addition to the current one, available via `Connection::previous_buffer`. This allows
+    /// probes and analysis running alongside the renderer to read stable data from the previous
+    /// request. Buffers are swapped rather than copied, so this costs memory but little time.
+    ///
+    /// Disabling double-buffering frees the previous buffers.
+    pub fn set_double_buffered(&mut self, double_buffered: bool) {
+        self.state.double_buffered = double_buffered;
+        if !double_buffered {
+            for connection in self.dag.edge_weights_mut() {
+                connection.previous = Vec::new();
+            }
+        }
+    }
+
+    /// Whether or not connection outputs are double-buffered.
+    pub fn is_double_buffered(&self) -> bool {
+        self.state.double_buffered
+    }
+
+    /// Feed the output of the `src` node back to the input of the `dest` node with a delay of
+    /// one buffer.
+    ///
+    /// Unlike `add_connection`, this may be used to form cycles, including connecting a node to
+    /// itself.
+    ///
+    /// Returns an error if there is no node for either `src` or `dest`.
+    pub fn add_feedback_connection(
+        &mut self,
+        src: NodeIndex<Ix>,
+        dest: NodeIndex<Ix>,
+    ) -> Result<(), RequestError<Ix>> {
+        self.check_node(src)?;
+        self.check_node(dest)?;
+        let len = self.state.dry_buffer.len();
+        self.state.feedback.push(FeedbackConnection {
+            source: src,
+            destination: dest,
+            current: vec![F::EQUILIBRIUM; len],
+            previous: vec![F::EQUILIBRIUM; len],
+        });
+        self.state.render_order_node = None;
+        Ok(())
+    }
+
+    /// Remove the feedback connection from `src` to `dest`.
+    ///
+    /// Returns `true` if a feedback connection was removed.
+    pub fn remove_feedback_connection(&mut self, src: NodeIndex<Ix>, dest: NodeIndex<Ix>) -> bool {
+        match self
+            .state
+            .feedback
+            .iter()
+            .position(|fb| fb.source == src && fb.destination == dest)
+        {
+            Some(i) => {
+                self.state.feedback.remove(i);
+                self.state.render_order_node = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// All feedback connections within the **Graph**.
+    pub fn feedback_connections(&self) -> &[FeedbackConnection<F, Ix>] {
+        &self.state.feedback
+    }
+
+    /// Resize the buffers of all feedback connections.
+    pub(crate) fn prepare_feedback_buffers(&mut self, buffer_size: usize) {
+        for fb in &mut self.state.feedback {
+            resize_buffer_to(&mut fb.current, buffer_size);
+            resize_buffer_to(&mut fb.previous, buffer_size);
+        }
+    }
+
+    /// Clear the buffers of all feedback connections.
+    pub(crate) fn clear_feedback_buffers(&mut self) {
+        for fb in &mut self.state.feedback {
+            dasp::slice::equilibrium(&mut fb.current);
+            dasp::slice::equilibrium(&mut fb.previous);
+        }
+    }
+
+    /// Sum the previous output of all feedback connections into the given node onto `output`.
+    pub(crate) fn sum_feedback(&self, node_idx: NodeIndex<Ix>, output: &mut [F]) {
+        for fb in self
+            .state
+            .feedback
+            .iter()
+            .filter(|fb| fb.destination == node_idx)
+        {
+            mix::sum_onto(output, &fb.previous);
+        }
+    }
+
+    /// Store the given output of a node in all feedback connections from that node.
+    pub(crate) fn write_feedback(&mut self, node_idx: NodeIndex<Ix>, output: &[F]) {
+        for fb in self
+            .state
+            .feedback
+            .iter_mut()
+            .filter(|fb| fb.source == node_idx)
+        {
+            dasp::slice::write(&mut fb.current, output);
+        }
+    }
+
+    /// Make the output of the current request available to the next and reset the current buffers
+    /// so that sources that were not rendered feed back silence.
+    pub(crate) fn advance_feedback(&mut self) {
+        for fb in &mut self.state.feedback {
+            std::mem::swap(&mut fb.current, &mut fb.previous);
+            dasp::slice::equilibrium(&mut fb.current);
+        }
+    }
+
+    /// Update feedback connections after the node at `idx` was removed and the last node was
+    /// shifted into its place.
+    pub(crate) fn remove_node_feedback(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        self.state
+            .feedback
+            .retain(|fb| fb.source != idx && fb.destination != idx);
+        for fb in &mut self.state.feedback {
+            if fb.source == last {
+                fb.source = idx;
+            }
+            if fb.destination == last {
+                fb.destination = idx;
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Detection of an idle **Graph**, so that rendering may fall back to filling silence and the host
+//! may suspend its audio stream to save energy.
+
+use super::{silence, Graph, Notification};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+/// The idle detection state of a **Graph**.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct IdleState {
+    /// The number of consecutive quiet requests after which the **Graph** becomes idle, or `None`
+    /// if idle detection is disabled.
+    threshold: Option<usize>,
+    /// The number of consecutive quiet requests so far.
+    quiet_blocks: usize,
+    idle: bool,
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Enable idle detection, so that the **Graph** becomes idle once it has been quiet for the
+    /// given number of consecutive requests for audio, or pass `None` to disable it.
+    ///
+    /// A request is quiet when every node rendered reports via `Node::is_silent` that it will
+    /// output silence, no tails are ringing out, the output is silent and no events, parameter
+    /// changes, messages or external input are due. While idle, requests for audio skip the
+    /// nodes entirely and fill the output with silence, so an always-on application costs next to
+    /// nothing while nothing is playing.
+    ///
+    /// `Notification::Idle` is emitted when the **Graph** becomes idle, so that the host may
+    /// suspend its audio stream, and `Notification::Resumed` when anything becomes due again and
+    /// rendering resumes. A suspended host should resume its stream before sending events to the
+    /// **Graph**.
+    ///
+    /// By default, idle detection is disabled.
+    pub fn idle_detect(&mut self, threshold_blocks: Option<usize>) {
+        self.state.idle.threshold = threshold_blocks;
+        self.state.idle.quiet_blocks = 0;
+        if threshold_blocks.is_none() {
+            self.state.idle.idle = false;
+        }
+    }
+
+    /// The number of consecutive quiet requests for audio after which the **Graph** becomes
+    /// idle, or `None` if idle detection is disabled.
+    pub fn idle_threshold_blocks(&self) -> Option<usize> {
+        self.state.idle.threshold
+    }
+
+    /// Whether the **Graph** is idle, filling requests for audio with silence.
+    pub fn is_idle(&self) -> bool {
+        self.state.idle.idle
+    }
+
+    /// Fill `output` with silence and advance the transport if the **Graph** is idle and remains
+    /// quiet, returning `true`. Otherwise, rendering resumes and `false` is returned.
+    pub(crate) fn render_idle(&mut self, output: &mut [F]) -> bool {
+        if !self.state.idle.idle {
+            return false;
+        }
+        let block_end = self.state.position + output.len() as u64;
+        if self.is_quiet(block_end) {
+            dasp::slice::equilibrium(output);
+            self.state.position = block_end;
+            return true;
+        }
+        self.state.idle.idle = false;
+        self.state.idle.quiet_blocks = 0;
+        self.state.notifier.send(Notification::Resumed);
+        false
+    }
+
+    /// Count a rendered request towards the idle threshold if it was quiet.
+    ///
+    /// `quiet` is whether nothing was due when the request began.
+    pub(crate) fn update_idle(&mut self, quiet: bool, output: &[F]) {
+        let threshold = match self.state.idle.threshold {
+            Some(threshold) => threshold,
+            None => return,
+        };
+        if !quiet || !silence::is_equilibrium(output) {
+            self.state.idle.quiet_blocks = 0;
+            return;
+        }
+        self.state.idle.quiet_blocks += 1;
+        if self.state.idle.quiet_blocks >= threshold {
+            self.state.idle.idle = true;
+            self.state.notifier.send(Notification::Idle);
+        }
+    }
+
+    /// Whether every node to be rendered is silent with nothing due before the given transport
+    /// frame, or `false` if idle detection is disabled.
+    pub(crate) fn is_quiet(&self, block_end: u64) -> bool {
+        if self.state.idle.threshold.is_none()
+            || self.has_pending_param_handles()
+            || self.state.message_buses.iter().any(|bus| !bus.is_empty())
+            || self.has_any_external_input()
+        {
+            return false;
+        }
+        self.state.render_order.iter().all(|&idx| {
+            let meta = &self.state.node_meta[idx.index()];
+            self.dag[idx].is_silent()
+                && meta.tail_remaining == 0
+                && !meta.smoothing.is_ramping()
+                && !self.has_events_before(idx, block_end)
+                && !self.is_crossfading_replacement(idx)
+        })
+    }
+}
@@ -0,0 +1,221 @@
+//! Latency reporting and plugin delay compensation (PDC).
+//!
+//! Each node may report the number of frames by which it delays its signal via
+//! `Node::latency`. When audio is requested, the **Graph** accumulates these latencies along
+//! every path and delays the shorter of any converging paths so that all inputs to a node arrive
+//! phase-aligned.
+
+use super::{EdgeIndex, Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use daggy::Walker;
+use dasp::{Frame, Sample};
+
+/// The number of frames over which a delay line crossfades between its old and new delay.
+const DELAY_FADE_FRAMES: usize = 64;
+
+/// A delay line used to compensate a connection for the latency of its slower sibling paths.
+///
+/// The line keeps a history of the frames passed through it, so that its delay may change while
+/// rendering without allocating or dropping the audio in flight, as long as the line has been
+/// prepared for the new delay via `reserve`. Changes are crossfaded over `DELAY_FADE_FRAMES`.
+#[derive(Clone, Debug)]
+pub(crate) struct Compensation<F> {
+    /// A ring of the most recent frames, one longer than the longest delay it can apply.
+    frames: Vec<F>,
+    /// The position of the most recently written frame.
+    position: usize,
+    delay: usize,
+    /// The delay from which the line is crossfading.
+    previous_delay: usize,
+    /// The number of frames remaining in the crossfade.
+    fade: usize,
+}
+
+impl<F> Compensation<F>
+where
+    F: Frame,
+{
+    /// A new delay line with no delay.
+    pub fn new() -> Self {
+        Compensation {
+            frames: Vec::new(),
+            position: 0,
+            delay: 0,
+            previous_delay: 0,
+            fade: 0,
+        }
+    }
+
+    /// The delay currently applied in frames.
+    pub fn delay(&self) -> usize {
+        self.delay
+    }
+
+    /// Whether the line can apply the given delay without allocating.
+    pub fn fits(&self, delay: usize) -> bool {
+        delay == 0 || delay < self.frames.len()
+    }
+
+    /// Whether frames pass through the line untouched, i.e. it has never applied a delay.
+    ///
+    /// Lines that have applied a delay keep processing frames at a delay of `0`, so that their
+    /// history remains intact should the delay rise again.
+    pub fn is_passthrough(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Grow the line so that it can apply the given delay without allocating, keeping the
+    /// frames in flight.
+    pub fn reserve(&mut self, delay: usize) {
+        if self.fits(delay) {
+            return;
+        }
+        // Unroll the ring from oldest to newest, padding the oldest end with silence.
+        let len = self.frames.len();
+        self.frames.rotate_left((self.position + 1) % len.max(1));
+        let padding = delay + 1 - len;
+        self.frames
+            .splice(0..0, std::iter::repeat_n(F::EQUILIBRIUM, padding));
+        self.position = delay;
+    }
+
+    /// Prepare the line to apply `delay` and to change to any delay up to `max_delay` without
+    /// allocating.
+    ///
+    /// Lines through which no delayed audio has yet passed take on the delay immediately.
+    pub fn prepare(&mut self, delay: usize, max_delay: usize) {
+        if self.is_passthrough() {
+            self.delay = delay;
+            self.previous_delay = delay;
+            self.fade = 0;
+        }
+        self.reserve(std::cmp::max(delay, max_delay));
+    }
+
+    /// Set the delay in frames, crossfading from the previous delay.
+    ///
+    /// This allocates if the line was not prepared for the delay via `reserve`.
+    pub fn set_delay(&mut self, delay: usize) {
+        if self.delay == delay {
+            return;
+        }
+        self.reserve(delay);
+        self.previous_delay = self.delay;
+        self.delay = delay;
+        self.fade = DELAY_FADE_FRAMES;
+    }
+
+    /// Clear the delayed frames without changing the delay.
+    pub fn clear(&mut self) {
+        dasp::slice::equilibrium(&mut self.frames);
+        self.fade = 0;
+    }
+
+    /// Push the given frame into the delay line and return the delayed frame.
+    #[inline]
+    pub fn process(&mut self, frame: F) -> F {
+        if self.frames.is_empty() {
+            return frame;
+        }
+        let len = self.frames.len();
+        self.position = (self.position + 1) % len;
+        self.frames[self.position] = frame;
+        let delayed = self.frames[(self.position + len - self.delay) % len];
+        if self.fade == 0 {
+            return delayed;
+        }
+        let previous = self.frames[(self.position + len - self.previous_delay) % len];
+        let mix = 1.0 - self.fade as f32 / (DELAY_FADE_FRAMES + 1) as f32;
+        self.fade -= 1;
+        previous.zip_map(delayed, |s_prev, s_new| {
+            let s_prev = s_prev.mul_amp((1.0 - mix).to_sample());
+            let s_new = s_new.mul_amp(mix.to_sample());
+            s_prev.add_amp(s_new.to_signed_sample())
+        })
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// The total latency in frames of the signal arriving at the output of the node at the given
+    /// index, accumulated along its slowest input path.
+    ///
+    /// Returns `None` if there is no node for the given index.
+    ///
+    /// This walks the whole **Graph** and allocates, so it is not intended for use on the audio
+    /// thread.
+    pub fn path_latency(&self, idx: NodeIndex<Ix>) -> Option<usize> {
+        self.node(idx)?;
+        let mut latencies = Vec::new();
+        self.compute_path_latencies(&mut latencies);
+        latencies.get(idx.index()).cloned()
+    }
+
+    /// The number of frames by which the connection at the given index is currently being
+    /// delayed to compensate for the latency of other paths into its output node.
+    ///
+    /// This is updated each time audio is requested.
+    pub fn connection_compensation(&self, edge: EdgeIndex<Ix>) -> Option<usize> {
+        self.dag.edge_weight(edge).map(|c| c.compensation.delay())
+    }
+
+    /// Recompute the path latency of every node and grow the delay line of every connection so
+    /// that compensating it does not allocate while rendering.
+    ///
+    /// Called whenever the **Graph** is restructured or its buffers are prepared.
+    pub(crate) fn prepare_compensation(&mut self) {
+        let mut latencies = std::mem::take(&mut self.state.path_latencies);
+        self.compute_path_latencies(&mut latencies);
+        for i in 0..self.state.visit_order.len() {
+            let node = self.state.visit_order[i];
+            // Lines are grown to include disabled connections, so that enabling them does not
+            // allocate.
+            let (mut max_enabled, mut max_input) = (0, 0);
+            let mut inputs = self.inputs(node);
+            while let Some((connection, input)) = inputs.next(self) {
+                let latency = latencies[input.index()];
+                if self.dag[connection].enabled {
+                    max_enabled = std::cmp::max(max_enabled, latency);
+                }
+                max_input = std::cmp::max(max_input, latency);
+            }
+            let mut inputs = self.inputs(node);
+            while let Some((connection, input)) = inputs.next(self) {
+                let latency = latencies[input.index()];
+                let delay = max_enabled.saturating_sub(latency);
+                let compensation = &mut self.dag[connection].compensation;
+                compensation.prepare(delay, max_input - latency);
+            }
+        }
+        self.state.path_latencies = latencies;
+    }
+
+    /// Fill `latencies` with the path latency of every node, indexed by node index.
+    pub(crate) fn compute_path_latencies(&self, latencies: &mut Vec<usize>) {
+        latencies.clear();
+        latencies.resize(self.dag.node_count(), 0);
+        for &node in &self.state.visit_order {
+            let mut inputs = self.inputs(node);
+            let mut max_input = 0;
+            while let Some((connection, input)) = inputs.next(self) {
+                if self.dag[connection].enabled {
+                    max_input = std::cmp::max(max_input, latencies[input.index()]);
+                }
+            }
+            // Bypassed nodes introduce no latency of their own unless it is preserved.
+            let latency = if self.state.node_meta[node.index()].is_fully_bypassed() {
+                self.bypass_latency(node)
+            } else if self.is_monitor_bypassed(node) {
+                0
+            } else {
+                self[node].latency()
+            };
+            latencies[node.index()] = max_input + latency;
+        }
+    }
+}
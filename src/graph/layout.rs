@@ -0,0 +1,112 @@
+//! Layout hints for front-ends that draw a diagram of the **Graph**.
+
+use super::{Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use daggy::Walker;
+use dasp::Frame;
+
+/// A suggested position for a node within a diagram of the **Graph**.
+///
+/// Nodes are arranged in layers from left to right in the direction of signal flow, such that
+/// every node is placed in a layer to the right of all of its inputs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NodeLayout<Ix = usize> {
+    /// The index of the node.
+    pub node: NodeIndex<Ix>,
+    /// The layer in which the node is placed. Nodes without inputs are placed in layer `0`.
+    pub level: usize,
+    /// The position of the node within its layer, from top to bottom.
+    pub row: usize,
+    /// The suggested horizontal position, one unit per layer.
+    pub x: f32,
+    /// The suggested vertical position, one unit per row, with each layer centred around `0.0`.
+    pub y: f32,
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Produce a layered layout of the **Graph**'s nodes for drawing a node diagram.
+    ///
+    /// Each node's level is the length of the longest path to it from a node without inputs.
+    /// Within each level, nodes are ordered by the average row of their inputs to reduce the
+    /// number of crossing connections.
+    ///
+    /// The returned layouts are indexed by node index.
+    pub fn layout_hints(&self) -> Vec<NodeLayout<Ix>> {
+        let node_count = self.dag.node_count();
+
+        // Assign each node to the level after the deepest of its inputs.
+        let mut levels = vec![0; node_count];
+        let mut visit_order = self.visit_order();
+        while let Some(idx) = visit_order.next(self) {
+            let mut inputs = self.inputs(idx);
+            while let Some(input) = inputs.next_node(self) {
+                levels[idx.index()] = levels[idx.index()].max(levels[input.index()] + 1);
+            }
+        }
+
+        // Group the nodes by level in visit order.
+        let num_levels = levels.iter().max().map_or(0, |&max| max + 1);
+        let mut layers: Vec<Vec<NodeIndex<Ix>>> = vec![Vec::new(); num_levels];
+        let mut visit_order = self.visit_order();
+        while let Some(idx) = visit_order.next(self) {
+            layers[levels[idx.index()]].push(idx);
+        }
+
+        // Order each layer by the average row of the nodes' inputs in the previous layers.
+        let mut rows = vec![0.0f32; node_count];
+        for layer in &mut layers {
+            let mut keyed: Vec<(f32, NodeIndex<Ix>)> = layer
+                .iter()
+                .enumerate()
+                .map(|(i, &idx)| {
+                    let (mut sum, mut count) = (0.0, 0);
+                    let mut inputs = self.inputs(idx);
+                    while let Some(input) = inputs.next_node(self) {
+                        sum += rows[input.index()];
+                        count += 1;
+                    }
+                    let key = if count > 0 {
+                        sum / count as f32
+                    } else {
+                        i as f32
+                    };
+                    (key, idx)
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+            for (row, &(_, idx)) in keyed.iter().enumerate() {
+                rows[idx.index()] = row as f32;
+            }
+            *layer = keyed.into_iter().map(|(_, idx)| idx).collect();
+        }
+
+        let mut layouts: Vec<NodeLayout<Ix>> = (0..node_count)
+            .map(|i| NodeLayout {
+                node: NodeIndex::new(i),
+                level: 0,
+                row: 0,
+                x: 0.0,
+                y: 0.0,
+            })
+            .collect();
+        for (level, layer) in layers.iter().enumerate() {
+            let centre = (layer.len() as f32 - 1.0) / 2.0;
+            for (row, &idx) in layer.iter().enumerate() {
+                layouts[idx.index()] = NodeLayout {
+                    node: idx,
+                    level,
+                    row,
+                    x: level as f32,
+                    y: row as f32 - centre,
+                };
+            }
+        }
+        layouts
+    }
+}
@@ -0,0 +1,156 @@
+//! Walkers over all ancestors or descendants of a node.
+
+use super::{Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use daggy::Walker;
+use dasp::Frame;
+
+/// A walker type for walking over every node from which there is a path to some node, in the
+/// order in which they will be visited when audio is requested from the **Graph**.
+///
+/// Unlike `Inputs`, which only yields the direct inputs of a node, this yields their inputs in
+/// turn and so on. The set of ancestors is collected when the walker is created, so the walker
+/// does not borrow the **Graph**.
+#[derive(Clone, Debug)]
+pub struct Ancestors {
+    lineage: Lineage,
+}
+
+/// A walker type for walking over every node to which there is a path from some node, in the
+/// order in which they will be visited when audio is requested from the **Graph**.
+///
+/// Unlike `Outputs`, which only yields the direct outputs of a node, this yields their outputs in
+/// turn and so on. The set of descendants is collected when the walker is created, so the walker
+/// does not borrow the **Graph**.
+#[derive(Clone, Debug)]
+pub struct Descendants {
+    lineage: Lineage,
+}
+
+/// A set of nodes walked in visit order.
+#[derive(Clone, Debug)]
+struct Lineage {
+    /// Whether the node at each index is in the set.
+    related: Vec<bool>,
+    current_visit_order_idx: usize,
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// A walker over every ancestor of the node at the given index (that is, every node that
+    /// feeds it either directly or via other nodes), in visit order.
+    ///
+    /// The node itself is not included. Feedback connections are not followed.
+    pub fn ancestors(&self, idx: NodeIndex<Ix>) -> Ancestors {
+        let lineage = self.lineage(idx, |graph, idx, stack| {
+            let mut inputs = graph.inputs(idx);
+            while let Some(input) = inputs.next_node(graph) {
+                stack.push(input);
+            }
+        });
+        Ancestors { lineage }
+    }
+
+    /// A walker over every descendant of the node at the given index (that is, every node that it
+    /// feeds either directly or via other nodes), in visit order.
+    ///
+    /// The node itself is not included. Feedback connections are not followed.
+    pub fn descendants(&self, idx: NodeIndex<Ix>) -> Descendants {
+        let lineage = self.lineage(idx, |graph, idx, stack| {
+            let mut outputs = graph.outputs(idx);
+            while let Some(output) = outputs.next_node(graph) {
+                stack.push(output);
+            }
+        });
+        Descendants { lineage }
+    }
+
+    /// Collect every node reachable from `idx` via `neighbours`, excluding `idx` itself.
+    fn lineage<G>(&self, idx: NodeIndex<Ix>, neighbours: G) -> Lineage
+    where
+        G: Fn(&Self, NodeIndex<Ix>, &mut Vec<NodeIndex<Ix>>),
+    {
+        let mut related = vec![false; self.dag.node_count()];
+        let mut stack = Vec::new();
+        if idx.index() < related.len() {
+            neighbours(self, idx, &mut stack);
+        }
+        while let Some(next) = stack.pop() {
+            if !std::mem::replace(&mut related[next.index()], true) {
+                neighbours(self, next, &mut stack);
+            }
+        }
+        Lineage {
+            related,
+            current_visit_order_idx: 0,
+        }
+    }
+}
+
+impl Lineage {
+    fn next<F, N, Ix>(&mut self, graph: &Graph<F, N, Ix>) -> Option<NodeIndex<Ix>>
+    where
+        F: Frame,
+        Ix: IndexType,
+    {
+        while let Some(&idx) = graph.state.visit_order.get(self.current_visit_order_idx) {
+            self.current_visit_order_idx += 1;
+            if self.contains(idx) {
+                return Some(idx);
+            }
+        }
+        None
+    }
+
+    fn contains<Ix>(&self, idx: NodeIndex<Ix>) -> bool
+    where
+        Ix: IndexType,
+    {
+        self.related.get(idx.index()).cloned().unwrap_or(false)
+    }
+}
+
+impl Ancestors {
+    /// The index of the next ancestor in visit order within the given **Graph**.
+    #[inline]
+    pub fn next<F, N, Ix>(&mut self, graph: &Graph<F, N, Ix>) -> Option<NodeIndex<Ix>>
+    where
+        F: Frame,
+        Ix: IndexType,
+    {
+        self.lineage.next(graph)
+    }
+
+    /// Whether the node at the given index is an ancestor.
+    pub fn contains<Ix>(&self, idx: NodeIndex<Ix>) -> bool
+    where
+        Ix: IndexType,
+    {
+        self.lineage.contains(idx)
+    }
+}
+
+impl Descendants {
+    /// The index of the next descendant in visit order within the given **Graph**.
+    #[inline]
+    pub fn next<F, N, Ix>(&mut self, graph: &Graph<F, N, Ix>) -> Option<NodeIndex<Ix>>
+    where
+        F: Frame,
+        Ix: IndexType,
+    {
+        self.lineage.next(graph)
+    }
+
+    /// Whether the node at the given index is a descendant.
+    pub fn contains<Ix>(&self, idx: NodeIndex<Ix>) -> bool
+    where
+        Ix: IndexType,
+    {
+        self.lineage.contains(idx)
+    }
+}
@@ -0,0 +1,46 @@
+//! Converting the nodes of a **Graph** to another type.
+
+use super::{Dag, Graph, NodeIndex};
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    /// Convert each node to another type via `f`, which is given the index of each node along
+    /// with the node itself, e.g. to migrate a graph of enum nodes into boxed trait objects or
+    /// vice versa.
+    ///
+    /// The topology is preserved, so all node and connection indices remain valid. Connections
+    /// keep their buffers and all other state maintained by the **Graph** on behalf of each node
+    /// and connection is carried over, as are the **Graph**'s settings.
+    ///
+    /// Any crossfades started by `crossfade_replace_node` are completed immediately, and replaced
+    /// nodes that have not yet been collected via `drain_replaced_nodes` are dropped.
+    pub fn map_nodes<M, G>(self, mut f: G) -> Graph<F, M, Ix>
+    where
+        G: FnMut(NodeIndex<Ix>, N) -> M,
+    {
+        let Graph { dag, state, .. } = self;
+        let (nodes, edges) = dag.into_graph().into_nodes_edges();
+        let mut mapped = Dag::with_capacity(nodes.len(), edges.len());
+        for (i, node) in nodes.into_iter().enumerate() {
+            mapped.add_node(f(NodeIndex::new(i), node.weight));
+        }
+        // Adding the edges in their original order preserves their indices.
+        let edges = edges
+            .into_iter()
+            .map(|edge| (edge.source(), edge.target(), edge.weight));
+        if mapped.add_edges(edges).is_err() {
+            unreachable!("the connections formed no cycle before the nodes were mapped");
+        }
+        Graph {
+            dag: mapped,
+            replaced: Vec::new(),
+            retired_nodes: Vec::new(),
+            state,
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! A typed message bus for delivering non-audio data such as triggers, gates or UI commands to
+//! nodes.
+
+use super::{Graph, NodeIndex, RequestError};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::any::Any;
+use std::fmt::Debug;
+
+/// The messages of a single type waiting to be delivered, in the order in which they were sent.
+#[derive(Clone, Debug)]
+struct Bus<M, Ix> {
+    messages: Vec<(NodeIndex<Ix>, M)>,
+}
+
+/// A **Bus** with its message type erased.
+pub(crate) trait MessageBus<Ix>: Debug + Send + Sync {
+    /// The bus as `Any`, so that it may be downcast to its message type.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// A clone of the bus and all of its messages.
+    fn box_clone(&self) -> Box<dyn MessageBus<Ix>>;
+    /// Pass each message addressed to the given node to `handle`, removing it from the bus.
+    fn deliver(&mut self, idx: NodeIndex<Ix>, handle: &mut dyn FnMut(&dyn Any));
+    /// Update the bus after the node at `idx` was removed and the last node was shifted into its
+    /// place.
+    fn remove_node(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>);
+    /// Remove all messages.
+    fn clear(&mut self);
+    /// Whether the bus holds no messages.
+    fn is_empty(&self) -> bool;
+}
+
+impl<M, Ix> MessageBus<Ix> for Bus<M, Ix>
+where
+    M: Clone + Debug + Send + Sync + 'static,
+    Ix: IndexType + Send + Sync,
+{
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn box_clone(&self) -> Box<dyn MessageBus<Ix>> {
+        Box::new(self.clone())
+    }
+
+    fn deliver(&mut self, idx: NodeIndex<Ix>, handle: &mut dyn FnMut(&dyn Any)) {
+        self.messages.retain(|(target, message)| {
+            if *target == idx {
+                handle(message);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    fn remove_node(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        self.messages.retain(|&(target, _)| target != idx);
+        for (target, _) in &mut self.messages {
+            if *target == last {
+                *target = idx;
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl<Ix> Clone for Box<dyn MessageBus<Ix>> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType + Send + Sync,
+{
+    /// Send a message to the node at the given index.
+    ///
+    /// Messages carry non-audio data such as triggers, gate signals or UI commands. They are
+    /// delivered via `Node::handle_message` at the start of the next request for audio in which
+    /// the node is rendered, in visit order and, for each node, in the order in which they were
+    /// sent.
+    ///
+    /// Each message type has its own queue, which is allocated when the first message of that
+    /// type is sent and grows as needed. Use `reserve_messages` beforehand so that sending
+    /// messages on the audio thread never allocates.
+    pub fn send_message<M>(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        message: M,
+    ) -> Result<(), RequestError<Ix>>
+    where
+        M: Clone + Debug + Send + Sync + 'static,
+    {
+        self.check_node(idx)?;
+        let bus = self.bus_mut::<M>();
+        let full = bus.messages.len() == bus.messages.capacity();
+        bus.messages.push((idx, message));
+        if full {
+            self.note_alloc("more messages were sent than were reserved");
+        }
+        Ok(())
+    }
+
+    /// Reserve capacity for at least `additional` more messages of type `M`.
+    pub fn reserve_messages<M>(&mut self, additional: usize)
+    where
+        M: Clone + Debug + Send + Sync + 'static,
+    {
+        self.bus_mut::<M>().messages.reserve(additional);
+    }
+
+    /// The bus for messages of type `M`, added if there is none.
+    fn bus_mut<M>(&mut self) -> &mut Bus<M, Ix>
+    where
+        M: Clone + Debug + Send + Sync + 'static,
+    {
+        let position = self
+            .state
+            .message_buses
+            .iter_mut()
+            .position(|bus| bus.as_any_mut().is::<Bus<M, Ix>>());
+        let i = match position {
+            Some(i) => i,
+            None => {
+                self.note_alloc("a message of a new type was sent");
+                let bus: Bus<M, Ix> = Bus {
+                    messages: Vec::new(),
+                };
+                self.state.message_buses.push(Box::new(bus));
+                self.state.message_buses.len() - 1
+            }
+        };
+        self.state.message_buses[i]
+            .as_any_mut()
+            .downcast_mut()
+            .expect("the bus has the message type")
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Deliver all messages sent to the node at the given index.
+    pub(crate) fn dispatch_messages(&mut self, idx: NodeIndex<Ix>) {
+        let node = &mut self.dag[idx];
+        for bus in &mut self.state.message_buses {
+            bus.deliver(idx, &mut |message| node.handle_message(message));
+        }
+    }
+
+    /// Update the message buses after the node at `idx` was removed and the last node was
+    /// shifted into its place.
+    pub(crate) fn remove_node_messages(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        for bus in &mut self.state.message_buses {
+            bus.remove_node(idx, last);
+        }
+    }
+
+    /// Remove all messages that have not yet been delivered.
+    pub(crate) fn clear_messages(&mut self) {
+        for bus in &mut self.state.message_buses {
+            bus.clear();
+        }
+    }
+}
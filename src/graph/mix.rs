@@ -0,0 +1,115 @@
+//! The buffer summing and dry/wet mixing loops run for every node when audio is requested.
+//!
+//! With the `simd` feature enabled, these process frames in fixed-size chunks so that the
+//! compiler can vectorise the inner loops for `f32` and `f64` frames.
+
+use dasp::{Frame, Sample};
+
+/// The number of frames processed per chunk by the vectorised loops.
+#[cfg(feature = "simd")]
+const CHUNK: usize = 8;
+
+/// Add `input` onto `output`.
+#[inline]
+fn add<F>(output: F, input: F) -> F
+where
+    F: Frame,
+{
+    output.zip_map(input, |out_sample, in_sample| {
+        out_sample.add_amp(in_sample.to_signed_sample())
+    })
+}
+
+/// Combine the `wet` and `dry` frames with the given amounts of each.
+#[inline]
+fn dry_wet<F>(
+    f_wet: F,
+    f_dry: F,
+    wet: <F::Sample as Sample>::Float,
+    dry: <F::Sample as Sample>::Float,
+) -> F
+where
+    F: Frame,
+{
+    f_wet.zip_map(f_dry, |s_wet, s_dry| {
+        let wet = s_wet.mul_amp(wet);
+        let dry = s_dry.mul_amp(dry);
+        wet.add_amp(dry.to_sample())
+    })
+}
+
+/// Sum the frames of `input` onto `output`.
+///
+/// **Panics** if the buffers differ in length.
+#[cfg(not(feature = "simd"))]
+pub(crate) fn sum_onto<F>(output: &mut [F], input: &[F])
+where
+    F: Frame,
+{
+    dasp::slice::zip_map_in_place(output, input, add);
+}
+
+/// Sum the frames of `input` onto `output`.
+///
+/// **Panics** if the buffers differ in length.
+#[cfg(feature = "simd")]
+pub(crate) fn sum_onto<F>(output: &mut [F], input: &[F])
+where
+    F: Frame,
+{
+    assert_eq!(output.len(), input.len());
+    let mut out_chunks = output.chunks_exact_mut(CHUNK);
+    let mut in_chunks = input.chunks_exact(CHUNK);
+    for (out_chunk, in_chunk) in (&mut out_chunks).zip(&mut in_chunks) {
+        for i in 0..CHUNK {
+            out_chunk[i] = add(out_chunk[i], in_chunk[i]);
+        }
+    }
+    let out_rem = out_chunks.into_remainder();
+    for (out_frame, &in_frame) in out_rem.iter_mut().zip(in_chunks.remainder()) {
+        *out_frame = add(*out_frame, in_frame);
+    }
+}
+
+/// Combine the fully wet `output` with the `dry` buffer, writing the result to `output`.
+///
+/// **Panics** if the buffers differ in length.
+#[cfg(not(feature = "simd"))]
+pub(crate) fn mix_dry_wet<F>(
+    output: &mut [F],
+    dry_buffer: &[F],
+    dry: <F::Sample as Sample>::Float,
+    wet: <F::Sample as Sample>::Float,
+) where
+    F: Frame,
+{
+    dasp::slice::zip_map_in_place(output, dry_buffer, |f_wet, f_dry| {
+        dry_wet(f_wet, f_dry, wet, dry)
+    });
+}
+
+/// Combine the fully wet `output` with the `dry` buffer, writing the result to `output`.
+///
+/// **Panics** if the buffers differ in length.
+#[cfg(feature = "simd")]
+pub(crate) fn mix_dry_wet<F>(
+    output: &mut [F],
+    dry_buffer: &[F],
+    dry: <F::Sample as Sample>::Float,
+    wet: <F::Sample as Sample>::Float,
+) where
+    F: Frame,
+{
+    assert_eq!(output.len(), dry_buffer.len());
+    let mut out_chunks = output.chunks_exact_mut(CHUNK);
+    let mut dry_chunks = dry_buffer.chunks_exact(CHUNK);
+    for (out_chunk, dry_chunk) in (&mut out_chunks).zip(&mut dry_chunks) {
+        for i in 0..CHUNK {
+            out_chunk[i] = dry_wet(out_chunk[i], dry_chunk[i], wet, dry);
+        }
+    }
+    let out_rem = out_chunks.into_remainder();
+    for (out_frame, &dry_frame) in out_rem.iter_mut().zip(dry_chunks.remainder()) {
+        *out_frame = dry_wet(*out_frame, dry_frame, wet, dry);
+    }
+}
@@ -0,0 +1,96 @@
+//! Low-latency monitoring, where nodes that introduce latency are bypassed in the audible path
+//! while still rendering at full quality for recording.
+
+use super::{Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::{self, Frame};
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Enable or disable low-latency monitoring.
+    ///
+    /// While enabled, each node whose `Node::latency` exceeds `max_latency` frames is still
+    /// rendered, but passes its input through unchanged and reports no latency of its own. This
+    /// mirrors a DAW's direct monitoring: performers hear themselves without the delay of
+    /// look-ahead or linear-phase processing, while the full-quality output of each bypassed node
+    /// remains available via
+    /// [`full_quality_output`](./struct.Graph.html#method.full_quality_output) for recording.
+    ///
+    /// Pass `None` to disable monitoring.
+    pub fn set_monitoring(&mut self, max_latency: Option<usize>) {
+        self.state.monitor_max_latency = max_latency;
+        if max_latency.is_none() {
+            self.state.full_quality_outputs.clear();
+        }
+    }
+
+    /// The maximum node latency in frames allowed in the monitoring path, if low-latency
+    /// monitoring is enabled.
+    pub fn monitoring(&self) -> Option<usize> {
+        self.state.monitor_max_latency
+    }
+
+    /// Whether or not the node at the given index is bypassed by low-latency monitoring.
+    pub fn is_monitor_bypassed(&self, idx: NodeIndex<Ix>) -> bool {
+        match (self.state.monitor_max_latency, self.dag.node_weight(idx)) {
+            (Some(max_latency), Some(node)) => node.latency() > max_latency,
+            _ => false,
+        }
+    }
+
+    /// The output rendered during the last request for audio by a node that is bypassed by
+    /// low-latency monitoring.
+    ///
+    /// Returns `None` if the node was not bypassed.
+    pub fn full_quality_output(&self, idx: NodeIndex<Ix>) -> Option<&[F]> {
+        self.state
+            .full_quality_outputs
+            .iter()
+            .find(|&&(node, _)| node == idx)
+            .map(|(_, buffer)| &buffer[..])
+    }
+
+    /// Store the full-quality output of a node bypassed by low-latency monitoring.
+    pub(crate) fn store_full_quality_output(&mut self, idx: NodeIndex<Ix>, output: &[F]) {
+        let position = self
+            .state
+            .full_quality_outputs
+            .iter()
+            .position(|&(node, _)| node == idx);
+        let buffer = match position {
+            Some(i) => &mut self.state.full_quality_outputs[i].1,
+            None => {
+                self.note_alloc("a node was bypassed by monitoring for the first time");
+                self.state.full_quality_outputs.push((idx, Vec::new()));
+                &mut self.state.full_quality_outputs.last_mut().unwrap().1
+            }
+        };
+        buffer.clear();
+        buffer.extend_from_slice(output);
+    }
+
+    /// Update the full-quality outputs after the node at `idx` was removed and the last node was
+    /// shifted into its place.
+    pub(crate) fn remove_node_monitor(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        self.state
+            .full_quality_outputs
+            .retain(|&(node, _)| node != idx);
+        for (node, _) in &mut self.state.full_quality_outputs {
+            if *node == last {
+                *node = idx;
+            }
+        }
+    }
+
+    /// Pass the dry input through in place of the rendered `output` of a node bypassed by
+    /// low-latency monitoring, keeping the rendered output for recording.
+    pub(crate) fn bypass_for_monitoring(&mut self, idx: NodeIndex<Ix>, output: &mut [F]) {
+        self.store_full_quality_output(idx, output);
+        dasp::slice::write(output, &self.state.dry_buffer);
+    }
+}
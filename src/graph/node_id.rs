@@ -0,0 +1,90 @@
+//! Identifiers that refer to a node for as long as it remains within the **Graph**.
+
+use super::{Graph, NodeIndex, NodeMeta};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::fmt;
+
+/// A unique identifier assigned to each node as it is added to a **Graph**.
+///
+/// Unlike a **NodeIndex**, a node's **NodeId** never changes while the node remains within the
+/// **Graph**, and is never reused for another node, even after the node is removed. This makes it
+/// suitable for referring to nodes from outside of the **Graph**, e.g. within a saved session,
+/// an undo history or a network protocol. Use `Graph::index_of` to find the node's current index.
+///
+/// Ids are assigned in increasing order, so a node added later always has a greater id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId(u64);
+
+impl NodeId {
+    /// The id with the given raw value, e.g. as received from another process.
+    pub fn new(id: u64) -> Self {
+        NodeId(id)
+    }
+
+    /// The raw value of the id.
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<NodeId> for u64 {
+    fn from(id: NodeId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// The id of the node at the given index, or `None` if there is no node for the index.
+    pub fn node_id(&self, idx: NodeIndex<Ix>) -> Option<NodeId> {
+        self.state
+            .node_meta
+            .get(idx.index())
+            .map(|meta| NodeId(meta.id))
+    }
+
+    /// The current index of the node with the given id, or `None` if it has been removed.
+    ///
+    /// This computes in **O(n)** time where n is the number of nodes.
+    pub fn index_of(&self, id: NodeId) -> Option<NodeIndex<Ix>> {
+        self.state
+            .node_meta
+            .iter()
+            .position(|meta| meta.id == id.0)
+            .map(NodeIndex::new)
+    }
+
+    /// Push the state of a newly added node, assigning it the next id.
+    pub(super) fn push_node_meta(&mut self, meta: NodeMeta) {
+        let id = self.state.next_node_id.0;
+        self.state.next_node_id = NodeId(id + 1);
+        self.state.node_meta.push(NodeMeta { id, ..meta });
+    }
+
+    /// Ensure that all ids assigned from now on are greater than `max`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn reserve_node_ids(&mut self, max: NodeId) {
+        if max >= self.state.next_node_id {
+            self.state.next_node_id = NodeId(max.0 + 1);
+        }
+    }
+
+    /// Assign the given id, which must have been reserved, to the node at the given index.
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore_node_id(&mut self, idx: NodeIndex<Ix>, id: NodeId) {
+        self.state.node_meta[idx.index()].id = id.0;
+    }
+}
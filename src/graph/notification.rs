@@ -0,0 +1,170 @@
+//! Notifications emitted by the **Graph** for the host to observe.
+
+use super::{Graph, NodeIndex};
+use crate::node::{Node, ParamChange};
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+
+/// The number of notifications that may be queued for a **NotificationReceiver** before further
+/// notifications are dropped.
+const NOTIFICATION_CAPACITY: usize = 256;
+
+/// Something that happened within the **Graph** that the host may want to react to.
+///
+/// Notifications are sent by the **Graph** (often while rendering on the audio thread) to each
+/// receiver returned by
+/// [`subscribe_notifications`](../struct.Graph.html#method.subscribe_notifications).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Notification<Ix = usize> {
+    /// The node at the given index panicked while rendering and is now bypassed.
+    NodePanicked(NodeIndex<Ix>),
+    /// The node at the given index changed one of its own parameters while rendering.
+    ParamChanged(NodeIndex<Ix>, ParamChange),
+    /// The given number of events sent to the node at the given index were dropped because its
+    /// event queue was full.
+    EventsDropped(NodeIndex<Ix>, usize),
+    /// The node at the given index finished loading in the background and is now active.
+    NodeLoaded(NodeIndex<Ix>),
+    /// The node at the given index finished crossfading from the node that it replaced, which may
+    /// now be collected via `drain_replaced_nodes`.
+    NodeReplaced(NodeIndex<Ix>),
+    /// The node at the given index repeatedly exceeded the watchdog's time budget and is now
+    /// bypassed.
+    NodeOverBudget(NodeIndex<Ix>),
+    /// The buffer advisor recommends reopening the audio stream with the given block size.
+    BufferSizeAdvised(usize),
+    /// The **Graph** has been quiet for the threshold given to `idle_detect` and is now filling
+    /// requests for audio with silence, so the audio stream may be suspended.
+    Idle,
+    /// Something became due while the **Graph** was idle, so rendering has resumed.
+    Resumed,
+}
+
+/// The receiving end of the **Graph**'s notifications, returned by
+/// [`Graph::subscribe_notifications`](./struct.Graph.html#method.subscribe_notifications).
+///
+/// The receiver may be moved to another thread, e.g. a GUI or control thread. The subscription is
+/// removed from the **Graph** once this is dropped.
+#[derive(Debug)]
+pub struct NotificationReceiver<Ix = usize>
+where
+    Ix: IndexType,
+{
+    receiver: mpsc::Receiver<Notification<Ix>>,
+    dropped: Arc<AtomicUsize>,
+}
+
+/// Sends notifications to every subscribed **NotificationReceiver** without blocking or
+/// allocating.
+#[derive(Clone, Debug)]
+pub(crate) struct Notifier<Ix>
+where
+    Ix: IndexType,
+{
+    subscribers: Vec<Subscriber<Ix>>,
+}
+
+/// The **Graph**'s end of a **NotificationReceiver**.
+#[derive(Clone, Debug)]
+struct Subscriber<Ix>
+where
+    Ix: IndexType,
+{
+    sender: mpsc::SyncSender<Notification<Ix>>,
+    dropped: Arc<AtomicUsize>,
+}
+
+impl<Ix> NotificationReceiver<Ix>
+where
+    Ix: IndexType,
+{
+    /// Yield all notifications received since this was last called in the order in which they
+    /// occurred, without blocking.
+    pub fn try_iter(&self) -> mpsc::TryIter<'_, Notification<Ix>> {
+        self.receiver.try_iter()
+    }
+
+    /// The total number of notifications that were dropped because this receiver had fallen
+    /// behind and its queue was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<Ix> Notifier<Ix>
+where
+    Ix: IndexType,
+{
+    /// Send the notification to every subscriber, removing those whose receivers were dropped.
+    pub fn send(&mut self, notification: Notification<Ix>) {
+        self.subscribers.retain(|subscriber| {
+            match subscriber.sender.try_send(notification.clone()) {
+                Ok(()) => true,
+                Err(mpsc::TrySendError::Full(_)) => {
+                    subscriber.dropped.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+                Err(mpsc::TrySendError::Disconnected(_)) => false,
+            }
+        });
+    }
+}
+
+// Implemented manually as the derive would require Ix: Default.
+impl<Ix> Default for Notifier<Ix>
+where
+    Ix: IndexType,
+{
+    fn default() -> Self {
+        Notifier {
+            subscribers: Vec::new(),
+        }
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    /// Subscribe to the **Graph**'s notifications, returning the receiving end.
+    ///
+    /// Notifications are sent on the audio thread without blocking or allocating. Each receiver
+    /// queues up to 256 notifications, beyond which further notifications are dropped and
+    /// counted until the receiver catches up. Notifications that occur while there are no
+    /// subscribers are discarded.
+    pub fn subscribe_notifications(&mut self) -> NotificationReceiver<Ix> {
+        let (sender, receiver) = mpsc::sync_channel(NOTIFICATION_CAPACITY);
+        let dropped = Arc::new(AtomicUsize::new(0));
+        self.state.notifier.subscribers.push(Subscriber {
+            sender,
+            dropped: dropped.clone(),
+        });
+        NotificationReceiver { receiver, dropped }
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Queue a notification for each parameter change reported by the node at the given index.
+    pub(crate) fn collect_param_changes(&mut self, idx: NodeIndex<Ix>) {
+        self.dag[idx].param_changes(&mut self.state.param_changes);
+        self.update_param_taps(idx);
+        self.update_param_handles(idx);
+        let smoothing = &mut self.state.node_meta[idx.index()].smoothing;
+        for change in &self.state.param_changes {
+            smoothing.observe(change.param, change.value);
+        }
+        for change in self.state.param_changes.drain(..) {
+            self.state
+                .notifier
+                .send(Notification::ParamChanged(idx, change));
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Containment of panics that occur while a node renders audio.
+
+use super::{ports, replace, Graph, NodeIndex, Notification};
+use crate::buffer::{self, BufferFormat, Planar};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::{self, Frame};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Describes how the **Graph** handles a node that panics within `audio_requested`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// The panic is propagated to the caller. This is the default.
+    #[default]
+    Propagate,
+    /// The panicking node is bypassed from then on, outputting silence.
+    Silence,
+    /// The panicking node is bypassed from then on, outputting its summed (dry) input.
+    Dry,
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Set how the **Graph** handles a node that panics within `audio_requested`.
+    ///
+    /// When set to anything other than `PanicPolicy::Propagate`, each call to a node's
+    /// `audio_requested` method is wrapped so that a panicking node is bypassed rather than
+    /// taking down the entire audio stream. A `Notification::NodePanicked` is emitted for each
+    /// node that panics.
+    ///
+    /// Containing a panic does not prevent the process's panic hook from running first, and the
+    /// default hook prints the panic message to stderr from the audio thread, which may block.
+    /// Hosts relying on containment should install a hook via `std::panic::set_hook` that does
+    /// not block, e.g. one that only records the message for another thread to report. The hook
+    /// is process-wide, so the **Graph** leaves installing one to the host.
+    pub fn set_panic_policy(&mut self, policy: PanicPolicy) {
+        self.state.panic_policy = policy;
+    }
+
+    /// The **Graph**'s current **PanicPolicy**.
+    pub fn panic_policy(&self) -> PanicPolicy {
+        self.state.panic_policy
+    }
+
+    /// Whether or not the node at the given index has panicked and is currently bypassed.
+    pub fn has_panicked(&self, idx: NodeIndex<Ix>) -> bool {
+        self.state
+            .node_meta
+            .get(idx.index())
+            .map(|meta| meta.panicked)
+            .unwrap_or(false)
+    }
+
+    /// Reinstate a node that was bypassed after panicking.
+    ///
+    /// Returns `true` if the node had panicked.
+    pub fn reset_panicked(&mut self, idx: NodeIndex<Ix>) -> bool {
+        match self.state.node_meta.get_mut(idx.index()) {
+            Some(meta) => std::mem::replace(&mut meta.panicked, false),
+            None => false,
+        }
+    }
+
+    /// Render the `output` buffer with the node at the given index, applying the panic policy.
+    ///
+    /// The buffer is split at the frame of each of the node's events that take effect within the
+    /// block starting at the transport frame `block_start`, delivering each event in between.
+    ///
+    /// Returns `false` if the node did not render, in which case `output` has been filled
+    /// according to the policy.
+    pub(crate) fn render_node(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        output: &mut [F],
+        block_start: u64,
+        sample_hz: f64,
+    ) -> bool {
+        let policy = self.state.panic_policy;
+        let num_ports = if self.gathers_inputs(idx) {
+            self.state.num_ports
+        } else {
+            0
+        };
+        if num_ports > ports::MAX_STACK_PORTS {
+            self.note_alloc("a node has too many inputs to pass them without allocating");
+        }
+        let meta = &mut self.state.node_meta[idx.index()];
+        let node = &mut self.dag[idx];
+        // A node that is being crossfaded out renders alongside its replacement, into the buffer
+        // holding the input stored for it.
+        let mut previous = replace::previous_node_mut(&mut self.replaced, idx);
+        let replace_buffer = &mut self.state.replace_buffer;
+        let planar_buffer = &mut self.state.planar_buffer;
+        let events = &mut meta.events;
+        let smoothing = &mut meta.smoothing;
+        let default_smoothing_ms = self.state.default_smoothing_ms;
+        let tempo = &self.state.tempo;
+
+        // Nodes that process separately from their inputs receive the buffer of each port, passed
+        // as slices stored on the stack unless there are too many.
+        let ports = &self.state.input_buffers[..num_ports];
+        let mut stack_inputs: [&[F]; ports::MAX_STACK_PORTS] = [&[]; ports::MAX_STACK_PORTS];
+        let mut heap_inputs: Vec<&[F]> = Vec::new();
+
+        let mut render = || {
+            let len = output.len();
+            let mut start = 0;
+            loop {
+                // Deliver the events that take effect at the start of this sub-block.
+                let frame = block_start + start as u64;
+                while let Some(event) = events.pop_before(frame + 1) {
+                    if let Some(event) = smoothing.filter(event, default_smoothing_ms) {
+                        node.handle_event(&event);
+                        if let Some(previous) = previous.as_deref_mut() {
+                            previous.handle_event(&event);
+                        }
+                    }
+                }
+                let mut end = match events.next_frame() {
+                    Some(next) if next < block_start + len as u64 => (next - block_start) as usize,
+                    _ => len,
+                };
+                // Split the buffer at each interval while parameters are being smoothed,
+                // delivering the values they reach by the end of each interval.
+                if smoothing.is_ramping() {
+                    let interval = smoothing.step(frame, sample_hz, |e| {
+                        node.handle_event(e);
+                        if let Some(previous) = previous.as_deref_mut() {
+                            previous.handle_event(e);
+                        }
+                    });
+                    end = end.min(start + interval);
+                }
+                // Split the buffer where the tempo changes course, so that a single **Tempo**
+                // describes each part exactly.
+                if let Some(tempo) = tempo.tempo(block_start, start, sample_hz) {
+                    if let Some(remaining) = tempo.frames_remaining() {
+                        end = end.min(start + remaining as usize);
+                    }
+                    node.update_tempo(&tempo);
+                    if let Some(previous) = previous.as_deref_mut() {
+                        previous.update_tempo(&tempo);
+                    }
+                }
+                let inputs: &[&[F]] = if num_ports <= ports::MAX_STACK_PORTS {
+                    for (input, port) in stack_inputs.iter_mut().zip(ports) {
+                        *input = &port[start..end];
+                    }
+                    &stack_inputs[..num_ports]
+                } else {
+                    heap_inputs.clear();
+                    heap_inputs.extend(ports.iter().map(|port| &port[start..end]));
+                    &heap_inputs
+                };
+                request_audio(
+                    node,
+                    inputs,
+                    &mut output[start..end],
+                    planar_buffer,
+                    sample_hz,
+                );
+                if let Some(previous) = previous.as_deref_mut() {
+                    request_audio(
+                        previous,
+                        inputs,
+                        &mut replace_buffer[start..end],
+                        planar_buffer,
+                        sample_hz,
+                    );
+                }
+                if end == len {
+                    break;
+                }
+                start = end;
+            }
+        };
+
+        let rendered = if meta.panicked {
+            false
+        } else if policy == PanicPolicy::Propagate {
+            render();
+            true
+        } else {
+            match panic::catch_unwind(AssertUnwindSafe(&mut render)) {
+                Ok(()) => true,
+                Err(_) => {
+                    meta.panicked = true;
+                    self.state.notifier.send(Notification::NodePanicked(idx));
+                    false
+                }
+            }
+        };
+
+        if !rendered {
+            match policy {
+                PanicPolicy::Dry => dasp::slice::write(output, &self.state.dry_buffer),
+                _ => dasp::slice::equilibrium(output),
+            }
+        }
+
+        rendered
+    }
+}
+
+/// Request audio from the given node in the layout described by its `buffer_format`, or via
+/// `Node::process` if it processes separately from its inputs.
+fn request_audio<F, N>(
+    node: &mut N,
+    inputs: &[&[F]],
+    output: &mut [F],
+    planar_buffer: &mut [F::Sample],
+    hz: f64,
+) where
+    F: Frame,
+    N: Node<F>,
+{
+    if node.separate_io() {
+        node.process(inputs, output, hz);
+        return;
+    }
+    match node.buffer_format() {
+        BufferFormat::Interleaved => node.audio_requested(output, hz),
+        BufferFormat::Planar => {
+            let planar_buffer = &mut planar_buffer[..output.len() * F::CHANNELS];
+            buffer::deinterleave(output, planar_buffer);
+            node.audio_requested_planar(Planar::new(planar_buffer, F::CHANNELS), hz);
+            buffer::interleave(planar_buffer, output);
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Handles through which other threads (e.g. a GUI) read, set and watch the parameters of nodes.
+
+use super::{Graph, NodeIndex, RequestError};
+use crate::event::Event;
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A handle to a single parameter of a node within a **Graph**, returned by
+/// [`Graph::param_handle`](./struct.Graph.html#method.param_handle).
+///
+/// The handle is `Send + Sync` and never blocks, so it may be shared with a GUI thread and bound
+/// to a widget. Values given to `set` are delivered to the node as `Event::Param` at the start of
+/// the next request for audio, while changes that the node makes itself (reported via
+/// `Node::param_changes`) are reflected by `get`.
+///
+/// The **Graph** follows the node as other nodes are removed and its index shifts, so the handle
+/// remains bound to the same node for as long as it exists. Once the node is removed (or the
+/// **Graph** is dropped), the handle is detached: `set` has no effect and `get` returns the last
+/// known value.
+#[derive(Clone)]
+pub struct ParamHandle {
+    shared: Arc<Shared>,
+}
+
+/// Watches a parameter for changes, returned by `ParamHandle::subscribe`.
+pub struct ParamSubscription {
+    shared: Arc<Shared>,
+    /// The version of the value when it was last observed.
+    seen: u64,
+}
+
+/// The state shared between the **Graph** and the handles to a parameter.
+struct Shared {
+    param: usize,
+    /// The bits of the latest `f32` value.
+    value: AtomicU32,
+    /// Incremented each time the value changes.
+    version: AtomicU64,
+    /// Whether the value was set via a handle and is yet to be delivered to the node.
+    pending: AtomicBool,
+    /// The number of **Graph**s holding a binding to the parameter.
+    bindings: AtomicUsize,
+}
+
+/// The **Graph**'s end of the handles to a parameter, following the node by index.
+pub(crate) struct ParamBinding<Ix> {
+    node: NodeIndex<Ix>,
+    shared: Arc<Shared>,
+}
+
+impl ParamHandle {
+    /// The index of the parameter within its node.
+    pub fn param(&self) -> usize {
+        self.shared.param
+    }
+
+    /// The latest value of the parameter, whether it was set via a handle or changed by the node.
+    pub fn get(&self) -> f32 {
+        self.shared.get()
+    }
+
+    /// Set the parameter, delivering the value to the node at the start of the next request for
+    /// audio.
+    ///
+    /// If the parameter is set several times between requests, only the latest value is
+    /// delivered.
+    pub fn set(&self, value: f32) {
+        self.shared.store(value);
+        self.shared.pending.store(true, Ordering::Release);
+    }
+
+    /// Whether the handle is still bound to a node within a **Graph**.
+    pub fn is_attached(&self) -> bool {
+        self.shared.bindings.load(Ordering::Acquire) > 0
+    }
+
+    /// Watch the parameter for changes from now on, whether they are made via a handle or by the
+    /// node itself.
+    pub fn subscribe(&self) -> ParamSubscription {
+        ParamSubscription {
+            shared: self.shared.clone(),
+            seen: self.shared.version.load(Ordering::Acquire),
+        }
+    }
+}
+
+impl ParamSubscription {
+    /// The latest value if the parameter changed since this was last called (or since the
+    /// subscription was created), without blocking.
+    pub fn changed(&mut self) -> Option<f32> {
+        let version = self.shared.version.load(Ordering::Acquire);
+        if version == self.seen {
+            return None;
+        }
+        self.seen = version;
+        Some(self.shared.get())
+    }
+
+    /// The latest value of the parameter.
+    pub fn get(&self) -> f32 {
+        self.shared.get()
+    }
+}
+
+impl Shared {
+    fn get(&self) -> f32 {
+        f32::from_bits(self.value.load(Ordering::Acquire))
+    }
+
+    fn store(&self, value: f32) {
+        self.value.store(value.to_bits(), Ordering::Release);
+        self.version.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+impl<Ix> Clone for ParamBinding<Ix>
+where
+    Ix: Copy,
+{
+    fn clone(&self) -> Self {
+        self.shared.bindings.fetch_add(1, Ordering::AcqRel);
+        ParamBinding {
+            node: self.node,
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<Ix> Drop for ParamBinding<Ix> {
+    fn drop(&mut self) {
+        self.shared.bindings.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<Ix> fmt::Debug for ParamBinding<Ix>
+where
+    Ix: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParamBinding")
+            .field("node", &self.node)
+            .field("param", &self.shared.param)
+            .finish()
+    }
+}
+
+impl fmt::Debug for ParamHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParamHandle")
+            .field("param", &self.shared.param)
+            .field("value", &self.get())
+            .field("attached", &self.is_attached())
+            .finish()
+    }
+}
+
+impl fmt::Debug for ParamSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParamSubscription")
+            .field("param", &self.shared.param)
+            .field("seen", &self.seen)
+            .finish()
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// A handle to the parameter `param` of the node at the given index, for binding the
+    /// parameter to a GUI.
+    ///
+    /// As nodes do not expose the values of their parameters, `value` should be the current value
+    /// of the parameter, which the handle reports until it is set or the node reports a change.
+    /// If a handle to the same parameter already exists, the new handle shares its value and
+    /// `value` is ignored.
+    ///
+    /// Returns an error if there is no node for the given index.
+    pub fn param_handle(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        param: usize,
+        value: f32,
+    ) -> Result<ParamHandle, RequestError<Ix>> {
+        self.check_node(idx)?;
+        let existing = self
+            .state
+            .param_handles
+            .iter()
+            .find(|binding| binding.node == idx && binding.shared.param == param);
+        if let Some(binding) = existing {
+            return Ok(ParamHandle {
+                shared: binding.shared.clone(),
+            });
+        }
+        // The value is the one from which the first change via the handle is smoothed.
+        self.state.node_meta[idx.index()]
+            .smoothing
+            .seed(param, value);
+        let shared = Arc::new(Shared {
+            param,
+            value: AtomicU32::new(value.to_bits()),
+            version: AtomicU64::new(0),
+            pending: AtomicBool::new(false),
+            bindings: AtomicUsize::new(1),
+        });
+        self.state.param_handles.push(ParamBinding {
+            node: idx,
+            shared: shared.clone(),
+        });
+        Ok(ParamHandle { shared })
+    }
+
+    /// The number of parameters bound to handles that have not yet been found to be dropped.
+    pub fn param_handle_count(&self) -> usize {
+        self.state.param_handles.len()
+    }
+
+    /// Whether any values set via handles are yet to be delivered to their nodes.
+    pub(crate) fn has_pending_param_handles(&self) -> bool {
+        self.state
+            .param_handles
+            .iter()
+            .any(|binding| binding.shared.pending.load(Ordering::Acquire))
+    }
+
+    /// Queue the values set via handles since the last request for audio as events for their
+    /// nodes, and release the bindings of parameters whose handles have all been dropped.
+    pub(crate) fn deliver_param_handles(&mut self) {
+        let node_meta = &mut self.state.node_meta;
+        self.state.param_handles.retain(|binding| {
+            let shared = &binding.shared;
+            if shared.pending.swap(false, Ordering::AcqRel) {
+                let event = Event::Param {
+                    param: shared.param,
+                    value: shared.get(),
+                };
+                node_meta[binding.node.index()].events.push(event);
+            }
+            Arc::strong_count(shared) > 1
+        });
+    }
+
+    /// Update the handles to the parameters of the node at the given index with its collected
+    /// parameter changes.
+    pub(crate) fn update_param_handles(&mut self, idx: NodeIndex<Ix>) {
+        for binding in self.state.param_handles.iter().filter(|b| b.node == idx) {
+            let param = binding.shared.param;
+            let latest = self
+                .state
+                .param_changes
+                .iter()
+                .rev()
+                .find(|c| c.param == param);
+            if let Some(change) = latest {
+                binding.shared.store(change.value);
+            }
+        }
+    }
+
+    /// Update the parameter handles after the node at `idx` was removed and the last node was
+    /// shifted into its place.
+    pub(crate) fn remove_node_param_handles(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        self.state
+            .param_handles
+            .retain(|binding| binding.node != idx);
+        for binding in &mut self.state.param_handles {
+            if binding.node == last {
+                binding.node = idx;
+            }
+        }
+    }
+}
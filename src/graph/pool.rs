@@ -0,0 +1,147 @@
+//! A pool of pre-sized buffers from which connection buffers are drawn, so that restructuring
+//! the **Graph** does not require allocating on the audio thread.
+
+use super::{resize_buffer_to, Connection, Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use daggy::Walker;
+use dasp::Frame;
+
+/// Spare buffers of the **Graph**'s current buffer size.
+#[derive(Clone, Debug)]
+pub(crate) struct BufferPool<F> {
+    buffers: Vec<Vec<F>>,
+    buffer_size: usize,
+}
+
+impl<F> BufferPool<F>
+where
+    F: Frame,
+{
+    /// A new, empty pool.
+    pub fn new() -> Self {
+        BufferPool {
+            buffers: Vec::new(),
+            buffer_size: 0,
+        }
+    }
+
+    /// The number of spare buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Take a buffer of the current buffer size from the pool, allocating one if it is empty.
+    pub fn take(&mut self) -> Vec<F> {
+        let mut buffer = self.buffers.pop().unwrap_or_default();
+        resize_buffer_to(&mut buffer, self.buffer_size);
+        buffer
+    }
+
+    /// Return a buffer to the pool.
+    pub fn give(&mut self, buffer: Vec<F>) {
+        if buffer.capacity() > 0 {
+            self.buffers.push(buffer);
+        }
+    }
+
+    /// Ensure that the pool holds at least `count` spare buffers.
+    pub fn reserve(&mut self, count: usize) {
+        let size = self.buffer_size;
+        while self.buffers.len() < count {
+            self.buffers.push(vec![F::EQUILIBRIUM; size]);
+        }
+    }
+
+    /// Resize all spare buffers to the given size.
+    pub fn resize(&mut self, buffer_size: usize) {
+        self.buffer_size = buffer_size;
+        for buffer in &mut self.buffers {
+            resize_buffer_to(buffer, buffer_size);
+        }
+    }
+
+    /// Release the memory held by all spare buffers.
+    pub fn clear(&mut self) {
+        self.buffers = Vec::new();
+    }
+
+    /// A new connection with buffers drawn from the pool.
+    pub fn connection(&mut self, double_buffered: bool) -> Connection<F> {
+        let mut connection = Connection::new();
+        connection.buffer = self.take();
+        if double_buffered {
+            connection.previous = self.take();
+        }
+        connection
+    }
+
+    /// Return the buffers of the given connection to the pool.
+    pub fn recycle(&mut self, connection: &mut Connection<F>) {
+        self.give(std::mem::take(&mut connection.buffer));
+        self.give(std::mem::take(&mut connection.previous));
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Ensure that at least `count` spare connection buffers of the current buffer size are
+    /// pooled.
+    ///
+    /// New connections draw their buffers from the pool and removed connections return their
+    /// buffers to it. Reserving buffers ahead of time ensures that connections can be added while
+    /// running without allocating. Buffers are sized by `prepare_buffers`.
+    pub fn reserve_buffers(&mut self, count: usize) {
+        self.state.pool.reserve(count);
+    }
+
+    /// The number of spare connection buffers currently pooled.
+    pub fn pooled_buffer_count(&self) -> usize {
+        self.state.pool.len()
+    }
+
+    /// Release the memory held by all spare pooled buffers.
+    pub fn clear_buffer_pool(&mut self) {
+        self.state.pool.clear();
+    }
+
+    /// When enabled, requesting audio from the **Graph** panics if it would require the
+    /// **Graph** to allocate.
+    ///
+    /// This happens if the buffer size has changed, or if the **Graph** was restructured since
+    /// `prepare_buffers` was last called. It is intended for catching such cases during
+    /// development and does not cover allocations made by the nodes themselves.
+    pub fn set_assert_no_alloc(&mut self, assert: bool) {
+        self.state.assert_no_alloc = assert;
+    }
+
+    /// Whether or not requesting audio panics if it would require the **Graph** to allocate.
+    pub fn asserts_no_alloc(&self) -> bool {
+        self.state.assert_no_alloc
+    }
+
+    /// Called where requesting audio would allocate, panicking if this is not allowed.
+    pub(crate) fn note_alloc(&self, cause: &str) {
+        assert!(
+            !self.state.assert_no_alloc,
+            "the graph allocated while rendering: {}",
+            cause
+        );
+    }
+
+    /// Return the buffers of all connections to and from the given node to the pool.
+    pub(crate) fn recycle_node_connections(&mut self, idx: NodeIndex<Ix>) {
+        let mut inputs = self.inputs(idx);
+        while let Some(edge) = inputs.next_edge(self) {
+            self.state.pool.recycle(&mut self.dag[edge]);
+        }
+        let mut outputs = self.outputs(idx);
+        while let Some(edge) = outputs.next_edge(self) {
+            self.state.pool.recycle(&mut self.dag[edge]);
+        }
+    }
+}
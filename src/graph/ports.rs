@@ -0,0 +1,143 @@
+//! Passing the inputs of nodes that process with separate input and output buffers.
+
+use super::{mix, ramp, resize_buffer_to, Connection, Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use daggy::Walker;
+use dasp::{self, Frame};
+
+/// The number of inputs that a node rendering via `Node::process` may have before passing their
+/// slices to it requires an allocation.
+pub(crate) const MAX_STACK_PORTS: usize = 32;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Whether the inputs of the node at the given index are written to input buffers of their
+    /// own, i.e. whether it or the node that it replaced processes separately from its inputs.
+    pub(crate) fn gathers_inputs(&self, idx: NodeIndex<Ix>) -> bool {
+        self.dag[idx].separate_io() || self.replaced_node(idx).is_some_and(N::separate_io)
+    }
+
+    /// Write each input of the node at the given index to its own input buffer, for nodes that
+    /// render via `Node::process`, and set `output` to the sum of all inputs.
+    ///
+    /// Returns the latency of the slowest path into the node.
+    pub(crate) fn gather_inputs(&mut self, node_idx: NodeIndex<Ix>, output: &mut [F]) -> usize {
+        let step = self.ramp_step();
+        let len = output.len();
+
+        // Find the latency of the slowest path into the current node and the number of ports.
+        let mut max_input_latency = 0;
+        let mut num_ports = 0;
+        let mut inputs = self.inputs(node_idx);
+        while let Some((connection_idx, input_idx)) = inputs.next(self) {
+            if self.dag[connection_idx].enabled {
+                let latency = self.state.path_latencies[input_idx.index()];
+                max_input_latency = std::cmp::max(max_input_latency, latency);
+                num_ports += 1;
+            }
+        }
+        num_ports += self
+            .state
+            .feedback
+            .iter()
+            .filter(|fb| fb.destination() == node_idx)
+            .count();
+
+        // Ensure there is an input buffer of the right size for each port.
+        let prepared = self.state.input_buffers.len() >= num_ports
+            && self.state.input_buffers[..num_ports]
+                .iter()
+                .all(|b| b.len() == len);
+        if !prepared {
+            self.note_alloc("a node has more inputs than were prepared for");
+            if self.state.input_buffers.len() < num_ports {
+                self.state.input_buffers.resize_with(num_ports, Vec::new);
+            }
+            for buffer in &mut self.state.input_buffers[..num_ports] {
+                resize_buffer_to(buffer, len);
+            }
+        }
+
+        // Write each enabled connection to its port, delaying faster paths so that they are
+        // aligned with the slowest.
+        dasp::slice::equilibrium(output);
+        let mut port = 0;
+        let mut inputs = self.inputs(node_idx);
+        while let Some((connection_idx, input_idx)) = inputs.next(self) {
+            if !self.dag[connection_idx].enabled {
+                continue;
+            }
+            let delay = max_input_latency - self.state.path_latencies[input_idx.index()];
+            if !self.dag[connection_idx].compensation.fits(delay) {
+                self.note_alloc("a node's latency rose since the buffers were prepared");
+            }
+            let port_buffer = &mut self.state.input_buffers[port];
+            let Connection {
+                ref buffer,
+                ref mut compensation,
+                ref mut gain,
+                ..
+            } = self.dag[connection_idx];
+            compensation.set_delay(delay);
+            for (port_frame, &con_frame) in port_buffer.iter_mut().zip(buffer) {
+                *port_frame = compensation.process(con_frame);
+            }
+            if *gain < 1.0 {
+                ramp::ramp_in_place(port_buffer, gain, step);
+            }
+            mix::sum_onto(output, port_buffer);
+            port += 1;
+        }
+
+        // Feedback connections follow as ports of their own.
+        for fb in self
+            .state
+            .feedback
+            .iter()
+            .filter(|fb| fb.destination() == node_idx)
+        {
+            let port_buffer = &mut self.state.input_buffers[port];
+            dasp::slice::write(port_buffer, fb.buffer());
+            mix::sum_onto(output, port_buffer);
+            port += 1;
+        }
+
+        self.sum_fading(node_idx, output);
+        self.state.num_ports = num_ports;
+        max_input_latency
+    }
+
+    /// Resize the input buffers used by nodes that render via `Node::process`.
+    pub(crate) fn prepare_input_buffers(&mut self, buffer_size: usize) {
+        let max_ports = self
+            .dag
+            .raw_nodes()
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.weight.separate_io())
+            .map(|(i, _)| {
+                let idx = NodeIndex::new(i);
+                let num_inputs = self.inputs(idx).count(self);
+                let num_feedback = self
+                    .state
+                    .feedback
+                    .iter()
+                    .filter(|fb| fb.destination() == idx)
+                    .count();
+                num_inputs + num_feedback
+            })
+            .max()
+            .unwrap_or(0);
+        if self.state.input_buffers.len() < max_ports {
+            self.state.input_buffers.resize_with(max_ports, Vec::new);
+        }
+        for buffer in &mut self.state.input_buffers {
+            resize_buffer_to(buffer, buffer_size);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Presets storing the parameters of many nodes at once, and morphing a **Graph** between them.
+
+use super::{Graph, NodeId};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+/// A stored set of parameter values across the nodes of a **Graph**, e.g. a patch or a scene.
+///
+/// Presets are captured via `Graph::capture_preset` or built via `set`, and recalled via
+/// `Graph::morph_to`. Nodes are referred to by **NodeId**, so a preset remains valid as other
+/// nodes are added to and removed from the **Graph**.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Preset {
+    values: Vec<(NodeId, usize, f32)>,
+}
+
+impl Preset {
+    /// An empty preset.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of the parameter `param` of the node with the given id.
+    pub fn set(&mut self, node: NodeId, param: usize, value: f32) {
+        let existing = self
+            .values
+            .iter_mut()
+            .find(|&&mut (n, p, _)| n == node && p == param);
+        match existing {
+            Some((_, _, v)) => *v = value,
+            None => self.values.push((node, param, value)),
+        }
+    }
+
+    /// The same preset with the value of the parameter `param` of the node with the given id.
+    pub fn with_value(mut self, node: NodeId, param: usize, value: f32) -> Self {
+        self.set(node, param, value);
+        self
+    }
+
+    /// The value of the parameter `param` of the node with the given id.
+    pub fn value(&self, node: NodeId, param: usize) -> Option<f32> {
+        self.values
+            .iter()
+            .find(|&&(n, p, _)| n == node && p == param)
+            .map(|&(_, _, v)| v)
+    }
+
+    /// Remove the value of the parameter `param` of the node with the given id, returning it.
+    pub fn remove(&mut self, node: NodeId, param: usize) -> Option<f32> {
+        let i = self
+            .values
+            .iter()
+            .position(|&(n, p, _)| n == node && p == param)?;
+        Some(self.values.remove(i).2)
+    }
+
+    /// Every stored value along with the id of its node and the index of its parameter.
+    pub fn values(&self) -> &[(NodeId, usize, f32)] {
+        &self.values
+    }
+
+    /// The number of stored values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the preset stores no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Capture the current value of every parameter known to the **Graph** as a **Preset**.
+    ///
+    /// The **Graph** only knows the values of parameters that have been set via `Event::Param`
+    /// or a **ParamHandle**, or reported via `Node::param_changes`. Parameters that are being
+    /// smoothed are captured at the value towards which they are moving.
+    pub fn capture_preset(&self) -> Preset {
+        let mut preset = Preset::new();
+        for meta in &self.state.node_meta {
+            let id = NodeId::new(meta.id);
+            for (param, value) in meta.smoothing.targets() {
+                preset.values.push((id, param, value));
+            }
+        }
+        preset
+    }
+
+    /// Morph every parameter stored in the preset from its current value towards the stored
+    /// value over the given number of seconds, e.g. for an expressive transition between two
+    /// whole patches.
+    ///
+    /// Each parameter moves via the **Graph**'s parameter smoothing as though it had been set
+    /// via `Event::Param` with a smoothing time of `seconds`, whatever its own smoothing, so the
+    /// morph is rendered identically regardless of the buffer size and the ramps begin as each
+    /// node is next rendered. Parameters whose smoothing is `ParamSmoothing::Stepped` are
+    /// discrete and take their stored value immediately, as do parameters whose current value is
+    /// unknown. Setting a parameter while it morphs starts a new ramp from wherever the morph
+    /// has reached.
+    ///
+    /// Values for nodes that are no longer within the **Graph** are ignored.
+    pub fn morph_to(&mut self, preset: &Preset, seconds: f32) {
+        let ms = seconds.max(0.0) * 1_000.0;
+        for &(id, param, value) in &preset.values {
+            let idx = match self.index_of(id) {
+                Some(idx) => idx,
+                None => continue,
+            };
+            let meta = &mut self.state.node_meta[idx.index()];
+            if meta.smoothing.target(param) == Some(value) {
+                continue;
+            }
+            let ms = if meta.smoothing.is_stepped(param) {
+                0.0
+            } else {
+                ms
+            };
+            if let Some(event) = meta.smoothing.ramp_to(param, value, ms) {
+                meta.events.push(event);
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Scheduling the punch-ins and punch-outs of recorders against the transport.
+
+use super::{Graph, NodeVariant, RequestError, TypedNodeIndex};
+use crate::event::Event;
+use crate::node::Node;
+use crate::nodes::Recorder;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::ops::Range;
+
+/// The parameter via which a **Recorder** is punched in and out.
+const PUNCH_PARAM: usize = 1;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F> + NodeVariant<Recorder>,
+    Ix: IndexType,
+{
+    /// Punch the **Recorder** at the given index in at the transport frame `punch.start` and out
+    /// at `punch.end`.
+    ///
+    /// Both take effect at exactly those frames, as the recorder's buffer is split at each event
+    /// boundary, and the take is marked as beginning at `punch.start`. The recorder only records
+    /// if it is armed when the punch-in is delivered. Multiple punches may be scheduled ahead of
+    /// time, in order.
+    ///
+    /// While loop recording, the host should relocate the transport to the start of the region
+    /// for each pass, and schedule the punch-out at the frame at which the last pass ends.
+    pub fn schedule_punch(
+        &mut self,
+        idx: TypedNodeIndex<Recorder, Ix>,
+        punch: Range<u64>,
+    ) -> Result<(), RequestError<Ix>> {
+        let node_idx = idx.node_index();
+        self.node_as_mut(idx)
+            .ok_or(RequestError::NoNode(node_idx))?
+            .push_punch(punch.start);
+        let punch_in = Event::Param {
+            param: PUNCH_PARAM,
+            value: 1.0,
+        };
+        let punch_out = Event::Param {
+            param: PUNCH_PARAM,
+            value: 0.0,
+        };
+        self.schedule_event(node_idx, punch.start, punch_in)?;
+        self.schedule_event(node_idx, punch.end, punch_out)
+    }
+}
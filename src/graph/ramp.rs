@@ -0,0 +1,237 @@
+//! Gain ramps on connections that are added or removed while rendering, so that live patching
+//! does not produce clicks.
+
+use super::latency::Compensation;
+use super::{mix, Connection, Graph, NodeIndex};
+use crate::node::Node;
+use crate::slice;
+use daggy::petgraph::graph::IndexType;
+use dasp::{self, Frame, Sample};
+
+/// A removed connection whose last source output is faded out of its destination.
+#[derive(Clone, Debug)]
+pub(crate) struct FadingConnection<F, Ix> {
+    source: NodeIndex<Ix>,
+    destination: NodeIndex<Ix>,
+    /// The output rendered by the source during the current request.
+    buffer: Vec<F>,
+    /// Whether the source has been rendered during the current request.
+    written: bool,
+    /// The current gain of the fade, falling from `1.0` to `0.0`.
+    gain: f32,
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Set the number of frames over which connections are faded in after being added and faded
+    /// out after being removed.
+    ///
+    /// While a removed connection fades out, its source node continues to be rendered and its
+    /// output is summed onto the input of the connection's former destination. If the source is
+    /// rendered after the destination in the new visit order, the fade is cut short.
+    ///
+    /// By default, this is `0` and connections take effect immediately.
+    pub fn set_connection_ramp_frames(&mut self, frames: usize) {
+        self.state.connection_ramp_frames = frames;
+        if frames == 0 {
+            self.clear_fading_connections();
+        }
+    }
+
+    /// The number of frames over which connections are faded in after being added and faded out
+    /// after being removed.
+    pub fn connection_ramp_frames(&self) -> usize {
+        self.state.connection_ramp_frames
+    }
+
+    /// A new connection with buffers drawn from the pool, faded in if connection ramps are
+    /// enabled.
+    pub(crate) fn new_connection(&mut self) -> Connection<F> {
+        let mut connection = self.state.pool.connection(self.state.double_buffered);
+        if self.state.connection_ramp_frames > 0 {
+            connection.gain = 0.0;
+        }
+        connection
+    }
+
+    /// The amount by which ramp gains change each frame.
+    pub(crate) fn ramp_step(&self) -> f32 {
+        1.0 / self.state.connection_ramp_frames.max(1) as f32
+    }
+
+    /// Fade the given connection out of `dest` if connection ramps are enabled, otherwise return
+    /// its buffers to the pool.
+    pub(crate) fn fade_out_connection(
+        &mut self,
+        src: NodeIndex<Ix>,
+        dest: NodeIndex<Ix>,
+        mut connection: Connection<F>,
+    ) {
+        if self.state.connection_ramp_frames > 0 {
+            self.state.fading.push(FadingConnection {
+                source: src,
+                destination: dest,
+                buffer: std::mem::take(&mut connection.buffer),
+                written: false,
+                gain: connection.gain,
+            });
+            self.state.render_order_node = None;
+        }
+        self.state.pool.recycle(&mut connection);
+    }
+
+    /// The sources of all connections fading out of the given node.
+    pub(crate) fn fading_sources(
+        &self,
+        dest: NodeIndex<Ix>,
+    ) -> impl Iterator<Item = NodeIndex<Ix>> + '_ {
+        self.state
+            .fading
+            .iter()
+            .filter(move |fading| fading.destination == dest)
+            .map(|fading| fading.source)
+    }
+
+    /// Store the given output of a node in all fading connections from that node.
+    pub(crate) fn write_fading(&mut self, node_idx: NodeIndex<Ix>, output: &[F]) {
+        for fading in self
+            .state
+            .fading
+            .iter_mut()
+            .filter(|f| f.source == node_idx)
+        {
+            if fading.buffer.len() != output.len() {
+                fading.buffer.resize(output.len(), F::EQUILIBRIUM);
+            }
+            dasp::slice::write(&mut fading.buffer, output);
+            fading.written = true;
+        }
+    }
+
+    /// Sum the fading connections into the given node onto `output`, advancing their fades.
+    pub(crate) fn sum_fading(&mut self, node_idx: NodeIndex<Ix>, output: &mut [F]) {
+        let step = self.ramp_step();
+        for fading in self
+            .state
+            .fading
+            .iter_mut()
+            .filter(|f| f.destination == node_idx)
+        {
+            if !fading.written {
+                fading.gain = 0.0;
+                continue;
+            }
+            let (frames, from, to) = advance_gain(&mut fading.gain, 0.0, step, output.len());
+            let buffer = &mut fading.buffer[..frames];
+            slice::apply_ramp(buffer, from, to);
+            mix::sum_onto(&mut output[..frames], buffer);
+        }
+    }
+
+    /// Remove the connections that have finished fading out, returning their buffers to the pool.
+    pub(crate) fn advance_fading(&mut self) {
+        let mut i = 0;
+        while i < self.state.fading.len() {
+            let fading = &mut self.state.fading[i];
+            if fading.gain <= 0.0 || !fading.written {
+                let fading = self.state.fading.swap_remove(i);
+                self.state.pool.give(fading.buffer);
+                self.state.render_order_node = None;
+            } else {
+                fading.written = false;
+                i += 1;
+            }
+        }
+    }
+
+    /// Remove all fading connections, returning their buffers to the pool.
+    pub(crate) fn clear_fading_connections(&mut self) {
+        for fading in self.state.fading.drain(..) {
+            self.state.pool.give(fading.buffer);
+        }
+        self.state.render_order_node = None;
+    }
+
+    /// Update the fading connections after the node at `idx` was removed and the last node was
+    /// shifted into its place.
+    pub(crate) fn remove_node_fading(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        self.state
+            .fading
+            .retain(|fading| fading.source != idx && fading.destination != idx);
+        for fading in &mut self.state.fading {
+            if fading.source == last {
+                fading.source = idx;
+            }
+            if fading.destination == last {
+                fading.destination = idx;
+            }
+        }
+    }
+}
+
+/// Advance `gain` linearly towards `target` over a buffer of `len` frames, changing by at most
+/// `step` each frame.
+///
+/// Returns the number of frames over which the gain changes, along with the gains to pass to
+/// `slice::apply_ramp` or `slice::crossfade_into` for those frames. The gain remains at `target`
+/// for the rest of the buffer.
+pub(crate) fn advance_gain(
+    gain: &mut f32,
+    target: f32,
+    step: f32,
+    len: usize,
+) -> (usize, f32, f32) {
+    let distance = target - *gain;
+    if distance == 0.0 || len == 0 {
+        return (0, *gain, *gain);
+    }
+    let ramp_frames = (distance.abs() / step).ceil().max(1.0) as usize;
+    let per_frame = distance / ramp_frames as f32;
+    let frames = ramp_frames.min(len);
+    let from = *gain + per_frame;
+    let to = from + per_frame * frames as f32;
+    *gain = if frames == ramp_frames {
+        target
+    } else {
+        *gain + per_frame * frames as f32
+    };
+    (frames, from, to)
+}
+
+/// Sum `buffer` onto `output`, passing it through the connection's latency `compensation` and
+/// fading it in by advancing `gain` towards `1.0` by `step` each frame.
+pub(crate) fn sum_ramped<F>(
+    output: &mut [F],
+    buffer: &[F],
+    compensation: &mut Compensation<F>,
+    gain: &mut f32,
+    step: f32,
+) where
+    F: Frame,
+{
+    let (frames, from, to) = advance_gain(gain, 1.0, step, output.len());
+    let per_frame = (to - from) / frames.max(1) as f32;
+    for (i, (out_frame, &con_frame)) in output.iter_mut().zip(buffer).enumerate() {
+        let con_frame = compensation.process(con_frame);
+        let gain = if i < frames {
+            from + per_frame * i as f32
+        } else {
+            1.0
+        };
+        let con_frame = con_frame.scale_amp(gain.to_sample());
+        *out_frame = out_frame.add_amp(con_frame.to_signed_frame());
+    }
+}
+
+/// Fade `buffer` in place by advancing `gain` towards `1.0` by `step` each frame.
+pub(crate) fn ramp_in_place<F>(buffer: &mut [F], gain: &mut f32, step: f32)
+where
+    F: Frame,
+{
+    let (frames, from, to) = advance_gain(gain, 1.0, step, buffer.len());
+    slice::apply_ramp(&mut buffer[..frames], from, to);
+}
@@ -0,0 +1,186 @@
+//! Replacing nodes while crossfading from their outgoing instance, e.g. for gapless preset
+//! switching.
+
+use super::{mix, ramp, resize_buffer_to, Graph, NodeIndex, Notification, RequestError};
+use crate::node::Node;
+use crate::slice;
+use daggy::petgraph::graph::IndexType;
+use dasp::{self, Frame, Sample};
+
+/// A node that was replaced and is being crossfaded out.
+#[derive(Clone, Debug)]
+pub(crate) struct Replaced<N, Ix> {
+    /// The index of the node that replaced it.
+    node: NodeIndex<Ix>,
+    previous: N,
+    /// The amount of the replacement in the mix, from `0.0` to `1.0`.
+    mix: f32,
+    /// The amount by which `mix` changes each frame.
+    step: f32,
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Replace the node at the given index with `new_node`, crossfading from the output of the
+    /// old node to that of the new node over the given number of frames.
+    ///
+    /// This allows instruments to switch presets gaplessly: the new preset is loaded into a
+    /// shadow instance off the audio thread, which then takes over here. Each note held by the
+    /// old node (see `Node::held_notes`) is started on the new node, so that held notes carry on
+    /// sounding with the new preset. Until the crossfade completes, the old node continues to
+    /// render from the same input and to receive the events sent to the node, so that its voices
+    /// are released as usual.
+    ///
+    /// Once the crossfade completes, `Notification::NodeReplaced` is emitted and the old node may
+    /// be collected via `drain_replaced_nodes`, so that it is not deallocated on the audio thread.
+    /// If the node is replaced again before the crossfade completes, the previous crossfade is cut
+    /// short.
+    ///
+    /// As with `replace_node`, all connections are left intact.
+    pub fn crossfade_replace_node(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        mut new_node: N,
+        frames: usize,
+    ) -> Result<(), RequestError<Ix>> {
+        self.check_node(idx)?;
+        let mut held = Vec::new();
+        self.dag[idx].held_notes(&mut held);
+        for event in &held {
+            new_node.handle_event(event);
+        }
+        let previous = self
+            .replace_node(idx, new_node)
+            .expect("the node was checked");
+        if let Some(i) = self.replaced.iter().position(|r| r.node == idx) {
+            let cut = self.replaced.swap_remove(i);
+            self.retired_nodes.push(cut.previous);
+        }
+        if frames == 0 {
+            self.retired_nodes.push(previous);
+            self.state.notifier.send(Notification::NodeReplaced(idx));
+            return Ok(());
+        }
+        self.replaced.push(Replaced {
+            node: idx,
+            previous,
+            mix: 0.0,
+            step: 1.0 / frames as f32,
+        });
+        // Ensure retiring the node on the audio thread does not allocate.
+        self.retired_nodes.reserve(self.replaced.len());
+        Ok(())
+    }
+
+    /// Whether the node at the given index is crossfading from the node that it replaced.
+    pub fn is_crossfading_replacement(&self, idx: NodeIndex<Ix>) -> bool {
+        self.replaced.iter().any(|r| r.node == idx)
+    }
+
+    /// Remove and yield all nodes that have been crossfaded out since this was last called.
+    pub fn drain_replaced_nodes(&mut self) -> std::vec::Drain<'_, N> {
+        self.retired_nodes.drain(..)
+    }
+
+    /// The node replaced by the node at the given index, if it is crossfading from one.
+    pub(crate) fn replaced_node(&self, idx: NodeIndex<Ix>) -> Option<&N> {
+        self.replaced
+            .iter()
+            .find(|r| r.node == idx)
+            .map(|r| &r.previous)
+    }
+
+    /// Whether the node replaced by the node at the given index, if any, mixes in its dry signal.
+    pub(crate) fn replaced_has_dry(&self, idx: NodeIndex<Ix>) -> bool {
+        self.replaced_node(idx)
+            .is_some_and(|node| node.dry() != Sample::EQUILIBRIUM)
+    }
+
+    /// Store the input of the node at the given index for rendering the node that it replaced,
+    /// if it is crossfading from one.
+    pub(crate) fn store_replaced_input(&mut self, idx: NodeIndex<Ix>, input: &[F]) {
+        if !self.is_crossfading_replacement(idx) {
+            return;
+        }
+        if self.state.replace_buffer.len() != input.len() {
+            self.note_alloc("the replacement buffer was not prepared");
+            resize_buffer_to(&mut self.state.replace_buffer, input.len());
+        }
+        slice::write(&mut self.state.replace_buffer, input);
+    }
+
+    /// Crossfade the output of the node replaced by the node at the given index, rendered
+    /// alongside it by `render_node`, with the replacement's `output`.
+    ///
+    /// The dry signal is mixed into the replaced node's output as for any other node, in which
+    /// case `has_dry` must be true.
+    pub(crate) fn crossfade_replaced(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        output: &mut [F],
+        has_dry: bool,
+    ) {
+        let i = match self.replaced.iter().position(|r| r.node == idx) {
+            Some(i) => i,
+            None => return,
+        };
+        let replaced = &mut self.replaced[i];
+        let buffer = &mut self.state.replace_buffer[..output.len()];
+        let (dry, wet) = (replaced.previous.dry(), replaced.previous.wet());
+        if has_dry {
+            mix::mix_dry_wet(buffer, &self.state.dry_buffer, dry, wet);
+        } else if wet != <F::Sample as Sample>::IDENTITY {
+            dasp::slice::map_in_place(buffer, |f| f.scale_amp(wet));
+        }
+        let (frames, from, to) =
+            ramp::advance_gain(&mut replaced.mix, 1.0, replaced.step, output.len());
+        slice::crossfade_into(&mut buffer[..frames], &output[..frames], from, to);
+        slice::write(&mut output[..frames], &buffer[..frames]);
+        if replaced.mix >= 1.0 {
+            let replaced = self.replaced.swap_remove(i);
+            self.retired_nodes.push(replaced.previous);
+            self.state.notifier.send(Notification::NodeReplaced(idx));
+        }
+    }
+
+    /// Update the replaced nodes after the node at `idx` was removed and the last node was
+    /// shifted into its place.
+    pub(crate) fn remove_node_replaced(&mut self, idx: NodeIndex<Ix>, last: NodeIndex<Ix>) {
+        let mut i = 0;
+        while i < self.replaced.len() {
+            if self.replaced[i].node == idx {
+                let replaced = self.replaced.swap_remove(i);
+                self.retired_nodes.push(replaced.previous);
+            } else {
+                if self.replaced[i].node == last {
+                    self.replaced[i].node = idx;
+                }
+                i += 1;
+            }
+        }
+    }
+
+    /// Cut all crossfades short, retiring the replaced nodes.
+    pub(crate) fn clear_replaced(&mut self) {
+        let retired = self.replaced.drain(..).map(|replaced| replaced.previous);
+        self.retired_nodes.extend(retired);
+    }
+}
+
+/// The node replaced by the node at the given index, if it is crossfading from one.
+pub(crate) fn previous_node_mut<N, Ix>(
+    replaced: &mut [Replaced<N, Ix>],
+    idx: NodeIndex<Ix>,
+) -> Option<&mut N>
+where
+    Ix: IndexType,
+{
+    replaced
+        .iter_mut()
+        .find(|r| r.node == idx)
+        .map(|r| &mut r.previous)
+}
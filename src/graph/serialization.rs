@@ -0,0 +1,230 @@
+//! Serialization of the **Graph**'s topology and node state via `serde`.
+//!
+//! Audio buffers are skipped: they are reallocated the next time audio is requested, or may be
+//! prepared up front via `Graph::prepare_buffers`.
+
+use super::{Connection, FeedbackConnection, Graph, NodeId, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use serde::{Deserialize as DeriveDeserialize, Serialize as DeriveSerialize};
+use std::ops::Range;
+
+/// A node along with the state maintained by the **Graph** on its behalf.
+#[derive(DeriveSerialize, DeriveDeserialize)]
+struct NodeEntry<N> {
+    node: N,
+    #[serde(default)]
+    id: Option<u64>,
+    bypassed: bool,
+    muted: bool,
+    soloed: bool,
+    active_range: Option<Range<u64>>,
+}
+
+#[derive(DeriveSerialize, DeriveDeserialize)]
+struct ConnectionEntry {
+    enabled: bool,
+}
+
+// Nodes are referred to by their position in `nodes`, so that the serialized form does not
+// depend on the **Graph**'s index type.
+#[derive(DeriveSerialize, DeriveDeserialize)]
+struct FeedbackEntry {
+    source: usize,
+    destination: usize,
+}
+
+#[derive(DeriveSerialize)]
+#[serde(bound = "N: Serialize", rename = "Graph")]
+struct GraphRef<'a, N> {
+    nodes: Vec<NodeEntry<&'a N>>,
+    connections: Vec<(usize, usize, ConnectionEntry)>,
+    feedback: Vec<FeedbackEntry>,
+    master: Option<usize>,
+}
+
+#[derive(DeriveDeserialize)]
+#[serde(bound = "N: Deserialize<'de>", rename = "Graph")]
+struct GraphData<N> {
+    nodes: Vec<NodeEntry<N>>,
+    connections: Vec<(usize, usize, ConnectionEntry)>,
+    #[serde(default)]
+    feedback: Vec<FeedbackEntry>,
+    #[serde(default)]
+    master: Option<usize>,
+}
+
+impl<F> Serialize for Connection<F> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        ConnectionEntry {
+            enabled: self.enabled,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, F> Deserialize<'de> for Connection<F>
+where
+    F: Frame,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let entry = ConnectionEntry::deserialize(deserializer)?;
+        let mut connection = Connection::new();
+        connection.enabled = entry.enabled;
+        Ok(connection)
+    }
+}
+
+impl<F, Ix> Serialize for FeedbackConnection<F, Ix>
+where
+    Ix: IndexType,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        FeedbackEntry {
+            source: self.source().index(),
+            destination: self.destination().index(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<F, N, Ix> Serialize for Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Serialize,
+    Ix: IndexType,
+{
+    /// Serialize the nodes along with their ids and their bypass, mute, solo and active range
+    /// state, the connections between them, the feedback connections and the master node.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let nodes = self
+            .dag
+            .raw_nodes()
+            .iter()
+            .zip(&self.state.node_meta)
+            .map(|(node, meta)| NodeEntry {
+                node: &node.weight,
+                id: Some(meta.id),
+                bypassed: meta.bypassed,
+                muted: meta.muted,
+                soloed: meta.soloed,
+                active_range: meta.active_range.clone(),
+            })
+            .collect();
+        let connections = self
+            .dag
+            .raw_edges()
+            .iter()
+            .map(|edge| {
+                let entry = ConnectionEntry {
+                    enabled: edge.weight.enabled,
+                };
+                (edge.source().index(), edge.target().index(), entry)
+            })
+            .collect();
+        let feedback = self
+            .state
+            .feedback
+            .iter()
+            .map(|fb| FeedbackEntry {
+                source: fb.source().index(),
+                destination: fb.destination().index(),
+            })
+            .collect();
+        GraphRef {
+            nodes,
+            connections,
+            feedback,
+            master: self.state.maybe_master.map(|master| master.index()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, F, N, Ix> Deserialize<'de> for Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F> + Deserialize<'de>,
+    Ix: IndexType,
+{
+    /// Rebuild a **Graph** from its serialized topology and node state.
+    ///
+    /// Each node keeps its serialized **NodeId**, while nodes serialized without one are assigned
+    /// new ids.
+    ///
+    /// Fails if two nodes share an id, or if a connection would create a cycle or refers to a node
+    /// that does not exist.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = GraphData::<N>::deserialize(deserializer)?;
+        let mut graph = Graph::with_capacity_indexed(data.nodes.len(), data.connections.len(), 0);
+        // Reserve the serialized ids so that nodes serialized without one are assigned new ids.
+        if let Some(max) = data.nodes.iter().filter_map(|entry| entry.id).max() {
+            graph.reserve_node_ids(NodeId::new(max));
+        }
+        for entry in data.nodes {
+            let idx = graph.add_node(entry.node);
+            // Restore the node's state as it was, without fading in or out of bypass.
+            let meta = &mut graph.state.node_meta[idx.index()];
+            meta.bypassed = entry.bypassed;
+            meta.bypass_mix = if entry.bypassed { 1.0 } else { 0.0 };
+            meta.muted = entry.muted;
+            meta.soloed = entry.soloed;
+            meta.active_range = entry.active_range;
+            if let Some(id) = entry.id {
+                let id = NodeId::new(id);
+                if graph.index_of(id).is_some() {
+                    return Err(de::Error::custom(format_args!("duplicate node id {}", id)));
+                }
+                graph.restore_node_id(idx, id);
+            }
+        }
+        graph.prepare_solo_path();
+        let node_count = graph.node_count();
+        let check = |idx: usize| {
+            if idx < node_count {
+                Ok(NodeIndex::new(idx))
+            } else {
+                Err(de::Error::custom(format_args!("no node at index {}", idx)))
+            }
+        };
+        let pairs = data
+            .connections
+            .iter()
+            .map(|&(src, dest, _)| Ok((check(src)?, check(dest)?)))
+            .collect::<Result<Vec<_>, D::Error>>()?;
+        let edges = graph
+            .add_connections(pairs)
+            .map_err(|_| de::Error::custom("the connections would create a cycle"))?;
+        for (edge, (_, _, entry)) in edges.into_iter().zip(&data.connections) {
+            graph.dag[edge].enabled = entry.enabled;
+        }
+        for fb in data.feedback {
+            let (src, dest) = (check(fb.source)?, check(fb.destination)?);
+            graph
+                .add_feedback_connection(src, dest)
+                .expect("the nodes were checked");
+        }
+        if let Some(master) = data.master {
+            graph.set_master(Some(check(master)?));
+        }
+        Ok(graph)
+    }
+}
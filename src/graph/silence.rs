@@ -0,0 +1,80 @@
+//! Detection and propagation of silence so that idle branches of the **Graph** can be skipped.
+
+use super::{Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use daggy::Walker;
+use dasp::Frame;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Whether or not the node at the given index output silence (i.e. only equilibrium frames)
+    /// during the last request for audio.
+    ///
+    /// Returns `false` if there is no node for the given index.
+    pub fn is_silent(&self, idx: NodeIndex<Ix>) -> bool {
+        self.state
+            .node_meta
+            .get(idx.index())
+            .map(|meta| meta.silent)
+            .unwrap_or(false)
+    }
+
+    /// Whether or not rendering the node at the given index may be skipped.
+    ///
+    /// This is the case when the node reports via `Node::is_silent` that it will output silence,
+    /// all of its inputs are silent and its tail has rung out.
+    pub(crate) fn can_skip_silent(&self, idx: NodeIndex<Ix>) -> bool {
+        self.dag[idx].is_silent()
+            && self.state.node_meta[idx.index()].tail_remaining == 0
+            && self.inputs_silent(idx)
+            && !self.is_crossfading_replacement(idx)
+    }
+
+    /// Whether or not all inputs of the node at the given index are silent.
+    pub(crate) fn inputs_silent(&self, idx: NodeIndex<Ix>) -> bool {
+        if self.has_external_input(idx)
+            || self
+                .feedback_connections()
+                .iter()
+                .any(|fb| fb.destination() == idx)
+        {
+            return false;
+        }
+        let mut inputs = self.inputs(idx);
+        while let Some(connection_idx) = inputs.next_edge(self) {
+            let connection = &self.dag[connection_idx];
+            if connection.enabled && !connection.silent {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Update the remaining tail of the node at the given index before it renders `frames`
+    /// frames.
+    ///
+    /// The tail restarts whenever the node has any input that is not silent.
+    pub(crate) fn update_tail(&mut self, idx: NodeIndex<Ix>, frames: usize) {
+        let inputs_silent = self.inputs_silent(idx);
+        let tail_frames = self.dag[idx].tail_frames();
+        let meta = &mut self.state.node_meta[idx.index()];
+        meta.tail_remaining = if inputs_silent {
+            meta.tail_remaining.saturating_sub(frames)
+        } else {
+            tail_frames
+        };
+    }
+}
+
+/// Whether or not the given buffer contains only equilibrium frames.
+pub(crate) fn is_equilibrium<F>(buffer: &[F]) -> bool
+where
+    F: Frame,
+{
+    buffer.iter().all(|frame| *frame == F::EQUILIBRIUM)
+}
@@ -0,0 +1,272 @@
+//! Smoothing of parameter changes delivered via `Event::Param`, so that hosts may trade
+//! responsiveness against zipper noise for the whole **Graph** at once.
+
+use super::{Graph, NodeIndex, RequestError};
+use crate::event::Event;
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+/// The number of frames between the intermediate values delivered while a parameter is being
+/// smoothed. The intervals are aligned to transport frames, so the same values are delivered at
+/// the same frames regardless of the buffer size.
+pub const SMOOTHING_INTERVAL: usize = 32;
+
+/// How changes to a parameter are smoothed by the **Graph** before being delivered to its node.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum ParamSmoothing {
+    /// Changes are smoothed over the **Graph**'s default smoothing time.
+    #[default]
+    Default,
+    /// Changes take effect immediately, as suits discrete parameters such as a waveform or a
+    /// number of voices.
+    Stepped,
+    /// Changes are smoothed over the given time in milliseconds.
+    Ms(f32),
+}
+
+/// The smoothing state maintained by the **Graph** for each node.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Smoother {
+    /// The smoothing of each parameter that does not follow the default.
+    overrides: Vec<(usize, ParamSmoothing)>,
+    /// The latest value of each parameter, as delivered to or reported by the node.
+    values: Vec<(usize, f32)>,
+    ramps: Vec<Ramp>,
+}
+
+/// A parameter moving linearly towards a new value.
+#[derive(Copy, Clone, Debug)]
+struct Ramp {
+    param: usize,
+    from: f32,
+    to: f32,
+    ms: f32,
+    /// The transport frame at which the ramp began, once it has begun to render.
+    start: Option<u64>,
+    /// The value most recently delivered.
+    value: f32,
+}
+
+impl Smoother {
+    /// Whether changes to the given parameter take effect immediately.
+    pub fn is_stepped(&self, param: usize) -> bool {
+        self.overrides
+            .iter()
+            .any(|&(p, smoothing)| p == param && smoothing == ParamSmoothing::Stepped)
+    }
+
+    /// The smoothing time in milliseconds of the given parameter.
+    fn ms(&self, param: usize, default_ms: f32) -> f32 {
+        let smoothing = self
+            .overrides
+            .iter()
+            .find(|&&(p, _)| p == param)
+            .map(|&(_, smoothing)| smoothing);
+        match smoothing {
+            None | Some(ParamSmoothing::Default) => default_ms,
+            Some(ParamSmoothing::Stepped) => 0.0,
+            Some(ParamSmoothing::Ms(ms)) => ms,
+        }
+    }
+
+    /// Record the latest value of a parameter, unless it is being smoothed.
+    pub fn observe(&mut self, param: usize, value: f32) {
+        if self.ramps.iter().any(|ramp| ramp.param == param) {
+            return;
+        }
+        match self.values.iter_mut().find(|(p, _)| *p == param) {
+            Some((_, v)) => *v = value,
+            None => self.values.push((param, value)),
+        }
+    }
+
+    /// Record the value of a parameter if its value is not yet known.
+    pub fn seed(&mut self, param: usize, value: f32) {
+        if !self.values.iter().any(|&(p, _)| p == param) {
+            self.values.push((param, value));
+        }
+    }
+
+    /// Filter an event about to be delivered to the node, returning it if it should be delivered
+    /// immediately or starting a ramp towards its value otherwise.
+    ///
+    /// Parameters whose previous value is unknown take their first value immediately.
+    pub fn filter(&mut self, event: Event, default_ms: f32) -> Option<Event> {
+        let (param, value) = match event {
+            Event::Param { param, value } => (param, value),
+            event => return Some(event),
+        };
+        let ms = self.ms(param, default_ms);
+        self.ramp_to(param, value, ms)
+    }
+
+    /// Start a ramp of the given parameter towards `value` over `ms`, returning the event to
+    /// deliver immediately instead if `ms` is zero, or if the previous value is unknown or equal
+    /// to `value`.
+    pub fn ramp_to(&mut self, param: usize, value: f32, ms: f32) -> Option<Event> {
+        let event = Event::Param { param, value };
+        if ms <= 0.0 {
+            self.ramps.retain(|ramp| ramp.param != param);
+            self.observe(param, value);
+            return Some(event);
+        }
+        let current = match self.ramps.iter().position(|ramp| ramp.param == param) {
+            // Start the new ramp from wherever the current one has reached.
+            Some(i) => Some(self.ramps.swap_remove(i).value),
+            None => self
+                .values
+                .iter()
+                .find(|&&(p, _)| p == param)
+                .map(|&(_, v)| v),
+        };
+        match current {
+            Some(from) if from != value => {
+                self.ramps.push(Ramp {
+                    param,
+                    from,
+                    to: value,
+                    ms,
+                    start: None,
+                    value: from,
+                });
+                None
+            }
+            _ => {
+                self.observe(param, value);
+                Some(event)
+            }
+        }
+    }
+
+    /// Whether any parameters are being smoothed.
+    pub fn is_ramping(&self) -> bool {
+        !self.ramps.is_empty()
+    }
+
+    /// The value towards which the given parameter is being smoothed, or its latest value if it
+    /// is not, or `None` if its value is unknown.
+    pub fn target(&self, param: usize) -> Option<f32> {
+        match self.ramps.iter().find(|ramp| ramp.param == param) {
+            Some(ramp) => Some(ramp.to),
+            None => self
+                .values
+                .iter()
+                .find(|&&(p, _)| p == param)
+                .map(|&(_, v)| v),
+        }
+    }
+
+    /// The parameters whose values are known, along with their targets.
+    pub fn targets(&self) -> impl Iterator<Item = (usize, f32)> + '_ {
+        let ramping = self.ramps.iter().map(|ramp| (ramp.param, ramp.to));
+        let values = self.values.iter().copied();
+        let settled = values.filter(move |&(p, _)| !self.ramps.iter().any(|r| r.param == p));
+        ramping.chain(settled)
+    }
+
+    /// Deliver the value that each ramp reaches by the end of the interval containing the given
+    /// transport frame, if the ramp begins at the frame or the frame begins an interval.
+    ///
+    /// Returns the number of frames until the next interval begins.
+    pub fn step(&mut self, frame: u64, sample_hz: f64, mut deliver: impl FnMut(&Event)) -> usize {
+        let interval = SMOOTHING_INTERVAL as u64;
+        let next = (frame / interval + 1) * interval;
+        let Smoother {
+            ref mut ramps,
+            ref mut values,
+            ..
+        } = *self;
+        ramps.retain_mut(|ramp| {
+            let start = *ramp.start.get_or_insert(frame);
+            if frame != start && !frame.is_multiple_of(interval) {
+                return true;
+            }
+            let value = ramp.value_at(next - start, sample_hz);
+            ramp.value = value;
+            deliver(&Event::Param {
+                param: ramp.param,
+                value,
+            });
+            if value != ramp.to {
+                return true;
+            }
+            match values.iter_mut().find(|(p, _)| *p == ramp.param) {
+                Some((_, v)) => *v = value,
+                None => values.push((ramp.param, value)),
+            }
+            false
+        });
+        (next - frame) as usize
+    }
+}
+
+impl Ramp {
+    /// The value reached the given number of frames into the ramp at the given sample rate.
+    fn value_at(&self, elapsed: u64, sample_hz: f64) -> f32 {
+        let frames = (f64::from(self.ms) * sample_hz / 1_000.0).round().max(1.0) as u64;
+        if elapsed >= frames {
+            return self.to;
+        }
+        let x = elapsed as f32 / frames as f32;
+        self.from + (self.to - self.from) * x
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Set the time in milliseconds over which changes to parameters are smoothed by default.
+    ///
+    /// When a node's parameter is set via `Event::Param` (whether sent directly, scheduled or set
+    /// via a **ParamHandle**), the **Graph** moves the parameter linearly from its previous value
+    /// to the new value over the smoothing time, delivering an intermediate value every
+    /// `SMOOTHING_INTERVAL` frames. This applies to every node without the node needing to
+    /// smooth its own parameters, at the cost of splitting its buffer while a parameter moves.
+    /// The first value of each parameter takes effect immediately, unless its current value was
+    /// given to `param_handle`, as do all changes to parameters whose smoothing is
+    /// `ParamSmoothing::Stepped`. Ramps only advance while the node is rendered.
+    ///
+    /// By default, this is `0.0` and changes take effect immediately.
+    pub fn set_default_smoothing_ms(&mut self, ms: f32) {
+        self.state.default_smoothing_ms = ms.max(0.0);
+    }
+
+    /// The time in milliseconds over which changes to parameters are smoothed by default.
+    pub fn default_smoothing_ms(&self) -> f32 {
+        self.state.default_smoothing_ms
+    }
+
+    /// Override the smoothing of the parameter `param` of the node at the given index, e.g. to
+    /// mark a discrete parameter as `ParamSmoothing::Stepped`.
+    ///
+    /// Returns an error if there is no node for the given index.
+    pub fn set_param_smoothing(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        param: usize,
+        smoothing: ParamSmoothing,
+    ) -> Result<(), RequestError<Ix>> {
+        self.check_node(idx)?;
+        let overrides = &mut self.state.node_meta[idx.index()].smoothing.overrides;
+        overrides.retain(|&(p, _)| p != param);
+        if smoothing != ParamSmoothing::Default {
+            overrides.push((param, smoothing));
+        }
+        Ok(())
+    }
+
+    /// The smoothing of the parameter `param` of the node at the given index, or `None` if there
+    /// is no node for the given index.
+    pub fn param_smoothing(&self, idx: NodeIndex<Ix>, param: usize) -> Option<ParamSmoothing> {
+        let overrides = &self.state.node_meta.get(idx.index())?.smoothing.overrides;
+        let smoothing = overrides
+            .iter()
+            .find(|&&(p, _)| p == param)
+            .map(|&(_, smoothing)| smoothing);
+        Some(smoothing.unwrap_or_default())
+    }
+}
@@ -0,0 +1,117 @@
+//! Muting and soloing of nodes, for using the **Graph** as a mixer.
+
+use super::{Graph, NodeIndex, RequestError};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use daggy::Walker;
+use dasp::Frame;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Mute or unmute the node at the given index.
+    ///
+    /// A muted node is still rendered so that its state continues to evolve, but its output is
+    /// replaced with silence.
+    pub fn set_muted(&mut self, idx: NodeIndex<Ix>, muted: bool) -> Result<(), RequestError<Ix>> {
+        let meta = self
+            .state
+            .node_meta
+            .get_mut(idx.index())
+            .ok_or(RequestError::NoNode(idx))?;
+        meta.muted = muted;
+        Ok(())
+    }
+
+    /// Whether or not the node at the given index is muted.
+    pub fn is_muted(&self, idx: NodeIndex<Ix>) -> bool {
+        self.state
+            .node_meta
+            .get(idx.index())
+            .map(|meta| meta.muted)
+            .unwrap_or(false)
+    }
+
+    /// Solo or unsolo the node at the given index.
+    ///
+    /// While any node is soloed, every node that is neither soloed nor an ancestor or descendant
+    /// of a soloed node is silenced. That is, the soloed nodes remain audible along with
+    /// everything that feeds them and every path from them to the output, while all parallel
+    /// branches are silenced.
+    pub fn set_soloed(&mut self, idx: NodeIndex<Ix>, soloed: bool) -> Result<(), RequestError<Ix>> {
+        let meta = self
+            .state
+            .node_meta
+            .get_mut(idx.index())
+            .ok_or(RequestError::NoNode(idx))?;
+        meta.soloed = soloed;
+        self.prepare_solo_path();
+        Ok(())
+    }
+
+    /// Whether or not the node at the given index is soloed.
+    pub fn is_soloed(&self, idx: NodeIndex<Ix>) -> bool {
+        self.state
+            .node_meta
+            .get(idx.index())
+            .map(|meta| meta.soloed)
+            .unwrap_or(false)
+    }
+
+    /// Whether or not the output of the node at the given index is audible, taking both its mute
+    /// state and the solo state of the **Graph** into account.
+    pub fn is_audible(&self, idx: NodeIndex<Ix>) -> bool {
+        self.state
+            .node_meta
+            .get(idx.index())
+            .map(|meta| !meta.muted && (!self.state.any_soloed || meta.in_solo_path))
+            .unwrap_or(false)
+    }
+
+    /// Mark each node that is soloed or that is an ancestor or descendant of a soloed node.
+    ///
+    /// Called whenever the solo state or the **Graph**'s connections change.
+    pub(crate) fn prepare_solo_path(&mut self) {
+        self.state.any_soloed = self.state.node_meta.iter().any(|meta| meta.soloed);
+        if !self.state.any_soloed {
+            return;
+        }
+        for meta in &mut self.state.node_meta {
+            meta.in_solo_path = false;
+        }
+
+        let soloed = (0..self.state.node_meta.len())
+            .filter(|&i| self.state.node_meta[i].soloed)
+            .map(NodeIndex::new);
+        let mut stack: Vec<_> = soloed.collect();
+        let mut descendants = stack.clone();
+
+        // Walk the ancestors of each soloed node.
+        while let Some(idx) = stack.pop() {
+            if std::mem::replace(&mut self.state.node_meta[idx.index()].in_solo_path, true) {
+                continue;
+            }
+            let mut inputs = self.inputs(idx);
+            while let Some(input) = inputs.next_node(self) {
+                stack.push(input);
+            }
+        }
+
+        // Walk the descendants of each soloed node. These are tracked separately, as a node may
+        // already have been marked as an ancestor of another soloed node.
+        let mut visited = vec![false; self.state.node_meta.len()];
+        while let Some(idx) = descendants.pop() {
+            if std::mem::replace(&mut visited[idx.index()], true) {
+                continue;
+            }
+            self.state.node_meta[idx.index()].in_solo_path = true;
+            let mut outputs = self.outputs(idx);
+            while let Some(output) = outputs.next_node(self) {
+                descendants.push(output);
+            }
+        }
+    }
+}
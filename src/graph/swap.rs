@@ -0,0 +1,270 @@
+//! Glitch-free restructuring by swapping in a modified copy of a **Graph** at a block boundary.
+
+use super::{Graph, NodeIndex, Tempo};
+use crate::bus::BusLayout;
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::{self, Frame, Sample};
+use std::sync::mpsc;
+
+/// Owns the **Graph** that is rendered on the audio thread and swaps in replacements sent via its
+/// [`SwapHandle`](./struct.SwapHandle.html).
+///
+/// Restructuring a large **Graph** (e.g. adding, removing or reconnecting many nodes) can take
+/// longer than the audio thread can afford. Instead, the host may clone the **Graph**, modify the
+/// copy on another thread and send it via the **SwapHandle**. The new **Graph** takes over at the
+/// start of the next request for audio.
+///
+/// If a crossfade length is set, the outputs of the previous and the new **Graph** are linearly
+/// crossfaded over that many frames to hide any discontinuities. Replaced graphs are sent back to
+/// the **SwapHandle** so that they are not deallocated on the audio thread.
+///
+/// Nodes whose state should survive the swap (e.g. when reloading a patch in which only some
+/// nodes changed) may be carried over from the replaced **Graph** into its replacement via
+/// [`SwapHandle::swap_preserving`](./struct.SwapHandle.html#method.swap_preserving).
+pub struct GraphSwap<F, N, Ix = usize>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    graph: Graph<F, N, Ix>,
+    /// The previous graph and the number of frames remaining in the crossfade.
+    fading: Option<(Graph<F, N, Ix>, usize)>,
+    crossfade_frames: usize,
+    /// A buffer to re-use when rendering the previous graph during a crossfade.
+    fade_buffer: Vec<F>,
+    incoming: mpsc::Receiver<Incoming<F, N, Ix>>,
+    retired: mpsc::Sender<Graph<F, N, Ix>>,
+}
+
+/// A replacement graph along with the nodes to carry over into it.
+struct Incoming<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    graph: Graph<F, N, Ix>,
+    /// Pairs of the index of a node in the replacement and the index of the node in the replaced
+    /// graph that takes its place.
+    preserved: Vec<(NodeIndex<Ix>, NodeIndex<Ix>)>,
+}
+
+/// Sends replacement graphs to a [`GraphSwap`](./struct.GraphSwap.html), usually from a thread
+/// other than the audio thread.
+pub struct SwapHandle<F, N, Ix = usize>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    outgoing: mpsc::Sender<Incoming<F, N, Ix>>,
+    retired: mpsc::Receiver<Graph<F, N, Ix>>,
+}
+
+impl<F, N, Ix> GraphSwap<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    /// Wrap the given **Graph** so that it may be replaced via the returned **SwapHandle**.
+    ///
+    /// `crossfade_frames` is the number of frames over which the output of a replaced **Graph**
+    /// is crossfaded with that of its replacement. A few milliseconds worth of frames is usually
+    /// enough to avoid clicks. If `0`, the new **Graph** takes over immediately.
+    pub fn new(graph: Graph<F, N, Ix>, crossfade_frames: usize) -> (Self, SwapHandle<F, N, Ix>) {
+        let (outgoing, incoming) = mpsc::channel();
+        let (retired_tx, retired_rx) = mpsc::channel();
+        let swap = GraphSwap {
+            graph,
+            fading: None,
+            crossfade_frames,
+            fade_buffer: Vec::new(),
+            incoming,
+            retired: retired_tx,
+        };
+        let handle = SwapHandle {
+            outgoing,
+            retired: retired_rx,
+        };
+        (swap, handle)
+    }
+
+    /// A reference to the **Graph** that is currently being rendered.
+    pub fn graph(&self) -> &Graph<F, N, Ix> {
+        &self.graph
+    }
+
+    /// A mutable reference to the **Graph** that is currently being rendered.
+    pub fn graph_mut(&mut self) -> &mut Graph<F, N, Ix> {
+        &mut self.graph
+    }
+
+    /// The number of frames over which a replaced **Graph** is crossfaded with its replacement.
+    pub fn crossfade_frames(&self) -> usize {
+        self.crossfade_frames
+    }
+
+    /// Set the number of frames over which a replaced **Graph** is crossfaded with its
+    /// replacement.
+    pub fn set_crossfade_frames(&mut self, frames: usize) {
+        self.crossfade_frames = frames;
+    }
+
+    /// Whether or not the previous **Graph** is still being crossfaded out.
+    pub fn is_crossfading(&self) -> bool {
+        self.fading.is_some()
+    }
+
+    /// Consume the **GraphSwap** and return the **Graph** that is currently being rendered.
+    pub fn into_graph(self) -> Graph<F, N, Ix> {
+        self.graph
+    }
+
+    /// Swap in the most recently sent **Graph**, if any.
+    fn receive(&mut self) {
+        while let Ok(incoming) = self.incoming.try_recv() {
+            let Incoming {
+                mut graph,
+                preserved,
+            } = incoming;
+            // Move the preserved nodes into the new graph, leaving their replacements behind.
+            for (new, old) in preserved {
+                if new.index() < graph.dag.node_count() && old.index() < self.graph.dag.node_count()
+                {
+                    std::mem::swap(&mut graph[new], &mut self.graph[old]);
+                }
+            }
+            let previous = std::mem::replace(&mut self.graph, graph);
+            // Only the most recently replaced graph is faded out.
+            if let Some((fading, _)) = self.fading.take() {
+                self.retire(fading);
+            }
+            if self.crossfade_frames > 0 {
+                self.fading = Some((previous, self.crossfade_frames));
+            } else {
+                self.retire(previous);
+            }
+        }
+    }
+
+    /// Send the given graph back to the **SwapHandle** to be deallocated.
+    fn retire(&self, graph: Graph<F, N, Ix>) {
+        // If the handle has been dropped there is nowhere else for the graph to go.
+        let _ = self.retired.send(graph);
+    }
+}
+
+impl<F, N, Ix> SwapHandle<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    /// Send the given **Graph** to replace the one being rendered at the start of the next
+    /// request for audio.
+    ///
+    /// Returns `false` if the **GraphSwap** no longer exists, in which case the **Graph** is
+    /// dropped.
+    pub fn swap(&self, graph: Graph<F, N, Ix>) -> bool {
+        self.swap_preserving(graph, Vec::new())
+    }
+
+    /// The same as [`swap`](./struct.SwapHandle.html#method.swap), but carries nodes over from
+    /// the replaced **Graph** so that their state is preserved.
+    ///
+    /// Each pair in `preserved` is the index of a node in `graph` followed by the index of the
+    /// node in the replaced **Graph** that should take its place. The node from `graph` is left
+    /// in the replaced **Graph** instead. Pairs referring to nodes that do not exist are ignored.
+    pub fn swap_preserving(
+        &self,
+        graph: Graph<F, N, Ix>,
+        preserved: Vec<(NodeIndex<Ix>, NodeIndex<Ix>)>,
+    ) -> bool {
+        self.outgoing.send(Incoming { graph, preserved }).is_ok()
+    }
+
+    /// Yields all graphs that have been replaced since this was last called.
+    ///
+    /// These may be dropped or re-used by the host. Calling this regularly ensures replaced
+    /// graphs do not accumulate.
+    pub fn retired(&self) -> mpsc::TryIter<'_, Graph<F, N, Ix>> {
+        self.retired.try_iter()
+    }
+}
+
+impl<F, N, Ix> Node<F> for GraphSwap<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    fn audio_requested(&mut self, output: &mut [F], sample_hz: f64) {
+        self.receive();
+
+        let (mut previous, remaining) = match self.fading.take() {
+            None => return self.graph.audio_requested(output, sample_hz),
+            Some(fading) => fading,
+        };
+
+        // Render the previous graph with the same input as the new one.
+        if self.fade_buffer.len() != output.len() {
+            self.fade_buffer.resize(output.len(), F::EQUILIBRIUM);
+        }
+        dasp::slice::write(&mut self.fade_buffer, output);
+        previous.audio_requested(&mut self.fade_buffer, sample_hz);
+        self.graph.audio_requested(output, sample_hz);
+
+        // Linearly crossfade from the previous graph's output to the new graph's output.
+        let total = self.crossfade_frames.max(remaining) as f64;
+        let mut faded = total - remaining as f64;
+        for (new, old) in output.iter_mut().zip(&self.fade_buffer) {
+            let gain = (faded / total).min(1.0);
+            *new = new.zip_map(*old, |s_new, s_old| {
+                let new = s_new.mul_amp(gain.to_sample());
+                let old = s_old.mul_amp((1.0 - gain).to_sample());
+                new.add_amp(old.to_sample())
+            });
+            faded += 1.0;
+        }
+
+        let remaining = remaining.saturating_sub(output.len());
+        if remaining > 0 {
+            self.fading = Some((previous, remaining));
+        } else {
+            self.retire(previous);
+        }
+    }
+
+    fn latency(&self) -> usize {
+        self.graph.latency()
+    }
+
+    fn tail_frames(&self) -> usize {
+        match self.fading {
+            Some((ref previous, _)) => {
+                std::cmp::max(self.graph.tail_frames(), previous.tail_frames())
+            }
+            None => self.graph.tail_frames(),
+        }
+    }
+
+    fn bus_layout(&self) -> BusLayout {
+        self.graph.bus_layout()
+    }
+
+    fn set_bus_layout(&mut self, layout: &BusLayout) -> bool {
+        self.graph.set_bus_layout(layout)
+    }
+
+    fn channels_changed(&mut self, channels: usize) {
+        self.graph.channels_changed(channels);
+        if let Some((ref mut previous, _)) = self.fading {
+            previous.channels_changed(channels);
+        }
+    }
+
+    fn update_tempo(&mut self, tempo: &Tempo) {
+        self.graph.update_tempo(tempo);
+        if let Some((ref mut previous, _)) = self.fading {
+            previous.update_tempo(tempo);
+        }
+    }
+}
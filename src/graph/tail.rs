@@ -0,0 +1,86 @@
+//! Flushing the tails of reverbs, delays and other nodes that continue to sound after their input
+//! ends, for offline rendering.
+
+use super::{Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use daggy::Walker;
+use dasp::Frame;
+
+/// The block size used to flush the tail if no buffers have been prepared.
+const DEFAULT_TAIL_BLOCK_SIZE: usize = 1024;
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Continue rendering the **Graph**'s output after its input has ended, returning the tail.
+    ///
+    /// Audio is requested block by block, at the buffer size last prepared, until every node that
+    /// is rendered has output silence and finished its `Node::tail_frames` for long enough to
+    /// cover the **Graph**'s latency plus one block, or until `max_frames` frames have been
+    /// rendered. Trailing silence is trimmed from
+    /// the returned tail, so that offline bounces neither truncate reverbs and delays nor end
+    /// with needless silence.
+    ///
+    /// Returns an empty tail if there is no output node.
+    pub fn flush_tail(&mut self, max_frames: usize, sample_hz: f64) -> Vec<F> {
+        let mut tail = Vec::new();
+        let out_node = match self.output_node() {
+            Some(node) => node,
+            None => return tail,
+        };
+        let block_size = match self.state.dry_buffer.len() {
+            0 => DEFAULT_TAIL_BLOCK_SIZE,
+            len => len,
+        };
+        let mut buffer = vec![F::EQUILIBRIUM; block_size];
+        let mut silent_frames = 0;
+        while tail.len() < max_frames {
+            let len = block_size.min(max_frames - tail.len());
+            let buffer = &mut buffer[..len];
+            dasp::slice::equilibrium(buffer);
+            self.audio_requested_from(out_node, buffer, sample_hz);
+            tail.extend_from_slice(buffer);
+
+            let all_silent = self.state.render_order.iter().all(|node| {
+                let meta = &self.state.node_meta[node.index()];
+                meta.silent && meta.tail_remaining == 0
+            });
+            if all_silent {
+                silent_frames += len;
+            } else {
+                silent_frames = 0;
+            }
+            let required = self.state.path_latencies[out_node.index()] + block_size;
+            if silent_frames >= required {
+                break;
+            }
+        }
+
+        // Trim the trailing silence.
+        let end = tail
+            .iter()
+            .rposition(|&frame| frame != F::EQUILIBRIUM)
+            .map_or(0, |i| i + 1);
+        tail.truncate(end);
+        tail
+    }
+
+    /// The longest sum of the tails of the nodes along any path into the node at the given index.
+    pub(crate) fn path_tail_frames(&self, idx: NodeIndex<Ix>) -> usize {
+        let mut tails = vec![0usize; self.dag.node_count()];
+        for &node_idx in &self.state.visit_order {
+            let mut max_input_tail = 0;
+            let mut inputs = self.inputs(node_idx);
+            while let Some(input_idx) = inputs.next_node(self) {
+                max_input_tail = std::cmp::max(max_input_tail, tails[input_idx.index()]);
+            }
+            let tail = self.dag[node_idx].tail_frames();
+            tails[node_idx.index()] = max_input_tail.saturating_add(tail);
+        }
+        tails.get(idx.index()).cloned().unwrap_or(0)
+    }
+}
@@ -0,0 +1,359 @@
+//! The **Graph**'s tempo, which may be automated with ramps, and the mapping between beats and
+//! transport frames that it implies.
+
+use super::Graph;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+
+/// How the tempo moves from one **TempoPoint** to the next.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TempoRamp {
+    /// The tempo holds until the next point, then jumps to its value.
+    Step,
+    /// The tempo changes at a constant rate.
+    Linear,
+    /// The tempo follows `x^curve` of the way between the two points, where `x` is the fraction
+    /// of the ramp that has elapsed: values above `1.0` change slowly at first and quickly
+    /// towards the end, while values between `0.0` and `1.0` do the opposite.
+    Curve(f64),
+}
+
+/// A tempo that the **TempoMap** reaches at a transport frame.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TempoPoint {
+    /// The transport frame at which the tempo is reached.
+    pub frame: u64,
+    /// The tempo in beats per minute.
+    pub bpm: f64,
+    /// How the tempo moves from the previous point to this one.
+    pub ramp: TempoRamp,
+}
+
+/// The tempo of the transport over time, automated by points between which it steps or ramps.
+///
+/// Before the first point, the tempo holds at the first point's tempo, and after the last point
+/// it holds at the last point's tempo. Beats are counted from transport frame zero.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TempoMap {
+    /// Sorted by frame, with no two points at the same frame.
+    points: Vec<TempoPoint>,
+}
+
+/// The tempo over a buffer of frames, passed to each node via `Node::update_tempo` before it
+/// renders the buffer.
+///
+/// The **Graph** splits each node's buffer at the points of its **TempoMap**, so a single
+/// **Tempo** describes the whole buffer exactly, including any ramp within it. Every node
+/// therefore sees the same mapping between beats and frames, however it divides up its buffer.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tempo {
+    sample_hz: f64,
+    /// The beat at the first frame of the buffer.
+    beat: f64,
+    /// The ramp along which the buffer lies.
+    segment: Segment,
+    /// The number of frames from the start of the segment to the start of the buffer.
+    offset: f64,
+}
+
+/// The part of the **TempoMap** between two points, or after the last point.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Segment {
+    start_bpm: f64,
+    end_bpm: f64,
+    ramp: TempoRamp,
+    /// The length of the ramp in frames, or infinity if the tempo holds forever.
+    len: f64,
+}
+
+/// The tempo state of a **Graph**.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TempoState {
+    pub map: Option<TempoMap>,
+    /// The tempo passed to the **Graph** by its parent when nested within another **Graph**,
+    /// used while it has no tempo map of its own.
+    pub parent: Option<Tempo>,
+}
+
+impl TempoMap {
+    /// A constant tempo in beats per minute.
+    pub fn new(bpm: f64) -> Self {
+        TempoMap {
+            points: vec![TempoPoint {
+                frame: 0,
+                bpm,
+                ramp: TempoRamp::Step,
+            }],
+        }
+    }
+
+    /// The points of the map, in order.
+    pub fn points(&self) -> &[TempoPoint] {
+        &self.points
+    }
+
+    /// Reach the given tempo at the given transport frame, moving from the previous point along
+    /// the given ramp. Any point at the same frame is replaced.
+    pub fn insert(&mut self, frame: u64, bpm: f64, ramp: TempoRamp) {
+        let point = TempoPoint { frame, bpm, ramp };
+        match self.points.binary_search_by_key(&frame, |p| p.frame) {
+            Ok(i) => self.points[i] = point,
+            Err(i) => self.points.insert(i, point),
+        }
+    }
+
+    /// Remove and return the point at the given frame, if there is one.
+    ///
+    /// The last remaining point is never removed.
+    pub fn remove(&mut self, frame: u64) -> Option<TempoPoint> {
+        if self.points.len() == 1 {
+            return None;
+        }
+        let i = self.points.binary_search_by_key(&frame, |p| p.frame).ok()?;
+        Some(self.points.remove(i))
+    }
+
+    /// The tempo in beats per minute at the given transport frame.
+    pub fn bpm_at(&self, frame: u64) -> f64 {
+        let (i, offset) = self.locate(frame);
+        self.segment(i).bpm_at(offset)
+    }
+
+    /// The beat at the given transport frame at the given sample rate.
+    pub fn beat_at(&self, frame: u64, sample_hz: f64) -> f64 {
+        let (i, offset) = self.locate(frame);
+        let beats_per_frame = 1.0 / (60.0 * sample_hz);
+        let whole: f64 = (0..i)
+            .map(|i| {
+                let segment = self.segment(i);
+                segment.integral(segment.len)
+            })
+            .sum();
+        (whole + self.segment(i).integral(offset)) * beats_per_frame
+    }
+
+    /// The transport frame at which the given beat falls at the given sample rate, rounded to the
+    /// nearest frame.
+    pub fn frame_at_beat(&self, beat: f64, sample_hz: f64) -> u64 {
+        let target = beat.max(0.0) * 60.0 * sample_hz;
+        let mut start = 0.0;
+        let mut segment_frame = 0;
+        for i in 0..=self.points.len() {
+            let segment = self.segment(i);
+            let end = start + segment.integral(segment.len);
+            if target < end || !segment.len.is_finite() {
+                // The integral increases monotonically, so bisect for the frame.
+                let (mut lo, mut hi) = (0.0, 1.0);
+                while start + segment.integral(hi) < target {
+                    hi *= 2.0;
+                }
+                for _ in 0..64 {
+                    let mid = 0.5 * (lo + hi);
+                    if start + segment.integral(mid) < target {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                return segment_frame + hi.round() as u64;
+            }
+            start = end;
+            segment_frame += segment.len as u64;
+        }
+        segment_frame
+    }
+
+    /// The tempo over a buffer starting at the given transport frame, valid until the next point.
+    pub fn tempo(&self, frame: u64, sample_hz: f64) -> Tempo {
+        let (i, offset) = self.locate(frame);
+        Tempo {
+            sample_hz,
+            beat: self.beat_at(frame, sample_hz),
+            segment: self.segment(i),
+            offset,
+        }
+    }
+
+    /// The index of the segment containing the given frame and the frame's offset within it.
+    ///
+    /// Segment `0` spans from frame zero to the first point, at which the tempo holds. Segment
+    /// `i` spans from point `i - 1` to point `i`, or onwards from the last point.
+    fn locate(&self, frame: u64) -> (usize, f64) {
+        let i = self.points.partition_point(|p| p.frame <= frame);
+        let start = match i {
+            0 => 0,
+            i => self.points[i - 1].frame,
+        };
+        (i, (frame - start) as f64)
+    }
+
+    /// The segment at the given index (see `locate`).
+    fn segment(&self, i: usize) -> Segment {
+        let last = self.points.len() - 1;
+        let hold = |point: &TempoPoint, len| Segment {
+            start_bpm: point.bpm,
+            end_bpm: point.bpm,
+            ramp: TempoRamp::Step,
+            len,
+        };
+        match i {
+            0 => hold(&self.points[0], self.points[0].frame as f64),
+            i if i > last => hold(&self.points[last], f64::INFINITY),
+            i => {
+                let (from, to) = (&self.points[i - 1], &self.points[i]);
+                Segment {
+                    start_bpm: from.bpm,
+                    end_bpm: to.bpm,
+                    ramp: to.ramp,
+                    len: (to.frame - from.frame) as f64,
+                }
+            }
+        }
+    }
+}
+
+impl Default for TempoMap {
+    fn default() -> Self {
+        TempoMap::new(120.0)
+    }
+}
+
+impl Tempo {
+    /// A constant tempo in beats per minute, with the first frame of the buffer at the given
+    /// beat.
+    pub fn constant(bpm: f64, beat: f64, sample_hz: f64) -> Self {
+        Tempo {
+            sample_hz,
+            beat,
+            segment: Segment {
+                start_bpm: bpm,
+                end_bpm: bpm,
+                ramp: TempoRamp::Step,
+                len: f64::INFINITY,
+            },
+            offset: 0.0,
+        }
+    }
+
+    /// The sample rate at which beats are mapped to frames.
+    pub fn sample_hz(&self) -> f64 {
+        self.sample_hz
+    }
+
+    /// The tempo in beats per minute at the given frame of the buffer.
+    pub fn bpm_at(&self, frame: usize) -> f64 {
+        self.segment.bpm_at(self.offset + frame as f64)
+    }
+
+    /// The beat at the given frame of the buffer, counted from transport frame zero.
+    pub fn beat_at(&self, frame: usize) -> f64 {
+        let from = self.segment.integral(self.offset);
+        let to = self.segment.integral(self.offset + frame as f64);
+        self.beat + (to - from) / (60.0 * self.sample_hz)
+    }
+
+    /// The length of one beat in frames at the given frame of the buffer.
+    pub fn beat_frames_at(&self, frame: usize) -> f64 {
+        60.0 * self.sample_hz / self.bpm_at(frame)
+    }
+
+    /// The number of frames from the start of the buffer until the tempo next changes course,
+    /// or `None` if it holds forever.
+    pub fn frames_remaining(&self) -> Option<u64> {
+        if self.segment.len.is_finite() {
+            Some((self.segment.len - self.offset) as u64)
+        } else {
+            None
+        }
+    }
+
+    /// The same tempo for a buffer starting the given number of frames later.
+    pub fn offset(&self, frames: usize) -> Tempo {
+        Tempo {
+            beat: self.beat_at(frames),
+            offset: self.offset + frames as f64,
+            ..*self
+        }
+    }
+}
+
+impl Segment {
+    /// The fraction of the way from the start tempo to the end tempo at the given offset.
+    fn shape(&self, offset: f64) -> f64 {
+        let x = (offset / self.len).clamp(0.0, 1.0);
+        match self.ramp {
+            TempoRamp::Step => 0.0,
+            TempoRamp::Linear => x,
+            TempoRamp::Curve(curve) => x.powf(curve.max(0.0)),
+        }
+    }
+
+    fn bpm_at(&self, offset: f64) -> f64 {
+        self.start_bpm + (self.end_bpm - self.start_bpm) * self.shape(offset)
+    }
+
+    /// The integral of the tempo in beats per minute over the first `offset` frames.
+    fn integral(&self, offset: f64) -> f64 {
+        let delta = self.end_bpm - self.start_bpm;
+        let ramp = match self.ramp {
+            TempoRamp::Step => 0.0,
+            _ if offset >= self.len => {
+                // Beyond the ramp, the tempo holds at its end.
+                let curve = self.curve();
+                return self.start_bpm * offset
+                    + delta * (self.len / (curve + 1.0) + (offset - self.len));
+            }
+            _ => {
+                let curve = self.curve();
+                self.len / (curve + 1.0) * (offset / self.len).powf(curve + 1.0)
+            }
+        };
+        self.start_bpm * offset + delta * ramp
+    }
+
+    /// The exponent of the ramp.
+    fn curve(&self) -> f64 {
+        match self.ramp {
+            TempoRamp::Curve(curve) => curve.max(0.0),
+            _ => 1.0,
+        }
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    /// Set the tempo of the transport, or `None` to remove it.
+    ///
+    /// While the **Graph** has a tempo map, each node is passed the tempo via
+    /// `Node::update_tempo` before it renders, and its buffer is split at each point of the map
+    /// so that tempo-synced nodes such as LFOs, delays, sequencers and clip players all follow
+    /// the same mapping between beats and frames within a block. A **Graph** nested within
+    /// another with no tempo map of its own follows the tempo of its parent.
+    pub fn set_tempo_map(&mut self, map: Option<TempoMap>) {
+        self.state.tempo.map = map;
+    }
+
+    /// The tempo of the transport, if any.
+    pub fn tempo_map(&self) -> Option<&TempoMap> {
+        self.state.tempo.map.as_ref()
+    }
+
+    /// The tempo of the transport, if any, to add or remove points.
+    pub fn tempo_map_mut(&mut self) -> Option<&mut TempoMap> {
+        self.state.tempo.map.as_mut()
+    }
+}
+
+impl TempoState {
+    /// The tempo over the sub-block starting `offset` frames into a request for audio that
+    /// starts at the transport frame `block_start`.
+    pub fn tempo(&self, block_start: u64, offset: usize, sample_hz: f64) -> Option<Tempo> {
+        match self.map {
+            Some(ref map) => Some(map.tempo(block_start + offset as u64, sample_hz)),
+            None => self.parent.map(|tempo| tempo.offset(offset)),
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! The **Graph**'s transport position and the scheduling of node activity against it.
+
+use super::{Graph, NodeIndex, NodeMeta, RequestError};
+use crate::event::Event;
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::ops::Range;
+
+/// The bars that the transport plays before a punch-in, so that musicians hear where recording
+/// begins: a count-in of metronome clicks followed by a pre-roll of the arrangement.
+///
+/// Both are measured in whole bars at the given tempo, so that the clicks of the count-in lead
+/// exactly onto the downbeat at which the pre-roll, or the recording itself, begins.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CountIn {
+    /// The tempo in beats per minute.
+    pub bpm: f64,
+    /// The number of beats per bar.
+    pub beats_per_bar: u32,
+    /// The number of bars of clicks.
+    pub bars: u32,
+    /// The number of bars of the arrangement played between the count-in and the punch-in.
+    pub pre_roll_bars: u32,
+}
+
+impl CountIn {
+    /// The `Event::Param` via which `Graph::start_count_in` sets the metronome's tempo in beats
+    /// per minute.
+    pub const BPM_PARAM: usize = 0;
+    /// The `Event::Param` via which `Graph::start_count_in` sets the metronome's beats per bar.
+    pub const BEATS_PER_BAR_PARAM: usize = 1;
+    /// The `Event::Param` via which `Graph::start_count_in` starts (a `value` of `1.0`) and
+    /// stops (`0.0`) the metronome.
+    pub const RUNNING_PARAM: usize = 3;
+
+    /// A count-in of the given number of bars at the given tempo, with no pre-roll.
+    pub fn new(bpm: f64, beats_per_bar: u32, bars: u32) -> Self {
+        CountIn {
+            bpm,
+            beats_per_bar,
+            bars,
+            pre_roll_bars: 0,
+        }
+    }
+
+    /// The same count-in followed by the given number of bars of pre-roll.
+    pub fn with_pre_roll(mut self, bars: u32) -> Self {
+        self.pre_roll_bars = bars;
+        self
+    }
+
+    /// The length of the count-in in frames at the given sample rate.
+    pub fn count_in_frames(&self, sample_hz: f64) -> u64 {
+        self.bars_frames(self.bars, sample_hz)
+    }
+
+    /// The length of the count-in and pre-roll together in frames at the given sample rate.
+    pub fn frames(&self, sample_hz: f64) -> u64 {
+        self.bars_frames(self.bars + self.pre_roll_bars, sample_hz)
+    }
+
+    /// The length of the given number of bars in frames, rounded to the nearest frame.
+    fn bars_frames(&self, bars: u32, sample_hz: f64) -> u64 {
+        let beats = f64::from(bars) * f64::from(self.beats_per_bar);
+        (beats * 60.0 * sample_hz / self.bpm).round() as u64
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    /// The transport position in frames at which the next request for audio will begin.
+    ///
+    /// The position advances by the length of the output buffer each time audio is requested
+    /// from the **Graph**.
+    pub fn position(&self) -> u64 {
+        self.state.position
+    }
+
+    /// Move the transport to the given position in frames.
+    pub fn set_position(&mut self, frame: u64) {
+        self.state.position = frame;
+    }
+
+    /// Only render the node at the given index while the transport is within the range of
+    /// frames `start..end`.
+    ///
+    /// Outside of this range, the node is skipped entirely: its inputs are not summed, its
+    /// `audio_requested` method is not called and it outputs silence. Activity is determined per
+    /// request, so the node is rendered for the whole buffer if any part of it overlaps the range.
+    ///
+    /// This is useful for arrangement playback where most clips and effects are inactive at any
+    /// moment.
+    pub fn set_active_range(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        start: u64,
+        end: u64,
+    ) -> Result<(), RequestError<Ix>> {
+        let meta = self
+            .state
+            .node_meta
+            .get_mut(idx.index())
+            .ok_or(RequestError::NoNode(idx))?;
+        meta.active_range = Some(start..end);
+        Ok(())
+    }
+
+    /// Remove the active range from the node at the given index so that it is always rendered.
+    ///
+    /// Returns the range that was removed, if there was one.
+    pub fn clear_active_range(&mut self, idx: NodeIndex<Ix>) -> Option<Range<u64>> {
+        self.state
+            .node_meta
+            .get_mut(idx.index())
+            .and_then(|meta| meta.active_range.take())
+    }
+
+    /// The range of transport frames within which the node at the given index is rendered, if
+    /// one has been set.
+    pub fn active_range(&self, idx: NodeIndex<Ix>) -> Option<Range<u64>> {
+        self.state
+            .node_meta
+            .get(idx.index())
+            .and_then(|meta| meta.active_range.clone())
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Render and discard at least the given number of frames leading up to the transport
+    /// position, so that reverbs, envelope followers, lookahead limiters and other stateful nodes
+    /// reach a steady state before the first audible block.
+    ///
+    /// This is particularly useful for offline bounces that begin part way through a song. The
+    /// pre-roll is rendered in blocks of the size for which the buffers were last prepared (or 512
+    /// frames if they have not been), so `frames` is rounded up to a whole number of blocks and
+    /// no buffers need to be reallocated when the first audible block is requested. The pre-roll
+    /// never begins before the start of the transport, so near the start it is shortened to end
+    /// exactly at the transport position, beginning with a partial block. The transport is moved
+    /// back to the start of the pre-roll while it renders and is left at its original position
+    /// afterwards.
+    ///
+    /// Does nothing if there is no output node.
+    pub fn warm_up(&mut self, frames: usize, sample_hz: f64) {
+        let out_node = match self.output_node() {
+            Some(out_node) => out_node,
+            None => return,
+        };
+        let block_size = match self.state.dry_buffer.len() {
+            0 => 512,
+            len => len,
+        };
+        let position = self.state.position;
+        let rounded = (frames.div_ceil(block_size) * block_size) as u64;
+        let pre_roll = rounded.min(position) as usize;
+        self.state.position = position - pre_roll as u64;
+        let mut buffer = vec![F::EQUILIBRIUM; block_size];
+        let partial = pre_roll % block_size;
+        if partial > 0 {
+            self.audio_requested_from(out_node, &mut buffer[..partial], sample_hz);
+        }
+        for _ in 0..pre_roll / block_size {
+            self.audio_requested_from(out_node, &mut buffer, sample_hz);
+        }
+        self.state.position = position;
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Move the transport back from `punch_frame` by the count-in and pre-roll, and schedule
+    /// the given metronome, if any, to click from the start of the count-in until the punch-in.
+    ///
+    /// The metronome should be a `nodes::Metronome`, or any node that treats the `Event::Param`s
+    /// `CountIn::BPM_PARAM`, `CountIn::BEATS_PER_BAR_PARAM` and `CountIn::RUNNING_PARAM` as the
+    /// tempo, beats per bar and start or stop. Its tempo and beats per bar are set to those of
+    /// the count-in. To record from the punch-in, schedule the punch of an
+    /// armed `nodes::Recorder` via `schedule_punch`; as both are scheduled against the transport,
+    /// the recording begins exactly on the downbeat that follows the count-in and pre-roll.
+    ///
+    /// While the **Graph** has a tempo map, the bars are measured along it and the metronome
+    /// follows it rather than `count_in.bpm`.
+    ///
+    /// If there are not enough frames before `punch_frame`, whole bars are dropped from the
+    /// start of the count-in, then of the pre-roll, so that the clicks still lead onto the
+    /// punch-in.
+    ///
+    /// Returns the range of frames of the count-in, excluding the pre-roll.
+    pub fn start_count_in(
+        &mut self,
+        metronome: Option<NodeIndex<Ix>>,
+        punch_frame: u64,
+        count_in: CountIn,
+        sample_hz: f64,
+    ) -> Result<Range<u64>, RequestError<Ix>> {
+        if let Some(idx) = metronome {
+            self.check_node(idx)?;
+        }
+        // With a tempo map, the bars are measured back from the punch-in along the map.
+        let map = self.state.tempo.map.as_ref();
+        let bars_frames = |count_in: &CountIn, bars: u32| -> Option<u64> {
+            let frames = match map {
+                Some(map) => {
+                    let beats = f64::from(bars) * f64::from(count_in.beats_per_bar);
+                    let beat = map.beat_at(punch_frame, sample_hz) - beats;
+                    if beat < 0.0 {
+                        return None;
+                    }
+                    punch_frame - map.frame_at_beat(beat, sample_hz).min(punch_frame)
+                }
+                None => count_in.bars_frames(bars, sample_hz),
+            };
+            Some(frames).filter(|&frames| frames <= punch_frame)
+        };
+        let mut count_in = count_in;
+        while bars_frames(&count_in, count_in.bars + count_in.pre_roll_bars).is_none() {
+            if count_in.bars > 0 {
+                count_in.bars -= 1;
+            } else if count_in.pre_roll_bars > 0 {
+                count_in.pre_roll_bars -= 1;
+            } else {
+                break;
+            }
+        }
+        let pre_roll = bars_frames(&count_in, count_in.pre_roll_bars).unwrap_or(0);
+        let total = count_in.bars + count_in.pre_roll_bars;
+        let start = punch_frame - bars_frames(&count_in, total).unwrap_or(0);
+        let end = punch_frame - pre_roll;
+        self.state.position = start;
+        if let Some(idx) = metronome {
+            let params = [
+                (CountIn::BPM_PARAM, count_in.bpm as f32),
+                (CountIn::BEATS_PER_BAR_PARAM, count_in.beats_per_bar as f32),
+                (CountIn::RUNNING_PARAM, 1.0),
+            ];
+            if end > start {
+                for &(param, value) in &params {
+                    self.schedule_event(idx, start, Event::Param { param, value })?;
+                }
+            }
+            let stop = Event::Param {
+                param: CountIn::RUNNING_PARAM,
+                value: 0.0,
+            };
+            self.schedule_event(idx, punch_frame, stop)?;
+        }
+        Ok(start..end)
+    }
+}
+
+impl NodeMeta {
+    /// Whether or not the node should be rendered for the given block of transport frames.
+    pub(crate) fn is_active(&self, block: &Range<u64>) -> bool {
+        match self.active_range {
+            Some(ref range) => range.start < block.end && block.start < range.end,
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Node indices that remember the type of the node that they refer to.
+
+use super::{Graph, NodeIndex};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// The index of a node of type `T` stored within a **Graph** whose nodes are of some other type,
+/// e.g. one variant of an enum of nodes.
+///
+/// Returned by [`Graph::add_node_typed`](./struct.Graph.html#method.add_node_typed) and used to
+/// access the node as a `T` via `Graph::node_as`, without matching on the variants of the enum.
+///
+/// Like any other **NodeIndex**, it may be invalidated by removing nodes from the **Graph**.
+pub struct TypedNodeIndex<T, Ix = usize> {
+    idx: NodeIndex<Ix>,
+    node: PhantomData<fn() -> T>,
+}
+
+/// Access to a node of type `T` stored within a node of type `Self`, e.g. one variant of an enum
+/// of nodes.
+///
+/// Every type may be accessed as itself.
+pub trait NodeVariant<T> {
+    /// The node as a `T`, or `None` if it is some other kind of node.
+    fn as_variant(&self) -> Option<&T>;
+    /// The node as a mutable `T`, or `None` if it is some other kind of node.
+    fn as_variant_mut(&mut self) -> Option<&mut T>;
+}
+
+impl<T> NodeVariant<T> for T {
+    fn as_variant(&self) -> Option<&T> {
+        Some(self)
+    }
+
+    fn as_variant_mut(&mut self) -> Option<&mut T> {
+        Some(self)
+    }
+}
+
+impl<T, Ix> TypedNodeIndex<T, Ix>
+where
+    Ix: IndexType,
+{
+    /// Assert that the node at the given index is a `T`.
+    ///
+    /// If it is not, accessing it via `Graph::node_as` returns `None`.
+    pub fn new(idx: NodeIndex<Ix>) -> Self {
+        TypedNodeIndex {
+            idx,
+            node: PhantomData,
+        }
+    }
+
+    /// The untyped index of the node.
+    pub fn node_index(&self) -> NodeIndex<Ix> {
+        self.idx
+    }
+}
+
+impl<T, Ix> From<TypedNodeIndex<T, Ix>> for NodeIndex<Ix>
+where
+    Ix: IndexType,
+{
+    fn from(idx: TypedNodeIndex<T, Ix>) -> Self {
+        idx.idx
+    }
+}
+
+impl<T, Ix> Clone for TypedNodeIndex<T, Ix>
+where
+    Ix: IndexType,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, Ix> Copy for TypedNodeIndex<T, Ix> where Ix: IndexType {}
+
+impl<T, Ix> PartialEq for TypedNodeIndex<T, Ix>
+where
+    Ix: IndexType,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.idx == other.idx
+    }
+}
+
+impl<T, Ix> Eq for TypedNodeIndex<T, Ix> where Ix: IndexType {}
+
+impl<T, Ix> Hash for TypedNodeIndex<T, Ix>
+where
+    Ix: IndexType,
+{
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.idx.index().hash(state);
+    }
+}
+
+impl<T, Ix> fmt::Debug for TypedNodeIndex<T, Ix>
+where
+    Ix: IndexType,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TypedNodeIndex").field(&self.idx).finish()
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Add a node to the dsp graph, converting it into the **Graph**'s node type, and return an
+    /// index via which it may be accessed as its original type.
+    ///
+    /// This computes in **O(1)** time.
+    pub fn add_node_typed<T>(&mut self, node: T) -> TypedNodeIndex<T, Ix>
+    where
+        T: Into<N>,
+    {
+        TypedNodeIndex::new(self.add_node(node.into()))
+    }
+
+    /// A reference to the node at the given index as a `T`.
+    ///
+    /// Returns `None` if there is no node for the given index or if it is not a `T`.
+    pub fn node_as<T>(&self, idx: TypedNodeIndex<T, Ix>) -> Option<&T>
+    where
+        N: NodeVariant<T>,
+    {
+        self.dag.node_weight(idx.idx)?.as_variant()
+    }
+
+    /// A mutable reference to the node at the given index as a `T`.
+    ///
+    /// Returns `None` if there is no node for the given index or if it is not a `T`.
+    pub fn node_as_mut<T>(&mut self, idx: TypedNodeIndex<T, Ix>) -> Option<&mut T>
+    where
+        N: NodeVariant<T>,
+    {
+        self.dag.node_weight_mut(idx.idx)?.as_variant_mut()
+    }
+}
@@ -0,0 +1,251 @@
+//! Checking of the **Graph**'s internal invariants.
+
+use super::{EdgeIndex, Graph, NodeIndex};
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::fmt;
+
+/// A report of all broken invariants found by
+/// [`Graph::validate`](../struct.Graph.html#method.validate).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationReport<Ix = usize>
+where
+    Ix: IndexType,
+{
+    /// Every violation that was found, in the order in which they were checked.
+    pub violations: Vec<Violation<Ix>>,
+}
+
+/// A single internal invariant of the **Graph** that does not hold.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Violation<Ix = usize>
+where
+    Ix: IndexType,
+{
+    /// The visit order does not contain exactly one entry per node.
+    VisitOrderLength {
+        /// The number of nodes in the **Graph**.
+        node_count: usize,
+        /// The number of entries in the visit order.
+        visit_order_len: usize,
+    },
+    /// The visit order refers to a node that does not exist.
+    VisitOrderInvalidNode(NodeIndex<Ix>),
+    /// The node appears more than once within the visit order.
+    VisitOrderDuplicate(NodeIndex<Ix>),
+    /// The connection's input node is visited after its output node.
+    VisitOrderNotTopological(EdgeIndex<Ix>),
+    /// The master index refers to a node that does not exist.
+    MissingMaster(NodeIndex<Ix>),
+    /// The connection refers to an input or output node that does not exist.
+    DanglingConnection(EdgeIndex<Ix>),
+    /// The state maintained by the **Graph** for each node is out of sync with the nodes.
+    NodeStateLength {
+        /// The number of nodes in the **Graph**.
+        node_count: usize,
+        /// The number of node states.
+        node_state_len: usize,
+    },
+    /// The connection's buffer is neither empty nor the same length as the **Graph**'s buffers.
+    BufferLength {
+        /// The index of the offending connection.
+        edge: EdgeIndex<Ix>,
+        /// The length of the **Graph**'s dry buffer.
+        expected: usize,
+        /// The length of the connection's buffer.
+        found: usize,
+    },
+}
+
+impl<Ix> ValidationReport<Ix>
+where
+    Ix: IndexType,
+{
+    /// Whether or not all invariants hold.
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    Ix: IndexType,
+{
+    /// Check the **Graph**'s internal invariants, returning a report of any that do not hold.
+    ///
+    /// The following are checked:
+    ///
+    /// - The visit order contains every node exactly once.
+    /// - The visit order is a valid topological sort, i.e. every connection's input node is
+    ///   visited before its output node.
+    /// - The master index (if there is one) refers to an existing node.
+    /// - Every connection refers to existing input and output nodes.
+    /// - There is exactly one node state for each node.
+    /// - Every connection buffer is either empty or the same length as the **Graph**'s buffers.
+    ///
+    /// This walks every node and connection and allocates, so it is only run automatically after
+    /// each mutation of the **Graph** in debug builds, unless disabled via `set_validate_mutations`.
+    pub fn validate(&self) -> ValidationReport<Ix> {
+        let mut violations = Vec::new();
+        let node_count = self.dag.node_count();
+
+        // Check that the visit order is a permutation of all node indices.
+        if self.state.visit_order.len() != node_count {
+            violations.push(Violation::VisitOrderLength {
+                node_count,
+                visit_order_len: self.state.visit_order.len(),
+            });
+        }
+        let mut positions = vec![None; node_count];
+        for (position, &node) in self.state.visit_order.iter().enumerate() {
+            match positions.get_mut(node.index()) {
+                None => violations.push(Violation::VisitOrderInvalidNode(node)),
+                Some(&mut Some(_)) => violations.push(Violation::VisitOrderDuplicate(node)),
+                Some(slot) => *slot = Some(position),
+            }
+        }
+
+        if self.state.node_meta.len() != node_count {
+            violations.push(Violation::NodeStateLength {
+                node_count,
+                node_state_len: self.state.node_meta.len(),
+            });
+        }
+
+        if let Some(master) = self.state.maybe_master {
+            if master.index() >= node_count {
+                violations.push(Violation::MissingMaster(master));
+            }
+        }
+
+        let expected_len = self.state.dry_buffer.len();
+        for (i, edge) in self.dag.raw_edges().iter().enumerate() {
+            let edge_idx = EdgeIndex::new(i);
+            let (src, dest) = (edge.source().index(), edge.target().index());
+            if src >= node_count || dest >= node_count {
+                violations.push(Violation::DanglingConnection(edge_idx));
+                continue;
+            }
+
+            // Only check the order if both nodes were actually found in the visit order.
+            if let (Some(src_pos), Some(dest_pos)) = (positions[src], positions[dest]) {
+                if src_pos >= dest_pos {
+                    violations.push(Violation::VisitOrderNotTopological(edge_idx));
+                }
+            }
+
+            let found = edge.weight.buffer.len();
+            if found != 0 && found != expected_len {
+                violations.push(Violation::BufferLength {
+                    edge: edge_idx,
+                    expected: expected_len,
+                    found,
+                });
+            }
+        }
+
+        ValidationReport { violations }
+    }
+
+    /// When enabled, the **Graph** checks its invariants via `validate` after each mutation and
+    /// panics with the report if any do not hold.
+    ///
+    /// Each check takes **O(n + e)** time and allocates, so this is intended for tests and for
+    /// fuzzing rather than for graphs that are restructured while rendering. Enabled by default in
+    /// debug builds and disabled in release builds.
+    pub fn set_validate_mutations(&mut self, validate: bool) {
+        self.state.validate_mutations = validate;
+    }
+
+    /// Whether or not the **Graph** checks its invariants after each mutation.
+    pub fn validates_mutations(&self) -> bool {
+        self.state.validate_mutations
+    }
+
+    /// Panics with the validation report if mutations are validated and any of the **Graph**'s
+    /// invariants do not hold.
+    #[inline]
+    pub(crate) fn debug_validate(&self) {
+        if self.state.validate_mutations {
+            let report = self.validate();
+            assert!(report.is_valid(), "invalid dsp graph: {}", report);
+        }
+    }
+}
+
+impl<Ix> fmt::Display for ValidationReport<Ix>
+where
+    Ix: IndexType,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.violations.is_empty() {
+            return write!(f, "no violations");
+        }
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Ix> fmt::Display for Violation<Ix>
+where
+    Ix: IndexType,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Violation::VisitOrderLength {
+                node_count,
+                visit_order_len,
+            } => write!(
+                f,
+                "visit order has {} entries but there are {} nodes",
+                visit_order_len, node_count
+            ),
+            Violation::VisitOrderInvalidNode(node) => {
+                write!(f, "visit order refers to missing node {}", node.index())
+            }
+            Violation::VisitOrderDuplicate(node) => {
+                write!(
+                    f,
+                    "node {} appears more than once in the visit order",
+                    node.index()
+                )
+            }
+            Violation::VisitOrderNotTopological(edge) => write!(
+                f,
+                "the input of connection {} is visited after its output",
+                edge.index()
+            ),
+            Violation::MissingMaster(node) => {
+                write!(f, "master refers to missing node {}", node.index())
+            }
+            Violation::DanglingConnection(edge) => {
+                write!(f, "connection {} refers to a missing node", edge.index())
+            }
+            Violation::NodeStateLength {
+                node_count,
+                node_state_len,
+            } => write!(
+                f,
+                "there are {} node states but {} nodes",
+                node_state_len, node_count
+            ),
+            Violation::BufferLength {
+                edge,
+                expected,
+                found,
+            } => write!(
+                f,
+                "connection {} has a buffer of length {} but expected {}",
+                edge.index(),
+                found,
+                expected
+            ),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! A watchdog that bypasses nodes which repeatedly take too long to render.
+
+use super::{Graph, NodeIndex, Notification};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::time::{Duration, Instant};
+
+/// Limits on the time that each node may take to render, enforced by the **Graph**.
+///
+/// See [`set_watchdog`](./struct.Graph.html#method.set_watchdog).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Watchdog {
+    /// The fraction of the real-time duration of each request for audio that a single node may
+    /// take to render, e.g. `0.5` allows a node half of the time available for the whole block.
+    pub budget: f64,
+    /// The number of consecutive requests for audio in which a node may exceed its budget
+    /// before it is bypassed.
+    pub max_overruns: usize,
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Watchdog {
+            budget: 0.5,
+            max_overruns: 8,
+        }
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Enable or disable the watchdog.
+    ///
+    /// While enabled, the time taken by each node to render is measured. A node that exceeds
+    /// its budget in more than `max_overruns` consecutive requests for audio is bypassed (as if
+    /// via `set_bypassed`) and `Notification::NodeOverBudget` is emitted, protecting live
+    /// performances from a single misbehaving node such as a third-party plugin. The host may
+    /// reinstate the node via `set_bypassed`.
+    ///
+    /// Pass `None` to disable the watchdog, which is the default.
+    pub fn set_watchdog(&mut self, watchdog: Option<Watchdog>) {
+        self.state.watchdog = watchdog;
+        for meta in &mut self.state.node_meta {
+            meta.overruns = 0;
+        }
+    }
+
+    /// The watchdog's limits, if it is enabled.
+    pub fn watchdog(&self) -> Option<Watchdog> {
+        self.state.watchdog
+    }
+
+    /// The time taken by the node at the given index to render during the last request for
+    /// audio in which it was rendered while the watchdog was enabled.
+    pub fn render_time(&self, idx: NodeIndex<Ix>) -> Option<Duration> {
+        self.state
+            .node_meta
+            .get(idx.index())
+            .map(|meta| meta.render_time)
+    }
+
+    /// The time at which a node started rendering, if the watchdog is enabled.
+    pub(crate) fn start_watchdog(&self) -> Option<Instant> {
+        self.state.watchdog.map(|_| Instant::now())
+    }
+
+    /// Check the time taken by the node at the given index to render `frames` frames since
+    /// `started` against its budget, bypassing it if it has overrun too often.
+    pub(crate) fn check_watchdog(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        started: Option<Instant>,
+        frames: usize,
+        sample_hz: f64,
+    ) {
+        let (watchdog, started) = match (self.state.watchdog, started) {
+            (Some(watchdog), Some(started)) => (watchdog, started),
+            _ => return,
+        };
+        let elapsed = started.elapsed();
+        let budget = frames as f64 / sample_hz * watchdog.budget;
+        let meta = &mut self.state.node_meta[idx.index()];
+        meta.render_time = elapsed;
+        if elapsed.as_secs_f64() <= budget {
+            meta.overruns = 0;
+            return;
+        }
+        meta.overruns += 1;
+        if meta.overruns > watchdog.max_overruns {
+            meta.overruns = 0;
+            meta.bypassed = true;
+            if self.state.bypass_fade_frames == 0 {
+                meta.bypass_mix = 1.0;
+            }
+            self.state.notifier.send(Notification::NodeOverBudget(idx));
+        }
+    }
+}
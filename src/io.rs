@@ -0,0 +1,318 @@
+//! Utilities for applications that exchange audio with a device in both directions, such as
+//! measuring the round-trip latency from an output to an input.
+
+use crate::analysis::fft;
+use crate::node::Node;
+use dasp::{Frame, Sample};
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The duration of the chirp in seconds.
+const CHIRP_SECS: f64 = 0.05;
+
+/// The frequency in hz at which the chirp starts.
+const CHIRP_START_HZ: f64 = 200.0;
+
+/// The highest frequency in hz at which the chirp ends, below the Nyquist frequency of any
+/// common sample rate.
+const CHIRP_END_HZ: f64 = 12_000.0;
+
+/// The confidence below which the chirp is considered not to have been detected.
+const MIN_CONFIDENCE: f32 = 0.2;
+
+/// Measures the round-trip latency from an output of a device to one of its inputs, e.g. from
+/// the speakers to the microphone, or across a loopback cable.
+///
+/// The probe outputs silence until a measurement is started via `start` or via its
+/// [**LatencyHandle**](./struct.LatencyHandle.html). It then emits a short chirp and records the
+/// mono sum of its input until `max_latency_frames` have passed since the chirp ended. The
+/// latency is the offset at which the recorded input best correlates with the chirp.
+///
+/// To calibrate the recording offset of a duplex application, connect the probe's input from
+/// the **Graph**'s external input that receives the device's input, and its output to the
+/// external output (or the master node) whose audio is sent to the device. The measured latency
+/// is then the number of frames between a frame being rendered for the device's output and the
+/// same frame arriving in a request for audio via the device's input, so recorded audio should be
+/// shifted earlier by that many frames to line up with what was playing. Any latency of nodes
+/// between the probe and the device's ports is included, so they are best connected directly.
+#[derive(Debug)]
+pub struct LatencyProbe {
+    /// The amplitude of the chirp, from `0.0` to `1.0`.
+    pub level: f32,
+    max_latency_frames: usize,
+    sample_hz: f64,
+    chirp: Vec<f64>,
+    /// The input recorded since the chirp began.
+    recorded: Vec<f64>,
+    /// The number of frames since the chirp began, or `None` while idle.
+    frame: Option<usize>,
+    re: Vec<f64>,
+    im: Vec<f64>,
+    chirp_re: Vec<f64>,
+    chirp_im: Vec<f64>,
+    measurement: Option<LatencyMeasurement>,
+    shared: Arc<Shared>,
+}
+
+/// Starts measurements and reads their results from any thread.
+#[derive(Clone, Debug)]
+pub struct LatencyHandle {
+    shared: Arc<Shared>,
+}
+
+/// The state shared between a **LatencyProbe** and its handles.
+#[derive(Debug)]
+struct Shared {
+    /// Whether a measurement was requested and is yet to start.
+    requested: AtomicBool,
+    /// Whether a measurement is in progress.
+    running: AtomicBool,
+    /// The latest measurement, packed as the latency in frames plus one along with the bits of
+    /// the confidence, or `0` if there is none.
+    measurement: AtomicU64,
+}
+
+/// The result of a round-trip latency measurement.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LatencyMeasurement {
+    /// The round-trip latency in frames.
+    pub frames: usize,
+    /// The normalised correlation between the chirp and the recorded input at the measured
+    /// latency, from `0.0` to `1.0`. Low values suggest a noisy or distorted path.
+    pub confidence: f32,
+}
+
+impl LatencyProbe {
+    /// A probe for a stream at the given sample rate, able to measure latencies of up to
+    /// `max_latency_frames`.
+    ///
+    /// All buffers are allocated up front, so measurements never allocate on the audio thread
+    /// unless the sample rate changes.
+    pub fn new(sample_hz: f64, max_latency_frames: usize) -> Self {
+        let mut probe = LatencyProbe {
+            level: 0.5,
+            max_latency_frames,
+            sample_hz: 0.0,
+            chirp: Vec::new(),
+            recorded: Vec::new(),
+            frame: None,
+            re: Vec::new(),
+            im: Vec::new(),
+            chirp_re: Vec::new(),
+            chirp_im: Vec::new(),
+            measurement: None,
+            shared: Arc::new(Shared {
+                requested: AtomicBool::new(false),
+                running: AtomicBool::new(false),
+                measurement: AtomicU64::new(0),
+            }),
+        };
+        probe.prepare(sample_hz);
+        probe
+    }
+
+    /// The longest latency in frames that may be measured.
+    pub fn max_latency_frames(&self) -> usize {
+        self.max_latency_frames
+    }
+
+    /// A handle for starting measurements and reading their results from another thread.
+    pub fn handle(&self) -> LatencyHandle {
+        LatencyHandle {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Start a measurement, beginning the chirp with the next frame rendered.
+    ///
+    /// Any measurement in progress is restarted.
+    pub fn start(&mut self) {
+        self.frame = Some(0);
+        self.shared.requested.store(false, Ordering::Relaxed);
+        self.shared.running.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether a measurement is in progress.
+    pub fn is_running(&self) -> bool {
+        self.frame.is_some()
+    }
+
+    /// The latest measurement, or `None` if there has been none or the chirp was not detected.
+    pub fn measurement(&self) -> Option<LatencyMeasurement> {
+        self.measurement
+    }
+
+    /// Generate the chirp and size the buffers for the given sample rate.
+    fn prepare(&mut self, sample_hz: f64) {
+        self.sample_hz = sample_hz;
+        let len = ((CHIRP_SECS * sample_hz).round() as usize).max(2);
+        let end_hz = CHIRP_END_HZ.min(0.45 * sample_hz);
+        self.chirp.clear();
+        self.chirp.extend((0..len).map(|i| {
+            // A linear sweep, faded in and out with a Hann window.
+            let t = i as f64 / sample_hz;
+            let sweep = (end_hz - CHIRP_START_HZ) / CHIRP_SECS;
+            let phase = 2.0 * PI * (CHIRP_START_HZ * t + 0.5 * sweep * t * t);
+            let window = 0.5 - 0.5 * (2.0 * PI * i as f64 / (len - 1) as f64).cos();
+            phase.sin() * window
+        }));
+        let recorded_len = len + self.max_latency_frames;
+        self.recorded.clear();
+        self.recorded.reserve(recorded_len);
+        let size = (recorded_len + len).next_power_of_two();
+        for buffer in [
+            &mut self.re,
+            &mut self.im,
+            &mut self.chirp_re,
+            &mut self.chirp_im,
+        ] {
+            buffer.clear();
+            buffer.resize(size, 0.0);
+        }
+        // The spectrum of the chirp is the same for every measurement.
+        self.chirp_re[..len].copy_from_slice(&self.chirp);
+        fft::fft(&mut self.chirp_re, &mut self.chirp_im);
+        // A measurement in progress is abandoned, as its recording no longer matches the chirp.
+        self.frame = None;
+        self.shared.running.store(false, Ordering::Relaxed);
+    }
+
+    /// Find the offset at which the recorded input best correlates with the chirp, and publish
+    /// the measurement.
+    fn finish(&mut self) {
+        self.frame = None;
+        self.shared.running.store(false, Ordering::Relaxed);
+
+        // Cross-correlate via the product of the recorded spectrum with the conjugate of the
+        // chirp's spectrum.
+        let n = self.re.len();
+        for (i, (re, im)) in self.re.iter_mut().zip(&mut self.im).enumerate() {
+            *re = self.recorded.get(i).cloned().unwrap_or(0.0);
+            *im = 0.0;
+        }
+        fft::fft(&mut self.re, &mut self.im);
+        let spectra = self.re.iter_mut().zip(&mut self.im);
+        for ((re, im), (&c_re, &c_im)) in spectra.zip(self.chirp_re.iter().zip(&self.chirp_im)) {
+            let (a, b) = (*re, *im);
+            // Multiply by the conjugate of the chirp's spectrum, then conjugate the product so
+            // that the forward transform below acts as an inverse transform.
+            *re = a * c_re + b * c_im;
+            *im = -(b * c_re - a * c_im);
+        }
+        fft::fft(&mut self.re, &mut self.im);
+
+        // Hardware may invert the signal, so the magnitude of the correlation is used.
+        let (lag, peak) = self.re[..=self.max_latency_frames]
+            .iter()
+            .map(|r| (r / n as f64).abs())
+            .enumerate()
+            .fold(
+                (0, 0.0),
+                |best, (lag, r)| if r > best.1 { (lag, r) } else { best },
+            );
+        let chirp_energy: f64 = self.chirp.iter().map(|s| s * s).sum();
+        let window = &self.recorded[lag..lag + self.chirp.len()];
+        let recorded_energy: f64 = window.iter().map(|s| s * s).sum();
+        let denominator = (chirp_energy * recorded_energy).sqrt();
+        let confidence = if denominator > 0.0 {
+            (peak / denominator).min(1.0) as f32
+        } else {
+            0.0
+        };
+
+        self.measurement = if confidence >= MIN_CONFIDENCE {
+            Some(LatencyMeasurement {
+                frames: lag,
+                confidence,
+            })
+        } else {
+            None
+        };
+        let bits = match self.measurement {
+            Some(m) => (m.frames as u64 + 1) | (u64::from(m.confidence.to_bits()) << 32),
+            None => 0,
+        };
+        self.shared.measurement.store(bits, Ordering::Release);
+    }
+}
+
+impl LatencyHandle {
+    /// Request a measurement, which begins at the start of the probe's next request for audio.
+    pub fn start(&self) {
+        self.shared.requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether a measurement has been requested or is in progress.
+    pub fn is_running(&self) -> bool {
+        self.shared.requested.load(Ordering::Relaxed) || self.shared.running.load(Ordering::Relaxed)
+    }
+
+    /// The latest measurement published by the **LatencyProbe**, or `None` if there has been
+    /// none or the chirp was not detected.
+    pub fn measurement(&self) -> Option<LatencyMeasurement> {
+        let bits = self.shared.measurement.load(Ordering::Acquire);
+        if bits == 0 {
+            return None;
+        }
+        Some(LatencyMeasurement {
+            frames: (bits & 0xFFFF_FFFF) as usize - 1,
+            confidence: f32::from_bits((bits >> 32) as u32),
+        })
+    }
+}
+
+impl LatencyMeasurement {
+    /// The round-trip latency in seconds at the given sample rate.
+    pub fn seconds(&self, sample_hz: f64) -> f64 {
+        self.frames as f64 / sample_hz
+    }
+}
+
+impl<F> Node<F> for LatencyProbe
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        if self.sample_hz != sample_hz {
+            self.prepare(sample_hz);
+        }
+        if self.shared.requested.load(Ordering::Relaxed) {
+            self.start();
+        }
+        let level = self.level as f64;
+        for frame in buffer.iter_mut() {
+            let i = match self.frame {
+                Some(i) => i,
+                None => {
+                    *frame = F::EQUILIBRIUM;
+                    continue;
+                }
+            };
+            let input = frame
+                .channels()
+                .map(|s| s.to_float_sample().to_sample::<f64>())
+                .sum::<f64>()
+                / F::CHANNELS as f64;
+            self.recorded.truncate(i);
+            self.recorded.push(input);
+            let chirp = self.chirp.get(i).map(|&s| s * level).unwrap_or(0.0);
+            let sample = chirp
+                .to_sample::<<F::Sample as Sample>::Float>()
+                .to_sample();
+            *frame = F::from_fn(|_| sample);
+            if i + 1 == self.chirp.len() + self.max_latency_frames {
+                self.finish();
+            } else {
+                self.frame = Some(i + 1);
+            }
+        }
+    }
+
+    /// Publishes the `"latency_frames"` of the latest measurement, if any.
+    fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+        if let Some(measurement) = self.measurement {
+            publish("latency_frames", measurement.frames as f32);
+        }
+    }
+}
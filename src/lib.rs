@@ -8,22 +8,52 @@
 //! 2. The [**Node** trait](./node/trait.Node.html) - to be implemented for types used within the
 //!    **Graph**.
 //!
+//! The most commonly used items may be imported at once via `use dsp::prelude::*;`.
+//!
 
 #![forbid(unsafe_code)]
 #![deny(missing_docs)]
 
-pub use daggy::{self, Walker};
+pub use buffer::{
+    deinterleave, from_dyn_frames, interleave, to_dyn_frames, BufferFormat, DynFrames, Planar,
+    Scratch, ScratchHandle,
+};
+pub use bus::{Bus, BusKind, BusLayout};
+pub use daggy::petgraph::graph::IndexType;
 pub use dasp::{
     self, interpolate,
     sample::{conv, Duplex as DuplexSample, FromSample, Sample, ToSample},
-    signal, slice, Frame, Signal,
+    signal, Frame, Signal,
 };
+#[cfg(feature = "derive")]
+pub use dsp_chain_derive::NodeEnum;
 pub use graph::{
-    Connection, Dag, EdgeIndex, Graph, Inputs, NodeIndex, NodesMut, Outputs, PetGraph, RawEdges,
-    RawNodes, VisitOrder, VisitOrderReverse, WouldCycle,
+    AnalysisRoute, Ancestors, BlockSizeReport, BufferAdvice, BufferAdvisor, Connection,
+    ControlSource, ControlTap, ControlValue, CountIn, Dag, Descendants, DeviceConfig, DeviceOutput,
+    EdgeIndex, External, ExternalKind, FeedbackConnection, Graph, Graph16, Graph32, GraphSwap,
+    IndexMap, Inputs, Insertion, NodeId, NodeIndex, NodeLayout, NodeVariant, NodesMut,
+    Notification, NotificationReceiver, Outputs, PanicPolicy, ParamHandle, ParamSmoothing,
+    ParamSubscription, PetGraph, Preset, RawEdges, RawNodes, RequestError, SwapHandle, Tempo,
+    TempoMap, TempoPoint, TempoRamp, TypedNodeIndex, ValidationReport, Violation, VisitOrder,
+    VisitOrderReverse, Watchdog, WouldCycle, SMOOTHING_INTERVAL,
 };
-pub use node::Node;
+pub use node::{BoxedNodeSend, Node, ParamChange};
+
+pub mod analysis;
+pub mod assets;
+pub mod description;
+pub mod engine;
+pub mod event;
+pub mod io;
+pub mod nodes;
+pub mod offline;
+pub mod prelude;
+pub mod raw;
+pub mod slice;
+pub mod template;
 
+mod buffer;
+mod bus;
 mod graph;
 mod node;
 
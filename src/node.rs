@@ -1,4 +1,24 @@
+use crate::buffer::{BufferFormat, Planar};
+use crate::bus::BusLayout;
+use crate::event::Event;
+use crate::graph::Tempo;
 use crate::{Frame, Sample};
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A change to one of a **Node**'s parameters that the **Node** made itself, e.g. in response to
+/// automation, MIDI or a macro while rendering.
+///
+/// The meaning of `param` is defined by each **Node** type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParamChange {
+    /// The index of the parameter that changed.
+    pub param: usize,
+    /// The new value of the parameter.
+    pub value: f32,
+}
 
 /// Types to be used as a **Node** within the DSP **Graph**.
 pub trait Node<F>
@@ -46,22 +66,354 @@ where
     fn wet(&self) -> <F::Sample as Sample>::Float {
         <F::Sample as Sample>::IDENTITY
     }
-}
 
-impl<F> Node<F> for Box<dyn Node<F>>
-where
-    F: Frame,
-{
-    #[inline]
-    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
-        (**self).audio_requested(buffer, sample_hz);
+    /// The number of frames by which the **Node** delays the signal passing through it.
+    ///
+    /// Nodes that use lookahead or block-based processing (e.g. limiters or FFT-based effects)
+    /// should override this. The `Graph` uses the reported latency to delay any parallel paths
+    /// that converge with this **Node**'s output so that they remain phase-aligned.
+    ///
+    /// By default, nodes are assumed to introduce no latency.
+    fn latency(&self) -> usize {
+        0
     }
-    #[inline]
-    fn dry(&self) -> <F::Sample as Sample>::Float {
-        (**self).dry()
+
+    /// The layout in which the **Node** would like to receive its buffer.
+    ///
+    /// By default, nodes receive an interleaved slice of **Frame**s via `audio_requested`.
+    ///
+    /// Nodes whose algorithms are faster on planar data may return `BufferFormat::Planar`, in
+    /// which case the `Graph` will convert the summed input to planar layout, call
+    /// `audio_requested_planar` instead of `audio_requested` and convert the result back.
+    fn buffer_format(&self) -> BufferFormat {
+        BufferFormat::Interleaved
     }
-    #[inline]
-    fn wet(&self) -> <F::Sample as Sample>::Float {
-        (**self).wet()
+
+    /// Request audio from the **Node** in planar layout.
+    ///
+    /// This is called instead of `audio_requested` when `buffer_format` returns
+    /// `BufferFormat::Planar`. The buffer holds the summed inputs in the same way as described by
+    /// `audio_requested`.
+    ///
+    /// By default, this leaves the buffer untouched.
+    fn audio_requested_planar(&mut self, buffer: Planar<F::Sample>, sample_hz: f64) {
+        let _ = (buffer, sample_hz);
     }
+
+    /// The number of frames for which the **Node** may continue to output sound after its inputs
+    /// fall silent, e.g. the decay of a reverb or the repeats of a delay.
+    ///
+    /// The `Graph` continues to render the **Node** for this many frames after its inputs fall
+    /// silent, even if `is_silent` returns `true`, and offline renderers continue to pull audio
+    /// until the tail has rung out. Nodes with an unbounded tail (e.g. a delay with full
+    /// feedback) should return `usize::MAX`.
+    ///
+    /// By default, nodes are assumed to have no tail.
+    fn tail_frames(&self) -> usize {
+        0
+    }
+
+    /// Whether the **Node** will output silence for as long as all of its inputs are silent.
+    ///
+    /// When this returns `true` and all inputs to the **Node** are silent, the `Graph` skips the
+    /// call to `audio_requested` and propagates silence downstream. Nodes that are idle (e.g. synth
+    /// voices that are not playing, or effects whose tail has fully decayed) should return `true`
+    /// so that they cost no CPU.
+    ///
+    /// By default, this returns `false` and the **Node** is always rendered.
+    fn is_silent(&self) -> bool {
+        false
+    }
+
+    /// Push any changes to the **Node**'s parameters that it made itself during the last call to
+    /// `audio_requested` onto `changes`.
+    ///
+    /// The `Graph` calls this after each time the **Node** renders and forwards the changes to the
+    /// host as `Notification::ParamChanged`, allowing UIs to reflect parameter movements that they
+    /// did not initiate (e.g. automation playback or MIDI learn feedback).
+    ///
+    /// By default, no changes are reported.
+    fn param_changes(&mut self, changes: &mut Vec<ParamChange>) {
+        let _ = changes;
+    }
+
+    /// Publish the **Node**'s latest analysis results (e.g. a level, pitch or tempo) as named
+    /// control values by calling `publish` with the name and value of each.
+    ///
+    /// The `Graph` calls this after each time the **Node** renders, making the values available
+    /// via `Graph::analysis_value` and sending them to any parameters routed from them via
+    /// `Graph::add_analysis_route`.
+    ///
+    /// By default, no values are published.
+    fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+        let _ = publish;
+    }
+
+    /// Handle an event sent to the **Node** via `Graph::send_event`, such as a note or a
+    /// controller change.
+    ///
+    /// The `Graph` delivers all queued events in the order in which they were sent before the
+    /// **Node** is rendered, even if the **Node** would otherwise be skipped as silent, so that
+    /// idle voices can be woken by a note.
+    ///
+    /// By default, events are ignored.
+    fn handle_event(&mut self, event: &Event) {
+        let _ = event;
+    }
+
+    /// Handle a message sent to the **Node** via `Graph::send_message`, such as a trigger, a gate
+    /// signal or a UI command.
+    ///
+    /// Messages may be of any type, so nodes should downcast `message` to the types that they
+    /// understand and ignore all others. The `Graph` delivers messages at the start of each
+    /// request for audio, before the **Node** is rendered.
+    ///
+    /// By default, messages are ignored.
+    fn handle_message(&mut self, message: &dyn Any) {
+        let _ = message;
+    }
+
+    /// The buses through which the **Node** exchanges audio when used as a processor in its own
+    /// right, e.g. when wrapped as a plugin or driven by an audio backend.
+    ///
+    /// By default, this is a main input and a main output with `F::CHANNELS` channels each.
+    fn bus_layout(&self) -> BusLayout {
+        BusLayout::effect(F::CHANNELS, F::CHANNELS)
+    }
+
+    /// Propose a new bus layout to the **Node**, returning whether it was accepted.
+    ///
+    /// Hosts call this while negotiating a layout (see `BusLayout::negotiate`). A **Node** that
+    /// accepts the layout should report it via `bus_layout` from then on.
+    ///
+    /// By default, only the current layout is accepted.
+    fn set_bus_layout(&mut self, layout: &BusLayout) -> bool {
+        *layout == self.bus_layout()
+    }
+
+    /// Whether the **Node** should be rendered via `process`, receiving the buffer of each of its
+    /// inputs separately from its output buffer.
+    ///
+    /// By default, this returns `false` and the **Node** processes the sum of its inputs in place
+    /// via `audio_requested`.
+    fn separate_io(&self) -> bool {
+        false
+    }
+
+    /// Render audio into `output` given the buffer of each of the **Node**'s inputs.
+    ///
+    /// This is called by the `Graph` instead of `audio_requested` when `separate_io` returns
+    /// `true`. `inputs` holds the buffer of each input connection in order, aligned for latency,
+    /// followed by the buffer of each feedback connection into the **Node**. Unlike the buffer
+    /// passed to `audio_requested`, these remain untouched while `output` is written, which suits
+    /// FFT-based and delay-based effects and nodes that treat their inputs as separate ports
+    /// (e.g. a sidechain). On entry, `output` holds the sum of all inputs.
+    ///
+    /// By default, this calls `audio_requested` with `output`.
+    fn process(&mut self, inputs: &[&[F]], output: &mut [F], sample_hz: f64) {
+        let _ = inputs;
+        self.audio_requested(output, sample_hz);
+    }
+
+    /// Called by the `Graph` when the number of active channels changes, e.g. after the host
+    /// switches from a stereo device to a 5.1 device.
+    ///
+    /// Only the first `channels` channels of each frame carry audio from then on. Nodes that keep
+    /// per-channel state should resize or reset it here.
+    ///
+    /// By default, this does nothing.
+    fn channels_changed(&mut self, channels: usize) {
+        let _ = channels;
+    }
+
+    /// Called by the `Graph` before each call to `audio_requested` or `process` while it has a
+    /// tempo map, with the tempo over the buffer about to be rendered.
+    ///
+    /// Tempo-synced nodes such as LFOs, delays, sequencers and clip players should follow the
+    /// mapping between beats and frames given by `tempo`, which is exact for the whole buffer
+    /// even while the tempo ramps.
+    ///
+    /// By default, this does nothing.
+    fn update_tempo(&mut self, tempo: &Tempo) {
+        let _ = tempo;
+    }
+
+    /// Complete a background load if it has finished, returning `true` if the **Node** switched
+    /// from loading to active during this call.
+    ///
+    /// The `Graph` calls this at the start of each request for audio in which the **Node** is
+    /// rendered and emits `Notification::NodeLoaded` when it returns `true`. See
+    /// `nodes::Placeholder`.
+    ///
+    /// By default, nodes are never loading and this returns `false`.
+    fn finish_loading(&mut self) -> bool {
+        false
+    }
+
+    /// Push an `Event::NoteOn` onto `notes` for each note that the **Node** is currently holding.
+    ///
+    /// When the **Node** is replaced via `Graph::crossfade_replace_node`, e.g. to switch an
+    /// instrument's preset, these notes are started on its replacement so that they carry on
+    /// sounding.
+    ///
+    /// By default, no notes are reported.
+    fn held_notes(&self, notes: &mut Vec<Event>) {
+        let _ = notes;
+    }
+
+    /// Whether the `Graph` should render the **Node** even when its output does not reach the
+    /// node from which audio is requested.
+    ///
+    /// The `Graph` only renders the nodes that feed the requested node, so that branches that
+    /// cannot be heard cost no CPU. Sinks whose work is a side effect, such as meters, recorders
+    /// and analysis taps hung off the signal path, should return `true` so that they are
+    /// rendered along with their inputs. This is queried whenever the `Graph` is restructured.
+    ///
+    /// By default, this returns `false`.
+    fn always_render(&self) -> bool {
+        false
+    }
+}
+
+/// A boxed **Node** that may be sent to another thread, e.g. to the audio thread.
+pub type BoxedNodeSend<F> = Box<dyn Node<F> + Send>;
+
+/// Implement **Node** for a pointer-like type by delegating every method to the pointee.
+///
+/// `$get` and `$get_mut` access the pointee for `&self` and `&mut self` methods respectively,
+/// given `self` bound to `$this`. Any attributes, e.g. doc comments, are applied to the impl.
+macro_rules! delegate_node {
+    ($(#[$attr:meta])* $ty:ty, $this:ident => $get:expr, $this_mut:ident => $get_mut:expr) => {
+        $(#[$attr])*
+        impl<F, N> Node<F> for $ty
+        where
+            F: Frame,
+            N: Node<F> + ?Sized,
+        {
+            #[inline]
+            fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+                let $this_mut = self;
+                $get_mut.audio_requested(buffer, sample_hz);
+            }
+            #[inline]
+            fn dry(&self) -> <F::Sample as Sample>::Float {
+                let $this = self;
+                $get.dry()
+            }
+            #[inline]
+            fn wet(&self) -> <F::Sample as Sample>::Float {
+                let $this = self;
+                $get.wet()
+            }
+            #[inline]
+            fn latency(&self) -> usize {
+                let $this = self;
+                $get.latency()
+            }
+            #[inline]
+            fn buffer_format(&self) -> BufferFormat {
+                let $this = self;
+                $get.buffer_format()
+            }
+            #[inline]
+            fn audio_requested_planar(&mut self, buffer: Planar<F::Sample>, sample_hz: f64) {
+                let $this_mut = self;
+                $get_mut.audio_requested_planar(buffer, sample_hz);
+            }
+            #[inline]
+            fn is_silent(&self) -> bool {
+                let $this = self;
+                $get.is_silent()
+            }
+            #[inline]
+            fn tail_frames(&self) -> usize {
+                let $this = self;
+                $get.tail_frames()
+            }
+            #[inline]
+            fn param_changes(&mut self, changes: &mut Vec<ParamChange>) {
+                let $this_mut = self;
+                $get_mut.param_changes(changes);
+            }
+            #[inline]
+            fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+                let $this = self;
+                $get.analysis_values(publish);
+            }
+            #[inline]
+            fn handle_event(&mut self, event: &Event) {
+                let $this_mut = self;
+                $get_mut.handle_event(event);
+            }
+            #[inline]
+            fn handle_message(&mut self, message: &dyn Any) {
+                let $this_mut = self;
+                $get_mut.handle_message(message);
+            }
+            #[inline]
+            fn bus_layout(&self) -> BusLayout {
+                let $this = self;
+                $get.bus_layout()
+            }
+            #[inline]
+            fn set_bus_layout(&mut self, layout: &BusLayout) -> bool {
+                let $this_mut = self;
+                $get_mut.set_bus_layout(layout)
+            }
+            #[inline]
+            fn separate_io(&self) -> bool {
+                let $this = self;
+                $get.separate_io()
+            }
+            #[inline]
+            fn process(&mut self, inputs: &[&[F]], output: &mut [F], sample_hz: f64) {
+                let $this_mut = self;
+                $get_mut.process(inputs, output, sample_hz);
+            }
+            #[inline]
+            fn channels_changed(&mut self, channels: usize) {
+                let $this_mut = self;
+                $get_mut.channels_changed(channels);
+            }
+            #[inline]
+            fn update_tempo(&mut self, tempo: &Tempo) {
+                let $this_mut = self;
+                $get_mut.update_tempo(tempo);
+            }
+            #[inline]
+            fn finish_loading(&mut self) -> bool {
+                let $this_mut = self;
+                $get_mut.finish_loading()
+            }
+            #[inline]
+            fn held_notes(&self, notes: &mut Vec<Event>) {
+                let $this = self;
+                $get.held_notes(notes);
+            }
+            #[inline]
+            fn always_render(&self) -> bool {
+                let $this = self;
+                $get.always_render()
+            }
+        }
+    };
 }
+
+delegate_node!(Box<N>, this => (**this), this => (**this));
+delegate_node!(&mut N, this => (**this), this => (**this));
+
+// Nodes shared with another part of the application (e.g. a GUI) on the same thread.
+delegate_node!(Rc<RefCell<N>>, this => this.borrow(), this => this.borrow_mut());
+
+delegate_node!(
+    /// Nodes shared with another thread, e.g. a controller thread.
+    ///
+    /// Every method takes the lock, blocking until it is free, so this is unsuitable for
+    /// real-time use: if the other thread holds the lock while the **Graph** renders, the audio
+    /// thread waits for it and may drop out. To control a node's parameters from another thread
+    /// while rendering in real time, use a **ParamHandle** instead. A poisoned lock is recovered
+    /// rather than propagating the panic onto the audio thread.
+    Arc<Mutex<N>>,
+    this => this.lock().unwrap_or_else(PoisonError::into_inner),
+    this => this.lock().unwrap_or_else(PoisonError::into_inner)
+);
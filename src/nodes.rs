@@ -0,0 +1,99 @@
+//! A collection of commonly used **Node** implementations.
+//!
+//! Each node is generic over the **Frame** type of the **Graph** in which it is used.
+//!
+//! Other than the `Chain`, `MidSide` and `Placeholder` utilities, nodes are grouped behind cargo features
+//! that are all enabled by default via the `full` feature:
+//!
+//! - `osc`: sources such as `SignalNode` and `Metronome`.
+//! - `filters`: filters, equalisers, crossovers and resonators such as `Crossover`, `GraphicEq`,
+//!   `MultiBand` and `ResonatorBank`.
+//! - `dynamics`: compressors, expanders, gates and saturation such as `Compressor`, `Expander`
+//!   and `Saturation`.
+//! - `reverb`: reverberation and the delay-based filters from which it is built, such as `Comb`
+//!   and `Allpass`.
+//! - `sampler`: sample playback and recording such as `Recorder` and `TakeLanes`.
+//! - `analysis`: meters and detectors such as `PhaseMeter`, `Tuner` and `TempoEstimator`.
+
+#[cfg(feature = "reverb")]
+pub use self::allpass::Allpass;
+pub use self::chain::{Chain, IntoNodes};
+#[cfg(feature = "reverb")]
+pub use self::comb::{Comb, CombKind};
+#[cfg(feature = "dynamics")]
+pub use self::compressor::Compressor;
+#[cfg(feature = "filters")]
+pub use self::crossover::Crossover;
+#[cfg(feature = "dynamics")]
+pub use self::expander::Expander;
+#[cfg(feature = "filters")]
+pub use self::graphic_eq::GraphicEq;
+#[cfg(feature = "analysis")]
+pub use self::key_detector::{Chord, Key, KeyDetector, KeyEvent, KeyEvents, Mode};
+#[cfg(feature = "osc")]
+pub use self::metronome::Metronome;
+pub use self::mid_side::MidSide;
+#[cfg(feature = "filters")]
+pub use self::multi_band::MultiBand;
+#[cfg(feature = "analysis")]
+pub use self::phase_meter::PhaseMeter;
+pub use self::placeholder::Placeholder;
+#[cfg(feature = "sampler")]
+pub use self::recorder::{RecordState, Recorder, Take};
+#[cfg(feature = "filters")]
+pub use self::resonator_bank::{Partial, ResonatorBank};
+#[cfg(feature = "dynamics")]
+pub use self::saturation::{Saturation, SaturationCurve};
+#[cfg(feature = "osc")]
+pub use self::signal::SignalNode;
+#[cfg(feature = "analysis")]
+pub use self::spectrogram::{Spectrogram, SpectrogramHandle, SpectrogramSnapshot};
+#[cfg(feature = "sampler")]
+pub use self::take_lanes::{CompSegment, TakeLanes};
+#[cfg(feature = "analysis")]
+pub use self::tempo_estimator::{TempoEstimator, TempoHandle, TempoReading};
+#[cfg(feature = "analysis")]
+pub use self::tuner::{Tuner, TunerHandle, TunerReading};
+
+#[cfg(feature = "reverb")]
+mod allpass;
+mod chain;
+#[cfg(feature = "reverb")]
+mod comb;
+#[cfg(feature = "dynamics")]
+mod compressor;
+#[cfg(feature = "filters")]
+mod crossover;
+#[cfg(feature = "reverb")]
+mod delay_line;
+#[cfg(feature = "dynamics")]
+mod expander;
+pub(crate) mod filter;
+#[cfg(feature = "filters")]
+mod graphic_eq;
+#[cfg(feature = "analysis")]
+mod key_detector;
+#[cfg(feature = "osc")]
+mod metronome;
+mod mid_side;
+#[cfg(feature = "filters")]
+mod multi_band;
+#[cfg(feature = "analysis")]
+mod phase_meter;
+mod placeholder;
+#[cfg(feature = "sampler")]
+mod recorder;
+#[cfg(feature = "filters")]
+mod resonator_bank;
+#[cfg(feature = "dynamics")]
+mod saturation;
+#[cfg(feature = "osc")]
+mod signal;
+#[cfg(feature = "analysis")]
+mod spectrogram;
+#[cfg(feature = "sampler")]
+mod take_lanes;
+#[cfg(feature = "analysis")]
+mod tempo_estimator;
+#[cfg(feature = "analysis")]
+mod tuner;
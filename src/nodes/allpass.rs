@@ -0,0 +1,76 @@
+//! Schroeder allpass filters.
+
+use super::comb::decay_frames;
+use super::delay_line::DelayLine;
+use super::filter::{from_f64, to_f64};
+use crate::node::Node;
+use dasp::Frame;
+
+/// A Schroeder allpass filter, delaying a signal by a fractional number of frames with a flat
+/// magnitude response.
+///
+/// `w[n] = x[n] + g * w[n - d]` and `y[n] = w[n - d] - g * w[n]`, so the filter smears
+/// transients in time without colouring a steady signal. Several in series diffuse the echoes of
+/// a reverb.
+///
+/// As with **Comb**, the delay is given in frames and read via linear interpolation.
+#[derive(Clone, Debug)]
+pub struct Allpass {
+    /// The delay in frames, from `1.0` up to the maximum delay given to `new`.
+    pub delay_frames: f64,
+    /// The feedback gain, which should be less than `1.0` in magnitude. `0.5` to `0.7` is
+    /// typical of reverbs.
+    pub gain: f32,
+    line: DelayLine,
+}
+
+impl Allpass {
+    /// An allpass filter able to delay by up to `max_delay_frames`, initially delaying by that
+    /// many frames.
+    pub fn new(max_delay_frames: usize, gain: f32) -> Self {
+        let max_delay_frames = max_delay_frames.max(1);
+        Allpass {
+            delay_frames: max_delay_frames as f64,
+            gain,
+            line: DelayLine::new(max_delay_frames),
+        }
+    }
+
+    /// The longest delay in frames.
+    pub fn max_delay_frames(&self) -> usize {
+        self.line.max_delay()
+    }
+
+    /// Clear the delay line.
+    pub fn reset(&mut self) {
+        self.line.reset();
+    }
+}
+
+impl<F> Node<F> for Allpass
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], _sample_hz: f64) {
+        self.line.prepare(F::CHANNELS);
+        let gain = self.gain as f64;
+        for frame in buffer.iter_mut() {
+            *frame = F::from_fn(|ch| {
+                let x = frame.channel(ch).map(|&s| to_f64(s)).unwrap_or(0.0);
+                let delayed = self.line.read(ch, self.delay_frames);
+                let w = x + gain * delayed;
+                self.line.write(ch, w);
+                from_f64(delayed - gain * w)
+            });
+            self.line.advance();
+        }
+    }
+
+    fn tail_frames(&self) -> usize {
+        decay_frames(self.delay_frames, self.gain)
+    }
+
+    fn channels_changed(&mut self, _channels: usize) {
+        self.reset();
+    }
+}
@@ -0,0 +1,224 @@
+//! Static pipelines of nodes, built at compile time.
+
+use crate::event::Event;
+use crate::graph::Tempo;
+use crate::node::{BoxedNodeSend, Node};
+use dasp::Frame;
+use std::any::Any;
+
+/// A fixed series of nodes, each processing the output of the one before it in place.
+///
+/// The stages are held as a tuple, e.g. `Chain<(Osc, Filter, Reverb)>`, of up to twelve nodes
+/// that all process the same **Frame** type. Every call to a stage is statically dispatched and
+/// the chain allocates nothing, so pipelines whose topology never changes avoid the overhead of
+/// a **Graph**. As the whole chain is a **Node**, it may also be added to a **Graph** as a single
+/// node.
+///
+/// Events and messages are forwarded to every stage. The latency and tail of the chain are the
+/// sums of those of its stages, while the dry and wet levels of the stages are not applied.
+///
+/// A `Chain<Vec<N>>` holds any number of stages of the same type (e.g. boxed nodes), as created
+/// by freezing a series of nodes within a **Graph** via `Graph::freeze_chain`. Either kind of
+/// chain may be expanded into a series of nodes within a **Graph** via `Graph::expand_chain`.
+#[derive(Clone, Debug, Default)]
+pub struct Chain<T> {
+    stages: T,
+}
+
+impl<T> Chain<T> {
+    /// A chain processing audio with each of the given stages in order.
+    pub fn new(stages: T) -> Self {
+        Chain { stages }
+    }
+
+    /// The tuple of stages.
+    pub fn stages(&self) -> &T {
+        &self.stages
+    }
+
+    /// The tuple of stages.
+    pub fn stages_mut(&mut self) -> &mut T {
+        &mut self.stages
+    }
+
+    /// Consume the chain, returning the tuple of stages.
+    pub fn into_stages(self) -> T {
+        self.stages
+    }
+}
+
+impl<T> From<T> for Chain<T> {
+    fn from(stages: T) -> Self {
+        Chain::new(stages)
+    }
+}
+
+impl<F> From<Chain<Vec<BoxedNodeSend<F>>>> for BoxedNodeSend<F>
+where
+    F: Frame + 'static,
+{
+    fn from(chain: Chain<Vec<BoxedNodeSend<F>>>) -> Self {
+        Box::new(chain)
+    }
+}
+
+/// The stages of a **Chain** that may each be converted into a node of type `N`.
+///
+/// Implemented for tuples of up to twelve stages and for `Vec`s of stages.
+pub trait IntoNodes<N> {
+    /// Convert each stage into a node of type `N`, pushing them onto `nodes` in order.
+    fn into_nodes(self, nodes: &mut Vec<N>);
+}
+
+impl<N, T> IntoNodes<N> for Vec<T>
+where
+    T: Into<N>,
+{
+    fn into_nodes(self, nodes: &mut Vec<N>) {
+        nodes.extend(self.into_iter().map(Into::into));
+    }
+}
+
+impl<F, N> Node<F> for Chain<Vec<N>>
+where
+    F: Frame,
+    N: Node<F>,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        for stage in &mut self.stages {
+            stage.audio_requested(buffer, sample_hz);
+        }
+    }
+
+    fn latency(&self) -> usize {
+        self.stages.iter().map(|stage| stage.latency()).sum()
+    }
+
+    fn tail_frames(&self) -> usize {
+        self.stages
+            .iter()
+            .fold(0, |tail, stage| tail.saturating_add(stage.tail_frames()))
+    }
+
+    fn is_silent(&self) -> bool {
+        self.stages.iter().all(|stage| stage.is_silent())
+    }
+
+    fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+        for stage in &self.stages {
+            stage.analysis_values(publish);
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        for stage in &mut self.stages {
+            stage.handle_event(event);
+        }
+    }
+
+    fn handle_message(&mut self, message: &dyn Any) {
+        for stage in &mut self.stages {
+            stage.handle_message(message);
+        }
+    }
+
+    fn channels_changed(&mut self, channels: usize) {
+        for stage in &mut self.stages {
+            stage.channels_changed(channels);
+        }
+    }
+
+    fn update_tempo(&mut self, tempo: &Tempo) {
+        for stage in &mut self.stages {
+            stage.update_tempo(tempo);
+        }
+    }
+
+    fn finish_loading(&mut self) -> bool {
+        self.stages
+            .iter_mut()
+            .fold(false, |loaded, stage| stage.finish_loading() | loaded)
+    }
+
+    fn held_notes(&self, notes: &mut Vec<Event>) {
+        for stage in &self.stages {
+            stage.held_notes(notes);
+        }
+    }
+}
+
+macro_rules! impl_chain {
+    ($($N:ident $n:tt),+) => {
+        impl<T, $($N),+> IntoNodes<T> for ($($N,)+)
+        where
+            $($N: Into<T>,)+
+        {
+            fn into_nodes(self, nodes: &mut Vec<T>) {
+                $(nodes.push(self.$n.into());)+
+            }
+        }
+
+        impl<F, $($N),+> Node<F> for Chain<($($N,)+)>
+        where
+            F: Frame,
+            $($N: Node<F>,)+
+        {
+            fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+                $(self.stages.$n.audio_requested(buffer, sample_hz);)+
+            }
+
+            fn latency(&self) -> usize {
+                0 $(+ self.stages.$n.latency())+
+            }
+
+            fn tail_frames(&self) -> usize {
+                0usize $(.saturating_add(self.stages.$n.tail_frames()))+
+            }
+
+            fn is_silent(&self) -> bool {
+                true $(&& self.stages.$n.is_silent())+
+            }
+
+            fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+                $(self.stages.$n.analysis_values(publish);)+
+            }
+
+            fn handle_event(&mut self, event: &Event) {
+                $(self.stages.$n.handle_event(event);)+
+            }
+
+            fn handle_message(&mut self, message: &dyn Any) {
+                $(self.stages.$n.handle_message(message);)+
+            }
+
+            fn channels_changed(&mut self, channels: usize) {
+                $(self.stages.$n.channels_changed(channels);)+
+            }
+
+            fn update_tempo(&mut self, tempo: &Tempo) {
+                $(self.stages.$n.update_tempo(tempo);)+
+            }
+
+            fn finish_loading(&mut self) -> bool {
+                false $(| self.stages.$n.finish_loading())+
+            }
+
+            fn held_notes(&self, notes: &mut Vec<Event>) {
+                $(self.stages.$n.held_notes(notes);)+
+            }
+        }
+    };
+}
+
+impl_chain!(A 0);
+impl_chain!(A 0, B 1);
+impl_chain!(A 0, B 1, C 2);
+impl_chain!(A 0, B 1, C 2, D 3);
+impl_chain!(A 0, B 1, C 2, D 3, E 4);
+impl_chain!(A 0, B 1, C 2, D 3, E 4, G 5);
+impl_chain!(A 0, B 1, C 2, D 3, E 4, G 5, H 6);
+impl_chain!(A 0, B 1, C 2, D 3, E 4, G 5, H 6, I 7);
+impl_chain!(A 0, B 1, C 2, D 3, E 4, G 5, H 6, I 7, J 8);
+impl_chain!(A 0, B 1, C 2, D 3, E 4, G 5, H 6, I 7, J 8, K 9);
+impl_chain!(A 0, B 1, C 2, D 3, E 4, G 5, H 6, I 7, J 8, K 9, L 10);
+impl_chain!(A 0, B 1, C 2, D 3, E 4, G 5, H 6, I 7, J 8, K 9, L 10, M 11);
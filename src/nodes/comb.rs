@@ -0,0 +1,144 @@
+//! Feedforward and feedback comb filters.
+
+use super::delay_line::DelayLine;
+use super::filter::{from_f64, to_f64};
+use crate::node::Node;
+use dasp::Frame;
+
+/// The structure of a **Comb** filter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CombKind {
+    /// Adds a delayed copy of the input to the input, `y[n] = x[n] + g * x[n - d]`, cutting
+    /// notches into the spectrum, e.g. for flangers.
+    FeedForward,
+    /// Adds a delayed copy of the output to the input, `y[n] = x[n] + g * y[n - d]`, producing
+    /// resonant peaks that ring on, e.g. for reverbs and resonators.
+    Feedback,
+}
+
+/// A comb filter, mixing a signal with a copy of itself delayed by a fractional number of frames.
+///
+/// The delay is given in frames rather than in time so that reverbs may choose mutually prime
+/// delays; multiply a time in seconds by the sample rate to convert it. Fractional delays are
+/// read via linear interpolation, so the delay may be modulated smoothly.
+///
+/// In a feedback comb, the delayed output may be damped by a one-pole low-pass filter, so that
+/// high frequencies decay faster than low frequencies as they do in a real room.
+#[derive(Clone, Debug)]
+pub struct Comb {
+    /// The delay in frames, from `1.0` up to the maximum delay given to `new`.
+    pub delay_frames: f64,
+    /// The gain applied to the delayed signal. For a feedback comb, values of `1.0` or more in
+    /// magnitude ring on forever.
+    pub gain: f32,
+    /// How strongly high frequencies in the feedback path are damped, from `0.0` (not at all) to
+    /// `1.0`. Ignored by feedforward combs.
+    pub damping: f32,
+    kind: CombKind,
+    line: DelayLine,
+    /// The state of the damping filter of each channel.
+    damped: Vec<f64>,
+}
+
+impl Comb {
+    /// A comb of the given kind able to delay by up to `max_delay_frames`, initially delaying by
+    /// that many frames.
+    pub fn new(kind: CombKind, max_delay_frames: usize, gain: f32) -> Self {
+        let max_delay_frames = max_delay_frames.max(1);
+        Comb {
+            delay_frames: max_delay_frames as f64,
+            gain,
+            damping: 0.0,
+            kind,
+            line: DelayLine::new(max_delay_frames),
+            damped: Vec::new(),
+        }
+    }
+
+    /// A feedforward comb able to delay by up to `max_delay_frames`.
+    pub fn feedforward(max_delay_frames: usize, gain: f32) -> Self {
+        Self::new(CombKind::FeedForward, max_delay_frames, gain)
+    }
+
+    /// A feedback comb able to delay by up to `max_delay_frames`.
+    pub fn feedback(max_delay_frames: usize, gain: f32) -> Self {
+        Self::new(CombKind::Feedback, max_delay_frames, gain)
+    }
+
+    /// The structure of the filter.
+    pub fn kind(&self) -> CombKind {
+        self.kind
+    }
+
+    /// The longest delay in frames.
+    pub fn max_delay_frames(&self) -> usize {
+        self.line.max_delay()
+    }
+
+    /// Clear the delay line.
+    pub fn reset(&mut self) {
+        self.line.reset();
+        for s in &mut self.damped {
+            *s = 0.0;
+        }
+    }
+}
+
+impl<F> Node<F> for Comb
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], _sample_hz: f64) {
+        self.line.prepare(F::CHANNELS);
+        if self.damped.len() != F::CHANNELS {
+            self.damped.resize(F::CHANNELS, 0.0);
+        }
+        let gain = self.gain as f64;
+        let damping = (self.damping as f64).clamp(0.0, 1.0);
+        for frame in buffer.iter_mut() {
+            *frame = F::from_fn(|ch| {
+                let x = frame.channel(ch).map(|&s| to_f64(s)).unwrap_or(0.0);
+                let delayed = self.line.read(ch, self.delay_frames);
+                let y = match self.kind {
+                    CombKind::FeedForward => {
+                        self.line.write(ch, x);
+                        x + gain * delayed
+                    }
+                    CombKind::Feedback => {
+                        let damped = &mut self.damped[ch];
+                        *damped = delayed + damping * (*damped - delayed);
+                        let y = x + gain * *damped;
+                        self.line.write(ch, y);
+                        y
+                    }
+                };
+                from_f64(y)
+            });
+            self.line.advance();
+        }
+    }
+
+    fn tail_frames(&self) -> usize {
+        match self.kind {
+            CombKind::FeedForward => self.delay_frames.ceil() as usize,
+            CombKind::Feedback => decay_frames(self.delay_frames, self.gain),
+        }
+    }
+
+    fn channels_changed(&mut self, _channels: usize) {
+        self.reset();
+    }
+}
+
+/// The number of frames taken for a signal recirculating through a delay of `delay_frames` with
+/// the given gain to decay by 60dB.
+pub(crate) fn decay_frames(delay_frames: f64, gain: f32) -> usize {
+    let gain = gain.abs() as f64;
+    if gain >= 1.0 {
+        usize::MAX
+    } else if gain <= 0.0 {
+        delay_frames.ceil() as usize
+    } else {
+        (delay_frames * (0.001f64.ln() / gain.ln())).ceil() as usize
+    }
+}
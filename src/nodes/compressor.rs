@@ -0,0 +1,194 @@
+//! Feed-forward compression with a soft knee and an optional sidechain input.
+
+use super::expander::{coefficient, from_db, peak, to_db};
+use crate::bus::{BusKind, BusLayout};
+use crate::event::Event;
+use crate::node::Node;
+use dasp::{Frame, Sample};
+
+/// A feed-forward compressor that attenuates signals rising above a threshold.
+///
+/// Above the threshold, every `ratio` decibels that the key signal rises is reduced to one
+/// decibel of output. Within `knee_db` around the threshold the ratio is eased in gradually, so
+/// that compression sets in smoothly. The gain is reduced over `attack_ms` and recovers over
+/// `release_ms`, and `makeup_db` of gain is applied afterwards to restore the level.
+///
+/// By default, the compressor is keyed by its own input. Once `sidechain_input` designates one
+/// of its input connections, the compressor is rendered via `Node::process` and keyed by that
+/// connection alone, e.g. to duck music under a voice-over, while its remaining inputs are
+/// summed and compressed. If the designated connection does not exist, the compressor falls back
+/// to keying from its main input. Use
+/// [`process`](./struct.Compressor.html#method.process) to key it from any other signal.
+///
+/// The parameters may also be set via `Event::Param`, where `param` is `0` for the threshold,
+/// `1` for the ratio, `2` for the attack, `3` for the release, `4` for the knee and `5` for the
+/// makeup gain.
+#[derive(Clone, Debug)]
+pub struct Compressor {
+    /// The level in decibels above which the signal is attenuated.
+    pub threshold_db: f32,
+    /// The compression ratio. `1.0` has no effect, while large ratios behave like a limiter.
+    pub ratio: f32,
+    /// The time in milliseconds taken to reduce the gain.
+    pub attack_ms: f32,
+    /// The time in milliseconds taken for the gain to recover.
+    pub release_ms: f32,
+    /// The width in decibels of the soft knee centred on the threshold. `0.0` is a hard knee.
+    pub knee_db: f32,
+    /// The gain in decibels applied after compression.
+    pub makeup_db: f32,
+    /// The index of the input connection that keys the compressor, in the order in which the
+    /// inputs are passed to `Node::process`, or `None` to key it from its main input.
+    pub sidechain_input: Option<usize>,
+    /// The current gain reduction in decibels, which is zero or negative.
+    gain_db: f32,
+}
+
+impl Compressor {
+    /// A new compressor with the given threshold in decibels and ratio.
+    ///
+    /// The attack defaults to 10ms, release to 100ms, knee to 6dB and makeup gain to 0dB.
+    pub fn new(threshold_db: f32, ratio: f32) -> Self {
+        Compressor {
+            threshold_db,
+            ratio,
+            attack_ms: 10.0,
+            release_ms: 100.0,
+            knee_db: 6.0,
+            makeup_db: 0.0,
+            sidechain_input: None,
+            gain_db: 0.0,
+        }
+    }
+
+    /// The same compressor keyed by the input connection at the given index.
+    pub fn with_sidechain_input(mut self, index: usize) -> Self {
+        self.sidechain_input = Some(index);
+        self
+    }
+
+    /// The gain reduction in decibels currently applied by the compressor, excluding the makeup
+    /// gain.
+    pub fn gain_reduction_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Reset the gain reduction to its initial state.
+    pub fn reset(&mut self) {
+        self.gain_db = 0.0;
+    }
+
+    /// Compress the `buffer` in place using the level of the `key` signal.
+    ///
+    /// **Panics** if `key` and `buffer` differ in length.
+    pub fn process<F>(&mut self, buffer: &mut [F], key: &[F], sample_hz: f64)
+    where
+        F: Frame,
+    {
+        assert_eq!(buffer.len(), key.len());
+        let attack = coefficient(self.attack_ms, sample_hz);
+        let release = coefficient(self.release_ms, sample_hz);
+        for (frame, key_frame) in buffer.iter_mut().zip(key) {
+            let gain = self.next_gain(peak(key_frame), attack, release);
+            *frame = frame.scale_amp(gain.to_sample());
+        }
+    }
+
+    /// The static gain in decibels applied to a key at the given level.
+    fn gain_computer(&self, level_db: f32) -> f32 {
+        let over = level_db - self.threshold_db;
+        let knee = self.knee_db.max(0.0);
+        let slope = 1.0 / self.ratio.max(1.0) - 1.0;
+        if 2.0 * over <= -knee {
+            0.0
+        } else if 2.0 * over.abs() < knee {
+            let x = over + 0.5 * knee;
+            slope * x * x / (2.0 * knee)
+        } else {
+            slope * over
+        }
+    }
+
+    /// Advance the detector by one frame with the given key level, returning the linear gain.
+    fn next_gain(&mut self, level: f32, attack: f32, release: f32) -> f32 {
+        let target_db = self.gain_computer(to_db(level));
+        let coef = if target_db < self.gain_db {
+            attack
+        } else {
+            release
+        };
+        self.gain_db = target_db + coef * (self.gain_db - target_db);
+        from_db(self.gain_db + self.makeup_db)
+    }
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Compressor::new(-18.0, 4.0)
+    }
+}
+
+impl<F> Node<F> for Compressor
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        let attack = coefficient(self.attack_ms, sample_hz);
+        let release = coefficient(self.release_ms, sample_hz);
+        for frame in buffer.iter_mut() {
+            let gain = self.next_gain(peak(frame), attack, release);
+            *frame = frame.scale_amp(gain.to_sample());
+        }
+    }
+
+    fn separate_io(&self) -> bool {
+        self.sidechain_input.is_some()
+    }
+
+    /// Compresses the sum of all inputs but the sidechain, keyed by the sidechain.
+    fn process(&mut self, inputs: &[&[F]], output: &mut [F], sample_hz: f64) {
+        let key = match self.sidechain_input.and_then(|i| inputs.get(i)) {
+            Some(&key) => key,
+            None => return self.audio_requested(output, sample_hz),
+        };
+        // `output` holds the sum of all inputs, including the sidechain, so it is summed anew.
+        dasp::slice::equilibrium(output);
+        let index = self.sidechain_input;
+        for (_, input) in inputs.iter().enumerate().filter(|&(i, _)| Some(i) != index) {
+            dasp::slice::zip_map_in_place(output, input, |out, input| {
+                out.add_amp(input.to_signed_frame())
+            });
+        }
+        self.process(output, key, sample_hz);
+    }
+
+    /// Declares a sidechain bus while `sidechain_input` is set.
+    fn bus_layout(&self) -> BusLayout {
+        match self.sidechain_input {
+            None => BusLayout::effect(F::CHANNELS, F::CHANNELS),
+            Some(_) => BusLayout::new()
+                .with_bus("Input", BusKind::MainInput, F::CHANNELS)
+                .with_sidechain("Sidechain", F::CHANNELS)
+                .with_bus("Output", BusKind::MainOutput, F::CHANNELS),
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { param, value } = *event {
+            match param {
+                0 => self.threshold_db = value,
+                1 => self.ratio = value,
+                2 => self.attack_ms = value,
+                3 => self.release_ms = value,
+                4 => self.knee_db = value,
+                5 => self.makeup_db = value,
+                _ => (),
+            }
+        }
+    }
+
+    /// Publishes the current `"gain_reduction_db"`.
+    fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+        publish("gain_reduction_db", self.gain_db);
+    }
+}
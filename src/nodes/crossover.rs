@@ -0,0 +1,137 @@
+//! Splitting a signal into phase-coherent frequency bands across separate nodes.
+
+use super::filter::LinkwitzRiley;
+use crate::node::Node;
+use dasp::Frame;
+
+/// One band of a set of fourth order Linkwitz-Riley crossovers, passing only the frequencies
+/// between two adjacent crossovers.
+///
+/// Create one node for each band via [`Crossover::bands`](#method.bands) and connect the same
+/// source to each of them to split the source into bands on separate paths through the
+/// **Graph**, e.g. to compress, saturate or route each band independently before summing them
+/// again. Each band is passed through the all-pass response of every crossover above it, so that
+/// the bands are phase-aligned and sum back to a flat response when left unchanged, exactly as
+/// within `MultiBand`.
+#[derive(Clone, Debug)]
+pub struct Crossover {
+    /// The frequency of each crossover in ascending order.
+    crossovers: Vec<f64>,
+    /// The index of the band passed, from `0` for the lowest band.
+    band: usize,
+    /// The crossovers below the band, each removing the frequencies beneath it.
+    high_passes: Vec<LinkwitzRiley>,
+    /// The crossover above the band, if any, removing the frequencies above it.
+    low_pass: Option<LinkwitzRiley>,
+    /// The crossovers further above the band, aligning its phase with the bands above.
+    all_passes: Vec<LinkwitzRiley>,
+    /// The sample rate for which the filters were designed.
+    sample_hz: f64,
+}
+
+impl Crossover {
+    /// Pass the band with the given index among the `crossovers.len() + 1` bands split at the
+    /// given crossover frequencies in hz, from `0` for the lowest band.
+    ///
+    /// The crossover frequencies are sorted into ascending order.
+    ///
+    /// **Panics** if `band` is greater than `crossovers.len()`.
+    pub fn new(crossovers: &[f64], band: usize) -> Self {
+        assert!(
+            band <= crossovers.len(),
+            "there is one more band than there are crossovers"
+        );
+        let mut crossovers = crossovers.to_vec();
+        crossovers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        Crossover {
+            crossovers,
+            band,
+            high_passes: Vec::new(),
+            low_pass: None,
+            all_passes: Vec::new(),
+            sample_hz: 0.0,
+        }
+    }
+
+    /// A node for each of the `crossovers.len() + 1` bands split at the given crossover
+    /// frequencies in hz, from the lowest band to the highest.
+    pub fn bands(crossovers: &[f64]) -> Vec<Self> {
+        (0..=crossovers.len())
+            .map(|band| Crossover::new(crossovers, band))
+            .collect()
+    }
+
+    /// The index of the band passed, from `0` for the lowest band.
+    pub fn band(&self) -> usize {
+        self.band
+    }
+
+    /// The frequency of each crossover in hz in ascending order.
+    pub fn crossovers(&self) -> &[f64] {
+        &self.crossovers
+    }
+
+    /// The range of frequencies in hz passed by the band.
+    pub fn range_hz(&self) -> (f64, f64) {
+        let low = match self.band {
+            0 => 0.0,
+            band => self.crossovers[band - 1],
+        };
+        let high = self
+            .crossovers
+            .get(self.band)
+            .cloned()
+            .unwrap_or(f64::INFINITY);
+        (low, high)
+    }
+
+    /// Clear the state of all filters.
+    pub fn reset(&mut self) {
+        let filters = self
+            .high_passes
+            .iter_mut()
+            .chain(&mut self.low_pass)
+            .chain(&mut self.all_passes);
+        for filter in filters {
+            filter.reset();
+        }
+    }
+
+    /// Rebuild all filters for the given sample rate.
+    fn prepare(&mut self, sample_hz: f64) {
+        self.sample_hz = sample_hz;
+        let filter = |&hz: &f64| LinkwitzRiley::new(hz, sample_hz);
+        let (below, above) = self.crossovers.split_at(self.band);
+        self.high_passes = below.iter().map(filter).collect();
+        self.low_pass = above.first().map(filter);
+        self.all_passes = above.iter().skip(1).map(filter).collect();
+    }
+}
+
+impl<F> Node<F> for Crossover
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        if self.sample_hz != sample_hz {
+            self.prepare(sample_hz);
+        }
+        for frame in buffer.iter_mut() {
+            let mut band = *frame;
+            for filter in &mut self.high_passes {
+                band = filter.high_pass(band);
+            }
+            if let Some(filter) = &mut self.low_pass {
+                band = filter.low_pass(band);
+            }
+            for filter in &mut self.all_passes {
+                band = filter.all_pass(band);
+            }
+            *frame = band;
+        }
+    }
+
+    fn channels_changed(&mut self, _channels: usize) {
+        self.reset();
+    }
+}
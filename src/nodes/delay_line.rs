@@ -0,0 +1,85 @@
+//! A multi-channel delay line with fractional delay, shared by the comb and allpass nodes.
+
+/// A circular buffer holding the most recent frames written to it, read at a fractional delay via
+/// linear interpolation.
+#[derive(Clone, Debug)]
+pub(crate) struct DelayLine {
+    /// The samples of each frame, interleaved by channel.
+    buffer: Vec<f64>,
+    /// The number of frames that the buffer holds.
+    len: usize,
+    channels: usize,
+    /// The frame at which the next frame is written.
+    pos: usize,
+}
+
+impl DelayLine {
+    /// A delay line able to delay by up to `max_delay` frames.
+    pub fn new(max_delay: usize) -> Self {
+        DelayLine {
+            buffer: Vec::new(),
+            // One more frame than the delay, so that the oldest frame is still available once the
+            // current frame has been written.
+            len: max_delay + 1,
+            channels: 0,
+            pos: 0,
+        }
+    }
+
+    /// The longest delay in frames.
+    pub fn max_delay(&self) -> usize {
+        self.len - 1
+    }
+
+    /// Ensure that the buffer holds frames of the given number of channels, clearing it if the
+    /// number changed.
+    pub fn prepare(&mut self, channels: usize) {
+        if self.channels != channels {
+            self.channels = channels;
+            self.buffer.clear();
+            self.buffer.resize(self.len * channels, 0.0);
+            self.pos = 0;
+        }
+    }
+
+    /// Clear the delay line.
+    pub fn reset(&mut self) {
+        for s in &mut self.buffer {
+            *s = 0.0;
+        }
+    }
+
+    /// The sample of the given channel written `delay` frames before the next frame, which is
+    /// clamped to the range from `1` to `max_delay` frames.
+    #[inline]
+    pub fn read(&self, channel: usize, delay: f64) -> f64 {
+        let delay = delay.max(1.0).min(self.max_delay() as f64);
+        let whole = delay.floor();
+        let frac = delay - whole;
+        let a = self.sample(channel, whole as usize);
+        if frac == 0.0 {
+            return a;
+        }
+        let b = self.sample(channel, whole as usize + 1);
+        a + (b - a) * frac
+    }
+
+    /// Write the sample of the given channel for the next frame.
+    #[inline]
+    pub fn write(&mut self, channel: usize, sample: f64) {
+        self.buffer[self.pos * self.channels + channel] = sample;
+    }
+
+    /// Move on to the next frame once all of its channels have been written.
+    #[inline]
+    pub fn advance(&mut self) {
+        self.pos = (self.pos + 1) % self.len;
+    }
+
+    /// The sample of the given channel written `delay` whole frames before the next frame.
+    #[inline]
+    fn sample(&self, channel: usize, delay: usize) -> f64 {
+        let frame = (self.pos + self.len - delay.min(self.len - 1)) % self.len;
+        self.buffer[frame * self.channels + channel]
+    }
+}
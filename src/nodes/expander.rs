@@ -0,0 +1,173 @@
+//! Downward expansion with hysteresis and an optional key (sidechain) input.
+
+use crate::node::Node;
+use dasp::{Frame, Sample};
+
+/// A downward expander that attenuates signals falling below a threshold.
+///
+/// Below the threshold, every decibel that the key signal falls is expanded to `ratio` decibels
+/// of output, down to at most `range_db` of attenuation. With a high ratio this behaves like a
+/// gate, while a low ratio gently reduces noise between hits or phrases.
+///
+/// Once the expander has opened, it only closes again after the key signal falls a further
+/// `hysteresis_db` below the threshold. This prevents chattering when the signal hovers around
+/// the threshold.
+///
+/// When used as a **Node**, the expander is keyed by its own input. Use
+/// [`process`](./struct.Expander.html#method.process) to key it from another signal.
+#[derive(Clone, Debug)]
+pub struct Expander {
+    /// The level in decibels below which the signal is attenuated.
+    pub threshold_db: f32,
+    /// The expansion ratio. `1.0` has no effect, while large ratios behave like a gate.
+    pub ratio: f32,
+    /// The maximum attenuation in decibels.
+    pub range_db: f32,
+    /// How far below the threshold in decibels the key must fall before the expander closes.
+    pub hysteresis_db: f32,
+    /// The time in milliseconds taken to open.
+    pub attack_ms: f32,
+    /// The time in milliseconds taken to close.
+    pub release_ms: f32,
+    /// The peak level of the key signal.
+    envelope: f32,
+    /// The current gain in decibels.
+    gain_db: f32,
+    /// Whether the level has risen above the threshold since last falling below the hysteresis.
+    open: bool,
+}
+
+impl Expander {
+    /// A new expander with the given threshold in decibels and ratio.
+    ///
+    /// The range defaults to 80dB, hysteresis to 3dB, attack to 1ms and release to 100ms.
+    pub fn new(threshold_db: f32, ratio: f32) -> Self {
+        Expander {
+            threshold_db,
+            ratio,
+            range_db: 80.0,
+            hysteresis_db: 3.0,
+            attack_ms: 1.0,
+            release_ms: 100.0,
+            envelope: 0.0,
+            gain_db: 0.0,
+            open: false,
+        }
+    }
+
+    /// The gain in decibels currently applied by the expander.
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Whether or not the expander is currently open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Reset the detector and gain to their initial state.
+    pub fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.gain_db = 0.0;
+        self.open = false;
+    }
+
+    /// Expand the `buffer` in place using the level of the `key` signal.
+    ///
+    /// **Panics** if `key` and `buffer` differ in length.
+    pub fn process<F>(&mut self, buffer: &mut [F], key: &[F], sample_hz: f64)
+    where
+        F: Frame,
+    {
+        assert_eq!(buffer.len(), key.len());
+        let attack = coefficient(self.attack_ms, sample_hz);
+        let release = coefficient(self.release_ms, sample_hz);
+        for (frame, key_frame) in buffer.iter_mut().zip(key) {
+            let gain = self.next_gain(peak(key_frame), attack, release);
+            *frame = frame.scale_amp(gain.to_sample());
+        }
+    }
+
+    /// Advance the detector by one frame with the given key level, returning the linear gain.
+    fn next_gain(&mut self, level: f32, attack: f32, release: f32) -> f32 {
+        // Follow peaks instantly and decay with the release time.
+        self.envelope = if level > self.envelope {
+            level
+        } else {
+            level + release * (self.envelope - level)
+        };
+
+        let level_db = to_db(self.envelope);
+        if level_db >= self.threshold_db {
+            self.open = true;
+        } else if level_db < self.threshold_db - self.hysteresis_db {
+            self.open = false;
+        }
+
+        let target_db = if self.open {
+            0.0
+        } else {
+            let below = (level_db - self.threshold_db).min(0.0);
+            (below * (self.ratio - 1.0)).max(-self.range_db.abs())
+        };
+
+        let coef = if target_db > self.gain_db {
+            attack
+        } else {
+            release
+        };
+        self.gain_db = target_db + coef * (self.gain_db - target_db);
+        from_db(self.gain_db)
+    }
+}
+
+impl Default for Expander {
+    fn default() -> Self {
+        Expander::new(-40.0, 2.0)
+    }
+}
+
+impl<F> Node<F> for Expander
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        let attack = coefficient(self.attack_ms, sample_hz);
+        let release = coefficient(self.release_ms, sample_hz);
+        for frame in buffer.iter_mut() {
+            let gain = self.next_gain(peak(frame), attack, release);
+            *frame = frame.scale_amp(gain.to_sample());
+        }
+    }
+}
+
+/// The one-pole smoothing coefficient for the given time in milliseconds.
+pub(crate) fn coefficient(ms: f32, sample_hz: f64) -> f32 {
+    let frames = ms as f64 * 0.001 * sample_hz;
+    if frames <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / frames).exp() as f32
+    }
+}
+
+/// The absolute peak across all channels of the given frame.
+pub(crate) fn peak<F>(frame: &F) -> f32
+where
+    F: Frame,
+{
+    frame
+        .channels()
+        .map(|s| s.to_float_sample().to_sample::<f32>().abs())
+        .fold(0.0, f32::max)
+}
+
+/// Convert the linear amplitude to decibels.
+pub(crate) fn to_db(amp: f32) -> f32 {
+    20.0 * amp.max(1e-9).log10()
+}
+
+/// Convert decibels to a linear amplitude.
+pub(crate) fn from_db(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
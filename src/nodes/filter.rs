@@ -0,0 +1,280 @@
+//! Filter building blocks shared by the nodes in this module.
+
+#[cfg(feature = "filters")]
+use dasp::Frame;
+use dasp::Sample;
+use std::f64::consts::PI;
+
+/// The Q of a second order Butterworth filter.
+#[cfg(feature = "filters")]
+pub(crate) const BUTTERWORTH_Q: f64 = std::f64::consts::FRAC_1_SQRT_2;
+
+/// The coefficients of a second order IIR filter, normalised so that `a0` is `1`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Coefficients {
+    pub b0: f64,
+    pub b1: f64,
+    pub b2: f64,
+    pub a1: f64,
+    pub a2: f64,
+}
+
+impl Coefficients {
+    /// A second order low-pass filter with the given cutoff and Q.
+    #[cfg(feature = "filters")]
+    pub fn low_pass(hz: f64, q: f64, sample_hz: f64) -> Self {
+        let (cos, alpha) = cos_alpha(hz, q, sample_hz);
+        Self::normalise(
+            (1.0 - cos) / 2.0,
+            1.0 - cos,
+            (1.0 - cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    /// A second order high-pass filter with the given cutoff and Q.
+    #[cfg(feature = "filters")]
+    pub fn high_pass(hz: f64, q: f64, sample_hz: f64) -> Self {
+        let (cos, alpha) = cos_alpha(hz, q, sample_hz);
+        Self::normalise(
+            (1.0 + cos) / 2.0,
+            -(1.0 + cos),
+            (1.0 + cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    /// A second order peaking filter boosting or cutting by `gain_db` around the given centre
+    /// frequency, with the bandwidth set by Q.
+    #[cfg(feature = "filters")]
+    pub fn peaking(hz: f64, q: f64, gain_db: f64, sample_hz: f64) -> Self {
+        let (cos, alpha) = cos_alpha(hz, q, sample_hz);
+        let a = 10.0f64.powf(gain_db / 40.0);
+        Self::normalise(
+            1.0 + alpha * a,
+            -2.0 * cos,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos,
+            1.0 - alpha / a,
+        )
+    }
+
+    /// The high shelf stage of the K-weighting filter used for loudness measurement
+    /// (ITU-R BS.1770), designed for the given sample rate.
+    pub fn k_weighting_shelf(sample_hz: f64) -> Self {
+        let hz = 1681.974450955533;
+        let gain_db = 3.999843853973347;
+        let q = 0.7071752369554196;
+        let k = (PI * hz / sample_hz).tan();
+        let vh = 10.0f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        Self::normalise(
+            vh + vb * k / q + k * k,
+            2.0 * (k * k - vh),
+            vh - vb * k / q + k * k,
+            1.0 + k / q + k * k,
+            2.0 * (k * k - 1.0),
+            1.0 - k / q + k * k,
+        )
+    }
+
+    /// The high-pass stage of the K-weighting filter used for loudness measurement
+    /// (ITU-R BS.1770), designed for the given sample rate.
+    pub fn k_weighting_high_pass(sample_hz: f64) -> Self {
+        let hz = 38.13547087602444;
+        let q = 0.5003270373238773;
+        let k = (PI * hz / sample_hz).tan();
+        // Unlike the other designs, the feed-forward coefficients are not normalised.
+        let a0 = 1.0 + k / q + k * k;
+        Self::normalise(
+            a0,
+            -2.0 * a0,
+            a0,
+            a0,
+            2.0 * (k * k - 1.0),
+            1.0 - k / q + k * k,
+        )
+    }
+
+    fn normalise(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        Coefficients {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// The cosine of the normalised angular frequency and the bandwidth term `alpha` used by the
+/// second order filter designs.
+#[cfg(feature = "filters")]
+fn cos_alpha(hz: f64, q: f64, sample_hz: f64) -> (f64, f64) {
+    let hz = hz.max(1.0).min(sample_hz * 0.49);
+    let w0 = 2.0 * PI * hz / sample_hz;
+    (w0.cos(), w0.sin() / (2.0 * q))
+}
+
+/// A second order IIR filter with independent state for each channel.
+#[derive(Clone, Debug)]
+pub(crate) struct Biquad {
+    pub coefficients: Coefficients,
+    /// The transposed direct form II state of each channel.
+    state: Vec<[f64; 2]>,
+}
+
+impl Biquad {
+    /// A filter with the given coefficients.
+    pub fn new(coefficients: Coefficients) -> Self {
+        Biquad {
+            coefficients,
+            state: Vec::new(),
+        }
+    }
+
+    /// Clear the filter's state.
+    #[cfg(feature = "filters")]
+    pub fn reset(&mut self) {
+        for state in &mut self.state {
+            *state = [0.0; 2];
+        }
+    }
+
+    /// Filter a single sample of the given channel.
+    #[inline]
+    pub fn process_sample(&mut self, channel: usize, x: f64) -> f64 {
+        if self.state.len() <= channel {
+            self.state.resize(channel + 1, [0.0; 2]);
+        }
+        let c = &self.coefficients;
+        let z = &mut self.state[channel];
+        let y = c.b0 * x + z[0];
+        z[0] = c.b1 * x - c.a1 * y + z[1];
+        z[1] = c.b2 * x - c.a2 * y;
+        y
+    }
+
+    /// Filter each channel of the given frame.
+    #[cfg(feature = "filters")]
+    #[inline]
+    pub fn process<F>(&mut self, frame: F) -> F
+    where
+        F: Frame,
+    {
+        F::from_fn(|ch| {
+            let x = to_f64(frame.channel(ch).copied().unwrap_or(F::Sample::EQUILIBRIUM));
+            from_f64(self.process_sample(ch, x))
+        })
+    }
+}
+
+/// A fourth order Linkwitz-Riley crossover, splitting a signal into a low and a high band that
+/// sum back to an all-pass response.
+#[cfg(feature = "filters")]
+#[derive(Clone, Debug)]
+pub(crate) struct LinkwitzRiley {
+    low: [Biquad; 2],
+    high: [Biquad; 2],
+}
+
+#[cfg(feature = "filters")]
+impl LinkwitzRiley {
+    /// A crossover at the given frequency.
+    pub fn new(hz: f64, sample_hz: f64) -> Self {
+        let low = Coefficients::low_pass(hz, BUTTERWORTH_Q, sample_hz);
+        let high = Coefficients::high_pass(hz, BUTTERWORTH_Q, sample_hz);
+        LinkwitzRiley {
+            low: [Biquad::new(low), Biquad::new(low)],
+            high: [Biquad::new(high), Biquad::new(high)],
+        }
+    }
+
+    /// Update the crossover frequency, preserving the filter state.
+    pub fn set_frequency(&mut self, hz: f64, sample_hz: f64) {
+        let low = Coefficients::low_pass(hz, BUTTERWORTH_Q, sample_hz);
+        let high = Coefficients::high_pass(hz, BUTTERWORTH_Q, sample_hz);
+        for filter in &mut self.low {
+            filter.coefficients = low;
+        }
+        for filter in &mut self.high {
+            filter.coefficients = high;
+        }
+    }
+
+    /// Clear the crossover's state.
+    pub fn reset(&mut self) {
+        for filter in self.low.iter_mut().chain(self.high.iter_mut()) {
+            filter.reset();
+        }
+    }
+
+    /// Split the given frame into its low and high bands.
+    #[inline]
+    pub fn split<F>(&mut self, frame: F) -> (F, F)
+    where
+        F: Frame,
+    {
+        let low = self.low[0].process(frame);
+        let low = self.low[1].process(low);
+        let high = self.high[0].process(frame);
+        let high = self.high[1].process(high);
+        (low, high)
+    }
+
+    /// The low band of the given frame, leaving the state of the high band untouched.
+    #[inline]
+    pub fn low_pass<F>(&mut self, frame: F) -> F
+    where
+        F: Frame,
+    {
+        let low = self.low[0].process(frame);
+        self.low[1].process(low)
+    }
+
+    /// The high band of the given frame, leaving the state of the low band untouched.
+    #[inline]
+    pub fn high_pass<F>(&mut self, frame: F) -> F
+    where
+        F: Frame,
+    {
+        let high = self.high[0].process(frame);
+        self.high[1].process(high)
+    }
+
+    /// Apply the crossover's all-pass response to the given frame without splitting it.
+    ///
+    /// This aligns the phase of a signal with one that has been split and recombined by a
+    /// crossover at the same frequency.
+    #[inline]
+    pub fn all_pass<F>(&mut self, frame: F) -> F
+    where
+        F: Frame,
+    {
+        let (low, high) = self.split(frame);
+        low.add_amp(high.to_signed_frame())
+    }
+}
+
+/// Convert the sample to an `f64` for filtering.
+#[inline]
+pub(crate) fn to_f64<S>(sample: S) -> f64
+where
+    S: Sample,
+{
+    sample.to_float_sample().to_sample::<f64>()
+}
+
+/// Convert the filtered `f64` back to a sample.
+#[inline]
+pub(crate) fn from_f64<S>(sample: f64) -> S
+where
+    S: Sample,
+{
+    sample.to_sample::<S::Float>().to_sample::<S>()
+}
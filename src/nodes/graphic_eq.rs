@@ -0,0 +1,154 @@
+//! Graphic equalisation with fixed ISO bands.
+
+use super::filter::{Biquad, Coefficients};
+use crate::event::Event;
+use crate::node::Node;
+use dasp::Frame;
+
+/// The ISO centre frequencies of the octave bands.
+const OCTAVE_HZ: [f64; 10] = [
+    31.5, 63.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// The ISO centre frequencies of the third-octave bands.
+const THIRD_OCTAVE_HZ: [f64; 31] = [
+    20.0, 25.0, 31.5, 40.0, 50.0, 63.0, 80.0, 100.0, 125.0, 160.0, 200.0, 250.0, 315.0, 400.0,
+    500.0, 630.0, 800.0, 1000.0, 1250.0, 1600.0, 2000.0, 2500.0, 3150.0, 4000.0, 5000.0, 6300.0,
+    8000.0, 10000.0, 12500.0, 16000.0, 20000.0,
+];
+
+/// A graphic equaliser boosting or cutting each of a fixed set of bands.
+///
+/// Each band is a peaking filter centred on one of the ISO octave or third-octave frequencies,
+/// with a bandwidth of one octave or a third of an octave respectively, and the bands are applied
+/// in series. All bands start flat at `0.0` dB.
+///
+/// The gain of each band may also be set via `Event::Param`, where `param` is the index of the
+/// band from the lowest to the highest and `value` is the gain in decibels.
+#[derive(Clone, Debug)]
+pub struct GraphicEq {
+    frequencies: &'static [f64],
+    q: f64,
+    gains_db: Vec<f32>,
+    filters: Vec<Biquad>,
+    /// The sample rate for which the filters were designed.
+    sample_hz: f64,
+    /// Whether a gain changed since the filters were designed.
+    dirty: bool,
+}
+
+impl GraphicEq {
+    /// A 10-band equaliser with a band on each ISO octave from 31.5hz to 16khz.
+    pub fn octave() -> Self {
+        Self::with_bands(&OCTAVE_HZ, 1.0)
+    }
+
+    /// A 31-band equaliser with a band on each ISO third-octave from 20hz to 20khz.
+    pub fn third_octave() -> Self {
+        Self::with_bands(&THIRD_OCTAVE_HZ, 1.0 / 3.0)
+    }
+
+    fn with_bands(frequencies: &'static [f64], octaves: f64) -> Self {
+        // The Q of a peaking filter spanning the given number of octaves.
+        let width = 2.0f64.powf(octaves);
+        let q = width.sqrt() / (width - 1.0);
+        // The filters are designed for the sample rate on the first request for audio.
+        let flat = Coefficients::peaking(1000.0, q, 0.0, 44_100.0);
+        GraphicEq {
+            frequencies,
+            q,
+            gains_db: vec![0.0; frequencies.len()],
+            filters: vec![Biquad::new(flat); frequencies.len()],
+            sample_hz: 0.0,
+            dirty: true,
+        }
+    }
+
+    /// The number of bands.
+    pub fn num_bands(&self) -> usize {
+        self.frequencies.len()
+    }
+
+    /// The centre frequency of each band in hz, from the lowest band to the highest.
+    pub fn frequencies(&self) -> &[f64] {
+        self.frequencies
+    }
+
+    /// The gain of each band in decibels, from the lowest band to the highest.
+    pub fn gains_db(&self) -> &[f32] {
+        &self.gains_db
+    }
+
+    /// The gain of the given band in decibels, or `None` if there is no such band.
+    pub fn gain_db(&self, band: usize) -> Option<f32> {
+        self.gains_db.get(band).cloned()
+    }
+
+    /// Set the gain of the given band in decibels.
+    ///
+    /// Does nothing if there is no such band.
+    pub fn set_gain_db(&mut self, band: usize, gain_db: f32) {
+        if let Some(gain) = self.gains_db.get_mut(band) {
+            if *gain != gain_db {
+                *gain = gain_db;
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Set every band back to `0.0` dB.
+    pub fn flatten(&mut self) {
+        for band in 0..self.gains_db.len() {
+            self.set_gain_db(band, 0.0);
+        }
+    }
+
+    /// Clear the state of every filter.
+    pub fn reset(&mut self) {
+        for filter in &mut self.filters {
+            filter.reset();
+        }
+    }
+
+    /// Design the filter of each band for the current gains at the given sample rate.
+    fn prepare(&mut self, sample_hz: f64) {
+        let bands = self.frequencies.iter().zip(&self.gains_db);
+        for (filter, (&hz, &gain_db)) in self.filters.iter_mut().zip(bands) {
+            filter.coefficients = Coefficients::peaking(hz, self.q, gain_db as f64, sample_hz);
+            // Flat bands are skipped, so clear any stale state for when they are next used.
+            if gain_db == 0.0 {
+                filter.reset();
+            }
+        }
+        self.sample_hz = sample_hz;
+        self.dirty = false;
+    }
+}
+
+impl<F> Node<F> for GraphicEq
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        if self.dirty || self.sample_hz != sample_hz {
+            self.prepare(sample_hz);
+        }
+        // Flat bands have no effect, so they are skipped.
+        let bands = self.filters.iter_mut().zip(&self.gains_db);
+        for (filter, _) in bands.filter(|&(_, &gain_db)| gain_db != 0.0) {
+            for frame in buffer.iter_mut() {
+                *frame = filter.process(*frame);
+            }
+        }
+    }
+
+    fn channels_changed(&mut self, _channels: usize) {
+        self.reset();
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { param, value } = *event {
+            self.set_gain_db(param, value);
+        }
+    }
+}
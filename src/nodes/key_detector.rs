@@ -0,0 +1,382 @@
+//! Estimating the key and the current chord of a signal from its chroma.
+
+use super::tuner::NOTE_NAMES;
+use crate::analysis::fft;
+use crate::node::Node;
+use dasp::{Frame, Sample};
+use std::sync::mpsc;
+
+/// The number of events that may be queued for a **KeyEvents** receiver before further events
+/// are dropped.
+const KEY_EVENT_CAPACITY: usize = 64;
+
+/// The range of frequencies in hz whose energy contributes to the chroma.
+const MIN_HZ: f64 = 55.0;
+const MAX_HZ: f64 = 4000.0;
+
+/// The chroma energy below which the signal is considered silent and no estimate is made.
+const MIN_ENERGY: f64 = 1e-8;
+
+/// The Krumhansl-Kessler key profiles, starting from the tonic.
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+/// Estimates the key and the current chord of the mono sum of its input from the chroma (the
+/// energy of each of the twelve pitch classes) of its spectrum, passing the signal through
+/// unchanged.
+///
+/// The spectrum is measured over the last `fft_size` frames every `hop_size` frames. The chord
+/// is matched against the major and minor triads using the chroma of the latest spectrum, while
+/// the key is matched against the Krumhansl-Kessler key profiles using the chroma integrated over
+/// `key_integration_secs`.
+///
+/// Whenever either estimate changes, a **KeyEvent** is published to the **KeyEvents** receiver
+/// returned by `new`, e.g. to drive a generative accompaniment.
+#[derive(Debug)]
+pub struct KeyDetector {
+    /// The time in seconds over which the chroma is integrated to estimate the key.
+    pub key_integration_secs: f64,
+    fft_size: usize,
+    hop_size: usize,
+    /// The most recent `fft_size` frames of the mono input.
+    input: Vec<f64>,
+    /// The position in `input` at which the next frame is written.
+    input_pos: usize,
+    /// The number of frames since the spectrum was last measured.
+    since_hop: usize,
+    /// The number of frames processed since the detector was created or reset.
+    position: u64,
+    /// The input in chronological order, ready to be transformed.
+    window: Vec<f64>,
+    re: Vec<f64>,
+    im: Vec<f64>,
+    magnitudes: Vec<f64>,
+    /// The chroma integrated over `key_integration_secs`.
+    key_chroma: [f64; 12],
+    key: Option<Key>,
+    chord: Option<Chord>,
+    sender: mpsc::SyncSender<KeyEvent>,
+}
+
+/// The receiving end of a **KeyDetector**'s events.
+#[derive(Debug)]
+pub struct KeyEvents {
+    receiver: mpsc::Receiver<KeyEvent>,
+}
+
+/// Whether a key or a triad is major or minor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// A major key or triad.
+    Major,
+    /// A minor key or triad.
+    Minor,
+}
+
+/// A musical key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    /// The pitch class of the tonic, from `0` (C) to `11` (B).
+    pub tonic: u8,
+    /// Whether the key is major or minor.
+    pub mode: Mode,
+}
+
+/// A major or minor triad.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Chord {
+    /// The pitch class of the root, from `0` (C) to `11` (B).
+    pub root: u8,
+    /// Whether the triad is major or minor.
+    pub mode: Mode,
+}
+
+/// A change in the estimate of a **KeyDetector**.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KeyEvent {
+    /// The estimated key changed.
+    Key {
+        /// The number of frames processed by the detector when the change was detected.
+        position: u64,
+        /// The new key.
+        key: Key,
+        /// The correlation between the chroma and the key's profile, from `-1.0` to `1.0`.
+        confidence: f32,
+    },
+    /// The estimated chord changed.
+    Chord {
+        /// The number of frames processed by the detector when the change was detected.
+        position: u64,
+        /// The new chord.
+        chord: Chord,
+        /// The similarity between the chroma and the chord's triad, from `0.0` to `1.0`.
+        confidence: f32,
+    },
+}
+
+impl KeyDetector {
+    /// A detector measuring a spectrum of `fft_size` frames every `hop_size` frames, along with
+    /// the receiver of its events.
+    ///
+    /// `fft_size` is rounded up to the next power of two. At 44.1khz, an `fft_size` of 8192
+    /// resolves the pitch classes down to around 100hz. The key is estimated over 10 seconds by
+    /// default.
+    pub fn new(fft_size: usize, hop_size: usize) -> (Self, KeyEvents) {
+        let fft_size = fft_size.max(2).next_power_of_two();
+        let (sender, receiver) = mpsc::sync_channel(KEY_EVENT_CAPACITY);
+        let detector = KeyDetector {
+            key_integration_secs: 10.0,
+            fft_size,
+            hop_size: hop_size.max(1),
+            input: vec![0.0; fft_size],
+            input_pos: 0,
+            since_hop: 0,
+            position: 0,
+            window: Vec::with_capacity(fft_size),
+            re: vec![0.0; fft_size],
+            im: vec![0.0; fft_size],
+            magnitudes: Vec::with_capacity(fft_size / 2 + 1),
+            key_chroma: [0.0; 12],
+            key: None,
+            chord: None,
+            sender,
+        };
+        (detector, KeyEvents { receiver })
+    }
+
+    /// The number of frames transformed for each spectrum.
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// The number of frames between consecutive spectra.
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    /// The estimated key, if any.
+    pub fn key(&self) -> Option<Key> {
+        self.key
+    }
+
+    /// The estimated chord, if any.
+    pub fn chord(&self) -> Option<Chord> {
+        self.chord
+    }
+
+    /// Clear the input and the estimates.
+    pub fn reset(&mut self) {
+        for s in &mut self.input {
+            *s = 0.0;
+        }
+        self.input_pos = 0;
+        self.since_hop = 0;
+        self.position = 0;
+        self.key_chroma = [0.0; 12];
+        self.key = None;
+        self.chord = None;
+    }
+
+    /// Analyse the given frames.
+    pub fn process<F>(&mut self, buffer: &[F], sample_hz: f64)
+    where
+        F: Frame,
+    {
+        for frame in buffer {
+            let sum = frame
+                .channels()
+                .map(|s| s.to_float_sample().to_sample::<f64>())
+                .sum::<f64>();
+            self.input[self.input_pos] = sum / F::CHANNELS as f64;
+            self.input_pos = (self.input_pos + 1) % self.fft_size;
+            self.position += 1;
+            self.since_hop += 1;
+            if self.since_hop >= self.hop_size {
+                self.since_hop = 0;
+                self.measure(sample_hz);
+            }
+        }
+    }
+
+    /// Measure the chroma of the most recent input and update the estimates.
+    fn measure(&mut self, sample_hz: f64) {
+        self.window.clear();
+        self.window.extend_from_slice(&self.input[self.input_pos..]);
+        self.window.extend_from_slice(&self.input[..self.input_pos]);
+        fft::magnitude_spectrum_with(
+            &self.window,
+            self.fft_size,
+            &mut self.re,
+            &mut self.im,
+            &mut self.magnitudes,
+        );
+        let chroma = chroma(&self.magnitudes, sample_hz / self.fft_size as f64);
+        if chroma.iter().sum::<f64>() < MIN_ENERGY {
+            return;
+        }
+
+        let hops = self.key_integration_secs * sample_hz / self.hop_size as f64;
+        let coeff = if hops > 1.0 { 1.0 / hops } else { 1.0 };
+        for (integrated, &energy) in self.key_chroma.iter_mut().zip(&chroma) {
+            *integrated += (energy - *integrated) * coeff;
+        }
+
+        let (chord, confidence) = match_chord(&chroma);
+        if self.chord != Some(chord) {
+            self.chord = Some(chord);
+            self.publish(KeyEvent::Chord {
+                position: self.position,
+                chord,
+                confidence,
+            });
+        }
+        let (key, confidence) = match_key(&self.key_chroma);
+        if self.key != Some(key) {
+            self.key = Some(key);
+            self.publish(KeyEvent::Key {
+                position: self.position,
+                key,
+                confidence,
+            });
+        }
+    }
+
+    /// Send the given event without blocking, dropping it if the receiver has fallen behind.
+    fn publish(&self, event: KeyEvent) {
+        let _ = self.sender.try_send(event);
+    }
+}
+
+impl KeyEvents {
+    /// Yield all events published since this was last called, without blocking.
+    pub fn try_iter(&self) -> mpsc::TryIter<'_, KeyEvent> {
+        self.receiver.try_iter()
+    }
+}
+
+impl Key {
+    /// The name of the tonic, e.g. `"F#"`.
+    pub fn name(&self) -> &'static str {
+        NOTE_NAMES[self.tonic as usize % 12]
+    }
+}
+
+impl Chord {
+    /// The name of the root, e.g. `"F#"`.
+    pub fn name(&self) -> &'static str {
+        NOTE_NAMES[self.root as usize % 12]
+    }
+
+    /// The pitch classes of the root, third and fifth.
+    pub fn pitch_classes(&self) -> [u8; 3] {
+        let third = match self.mode {
+            Mode::Major => 4,
+            Mode::Minor => 3,
+        };
+        [self.root, (self.root + third) % 12, (self.root + 7) % 12]
+    }
+}
+
+/// The energy of each pitch class, starting from C, of the given magnitude spectrum.
+fn chroma(magnitudes: &[f64], bin_hz: f64) -> [f64; 12] {
+    let mut chroma = [0.0; 12];
+    for (bin, &magnitude) in magnitudes.iter().enumerate().skip(1) {
+        let hz = bin as f64 * bin_hz;
+        if !(MIN_HZ..=MAX_HZ).contains(&hz) {
+            continue;
+        }
+        // The nearest MIDI note, where A4 at 440hz is note 69.
+        let note = (12.0 * (hz / 440.0).log2()).round() as i32 + 69;
+        chroma[note.rem_euclid(12) as usize] += magnitude * magnitude;
+    }
+    chroma
+}
+
+/// The triad whose pitch classes best match the given chroma, along with their cosine
+/// similarity.
+fn match_chord(chroma: &[f64; 12]) -> (Chord, f32) {
+    let norm = chroma.iter().map(|e| e * e).sum::<f64>().sqrt();
+    let mut best = (
+        Chord {
+            root: 0,
+            mode: Mode::Major,
+        },
+        f64::MIN,
+    );
+    for root in 0..12 {
+        for &mode in &[Mode::Major, Mode::Minor] {
+            let chord = Chord { root, mode };
+            let energy: f64 = chord
+                .pitch_classes()
+                .iter()
+                .map(|&pc| chroma[pc as usize])
+                .sum();
+            // The cosine similarity with a template of three equal pitch classes.
+            let similarity = energy / (norm * 3f64.sqrt());
+            if similarity > best.1 {
+                best = (chord, similarity);
+            }
+        }
+    }
+    (best.0, best.1 as f32)
+}
+
+/// The key whose profile best correlates with the given chroma, along with the correlation.
+fn match_key(chroma: &[f64; 12]) -> (Key, f32) {
+    let mut best = (
+        Key {
+            tonic: 0,
+            mode: Mode::Major,
+        },
+        f64::MIN,
+    );
+    for tonic in 0..12 {
+        for &(mode, profile) in &[(Mode::Major, &MAJOR_PROFILE), (Mode::Minor, &MINOR_PROFILE)] {
+            let rotated = |pc: usize| profile[(pc + 12 - tonic as usize) % 12];
+            let correlation = correlation(chroma, rotated);
+            if correlation > best.1 {
+                best = (Key { tonic, mode }, correlation);
+            }
+        }
+    }
+    (best.0, best.1 as f32)
+}
+
+/// The Pearson correlation between the given chroma and profile.
+fn correlation<P>(chroma: &[f64; 12], profile: P) -> f64
+where
+    P: Fn(usize) -> f64,
+{
+    let mean_c = chroma.iter().sum::<f64>() / 12.0;
+    let mean_p = (0..12).map(&profile).sum::<f64>() / 12.0;
+    let (mut cov, mut var_c, mut var_p) = (0.0, 0.0, 0.0);
+    for (pc, &c) in chroma.iter().enumerate() {
+        let (dc, dp) = (c - mean_c, profile(pc) - mean_p);
+        cov += dc * dp;
+        var_c += dc * dc;
+        var_p += dp * dp;
+    }
+    let denominator = (var_c * var_p).sqrt();
+    if denominator > 0.0 {
+        cov / denominator
+    } else {
+        0.0
+    }
+}
+
+impl<F> Node<F> for KeyDetector
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        self.process(buffer, sample_hz);
+    }
+
+    fn always_render(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,195 @@
+//! A click track for counting in and keeping time.
+
+use crate::event::Event;
+use crate::graph::{CountIn, Tempo};
+use crate::node::Node;
+use dasp::{Frame, Sample};
+use std::f64::consts::PI;
+
+/// The duration of each click in seconds.
+const CLICK_SECS: f64 = 0.03;
+
+/// The pitch in hz of the click on the first beat of each bar.
+const ACCENT_HZ: f64 = 1_500.0;
+
+/// The pitch in hz of the click on every other beat.
+const BEAT_HZ: f64 = 1_000.0;
+
+/// Renders a click on each beat, with an accented click on the first beat of each bar.
+///
+/// The metronome is silent until it is started, at which point the first beat of a bar clicks
+/// immediately. It is started and stopped via `Event::Param`, so that the clicks line up with
+/// exact transport frames: [`Graph::start_count_in`](../struct.Graph.html#method.start_count_in)
+/// schedules these events for a count-in. The parameters may be set via `Event::Param`, where
+/// `param` is `BPM_PARAM` (`0`) for the tempo in beats per minute, `BEATS_PER_BAR_PARAM` (`1`)
+/// for the beats per bar, `LEVEL_PARAM` (`2`) for the level and `RUNNING_PARAM` (`3`) to start
+/// (a `value` of `1.0`) or stop (`0.0`) the metronome.
+///
+/// Changing the tempo while running takes effect from the next beat. While the **Graph** has a
+/// tempo map, the metronome follows it instead of `bpm`, clicking each time another beat of the
+/// map has passed since it was started.
+#[derive(Clone, Debug)]
+pub struct Metronome {
+    /// The tempo in beats per minute.
+    pub bpm: f64,
+    /// The number of beats per bar.
+    pub beats_per_bar: u32,
+    /// The amplitude of the clicks, from `0.0` to `1.0`.
+    pub level: f32,
+    running: bool,
+    /// The number of frames rendered since the metronome was started.
+    frame: u64,
+    /// The index of the next beat, counting from the start.
+    beat: u64,
+    /// The frame and beat from which the beats are counted at the current tempo.
+    anchor: (u64, u64),
+    /// The tempo at which the beats from `anchor` are counted.
+    anchor_bpm: f64,
+    /// The position within the current click and its pitch, if one is sounding.
+    click: Option<(usize, f64)>,
+    /// The tempo of the **Graph** over the buffer about to be rendered, if it has a tempo map.
+    tempo: Option<Tempo>,
+    /// The beat of the **Graph**'s tempo map at which the metronome was started.
+    start_beat: Option<f64>,
+}
+
+impl Metronome {
+    /// The parameter for the tempo in beats per minute.
+    pub const BPM_PARAM: usize = CountIn::BPM_PARAM;
+    /// The parameter for the number of beats per bar.
+    pub const BEATS_PER_BAR_PARAM: usize = CountIn::BEATS_PER_BAR_PARAM;
+    /// The parameter for the amplitude of the clicks.
+    pub const LEVEL_PARAM: usize = 2;
+    /// The parameter for starting (a `value` of `1.0`) or stopping (`0.0`) the metronome.
+    pub const RUNNING_PARAM: usize = CountIn::RUNNING_PARAM;
+
+    /// A stopped metronome at the given tempo and number of beats per bar.
+    pub fn new(bpm: f64, beats_per_bar: u32) -> Self {
+        Metronome {
+            bpm,
+            beats_per_bar,
+            level: 0.5,
+            running: false,
+            frame: 0,
+            beat: 0,
+            anchor: (0, 0),
+            anchor_bpm: bpm,
+            click: None,
+            tempo: None,
+            start_beat: None,
+        }
+    }
+
+    /// Whether the metronome is running.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Start the metronome, clicking the first beat of a bar with the next frame rendered.
+    pub fn start(&mut self) {
+        self.running = true;
+        self.frame = 0;
+        self.beat = 0;
+        self.anchor = (0, 0);
+        self.anchor_bpm = self.bpm;
+        self.start_beat = None;
+    }
+
+    /// Stop the metronome, letting the current click ring out.
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    /// Start the click of the next beat, accented on the first beat of each bar.
+    fn click_beat(&mut self) {
+        let accent = self
+            .beat
+            .is_multiple_of(u64::from(self.beats_per_bar.max(1)));
+        let hz = if accent { ACCENT_HZ } else { BEAT_HZ };
+        self.click = Some((0, hz));
+        self.beat += 1;
+    }
+
+    /// The frame since the start at which the next beat clicks.
+    fn next_beat_frame(&self, sample_hz: f64) -> u64 {
+        let (frame, beat) = self.anchor;
+        let beat_frames = 60.0 * sample_hz / self.anchor_bpm;
+        frame + ((self.beat - beat) as f64 * beat_frames).round() as u64
+    }
+}
+
+impl Default for Metronome {
+    fn default() -> Self {
+        Metronome::new(120.0, 4)
+    }
+}
+
+impl<F> Node<F> for Metronome
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        let click_len = (CLICK_SECS * sample_hz) as usize;
+        let tempo = self.tempo.take();
+        for (i, frame) in buffer.iter_mut().enumerate() {
+            if let (true, Some(tempo)) = (self.running, tempo) {
+                // Click on the frame nearest to each beat of the tempo map.
+                let beat = tempo.beat_at(i);
+                let half_frame = 0.5 / tempo.beat_frames_at(i);
+                let start = *self.start_beat.get_or_insert(beat);
+                if beat - start + half_frame >= self.beat as f64 {
+                    self.click_beat();
+                }
+            } else if self.running {
+                if self.anchor_bpm != self.bpm && self.bpm > 0.0 {
+                    // Count the remaining beats at the new tempo from the last beat.
+                    let last = self.beat.saturating_sub(1);
+                    let (anchor_frame, anchor_beat) = self.anchor;
+                    let beat_frames = 60.0 * sample_hz / self.anchor_bpm;
+                    let elapsed = ((last - anchor_beat) as f64 * beat_frames).round() as u64;
+                    self.anchor = (anchor_frame + elapsed, last);
+                    self.anchor_bpm = self.bpm;
+                }
+                if self.frame == self.next_beat_frame(sample_hz) {
+                    self.click_beat();
+                }
+                self.frame += 1;
+            }
+            let s = match self.click {
+                Some((i, hz)) if i < click_len => {
+                    self.click = Some((i + 1, hz));
+                    let t = i as f64 / sample_hz;
+                    let envelope = 1.0 - i as f64 / click_len as f64;
+                    (2.0 * PI * hz * t).sin() * envelope * envelope * self.level as f64
+                }
+                _ => {
+                    self.click = None;
+                    0.0
+                }
+            };
+            let s = s.to_sample::<<F::Sample as Sample>::Float>().to_sample();
+            *frame = F::from_fn(|_| s);
+        }
+    }
+
+    fn update_tempo(&mut self, tempo: &Tempo) {
+        self.tempo = Some(*tempo);
+    }
+
+    fn is_silent(&self) -> bool {
+        !self.running && self.click.is_none()
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { param, value } = *event {
+            match param {
+                Metronome::BPM_PARAM => self.bpm = value as f64,
+                Metronome::BEATS_PER_BAR_PARAM => self.beats_per_bar = value as u32,
+                Metronome::LEVEL_PARAM => self.level = value,
+                Metronome::RUNNING_PARAM if value >= 0.5 => self.start(),
+                Metronome::RUNNING_PARAM => self.stop(),
+                _ => (),
+            }
+        }
+    }
+}
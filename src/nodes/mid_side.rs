@@ -0,0 +1,110 @@
+//! Mid/side processing of stereo signals with a pair of mono nodes.
+
+use crate::node::Node;
+use dasp::Sample;
+
+/// Encodes a stereo signal to mid/side, processes the mid and side channels with two independent
+/// mono nodes and decodes the result back to stereo.
+///
+/// This turns any mono effect into a mid/side processor, e.g. for widening, side-only
+/// equalisation or compressing the centre of a mix.
+///
+/// The mid channel is `(left + right) / 2` and the side channel is `(left - right) / 2`, so that
+/// left and right are recovered exactly as `mid + side` and `mid - side`.
+#[derive(Clone, Debug)]
+pub struct MidSide<N, S = f32> {
+    mid: N,
+    side: N,
+    mid_buffer: Vec<[S; 1]>,
+    side_buffer: Vec<[S; 1]>,
+}
+
+impl<N, S> MidSide<N, S>
+where
+    S: Sample,
+{
+    /// Process the mid channel with `mid` and the side channel with `side`.
+    pub fn new(mid: N, side: N) -> Self {
+        MidSide {
+            mid,
+            side,
+            mid_buffer: Vec::new(),
+            side_buffer: Vec::new(),
+        }
+    }
+
+    /// The node processing the mid channel.
+    pub fn mid(&self) -> &N {
+        &self.mid
+    }
+
+    /// The node processing the mid channel.
+    pub fn mid_mut(&mut self) -> &mut N {
+        &mut self.mid
+    }
+
+    /// The node processing the side channel.
+    pub fn side(&self) -> &N {
+        &self.side
+    }
+
+    /// The node processing the side channel.
+    pub fn side_mut(&mut self) -> &mut N {
+        &mut self.side
+    }
+
+    /// Consume the wrapper, returning the mid and side nodes.
+    pub fn into_inner(self) -> (N, N) {
+        (self.mid, self.side)
+    }
+}
+
+impl<N, S> Node<[S; 2]> for MidSide<N, S>
+where
+    N: Node<[S; 1]>,
+    S: Sample,
+{
+    fn audio_requested(&mut self, buffer: &mut [[S; 2]], sample_hz: f64) {
+        let len = buffer.len();
+        if self.mid_buffer.len() != len {
+            self.mid_buffer.resize(len, [S::EQUILIBRIUM]);
+            self.side_buffer.resize(len, [S::EQUILIBRIUM]);
+        }
+
+        // Encode.
+        let half = 0.5.to_sample::<S::Float>();
+        for ((frame, mid), side) in buffer
+            .iter()
+            .zip(&mut self.mid_buffer)
+            .zip(&mut self.side_buffer)
+        {
+            let left = frame[0].to_float_sample();
+            let right = frame[1].to_float_sample();
+            mid[0] = ((left + right) * half).to_sample();
+            side[0] = ((left - right) * half).to_sample();
+        }
+
+        self.mid.audio_requested(&mut self.mid_buffer, sample_hz);
+        self.side.audio_requested(&mut self.side_buffer, sample_hz);
+
+        // Decode.
+        let encoded = self.mid_buffer.iter().zip(&self.side_buffer);
+        for (frame, (mid, side)) in buffer.iter_mut().zip(encoded) {
+            let mid = mid[0].to_float_sample();
+            let side = side[0].to_float_sample();
+            *frame = [(mid + side).to_sample(), (mid - side).to_sample()];
+        }
+    }
+
+    fn latency(&self) -> usize {
+        std::cmp::max(self.mid.latency(), self.side.latency())
+    }
+
+    fn tail_frames(&self) -> usize {
+        std::cmp::max(self.mid.tail_frames(), self.side.tail_frames())
+    }
+
+    fn is_silent(&self) -> bool {
+        self.mid.is_silent() && self.side.is_silent()
+    }
+}
@@ -0,0 +1,229 @@
+//! Multi-band processing with any effect node.
+
+use super::filter::LinkwitzRiley;
+use crate::buffer::{Scratch, ScratchHandle};
+use crate::graph::Tempo;
+use crate::node::Node;
+use dasp::{self, Frame};
+
+/// Splits the input into frequency bands, processes each band with its own copy of an inner node
+/// and sums the results.
+///
+/// The bands are split by fourth order Linkwitz-Riley crossovers. The lower bands are passed
+/// through the all-pass response of each higher crossover so that all bands are phase-aligned and
+/// sum back to a flat response when the inner nodes leave them unchanged. This generalises
+/// multi-band compression, saturation and so on over any effect.
+#[derive(Clone, Debug)]
+pub struct MultiBand<F, N> {
+    bands: Vec<N>,
+    /// The frequency of each crossover in ascending order.
+    crossovers: Vec<f64>,
+    /// The crossover splitting each band from the bands above it.
+    splitters: Vec<LinkwitzRiley>,
+    /// The all-pass filters aligning each band with the crossovers above it, indexed by
+    /// `[band][crossover - band - 1]`.
+    all_passes: Vec<Vec<LinkwitzRiley>>,
+    /// The sample rate for which the filters were designed.
+    sample_hz: f64,
+    /// The buffers for all bands.
+    scratch: Scratch<F>,
+    /// The handle to the buffer of each band.
+    buffers: Vec<ScratchHandle>,
+}
+
+impl<F, N> MultiBand<F, N>
+where
+    F: Frame,
+{
+    /// Split the input at the given crossover frequencies in hz, processing each of the
+    /// `crossovers.len() + 1` bands with a clone of `node`.
+    ///
+    /// The crossover frequencies are sorted into ascending order.
+    pub fn new(node: N, crossovers: &[f64]) -> Self
+    where
+        N: Clone,
+    {
+        let bands = vec![node; crossovers.len() + 1];
+        Self::from_bands(bands, crossovers)
+    }
+
+    /// Process each band with the given nodes, from the lowest band to the highest.
+    ///
+    /// **Panics** if there is not exactly one more node than there are crossovers.
+    pub fn from_bands(bands: Vec<N>, crossovers: &[f64]) -> Self {
+        assert_eq!(
+            bands.len(),
+            crossovers.len() + 1,
+            "there must be one more band than there are crossovers"
+        );
+        let mut crossovers = crossovers.to_vec();
+        crossovers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let num_bands = bands.len();
+        MultiBand {
+            bands,
+            crossovers,
+            splitters: Vec::new(),
+            all_passes: Vec::new(),
+            sample_hz: 0.0,
+            scratch: Scratch::new(),
+            buffers: Vec::with_capacity(num_bands),
+        }
+    }
+
+    /// The nodes processing each band, from the lowest band to the highest.
+    pub fn bands(&self) -> &[N] {
+        &self.bands
+    }
+
+    /// The nodes processing each band, from the lowest band to the highest.
+    ///
+    /// Use this to set the parameters of each band.
+    pub fn bands_mut(&mut self) -> &mut [N] {
+        &mut self.bands
+    }
+
+    /// The crossover frequencies in hz in ascending order.
+    pub fn crossovers(&self) -> &[f64] {
+        &self.crossovers
+    }
+
+    /// Move the crossover at the given index to the given frequency in hz.
+    ///
+    /// The frequency is clamped between its neighbouring crossovers.
+    ///
+    /// **Panics** if `index` is out of range.
+    pub fn set_crossover(&mut self, index: usize, hz: f64) {
+        let min = if index > 0 {
+            self.crossovers[index - 1]
+        } else {
+            0.0
+        };
+        let max = self.crossovers.get(index + 1).cloned().unwrap_or(f64::MAX);
+        self.crossovers[index] = hz.max(min).min(max);
+        if self.sample_hz > 0.0 {
+            self.update_filters();
+        }
+    }
+
+    /// Clear the state of all crossover filters.
+    pub fn reset(&mut self) {
+        let all_passes = self.all_passes.iter_mut().flat_map(|band| band.iter_mut());
+        for filter in self.splitters.iter_mut().chain(all_passes) {
+            filter.reset();
+        }
+    }
+
+    /// Consume the wrapper, returning the node for each band.
+    pub fn into_bands(self) -> Vec<N> {
+        self.bands
+    }
+
+    /// Rebuild all filters for the given sample rate.
+    fn prepare(&mut self, sample_hz: f64) {
+        self.sample_hz = sample_hz;
+        let num_crossovers = self.crossovers.len();
+        self.splitters = self
+            .crossovers
+            .iter()
+            .map(|&hz| LinkwitzRiley::new(hz, sample_hz))
+            .collect();
+        self.all_passes = (0..num_crossovers)
+            .map(|band| {
+                self.crossovers[band + 1..]
+                    .iter()
+                    .map(|&hz| LinkwitzRiley::new(hz, sample_hz))
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Update the frequencies of all filters, preserving their state.
+    fn update_filters(&mut self) {
+        let sample_hz = self.sample_hz;
+        for (splitter, &hz) in self.splitters.iter_mut().zip(&self.crossovers) {
+            splitter.set_frequency(hz, sample_hz);
+        }
+        for (band, all_passes) in self.all_passes.iter_mut().enumerate() {
+            for (all_pass, &hz) in all_passes.iter_mut().zip(&self.crossovers[band + 1..]) {
+                all_pass.set_frequency(hz, sample_hz);
+            }
+        }
+    }
+}
+
+impl<F, N> Node<F> for MultiBand<F, N>
+where
+    F: Frame,
+    N: Node<F>,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        if self.sample_hz != sample_hz {
+            self.prepare(sample_hz);
+        }
+        let len = buffer.len();
+        if self.scratch.len() != self.bands.len() || self.scratch[self.buffers[0]].len() != len {
+            self.scratch.clear();
+            self.scratch.reserve(len, self.bands.len());
+            self.buffers.clear();
+            for _ in 0..self.bands.len() {
+                self.buffers.push(self.scratch.alloc(len));
+            }
+        }
+
+        // Split the input into bands, aligning the phase of each band with the crossovers above
+        // it.
+        for (i, &frame) in buffer.iter().enumerate() {
+            let mut rest = frame;
+            for (k, splitter) in self.splitters.iter_mut().enumerate() {
+                let (low, high) = splitter.split(rest);
+                self.scratch[self.buffers[k]][i] = low;
+                rest = high;
+                for band in 0..k {
+                    let all_pass = &mut self.all_passes[band][k - band - 1];
+                    let band = &mut self.scratch[self.buffers[band]][i];
+                    *band = all_pass.all_pass(*band);
+                }
+            }
+            let last = self.buffers[self.buffers.len() - 1];
+            self.scratch[last][i] = rest;
+        }
+
+        // Process and sum the bands.
+        dasp::slice::equilibrium(buffer);
+        for (node, band) in self.bands.iter_mut().zip(self.scratch.iter_mut()) {
+            node.audio_requested(band, sample_hz);
+            dasp::slice::zip_map_in_place(buffer, band, |out, band| {
+                out.add_amp(band.to_signed_frame())
+            });
+        }
+    }
+
+    fn latency(&self) -> usize {
+        self.bands
+            .iter()
+            .map(|band| band.latency())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn tail_frames(&self) -> usize {
+        self.bands
+            .iter()
+            .map(|band| band.tail_frames())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn channels_changed(&mut self, channels: usize) {
+        self.reset();
+        for band in &mut self.bands {
+            band.channels_changed(channels);
+        }
+    }
+
+    fn update_tempo(&mut self, tempo: &Tempo) {
+        for band in &mut self.bands {
+            band.update_tempo(tempo);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Stereo correlation and goniometer metering.
+
+use crate::node::Node;
+use dasp::Sample;
+use std::f32::consts::FRAC_1_SQRT_2;
+
+/// Measures the correlation between the channels of a stereo signal and keeps its most recent
+/// frames as goniometer points, passing the signal through unchanged.
+///
+/// The correlation ranges from `1.0` for identical channels (mono), through `0.0` for unrelated
+/// channels, to `-1.0` for channels in opposite phase, which cancel when summed to mono. It is
+/// integrated over `integration_ms`.
+///
+/// Each goniometer point is the frame rotated by 45 degrees, so that mono material lies on the
+/// vertical axis and material in opposite phase lies on the horizontal axis.
+#[derive(Clone, Debug)]
+pub struct PhaseMeter {
+    /// The time in milliseconds over which the correlation is integrated.
+    pub integration_ms: f32,
+    /// The integrated product of the left and right channels.
+    left_right: f32,
+    /// The integrated energy of the left channel.
+    left_left: f32,
+    /// The integrated energy of the right channel.
+    right_right: f32,
+    /// The most recent goniometer points, oldest first from `next`.
+    points: Vec<[f32; 2]>,
+    /// The position in `points` at which the next point is written.
+    next: usize,
+    /// Whether `points` has been filled since it was last cleared.
+    full: bool,
+}
+
+impl PhaseMeter {
+    /// A meter that keeps the given number of goniometer points.
+    ///
+    /// The correlation is integrated over 300ms by default.
+    pub fn new(num_points: usize) -> Self {
+        PhaseMeter {
+            integration_ms: 300.0,
+            left_right: 0.0,
+            left_left: 0.0,
+            right_right: 0.0,
+            points: vec![[0.0; 2]; num_points],
+            next: 0,
+            full: false,
+        }
+    }
+
+    /// The correlation between the channels, from `-1.0` to `1.0`.
+    ///
+    /// This is `0.0` while the signal is silent.
+    pub fn correlation(&self) -> f32 {
+        let energy = (self.left_left * self.right_right).sqrt();
+        if energy <= f32::EPSILON {
+            return 0.0;
+        }
+        (self.left_right / energy).clamp(-1.0, 1.0)
+    }
+
+    /// The number of goniometer points kept by the meter.
+    pub fn num_points(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Write a snapshot of the most recent goniometer points to `points`, oldest first.
+    ///
+    /// Each point is `[side, mid]`, where `side = (left - right) / √2` and
+    /// `mid = (left + right) / √2`.
+    pub fn snapshot(&self, points: &mut Vec<[f32; 2]>) {
+        points.clear();
+        if self.full {
+            points.extend_from_slice(&self.points[self.next..]);
+        }
+        points.extend_from_slice(&self.points[..self.next]);
+    }
+
+    /// Reset the correlation and clear the goniometer points.
+    pub fn reset(&mut self) {
+        self.left_right = 0.0;
+        self.left_left = 0.0;
+        self.right_right = 0.0;
+        self.next = 0;
+        self.full = false;
+    }
+
+    /// Measure the given frames of the left and right channels.
+    pub fn process<S>(&mut self, buffer: &[[S; 2]], sample_hz: f64)
+    where
+        S: Sample,
+    {
+        let frames = (self.integration_ms.max(0.0) as f64 * 0.001 * sample_hz) as f32;
+        let coeff = if frames > 1.0 { 1.0 / frames } else { 1.0 };
+        for frame in buffer {
+            let left = frame[0].to_float_sample().to_sample::<f32>();
+            let right = frame[1].to_float_sample().to_sample::<f32>();
+            self.left_right += (left * right - self.left_right) * coeff;
+            self.left_left += (left * left - self.left_left) * coeff;
+            self.right_right += (right * right - self.right_right) * coeff;
+            if !self.points.is_empty() {
+                let point = [
+                    (left - right) * FRAC_1_SQRT_2,
+                    (left + right) * FRAC_1_SQRT_2,
+                ];
+                self.points[self.next] = point;
+                self.next += 1;
+                if self.next == self.points.len() {
+                    self.next = 0;
+                    self.full = true;
+                }
+            }
+        }
+    }
+}
+
+impl<S> Node<[S; 2]> for PhaseMeter
+where
+    S: Sample,
+{
+    fn audio_requested(&mut self, buffer: &mut [[S; 2]], sample_hz: f64) {
+        self.process(buffer, sample_hz);
+    }
+
+    /// Publishes the `"correlation"`.
+    fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+        publish("correlation", self.correlation());
+    }
+
+    fn always_render(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,217 @@
+//! A node that stands in for another while it is loaded on a background thread.
+
+use crate::buffer::{BufferFormat, Planar};
+use crate::bus::BusLayout;
+use crate::event::Event;
+use crate::graph::Tempo;
+use crate::node::{Node, ParamChange};
+use dasp::{Frame, Sample};
+use std::any::Any;
+use std::sync::mpsc;
+use std::thread;
+
+/// A node that renders silence while the node that it stands in for is loaded on a background
+/// thread, then switches to rendering that node.
+///
+/// This allows nodes that take a long time to construct, such as samplers reading their samples
+/// from disk, convolution reverbs loading impulse responses or hosted plugins, to be inserted into
+/// a **Graph** without blocking the audio thread. The loaded node is handed over through a
+/// pre-allocated channel and installed at the start of the first request for audio after it
+/// arrives, at which point the **Graph** emits `Notification::NodeLoaded`.
+///
+/// While loading, events and messages sent to the node are discarded.
+#[derive(Debug)]
+pub struct Placeholder<N> {
+    node: Option<N>,
+    receiver: Option<mpsc::Receiver<N>>,
+    /// The channel count most recently reported by the **Graph**, forwarded on load.
+    channels: Option<usize>,
+}
+
+impl<N> Placeholder<N> {
+    /// Start loading a node via `load` on a new background thread.
+    pub fn spawn<L>(load: L) -> Self
+    where
+        L: FnOnce() -> N + Send + 'static,
+        N: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        thread::spawn(move || {
+            // The placeholder may have been dropped by the time the node is loaded.
+            let _ = sender.send(load());
+        });
+        Placeholder::from_receiver(receiver)
+    }
+
+    /// Wait for a node sent via the given channel, e.g. by a loader thread pool.
+    ///
+    /// The channel should be created via `mpsc::sync_channel` so that receiving the node does not
+    /// free memory on the audio thread.
+    pub fn from_receiver(receiver: mpsc::Receiver<N>) -> Self {
+        Placeholder {
+            node: None,
+            receiver: Some(receiver),
+            channels: None,
+        }
+    }
+
+    /// A placeholder that is already active with the given node.
+    pub fn loaded(node: N) -> Self {
+        Placeholder {
+            node: Some(node),
+            receiver: None,
+            channels: None,
+        }
+    }
+
+    /// Whether the node is still being loaded.
+    ///
+    /// This remains `true` if the loader panicked or dropped the channel without sending a node.
+    pub fn is_loading(&self) -> bool {
+        self.node.is_none()
+    }
+
+    /// The loaded node, if loading has completed.
+    pub fn node(&self) -> Option<&N> {
+        self.node.as_ref()
+    }
+
+    /// The loaded node, if loading has completed.
+    pub fn node_mut(&mut self) -> Option<&mut N> {
+        self.node.as_mut()
+    }
+
+    /// Consume the placeholder, returning the loaded node if loading has completed.
+    pub fn into_node(self) -> Option<N> {
+        self.node
+    }
+}
+
+impl<F, N> Node<F> for Placeholder<N>
+where
+    F: Frame,
+    N: Node<F>,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        match self.node {
+            Some(ref mut node) => node.audio_requested(buffer, sample_hz),
+            None => dasp::slice::equilibrium(buffer),
+        }
+    }
+
+    fn dry(&self) -> <F::Sample as Sample>::Float {
+        match self.node {
+            Some(ref node) => node.dry(),
+            None => Sample::EQUILIBRIUM,
+        }
+    }
+
+    fn wet(&self) -> <F::Sample as Sample>::Float {
+        match self.node {
+            Some(ref node) => node.wet(),
+            None => <<F::Sample as Sample>::Float as Sample>::IDENTITY,
+        }
+    }
+
+    fn latency(&self) -> usize {
+        self.node.as_ref().map_or(0, |node| node.latency())
+    }
+
+    fn buffer_format(&self) -> BufferFormat {
+        match self.node {
+            Some(ref node) => node.buffer_format(),
+            None => BufferFormat::Interleaved,
+        }
+    }
+
+    fn audio_requested_planar(&mut self, buffer: Planar<F::Sample>, sample_hz: f64) {
+        if let Some(ref mut node) = self.node {
+            node.audio_requested_planar(buffer, sample_hz);
+        }
+    }
+
+    fn tail_frames(&self) -> usize {
+        self.node.as_ref().map_or(0, |node| node.tail_frames())
+    }
+
+    fn is_silent(&self) -> bool {
+        self.node.as_ref().is_none_or(|node| node.is_silent())
+    }
+
+    fn param_changes(&mut self, changes: &mut Vec<ParamChange>) {
+        if let Some(ref mut node) = self.node {
+            node.param_changes(changes);
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Some(ref mut node) = self.node {
+            node.handle_event(event);
+        }
+    }
+
+    fn handle_message(&mut self, message: &dyn Any) {
+        if let Some(ref mut node) = self.node {
+            node.handle_message(message);
+        }
+    }
+
+    fn bus_layout(&self) -> BusLayout {
+        match self.node {
+            Some(ref node) => node.bus_layout(),
+            None => BusLayout::effect(F::CHANNELS, F::CHANNELS),
+        }
+    }
+
+    fn set_bus_layout(&mut self, layout: &BusLayout) -> bool {
+        match self.node {
+            Some(ref mut node) => node.set_bus_layout(layout),
+            None => *layout == BusLayout::effect(F::CHANNELS, F::CHANNELS),
+        }
+    }
+
+    fn separate_io(&self) -> bool {
+        self.node.as_ref().is_some_and(|node| node.separate_io())
+    }
+
+    fn process(&mut self, inputs: &[&[F]], output: &mut [F], sample_hz: f64) {
+        match self.node {
+            Some(ref mut node) => node.process(inputs, output, sample_hz),
+            None => dasp::slice::equilibrium(output),
+        }
+    }
+
+    fn channels_changed(&mut self, channels: usize) {
+        match self.node {
+            Some(ref mut node) => node.channels_changed(channels),
+            None => self.channels = Some(channels),
+        }
+    }
+
+    fn update_tempo(&mut self, tempo: &Tempo) {
+        if let Some(ref mut node) = self.node {
+            node.update_tempo(tempo);
+        }
+    }
+
+    fn held_notes(&self, notes: &mut Vec<Event>) {
+        if let Some(ref node) = self.node {
+            node.held_notes(notes);
+        }
+    }
+
+    fn finish_loading(&mut self) -> bool {
+        if let Some(ref mut node) = self.node {
+            return node.finish_loading();
+        }
+        let mut node = match self.receiver.as_ref().map(|receiver| receiver.try_recv()) {
+            Some(Ok(node)) => node,
+            _ => return false,
+        };
+        if let Some(channels) = self.channels.take() {
+            node.channels_changed(channels);
+        }
+        self.node = Some(node);
+        true
+    }
+}
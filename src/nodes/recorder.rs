@@ -0,0 +1,311 @@
+//! Recording the input of a node into takes, punched in and out at exact transport frames.
+
+use crate::event::Event;
+use crate::node::Node;
+use dasp::{Frame, Sample};
+use std::collections::VecDeque;
+
+/// The number of takes that a **Recorder** can hold before they are collected without
+/// allocating on the audio thread.
+const TAKE_CAPACITY: usize = 64;
+
+/// The number of scheduled punch-ins that a **Recorder** can hold without allocating.
+const PUNCH_CAPACITY: usize = 16;
+
+/// The state of a **Recorder**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RecordState {
+    /// Punch-ins are ignored.
+    Idle,
+    /// Waiting to punch in.
+    Armed,
+    /// Recording the input into a take.
+    Recording,
+}
+
+/// One pass recorded by a **Recorder**, from a punch-in to a punch-out or, while loop recording,
+/// from one pass of the loop to the next.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Take {
+    start: Option<u64>,
+    pass: usize,
+    channels: usize,
+    samples: Vec<f32>,
+}
+
+/// Where a take lies within the buffer of a **Recorder**.
+#[derive(Copy, Clone, Debug)]
+struct Mark {
+    start: Option<u64>,
+    pass: usize,
+    /// The index of the take's first frame within the buffer.
+    offset: usize,
+    frames: usize,
+}
+
+/// Records its input into takes while passing it through unchanged, for building overdub
+/// workflows from **Graph** primitives.
+///
+/// A recorder must be armed before it punches in. Punch-ins and punch-outs are delivered as
+/// events, so that they take effect at exact transport frames: schedule them via
+/// [`Graph::schedule_punch`](../struct.Graph.html#method.schedule_punch), which also tells the
+/// recorder where on the transport each take begins. Punching out returns the recorder to
+/// `RecordState::Armed`, ready for the next punch-in.
+///
+/// While a loop length is set, the recording is split into a new take each time that many
+/// frames have been recorded, so that every pass over a looped region becomes a take of its own.
+/// Each of these takes begins at the frame of the punch-in, as the host relocates the transport
+/// to the start of the region for each pass. A punch-out ends the last pass, which may be
+/// partial.
+///
+/// Audio is recorded into a buffer that is allocated up front, so recording never allocates on
+/// the audio thread. Once the buffer is full, the recorder punches out and `is_full` returns
+/// `true` until the takes are collected via `collect_takes`. The recorder may also be armed and
+/// punched via `Event::Param`, where `param` is `0` to arm (a `value` of `1.0`) or disarm (`0.0`)
+/// and `1` to punch in (`1.0`) or out (`0.0`).
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    channels: usize,
+    state: RecordState,
+    loop_frames: Option<usize>,
+    /// The recorded audio of every take, interleaved.
+    buffer: Vec<f32>,
+    /// The number of frames of the buffer in use.
+    frames: usize,
+    marks: Vec<Mark>,
+    /// The transport frames of the scheduled punch-ins that are yet to be delivered.
+    punches: VecDeque<u64>,
+    full: bool,
+}
+
+impl Take {
+    pub(crate) fn new(start: Option<u64>, pass: usize, channels: usize, samples: Vec<f32>) -> Self {
+        Take {
+            start,
+            pass,
+            channels,
+            samples,
+        }
+    }
+
+    /// The transport frame at which the take begins, or `None` if it was punched in via an event
+    /// that was not scheduled via `Graph::schedule_punch`.
+    pub fn start(&self) -> Option<u64> {
+        self.start
+    }
+
+    /// The pass over the loop during which the take was recorded, counting from `0` at the
+    /// punch-in. This is always `0` when not loop recording.
+    pub fn pass(&self) -> usize {
+        self.pass
+    }
+
+    /// The number of interleaved channels.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The length of the take in frames.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1)
+    }
+
+    /// The recorded samples, interleaved.
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Consume the take, returning its interleaved samples.
+    pub fn into_samples(self) -> Vec<f32> {
+        self.samples
+    }
+}
+
+impl Recorder {
+    /// A recorder of the given number of channels, able to hold `capacity_frames` of audio
+    /// across its takes before they are collected.
+    pub fn new(channels: usize, capacity_frames: usize) -> Self {
+        Recorder {
+            channels,
+            state: RecordState::Idle,
+            loop_frames: None,
+            buffer: vec![0.0; channels * capacity_frames],
+            frames: 0,
+            marks: Vec::with_capacity(TAKE_CAPACITY),
+            punches: VecDeque::with_capacity(PUNCH_CAPACITY),
+            full: false,
+        }
+    }
+
+    /// The number of channels recorded.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The number of frames that may be recorded before the takes are collected.
+    pub fn capacity_frames(&self) -> usize {
+        self.buffer.len() / self.channels.max(1)
+    }
+
+    /// The current state of the recorder.
+    pub fn state(&self) -> RecordState {
+        self.state
+    }
+
+    /// Arm the recorder so that it records from the next punch-in.
+    pub fn arm(&mut self) {
+        if self.state == RecordState::Idle {
+            self.state = RecordState::Armed;
+        }
+    }
+
+    /// Disarm the recorder, ending the take in progress, if any.
+    pub fn disarm(&mut self) {
+        self.state = RecordState::Idle;
+    }
+
+    /// Start a new take if the recorder is armed.
+    ///
+    /// `start` is the transport frame at which the take begins, if known.
+    pub fn punch_in(&mut self, start: Option<u64>) {
+        if self.state != RecordState::Armed || self.full {
+            return;
+        }
+        self.state = RecordState::Recording;
+        self.begin_take(start, 0);
+    }
+
+    /// End the take in progress, if any, leaving the recorder armed.
+    pub fn punch_out(&mut self) {
+        if self.state == RecordState::Recording {
+            self.state = RecordState::Armed;
+        }
+    }
+
+    /// The number of frames per pass while loop recording, or `None` if not loop recording.
+    pub fn loop_frames(&self) -> Option<usize> {
+        self.loop_frames
+    }
+
+    /// Split the recording into a take for each pass of the given number of frames, or pass
+    /// `None` to record each punch into a single take.
+    pub fn set_loop_frames(&mut self, frames: Option<usize>) {
+        self.loop_frames = frames.filter(|&frames| frames > 0);
+    }
+
+    /// The number of frames recorded across all takes that are yet to be collected.
+    pub fn recorded_frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Whether the buffer filled up, ending the last take early.
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    /// Remove and return every completed take, making room in the buffer for more.
+    ///
+    /// A take that is still being recorded is kept.
+    pub fn collect_takes(&mut self) -> Vec<Take> {
+        let done = match self.state {
+            RecordState::Recording => self.marks.len().saturating_sub(1),
+            _ => self.marks.len(),
+        };
+        let Recorder {
+            channels,
+            ref mut buffer,
+            ref mut marks,
+            ..
+        } = *self;
+        let takes = marks
+            .drain(..done)
+            .map(|mark| {
+                let samples = &buffer[mark.offset * channels..][..mark.frames * channels];
+                Take::new(mark.start, mark.pass, channels, samples.to_vec())
+            })
+            .collect();
+        // Move the take in progress, if any, to the start of the buffer.
+        let offset = self
+            .marks
+            .first()
+            .map(|mark| mark.offset)
+            .unwrap_or(self.frames);
+        self.buffer
+            .copy_within(offset * channels..self.frames * channels, 0);
+        self.frames -= offset;
+        for mark in &mut self.marks {
+            mark.offset -= offset;
+        }
+        self.full = false;
+        takes
+    }
+
+    /// Queue the transport frame of a scheduled punch-in.
+    pub(crate) fn push_punch(&mut self, start: u64) {
+        self.punches.push_back(start);
+    }
+
+    /// Begin a new take at the end of the buffer.
+    fn begin_take(&mut self, start: Option<u64>, pass: usize) {
+        self.marks.push(Mark {
+            start,
+            pass,
+            offset: self.frames,
+            frames: 0,
+        });
+    }
+}
+
+impl<F> Node<F> for Recorder
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], _sample_hz: f64) {
+        if self.state != RecordState::Recording {
+            return;
+        }
+        let channels = self.channels;
+        for frame in buffer.iter() {
+            if self.frames * channels >= self.buffer.len() {
+                self.full = true;
+                self.punch_out();
+                return;
+            }
+            let mut mark = *self.marks.last().expect("a take is being recorded");
+            if self.loop_frames == Some(mark.frames) {
+                self.begin_take(mark.start, mark.pass + 1);
+                mark = *self.marks.last().expect("a take is being recorded");
+            }
+            let samples = &mut self.buffer[self.frames * channels..][..channels];
+            for (ch, s) in samples.iter_mut().enumerate() {
+                *s = frame
+                    .channel(ch)
+                    .map(|s| s.to_float_sample().to_sample::<f32>())
+                    .unwrap_or(0.0);
+            }
+            self.frames += 1;
+            mark.frames += 1;
+            *self.marks.last_mut().expect("a take is being recorded") = mark;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { param, value } = *event {
+            match (param, value >= 0.5) {
+                (0, true) => self.arm(),
+                (0, false) => self.disarm(),
+                (1, true) => {
+                    let start = self.punches.pop_front();
+                    self.punch_in(start);
+                }
+                (1, false) => self.punch_out(),
+                _ => (),
+            }
+        }
+    }
+
+    fn always_render(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,276 @@
+//! A bank of tuned resonators for modal synthesis.
+
+use super::filter::{from_f64, to_f64};
+use crate::event::Event;
+use crate::node::Node;
+use dasp::Frame;
+use std::f64::consts::PI;
+
+/// The level below which the ringing of every resonator is considered to have died away.
+const SILENCE: f64 = 1e-9;
+
+/// One mode of vibration of a **ResonatorBank**.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Partial {
+    /// The frequency at which the resonator rings in hz.
+    pub hz: f32,
+    /// The time in seconds taken for the ringing to decay by 60dB.
+    pub decay_secs: f32,
+    /// The amplitude of the ringing in response to a unit impulse.
+    pub gain: f32,
+}
+
+/// A bank of resonators excited by the input, each ringing at the frequency of one **Partial**
+/// and decaying at its own rate, for modal synthesis of struck or plucked objects such as bells,
+/// bars and drums.
+///
+/// Each resonator is a two-pole filter, so dozens of partials may be rendered cheaply. Feed the
+/// bank a short burst, such as an impulse or a click of noise, to strike it, or a sustained
+/// signal to colour it with the resonances. The outputs of all resonators are summed.
+///
+/// The whole bank may be retuned via `set_tuning` without changing the partials' ratios. Each
+/// partial may also be set via `Event::Param`, where `param` is three times the index of the
+/// partial plus `0` for its frequency, `1` for its decay time or `2` for its gain.
+///
+/// Once the ringing of every resonator has died away, the bank reports itself as silent so that
+/// the **Graph** skips it until its input is no longer silent.
+#[derive(Clone, Debug)]
+pub struct ResonatorBank {
+    partials: Vec<Partial>,
+    /// The ratio by which the frequency of every partial is multiplied.
+    tuning: f32,
+    /// The coefficients of each resonator, `y[n] = b0 * x[n] + a1 * y[n - 1] - a2 * y[n - 2]`.
+    b0: Vec<f64>,
+    a1: Vec<f64>,
+    a2: Vec<f64>,
+    /// The previous two outputs of each resonator for each channel, partial by partial.
+    y1: Vec<f64>,
+    y2: Vec<f64>,
+    channels: usize,
+    /// The sample rate for which the coefficients were calculated.
+    sample_hz: f64,
+    /// Whether a partial changed since the coefficients were calculated.
+    dirty: bool,
+    /// Whether all resonators have stopped ringing.
+    idle: bool,
+}
+
+impl Partial {
+    /// A partial ringing at `hz` for `decay_secs` with the given gain.
+    pub fn new(hz: f32, decay_secs: f32, gain: f32) -> Self {
+        Partial {
+            hz,
+            decay_secs,
+            gain,
+        }
+    }
+}
+
+impl ResonatorBank {
+    /// A bank with a resonator for each of the given partials.
+    pub fn new(partials: Vec<Partial>) -> Self {
+        let len = partials.len();
+        ResonatorBank {
+            partials,
+            tuning: 1.0,
+            b0: vec![0.0; len],
+            a1: vec![0.0; len],
+            a2: vec![0.0; len],
+            y1: Vec::new(),
+            y2: Vec::new(),
+            channels: 0,
+            sample_hz: 0.0,
+            dirty: true,
+            idle: true,
+        }
+    }
+
+    /// A bank of partials at the given ratios of `fundamental_hz`, each decaying over
+    /// `decay_secs` divided by its ratio and with a gain of one over its ratio, as is typical
+    /// of struck objects whose higher modes die away sooner.
+    ///
+    /// For example, the ratios `[1.0, 2.76, 5.40, 8.93]` approximate a struck bar.
+    pub fn from_ratios(fundamental_hz: f32, ratios: &[f32], decay_secs: f32) -> Self {
+        let partials = ratios
+            .iter()
+            .map(|&ratio| Partial::new(fundamental_hz * ratio, decay_secs / ratio, 1.0 / ratio))
+            .collect();
+        Self::new(partials)
+    }
+
+    /// The partials of the bank.
+    pub fn partials(&self) -> &[Partial] {
+        &self.partials
+    }
+
+    /// Set the partial at the given index, keeping its resonator ringing.
+    ///
+    /// Does nothing if there is no partial at the index.
+    pub fn set_partial(&mut self, index: usize, partial: Partial) {
+        if let Some(p) = self.partials.get_mut(index) {
+            *p = partial;
+            self.dirty = true;
+        }
+    }
+
+    /// Add a resonator for the given partial, silencing the ringing of the others.
+    pub fn add_partial(&mut self, partial: Partial) {
+        self.partials.push(partial);
+        self.b0.push(0.0);
+        self.a1.push(0.0);
+        self.a2.push(0.0);
+        // The state is resized on the next request for audio.
+        self.channels = 0;
+        self.dirty = true;
+    }
+
+    /// Remove and return the partial at the given index, or `None` if there is none.
+    ///
+    /// The ringing of the remaining resonators is silenced.
+    pub fn remove_partial(&mut self, index: usize) -> Option<Partial> {
+        if index >= self.partials.len() {
+            return None;
+        }
+        self.b0.remove(index);
+        self.a1.remove(index);
+        self.a2.remove(index);
+        self.channels = 0;
+        Some(self.partials.remove(index))
+    }
+
+    /// The ratio by which the frequency of every partial is multiplied.
+    pub fn tuning(&self) -> f32 {
+        self.tuning
+    }
+
+    /// Multiply the frequency of every partial by the given ratio, e.g. `2.0` to raise the whole
+    /// bank by an octave.
+    pub fn set_tuning(&mut self, ratio: f32) {
+        self.tuning = ratio;
+        self.dirty = true;
+    }
+
+    /// Silence the ringing of every resonator.
+    pub fn reset(&mut self) {
+        for s in self.y1.iter_mut().chain(&mut self.y2) {
+            *s = 0.0;
+        }
+        self.idle = true;
+    }
+
+    /// Calculate the coefficients of each resonator for the given sample rate.
+    fn prepare(&mut self, sample_hz: f64) {
+        self.sample_hz = sample_hz;
+        self.dirty = false;
+        let nyquist = 0.5 * sample_hz;
+        for (i, partial) in self.partials.iter().enumerate() {
+            let hz = partial.hz as f64 * self.tuning as f64;
+            if hz <= 0.0 || hz >= nyquist || partial.decay_secs <= 0.0 {
+                // Partials that cannot ring at this sample rate are muted.
+                self.b0[i] = 0.0;
+                self.a1[i] = 0.0;
+                self.a2[i] = 0.0;
+                continue;
+            }
+            // The pole radius at which the ringing decays by 60dB over the decay time.
+            let r = 0.001f64.powf(1.0 / (partial.decay_secs as f64 * sample_hz));
+            let w = 2.0 * PI * hz / sample_hz;
+            // Scaling the input by `sin(w)` gives an impulse response of `gain * r^n *
+            // sin((n + 1) * w)`, which rings with an amplitude of `gain`.
+            self.b0[i] = partial.gain as f64 * w.sin();
+            self.a1[i] = 2.0 * r * w.cos();
+            self.a2[i] = r * r;
+        }
+    }
+
+    /// Size the state of the resonators for the given number of channels, silencing them.
+    fn prepare_channels(&mut self, channels: usize) {
+        let len = self.partials.len() * channels;
+        self.y1.clear();
+        self.y1.resize(len, 0.0);
+        self.y2.clear();
+        self.y2.resize(len, 0.0);
+        self.channels = channels;
+        self.idle = true;
+    }
+}
+
+impl<F> Node<F> for ResonatorBank
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        if self.sample_hz != sample_hz || self.dirty {
+            self.prepare(sample_hz);
+        }
+        if self.channels != F::CHANNELS {
+            self.prepare_channels(F::CHANNELS);
+        }
+        let partials = self.partials.len();
+        let ResonatorBank {
+            ref b0,
+            ref a1,
+            ref a2,
+            ref mut y1,
+            ref mut y2,
+            ..
+        } = *self;
+        for frame in buffer.iter_mut() {
+            *frame = F::from_fn(|ch| {
+                let x = frame.channel(ch).map(|&s| to_f64(s)).unwrap_or(0.0);
+                let y1 = &mut y1[ch * partials..(ch + 1) * partials];
+                let y2 = &mut y2[ch * partials..(ch + 1) * partials];
+                let mut sum = 0.0;
+                for i in 0..partials {
+                    let y = b0[i] * x + a1[i] * y1[i] - a2[i] * y2[i];
+                    y2[i] = y1[i];
+                    y1[i] = y;
+                    sum += y;
+                }
+                from_f64(sum)
+            });
+        }
+        let ringing = self.y1.iter().chain(&self.y2).any(|s| s.abs() > SILENCE);
+        if !ringing {
+            self.reset();
+        }
+        self.idle = !ringing;
+    }
+
+    fn tail_frames(&self) -> usize {
+        let decay_secs = self
+            .partials
+            .iter()
+            .filter(|p| p.gain != 0.0)
+            .map(|p| p.decay_secs)
+            .fold(0.0, f32::max);
+        let sample_hz = if self.sample_hz > 0.0 {
+            self.sample_hz
+        } else {
+            44_100.0
+        };
+        (decay_secs as f64 * sample_hz).ceil() as usize
+    }
+
+    fn is_silent(&self) -> bool {
+        self.idle
+    }
+
+    fn channels_changed(&mut self, _channels: usize) {
+        self.reset();
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { param, value } = *event {
+            let index = param / 3;
+            if let Some(mut partial) = self.partials.get(index).cloned() {
+                match param % 3 {
+                    0 => partial.hz = value,
+                    1 => partial.decay_secs = value,
+                    _ => partial.gain = value,
+                }
+                self.set_partial(index, partial);
+            }
+        }
+    }
+}
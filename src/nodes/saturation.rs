@@ -0,0 +1,144 @@
+//! Waveshaping saturation with selectable transfer curves, for colour or as a safety clipper.
+
+use super::expander::from_db;
+use crate::event::Event;
+use crate::node::Node;
+use dasp::{Frame, Sample};
+
+/// The transfer curve through which a **Saturation** shapes each sample.
+///
+/// Every curve passes quiet signals at unity gain and never exceeds a magnitude of `1.0`, so
+/// that with an output trim of 0dB or below the output never clips.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SaturationCurve {
+    /// The hyperbolic tangent, a smooth and symmetrical curve adding odd harmonics.
+    Tanh,
+    /// A cubic polynomial that reaches full scale at an input of `1.5` and is hard-limited
+    /// beyond. It stays closest to linear below full scale, so it suits a safety clipper.
+    Cubic,
+    /// An asymmetrical curve that saturates positive half-cycles sooner than negative ones, like
+    /// a diode clipper, adding even harmonics along with a DC offset on loud signals.
+    Diode,
+    /// A gentle curve that begins compressing at lower levels than the others and approaches
+    /// full scale slowly, like magnetic tape driven hard.
+    Tape,
+}
+
+/// A waveshaper that saturates its input through a **SaturationCurve**.
+///
+/// The input is scaled by `input_db` before the curve, so that raising it drives the curve
+/// harder, and by `output_db` afterwards to restore the level. As the curves never exceed full
+/// scale, a **Saturation** with the `Cubic` curve and 0dB trims placed before the output of a
+/// **Graph** acts as a safety clipper, leaving quiet signals nearly untouched while preventing
+/// the output from clipping. See [`clipper`](./struct.Saturation.html#method.clipper).
+///
+/// The parameters may also be set via `Event::Param`, where `param` is `0` for the input trim,
+/// `1` for the output trim and `2` for the curve, given by its index in the order `Tanh`,
+/// `Cubic`, `Diode`, `Tape`.
+#[derive(Clone, Debug)]
+pub struct Saturation {
+    /// The curve through which the signal is shaped.
+    pub curve: SaturationCurve,
+    /// The gain in decibels applied before the curve, driving it harder as it rises.
+    pub input_db: f32,
+    /// The gain in decibels applied after the curve.
+    pub output_db: f32,
+}
+
+impl SaturationCurve {
+    /// Every curve in the order in which they are indexed via `Event::Param`.
+    pub const ALL: [SaturationCurve; 4] = [
+        SaturationCurve::Tanh,
+        SaturationCurve::Cubic,
+        SaturationCurve::Diode,
+        SaturationCurve::Tape,
+    ];
+
+    /// Shape the given sample through the curve.
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            SaturationCurve::Tanh => x.tanh(),
+            SaturationCurve::Cubic => {
+                let x = x.clamp(-1.5, 1.5);
+                x - x * x * x * 4.0 / 27.0
+            }
+            SaturationCurve::Diode => {
+                if x >= 0.0 {
+                    1.0 - (-x).exp()
+                } else {
+                    x.tanh()
+                }
+            }
+            SaturationCurve::Tape => x / (1.0 + x * x).sqrt(),
+        }
+    }
+}
+
+impl Saturation {
+    /// A new saturation with the given curve and 0dB trims.
+    pub fn new(curve: SaturationCurve) -> Self {
+        Saturation {
+            curve,
+            input_db: 0.0,
+            output_db: 0.0,
+        }
+    }
+
+    /// A safety clipper to place before the output of a **Graph**, using the `Cubic` curve with
+    /// 0dB trims.
+    pub fn clipper() -> Self {
+        Saturation::new(SaturationCurve::Cubic)
+    }
+
+    /// The same saturation driven by the given input trim in decibels.
+    pub fn with_input_db(mut self, input_db: f32) -> Self {
+        self.input_db = input_db;
+        self
+    }
+
+    /// The same saturation with the given output trim in decibels.
+    pub fn with_output_db(mut self, output_db: f32) -> Self {
+        self.output_db = output_db;
+        self
+    }
+}
+
+impl Default for Saturation {
+    fn default() -> Self {
+        Saturation::new(SaturationCurve::Tanh)
+    }
+}
+
+impl<F> Node<F> for Saturation
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], _sample_hz: f64) {
+        let input = from_db(self.input_db);
+        let output = from_db(self.output_db);
+        let curve = self.curve;
+        dasp::slice::map_in_place(buffer, |frame| {
+            frame.map(|s| {
+                let x = s.to_float_sample().to_sample::<f32>() * input;
+                let y = curve.apply(x) * output;
+                y.to_sample::<<F::Sample as Sample>::Float>().to_sample()
+            })
+        });
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { param, value } = *event {
+            match param {
+                0 => self.input_db = value,
+                1 => self.output_db = value,
+                2 => {
+                    let index = value.round().max(0.0) as usize;
+                    if let Some(&curve) = SaturationCurve::ALL.get(index) {
+                        self.curve = curve;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
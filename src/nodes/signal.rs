@@ -0,0 +1,69 @@
+//! Using any `dasp::Signal` as a source within the **Graph**.
+
+use crate::node::Node;
+use dasp::{Frame, Signal};
+
+/// A source that renders the frames yielded by a `dasp::Signal`.
+///
+/// This allows the generators of `dasp::signal` (oscillators, noise, envelopes built from
+/// iterators and so on) and their combinators to be used directly as sources within a **Graph**.
+/// Each call to `audio_requested` overwrites the buffer with the next frames of the signal.
+///
+/// The signal is expected to yield frames at the sample rate of the **Graph**, as signals carry
+/// no sample rate of their own. Once the signal reports that it is exhausted, the node outputs
+/// silence and may be skipped by the **Graph**.
+#[derive(Clone, Debug)]
+pub struct SignalNode<S> {
+    signal: S,
+}
+
+impl<S> SignalNode<S> {
+    /// Render the frames of the given signal.
+    pub fn new(signal: S) -> Self {
+        SignalNode { signal }
+    }
+
+    /// The wrapped signal.
+    pub fn signal(&self) -> &S {
+        &self.signal
+    }
+
+    /// The wrapped signal.
+    pub fn signal_mut(&mut self) -> &mut S {
+        &mut self.signal
+    }
+
+    /// Consume the node, returning the wrapped signal.
+    pub fn into_signal(self) -> S {
+        self.signal
+    }
+}
+
+impl<F, S> Node<F> for SignalNode<S>
+where
+    F: Frame,
+    S: Signal<Frame = F>,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], _sample_hz: f64) {
+        if self.signal.is_exhausted() {
+            dasp::slice::equilibrium(buffer);
+            return;
+        }
+        for frame in buffer {
+            *frame = self.signal.next();
+        }
+    }
+
+    fn is_silent(&self) -> bool {
+        self.signal.is_exhausted()
+    }
+}
+
+impl<S> From<S> for SignalNode<S>
+where
+    S: Signal,
+{
+    fn from(signal: S) -> Self {
+        SignalNode::new(signal)
+    }
+}
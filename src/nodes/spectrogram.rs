@@ -0,0 +1,293 @@
+//! A rolling history of the magnitude spectrum of a signal, for drawing scrolling spectrograms.
+
+use crate::analysis::{fft, to_db, MIN_DB};
+use crate::node::Node;
+use dasp::{Frame, Sample};
+use std::sync::{Arc, Mutex};
+
+/// Measures the magnitude spectrum of the mono sum of its input via a short-time Fourier
+/// transform, keeping a rolling history of the most recent spectra while passing the signal
+/// through unchanged.
+///
+/// A spectrum (or column) is measured over the last `fft_size` frames every `hop_size` frames,
+/// and the history holds `num_columns` columns, spanning `num_columns * hop_size` frames. The
+/// history may be read from another thread (e.g. by a UI) via the
+/// [**SpectrogramHandle**](./struct.SpectrogramHandle.html) returned by `handle`. The audio
+/// thread never blocks on the handle: new columns are published whenever it is not being read.
+#[derive(Debug)]
+pub struct Spectrogram {
+    fft_size: usize,
+    hop_size: usize,
+    /// The most recent `fft_size` frames of the mono input.
+    input: Vec<f64>,
+    /// The position in `input` at which the next frame is written.
+    input_pos: usize,
+    /// The number of frames since the last column was measured.
+    since_hop: usize,
+    /// The input in chronological order, ready to be transformed.
+    window: Vec<f64>,
+    re: Vec<f64>,
+    im: Vec<f64>,
+    magnitudes: Vec<f64>,
+    /// The history of columns, in decibels.
+    history: History,
+    /// The number of the most recent columns not yet published to the handle.
+    pending: usize,
+    shared: Arc<Mutex<History>>,
+}
+
+/// Reads the history of a **Spectrogram** from any thread.
+#[derive(Clone, Debug)]
+pub struct SpectrogramHandle {
+    shared: Arc<Mutex<History>>,
+}
+
+/// A snapshot of the history of a **Spectrogram**.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpectrogramSnapshot {
+    /// The number of frequency bins in each column, from DC up to the Nyquist frequency.
+    pub num_bins: usize,
+    /// The number of columns measured so far, up to the length of the history.
+    pub num_columns: usize,
+    /// The width of each frequency bin in Hz.
+    pub bin_hz: f64,
+    /// The time between consecutive columns in seconds.
+    pub hop_secs: f64,
+    /// The magnitude of each bin in dBFS, column by column from the oldest to the newest.
+    pub magnitudes_db: Vec<f32>,
+}
+
+/// A ring of columns of magnitudes in decibels.
+#[derive(Clone, Debug)]
+struct History {
+    num_bins: usize,
+    magnitudes_db: Vec<f32>,
+    /// The column at which the next spectrum is written.
+    next: usize,
+    /// The number of columns written, up to the length of the history.
+    filled: usize,
+    hop_size: usize,
+    sample_hz: f64,
+}
+
+impl Spectrogram {
+    /// A spectrogram measuring a spectrum of `fft_size` frames every `hop_size` frames and
+    /// keeping the most recent `num_columns` spectra.
+    ///
+    /// `fft_size` is rounded up to the next power of two, determining the frequency resolution,
+    /// while `hop_size` and `num_columns` determine the time resolution and span.
+    pub fn new(fft_size: usize, hop_size: usize, num_columns: usize) -> Self {
+        let fft_size = fft_size.max(2).next_power_of_two();
+        let hop_size = hop_size.max(1);
+        let history = History::new(fft_size / 2 + 1, num_columns, hop_size);
+        Spectrogram {
+            fft_size,
+            hop_size,
+            input: vec![0.0; fft_size],
+            input_pos: 0,
+            since_hop: 0,
+            window: Vec::with_capacity(fft_size),
+            re: vec![0.0; fft_size],
+            im: vec![0.0; fft_size],
+            magnitudes: Vec::with_capacity(fft_size / 2 + 1),
+            shared: Arc::new(Mutex::new(history.clone())),
+            history,
+            pending: 0,
+        }
+    }
+
+    /// The number of frames transformed for each column.
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// The number of frames between consecutive columns.
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    /// The number of columns kept in the history.
+    pub fn num_columns(&self) -> usize {
+        self.history.num_columns()
+    }
+
+    /// A handle for reading the history from another thread.
+    pub fn handle(&self) -> SpectrogramHandle {
+        SpectrogramHandle {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// A snapshot of the history.
+    pub fn snapshot(&self) -> SpectrogramSnapshot {
+        self.history.snapshot()
+    }
+
+    /// Clear the input and the history.
+    pub fn reset(&mut self) {
+        for s in &mut self.input {
+            *s = 0.0;
+        }
+        self.input_pos = 0;
+        self.since_hop = 0;
+        self.history.clear();
+        self.pending = 0;
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.clear();
+        }
+    }
+
+    /// Measure the given frames.
+    pub fn process<F>(&mut self, buffer: &[F], sample_hz: f64)
+    where
+        F: Frame,
+    {
+        self.history.sample_hz = sample_hz;
+        for frame in buffer {
+            let sum = frame
+                .channels()
+                .map(|s| s.to_float_sample().to_sample::<f64>())
+                .sum::<f64>();
+            self.input[self.input_pos] = sum / F::CHANNELS as f64;
+            self.input_pos = (self.input_pos + 1) % self.fft_size;
+            self.since_hop += 1;
+            if self.since_hop >= self.hop_size {
+                self.since_hop = 0;
+                self.measure();
+            }
+        }
+        self.publish();
+    }
+
+    /// Measure the spectrum of the most recent input as the next column of the history.
+    fn measure(&mut self) {
+        self.window.clear();
+        self.window.extend_from_slice(&self.input[self.input_pos..]);
+        self.window.extend_from_slice(&self.input[..self.input_pos]);
+        fft::magnitude_spectrum_with(
+            &self.window,
+            self.fft_size,
+            &mut self.re,
+            &mut self.im,
+            &mut self.magnitudes,
+        );
+        self.history.push(self.magnitudes.iter().map(|&m| to_db(m)));
+        self.pending = (self.pending + 1).min(self.history.num_columns());
+    }
+
+    /// Copy the columns measured since they were last published to the handle, unless it is
+    /// currently being read.
+    fn publish(&mut self) {
+        if self.pending == 0 {
+            return;
+        }
+        let mut shared = match self.shared.try_lock() {
+            Ok(shared) => shared,
+            Err(_) => return,
+        };
+        let columns = self.history.num_columns();
+        let bins = self.history.num_bins;
+        for i in 0..self.pending {
+            let column = (self.history.next + columns - self.pending + i) % columns;
+            let range = column * bins..(column + 1) * bins;
+            shared.magnitudes_db[range.clone()].copy_from_slice(&self.history.magnitudes_db[range]);
+        }
+        shared.next = self.history.next;
+        shared.filled = self.history.filled;
+        shared.sample_hz = self.history.sample_hz;
+        self.pending = 0;
+    }
+}
+
+impl SpectrogramHandle {
+    /// A snapshot of the history most recently published by the **Spectrogram**.
+    pub fn snapshot(&self) -> SpectrogramSnapshot {
+        let history = match self.shared.lock() {
+            Ok(history) => history,
+            // The history is always left in a consistent state.
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        history.snapshot()
+    }
+}
+
+impl History {
+    fn new(num_bins: usize, num_columns: usize, hop_size: usize) -> Self {
+        History {
+            num_bins,
+            magnitudes_db: vec![MIN_DB; num_bins * num_columns],
+            next: 0,
+            filled: 0,
+            hop_size,
+            sample_hz: 0.0,
+        }
+    }
+
+    fn num_columns(&self) -> usize {
+        self.magnitudes_db.len() / self.num_bins
+    }
+
+    fn clear(&mut self) {
+        for db in &mut self.magnitudes_db {
+            *db = MIN_DB;
+        }
+        self.next = 0;
+        self.filled = 0;
+    }
+
+    fn push<I>(&mut self, column: I)
+    where
+        I: IntoIterator<Item = f32>,
+    {
+        let columns = self.num_columns();
+        if columns == 0 {
+            return;
+        }
+        let start = self.next * self.num_bins;
+        let dest = &mut self.magnitudes_db[start..start + self.num_bins];
+        for (d, db) in dest.iter_mut().zip(column) {
+            *d = db;
+        }
+        self.next = (self.next + 1) % columns;
+        self.filled = (self.filled + 1).min(columns);
+    }
+
+    fn snapshot(&self) -> SpectrogramSnapshot {
+        let columns = self.num_columns();
+        let mut magnitudes_db = Vec::with_capacity(self.filled * self.num_bins);
+        for i in 0..self.filled {
+            let column = (self.next + columns - self.filled + i) % columns;
+            let start = column * self.num_bins;
+            magnitudes_db.extend_from_slice(&self.magnitudes_db[start..start + self.num_bins]);
+        }
+        let fft_size = (self.num_bins - 1) * 2;
+        let (bin_hz, hop_secs) = if self.sample_hz > 0.0 {
+            (
+                self.sample_hz / fft_size as f64,
+                self.hop_size as f64 / self.sample_hz,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        SpectrogramSnapshot {
+            num_bins: self.num_bins,
+            num_columns: self.filled,
+            bin_hz,
+            hop_secs,
+            magnitudes_db,
+        }
+    }
+}
+
+impl<F> Node<F> for Spectrogram
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        self.process(buffer, sample_hz);
+    }
+
+    fn always_render(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,197 @@
+//! Multiple recorded takes of one region, comped together from selected segments.
+
+use super::recorder::Take;
+use std::ops::Range;
+
+/// The point on the transport from which a **TakeLanes** plays one of its takes, until the start
+/// of the next segment or the end of the region.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompSegment {
+    /// The transport frame at which the segment begins.
+    pub start: u64,
+    /// The index of the take's lane.
+    pub take: usize,
+}
+
+/// The takes recorded over one region of the transport, each on a lane of its own, along with
+/// the comp: the segments of each take that are selected to be played.
+///
+/// Takes collected from a **Recorder** via `Recorder::collect_takes` are added via `add_take`.
+/// Each new take is selected for the whole region, as the newest pass is usually the one to
+/// audition, and segments of other takes are then selected via `select`. The comp may be
+/// rendered via `render` or bounced to a single take via `flatten`.
+///
+/// With the `serde` feature enabled, the lanes may be serialized alongside a **Graph** as part of
+/// a session, including the audio of every take.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TakeLanes {
+    region: Range<u64>,
+    channels: usize,
+    takes: Vec<Take>,
+    /// Sorted by start, with the first segment starting at the start of the region.
+    segments: Vec<CompSegment>,
+}
+
+impl TakeLanes {
+    /// Empty lanes for takes of the given number of channels over the given region of transport
+    /// frames.
+    pub fn new(region: Range<u64>, channels: usize) -> Self {
+        TakeLanes {
+            region,
+            channels,
+            takes: Vec::new(),
+            segments: Vec::new(),
+        }
+    }
+
+    /// The region of transport frames covered by the lanes.
+    pub fn region(&self) -> Range<u64> {
+        self.region.clone()
+    }
+
+    /// The number of channels of each take.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The take on each lane, oldest first.
+    pub fn takes(&self) -> &[Take] {
+        &self.takes
+    }
+
+    /// The segments of the comp, in order.
+    pub fn segments(&self) -> &[CompSegment] {
+        &self.segments
+    }
+
+    /// Add a take on a new lane and select it for the whole region, returning the index of its
+    /// lane.
+    ///
+    /// A take with no known start is placed at the start of the region.
+    ///
+    /// **Panics** if the take's channel count differs from that of the lanes.
+    pub fn add_take(&mut self, take: Take) -> usize {
+        assert_eq!(take.channels(), self.channels);
+        let lane = self.takes.len();
+        self.takes.push(take);
+        self.segments.clear();
+        self.segments.push(CompSegment {
+            start: self.region.start,
+            take: lane,
+        });
+        lane
+    }
+
+    /// Remove and return the take on the given lane, or `None` if there is no such lane.
+    ///
+    /// Segments that selected the take are given to the take selected before them, and the lanes
+    /// above it move down by one.
+    pub fn remove_take(&mut self, lane: usize) -> Option<Take> {
+        if lane >= self.takes.len() {
+            return None;
+        }
+        let take = self.takes.remove(lane);
+        let mut previous = None;
+        self.segments.retain_mut(|segment| {
+            if segment.take == lane {
+                segment.take = match previous {
+                    Some(previous) => previous,
+                    None => return false,
+                };
+            } else if segment.take > lane {
+                segment.take -= 1;
+            }
+            previous = Some(segment.take);
+            true
+        });
+        if self.takes.is_empty() {
+            self.segments.clear();
+        } else if let Some(first) = self.segments.first_mut() {
+            first.start = self.region.start;
+        } else {
+            self.segments.push(CompSegment {
+                start: self.region.start,
+                take: 0,
+            });
+        }
+        self.merge_segments();
+        Some(take)
+    }
+
+    /// Select the take on the given lane for the given range of transport frames, clipped to the
+    /// region.
+    ///
+    /// Does nothing if there is no such lane or the range is empty.
+    pub fn select(&mut self, range: Range<u64>, lane: usize) {
+        let start = range.start.max(self.region.start);
+        let end = range.end.min(self.region.end);
+        if lane >= self.takes.len() || start >= end {
+            return;
+        }
+        // The take that resumes once the range ends.
+        let resume = self.take_at(end);
+        self.segments.retain(|s| s.start < start || s.start > end);
+        let i = self.segments.partition_point(|s| s.start < start);
+        self.segments.insert(i, CompSegment { start, take: lane });
+        if let Some(resume) = resume {
+            if end < self.region.end {
+                let segment = CompSegment {
+                    start: end,
+                    take: resume,
+                };
+                self.segments.insert(i + 1, segment);
+            }
+        }
+        self.merge_segments();
+    }
+
+    /// The lane of the take selected at the given transport frame, or `None` if the frame is
+    /// outside of the region or there are no takes.
+    pub fn take_at(&self, frame: u64) -> Option<usize> {
+        if !self.region.contains(&frame) {
+            return None;
+        }
+        let i = self.segments.partition_point(|s| s.start <= frame);
+        i.checked_sub(1).map(|i| self.segments[i].take)
+    }
+
+    /// Render the comp into the interleaved `buffer`, starting at the transport frame `start`.
+    ///
+    /// Frames outside of the region, or beyond the end of the selected take, are silent.
+    pub fn render(&self, buffer: &mut [f32], start: u64) {
+        let channels = self.channels.max(1);
+        for (i, frame) in buffer.chunks_mut(channels).enumerate() {
+            let t = start + i as u64;
+            let samples = self.take_at(t).and_then(|lane| self.take_frame(lane, t));
+            match samples {
+                Some(samples) => frame.copy_from_slice(&samples[..frame.len()]),
+                None => frame.iter_mut().for_each(|s| *s = 0.0),
+            }
+        }
+    }
+
+    /// Bounce the comp to a single take spanning the region.
+    pub fn flatten(&self) -> Take {
+        let frames = (self.region.end - self.region.start) as usize;
+        let mut samples = vec![0.0; frames * self.channels];
+        self.render(&mut samples, self.region.start);
+        Take::new(Some(self.region.start), 0, self.channels, samples)
+    }
+
+    /// The samples of the take on the given lane at the given transport frame, if it covers the
+    /// frame.
+    fn take_frame(&self, lane: usize, frame: u64) -> Option<&[f32]> {
+        let take = &self.takes[lane];
+        let offset = frame.checked_sub(take.start().unwrap_or(self.region.start))? as usize;
+        let channels = self.channels.max(1);
+        take.samples()
+            .get(offset * channels..(offset + 1) * channels)
+    }
+
+    /// Remove segments that continue the take of the segment before them.
+    fn merge_segments(&mut self) {
+        self.segments.dedup_by_key(|s| s.take);
+    }
+}
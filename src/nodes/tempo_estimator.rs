@@ -0,0 +1,314 @@
+//! Estimating the tempo and beat phase of a signal from the periodicity of its onsets.
+
+use crate::analysis::fft;
+use crate::node::Node;
+use dasp::{Frame, Sample};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Estimates the tempo and beat phase of the mono sum of its input, passing the signal through
+/// unchanged.
+///
+/// The onset strength (the rise in energy across the spectrum) is measured over the last
+/// `fft_size` frames every `hop_size` frames, and the most recent `history_len` onset strengths
+/// are kept. The tempo is the period between `min_bpm` and `max_bpm` at which the autocorrelation
+/// of the onset strength peaks, while the beat phase is the offset of that period that best
+/// lines up with the onsets.
+///
+/// The latest reading may be read from another thread via the
+/// [**TempoHandle**](./struct.TempoHandle.html) returned by `handle`, which never blocks the
+/// audio thread. To sync to incoming audio, the host may align the **Graph**'s transport to the
+/// beat via `TempoReading::frames_until_beat` and `Graph::set_position`.
+#[derive(Debug)]
+pub struct TempoEstimator {
+    /// The slowest tempo in beats per minute that may be estimated.
+    pub min_bpm: f64,
+    /// The fastest tempo in beats per minute that may be estimated.
+    pub max_bpm: f64,
+    fft_size: usize,
+    hop_size: usize,
+    /// The most recent `fft_size` frames of the mono input.
+    input: Vec<f64>,
+    /// The position in `input` at which the next frame is written.
+    input_pos: usize,
+    /// The number of frames since the onset strength was last measured.
+    since_hop: usize,
+    /// The input in chronological order, ready to be transformed.
+    window: Vec<f64>,
+    re: Vec<f64>,
+    im: Vec<f64>,
+    magnitudes: Vec<f64>,
+    /// The log-compressed magnitudes of the previous spectrum.
+    previous: Vec<f64>,
+    /// The most recent onset strengths.
+    onsets: Vec<f64>,
+    /// The position in `onsets` at which the next onset strength is written.
+    onset_pos: usize,
+    /// The number of onset strengths measured, up to the length of `onsets`.
+    filled: usize,
+    /// The onset strengths in chronological order with their mean removed.
+    series: Vec<f64>,
+    reading: Option<TempoReading>,
+    shared: Arc<AtomicU64>,
+}
+
+/// Reads the latest reading of a **TempoEstimator** from any thread.
+#[derive(Clone, Debug)]
+pub struct TempoHandle {
+    shared: Arc<AtomicU64>,
+}
+
+/// An estimate of the tempo and beat phase.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TempoReading {
+    /// The tempo in beats per minute.
+    pub bpm: f32,
+    /// The fraction of the current beat that has elapsed, from `0.0` on the beat up to `1.0`.
+    pub phase: f32,
+}
+
+impl TempoEstimator {
+    /// An estimator measuring the onset strength over `fft_size` frames every `hop_size` frames
+    /// and analysing the most recent `history_len` onset strengths.
+    ///
+    /// `fft_size` is rounded up to the next power of two. At 44.1khz,
+    /// `TempoEstimator::new(1024, 512, 512)` analyses the last 6 seconds. Tempos from 60 to 180
+    /// beats per minute are estimated by default.
+    pub fn new(fft_size: usize, hop_size: usize, history_len: usize) -> Self {
+        let fft_size = fft_size.max(2).next_power_of_two();
+        TempoEstimator {
+            min_bpm: 60.0,
+            max_bpm: 180.0,
+            fft_size,
+            hop_size: hop_size.max(1),
+            input: vec![0.0; fft_size],
+            input_pos: 0,
+            since_hop: 0,
+            window: Vec::with_capacity(fft_size),
+            re: vec![0.0; fft_size],
+            im: vec![0.0; fft_size],
+            magnitudes: Vec::with_capacity(fft_size / 2 + 1),
+            previous: vec![0.0; fft_size / 2 + 1],
+            onsets: vec![0.0; history_len],
+            onset_pos: 0,
+            filled: 0,
+            series: Vec::with_capacity(history_len),
+            reading: None,
+            shared: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// The number of frames transformed for each onset strength.
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// The number of frames between consecutive onset strengths.
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    /// The number of onset strengths analysed.
+    pub fn history_len(&self) -> usize {
+        self.onsets.len()
+    }
+
+    /// The latest reading, or `None` until a tempo has been estimated.
+    pub fn reading(&self) -> Option<TempoReading> {
+        self.reading
+    }
+
+    /// A handle for reading the latest reading from another thread.
+    pub fn handle(&self) -> TempoHandle {
+        TempoHandle {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Clear the input, the onset history and the latest reading.
+    pub fn reset(&mut self) {
+        for s in self.input.iter_mut().chain(&mut self.previous) {
+            *s = 0.0;
+        }
+        self.input_pos = 0;
+        self.since_hop = 0;
+        self.onset_pos = 0;
+        self.filled = 0;
+        self.reading = None;
+        self.shared.store(0, Ordering::Relaxed);
+    }
+
+    /// Analyse the given frames.
+    pub fn process<F>(&mut self, buffer: &[F], sample_hz: f64)
+    where
+        F: Frame,
+    {
+        for frame in buffer {
+            let sum = frame
+                .channels()
+                .map(|s| s.to_float_sample().to_sample::<f64>())
+                .sum::<f64>();
+            self.input[self.input_pos] = sum / F::CHANNELS as f64;
+            self.input_pos = (self.input_pos + 1) % self.fft_size;
+            self.since_hop += 1;
+            if self.since_hop >= self.hop_size {
+                self.since_hop = 0;
+                self.measure_onset();
+                self.estimate(sample_hz);
+            }
+        }
+    }
+
+    /// Measure the onset strength of the most recent input as the spectral flux: the sum of the
+    /// rises in log-compressed magnitude of each bin since the previous spectrum.
+    fn measure_onset(&mut self) {
+        self.window.clear();
+        self.window.extend_from_slice(&self.input[self.input_pos..]);
+        self.window.extend_from_slice(&self.input[..self.input_pos]);
+        fft::magnitude_spectrum_with(
+            &self.window,
+            self.fft_size,
+            &mut self.re,
+            &mut self.im,
+            &mut self.magnitudes,
+        );
+        let mut flux = 0.0;
+        for (previous, &magnitude) in self.previous.iter_mut().zip(&self.magnitudes) {
+            let compressed = (1.0 + 100.0 * magnitude).ln();
+            flux += (compressed - *previous).max(0.0);
+            *previous = compressed;
+        }
+        if self.onsets.is_empty() {
+            return;
+        }
+        self.onsets[self.onset_pos] = flux;
+        self.onset_pos = (self.onset_pos + 1) % self.onsets.len();
+        self.filled = (self.filled + 1).min(self.onsets.len());
+    }
+
+    /// Estimate the tempo and beat phase from the onset history and publish the reading.
+    fn estimate(&mut self, sample_hz: f64) {
+        let onset_hz = sample_hz / self.hop_size as f64;
+        let min_lag = ((60.0 * onset_hz / self.max_bpm).floor() as usize).max(1);
+        let max_lag = (60.0 * onset_hz / self.min_bpm).ceil() as usize;
+        // At least two periods of the slowest tempo are needed to measure its periodicity.
+        if min_lag >= max_lag || self.filled < max_lag * 2 {
+            return;
+        }
+
+        let len = self.onsets.len();
+        let start = (self.onset_pos + len - self.filled) % len;
+        let onsets = &self.onsets;
+        self.series.clear();
+        self.series
+            .extend((0..self.filled).map(|i| onsets[(start + i) % len]));
+        let mean = self.series.iter().sum::<f64>() / self.filled as f64;
+        for s in &mut self.series {
+            *s -= mean;
+        }
+
+        // Find the lag at which the autocorrelation of the onset strength peaks.
+        let series = &self.series;
+        let autocorrelation = |lag: usize| {
+            let sum: f64 = series.iter().zip(&series[lag..]).map(|(a, b)| a * b).sum();
+            sum / (series.len() - lag) as f64
+        };
+        let (lag, peak) = (min_lag..=max_lag)
+            .map(|lag| (lag, autocorrelation(lag)))
+            .fold(
+                (0, 0.0),
+                |best, (lag, ac)| if ac > best.1 { (lag, ac) } else { best },
+            );
+        if lag == 0 || peak <= 0.0 {
+            return;
+        }
+
+        // Refine the lag by fitting a parabola through its neighbours.
+        let (a, c) = (autocorrelation(lag - 1), autocorrelation(lag + 1));
+        let denominator = a - 2.0 * peak + c;
+        let offset = if denominator.abs() > f64::EPSILON {
+            (0.5 * (a - c) / denominator).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+        let period = lag as f64 + offset;
+
+        // Find the number of hops since the last beat by summing the onsets at each offset.
+        let last = series.len() - 1;
+        let beats_ago = (0..lag)
+            .map(|ago| {
+                let sum: f64 = (ago..=last).step_by(lag).map(|i| series[last - i]).sum();
+                (ago, sum)
+            })
+            .fold(
+                (0, f64::MIN),
+                |best, (ago, sum)| if sum > best.1 { (ago, sum) } else { best },
+            )
+            .0;
+
+        let reading = TempoReading {
+            bpm: (60.0 * onset_hz / period) as f32,
+            phase: (beats_ago as f64 / period).min(1.0 - f64::EPSILON) as f32,
+        };
+        self.reading = Some(reading);
+        // Publish the tempo along with its phase so that both are read atomically.
+        let bits = u64::from(reading.bpm.to_bits()) | (u64::from(reading.phase.to_bits()) << 32);
+        self.shared.store(bits, Ordering::Relaxed);
+    }
+}
+
+impl TempoHandle {
+    /// The latest reading published by the **TempoEstimator**, or `None` until a tempo has been
+    /// estimated.
+    pub fn reading(&self) -> Option<TempoReading> {
+        let bits = self.shared.load(Ordering::Relaxed);
+        let bpm = f32::from_bits(bits as u32);
+        let phase = f32::from_bits((bits >> 32) as u32);
+        if bpm > 0.0 {
+            Some(TempoReading { bpm, phase })
+        } else {
+            None
+        }
+    }
+}
+
+impl TempoReading {
+    /// The duration of a beat in frames at the given sample rate.
+    pub fn frames_per_beat(&self, sample_hz: f64) -> f64 {
+        60.0 * sample_hz / self.bpm as f64
+    }
+
+    /// The number of frames until the next beat at the given sample rate.
+    pub fn frames_until_beat(&self, sample_hz: f64) -> f64 {
+        (1.0 - self.phase as f64) * self.frames_per_beat(sample_hz)
+    }
+}
+
+impl<F> Node<F> for TempoEstimator
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        self.process(buffer, sample_hz);
+    }
+
+    /// Publishes the latest `"onset"` strength, along with the `"bpm"` and `"beat_phase"` of the
+    /// latest reading, if any.
+    fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+        if self.filled > 0 {
+            let len = self.onsets.len();
+            publish(
+                "onset",
+                self.onsets[(self.onset_pos + len - 1) % len] as f32,
+            );
+        }
+        if let Some(reading) = self.reading {
+            publish("bpm", reading.bpm);
+            publish("beat_phase", reading.phase);
+        }
+    }
+
+    fn always_render(&self) -> bool {
+        true
+    }
+}
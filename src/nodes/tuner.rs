@@ -0,0 +1,211 @@
+//! An instrument tuner reporting the nearest note to the pitch of a signal.
+
+use crate::analysis::{pitch, to_db};
+use crate::node::Node;
+use dasp::{Frame, Sample};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The names of the twelve pitch classes, starting from C.
+pub(crate) const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Detects the pitch of the mono sum of its input and reports the nearest note along with its
+/// deviation in cents, passing the signal through unchanged.
+///
+/// The pitch is detected over the last `window_size` frames every `window_size / 2` frames. The
+/// window must hold at least two periods of `min_hz`. The latest reading may be read from
+/// another thread (e.g. by a UI) via the [**TunerHandle**](./struct.TunerHandle.html) returned by
+/// `handle`, which never blocks the audio thread.
+#[derive(Debug)]
+pub struct Tuner {
+    /// The lowest frequency in hz that may be detected.
+    pub min_hz: f64,
+    /// The highest frequency in hz that may be detected.
+    pub max_hz: f64,
+    /// The frequency of A4 in hz against which notes are named.
+    pub reference_hz: f32,
+    /// The RMS level in dBFS below which no pitch is detected.
+    pub gate_db: f32,
+    /// The most recent `window_size` frames of the mono input.
+    input: Vec<f64>,
+    /// The position in `input` at which the next frame is written.
+    input_pos: usize,
+    /// The number of frames since the pitch was last detected.
+    since_hop: usize,
+    /// The input in chronological order, ready to be analysed.
+    window: Vec<f64>,
+    reading: Option<TunerReading>,
+    shared: Arc<AtomicU64>,
+}
+
+/// Reads the latest reading of a **Tuner** from any thread.
+#[derive(Clone, Debug)]
+pub struct TunerHandle {
+    shared: Arc<AtomicU64>,
+}
+
+/// The note nearest to a detected pitch.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TunerReading {
+    /// The detected frequency in hz.
+    pub hz: f32,
+    /// The name of the nearest note, e.g. `"C#"`.
+    pub name: &'static str,
+    /// The octave of the nearest note in scientific pitch notation, where A4 is the reference.
+    pub octave: i32,
+    /// The deviation of the detected frequency from the nearest note in cents, from `-50.0` to
+    /// `50.0`. Positive values are sharp and negative values are flat.
+    pub cents: f32,
+}
+
+impl Tuner {
+    /// A tuner detecting the pitch over windows of the given number of frames.
+    ///
+    /// By default, pitches from 60hz to 1500hz are detected against A4 at 440hz, and input
+    /// quieter than -60 dBFS is ignored. At 44.1khz, a window of 2048 frames suits most
+    /// instruments.
+    pub fn new(window_size: usize) -> Self {
+        let window_size = window_size.max(2);
+        Tuner {
+            min_hz: 60.0,
+            max_hz: 1500.0,
+            reference_hz: 440.0,
+            gate_db: -60.0,
+            input: vec![0.0; window_size],
+            input_pos: 0,
+            since_hop: 0,
+            window: Vec::with_capacity(window_size),
+            reading: None,
+            shared: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// The number of frames over which the pitch is detected.
+    pub fn window_size(&self) -> usize {
+        self.input.len()
+    }
+
+    /// The latest reading, or `None` if no pitch was detected.
+    pub fn reading(&self) -> Option<TunerReading> {
+        self.reading
+    }
+
+    /// A handle for reading the latest reading from another thread.
+    pub fn handle(&self) -> TunerHandle {
+        TunerHandle {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Clear the input and the latest reading.
+    pub fn reset(&mut self) {
+        for s in &mut self.input {
+            *s = 0.0;
+        }
+        self.input_pos = 0;
+        self.since_hop = 0;
+        self.reading = None;
+        self.shared.store(0, Ordering::Relaxed);
+    }
+
+    /// Detect the pitch of the given frames.
+    pub fn process<F>(&mut self, buffer: &[F], sample_hz: f64)
+    where
+        F: Frame,
+    {
+        let window_size = self.input.len();
+        let hop_size = (window_size / 2).max(1);
+        for frame in buffer {
+            let sum = frame
+                .channels()
+                .map(|s| s.to_float_sample().to_sample::<f64>())
+                .sum::<f64>();
+            self.input[self.input_pos] = sum / F::CHANNELS as f64;
+            self.input_pos = (self.input_pos + 1) % window_size;
+            self.since_hop += 1;
+            if self.since_hop >= hop_size {
+                self.since_hop = 0;
+                self.detect(sample_hz);
+            }
+        }
+    }
+
+    /// Detect the pitch of the most recent input and publish the reading.
+    fn detect(&mut self, sample_hz: f64) {
+        self.window.clear();
+        self.window.extend_from_slice(&self.input[self.input_pos..]);
+        self.window.extend_from_slice(&self.input[..self.input_pos]);
+        let mean_square = self.window.iter().map(|s| s * s).sum::<f64>() / self.window.len() as f64;
+        let hz = if to_db(mean_square.sqrt()) < self.gate_db {
+            None
+        } else {
+            pitch::detect_pitch(&self.window, sample_hz, self.min_hz, self.max_hz)
+        };
+        self.reading = hz.and_then(|hz| TunerReading::from_hz(hz as f32, self.reference_hz));
+        // Publish the frequency along with its reference so that both are read atomically.
+        let bits = match self.reading {
+            Some(reading) => pack(reading.hz, self.reference_hz),
+            None => 0,
+        };
+        self.shared.store(bits, Ordering::Relaxed);
+    }
+}
+
+impl TunerHandle {
+    /// The latest reading published by the **Tuner**, or `None` if no pitch was detected.
+    pub fn reading(&self) -> Option<TunerReading> {
+        let bits = self.shared.load(Ordering::Relaxed);
+        let hz = f32::from_bits(bits as u32);
+        let reference_hz = f32::from_bits((bits >> 32) as u32);
+        TunerReading::from_hz(hz, reference_hz)
+    }
+}
+
+impl TunerReading {
+    /// The note nearest to the given frequency, named against the given frequency of A4.
+    ///
+    /// Returns `None` unless both frequencies are positive and finite.
+    pub fn from_hz(hz: f32, reference_hz: f32) -> Option<Self> {
+        if !(hz > 0.0 && hz.is_finite() && reference_hz > 0.0 && reference_hz.is_finite()) {
+            return None;
+        }
+        // The number of semitones above A4, where A4 is MIDI note 69.
+        let semitones = 12.0 * (hz / reference_hz).log2();
+        let nearest = semitones.round();
+        let note = nearest as i32 + 69;
+        Some(TunerReading {
+            hz,
+            name: NOTE_NAMES[note.rem_euclid(12) as usize],
+            octave: note.div_euclid(12) - 1,
+            cents: (semitones - nearest) * 100.0,
+        })
+    }
+}
+
+/// Pack the given frequencies into the bits of a single `u64`.
+fn pack(hz: f32, reference_hz: f32) -> u64 {
+    u64::from(hz.to_bits()) | (u64::from(reference_hz.to_bits()) << 32)
+}
+
+impl<F> Node<F> for Tuner
+where
+    F: Frame,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], sample_hz: f64) {
+        self.process(buffer, sample_hz);
+    }
+
+    /// Publishes the `"pitch_hz"` and `"cents"` of the latest reading, if any.
+    fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+        if let Some(reading) = self.reading {
+            publish("pitch_hz", reading.hz);
+            publish("cents", reading.cents);
+        }
+    }
+
+    fn always_render(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,231 @@
+//! Batch processing of audio files with a **Node** or **Graph**.
+//!
+//! [`process_files`](./fn.process_files.html) decodes each input file, converts it to the
+//! processing sample rate, renders it through a fresh node created by a user-supplied factory,
+//! flushes the node's tail and encodes the result, spreading the files over several worker
+//! threads. Files are read and written as RIFF WAVE.
+
+use crate::graph::silence;
+use crate::node::Node;
+use crate::nodes::filter::{from_f64, to_f64};
+use dasp::Frame;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+mod resample;
+pub(crate) mod wav;
+
+/// The sample format of the encoded output files.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SampleFormat {
+    /// 16-bit integer PCM.
+    I16,
+    /// 24-bit integer PCM.
+    I24,
+    /// 32-bit floating point.
+    F32,
+}
+
+/// Options for [`process_files`](./fn.process_files.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Options {
+    /// The sample rate at which files are processed and written.
+    ///
+    /// If `None`, each file is processed at its own sample rate. Otherwise, files at other rates
+    /// are resampled before processing.
+    pub sample_hz: Option<f64>,
+    /// The number of frames rendered per request for audio.
+    pub block_size: usize,
+    /// The number of worker threads. If `0`, the available parallelism is used.
+    pub threads: usize,
+    /// The maximum number of frames rendered after the end of the input while waiting for the
+    /// node's output to fall silent.
+    pub max_tail_frames: usize,
+    /// Whether to trim the node's reported latency from the start of the output, so that it is
+    /// aligned with the input.
+    pub compensate_latency: bool,
+    /// The sample format of the output files.
+    pub format: SampleFormat,
+}
+
+/// An error that occurred while processing a single file.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading the input or writing the output failed.
+    Io(io::Error),
+    /// The input is not a WAVE file that can be decoded.
+    InvalidWav(&'static str),
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            sample_hz: None,
+            block_size: 1024,
+            threads: 0,
+            max_tail_frames: 10 * 48_000,
+            compensate_latency: true,
+            format: SampleFormat::F32,
+        }
+    }
+}
+
+/// Process each of the `inputs` into the output at the same position in `outputs`, spreading the
+/// files over `options.threads` worker threads.
+///
+/// A new node is created via `graph_factory` for each file. The decoded input is passed through
+/// the node block by block, each block being written to the buffer before the node's
+/// `audio_requested` method is called. Once the input ends, silent blocks continue to be rendered
+/// until the node's `Node::tail_frames` have passed and its output falls silent, or until
+/// `options.max_tail_frames` is reached.
+///
+/// The channels of each file are mapped onto the channels of `F` by index, repeating the file's
+/// channels if it has fewer and dropping any surplus.
+///
+/// Returns the result of processing each file in order.
+///
+/// **Panics** if `inputs` and `outputs` differ in length.
+pub fn process_files<F, N, G, I, O>(
+    graph_factory: G,
+    inputs: &[I],
+    outputs: &[O],
+    options: &Options,
+) -> Vec<Result<(), Error>>
+where
+    F: Frame,
+    N: Node<F>,
+    G: Fn() -> N + Sync,
+    I: AsRef<Path> + Sync,
+    O: AsRef<Path> + Sync,
+{
+    assert_eq!(
+        inputs.len(),
+        outputs.len(),
+        "there must be one output for each input"
+    );
+    let threads = match options.threads {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    let next = AtomicUsize::new(0);
+    let results: Vec<_> = inputs.iter().map(|_| Mutex::new(None)).collect();
+    std::thread::scope(|scope| {
+        for _ in 0..threads.min(inputs.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= inputs.len() {
+                    break;
+                }
+                let mut node = graph_factory();
+                let result = process_file(&mut node, &inputs[i], &outputs[i], options);
+                *results[i].lock().unwrap() = Some(result);
+            });
+        }
+    });
+    results
+        .into_iter()
+        .map(|result| result.into_inner().unwrap().unwrap())
+        .collect()
+}
+
+/// Process a single file with the given node.
+///
+/// See [`process_files`](./fn.process_files.html) for details.
+pub fn process_file<F, N, I, O>(
+    node: &mut N,
+    input: I,
+    output: O,
+    options: &Options,
+) -> Result<(), Error>
+where
+    F: Frame,
+    N: Node<F>,
+    I: AsRef<Path>,
+    O: AsRef<Path>,
+{
+    // Decode the input and convert it to the processing sample rate.
+    let decoded = wav::read(BufReader::new(File::open(input)?))?;
+    let file_hz = decoded.sample_hz as f64;
+    let sample_hz = options.sample_hz.unwrap_or(file_hz);
+    let samples = resample::resample(&decoded.samples, decoded.channels, file_hz, sample_hz);
+    let frames: Vec<F> = samples
+        .chunks_exact(decoded.channels)
+        .map(|frame| F::from_fn(|ch| from_f64(frame[ch % decoded.channels] as f64)))
+        .collect();
+
+    // Render the input, followed by the tail.
+    let block_size = options.block_size.max(1);
+    let latency = if options.compensate_latency {
+        node.latency()
+    } else {
+        0
+    };
+    let tail = latency.saturating_add(node.tail_frames());
+    let mut rendered = Vec::with_capacity(frames.len() + latency);
+    let mut buffer = Vec::with_capacity(block_size);
+    for block in frames.chunks(block_size) {
+        buffer.clear();
+        buffer.extend_from_slice(block);
+        node.audio_requested(&mut buffer, sample_hz);
+        rendered.extend_from_slice(&buffer);
+    }
+    let mut tail_frames = 0;
+    while tail_frames < options.max_tail_frames {
+        let len = block_size.min(options.max_tail_frames - tail_frames);
+        buffer.clear();
+        buffer.resize(len, F::EQUILIBRIUM);
+        node.audio_requested(&mut buffer, sample_hz);
+        rendered.extend_from_slice(&buffer);
+        tail_frames += len;
+        if tail_frames >= tail && silence::is_equilibrium(&buffer) {
+            break;
+        }
+    }
+
+    // Trim the silence at the end of the tail.
+    let min_len = frames.len() + latency;
+    while rendered.len() > min_len && silence::is_equilibrium(&rendered[rendered.len() - 1..]) {
+        rendered.pop();
+    }
+
+    // Encode the output.
+    let start = latency.min(rendered.len());
+    let encoded = wav::Wav {
+        sample_hz: sample_hz.round() as u32,
+        channels: F::CHANNELS,
+        samples: rendered[start..]
+            .iter()
+            .flat_map(|frame| frame.channels().map(|s| to_f64(s) as f32))
+            .collect(),
+    };
+    let mut writer = BufWriter::new(File::create(output)?);
+    wav::write(&mut writer, &encoded, options.format)?;
+    Ok(())
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl ::std::fmt::Display for Error {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        match *self {
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::InvalidWav(reason) => write!(f, "Invalid WAVE file: {}", reason),
+        }
+    }
+}
+
+impl ::std::error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Io(_) => "An I/O error occurred",
+            Error::InvalidWav(_) => "Invalid WAVE file",
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Sample rate conversion of decoded audio.
+
+/// Resample the given interleaved samples from `from_hz` to `to_hz` using cubic Hermite
+/// interpolation.
+pub(crate) fn resample(samples: &[f32], channels: usize, from_hz: f64, to_hz: f64) -> Vec<f32> {
+    let frames = samples.len() / channels;
+    if frames == 0 || from_hz == to_hz {
+        return samples.to_vec();
+    }
+    let step = from_hz / to_hz;
+    let out_frames = (frames as f64 / step).ceil() as usize;
+    let at = |frame: isize, channel: usize| {
+        let frame = frame.max(0).min(frames as isize - 1) as usize;
+        samples[frame * channels + channel]
+    };
+
+    let mut out = Vec::with_capacity(out_frames * channels);
+    for i in 0..out_frames {
+        let pos = i as f64 * step;
+        let frame = pos.floor() as isize;
+        let t = (pos - pos.floor()) as f32;
+        for channel in 0..channels {
+            let y0 = at(frame - 1, channel);
+            let y1 = at(frame, channel);
+            let y2 = at(frame + 1, channel);
+            let y3 = at(frame + 2, channel);
+            let c1 = 0.5 * (y2 - y0);
+            let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+            let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
+            out.push(((c3 * t + c2) * t + c1) * t + y1);
+        }
+    }
+    out
+}
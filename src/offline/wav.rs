@@ -0,0 +1,155 @@
+//! Minimal reading and writing of RIFF WAVE files.
+
+use super::{Error, SampleFormat};
+use std::io::{self, Read, Write};
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// The decoded contents of a WAVE file.
+#[derive(Clone, Debug)]
+pub(crate) struct Wav {
+    pub sample_hz: u32,
+    pub channels: usize,
+    /// Interleaved samples in the range `-1.0..1.0`.
+    pub samples: Vec<f32>,
+}
+
+/// The layout of the samples described by the `fmt ` chunk.
+struct Format {
+    tag: u16,
+    channels: u16,
+    sample_hz: u32,
+    bits: u16,
+}
+
+/// Decode a WAVE file holding integer PCM or floating point samples.
+pub(crate) fn read<R>(mut reader: R) -> Result<Wav, Error>
+where
+    R: Read,
+{
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(Error::InvalidWav("not a RIFF WAVE file"));
+    }
+
+    let mut format = None;
+    let mut data = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32_at(&bytes, pos + 4) as usize;
+        let start = pos + 8;
+        let end = start.saturating_add(size).min(bytes.len());
+        let chunk = &bytes[start..end];
+        match id {
+            b"fmt " if chunk.len() >= 16 => {
+                let mut tag = u16_at(chunk, 0);
+                if tag == FORMAT_EXTENSIBLE && chunk.len() >= 26 {
+                    // The format tag is the first two bytes of the sub-format GUID.
+                    tag = u16_at(chunk, 24);
+                }
+                format = Some(Format {
+                    tag,
+                    channels: u16_at(chunk, 2),
+                    sample_hz: u32_at(chunk, 4),
+                    bits: u16_at(chunk, 14),
+                });
+            }
+            b"data" => data = Some(chunk),
+            _ => (),
+        }
+        // Chunks are padded to an even number of bytes.
+        pos = start.saturating_add(size + (size & 1));
+    }
+
+    let format = format.ok_or(Error::InvalidWav("missing fmt chunk"))?;
+    let data = data.ok_or(Error::InvalidWav("missing data chunk"))?;
+    if format.channels == 0 {
+        return Err(Error::InvalidWav("no channels"));
+    }
+    let samples = match (format.tag, format.bits) {
+        (FORMAT_PCM, 8) => data.iter().map(|&b| (b as f32 - 128.0) / 128.0).collect(),
+        (FORMAT_PCM, 16) => data
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32_768.0)
+            .collect(),
+        (FORMAT_PCM, 24) => data
+            .chunks_exact(3)
+            .map(|b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0)
+            .collect(),
+        (FORMAT_PCM, 32) => data
+            .chunks_exact(4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0)
+            .collect(),
+        (FORMAT_FLOAT, 32) => data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+        (FORMAT_FLOAT, 64) => data
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32)
+            .collect(),
+        _ => return Err(Error::InvalidWav("unsupported sample format")),
+    };
+
+    Ok(Wav {
+        sample_hz: format.sample_hz,
+        channels: format.channels as usize,
+        samples,
+    })
+}
+
+/// Encode the given interleaved samples as a WAVE file.
+pub(crate) fn write<W>(mut writer: W, wav: &Wav, format: SampleFormat) -> io::Result<()>
+where
+    W: Write,
+{
+    let (tag, bits) = match format {
+        SampleFormat::I16 => (FORMAT_PCM, 16),
+        SampleFormat::I24 => (FORMAT_PCM, 24),
+        SampleFormat::F32 => (FORMAT_FLOAT, 32),
+    };
+    let bytes_per_sample = bits as usize / 8;
+    let block_align = wav.channels * bytes_per_sample;
+    let data_len = wav.samples.len() * bytes_per_sample;
+
+    let mut bytes = Vec::with_capacity(44 + data_len);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len as u32).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&tag.to_le_bytes());
+    bytes.extend_from_slice(&(wav.channels as u16).to_le_bytes());
+    bytes.extend_from_slice(&wav.sample_hz.to_le_bytes());
+    bytes.extend_from_slice(&(wav.sample_hz * block_align as u32).to_le_bytes());
+    bytes.extend_from_slice(&(block_align as u16).to_le_bytes());
+    bytes.extend_from_slice(&(bits as u16).to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&(data_len as u32).to_le_bytes());
+    for &s in &wav.samples {
+        let s = s.clamp(-1.0, 1.0);
+        match format {
+            SampleFormat::I16 => {
+                bytes.extend_from_slice(&((s * 32_767.0).round() as i16).to_le_bytes())
+            }
+            SampleFormat::I24 => {
+                let s = (s * 8_388_607.0).round() as i32;
+                bytes.extend_from_slice(&s.to_le_bytes()[..3]);
+            }
+            SampleFormat::F32 => bytes.extend_from_slice(&s.to_le_bytes()),
+        }
+    }
+    writer.write_all(&bytes)
+}
+
+fn u16_at(bytes: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes([bytes[pos], bytes[pos + 1]])
+}
+
+fn u32_at(bytes: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]])
+}
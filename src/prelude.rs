@@ -0,0 +1,37 @@
+//! A single import for the most commonly used items.
+//!
+//! ```ignore
+//! use dsp::prelude::*;
+//! ```
+//!
+//! This brings the **Graph** and its index and error types, the **Node** trait, the nodes of the
+//! enabled node library features, the amplitude and panning units, and the `dasp` traits required
+//! to implement a **Node** into scope, so that none of `dsp`, `dasp` or `daggy` need to be
+//! explored to get started. The `Walker` trait is included for stepping through `Graph::inputs`
+//! and `Graph::outputs`, though `Graph::input_nodes` and `Graph::output_nodes` are often simpler.
+
+pub use crate::event::Event;
+pub use crate::graph::{
+    Connection, EdgeIndex, Graph, NodeId, NodeIndex, NodeVariant, RequestError, TypedNodeIndex,
+    WouldCycle,
+};
+pub use crate::node::{BoxedNodeSend, Node, ParamChange};
+#[cfg(feature = "reverb")]
+pub use crate::nodes::{Allpass, Comb, CombKind};
+pub use crate::nodes::{Chain, MidSide, Placeholder};
+#[cfg(feature = "dynamics")]
+pub use crate::nodes::{Compressor, Expander, Saturation, SaturationCurve};
+#[cfg(feature = "filters")]
+pub use crate::nodes::{Crossover, GraphicEq, MultiBand, ResonatorBank};
+#[cfg(feature = "analysis")]
+pub use crate::nodes::{KeyDetector, PhaseMeter, Spectrogram, TempoEstimator, Tuner};
+#[cfg(feature = "osc")]
+pub use crate::nodes::{Metronome, SignalNode};
+#[cfg(feature = "sampler")]
+pub use crate::nodes::{Recorder, TakeLanes};
+pub use crate::{Panning, Volume};
+pub use daggy::Walker;
+pub use dasp::sample::{Duplex as DuplexSample, FromSample, ToSample};
+pub use dasp::{Frame, Sample, Signal};
+#[cfg(feature = "derive")]
+pub use dsp_chain_derive::NodeEnum;
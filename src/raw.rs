@@ -0,0 +1,6 @@
+//! The underlying `daggy` crate, for direct access to the graph beneath a **Graph**.
+//!
+//! The **Graph** API covers most uses, so these are only needed when working with `Graph::dag`,
+//! `Graph::inputs` or `Graph::outputs` directly. The `Walker` trait is also in the prelude.
+
+pub use daggy::{self, Walker};
@@ -0,0 +1,55 @@
+//! Functions for working with slices of **Frame**s.
+//!
+//! This re-exports everything in `dasp::slice` along with gain ramps and crossfades that are
+//! useful to node authors implementing their own fades. The gain of each frame is computed from
+//! its index rather than accumulated from the previous frame, so that the loops carry no
+//! dependency between iterations and can be vectorised by the compiler.
+
+pub use dasp::slice::*;
+
+use dasp::{Frame, Sample};
+
+/// Apply a linear gain ramp to `buffer`, starting with a gain of `from` at the first frame.
+///
+/// The gain reaches `to` one frame past the end of `buffer`, so that consecutive buffers can be
+/// ramped seamlessly by passing the `to` of one as the `from` of the next.
+pub fn apply_ramp<F>(buffer: &mut [F], from: f32, to: f32)
+where
+    F: Frame,
+{
+    if from == to {
+        if from != 1.0 {
+            let gain = from.to_sample();
+            map_in_place(buffer, |frame| frame.scale_amp(gain));
+        }
+        return;
+    }
+    let step = (to - from) / buffer.len() as f32;
+    for (i, frame) in buffer.iter_mut().enumerate() {
+        let gain = from + step * i as f32;
+        *frame = frame.scale_amp(gain.to_sample());
+    }
+}
+
+/// Crossfade `output` into `input`, writing the result to `output`.
+///
+/// The amount of `input` in the mix ramps linearly from `from` at the first frame, reaching `to`
+/// one frame past the end of the buffers, while the amount of `output` is `1.0` minus that of
+/// `input`. A crossfade from `0.0` to `1.0` therefore fades from `output` to `input`.
+///
+/// **Panics** if the buffers differ in length.
+pub fn crossfade_into<F>(output: &mut [F], input: &[F], from: f32, to: f32)
+where
+    F: Frame,
+{
+    assert_eq!(output.len(), input.len());
+    let step = (to - from) / output.len() as f32;
+    for (i, (out_frame, &in_frame)) in output.iter_mut().zip(input).enumerate() {
+        let mix = from + step * i as f32;
+        *out_frame = out_frame.zip_map(in_frame, |s_out, s_in| {
+            let s_out = s_out.mul_amp((1.0 - mix).to_sample());
+            let s_in = s_in.mul_amp(mix.to_sample());
+            s_out.add_amp(s_in.to_signed_sample())
+        });
+    }
+}
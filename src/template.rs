@@ -0,0 +1,272 @@
+//! Parameterised blueprints of sub-graphs that may be instantiated many times.
+//!
+//! A [**Template**](./trait.Template.html) describes how to build a sub-graph, e.g. "an N-voice
+//! synth with the chosen filter type", from a set of typed arguments. Each call to
+//! [`Graph::instantiate`](../struct.Graph.html#method.instantiate) adds a new, independent
+//! instance of the sub-graph to a **Graph** and returns an
+//! [**Instance**](./struct.Instance.html) describing where it was added.
+//!
+//! Data that is expensive to load or large, such as samples or wavetables, may be shared between
+//! all instances via [**Resources**](./struct.Resources.html): the first instance loads the data
+//! and all following instances receive a handle to the same allocation. This keeps hosts with
+//! many similar tracks cheap to build.
+
+use crate::graph::{EdgeIndex, Graph, NodeIndex, WouldCycle};
+use crate::node::Node;
+use daggy::petgraph::graph::IndexType;
+use dasp::Frame;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+/// A parameterised blueprint of a sub-graph.
+pub trait Template<F, N, Ix = usize>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// The arguments from which each instance is built.
+    type Args;
+
+    /// Add the nodes and connections of a single instance via the given builder.
+    ///
+    /// If this returns an error, all nodes added by the builder are removed again.
+    fn build(&self, builder: &mut Builder<F, N, Ix>, args: &Self::Args) -> Result<(), WouldCycle>;
+}
+
+/// Data shared between all instances of one or more templates, stored by name.
+#[derive(Clone, Default)]
+pub struct Resources {
+    entries: Vec<(String, Arc<dyn Any + Send + Sync>)>,
+}
+
+/// Adds the nodes and connections of a single instance of a **Template** to a **Graph**.
+pub struct Builder<'a, F, N, Ix = usize>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    graph: &'a mut Graph<F, N, Ix>,
+    resources: &'a mut Resources,
+    instance: Instance<Ix>,
+}
+
+/// The nodes of a single instance of a **Template** within a **Graph**.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instance<Ix = usize> {
+    /// All nodes added for the instance in the order in which they were added.
+    pub nodes: Vec<NodeIndex<Ix>>,
+    /// The node to which the instance's input should be connected, if any.
+    pub input: Option<NodeIndex<Ix>>,
+    /// The node whose output is the output of the instance, if any.
+    pub output: Option<NodeIndex<Ix>>,
+}
+
+impl Resources {
+    /// An empty set of resources.
+    pub fn new() -> Self {
+        Resources::default()
+    }
+
+    /// The resource with the given name, loading it via `load` if there is none.
+    ///
+    /// **Panics** if there is already a resource of a different type with the given name.
+    pub fn get_or_insert_with<T, L>(&mut self, name: &str, load: L) -> Arc<T>
+    where
+        T: Any + Send + Sync,
+        L: FnOnce() -> T,
+    {
+        if let Some(resource) = self.get_any(name) {
+            return resource
+                .downcast()
+                .expect("there is already a resource of a different type with the given name");
+        }
+        let resource = Arc::new(load());
+        self.entries.push((name.to_string(), resource.clone()));
+        resource
+    }
+
+    /// The resource with the given name, if there is one of type `T`.
+    pub fn get<T>(&self, name: &str) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        self.get_any(name)
+            .and_then(|resource| resource.downcast().ok())
+    }
+
+    /// Remove the resource with the given name.
+    ///
+    /// Instances that hold a handle to the resource keep it alive until they are dropped.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|(entry, _)| entry != name);
+        self.entries.len() != len
+    }
+
+    /// The names of all resources in the order in which they were added.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| &name[..])
+    }
+
+    /// The number of resources.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no resources.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all resources.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn get_any(&self, name: &str) -> Option<Arc<dyn Any + Send + Sync>> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| entry == name)
+            .map(|(_, resource)| resource.clone())
+    }
+}
+
+impl fmt::Debug for Resources {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl<'a, F, N, Ix> Builder<'a, F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Add a node to the instance.
+    pub fn add_node(&mut self, node: N) -> NodeIndex<Ix> {
+        let idx = self.graph.add_node(node);
+        self.instance.nodes.push(idx);
+        idx
+    }
+
+    /// Connect two nodes of the instance.
+    pub fn add_connection(
+        &mut self,
+        src: NodeIndex<Ix>,
+        dest: NodeIndex<Ix>,
+    ) -> Result<EdgeIndex<Ix>, WouldCycle> {
+        self.graph.add_connection(src, dest)
+    }
+
+    /// Set the node to which the instance's input should be connected.
+    pub fn set_input(&mut self, idx: NodeIndex<Ix>) {
+        self.instance.input = Some(idx);
+    }
+
+    /// Set the node whose output is the output of the instance.
+    pub fn set_output(&mut self, idx: NodeIndex<Ix>) {
+        self.instance.output = Some(idx);
+    }
+
+    /// The shared resource with the given name, loading it via `load` if no instance has yet.
+    ///
+    /// See `Resources::get_or_insert_with`.
+    pub fn resource<T, L>(&mut self, name: &str, load: L) -> Arc<T>
+    where
+        T: Any + Send + Sync,
+        L: FnOnce() -> T,
+    {
+        self.resources.get_or_insert_with(name, load)
+    }
+
+    /// The **Graph** to which the instance is being added.
+    pub fn graph(&self) -> &Graph<F, N, Ix> {
+        self.graph
+    }
+
+    /// The nodes added to the instance so far.
+    pub fn nodes(&self) -> &[NodeIndex<Ix>] {
+        &self.instance.nodes
+    }
+}
+
+impl<'a, F, N, Ix> fmt::Debug for Builder<'a, F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("resources", &self.resources)
+            .field("instance", &self.instance)
+            .finish()
+    }
+}
+
+impl<Ix> Instance<Ix>
+where
+    Ix: IndexType,
+{
+    /// Remove all nodes of the instance from the given graph.
+    ///
+    /// As removing a node shifts the last node into its index, this is only guaranteed to remove
+    /// the right nodes if the instance's nodes are the last nodes of the graph, e.g. if no nodes
+    /// were added since the instance.
+    pub fn remove_from<F, N>(self, graph: &mut Graph<F, N, Ix>)
+    where
+        F: Frame,
+        N: Node<F>,
+    {
+        let mut nodes = self.nodes;
+        nodes.sort();
+        for idx in nodes.into_iter().rev() {
+            graph.remove_node(idx);
+        }
+    }
+}
+
+impl<F, N, Ix> Graph<F, N, Ix>
+where
+    F: Frame,
+    N: Node<F>,
+    Ix: IndexType,
+{
+    /// Add a new instance of the given template, built from the given arguments.
+    ///
+    /// Resources requested by the template are taken from `resources` if an earlier instance
+    /// loaded them, so that their data is shared.
+    ///
+    /// If the template fails to build, all nodes that it added are removed again and the error
+    /// is returned.
+    pub fn instantiate<T>(
+        &mut self,
+        template: &T,
+        resources: &mut Resources,
+        args: &T::Args,
+    ) -> Result<Instance<Ix>, WouldCycle>
+    where
+        T: Template<F, N, Ix> + ?Sized,
+    {
+        let mut builder = Builder {
+            graph: self,
+            resources,
+            instance: Instance {
+                nodes: Vec::new(),
+                input: None,
+                output: None,
+            },
+        };
+        match template.build(&mut builder, args) {
+            Ok(()) => Ok(builder.instance),
+            Err(err) => {
+                builder.instance.remove_from(self);
+                Err(err)
+            }
+        }
+    }
+}
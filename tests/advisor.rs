@@ -0,0 +1,67 @@
+//! The buffer advisor recommends a block size from the render load and reported xruns.
+
+use dsp::{BufferAdvice, BufferAdvisor, Graph, Node, Notification};
+
+type Mono = [f32; 1];
+
+/// Outputs silence.
+struct Silence;
+
+impl Node<Mono> for Silence {
+    fn audio_requested(&mut self, _buffer: &mut [Mono], _sample_hz: f64) {}
+}
+
+/// An advisor that advises after every `4` requests.
+fn advisor() -> BufferAdvisor {
+    BufferAdvisor {
+        window: 4,
+        ..BufferAdvisor::default()
+    }
+}
+
+/// Render `count` buffers of `64` frames.
+fn render(graph: &mut Graph<Mono, Silence>, count: usize) {
+    let mut buffer = [[0.0]; 64];
+    for _ in 0..count {
+        graph.audio_requested(&mut buffer, 44_100.0);
+    }
+}
+
+#[test]
+fn advice_follows_the_load_within_the_limits() {
+    let advisor = BufferAdvisor::default();
+    assert_eq!(advisor.advise(256, 0.9, 0), BufferAdvice::Increase(512));
+    assert_eq!(advisor.advise(256, 0.1, 1), BufferAdvice::Increase(512));
+    assert_eq!(advisor.advise(4_096, 0.9, 0), BufferAdvice::Keep);
+    assert_eq!(advisor.advise(256, 0.6, 0), BufferAdvice::Keep);
+    assert_eq!(advisor.advise(256, 0.1, 0), BufferAdvice::Decrease(128));
+    assert_eq!(advisor.advise(32, 0.1, 0), BufferAdvice::Keep);
+}
+
+#[test]
+fn xruns_advise_a_larger_block_size() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Silence);
+    graph.set_master(Some(node));
+    graph.set_buffer_advisor(Some(advisor()));
+    let notifications = graph.subscribe_notifications();
+
+    render(&mut graph, 3);
+    graph.report_xrun();
+    assert_eq!(graph.buffer_advice(), None);
+    render(&mut graph, 1);
+    assert_eq!(graph.buffer_advice(), Some(BufferAdvice::Increase(128)));
+    let received: Vec<_> = notifications.try_iter().collect();
+    assert_eq!(received, vec![Notification::BufferSizeAdvised(128)]);
+}
+
+#[test]
+fn nothing_is_advised_while_disabled() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Silence);
+    graph.set_master(Some(node));
+    assert_eq!(graph.buffer_advisor(), None);
+    graph.report_xrun();
+    render(&mut graph, 8);
+    assert_eq!(graph.buffer_advice(), None);
+}
@@ -0,0 +1,91 @@
+//! The **Analyser** measures signals block by block and exports the tracks as CSV and JSON.
+
+use dsp::analysis::{Analyser, MIN_DB};
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 48_000.0;
+
+/// One second of a sine at 1khz with a peak of `0.5`.
+fn sine() -> Vec<Mono> {
+    (0..SAMPLE_HZ as usize)
+        .map(|i| [0.5 * (i as f64 * 1_000.0 * std::f64::consts::TAU / SAMPLE_HZ).sin() as f32])
+        .collect()
+}
+
+/// Halves its input.
+struct Halve;
+
+impl Node<Mono> for Halve {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] *= 0.5;
+        }
+    }
+}
+
+#[test]
+fn each_block_is_metered() {
+    let analysis = Analyser::new(SAMPLE_HZ).analyse(&sine());
+    assert_eq!(analysis.blocks.len(), 24);
+    let block = &analysis.blocks[15];
+    assert_eq!(block.time_secs, 15.0 * 2_048.0 / SAMPLE_HZ);
+    assert!((block.peak_db + 6.02).abs() < 0.1);
+    assert!((block.rms_db + 9.03).abs() < 0.1);
+    assert!((block.pitch_hz.unwrap() - 1_000.0).abs() < 1.0);
+
+    // The loudest bin of the spectrum is the one nearest the sine's frequency.
+    let loudest = (0..block.spectrum_db.len())
+        .max_by(|&a, &b| block.spectrum_db[a].total_cmp(&block.spectrum_db[b]))
+        .unwrap();
+    let frequencies: Vec<_> = analysis.spectrum_frequencies().collect();
+    assert_eq!(frequencies.len(), block.spectrum_db.len());
+    assert!((frequencies[loudest] - 1_000.0).abs() < 30.0);
+}
+
+#[test]
+fn nodes_are_analysed_by_their_output() {
+    let mut analyser = Analyser::new(SAMPLE_HZ);
+    analyser.spectrum = false;
+    let analysis = analyser.analyse_node(&mut Halve, &sine());
+    let block = &analysis.blocks[15];
+    assert!((block.peak_db + 12.04).abs() < 0.1);
+    assert!(block.spectrum_db.is_empty());
+}
+
+#[test]
+fn tracks_are_exported_as_csv_and_json() {
+    let mut analyser = Analyser::new(SAMPLE_HZ);
+    analyser.block_size = 1_024;
+    let mut signal = vec![[0.0]; 1_024];
+    signal.extend_from_slice(&sine()[..1_024]);
+    let analysis = analyser.analyse(&signal);
+
+    let mut csv = Vec::new();
+    analysis.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "time_secs,peak_db,rms_db,loudness_lufs,pitch_hz");
+    // No pitch is detected in silence.
+    assert!(lines[1].ends_with(','));
+
+    let mut json = Vec::new();
+    analysis.write_json(&mut json).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["block_size"], 1_024);
+    let blocks = json["blocks"].as_array().unwrap();
+    assert_eq!(blocks.len(), 2);
+    // Silence is floored rather than written as a non-finite level.
+    assert_eq!(blocks[0]["peak_db"], MIN_DB);
+    assert_eq!(blocks[0]["loudness_lufs"], MIN_DB);
+    assert!(blocks[0]["pitch_hz"].is_null());
+    assert!(blocks[1]["peak_db"].as_f64().unwrap() < 0.0);
+
+    let mut spectrum = Vec::new();
+    analysis.write_spectrum_csv(&mut spectrum).unwrap();
+    let spectrum = String::from_utf8(spectrum).unwrap();
+    let header = spectrum.lines().next().unwrap();
+    assert_eq!(header.split(',').count(), 1 + 513);
+}
@@ -0,0 +1,79 @@
+//! Control values published by analysis nodes are routed to the parameters of other nodes.
+
+mod common;
+
+use common::{render, Mono};
+use dsp::event::Event;
+use dsp::{AnalysisRoute, Graph, Node, NodeIndex};
+
+#[derive(Debug, PartialEq)]
+enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Passes its input through, publishing its latest level.
+    Meter(f32),
+    /// Scales its input by its only parameter.
+    Gain(f32),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = match *self {
+                Test::Dc(value) => [value],
+                Test::Meter(ref mut level) => {
+                    *level = frame[0].abs();
+                    *frame
+                }
+                Test::Gain(amp) => [frame[0] * amp],
+            };
+        }
+    }
+
+    fn analysis_values(&self, publish: &mut dyn FnMut(&'static str, f32)) {
+        if let Test::Meter(level) = *self {
+            publish("level", level);
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let (Test::Gain(amp), &Event::Param { param: 0, value }) = (self, event) {
+            *amp = value;
+        }
+    }
+}
+
+#[test]
+fn published_values_modulate_later_nodes_within_the_same_request() {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(0.5));
+    let (_, meter) = graph.add_output(dc, Test::Meter(0.0));
+    let (_, gain) = graph.add_output(meter, Test::Gain(1.0));
+    graph.set_master(Some(gain));
+    let route = AnalysisRoute::new(meter, "level", gain, 0).mapped((0.0, 1.0), (0.0, 4.0));
+    assert_eq!((route.scale, route.offset), (4.0, 0.0));
+    graph.add_analysis_route(route).unwrap();
+    assert_eq!(graph.analysis_value(meter, "level"), None);
+
+    assert_eq!(render(&mut graph), 1.0);
+    assert_eq!(graph.analysis_value(meter, "level"), Some(0.5));
+    assert_eq!(graph.analysis_value(dc, "level"), None);
+    assert_eq!(graph[gain], Test::Gain(2.0));
+}
+
+#[test]
+fn routes_are_removed_along_with_their_nodes() {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(0.5));
+    let (_, meter) = graph.add_output(dc, Test::Meter(0.0));
+    let (_, gain) = graph.add_output(meter, Test::Gain(1.0));
+    let route = AnalysisRoute::new(meter, "level", gain, 0);
+    graph.add_analysis_route(route).unwrap();
+    assert_eq!(graph.analysis_routes(), &[route]);
+    let missing = AnalysisRoute::new(meter, "level", NodeIndex::new(9), 0);
+    assert!(graph.add_analysis_route(missing).is_err());
+
+    graph.remove_node(gain);
+    assert!(graph.analysis_routes().is_empty());
+    assert_eq!(graph.remove_analysis_route(0), None);
+}
@@ -0,0 +1,53 @@
+//! Appending one **Graph** to another moves its nodes and connections across.
+
+mod common;
+
+use common::{render, Mono, Test};
+use dsp::{Graph, NodeIndex};
+
+/// A constant of `value` feeding a gain of `amp`, returning the graph along with both nodes.
+fn patch(value: f32, amp: f32) -> (Graph<Mono, Test>, NodeIndex, NodeIndex) {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(value));
+    let (_, gain) = graph.add_output(dc, Test::Gain(amp));
+    (graph, dc, gain)
+}
+
+#[test]
+fn appended_nodes_are_connected_via_the_index_map() {
+    let (mut graph, _, gain) = patch(1.0, 2.0);
+    graph.set_master(Some(gain));
+    let (other, other_dc, other_gain) = patch(3.0, 10.0);
+
+    let map = graph.append(other);
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(graph.connection_count(), 2);
+    assert_eq!(graph.master_index(), Some(gain));
+    let appended_dc = map.node(other_dc).unwrap();
+    let appended_gain = map.node(other_gain).unwrap();
+    assert_eq!(graph[appended_dc], Test::Dc(3.0));
+    assert_eq!(map.nodes().len(), 2);
+    assert_eq!(map.connections().len(), 1);
+
+    graph.add_connection(appended_gain, gain).unwrap();
+    assert_eq!(render(&mut graph), 62.0);
+}
+
+#[test]
+fn appended_nodes_and_connections_keep_their_state() {
+    let (mut graph, dc, _) = patch(1.0, 2.0);
+    let (mut other, other_dc, other_gain) = patch(3.0, 10.0);
+    let edge = other.find_connection(other_dc, other_gain).unwrap();
+    other.set_connection_enabled(edge, false).unwrap();
+    other.set_muted(other_gain, true).unwrap();
+    other.add_feedback_connection(other_gain, other_dc).unwrap();
+    other.set_master(Some(other_gain));
+
+    let map = graph.append(other);
+    let appended_gain = map.node(other_gain).unwrap();
+    assert!(graph.is_muted(appended_gain));
+    assert!(!graph.is_connection_enabled(map.connection(edge).unwrap()));
+    assert_eq!(graph.feedback_connections().len(), 1);
+    assert_eq!(graph.master_index(), None);
+    assert_ne!(graph.node_id(appended_gain), graph.node_id(dc));
+}
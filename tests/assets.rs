@@ -0,0 +1,64 @@
+//! The **SamplePool** shares buffers between nodes, loading them in the background and evicting
+//! them by its policy.
+
+use dsp::assets::{EvictionPolicy, SampleBuffer, SamplePool};
+use std::sync::Arc;
+
+type Mono = [f32; 1];
+
+/// A buffer of the given number of frames.
+fn buffer(frames: usize) -> SampleBuffer<Mono> {
+    SampleBuffer::new(vec![[0.0]; frames], 44_100.0)
+}
+
+#[test]
+fn buffers_are_shared_by_name() {
+    let pool = SamplePool::default();
+    let kick = pool.insert("kick", buffer(10));
+    assert!(Arc::ptr_eq(&pool.get("kick").unwrap(), &kick));
+    let loaded = pool.get_or_load::<_, ()>("kick", || panic!("the kick is already loaded"));
+    assert!(Arc::ptr_eq(&loaded.unwrap(), &kick));
+    assert_eq!(
+        pool.get_or_load("snare", || Err("unreadable")),
+        Err("unreadable")
+    );
+    assert_eq!(pool.len(), 1);
+    assert!(pool.remove("kick"));
+    assert!(!pool.contains("kick"));
+}
+
+#[test]
+fn buffers_are_loaded_in_the_background() {
+    let pool = SamplePool::new(EvictionPolicy::Never);
+    let handle = pool.load_in_background::<_, ()>("pad", || Ok(buffer(100)));
+    let pad = handle.join().unwrap().unwrap();
+    assert_eq!(pad.len(), 100);
+    assert!(pool.contains("pad"));
+    assert_eq!(pool.total_frames(), 100);
+}
+
+#[test]
+fn unreferenced_buffers_are_evicted() {
+    let pool = SamplePool::new(EvictionPolicy::Unreferenced);
+    let kick = pool.insert("kick", buffer(10));
+    assert_eq!(pool.evict(), 0);
+    drop(kick);
+    assert_eq!(pool.evict(), 1);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn least_recently_used_buffers_are_evicted_beyond_the_maximum() {
+    let pool = SamplePool::new(EvictionPolicy::Never);
+    pool.insert("a", buffer(10));
+    pool.insert("b", buffer(10));
+    let held = pool.insert("c", buffer(10));
+    pool.get("a");
+    pool.set_policy(EvictionPolicy::MaxFrames(15));
+    assert_eq!(pool.policy(), EvictionPolicy::MaxFrames(15));
+
+    // "b" is the least recently used and "c" is still held, so "a" is evicted after "b".
+    assert!(!pool.contains("a") && !pool.contains("b"));
+    assert!(pool.contains("c"));
+    assert_eq!(pool.total_frames(), held.len());
+}
@@ -0,0 +1,56 @@
+//! Removing a node while bridging its inputs directly to its outputs keeps the signal path.
+
+mod common;
+
+use common::{render, Mono, Test};
+use dsp::Graph;
+
+#[test]
+fn inputs_are_connected_directly_to_outputs() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Test::Dc(1.0));
+    let b = graph.add_node(Test::Dc(2.0));
+    let (_, mid) = graph.add_output(a, Test::Gain(10.0));
+    graph.add_connection(b, mid).unwrap();
+    let (_, out) = graph.add_output(mid, Test::Gain(3.0));
+    graph.set_master(Some(out));
+    assert_eq!(render(&mut graph), 90.0);
+
+    assert_eq!(graph.remove_node_bridging(mid), Some(Test::Gain(10.0)));
+    assert_eq!(graph.node_count(), 3);
+    assert_eq!(graph.connection_count(), 2);
+    // The output was shifted into the index of the removed node.
+    let out = mid;
+    assert_eq!(graph.master_index(), Some(out));
+    assert_eq!(render(&mut graph), 9.0);
+}
+
+#[test]
+fn bridges_are_only_enabled_where_both_connections_were() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Test::Dc(1.0));
+    let b = graph.add_node(Test::Dc(2.0));
+    let out = graph.add_node(Test::Gain(1.0));
+    let mid = graph.add_node(Test::Gain(10.0));
+    let disabled = graph.add_connection(a, mid).unwrap();
+    graph.set_connection_enabled(disabled, false).unwrap();
+    graph.add_connection(b, mid).unwrap();
+    graph.add_connection(mid, out).unwrap();
+    graph.add_connection(b, out).unwrap();
+    graph.add_feedback_connection(mid, a).unwrap();
+
+    graph.remove_node_bridging(mid);
+    let bridge = graph.find_connection(a, out).unwrap();
+    assert!(!graph.is_connection_enabled(bridge));
+    assert!(graph.is_connection_enabled(graph.find_connection(b, out).unwrap()));
+    assert_eq!(graph.connection_count(), 2);
+    assert!(graph.feedback_connections().is_empty());
+}
+
+#[test]
+fn missing_nodes_are_ignored() {
+    let mut graph: Graph<Mono, Test> = Graph::new();
+    let a = graph.add_node(Test::Dc(1.0));
+    graph.remove_node(a);
+    assert_eq!(graph.remove_node_bridging(a), None);
+}
@@ -0,0 +1,61 @@
+//! Processors describe their buses via a **BusLayout**, which hosts negotiate from a list of
+//! proposals.
+
+use dsp::{BusKind, BusLayout, Graph, Node};
+
+type Stereo = [f32; 2];
+
+/// Passes its input through.
+struct Pass;
+
+impl Node<Stereo> for Pass {
+    fn audio_requested(&mut self, _buffer: &mut [Stereo], _sample_hz: f64) {}
+}
+
+#[test]
+fn inputs_are_placed_before_outputs() {
+    let layout = BusLayout::effect(2, 2)
+        .with_aux_output("Room", 2)
+        .with_sidechain("Key", 1);
+    let kinds: Vec<_> = layout.buses.iter().map(|bus| bus.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            BusKind::MainInput,
+            BusKind::Sidechain,
+            BusKind::MainOutput,
+            BusKind::AuxOutput,
+        ]
+    );
+    assert_eq!(layout.input_channels(), 3);
+    assert_eq!(layout.output_channels(), 4);
+    assert_eq!(layout.sidechains().count(), 1);
+    assert_eq!(layout.bus("Room").map(|bus| bus.channels), Some(2));
+    assert!(layout.is_valid());
+}
+
+#[test]
+fn layouts_need_exactly_one_main_output() {
+    assert!(BusLayout::instrument(2).is_valid());
+    assert!(!BusLayout::new().is_valid());
+    let two_outputs = BusLayout::instrument(2).with_bus("Other", BusKind::MainOutput, 2);
+    assert!(!two_outputs.is_valid());
+}
+
+#[test]
+fn nodes_accept_only_their_current_layout_by_default() {
+    let proposals = [BusLayout::effect(1, 1), BusLayout::effect(2, 2)];
+    let accepted = BusLayout::negotiate(&mut Pass, &proposals);
+    assert_eq!(accepted, Some(&proposals[1]));
+    assert_eq!(BusLayout::negotiate(&mut Pass, &proposals[..1]), None);
+}
+
+#[test]
+fn graphs_accept_layouts_that_fit_their_frame() {
+    let mut graph: Graph<Stereo, Pass> = Graph::new();
+    let proposals = [BusLayout::effect(4, 4), BusLayout::effect(1, 1)];
+    let accepted = BusLayout::negotiate(&mut graph, &proposals);
+    assert_eq!(accepted, Some(&proposals[1]));
+    assert_eq!(graph.bus_layout(), proposals[1]);
+    assert_eq!(graph.channels(), 1);
+}
@@ -0,0 +1,134 @@
+//! Nodes bypassed by the **Graph** pass their input through, optionally keeping their latency.
+
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+enum Test {
+    /// Outputs a single impulse on the first frame it renders.
+    Impulse(bool),
+    /// Delays its input by the length of its line, reporting that as its latency.
+    Delay(Vec<Mono>),
+    /// Outputs a constant.
+    Dc(f32),
+    /// Scales its input.
+    Gain(f32),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        match self {
+            Test::Impulse(done) => {
+                if !*done {
+                    buffer[0] = [1.0];
+                    *done = true;
+                }
+            }
+            Test::Delay(line) => {
+                for frame in buffer.iter_mut() {
+                    line.push(*frame);
+                    *frame = line.remove(0);
+                }
+            }
+            Test::Dc(value) => {
+                for frame in buffer.iter_mut() {
+                    *frame = [*value];
+                }
+            }
+            Test::Gain(gain) => {
+                for frame in buffer.iter_mut() {
+                    frame[0] *= *gain;
+                }
+            }
+        }
+    }
+
+    fn latency(&self) -> usize {
+        match self {
+            Test::Delay(line) => line.len(),
+            _ => 0,
+        }
+    }
+}
+
+/// An impulse passed through a delay of `3` frames, returning the index of the delay.
+fn delayed_impulse() -> (Graph<Mono, Test>, dsp::NodeIndex) {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Impulse(false));
+    let (_, delay) = graph.add_output(src, Test::Delay(vec![[0.0]; 3]));
+    graph.set_master(Some(delay));
+    graph.set_bypass_fade_frames(0);
+    (graph, delay)
+}
+
+/// Render a buffer of `8` frames.
+fn render(graph: &mut Graph<Mono, Test>) -> [Mono; 8] {
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    buffer
+}
+
+#[test]
+fn bypassing_crossfades_between_the_output_and_the_input() {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Dc(1.0));
+    let (_, halve) = graph.add_output(src, Test::Gain(0.5));
+    graph.set_master(Some(halve));
+    assert_eq!(graph.bypass_fade_frames(), 128);
+    graph.set_bypass_fade_frames(4);
+    assert_eq!(render(&mut graph), [[0.5]; 8]);
+
+    graph.set_bypassed(halve, true).unwrap();
+    assert!(graph.is_bypassed(halve));
+    let buffer = render(&mut graph);
+    assert_eq!(buffer[0], [0.625]);
+    assert_eq!(&buffer[3..], &[[1.0]; 5]);
+    assert_eq!(render(&mut graph), [[1.0]; 8]);
+
+    graph.set_bypassed(halve, false).unwrap();
+    let buffer = render(&mut graph);
+    assert_eq!(buffer[0], [0.875]);
+    assert_eq!(buffer[7], [0.5]);
+}
+
+#[test]
+fn bypassing_without_a_fade_takes_effect_immediately() {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Dc(1.0));
+    let (_, halve) = graph.add_output(src, Test::Gain(0.5));
+    graph.set_master(Some(halve));
+    graph.set_bypass_fade_frames(0);
+    graph.set_bypassed(halve, true).unwrap();
+    assert_eq!(render(&mut graph), [[1.0]; 8]);
+    assert!(graph.set_bypassed(dsp::NodeIndex::new(9), true).is_err());
+}
+
+#[test]
+fn bypassed_nodes_drop_their_latency_by_default() {
+    let (mut graph, delay) = delayed_impulse();
+    graph.set_bypassed(delay, true).unwrap();
+    assert!(graph.is_bypassed(delay));
+    assert_eq!(graph.path_latency(delay), Some(0));
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer[0], [1.0]);
+}
+
+#[test]
+fn bypassed_nodes_may_preserve_their_latency() {
+    let (mut graph, delay) = delayed_impulse();
+    graph.set_bypass_preserves_latency(true);
+    graph.set_bypassed(delay, true).unwrap();
+    assert_eq!(graph.path_latency(delay), Some(3));
+
+    // The passed-through input is delayed by the full latency from the first frame.
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer[3], [1.0]);
+    assert_eq!(buffer.iter().filter(|f| f[0] != 0.0).count(), 1);
+
+    graph.set_bypass_preserves_latency(false);
+    assert_eq!(graph.path_latency(delay), Some(0));
+}
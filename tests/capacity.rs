@@ -0,0 +1,41 @@
+//! Reserving and shrinking the **Graph**'s capacity keeps every index and connection intact.
+
+mod common;
+
+use common::{render, Mono, Test};
+use dsp::Graph;
+
+#[test]
+fn reserving_and_shrinking_keeps_indices_stable() {
+    let mut graph = Graph::new();
+    let gain = graph.add_node(Test::Gain(0.5));
+    let (a_edge, a) = graph.add_input(Test::Dc(1.0), gain);
+    let (b_edge, b) = graph.add_input(Test::Dc(2.0), gain);
+    graph.set_master(Some(gain));
+
+    graph.reserve_nodes(100);
+    graph.reserve_connections(100);
+    graph.shrink_to_fit();
+
+    assert_eq!(graph.node(a), Some(&Test::Dc(1.0)));
+    assert_eq!(graph.node(b), Some(&Test::Dc(2.0)));
+    assert_eq!(graph.raw_edges()[a_edge.index()].source(), a);
+    assert_eq!(graph.raw_edges()[b_edge.index()].source(), b);
+    assert_eq!(graph.raw_edges()[b_edge.index()].target(), gain);
+    assert_eq!(graph.connection_count(), 2);
+    assert_eq!(graph.master_index(), Some(gain));
+    assert!(graph.validate().is_valid());
+
+    assert_eq!(render(&mut graph), 1.5);
+}
+
+#[test]
+fn remaining_capacity_is_limited_by_the_index_type() {
+    let mut graph: Graph<Mono, Test, u16> = Graph::with_capacity_indexed(2, 1, 0);
+    assert_eq!(graph.max_node_count(), u16::MAX as usize);
+    assert_eq!(graph.max_connection_count(), u16::MAX as usize);
+    let gain = graph.add_node(Test::Gain(1.0));
+    graph.add_input(Test::Dc(1.0), gain);
+    assert_eq!(graph.remaining_node_capacity(), u16::MAX as usize - 2);
+    assert_eq!(graph.remaining_connection_capacity(), u16::MAX as usize - 1);
+}
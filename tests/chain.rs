@@ -0,0 +1,45 @@
+//! Serial chains of nodes, and sets of nodes feeding a single destination, are added in a single
+//! call.
+
+mod common;
+
+use common::{render, Mono, Test};
+use dsp::raw::Walker;
+use dsp::Graph;
+
+#[test]
+fn each_node_feeds_the_next() {
+    let mut graph = Graph::new();
+    let chain = vec![Test::Dc(1.0), Test::Gain(2.0), Test::Gain(3.0)];
+    let (nodes, edges) = graph.add_chain(chain);
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(edges.len(), 2);
+    assert_eq!(graph.find_connection(nodes[1], nodes[2]), Some(edges[1]));
+    assert_eq!(graph.inputs(nodes[2]).iter(&graph).count(), 1);
+
+    graph.set_master(Some(nodes[2]));
+    assert_eq!(render(&mut graph), 6.0);
+}
+
+#[test]
+fn empty_chains_add_nothing() {
+    let mut graph: Graph<Mono, Test> = Graph::new();
+    let (nodes, edges) = graph.add_chain(Vec::new());
+    assert!(nodes.is_empty() && edges.is_empty());
+    assert_eq!(graph.node_count(), 0);
+}
+
+#[test]
+fn parallel_nodes_feed_the_destination() {
+    let mut graph = Graph::new();
+    let bus = graph.add_node(Test::Gain(1.0));
+    let sources = vec![Test::Dc(1.0), Test::Dc(2.0), Test::Dc(3.0)];
+    let (nodes, edges) = graph.add_parallel(sources, bus);
+    assert_eq!(nodes.len(), 3);
+    for (&node, &edge) in nodes.iter().zip(&edges) {
+        assert_eq!(graph.find_connection(node, bus), Some(edge));
+    }
+
+    graph.set_master(Some(bus));
+    assert_eq!(render(&mut graph), 6.0);
+}
@@ -0,0 +1,79 @@
+//! The number of channels carrying audio may be changed at runtime, up to the width of the frame.
+
+use dsp::{Graph, Node};
+
+type Stereo = [f32; 2];
+
+enum Test {
+    /// Outputs a constant on every channel.
+    Dc(f32),
+    /// Passes its input through, remembering the last channel count it was notified of.
+    Pass(Option<usize>),
+}
+
+impl Node<Stereo> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Stereo], _sample_hz: f64) {
+        if let Test::Dc(value) = *self {
+            for frame in buffer.iter_mut() {
+                *frame = [value; 2];
+            }
+        }
+    }
+
+    fn channels_changed(&mut self, channels: usize) {
+        if let Test::Pass(notified) = self {
+            *notified = Some(channels);
+        }
+    }
+}
+
+/// A constant source passed through to the output.
+fn graph() -> (Graph<Stereo, Test>, dsp::NodeIndex) {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Dc(1.0));
+    let (_, out) = graph.add_output(src, Test::Pass(None));
+    graph.set_master(Some(out));
+    (graph, out)
+}
+
+/// Render a buffer, returning its first frame.
+fn render(graph: &mut Graph<Stereo, Test>) -> Stereo {
+    let mut buffer = [[0.0; 2]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    buffer[0]
+}
+
+#[test]
+fn inactive_channels_are_silenced() {
+    let (mut graph, _) = graph();
+    assert_eq!(graph.channels(), 2);
+    assert_eq!(render(&mut graph), [1.0, 1.0]);
+
+    graph.set_channels(1);
+    assert_eq!(graph.channels(), 1);
+    assert_eq!(render(&mut graph), [1.0, 0.0]);
+
+    graph.set_channels(2);
+    assert_eq!(render(&mut graph), [1.0, 1.0]);
+}
+
+#[test]
+fn nodes_are_notified_of_the_change() {
+    let (mut graph, out) = graph();
+    graph.set_channels(1);
+    assert!(matches!(graph[out], Test::Pass(Some(1))));
+}
+
+#[test]
+#[should_panic]
+fn channels_cannot_exceed_the_frame() {
+    let (mut graph, _) = graph();
+    graph.set_channels(3);
+}
+
+#[test]
+#[should_panic]
+fn at_least_one_channel_is_active() {
+    let (mut graph, _) = graph();
+    graph.set_channels(0);
+}
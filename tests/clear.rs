@@ -0,0 +1,51 @@
+//! Nodes cleared from a **Graph** may be handed to a callback to release their resources.
+
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+/// Outputs a constant.
+#[derive(Debug, PartialEq)]
+struct Dc(f32);
+
+impl Node<Mono> for Dc {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+}
+
+#[test]
+fn cleared_nodes_are_passed_to_the_callback() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Dc(1.0));
+    let (_, b) = graph.add_output(a, Dc(2.0));
+    graph.set_master(Some(b));
+
+    let mut removed = Vec::new();
+    graph.clear_with(|idx, node| removed.push((idx, node)));
+    assert_eq!(removed, vec![(b, Dc(2.0)), (a, Dc(1.0))]);
+    assert_eq!(graph.node_count(), 0);
+    assert_eq!(graph.connection_count(), 0);
+    assert_eq!(graph.master_index(), None);
+}
+
+#[test]
+fn disconnected_nodes_are_passed_along_with_their_original_index() {
+    let mut graph = Graph::new();
+    let lone = graph.add_node(Dc(1.0));
+    let (_, b) = graph.add_output(lone, Dc(2.0));
+    graph.add_output(b, Dc(3.0));
+    let other = graph.add_node(Dc(4.0));
+    graph.remove_all_output_connections(lone);
+
+    let mut removed = Vec::new();
+    let count = graph.clear_disconnected_with(|idx, node| removed.push((idx, node)));
+    assert_eq!(count, 2);
+    assert_eq!(removed, vec![(other, Dc(4.0)), (lone, Dc(1.0))]);
+    assert_eq!(graph.node_count(), 2);
+    // The last remaining node is shifted into the index of the first removed node.
+    assert_eq!(graph[lone], Dc(3.0));
+    assert!(graph.find_connection(b, lone).is_some());
+}
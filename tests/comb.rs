@@ -0,0 +1,65 @@
+//! **Comb** and **Allpass** filters mix a signal with delayed copies of itself.
+
+#![cfg(feature = "reverb")]
+
+use dsp::nodes::{Allpass, Comb, CombKind};
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+/// The response of the given node to a unit impulse over `len` frames.
+fn impulse_response<N: Node<Mono>>(node: &mut N, len: usize) -> Vec<f32> {
+    let mut buffer = vec![[0.0]; len];
+    buffer[0] = [1.0];
+    node.audio_requested(&mut buffer, 44_100.0);
+    buffer.iter().map(|frame| frame[0]).collect()
+}
+
+#[test]
+fn feedforward_combs_add_a_single_echo() {
+    let mut comb = Comb::feedforward(3, 0.5);
+    assert_eq!(comb.kind(), CombKind::FeedForward);
+    assert_eq!(comb.max_delay_frames(), 3);
+    assert_eq!(
+        impulse_response(&mut comb, 7),
+        vec![1.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0]
+    );
+    assert_eq!(Node::<Mono>::tail_frames(&comb), 3);
+
+    comb.reset();
+    comb.delay_frames = 1.5;
+    assert_eq!(impulse_response(&mut comb, 4), vec![1.0, 0.25, 0.25, 0.0]);
+}
+
+#[test]
+fn feedback_combs_ring_on() {
+    let mut comb = Comb::feedback(2, 0.5);
+    let response = impulse_response(&mut comb, 7);
+    assert_eq!(response, vec![1.0, 0.0, 0.5, 0.0, 0.25, 0.0, 0.125]);
+    comb.delay_frames = 10.0;
+    assert_eq!(Node::<Mono>::tail_frames(&comb), 100);
+    comb.gain = 1.0;
+    assert_eq!(Node::<Mono>::tail_frames(&comb), usize::MAX);
+}
+
+#[test]
+fn damping_decays_high_frequencies_faster() {
+    // A negative gain with a delay of one frame resonates at the Nyquist frequency.
+    let mut bright = Comb::feedback(1, -0.9);
+    let mut damped = Comb::feedback(1, -0.9);
+    damped.damping = 0.5;
+    let bright = impulse_response(&mut bright, 64);
+    let damped = impulse_response(&mut damped, 64);
+    assert!(damped[1].abs() < bright[1].abs());
+    assert!(damped[63].abs() < bright[63].abs());
+}
+
+#[test]
+fn allpass_filters_keep_the_energy_of_the_input() {
+    let mut allpass = Allpass::new(2, 0.5);
+    let response = impulse_response(&mut allpass, 2_048);
+    assert_eq!(&response[..5], &[-0.5, 0.0, 0.75, 0.0, 0.375]);
+    let energy: f32 = response.iter().map(|s| s * s).sum();
+    assert!((energy - 1.0).abs() < 1e-6);
+    assert_eq!(Node::<Mono>::tail_frames(&allpass), 20);
+}
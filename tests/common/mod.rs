@@ -0,0 +1,39 @@
+//! Nodes and helpers shared between the integration tests.
+
+// Each test file compiles this module separately and uses only part of it.
+#![allow(dead_code)]
+
+use dsp::{Graph, IndexType, Node};
+
+pub type Mono = [f32; 1];
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Scales its input.
+    Gain(f32),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = match *self {
+                Test::Dc(value) => [value],
+                Test::Gain(amp) => [frame[0] * amp],
+            };
+        }
+    }
+}
+
+/// Render a buffer of `4` frames, returning its first frame.
+pub fn render<N, Ix>(graph: &mut Graph<Mono, N, Ix>) -> f32
+where
+    N: Node<Mono>,
+    Ix: IndexType,
+{
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    buffer[0][0]
+}
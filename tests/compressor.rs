@@ -0,0 +1,125 @@
+//! The **Compressor** attenuates loud signals, keyed by its own input or by a sidechain.
+
+#![cfg(feature = "dynamics")]
+
+use dsp::event::Event;
+use dsp::nodes::Compressor;
+use dsp::{BoxedNodeSend, Graph, Node};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// Outputs a constant.
+struct Dc(f32);
+
+impl Node<Mono> for Dc {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+}
+
+/// A compressor with a threshold of -20dB, a ratio of 4 and a hard knee that reacts instantly.
+fn compressor() -> Compressor {
+    let mut compressor = Compressor::new(-20.0, 4.0);
+    compressor.attack_ms = 0.0;
+    compressor.release_ms = 0.0;
+    compressor.knee_db = 0.0;
+    compressor
+}
+
+/// Compress a buffer of the given constant level by its own level, returning the last frame.
+fn compress(compressor: &mut Compressor, level: f32) -> f32 {
+    let mut buffer = [[level]; 16];
+    Node::<Mono>::audio_requested(compressor, &mut buffer, SAMPLE_HZ);
+    buffer[15][0]
+}
+
+/// Decibels as a linear amplitude.
+fn from_db(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
+
+#[test]
+fn signals_below_the_threshold_pass_unchanged() {
+    let mut compressor = compressor();
+    assert!((compress(&mut compressor, 0.05) - 0.05).abs() < 1e-6);
+    assert_eq!(compressor.gain_reduction_db(), 0.0);
+}
+
+#[test]
+fn signals_above_the_threshold_are_reduced_by_the_ratio() {
+    let mut compressor = compressor();
+    // 0dB is 20dB above the threshold, reduced by a ratio of 4 to 5dB above it.
+    let out = compress(&mut compressor, 1.0);
+    assert!((compressor.gain_reduction_db() + 15.0).abs() < 1e-3);
+    assert!((out - from_db(-15.0)).abs() < 1e-4);
+
+    let makeup = Event::Param {
+        param: 5,
+        value: 15.0,
+    };
+    Node::<Mono>::handle_event(&mut compressor, &makeup);
+    assert!((compress(&mut compressor, 1.0) - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn the_soft_knee_eases_compression_in_around_the_threshold() {
+    let mut compressor = compressor();
+    let at_threshold = from_db(-20.0);
+    compress(&mut compressor, at_threshold);
+    assert!(compressor.gain_reduction_db().abs() < 1e-3);
+
+    // Halfway through a 6dB knee, a quarter of the slope's reduction over half the knee applies.
+    compressor.knee_db = 6.0;
+    compress(&mut compressor, at_threshold);
+    assert!((compressor.gain_reduction_db() + 0.5625).abs() < 1e-3);
+    // Beyond the knee the full ratio applies.
+    compress(&mut compressor, 1.0);
+    assert!((compressor.gain_reduction_db() + 15.0).abs() < 1e-3);
+}
+
+#[test]
+fn the_gain_is_reduced_over_the_attack_and_recovers_over_the_release() {
+    let mut compressor = compressor();
+    compressor.attack_ms = 10.0;
+    compressor.release_ms = 100.0;
+    let mut buffer = [[1.0]; 441];
+    Node::<Mono>::audio_requested(&mut compressor, &mut buffer, SAMPLE_HZ);
+    // After one attack time, the reduction has covered all but `1 / e` of the way.
+    let expected = -15.0 * (1.0 - (-1.0f32).exp());
+    assert!((compressor.gain_reduction_db() - expected).abs() < 0.1);
+
+    compressor.reset();
+    assert_eq!(compressor.gain_reduction_db(), 0.0);
+    compress(&mut compressor, 1.0);
+    let reduced = compressor.gain_reduction_db();
+    compress(&mut compressor, 0.0);
+    assert!(compressor.gain_reduction_db() > reduced);
+    assert!(compressor.gain_reduction_db() < 0.0);
+}
+
+#[test]
+fn the_sidechain_input_keys_the_compression_of_the_others() {
+    let mut graph: Graph<Mono, BoxedNodeSend<Mono>> = Graph::new();
+    let mut compressor = compressor().with_sidechain_input(1);
+    compressor.ratio = 1_000.0;
+    let compressor = graph.add_node(Box::new(compressor));
+    // The voice keys the compressor, ducking the music without being heard itself. Inputs
+    // are walked most recently connected first, so the voice is the second.
+    graph.add_input(Box::new(Dc(1.0)), compressor);
+    graph.add_input(Box::new(Dc(0.05)), compressor);
+    graph.set_master(Some(compressor));
+    assert_eq!(graph[compressor].bus_layout().sidechains().count(), 1);
+
+    let mut buffer = [[0.0]; 16];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    let ducked = 0.05 * from_db(-20.0);
+    assert!((buffer[15][0] - ducked).abs() < 1e-3, "{}", buffer[15][0]);
+    let reduction = graph
+        .analysis_value(compressor, "gain_reduction_db")
+        .unwrap();
+    assert!(reduction < -19.0);
+}
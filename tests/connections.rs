@@ -0,0 +1,68 @@
+//! Connections may be disabled without being removed, and are ramped in and out when added and
+//! removed.
+
+mod common;
+
+use common::{Mono, Test};
+use dsp::{Graph, Node};
+
+/// Render a buffer of `8` frames.
+fn render(graph: &mut Graph<Mono, Test>) -> [Mono; 8] {
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    buffer
+}
+
+#[test]
+fn disabled_connections_are_ignored() {
+    let mut graph = Graph::new();
+    let bus = graph.add_node(Test::Gain(0.5));
+    let (a, _) = graph.add_input(Test::Dc(1.0), bus);
+    let (b, _) = graph.add_input(Test::Dc(2.0), bus);
+    graph.set_master(Some(bus));
+    assert_eq!(render(&mut graph), [[1.5]; 8]);
+
+    graph.set_connection_enabled(b, false).unwrap();
+    assert!(!graph.is_connection_enabled(b));
+    assert_eq!(render(&mut graph), [[0.5]; 8]);
+    graph.set_connection_enabled(a, false).unwrap();
+    assert_eq!(render(&mut graph), [[0.0]; 8]);
+
+    // Disabled connections keep their index.
+    assert_eq!(graph.connection_count(), 2);
+    graph.set_connection_enabled(b, true).unwrap();
+    assert!(graph.is_connection_enabled(b));
+    assert_eq!(render(&mut graph), [[1.0]; 8]);
+}
+
+#[test]
+fn missing_connections_are_not_enabled() {
+    let mut graph: Graph<Mono, Test> = Graph::new();
+    let missing = dsp::EdgeIndex::new(0);
+    assert!(!graph.is_connection_enabled(missing));
+    assert!(graph.set_connection_enabled(missing, true).is_err());
+}
+
+#[test]
+fn connections_are_ramped_in_and_out() {
+    let mut graph = Graph::new();
+    let bus = graph.add_node(Test::Gain(0.5));
+    graph.add_input(Test::Dc(1.0), bus);
+    let b = graph.add_node(Test::Dc(2.0));
+    graph.set_master(Some(bus));
+    assert_eq!(graph.connection_ramp_frames(), 0);
+    graph.set_connection_ramp_frames(4);
+    assert_eq!(render(&mut graph), [[0.5]; 8]);
+
+    let edge = graph.add_connection(b, bus).unwrap();
+    let buffer = render(&mut graph);
+    assert_eq!(buffer[0], [0.75]);
+    assert_eq!(&buffer[3..], &[[1.5]; 5]);
+
+    // The removed connection's source keeps being rendered until it has faded out.
+    graph.remove_edge(edge);
+    let buffer = render(&mut graph);
+    assert_eq!(buffer[0], [1.25]);
+    assert_eq!(&buffer[3..], &[[0.5]; 5]);
+    assert_eq!(render(&mut graph), [[0.5]; 8]);
+}
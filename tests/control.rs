@@ -0,0 +1,62 @@
+//! Control taps publish decimated values of connections and follow them as the graph changes.
+
+use dsp::{ControlSource, Graph, Node};
+
+type Mono = [f32; 1];
+
+enum Test {
+    Constant(f32),
+    Pass,
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        if let Test::Constant(value) = *self {
+            for frame in buffer.iter_mut() {
+                *frame = [value];
+            }
+        }
+    }
+}
+
+#[test]
+fn connection_taps_publish_peaks() {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Constant(0.5));
+    let (edge, dest) = graph.add_output(src, Test::Pass);
+    graph.set_master(Some(dest));
+    let tap = graph.add_control_tap(ControlSource::Connection(edge), 100.0);
+    let mut buffer = vec![[0.0]; 1000];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    let values: Vec<_> = tap.try_iter().collect();
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[0].value, 0.5);
+    assert_eq!(values[0].position, 440);
+
+    // Dropping the receiver removes the tap.
+    drop(tap);
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(graph.control_tap_count(), 0);
+}
+
+#[test]
+fn connection_taps_follow_shifted_connections() {
+    let mut graph = Graph::new();
+    let mix = graph.add_node(Test::Pass);
+    let (quiet_edge, _) = graph.add_input(Test::Constant(0.25), mix);
+    let (loud_edge, loud) = graph.add_input(Test::Constant(1.0), mix);
+    graph.set_master(Some(mix));
+    let tap = graph.add_control_tap(ControlSource::Connection(loud_edge), 100.0);
+
+    // Removing the other connection shifts the followed connection into its index.
+    assert!(graph.remove_edge(quiet_edge));
+    assert_eq!(graph.find_connection(loud, mix), Some(quiet_edge));
+    let mut buffer = vec![[0.0]; 441];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(tap.latest().map(|v| v.value), Some(1.0));
+
+    // Removing the followed connection removes the tap.
+    assert!(graph.remove_connection(loud, mix));
+    assert_eq!(graph.control_tap_count(), 0);
+    assert!(tap.latest().is_none());
+}
@@ -0,0 +1,79 @@
+//! A count-in schedules a metronome to click from its start until the punch-in.
+#![cfg(feature = "osc")]
+
+use dsp::event::Event;
+use dsp::nodes::Metronome;
+use dsp::{CountIn, Graph, Node, TempoMap};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 1_000.0;
+
+/// Whether any frame of the given buffer is audible.
+fn audible(buffer: &[Mono]) -> bool {
+    buffer.iter().any(|frame| frame[0] != 0.0)
+}
+
+#[test]
+fn metronome_clicks_through_the_count_in() {
+    let mut graph = Graph::new();
+    let metronome = graph.add_node(Metronome::new(120.0, 3));
+    graph.set_master(Some(metronome));
+
+    // One bar of four beats at 60bpm is 4000 frames at 1khz.
+    let range = graph
+        .start_count_in(Some(metronome), 10_000, CountIn::new(60.0, 4, 1), SAMPLE_HZ)
+        .unwrap();
+    assert_eq!(range, 6_000..10_000);
+    assert_eq!(graph.position(), 6_000);
+
+    // A click sounds on each beat of the count-in, at the count-in's tempo.
+    let mut beat = [[0.0]; 1_000];
+    for _ in 0..4 {
+        graph.audio_requested(&mut beat, SAMPLE_HZ);
+        assert!(audible(&beat[..100]));
+        assert!(!audible(&beat[100..]));
+    }
+
+    // The metronome stops at the punch-in.
+    graph.audio_requested(&mut beat, SAMPLE_HZ);
+    assert!(!audible(&beat));
+    let node = graph.node(metronome).unwrap();
+    assert!(!node.is_running());
+    assert_eq!(node.beats_per_bar, 4);
+}
+
+#[test]
+fn the_count_in_follows_the_tempo_map() {
+    let mut graph = Graph::new();
+    let metronome = graph.add_node(Metronome::new(60.0, 4));
+    graph.set_master(Some(metronome));
+    graph.set_tempo_map(Some(TempoMap::new(120.0)));
+
+    // The map's 120bpm overrides the count-in's 60bpm, so one bar is 2000 frames at 1khz.
+    let range = graph
+        .start_count_in(Some(metronome), 10_000, CountIn::new(60.0, 4, 1), SAMPLE_HZ)
+        .unwrap();
+    assert_eq!(range, 8_000..10_000);
+    let mut beat = [[0.0]; 500];
+    for _ in 0..4 {
+        graph.audio_requested(&mut beat, SAMPLE_HZ);
+        assert!(audible(&beat[..50]));
+        assert!(!audible(&beat[100..]));
+    }
+}
+
+#[test]
+fn named_params_control_the_metronome() {
+    let mut metronome = Metronome::new(120.0, 4);
+    let param = |param, value| Event::Param { param, value };
+    Node::<Mono>::handle_event(&mut metronome, &param(Metronome::BPM_PARAM, 90.0));
+    Node::<Mono>::handle_event(&mut metronome, &param(Metronome::BEATS_PER_BAR_PARAM, 7.0));
+    Node::<Mono>::handle_event(&mut metronome, &param(Metronome::LEVEL_PARAM, 0.25));
+    Node::<Mono>::handle_event(&mut metronome, &param(Metronome::RUNNING_PARAM, 1.0));
+    assert_eq!(metronome.bpm, 90.0);
+    assert_eq!(metronome.beats_per_bar, 7);
+    assert_eq!(metronome.level, 0.25);
+    assert!(metronome.is_running());
+    assert_eq!(Metronome::RUNNING_PARAM, CountIn::RUNNING_PARAM);
+}
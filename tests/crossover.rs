@@ -0,0 +1,83 @@
+//! **Crossover** bands split a source across separate paths and sum back flat.
+
+#![cfg(feature = "filters")]
+
+use dsp::nodes::Crossover;
+use dsp::{BoxedNodeSend, Graph, Node};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// A sine at the given frequency.
+struct Sine(f64, usize);
+
+/// Passes its summed inputs through.
+struct Sum;
+
+impl Node<Mono> for Sine {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            let phase = self.1 as f64 * self.0 * std::f64::consts::TAU / SAMPLE_HZ;
+            *frame = [phase.sin() as f32];
+            self.1 += 1;
+        }
+    }
+}
+
+impl Node<Mono> for Sum {
+    fn audio_requested(&mut self, _buffer: &mut [Mono], _sample_hz: f64) {}
+}
+
+/// The peak level of the second half of a quarter second of the graph's output.
+fn peak(graph: &mut Graph<Mono, BoxedNodeSend<Mono>>) -> f32 {
+    let mut buffer = vec![[0.0]; SAMPLE_HZ as usize / 4];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    let settled = &buffer[buffer.len() / 2..];
+    settled
+        .iter()
+        .fold(0.0, |peak, frame| frame[0].abs().max(peak))
+}
+
+/// A sine at `hz` split by crossovers at 200hz and 2khz, with only the given bands summed.
+fn split(hz: f64, bands: &[usize]) -> Graph<Mono, BoxedNodeSend<Mono>> {
+    let mut graph: Graph<Mono, BoxedNodeSend<Mono>> = Graph::new();
+    let source = graph.add_node(Box::new(Sine(hz, 0)));
+    let sum = graph.add_node(Box::new(Sum));
+    for crossover in Crossover::bands(&[200.0, 2_000.0]) {
+        if bands.contains(&crossover.band()) {
+            let (_, band) = graph.add_output(source, Box::new(crossover));
+            graph.add_connection(band, sum).unwrap();
+        }
+    }
+    graph.set_master(Some(sum));
+    graph
+}
+
+#[test]
+fn bands_cover_the_spectrum_between_sorted_crossovers() {
+    let bands = Crossover::bands(&[2_000.0, 200.0]);
+    assert_eq!(bands.len(), 3);
+    assert_eq!(bands[1].crossovers(), &[200.0, 2_000.0]);
+    assert_eq!(bands[0].range_hz(), (0.0, 200.0));
+    assert_eq!(bands[1].range_hz(), (200.0, 2_000.0));
+    assert_eq!(bands[2].range_hz(), (2_000.0, f64::INFINITY));
+    assert_eq!(Crossover::new(&[200.0], 1).band(), 1);
+}
+
+#[test]
+fn each_band_passes_only_its_own_frequencies() {
+    assert!(peak(&mut split(50.0, &[0])) > 0.95);
+    assert!(peak(&mut split(8_000.0, &[0])) < 0.01);
+    assert!(peak(&mut split(630.0, &[1])) > 0.9);
+    assert!(peak(&mut split(8_000.0, &[2])) > 0.95);
+    assert!(peak(&mut split(50.0, &[2])) < 0.01);
+}
+
+#[test]
+fn all_bands_sum_back_flat() {
+    for &hz in &[50.0, 200.0, 630.0, 2_000.0, 8_000.0] {
+        let level = peak(&mut split(hz, &[0, 1, 2]));
+        assert!((level - 1.0).abs() < 0.01, "{}hz: {}", hz, level);
+    }
+}
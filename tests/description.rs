@@ -0,0 +1,130 @@
+//! Graphs are saved to and loaded from JSON descriptions via a registry of node constructors.
+
+mod common;
+
+use common::{render, Mono, Test};
+use dsp::description::{Describe, Error, GraphDescription, NodeFactory, Value};
+use dsp::Graph;
+
+impl Describe for Test {
+    fn type_name(&self) -> &str {
+        match *self {
+            Test::Dc(_) => "dc",
+            Test::Gain(_) => "gain",
+        }
+    }
+
+    fn params(&self) -> Value {
+        let (name, value) = match *self {
+            Test::Dc(value) => ("value", value),
+            Test::Gain(amp) => ("amp", amp),
+        };
+        Value::Object(vec![(name.to_string(), value.into())])
+    }
+}
+
+/// The parameter of the given name as an `f32`.
+fn param(params: &Value, name: &str) -> Result<f32, String> {
+    params
+        .get(name)
+        .and_then(Value::as_f64)
+        .map(|value| value as f32)
+        .ok_or_else(|| format!("expected a number for {:?}", name))
+}
+
+/// A factory for all nodes of the `Test` type.
+fn factory() -> NodeFactory<Test> {
+    NodeFactory::new()
+        .with("dc", |params| param(params, "value").map(Test::Dc))
+        .with("gain", |params| param(params, "amp").map(Test::Gain))
+}
+
+#[test]
+fn graphs_round_trip_through_json() {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(1.0));
+    let (edge, gain) = graph.add_output(dc, Test::Gain(0.5));
+    graph.set_connection_enabled(edge, false).unwrap();
+    graph.add_feedback_connection(gain, dc).unwrap();
+    graph.set_master(Some(gain));
+
+    let description = graph.to_description();
+    let json = description.to_json();
+    assert_eq!(GraphDescription::from_json(&json), Ok(description.clone()));
+    let loaded: Graph<Mono, Test> = Graph::from_description(&description, &factory()).unwrap();
+    assert_eq!(loaded.to_description(), description);
+    assert_eq!(loaded[gain], Test::Gain(0.5));
+    assert!(!loaded.is_connection_enabled(edge));
+}
+
+#[test]
+fn descriptions_are_parsed_from_json() {
+    let json = r#"{
+        "nodes": [
+            { "type": "dc", "params": { "value": 2 } },
+            { "type": "gain", "params": { "amp": 0.5 } }
+        ],
+        "connections": [{ "source": 0, "destination": 1 }],
+        "master": 1
+    }"#;
+    let description = GraphDescription::from_json(json).unwrap();
+    assert!(description.feedback.is_empty());
+    let mut graph: Graph<Mono, Test> = Graph::from_description(&description, &factory()).unwrap();
+    assert_eq!(render(&mut graph), 1.0);
+}
+
+#[test]
+fn invalid_descriptions_are_rejected() {
+    let load = |json: &str| {
+        let description = GraphDescription::from_json(json)?;
+        Graph::<Mono, Test>::from_description(&description, &factory()).map(|_| ())
+    };
+    assert!(matches!(load("{"), Err(Error::Parse(_))));
+    assert!(matches!(load("{}"), Err(Error::Invalid(_))));
+    assert_eq!(
+        load(r#"{ "nodes": [{ "type": "reverb" }] }"#),
+        Err(Error::UnknownType(0, "reverb".to_string()))
+    );
+    assert!(matches!(
+        load(r#"{ "nodes": [{ "type": "gain" }] }"#),
+        Err(Error::Node(0, _))
+    ));
+    let one_gain = r#"{ "type": "gain", "params": { "amp": 1 } }"#;
+    let missing = format!(r#"{{ "nodes": [{}], "master": 3 }}"#, one_gain);
+    assert_eq!(load(&missing), Err(Error::NoNode(3)));
+    let cycle = format!(
+        r#"{{ "nodes": [{0}, {0}], "connections": [
+            {{ "source": 0, "destination": 1 }}, {{ "source": 1, "destination": 0 }}
+        ] }}"#,
+        one_gain
+    );
+    assert_eq!(load(&cycle), Err(Error::WouldCycle));
+}
+
+#[test]
+fn constructors_are_registered_by_type_name() {
+    let mut factory = factory();
+    assert!(factory.contains("dc"));
+    factory.register("dc", |_| Ok(Test::Dc(0.0)));
+    assert_eq!(factory.type_names().collect::<Vec<_>>(), vec!["gain", "dc"]);
+}
+
+#[test]
+fn json_is_parsed_strictly() {
+    let parse = |json: &str| Value::parse(json).map_err(|e| e.reason);
+    assert_eq!(parse("-0.5e+2"), Ok(Value::Number(-50.0)));
+    assert_eq!(parse("10"), Ok(Value::Number(10.0)));
+    for invalid in &["-.5", "01", "-", "1.", "1e", "+1", "1.5.5", "--1"] {
+        assert!(parse(invalid).is_err(), "{}", invalid);
+    }
+
+    assert_eq!(parse(r#""é🎵""#), Ok(Value::String("é🎵".to_string())));
+    assert_eq!(parse(r#""\u+0e9""#), Err("invalid escape"));
+    assert_eq!(parse(r#""\u00e""#), Err("invalid escape"));
+
+    // Deep nesting is rejected rather than overflowing the stack.
+    let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+    assert!(parse(&nested(128)).is_ok());
+    assert_eq!(parse(&nested(129)), Err("nested too deeply"));
+    assert_eq!(parse(&"[".repeat(100_000)), Err("nested too deeply"));
+}
@@ -0,0 +1,74 @@
+//! The **Graph** renders the same output whatever the buffer size.
+
+use dsp::event::Event;
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 1_000.0;
+
+#[derive(Clone)]
+enum Test {
+    /// Outputs a constant level, set via parameter `0`.
+    Dc(f32),
+    /// Outputs the length of each buffer that it renders, so depends on how it is divided.
+    BlockLen,
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        let value = match *self {
+            Test::Dc(level) => level,
+            Test::BlockLen => buffer.len() as f32,
+        };
+        for frame in buffer.iter_mut() {
+            *frame = [value];
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let (Test::Dc(level), Event::Param { param: 0, value }) = (self, event) {
+            *level = *value;
+        }
+    }
+}
+
+#[test]
+fn scheduled_and_smoothed_params_are_block_size_independent() {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(0.0));
+    graph.set_master(Some(dc));
+    graph.set_default_smoothing_ms(20.0);
+    for (i, &frame) in [0, 33, 250, 251, 700].iter().enumerate() {
+        let event = Event::Param {
+            param: 0,
+            value: i as f32 * 0.25,
+        };
+        graph.schedule_event(dc, frame, event).unwrap();
+    }
+
+    let report = graph.verify_block_sizes(&[64, 1, 7, 100, 1_000], 1_000, SAMPLE_HZ);
+    assert_eq!(report.frames, 1_000);
+    assert!(report.is_bit_identical(), "{:?}", report);
+    assert!(report.is_within(0.0));
+
+    // The **Graph** itself is left to render the events, which ramp between their values.
+    assert_eq!(graph.position(), 0);
+    let mut output = vec![[0.0]; 1_000];
+    graph.audio_requested(&mut output, SAMPLE_HZ);
+    assert!(output.iter().any(|frame| frame[0] > 0.25 && frame[0] < 0.5));
+}
+
+#[test]
+fn block_size_dependent_nodes_are_reported() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Test::BlockLen);
+    graph.set_master(Some(node));
+
+    let report = graph.verify_block_sizes(&[8, 8, 4], 16, SAMPLE_HZ);
+    assert!(!report.is_bit_identical());
+    assert_eq!(report.first_difference, Some((0, 4)));
+    assert_eq!(report.max_difference, 4.0);
+    assert!(report.is_within(4.0));
+    assert!(!report.is_within(3.0));
+}
@@ -0,0 +1,76 @@
+//! Switching the device driving a **Graph** fades its output out and back in around the switch.
+
+use dsp::{DeviceConfig, Graph, Node, NodeIndex};
+
+type Stereo = [f32; 2];
+
+/// Outputs a constant, remembering the last channel count it was notified of.
+struct Dc(f32, Option<usize>);
+
+impl Node<Stereo> for Dc {
+    fn audio_requested(&mut self, buffer: &mut [Stereo], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0; 2];
+        }
+    }
+
+    fn channels_changed(&mut self, channels: usize) {
+        self.1 = Some(channels);
+    }
+}
+
+/// A constant output fading over `4` frames.
+fn graph() -> (Graph<Stereo, Dc>, NodeIndex) {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Dc(1.0, None));
+    graph.set_master(Some(node));
+    graph.set_device_fade_frames(4);
+    (graph, node)
+}
+
+/// Render `6` frames, returning the left channel.
+fn render(graph: &mut Graph<Stereo, Dc>) -> Vec<f32> {
+    let mut buffer = [[0.0; 2]; 6];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    buffer.iter().map(|frame| frame[0]).collect()
+}
+
+#[test]
+fn the_output_fades_out_and_back_in_around_a_switch() {
+    let (mut graph, _) = graph();
+    assert_eq!(graph.device(), None);
+    assert_eq!(render(&mut graph), vec![1.0; 6]);
+
+    graph.begin_device_switch();
+    assert!(!graph.is_ready_for_device_switch());
+    assert_eq!(render(&mut graph), vec![0.75, 0.5, 0.25, 0.0, 0.0, 0.0]);
+    assert!(graph.is_ready_for_device_switch());
+
+    let config = DeviceConfig {
+        sample_hz: 48_000.0,
+        channels: 2,
+        buffer_size: 6,
+    };
+    graph.switch_device(config);
+    assert_eq!(graph.device(), Some(config));
+    assert!(!graph.is_ready_for_device_switch());
+    assert_eq!(render(&mut graph), vec![0.25, 0.5, 0.75, 1.0, 1.0, 1.0]);
+}
+
+#[test]
+fn switching_to_fewer_channels_notifies_the_nodes() {
+    let (mut graph, node) = graph();
+    graph.set_device_fade_frames(0);
+    graph.begin_device_switch();
+    assert!(graph.is_ready_for_device_switch());
+    assert_eq!(render(&mut graph), vec![0.0; 6]);
+
+    graph.switch_device(DeviceConfig {
+        sample_hz: 44_100.0,
+        channels: 1,
+        buffer_size: 64,
+    });
+    assert_eq!(graph.channels(), 1);
+    assert_eq!(graph[node].1, Some(1));
+    assert_eq!(render(&mut graph)[0], 1.0);
+}
@@ -0,0 +1,89 @@
+//! Device outputs carry an external output of a **Graph** to the stream of another device.
+
+use dsp::{DeviceConfig, DeviceOutput, Graph, Node};
+
+type Mono = [f32; 1];
+
+/// Outputs a constant.
+struct Dc(f32);
+
+impl Node<Mono> for Dc {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+}
+
+/// A constant as the external output `"aux"`, sent to a device with the same sample rate and a
+/// block size of `64` frames.
+fn graph() -> (Graph<Mono, Dc>, DeviceOutput<Mono>) {
+    let mut graph = Graph::new();
+    graph.add_external_output("aux", 1, Dc(0.5));
+    graph.prepare_buffers(64);
+    let device = DeviceConfig {
+        sample_hz: 44_100.0,
+        channels: 1,
+        buffer_size: 64,
+    };
+    let output = graph.add_device_output("aux", 44_100.0, device).unwrap();
+    (graph, output)
+}
+
+/// Render `count` blocks of `64` frames.
+fn render(graph: &mut Graph<Mono, Dc>, count: usize) {
+    let mut buffer = [[0.0]; 64];
+    for _ in 0..count {
+        graph.audio_requested(&mut buffer, 44_100.0);
+    }
+}
+
+#[test]
+fn output_starts_once_the_target_latency_is_queued() {
+    let (mut graph, mut output) = graph();
+    assert_eq!(output.target_latency_frames(), 128);
+    assert!(output.is_attached());
+    let mut buffer = [[1.0]; 64];
+
+    render(&mut graph, 1);
+    assert_eq!(output.latency_frames(), 64);
+    output.read(&mut buffer);
+    assert_eq!(buffer, [[0.0]; 64]);
+
+    render(&mut graph, 1);
+    output.read(&mut buffer);
+    assert_eq!(output.ratio(), 1.0);
+    assert_eq!(buffer[63], [0.5]);
+    assert_eq!(output.underruns(), 0);
+
+    output.read(&mut buffer);
+    output.read(&mut buffer);
+    assert_eq!(output.underruns(), 1);
+    assert_eq!(buffer[63], [0.0]);
+}
+
+#[test]
+fn the_ratio_is_corrected_for_drift() {
+    let (mut graph, mut output) = graph();
+    let mut buffer = [[0.0]; 64];
+    render(&mut graph, 3);
+    output.read(&mut buffer);
+    assert!(output.ratio() > 1.0);
+
+    render(&mut graph, 16);
+    assert!(output.dropped_frames() > 0);
+}
+
+#[test]
+fn removed_device_outputs_are_detached() {
+    let (mut graph, output) = graph();
+    assert!(graph.remove_device_output("aux"));
+    assert!(!output.is_attached());
+    assert!(!graph.remove_device_output("aux"));
+    let device = DeviceConfig {
+        sample_hz: 48_000.0,
+        channels: 2,
+        buffer_size: 128,
+    };
+    assert!(graph.add_device_output("main", 44_100.0, device).is_none());
+}
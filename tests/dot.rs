@@ -0,0 +1,67 @@
+//! The **Graph** is exported to the Graphviz DOT language, optionally with per-node stats.
+
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+#[derive(Debug)]
+enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Passes its input through, reporting the length of its name as its latency.
+    Named(&'static str),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        if let Test::Dc(value) = *self {
+            for frame in buffer.iter_mut() {
+                *frame = [value];
+            }
+        }
+    }
+
+    fn latency(&self) -> usize {
+        match *self {
+            Test::Dc(_) => 0,
+            Test::Named(name) => name.len(),
+        }
+    }
+}
+
+/// A source feeding a named effect, which feeds back into it and is the output.
+fn graph() -> (Graph<Mono, Test>, dsp::NodeIndex, dsp::EdgeIndex) {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Dc(1.0));
+    let (edge, fx) = graph.add_output(src, Test::Named("a \"quoted\" name"));
+    graph.add_feedback_connection(fx, src).unwrap();
+    graph.set_master(Some(fx));
+    (graph, fx, edge)
+}
+
+#[test]
+fn nodes_and_connections_are_described() {
+    let (mut graph, _, edge) = graph();
+    graph.set_connection_enabled(edge, false).unwrap();
+    let dot = graph.to_dot();
+    let expected = r#"digraph {
+    node [shape=box];
+    0 [label="0: Dc(1.0)"];
+    1 [label="1: Named(\"a \\\"quoted\\\" name\")", peripheries=2, style=bold];
+    0 -> 1 [style=dashed];
+    1 -> 0 [style=dotted, constraint=false];
+}
+"#;
+    assert_eq!(dot, expected);
+}
+
+#[test]
+fn stats_label_the_latency_and_state_of_each_node() {
+    let (mut graph, fx, _) = graph();
+    graph.set_muted(fx, true).unwrap();
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    let dot = graph.to_dot_with_stats();
+    assert!(dot.contains("\\nlatency: 15\\nmuted"));
+    assert!(dot.contains("0: Dc(1.0)\\nlatency: 0\""));
+}
@@ -0,0 +1,73 @@
+//! Buffers whose number of channels is only known at runtime are rendered via **DynFrames**.
+
+use dsp::{from_dyn_frames, to_dyn_frames, DynFrames, Graph, Node};
+
+type Stereo = [f32; 2];
+
+/// Outputs the index of each channel, plus one.
+struct Ramp;
+
+impl Node<Stereo> for Ramp {
+    fn audio_requested(&mut self, buffer: &mut [Stereo], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [1.0, 2.0];
+        }
+    }
+}
+
+/// A graph with a single **Ramp** as its output.
+fn graph() -> Graph<Stereo, Ramp> {
+    let mut graph = Graph::new();
+    let ramp = graph.add_node(Ramp);
+    graph.set_master(Some(ramp));
+    graph
+}
+
+#[test]
+fn frames_are_strides_of_samples() {
+    let mut samples = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+    let mut frames = DynFrames::new(&mut samples, 3);
+    assert_eq!(frames.channels(), 3);
+    assert_eq!(frames.frames(), 2);
+    assert_eq!(frames.frame(1), &[3.0, 4.0, 5.0]);
+    frames.frame_mut(0)[2] = 9.0;
+    assert_eq!(frames.iter().count(), 2);
+    assert_eq!(frames.as_slice(), &[0.0, 1.0, 9.0, 3.0, 4.0, 5.0]);
+}
+
+#[test]
+#[should_panic]
+fn partial_frames_are_rejected() {
+    let mut samples = [0.0; 5];
+    DynFrames::new(&mut samples, 2);
+}
+
+#[test]
+fn missing_channels_are_equilibrium_and_extra_channels_are_dropped() {
+    let mut samples = [1.0, 2.0, 3.0];
+    let wide = DynFrames::new(&mut samples, 3);
+    let mut frames = [[0.0; 2]; 1];
+    from_dyn_frames(&wide, &mut frames);
+    assert_eq!(frames, [[1.0, 2.0]]);
+
+    let mut samples = [0.0; 3];
+    let mut wide = DynFrames::new(&mut samples, 3);
+    to_dyn_frames(&frames, &mut wide);
+    assert_eq!(wide.as_slice(), &[1.0, 2.0, 0.0]);
+}
+
+#[test]
+fn the_graph_follows_the_channels_of_the_buffer() {
+    let mut graph = graph();
+
+    let mut samples = [0.0; 4];
+    graph.audio_requested_dyn(DynFrames::new(&mut samples, 1), 44_100.0);
+    assert_eq!(samples, [1.0; 4]);
+    assert_eq!(graph.channels(), 1);
+
+    // Channels beyond the width of the frame are silent.
+    let mut samples = [9.0; 6];
+    graph.audio_requested_dyn(DynFrames::new(&mut samples, 3), 44_100.0);
+    assert_eq!(samples, [1.0, 2.0, 0.0, 1.0, 2.0, 0.0]);
+    assert_eq!(graph.channels(), 2);
+}
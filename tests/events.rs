@@ -0,0 +1,179 @@
+//! Events are queued for each node with a fixed capacity and delivered at the frame at which they
+//! take effect.
+
+use dsp::event::{Event, EventQueue, OverflowPolicy};
+use dsp::{Graph, Node, Notification};
+
+type Mono = [f32; 1];
+
+/// Records the events that it handles.
+struct Recorder(Vec<Event>);
+
+impl Node<Mono> for Recorder {
+    fn audio_requested(&mut self, _buffer: &mut [Mono], _sample_hz: f64) {}
+
+    fn handle_event(&mut self, event: &Event) {
+        self.0.push(*event);
+    }
+}
+
+/// Outputs a constant that is set by changes to its only parameter.
+struct Level(f32);
+
+impl Node<Mono> for Level {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { value, .. } = *event {
+            self.0 = value;
+        }
+    }
+}
+
+/// A change to the given parameter.
+fn param(param: usize, value: f32) -> Event {
+    Event::Param { param, value }
+}
+
+/// A full queue of the given policy holding changes to parameters `0` and `1`.
+fn full_queue(policy: OverflowPolicy) -> EventQueue {
+    let mut queue = EventQueue::new(2, policy);
+    assert!(queue.push(param(0, 0.0)));
+    assert!(queue.push(param(1, 0.0)));
+    queue
+}
+
+/// Drain the events of the given queue.
+fn drain(queue: &mut EventQueue) -> Vec<Event> {
+    std::iter::from_fn(|| queue.pop()).collect()
+}
+
+#[test]
+fn full_queues_drop_the_newest_event_by_default() {
+    let mut queue = full_queue(OverflowPolicy::default());
+    assert!(!queue.push(param(2, 0.0)));
+    assert_eq!(queue.take_dropped(), 1);
+    assert_eq!(queue.take_dropped(), 0);
+    assert_eq!(drain(&mut queue), vec![param(0, 0.0), param(1, 0.0)]);
+}
+
+#[test]
+fn full_queues_may_drop_the_oldest_event() {
+    let mut queue = full_queue(OverflowPolicy::DropOldest);
+    assert!(!queue.push(param(2, 0.0)));
+    assert_eq!(drain(&mut queue), vec![param(1, 0.0), param(2, 0.0)]);
+}
+
+#[test]
+fn full_queues_may_coalesce_changes() {
+    let mut queue = full_queue(OverflowPolicy::CoalesceControls);
+    assert!(queue.push(param(0, 1.0)));
+    assert!(!queue.push(param(2, 1.0)));
+    assert_eq!(queue.take_dropped(), 1);
+    assert_eq!(drain(&mut queue), vec![param(0, 1.0), param(1, 0.0)]);
+}
+
+#[test]
+fn events_are_delivered_in_order_and_drops_are_reported() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Recorder(Vec::new()));
+    graph.set_master(Some(node));
+    graph
+        .set_event_queue(node, 2, OverflowPolicy::DropNewest)
+        .unwrap();
+    assert!(graph.event_queue(node).unwrap().is_allocated());
+    let notifications = graph.subscribe_notifications();
+
+    for value in 0..3 {
+        graph.send_event(node, param(0, value as f32)).unwrap();
+    }
+    assert_eq!(graph.event_queue(node).unwrap().len(), 2);
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(graph[node].0, vec![param(0, 0.0), param(0, 1.0)]);
+    assert!(graph.event_queue(node).unwrap().is_empty());
+    let dropped: Vec<_> = notifications.try_iter().collect();
+    assert!(dropped.contains(&Notification::EventsDropped(node, 1)));
+}
+
+#[test]
+fn queued_events_are_ordered_by_frame() {
+    let mut queue = EventQueue::new(4, OverflowPolicy::default());
+    queue.push_at(8, param(0, 2.0));
+    queue.push_at(4, param(0, 0.0));
+    queue.push_at(4, param(0, 1.0));
+    assert_eq!(queue.next_frame(), Some(4));
+    assert_eq!(queue.pop_before(4), None);
+    assert_eq!(queue.pop_before(5), Some(param(0, 0.0)));
+    assert_eq!(queue.pop_before(5), Some(param(0, 1.0)));
+    assert_eq!(queue.pop_before(5), None);
+}
+
+#[test]
+fn events_take_effect_at_their_frame() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Level(0.0));
+    graph.set_master(Some(node));
+    graph.schedule_event(node, 3, param(0, 1.0)).unwrap();
+    graph.schedule_event(node, 10, param(0, 2.0)).unwrap();
+
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(
+        buffer,
+        [[0.0], [0.0], [0.0], [1.0], [1.0], [1.0], [1.0], [1.0]]
+    );
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(
+        buffer,
+        [[1.0], [1.0], [2.0], [2.0], [2.0], [2.0], [2.0], [2.0]]
+    );
+
+    // Events for frames that have already been rendered take effect at the next request.
+    graph.schedule_event(node, 0, param(0, 3.0)).unwrap();
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[3.0]; 8]);
+}
+
+#[test]
+fn only_the_first_and_last_change_within_a_range_are_kept() {
+    let mut queue = EventQueue::new(8, OverflowPolicy::default());
+    for value in 0..4 {
+        queue.push_at(value, param(0, value as f32));
+    }
+    queue.push_at(2, param(1, 0.0));
+    queue.push_at(8, param(0, 8.0));
+    assert_eq!(queue.coalesce_within(0..8), 2);
+    assert_eq!(
+        drain(&mut queue),
+        vec![param(0, 0.0), param(1, 0.0), param(0, 3.0), param(0, 8.0)]
+    );
+}
+
+#[test]
+fn the_graph_coalesces_changes_when_enabled() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Recorder(Vec::new()));
+    graph.set_master(Some(node));
+    assert!(!graph.coalesces_events());
+    graph.set_coalesce_events(true);
+
+    let note = Event::NoteOn {
+        channel: 0,
+        note: 60,
+        velocity: 1.0,
+    };
+    for value in 0..4 {
+        graph
+            .schedule_event(node, value, param(0, value as f32))
+            .unwrap();
+    }
+    graph.schedule_event(node, 1, note).unwrap();
+    let mut buffer = [[0.0]; dsp::SMOOTHING_INTERVAL];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(graph[node].0, vec![param(0, 0.0), note, param(0, 3.0)]);
+}
@@ -0,0 +1,76 @@
+//! The **Expander** attenuates quiet signals, keyed by its own input or by a sidechain.
+#![cfg(feature = "dynamics")]
+
+use dsp::nodes::Expander;
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// An expander with a threshold of -20dB and a ratio of 4 that reacts instantly.
+fn expander() -> Expander {
+    let mut expander = Expander::new(-20.0, 4.0);
+    expander.attack_ms = 0.0;
+    expander.release_ms = 0.0;
+    expander
+}
+
+/// Expand a buffer of the given constant level by its own level, returning the last frame.
+fn expand(expander: &mut Expander, level: f32) -> f32 {
+    let mut buffer = [[level]; 16];
+    Node::<Mono>::audio_requested(expander, &mut buffer, SAMPLE_HZ);
+    buffer[15][0]
+}
+
+#[test]
+fn signals_above_the_threshold_pass_unchanged() {
+    let mut expander = expander();
+    assert!((expand(&mut expander, 0.5) - 0.5).abs() < 1e-4);
+    assert!(expander.is_open());
+}
+
+#[test]
+fn signals_below_the_threshold_are_expanded_within_the_range() {
+    let mut expander = expander();
+    // -40dB is 20dB below the threshold, expanded by a ratio of 4 to 60dB of attenuation.
+    expand(&mut expander, 0.01);
+    assert!(!expander.is_open());
+    assert!((expander.gain_db() + 60.0).abs() < 0.1);
+
+    expander.range_db = 30.0;
+    expand(&mut expander, 0.01);
+    assert!((expander.gain_db() + 30.0).abs() < 0.1);
+}
+
+#[test]
+fn hysteresis_keeps_the_expander_open_just_below_the_threshold() {
+    let mut expander = expander();
+    expander.hysteresis_db = 6.0;
+    expand(&mut expander, 0.5);
+    // -23dB is below the threshold but within the hysteresis.
+    let level = 10.0f32.powf(-23.0 / 20.0);
+    assert!((expand(&mut expander, level) - level).abs() < 1e-4);
+    assert!(expander.is_open());
+    expand(&mut expander, 0.01);
+    assert!(!expander.is_open());
+
+    // Once closed, it stays closed until the level reaches the threshold once more.
+    expand(&mut expander, level);
+    assert!(!expander.is_open());
+}
+
+#[test]
+fn the_sidechain_key_controls_the_gain() {
+    let mut expander = expander();
+    let mut buffer: [Mono; 4] = [[0.5]; 4];
+    expander.process(&mut buffer, &[[0.01]; 4], SAMPLE_HZ);
+    assert!(buffer[3][0] < 0.001);
+    let mut buffer: [Mono; 4] = [[0.01]; 4];
+    expander.process(&mut buffer, &[[0.5]; 4], SAMPLE_HZ);
+    assert!((buffer[3][0] - 0.01).abs() < 1e-6);
+
+    expander.reset();
+    assert!(!expander.is_open());
+    assert_eq!(expander.gain_db(), 0.0);
+}
@@ -0,0 +1,65 @@
+//! Named external inputs and outputs exchange audio between the **Graph** and its host.
+
+use dsp::{ExternalKind, Graph, Node};
+
+type Mono = [f32; 1];
+
+enum Test {
+    /// Passes its input through.
+    Pass,
+    /// Scales its input.
+    Gain(f32),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        if let Test::Gain(gain) = *self {
+            for frame in buffer.iter_mut() {
+                frame[0] *= gain;
+            }
+        }
+    }
+}
+
+/// A microphone input feeding both the master and a separate cue output.
+fn graph() -> Graph<Mono, Test> {
+    let mut graph = Graph::new();
+    let mic = graph.add_external_input("mic", 1, Test::Pass);
+    let (_, master) = graph.add_output(mic, Test::Gain(0.5));
+    let cue = graph.add_external_output("cue", 1, Test::Gain(2.0));
+    graph.add_connection(mic, cue).unwrap();
+    graph.set_master(Some(master));
+    graph
+}
+
+#[test]
+fn externals_are_found_by_name() {
+    let graph = graph();
+    assert_eq!(graph.externals().len(), 2);
+    let mic = graph.external("mic").unwrap();
+    assert_eq!(mic.kind, ExternalKind::Input);
+    assert_eq!(mic.channels, 1);
+    assert_eq!(graph.external("cue").unwrap().kind, ExternalKind::Output);
+    assert!(graph.external("missing").is_none());
+}
+
+#[test]
+fn audio_flows_through_the_externals() {
+    let mut graph = graph();
+    assert!(graph.write_external_input("mic", &[[1.0]; 4]));
+    assert!(!graph.write_external_input("cue", &[[1.0]; 4]));
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.5]; 4]);
+
+    // The output is rendered even though it does not feed the master.
+    assert_eq!(graph.external_output_buffer("cue"), Some(&[[2.0]; 4][..]));
+    assert!(graph.external_output_buffer("mic").is_none());
+}
+
+#[test]
+#[should_panic]
+fn names_are_unique() {
+    let mut graph = graph();
+    graph.add_external_output("mic", 1, Test::Pass);
+}
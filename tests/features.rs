@@ -0,0 +1,44 @@
+//! The graph core builds without the node library, whose nodes are enabled by feature.
+
+use dsp::nodes::MidSide;
+use dsp::{BoxedNodeSend, Graph, Node};
+
+type Stereo = [f32; 2];
+
+/// Scales its input.
+struct Gain(f32);
+
+impl Node<[f32; 1]> for Gain {
+    fn audio_requested(&mut self, buffer: &mut [[f32; 1]], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] *= self.0;
+        }
+    }
+}
+
+#[test]
+fn the_core_utilities_are_always_available() {
+    let mut graph: Graph<Stereo, BoxedNodeSend<Stereo>> = Graph::new();
+    let node = graph.add_node(Box::new(MidSide::new(Gain(1.0), Gain(0.0))));
+    let mut buffer = [[1.0, 0.0]; 4];
+    graph[node].audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.5; 2]; 4]);
+}
+
+#[cfg(feature = "full")]
+#[test]
+fn the_full_node_library_is_enabled_by_default() {
+    use dsp::nodes::{Expander, MultiBand, PhaseMeter};
+
+    let mut graph: Graph<Stereo, BoxedNodeSend<Stereo>> = Graph::new();
+    let expander = graph.add_node(Box::new(Expander::new(-40.0, 2.0)));
+    let (_, bands) = graph.add_output(
+        expander,
+        Box::new(MultiBand::new(Expander::new(-40.0, 2.0), &[500.0])),
+    );
+    let (_, meter) = graph.add_output(bands, Box::new(PhaseMeter::new(0)));
+    graph.set_master(Some(meter));
+    let mut buffer = [[0.0; 2]; 64];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.0; 2]; 64]);
+}
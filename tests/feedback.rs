@@ -0,0 +1,69 @@
+//! Feedback connections deliver a node's output one buffer late, and double-buffered connections
+//! keep the previous buffer available.
+
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// Adds a constant to its input.
+struct Add(f32);
+
+impl Node<Mono> for Add {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] += self.0;
+        }
+    }
+}
+
+/// Render a buffer of two frames, returning its first frame.
+fn render(graph: &mut Graph<Mono, Add>) -> f32 {
+    let mut buffer = [[0.0]; 2];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    buffer[0][0]
+}
+
+#[test]
+fn feedback_arrives_one_buffer_late() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Add(1.0));
+    let (_, b) = graph.add_output(a, Add(0.0));
+    graph.add_feedback_connection(b, a).unwrap();
+    graph.set_master(Some(b));
+    let outputs: Vec<_> = (0..3).map(|_| render(&mut graph)).collect();
+    assert_eq!(outputs, [1.0, 2.0, 3.0]);
+
+    assert!(graph.remove_feedback_connection(b, a));
+    assert!(!graph.remove_feedback_connection(b, a));
+    assert_eq!(render(&mut graph), 1.0);
+}
+
+#[test]
+fn feedback_connections_are_removed_with_their_nodes() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Add(1.0));
+    graph.add_feedback_connection(a, a).unwrap();
+    assert_eq!(graph.feedback_connections().len(), 1);
+    graph.remove_node(a);
+    assert!(graph.feedback_connections().is_empty());
+}
+
+#[test]
+fn double_buffered_connections_keep_the_previous_buffer() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Add(1.0));
+    let (edge, b) = graph.add_output(a, Add(0.0));
+    graph.add_feedback_connection(b, a).unwrap();
+    graph.set_master(Some(b));
+    graph.set_double_buffered(true);
+    assert!(graph.is_double_buffered());
+    for _ in 0..3 {
+        render(&mut graph);
+    }
+    assert_eq!(graph[edge].previous_buffer(), &[[2.0], [2.0]]);
+
+    graph.set_double_buffered(false);
+    assert!(graph[edge].previous_buffer().is_empty());
+}
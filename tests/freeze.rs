@@ -0,0 +1,92 @@
+//! Series of nodes are frozen into a single **Chain** node and chains are expanded into nodes.
+
+use dsp::nodes::Chain;
+use dsp::{BoxedNodeSend, Graph, Node};
+
+type Mono = [f32; 1];
+
+/// Adds a constant to its input.
+struct Offset(f32);
+
+/// Scales its input.
+struct Gain(f32);
+
+impl Node<Mono> for Offset {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] += self.0;
+        }
+    }
+}
+
+impl Node<Mono> for Gain {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] *= self.0;
+        }
+    }
+}
+
+/// Box the given node.
+fn boxed<N: Node<Mono> + Send + 'static>(node: N) -> BoxedNodeSend<Mono> {
+    Box::new(node)
+}
+
+/// Render a buffer of `4` frames, returning its first frame.
+fn render(graph: &mut Graph<Mono, BoxedNodeSend<Mono>>) -> f32 {
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    buffer[0][0]
+}
+
+#[test]
+fn frozen_series_render_the_same() {
+    let mut graph = Graph::new();
+    let source = graph.add_node(boxed(Offset(1.0)));
+    let (_, a) = graph.add_output(source, boxed(Gain(2.0)));
+    let (_, b) = graph.add_output(a, boxed(Offset(3.0)));
+    let (_, c) = graph.add_output(b, boxed(Gain(4.0)));
+    graph.set_master(Some(c));
+    assert_eq!(render(&mut graph), 20.0);
+
+    let frozen = graph.freeze_chain(&[a, b, c]).unwrap();
+    assert_eq!(graph.node_count(), 2);
+    assert_eq!(graph.master_index(), Some(frozen));
+    assert!(graph.find_connection(source, frozen).is_some());
+    assert_eq!(render(&mut graph), 20.0);
+}
+
+#[test]
+fn only_series_of_single_connections_are_frozen() {
+    let mut graph = Graph::new();
+    let source = graph.add_node(boxed(Offset(1.0)));
+    let (_, a) = graph.add_output(source, boxed(Gain(2.0)));
+    let (_, b) = graph.add_output(a, boxed(Gain(3.0)));
+    let (_, branch) = graph.add_output(a, boxed(Gain(4.0)));
+
+    assert!(graph.freeze_chain(&[]).is_none());
+    assert!(graph.freeze_chain(&[a, b, a]).is_none());
+    assert!(graph.freeze_chain(&[a, b]).is_none());
+    assert!(graph.freeze_chain(&[b, branch]).is_none());
+    graph.remove_node(branch);
+    graph.add_feedback_connection(b, a).unwrap();
+    assert!(graph.freeze_chain(&[a, b]).is_none());
+    assert_eq!(graph.node_count(), 3);
+}
+
+#[test]
+fn chains_are_expanded_into_a_series_of_nodes() {
+    let mut graph = Graph::new();
+    let chain = Chain::new((boxed(Offset(1.0)), boxed(Gain(5.0)), boxed(Offset(-2.0))));
+    let (nodes, edges) = graph.expand_chain(chain);
+    assert_eq!((nodes.len(), edges.len()), (3, 2));
+    graph.set_master(Some(nodes[2]));
+    assert_eq!(render(&mut graph), 3.0);
+
+    let frozen = graph.freeze_chain(&nodes).unwrap();
+    assert_eq!(render(&mut graph), 3.0);
+    let (nodes, _) = graph.expand_chain(Chain::new(vec![boxed(Gain(2.0))]));
+    graph.add_connection(frozen, nodes[0]).unwrap();
+    graph.set_master(Some(nodes[0]));
+    assert_eq!(render(&mut graph), 6.0);
+}
@@ -0,0 +1,74 @@
+//! The **GraphicEq** boosts or cuts each of its fixed ISO bands.
+
+#![cfg(feature = "filters")]
+
+use dsp::event::Event;
+use dsp::nodes::GraphicEq;
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// The peak level of a sine at `hz` once the equaliser has settled.
+fn peak(eq: &mut GraphicEq, hz: f64) -> f32 {
+    let mut buffer: Vec<Mono> = (0..SAMPLE_HZ as usize / 4)
+        .map(|i| [(i as f64 * hz * std::f64::consts::TAU / SAMPLE_HZ).sin() as f32])
+        .collect();
+    eq.audio_requested(&mut buffer, SAMPLE_HZ);
+    let settled = &buffer[buffer.len() / 2..];
+    settled
+        .iter()
+        .fold(0.0, |peak, frame| frame[0].abs().max(peak))
+}
+
+/// Send the given gain for the given band as a parameter event.
+fn send_gain(eq: &mut GraphicEq, band: usize, gain_db: f32) {
+    let event = Event::Param {
+        param: band,
+        value: gain_db,
+    };
+    Node::<Mono>::handle_event(eq, &event);
+}
+
+#[test]
+fn bands_are_centred_on_the_iso_frequencies() {
+    let octave = GraphicEq::octave();
+    assert_eq!(octave.num_bands(), 10);
+    assert_eq!(octave.frequencies()[0], 31.5);
+    assert_eq!(octave.frequencies()[9], 16_000.0);
+    assert_eq!(octave.gains_db(), &[0.0; 10]);
+
+    let third = GraphicEq::third_octave();
+    assert_eq!(third.num_bands(), 31);
+    assert_eq!(third.frequencies()[17], 1_000.0);
+    assert_eq!(third.gain_db(31), None);
+}
+
+#[test]
+fn boosted_bands_only_affect_their_own_frequencies() {
+    let mut eq = GraphicEq::octave();
+    assert!((peak(&mut eq, 1_000.0) - 1.0).abs() < 1e-3);
+
+    eq.set_gain_db(5, 12.0);
+    assert_eq!(eq.gain_db(5), Some(12.0));
+    let boosted = peak(&mut eq, 1_000.0);
+    assert!((boosted - 3.98).abs() < 0.05, "{}", boosted);
+    eq.reset();
+    assert!((peak(&mut eq, 63.0) - 1.0).abs() < 0.02);
+
+    eq.flatten();
+    assert_eq!(eq.gains_db(), &[0.0; 10]);
+    assert!((peak(&mut eq, 1_000.0) - 1.0).abs() < 1e-3);
+}
+
+#[test]
+fn gains_are_set_via_param_events() {
+    let mut eq = GraphicEq::octave();
+    send_gain(&mut eq, 2, -6.0);
+    assert_eq!(eq.gain_db(2), Some(-6.0));
+    send_gain(&mut eq, 10, 6.0);
+    assert_eq!(eq.gains_db().iter().filter(|&&db| db != 0.0).count(), 1);
+    let cut = peak(&mut eq, 125.0);
+    assert!((cut - 0.5).abs() < 0.02, "{}", cut);
+}
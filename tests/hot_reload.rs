@@ -0,0 +1,102 @@
+//! Edited patches are reloaded into a running **Graph**, preserving the nodes that did not change.
+
+use dsp::description::{Describe, GraphDescription, HotReload, NodeFactory, Value};
+use dsp::{Graph, GraphSwap, Node};
+
+type Mono = [f32; 1];
+
+#[derive(Debug)]
+enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Adds a count that rises by one each frame to its input, so that its state is audible.
+    Counter(f32),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = match self {
+                Test::Dc(value) => [*value],
+                Test::Counter(count) => {
+                    *count += 1.0;
+                    [frame[0] + *count]
+                }
+            };
+        }
+    }
+}
+
+impl Describe for Test {
+    fn type_name(&self) -> &str {
+        match *self {
+            Test::Dc(_) => "dc",
+            Test::Counter(_) => "counter",
+        }
+    }
+
+    fn params(&self) -> Value {
+        match *self {
+            Test::Dc(value) => value.into(),
+            Test::Counter(_) => Value::Null,
+        }
+    }
+}
+
+/// A factory for all nodes of the `Test` type.
+fn factory() -> NodeFactory<Test> {
+    NodeFactory::new()
+        .with("dc", |params| {
+            let value = params.as_f64().ok_or("expected a number")?;
+            Ok(Test::Dc(value as f32))
+        })
+        .with("counter", |_| Ok(Test::Counter(0.0)))
+}
+
+/// A patch of a constant feeding a counter.
+fn patch(value: f32) -> String {
+    format!(
+        r#"{{
+            "nodes": [{{ "type": "dc", "params": {} }}, {{ "type": "counter" }}],
+            "connections": [{{ "source": 0, "destination": 1 }}],
+            "master": 1
+        }}"#,
+        value
+    )
+}
+
+/// Render a buffer of `2` frames.
+fn render(swap: &mut GraphSwap<Mono, Test>) -> [Mono; 2] {
+    let mut buffer = [[0.0]; 2];
+    swap.audio_requested(&mut buffer, 44_100.0);
+    buffer
+}
+
+#[test]
+fn unchanged_nodes_keep_their_state() {
+    let description = GraphDescription::from_json(&patch(10.0)).unwrap();
+    let graph = Graph::from_description(&description, &factory()).unwrap();
+    let (mut swap, handle) = GraphSwap::new(graph, 0);
+    let mut reload = HotReload::new(handle, factory(), description);
+    assert_eq!(render(&mut swap), [[11.0], [12.0]]);
+
+    assert_eq!(reload.reload_json(&patch(20.0)), Ok(true));
+    assert_eq!(render(&mut swap), [[23.0], [24.0]]);
+    assert_eq!(reload.description().nodes[0].params, Value::from(20.0));
+    assert_eq!(reload.handle().retired().count(), 1);
+}
+
+#[test]
+fn failed_reloads_leave_the_running_graph_untouched() {
+    let description = GraphDescription::from_json(&patch(10.0)).unwrap();
+    let graph = Graph::from_description(&description, &factory()).unwrap();
+    let (mut swap, handle) = GraphSwap::new(graph, 0);
+    let mut reload = HotReload::new(handle, factory(), description.clone());
+    render(&mut swap);
+
+    assert!(reload.reload_json("{").is_err());
+    let unknown = r#"{ "nodes": [{ "type": "reverb" }] }"#;
+    assert!(reload.reload_json(unknown).is_err());
+    assert_eq!(reload.description(), &description);
+    assert_eq!(render(&mut swap), [[13.0], [14.0]]);
+}
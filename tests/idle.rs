@@ -0,0 +1,107 @@
+//! An idle **Graph** fills requests with silence until something becomes due.
+
+use dsp::event::Event;
+use dsp::{Graph, Node, NodeIndex, Notification};
+
+type Mono = [f32; 1];
+
+/// Outputs a level that is set by its only parameter, and is silent while the level is zero.
+struct Level(f32);
+
+impl Node<Mono> for Level {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+
+    fn is_silent(&self) -> bool {
+        self.0 == 0.0
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { value, .. } = *event {
+            self.0 = value;
+        }
+    }
+}
+
+/// A **Level** node at the given level as the output.
+fn graph(level: f32) -> (Graph<Mono, Level>, NodeIndex) {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Level(level));
+    graph.set_master(Some(node));
+    (graph, node)
+}
+
+/// Render `count` buffers of `16` frames, returning the last.
+fn render(graph: &mut Graph<Mono, Level>, count: usize) -> [Mono; 16] {
+    let mut buffer = [[1.0]; 16];
+    for _ in 0..count {
+        buffer = [[1.0]; 16];
+        graph.audio_requested(&mut buffer, 44_100.0);
+    }
+    buffer
+}
+
+/// A change to the level.
+fn level(value: f32) -> Event {
+    Event::Param { param: 0, value }
+}
+
+#[test]
+fn quiet_graphs_become_idle_after_the_threshold() {
+    let (mut graph, _) = graph(0.0);
+    graph.idle_detect(Some(3));
+    assert_eq!(graph.idle_threshold_blocks(), Some(3));
+    let notifications = graph.subscribe_notifications();
+
+    render(&mut graph, 2);
+    assert!(!graph.is_idle());
+    render(&mut graph, 1);
+    assert!(graph.is_idle());
+    let received: Vec<_> = notifications.try_iter().collect();
+    assert_eq!(received, vec![Notification::Idle]);
+
+    // While idle, the output is silent and the transport advances.
+    assert_eq!(render(&mut graph, 4), [[0.0]; 16]);
+    assert!(graph.is_idle());
+    assert_eq!(graph.position(), 7 * 16);
+    assert_eq!(notifications.try_iter().count(), 0);
+}
+
+#[test]
+fn events_resume_rendering() {
+    let (mut graph, node) = graph(0.0);
+    graph.idle_detect(Some(1));
+    render(&mut graph, 1);
+    assert!(graph.is_idle());
+    let notifications = graph.subscribe_notifications();
+
+    graph.send_event(node, level(0.5)).unwrap();
+    assert_eq!(render(&mut graph, 1), [[0.5]; 16]);
+    assert!(!graph.is_idle());
+    let received: Vec<_> = notifications.try_iter().collect();
+    assert_eq!(received, vec![Notification::Resumed]);
+
+    // The audible node keeps the graph from becoming idle again until it falls silent.
+    render(&mut graph, 4);
+    assert!(!graph.is_idle());
+    graph.schedule_event(node, 100, level(0.0)).unwrap();
+    render(&mut graph, 2);
+    assert!(graph.is_idle());
+}
+
+#[test]
+fn nothing_becomes_idle_while_disabled() {
+    let (mut graph, _) = graph(0.0);
+    assert_eq!(graph.idle_threshold_blocks(), None);
+    render(&mut graph, 8);
+    assert!(!graph.is_idle());
+
+    graph.idle_detect(Some(1));
+    render(&mut graph, 1);
+    assert!(graph.is_idle());
+    graph.idle_detect(None);
+    assert!(!graph.is_idle());
+}
@@ -0,0 +1,33 @@
+//! Graphs with narrower index types behave the same as the default **Graph**.
+
+mod common;
+
+use common::{Mono, Test};
+use dsp::{Graph, Graph16, Graph32};
+
+/// Build a source scaled by a gain and render a buffer from it, returning its first frame.
+fn render<Ix>(mut graph: Graph<Mono, Test, Ix>) -> f32
+where
+    Ix: dsp::IndexType,
+{
+    let gain = graph.add_node(Test::Gain(0.5));
+    graph.add_input(Test::Dc(3.0), gain);
+    graph.set_master(Some(gain));
+    common::render(&mut graph)
+}
+
+#[test]
+fn every_index_type_renders_alike() {
+    let expected = render(Graph::new());
+    assert_eq!(expected, 1.5);
+    assert_eq!(render(Graph32::with_capacity_indexed(2, 1, 4)), expected);
+    assert_eq!(render(Graph16::default()), expected);
+}
+
+#[test]
+fn index_types_limit_the_graph_size() {
+    let graph16: Graph16<Mono, Test> = Graph16::default();
+    let graph32: Graph32<Mono, Test> = Graph32::default();
+    assert_eq!(graph16.max_node_count(), 65_535);
+    assert_eq!(graph32.max_node_count(), u32::MAX as usize);
+}
@@ -0,0 +1,73 @@
+//! Inserting a node into a connection behaves like removing it and adding the new connections.
+
+use dsp::raw::Walker;
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+enum Test {
+    Constant(f32),
+    Scale(f32),
+    Pass,
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            match *self {
+                Test::Constant(value) => *frame = [value],
+                Test::Scale(amp) => frame[0] *= amp,
+                Test::Pass => (),
+            }
+        }
+    }
+}
+
+#[test]
+fn insert_between_rewires_the_connection() {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Constant(1.0));
+    let (edge, dest) = graph.add_output(src, Test::Pass);
+    graph.set_master(Some(dest));
+    let (src_edge, node, dest_edge) = graph.insert_between(edge, Test::Scale(0.5)).unwrap();
+    assert_eq!(graph.connection_count(), 2);
+    assert_eq!(graph.find_connection(src, node), Some(src_edge));
+    assert_eq!(graph.find_connection(node, dest), Some(dest_edge));
+    assert_eq!(graph.find_connection(src, dest), None);
+    assert_eq!(graph.inputs(dest).iter(&graph).count(), 1);
+    assert!(graph.validate().is_valid());
+
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.5]; 4]);
+}
+
+#[test]
+fn insert_between_crossfades_like_remove_edge() {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Constant(1.0));
+    let (edge, dest) = graph.add_output(src, Test::Pass);
+    graph.set_master(Some(dest));
+    graph.set_connection_ramp_frames(8);
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer[7], [1.0]);
+
+    // The direct path fades out as the path through the new node fades in.
+    graph.insert_between(edge, Test::Scale(0.5)).unwrap();
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert!(buffer[0][0] > 0.8, "{:?}", buffer);
+    assert!(buffer.windows(2).all(|w| w[1][0] <= w[0][0]));
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.5]; 8]);
+}
+
+#[test]
+fn insert_between_stale_connection_is_an_error() {
+    let mut graph: Graph<Mono, Test> = Graph::new();
+    let src = graph.add_node(Test::Constant(1.0));
+    let (edge, _) = graph.add_output(src, Test::Pass);
+    assert!(graph.remove_edge(edge));
+    assert!(graph.insert_between(edge, Test::Pass).is_err());
+    assert_eq!(graph.node_count(), 2);
+}
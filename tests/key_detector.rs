@@ -0,0 +1,91 @@
+//! The **KeyDetector** estimates the key and chord of its input from its chroma.
+
+#![cfg(feature = "analysis")]
+
+use dsp::nodes::{Chord, KeyDetector, KeyEvent, Mode};
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// One second of the given frequencies played together.
+fn triad(hz: [f64; 3]) -> Vec<Mono> {
+    (0..SAMPLE_HZ as usize)
+        .map(|i| {
+            let t = i as f64 * std::f64::consts::TAU / SAMPLE_HZ;
+            [hz.iter().map(|hz| 0.2 * (t * hz).sin()).sum::<f64>() as f32]
+        })
+        .collect()
+}
+
+const C_MAJOR: [f64; 3] = [261.63, 329.63, 392.0];
+const A_MINOR: [f64; 3] = [220.0, 261.63, 329.63];
+
+#[test]
+fn chords_and_keys_are_estimated_from_the_chroma() {
+    let (mut detector, events) = KeyDetector::new(8192, 4096);
+    let mut buffer = triad(C_MAJOR);
+    let input = buffer.clone();
+    detector.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer, input);
+
+    let c_major = Chord {
+        root: 0,
+        mode: Mode::Major,
+    };
+    assert_eq!(detector.chord(), Some(c_major));
+    assert_eq!(c_major.pitch_classes(), [0, 4, 7]);
+    let key = detector.key().unwrap();
+    assert_eq!((key.name(), key.mode), ("C", Mode::Major));
+
+    let received: Vec<_> = events.try_iter().collect();
+    assert!(received.iter().any(|event| match *event {
+        KeyEvent::Chord { chord, .. } => chord == c_major,
+        _ => false,
+    }));
+    assert!(received
+        .iter()
+        .any(|event| matches!(event, KeyEvent::Key { .. })));
+}
+
+#[test]
+fn changes_of_chord_are_published_once() {
+    let (mut detector, events) = KeyDetector::new(8192, 4096);
+    detector.process(&triad(C_MAJOR), SAMPLE_HZ);
+    events.try_iter().for_each(drop);
+    detector.process(&triad(A_MINOR), SAMPLE_HZ);
+
+    let a_minor = Chord {
+        root: 9,
+        mode: Mode::Minor,
+    };
+    assert_eq!(detector.chord(), Some(a_minor));
+    assert_eq!(a_minor.name(), "A");
+    let chords: Vec<_> = events
+        .try_iter()
+        .filter_map(|event| match event {
+            KeyEvent::Chord {
+                chord, position, ..
+            } => Some((chord, position)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(chords.last().map(|&(chord, _)| chord), Some(a_minor));
+    assert!(chords
+        .iter()
+        .all(|&(_, position)| position > SAMPLE_HZ as u64));
+}
+
+#[test]
+fn silence_is_not_estimated() {
+    let (mut detector, events) = KeyDetector::new(8192, 4096);
+    detector.process(&[[0.0]; 16_384], SAMPLE_HZ);
+    assert_eq!(detector.key(), None);
+    assert_eq!(detector.chord(), None);
+    assert_eq!(events.try_iter().count(), 0);
+
+    detector.process(&triad(C_MAJOR), SAMPLE_HZ);
+    detector.reset();
+    assert_eq!(detector.chord(), None);
+}
@@ -0,0 +1,120 @@
+//! Plugin delay compensation aligns converging paths without allocating while rendering.
+
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+enum Test {
+    /// Outputs a single impulse on the first frame it renders.
+    Impulse(bool),
+    /// Delays its input by the length of its line, reporting that as its latency.
+    Delay(Vec<Mono>),
+    Pass,
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        match self {
+            Test::Impulse(done) => {
+                if !*done {
+                    buffer[0] = [1.0];
+                    *done = true;
+                }
+            }
+            Test::Delay(line) => {
+                for frame in buffer.iter_mut() {
+                    line.push(*frame);
+                    *frame = line.remove(0);
+                }
+            }
+            Test::Pass => (),
+        }
+    }
+
+    fn latency(&self) -> usize {
+        match self {
+            Test::Delay(line) => line.len(),
+            _ => 0,
+        }
+    }
+}
+
+/// An impulse that reaches `mix` both via a node with a latency of `3` and via a node without.
+fn converging() -> (Graph<Mono, Test>, dsp::EdgeIndex) {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Impulse(false));
+    let mix = graph.add_node(Test::Pass);
+    let (_, slow) = graph.add_output(src, Test::Delay(vec![[0.0]; 3]));
+    let (_, fast) = graph.add_output(src, Test::Pass);
+    let slow_edge = graph.add_connection(slow, mix).unwrap();
+    graph.add_connection(fast, mix).unwrap();
+    graph.set_master(Some(mix));
+    (graph, slow_edge)
+}
+
+#[test]
+fn converging_paths_are_aligned() {
+    let (mut graph, _) = converging();
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer[3], [2.0]);
+    assert_eq!(buffer.iter().filter(|f| f[0] != 0.0).count(), 1);
+    let master = graph.master_index().unwrap();
+    assert_eq!(graph.path_latency(master), Some(3));
+}
+
+#[test]
+fn latency_is_known_before_rendering() {
+    let (graph, _) = converging();
+    assert_eq!(Node::<Mono>::latency(&graph), 3);
+}
+
+#[test]
+fn prepared_compensation_does_not_allocate() {
+    let (mut graph, slow_edge) = converging();
+    graph.prepare_buffers(4);
+    graph.set_assert_no_alloc(true);
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer[3], [2.0]);
+
+    // Toggling the slower path changes the compensation without allocating.
+    graph.set_connection_enabled(slow_edge, false).unwrap();
+    graph.audio_requested(&mut buffer, 44_100.0);
+    graph.set_connection_enabled(slow_edge, true).unwrap();
+    graph.audio_requested(&mut buffer, 44_100.0);
+}
+
+#[test]
+fn replacements_are_compensated_for_their_own_latency() {
+    let (mut graph, slow_edge) = converging();
+    let mix = graph.master_index().unwrap();
+    let fast_edge = graph.find_connection(dsp::NodeIndex::new(3), mix).unwrap();
+    graph.prepare_buffers(8);
+    graph.set_assert_no_alloc(true);
+    let slow = graph.raw_edges()[slow_edge.index()].source();
+    graph.replace_node(slow, Test::Delay(vec![[0.0]; 5]));
+    assert_eq!(graph.path_latency(mix), Some(5));
+
+    // The faster path's delay line was grown for the new latency ahead of rendering.
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(graph.connection_compensation(fast_edge), Some(5));
+    assert_eq!(graph.connection_compensation(slow_edge), Some(0));
+}
+
+#[test]
+fn audio_in_flight_survives_compensation_changes() {
+    let (mut graph, slow_edge) = converging();
+    let mut buffer = [[0.0]; 2];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.0]; 2]);
+
+    // With only the faster path left its delay is faded out rather than dropped, so the impulse
+    // that was already delayed still arrives.
+    graph.set_connection_enabled(slow_edge, false).unwrap();
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer[0], [0.0]);
+    assert!(buffer[1][0] > 0.9, "{:?}", buffer);
+    assert_eq!(graph.connection_compensation(slow_edge), Some(0));
+}
@@ -0,0 +1,64 @@
+//! The **LatencyProbe** measures the round-trip latency from an output to an input.
+
+use dsp::io::LatencyProbe;
+use dsp::Node;
+use std::collections::VecDeque;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// Render blocks of `64` frames through a loopback that delays the output by `latency` frames
+/// and scales it by `gain` before it returns as the input, until the measurement has finished.
+fn loopback(probe: &mut LatencyProbe, latency: usize, gain: f32) {
+    let mut returning = VecDeque::from(vec![0.0; latency]);
+    let mut buffer: [Mono; 64] = [[0.0]; 64];
+    loop {
+        for frame in buffer.iter_mut() {
+            *frame = [returning.pop_front().unwrap()];
+        }
+        probe.audio_requested(&mut buffer, SAMPLE_HZ);
+        returning.extend(buffer.iter().map(|frame| frame[0] * gain));
+        if !probe.is_running() {
+            break;
+        }
+    }
+}
+
+#[test]
+fn the_round_trip_latency_is_measured() {
+    let mut probe = LatencyProbe::new(SAMPLE_HZ, 4_096);
+    assert_eq!(probe.max_latency_frames(), 4_096);
+    assert_eq!(probe.measurement(), None);
+    probe.start();
+    loopback(&mut probe, 1_234, 0.3);
+
+    let measurement = probe.measurement().unwrap();
+    assert_eq!(measurement.frames, 1_234);
+    assert!(measurement.confidence > 0.9);
+    assert!((measurement.seconds(SAMPLE_HZ) - 1_234.0 / SAMPLE_HZ).abs() < 1e-12);
+}
+
+#[test]
+fn handles_start_measurements_and_read_their_results() {
+    let mut probe = LatencyProbe::new(SAMPLE_HZ, 2_048);
+    let handle = probe.handle();
+    let mut buffer = [[0.5]; 64];
+    probe.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer, [[0.0]; 64]);
+
+    handle.start();
+    assert!(handle.is_running());
+    assert!(!probe.is_running());
+    loopback(&mut probe, 100, 1.0);
+    assert!(!handle.is_running());
+    assert_eq!(handle.measurement().map(|m| m.frames), Some(100));
+}
+
+#[test]
+fn nothing_is_measured_without_a_return_path() {
+    let mut probe = LatencyProbe::new(SAMPLE_HZ, 1_024);
+    probe.start();
+    loopback(&mut probe, 100, 0.0);
+    assert_eq!(probe.measurement(), None);
+}
@@ -0,0 +1,54 @@
+//! Layout hints place every node in a layer to the right of all of its inputs.
+
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+/// Renders nothing, as only the shape of the **Graph** matters here.
+struct Silent;
+
+impl Node<Mono> for Silent {
+    fn audio_requested(&mut self, _buffer: &mut [Mono], _sample_hz: f64) {}
+}
+
+#[test]
+fn nodes_are_layered_by_their_longest_input_path() {
+    let mut graph = Graph::new();
+    let master = graph.add_node(Silent);
+    let (_, a) = graph.add_input(Silent, master);
+    let (_, b) = graph.add_input(Silent, master);
+    let (_, c) = graph.add_input(Silent, a);
+    let layouts = graph.layout_hints();
+    assert_eq!(layouts.len(), 4);
+    for layout in &layouts {
+        assert_eq!(layout.x, layout.level as f32);
+    }
+    assert_eq!(layouts[c.index()].level, 0);
+    assert_eq!(layouts[b.index()].level, 0);
+    assert_eq!(layouts[a.index()].level, 1);
+    assert_eq!(layouts[master.index()].level, 2);
+    assert_eq!(layouts[master.index()].node, master);
+}
+
+#[test]
+fn each_layer_is_centred_and_ordered_by_its_inputs() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Silent);
+    let b = graph.add_node(Silent);
+    let (_, a_out) = graph.add_output(a, Silent);
+    let (_, b_out) = graph.add_output(b, Silent);
+    let layouts = graph.layout_hints();
+
+    // Each node in the second layer shares the row of its input.
+    assert_eq!(layouts[a_out.index()].row, layouts[a.index()].row);
+    assert_eq!(layouts[b_out.index()].row, layouts[b.index()].row);
+    let ys: Vec<_> = [a, b].iter().map(|idx| layouts[idx.index()].y).collect();
+    assert_eq!(ys.iter().sum::<f32>(), 0.0);
+    assert_eq!((ys[0] - ys[1]).abs(), 1.0);
+}
+
+#[test]
+fn an_empty_graph_has_no_layout() {
+    let graph: Graph<Mono, Silent> = Graph::new();
+    assert!(graph.layout_hints().is_empty());
+}
@@ -0,0 +1,65 @@
+//! The ancestors and descendants of a node are walked in visit order.
+
+use dsp::{Graph, Node, NodeIndex};
+
+type Mono = [f32; 1];
+
+/// Outputs silence.
+struct Silence;
+
+impl Node<Mono> for Silence {
+    fn audio_requested(&mut self, _buffer: &mut [Mono], _sample_hz: f64) {}
+}
+
+/// A diamond of `a` feeding `b` and `c` which both feed `d`, along with `e` which also feeds `d`
+/// and a lone node `f`, returned in that order.
+fn diamond() -> (Graph<Mono, Silence>, [NodeIndex; 6]) {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Silence);
+    let (_, b) = graph.add_output(a, Silence);
+    let (_, c) = graph.add_output(a, Silence);
+    let (_, d) = graph.add_output(b, Silence);
+    graph.add_connection(c, d).unwrap();
+    let (_, e) = graph.add_input(Silence, d);
+    let f = graph.add_node(Silence);
+    (graph, [a, b, c, d, e, f])
+}
+
+/// The position of the given node within the given walk.
+fn position(walk: &[NodeIndex], idx: NodeIndex) -> usize {
+    walk.iter().position(|&n| n == idx).unwrap()
+}
+
+#[test]
+fn ancestors_include_every_node_feeding_a_node() {
+    let (graph, [a, b, c, d, e, f]) = diamond();
+    let mut ancestors = graph.ancestors(d);
+    assert!(ancestors.contains(a));
+    assert!(!ancestors.contains(f));
+    let mut walk = Vec::new();
+    while let Some(idx) = ancestors.next(&graph) {
+        walk.push(idx);
+    }
+    assert_eq!(walk.len(), 4);
+    assert!(position(&walk, a) < position(&walk, b));
+    assert!(position(&walk, a) < position(&walk, c));
+    assert!(walk.contains(&e));
+    assert!(!walk.contains(&d));
+}
+
+#[test]
+fn descendants_include_every_node_fed_by_a_node() {
+    let (mut graph, [a, b, c, d, e, _]) = diamond();
+    graph.add_feedback_connection(d, a).unwrap();
+    let mut descendants = graph.descendants(a);
+    let mut walk = Vec::new();
+    while let Some(idx) = descendants.next(&graph) {
+        walk.push(idx);
+    }
+    assert_eq!(walk.len(), 3);
+    assert_eq!(walk[2], d);
+    assert!(walk.contains(&b) && walk.contains(&c));
+    assert!(!descendants.contains(e));
+    // Feedback connections are not followed.
+    assert_eq!(graph.descendants(d).next(&graph), None);
+}
@@ -0,0 +1,43 @@
+//! Mapping the nodes of a **Graph** to another type preserves its topology and state.
+
+mod common;
+
+use common::{render, Mono, Test};
+use dsp::{BoxedNodeSend, Graph};
+
+#[test]
+fn mapped_graphs_keep_their_topology_and_state() {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(1.0));
+    let (edge, gain) = graph.add_output(dc, Test::Gain(2.0));
+    let (_, muted) = graph.add_output(dc, Test::Gain(3.0));
+    graph.add_connection(muted, gain).unwrap();
+    graph.set_muted(muted, true).unwrap();
+    graph.set_master(Some(gain));
+    let id = graph.node_id(gain);
+    assert_eq!(render(&mut graph), 2.0);
+
+    let mut boxed: Graph<Mono, BoxedNodeSend<Mono>> =
+        graph.map_nodes(|_, node| Box::new(node) as BoxedNodeSend<Mono>);
+    assert_eq!(boxed.node_count(), 3);
+    assert_eq!(boxed.find_connection(dc, gain), Some(edge));
+    assert_eq!(boxed.master_index(), Some(gain));
+    assert!(boxed.is_muted(muted));
+    assert_eq!(boxed.node_id(gain), id);
+    assert_eq!(render(&mut boxed), 2.0);
+}
+
+#[test]
+fn nodes_are_mapped_along_with_their_index() {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(1.0));
+    let (_, gain) = graph.add_output(dc, Test::Gain(2.0));
+    graph.set_master(Some(gain));
+
+    let mut mapped = graph.map_nodes(|idx, node| match node {
+        Test::Gain(amp) => Test::Gain(amp * 10.0 + idx.index() as f32),
+        node => node,
+    });
+    assert_eq!(mapped[gain], Test::Gain(21.0));
+    assert_eq!(render(&mut mapped), 21.0);
+}
@@ -0,0 +1,73 @@
+//! Messages of any type are delivered to nodes at the start of the next request for audio.
+
+use dsp::{Graph, Node};
+use std::any::Any;
+
+type Mono = [f32; 1];
+
+/// A message that retriggers a node.
+#[derive(Clone, Debug)]
+struct Trigger;
+
+/// Records the messages that it handles by name.
+struct Recorder(Vec<String>);
+
+impl Node<Mono> for Recorder {
+    fn audio_requested(&mut self, _buffer: &mut [Mono], _sample_hz: f64) {}
+
+    fn handle_message(&mut self, message: &dyn Any) {
+        if message.is::<Trigger>() {
+            self.0.push("trigger".to_string());
+        } else if let Some(command) = message.downcast_ref::<String>() {
+            self.0.push(command.clone());
+        }
+    }
+}
+
+/// Render a buffer of `8` frames.
+fn render(graph: &mut Graph<Mono, Recorder>) {
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+}
+
+#[test]
+fn messages_are_delivered_to_their_node() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Recorder(Vec::new()));
+    let (_, b) = graph.add_output(a, Recorder(Vec::new()));
+    graph.set_master(Some(b));
+    graph.reserve_messages::<Trigger>(4);
+
+    graph.send_message(a, "first".to_string()).unwrap();
+    graph.send_message(a, "second".to_string()).unwrap();
+    graph.send_message(b, Trigger).unwrap();
+    assert!(graph[a].0.is_empty());
+    render(&mut graph);
+    assert_eq!(graph[a].0, vec!["first", "second"]);
+    assert_eq!(graph[b].0, vec!["trigger"]);
+
+    // Messages are only delivered once.
+    render(&mut graph);
+    assert_eq!(graph[a].0.len(), 2);
+}
+
+#[test]
+fn messages_follow_nodes_that_are_shifted_by_a_removal() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Recorder(Vec::new()));
+    let b = graph.add_node(Recorder(Vec::new()));
+    graph.send_message(a, Trigger).unwrap();
+    graph.send_message(b, "shifted".to_string()).unwrap();
+    graph.remove_node(a);
+
+    // The last node takes the index of the removed node.
+    graph.set_master(Some(a));
+    render(&mut graph);
+    assert_eq!(graph[a].0, vec!["shifted"]);
+}
+
+#[test]
+fn messages_to_missing_nodes_are_rejected() {
+    let mut graph: Graph<Mono, Recorder> = Graph::new();
+    assert!(graph.send_message(dsp::NodeIndex::new(0), Trigger).is_err());
+}
@@ -0,0 +1,56 @@
+//! The **MidSide** wrapper processes the mid and side of a stereo signal with separate nodes.
+
+use dsp::nodes::MidSide;
+use dsp::Node;
+
+type Mono = [f32; 1];
+type Stereo = [f32; 2];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// Scales its input, reporting the given latency.
+struct Gain(f32, usize);
+
+impl Node<Mono> for Gain {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] *= self.0;
+        }
+    }
+
+    fn latency(&self) -> usize {
+        self.1
+    }
+}
+
+/// Process a buffer of the given frame, returning its first frame.
+fn process(mid_side: &mut MidSide<Gain>, frame: Stereo) -> Stereo {
+    let mut buffer = [frame; 4];
+    mid_side.audio_requested(&mut buffer, SAMPLE_HZ);
+    buffer[0]
+}
+
+#[test]
+fn unity_gain_recovers_the_input() {
+    let mut mid_side = MidSide::new(Gain(1.0, 0), Gain(1.0, 0));
+    let [left, right] = process(&mut mid_side, [0.25, -0.75]);
+    assert_eq!([left, right], [0.25, -0.75]);
+}
+
+#[test]
+fn removing_the_side_collapses_to_mono() {
+    let mut mid_side = MidSide::new(Gain(1.0, 0), Gain(0.0, 0));
+    assert_eq!(process(&mut mid_side, [1.0, 0.0]), [0.5, 0.5]);
+
+    // Doubling the side instead widens the image.
+    mid_side.side_mut().0 = 2.0;
+    assert_eq!(process(&mut mid_side, [1.0, 0.0]), [1.5, -0.5]);
+}
+
+#[test]
+fn latency_is_that_of_the_slower_node() {
+    let mid_side = MidSide::new(Gain(1.0, 4), Gain(1.0, 16));
+    assert_eq!(Node::<Stereo>::latency(&mid_side), 16);
+    let (mid, side) = mid_side.into_inner();
+    assert_eq!((mid.1, side.1), (4, 16));
+}
@@ -0,0 +1,362 @@
+//! The **Mixer** sums its tracks through their faders, whether rendered serially or in parallel.
+
+use dsp::engine::{Mixer, MixerParam, TrackParam, VcaParam};
+use dsp::event::Event;
+use dsp::{Graph, Node, ParamChange};
+use std::any::Any;
+use std::rc::Rc;
+use std::thread::{self, ThreadId};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+#[derive(Clone)]
+enum Test {
+    /// Outputs a constant, which may be set via a message of type `f32`.
+    Dc(f32),
+    /// Passes its input through, reporting the given latency.
+    Pass(usize),
+    /// Panics when rendered.
+    Panic,
+    /// Outputs `1.0` when rendered on the given thread, or silence on any other.
+    OnThread(ThreadId),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        match *self {
+            Test::Dc(value) => {
+                for frame in buffer.iter_mut() {
+                    *frame = [value];
+                }
+            }
+            Test::Pass(_) => (),
+            Test::Panic => panic!("the test node panicked"),
+            Test::OnThread(id) => {
+                let value = if thread::current().id() == id {
+                    1.0
+                } else {
+                    0.0
+                };
+                for frame in buffer.iter_mut() {
+                    *frame = [value];
+                }
+            }
+        }
+    }
+
+    fn latency(&self) -> usize {
+        match *self {
+            Test::Pass(latency) => latency,
+            _ => 0,
+        }
+    }
+
+    fn handle_message(&mut self, message: &dyn Any) {
+        if let (Test::Dc(value), Some(&new)) = (self, message.downcast_ref::<f32>()) {
+            *value = new;
+        }
+    }
+}
+
+/// A graph whose output is the given node.
+fn graph_of(node: Test) -> Graph<Mono, Test> {
+    let mut graph = Graph::new();
+    let idx = graph.add_node(node);
+    graph.set_master(Some(idx));
+    graph
+}
+
+/// A mixer with a track outputting each of the given constants.
+fn mixer(values: &[f32]) -> Mixer<Mono, Test> {
+    let mut master = Graph::new();
+    let input = master.add_external_input("mix", 1, Test::Pass(0));
+    master.set_master(Some(input));
+    let mut mixer = Mixer::new(master, "mix");
+    for (i, &value) in values.iter().enumerate() {
+        mixer.add_track(&format!("track {}", i), graph_of(Test::Dc(value)));
+    }
+    mixer
+}
+
+/// An event setting the given parameter of a mixer.
+fn param(param: MixerParam, value: f32) -> Event {
+    Event::Param {
+        param: param.index(),
+        value,
+    }
+}
+
+/// Render a buffer from the mixer, returning its first frame.
+fn render(mixer: &mut Mixer<Mono, Test>) -> f32 {
+    let mut buffer = [[0.0]; 16];
+    mixer.audio_requested(&mut buffer, SAMPLE_HZ);
+    buffer[0][0]
+}
+
+#[test]
+fn tracks_are_summed_through_their_faders() {
+    let mut mixer = mixer(&[1.0, 2.0, 4.0]);
+    mixer.track_mut(1).unwrap().gain = 0.5;
+    assert_eq!(render(&mut mixer), 6.0);
+}
+
+#[test]
+fn muted_tracks_are_silent() {
+    let mut mixer = mixer(&[1.0, 2.0]);
+    render(&mut mixer);
+    mixer.track_mut(0).unwrap().muted = true;
+    assert!(!mixer.is_audible(0));
+    // The fader ramps out over the first request after the mute.
+    render(&mut mixer);
+    assert_eq!(render(&mut mixer), 2.0);
+}
+
+#[test]
+fn soloed_tracks_silence_the_others() {
+    let mut mixer = mixer(&[1.0, 2.0, 4.0]);
+    mixer.track_mut(0).unwrap().soloed = true;
+    mixer.track_mut(2).unwrap().soloed = true;
+    assert!(mixer.any_soloed());
+    assert!(!mixer.is_audible(1));
+    assert_eq!(render(&mut mixer), 5.0);
+
+    // A muted track stays silent while soloed.
+    mixer.track_mut(2).unwrap().muted = true;
+    render(&mut mixer);
+    assert_eq!(render(&mut mixer), 1.0);
+}
+
+#[test]
+fn faders_are_automated_via_params() {
+    let mut mixer = mixer(&[1.0, 2.0]);
+    mixer.handle_event(&param(MixerParam::Track(0, TrackParam::Gain), 3.0));
+    mixer.handle_event(&param(MixerParam::Track(1, TrackParam::Mute), 1.0));
+    assert_eq!(render(&mut mixer), 3.0);
+
+    // Changes made via params are not reported back, while direct moves are.
+    let mut changes = Vec::new();
+    mixer.param_changes(&mut changes);
+    assert!(changes.is_empty());
+    mixer.track_mut(1).unwrap().gain = 0.25;
+    mixer.param_changes(&mut changes);
+    let expected = ParamChange {
+        param: MixerParam::Track(1, TrackParam::Gain).index(),
+        value: 0.25,
+    };
+    assert_eq!(changes, [expected]);
+}
+
+#[test]
+fn parallel_rendering_matches_serial_rendering() {
+    let values: Vec<f32> = (0..24).map(|i| i as f32).collect();
+    let mut serial = mixer(&values);
+    let mut parallel = mixer(&values);
+    parallel.set_parallel(true);
+    assert!(parallel.is_parallel());
+    for i in (0..values.len()).step_by(3) {
+        serial.track_mut(i).unwrap().muted = true;
+        parallel.track_mut(i).unwrap().muted = true;
+    }
+    for _ in 0..4 {
+        let (mut a, mut b) = ([[0.0]; 64], [[0.0]; 64]);
+        serial.audio_requested(&mut a, SAMPLE_HZ);
+        parallel.audio_requested(&mut b, SAMPLE_HZ);
+        assert_eq!(a, b);
+    }
+    // The tracks keep their order.
+    let names: Vec<_> = parallel
+        .tracks()
+        .iter()
+        .map(|t| t.name().to_string())
+        .collect();
+    let expected: Vec<_> = (0..values.len()).map(|i| format!("track {}", i)).collect();
+    assert_eq!(names, expected);
+
+    // A clone renders serially until it starts threads of its own.
+    let mut clone = parallel.clone();
+    assert_eq!(render(&mut clone), render(&mut serial));
+    clone.set_parallel(true);
+    assert_eq!(render(&mut clone), render(&mut serial));
+}
+
+#[test]
+fn worker_threads_are_only_restarted_when_reserved() {
+    let mut mixer = mixer(&[]);
+    mixer.set_parallel(true);
+    let caller = thread::current().id();
+    for i in 0..24 {
+        mixer.add_track(&format!("track {}", i), graph_of(Test::OnThread(caller)));
+    }
+    // Beyond the capacity of the threads, the tracks are rendered on the calling thread.
+    assert_eq!(render(&mut mixer), 24.0);
+    mixer.reserve_tracks(0);
+    assert_eq!(render(&mut mixer), 0.0);
+}
+
+#[test]
+#[should_panic(expected = "the test node panicked")]
+fn panics_on_worker_threads_reach_the_caller() {
+    let mut mixer = mixer(&[1.0, 2.0]);
+    mixer.add_track("panic", graph_of(Test::Panic));
+    mixer.set_parallel(true);
+    render(&mut mixer);
+}
+
+/// Outputs the shared value, or passes its input through if there is none.
+struct Shared(Option<Rc<f32>>);
+
+impl Node<Mono> for Shared {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        if let Some(ref value) = self.0 {
+            for frame in buffer.iter_mut() {
+                *frame = [**value];
+            }
+        }
+    }
+}
+
+#[test]
+fn nodes_that_are_not_send_are_rendered_serially() {
+    let mut master = Graph::new();
+    let input = master.add_external_input("mix", 1, Shared(None));
+    master.set_master(Some(input));
+    let mut mixer = Mixer::new(master, "mix");
+    let value = Rc::new(2.0);
+    for name in &["a", "b"] {
+        let mut graph = Graph::new();
+        let idx = graph.add_node(Shared(Some(value.clone())));
+        graph.set_master(Some(idx));
+        mixer.add_track(name, graph);
+    }
+    let mut buffer = [[0.0]; 16];
+    mixer.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer[0], [4.0]);
+}
+
+#[test]
+fn latency_is_that_of_the_slowest_path() {
+    let mut mixer = mixer(&[1.0]);
+    mixer.add_track("slow", graph_of(Test::Pass(32)));
+    assert_eq!(Node::<Mono>::latency(&mixer), 32);
+}
+
+#[test]
+fn messages_reach_every_track() {
+    let mut mixer = mixer(&[1.0, 2.0]);
+    mixer.handle_message(&3.0f32);
+    assert_eq!(render(&mut mixer), 6.0);
+}
+
+/// A group bus graph that passes the sum of its members through, reporting the given latency.
+fn group_graph(latency: usize) -> Graph<Mono, Test> {
+    let mut graph = Graph::new();
+    let input = graph.add_external_input("in", 1, Test::Pass(latency));
+    graph.set_master(Some(input));
+    graph
+}
+
+#[test]
+fn group_buses_sum_their_members() {
+    let mut mixer = mixer(&[1.0, 2.0, 4.0]);
+    let drums = mixer.add_group("drums", group_graph(8), "in");
+    assert!(mixer.set_track_group(0, Some(drums)));
+    assert!(mixer.set_track_group(1, Some(drums)));
+    assert!(!mixer.set_track_group(2, Some(drums + 1)));
+    mixer.group_mut(drums).unwrap().gain = 0.5;
+    assert_eq!(render(&mut mixer), 5.5);
+    assert_eq!(Node::<Mono>::latency(&mixer), 8);
+
+    // Removing the group bus routes its members straight into the master once more.
+    assert!(mixer.remove_group(drums).is_some());
+    assert_eq!(mixer.track(0).unwrap().group(), None);
+    assert_eq!(render(&mut mixer), 7.0);
+}
+
+#[test]
+fn soloing_a_group_bus_solos_its_members() {
+    let mut mixer = mixer(&[1.0, 2.0, 4.0]);
+    let group = mixer.add_group("group", group_graph(0), "in");
+    mixer.set_track_group(0, Some(group));
+    mixer.set_track_group(1, Some(group));
+    mixer.group_mut(group).unwrap().soloed = true;
+    assert!(mixer.is_audible(0) && mixer.is_audible(1));
+    assert!(!mixer.is_audible(2));
+    assert_eq!(render(&mut mixer), 3.0);
+}
+
+#[test]
+fn soloing_a_member_keeps_its_group_bus_audible() {
+    let mut mixer = mixer(&[1.0, 2.0, 4.0]);
+    let group = mixer.add_group("group", group_graph(0), "in");
+    mixer.set_track_group(0, Some(group));
+    mixer.set_track_group(1, Some(group));
+    mixer.handle_event(&param(MixerParam::Track(1, TrackParam::Solo), 1.0));
+    assert!(mixer.is_group_audible(group));
+    assert!(!mixer.is_audible(0) && !mixer.is_audible(2));
+    assert_eq!(render(&mut mixer), 2.0);
+}
+
+#[test]
+fn vcas_scale_and_mute_their_members() {
+    let mut mixer = mixer(&[1.0, 2.0, 4.0]);
+    let a = mixer.add_vca("a");
+    let b = mixer.add_vca("b");
+    assert!(mixer.assign_vca(0, a));
+    assert!(mixer.assign_vca(1, a));
+    assert!(mixer.assign_vca(1, b));
+    mixer.handle_event(&param(MixerParam::Vca(a, VcaParam::Gain), 2.0));
+    mixer.handle_event(&param(MixerParam::Vca(b, VcaParam::Gain), 0.5));
+    assert_eq!(mixer.vca_gain(1), 1.0);
+    assert_eq!(render(&mut mixer), 8.0);
+
+    mixer.handle_event(&param(MixerParam::Vca(b, VcaParam::Mute), 1.0));
+    assert!(!mixer.is_audible(1));
+    render(&mut mixer);
+    assert_eq!(render(&mut mixer), 6.0);
+
+    // Removing a VCA releases its members and shifts the VCAs after it.
+    assert!(mixer.remove_vca(a).is_some());
+    assert_eq!(mixer.track(1).unwrap().vcas(), [0]);
+    assert_eq!(mixer.vca_index("b"), Some(0));
+}
+
+#[test]
+fn params_address_any_number_of_strips() {
+    let params = [
+        MixerParam::Track(0, TrackParam::Gain),
+        MixerParam::Track(3, TrackParam::Solo),
+        MixerParam::Group(0, TrackParam::Pan),
+        MixerParam::Group(1_000_000, TrackParam::Mute),
+        MixerParam::Vca(0, VcaParam::Gain),
+        MixerParam::Vca(7, VcaParam::Mute),
+    ];
+    for &param in &params {
+        assert_eq!(MixerParam::from_index(param.index()), param);
+    }
+    let mut indices: Vec<_> = params.iter().map(|param| param.index()).collect();
+    indices.sort_unstable();
+    indices.dedup();
+    assert_eq!(indices.len(), params.len());
+}
+
+#[test]
+fn direct_moves_of_groups_and_vcas_are_reported() {
+    let mut mixer = mixer(&[1.0]);
+    let group = mixer.add_group("group", group_graph(0), "in");
+    let vca = mixer.add_vca("vca");
+    mixer.group_mut(group).unwrap().muted = true;
+    mixer.vca_mut(vca).unwrap().gain = 0.5;
+    let mut changes = Vec::new();
+    mixer.param_changes(&mut changes);
+    let change = |param: MixerParam, value| ParamChange {
+        param: param.index(),
+        value,
+    };
+    let expected = [
+        change(MixerParam::Group(group, TrackParam::Mute), 1.0),
+        change(MixerParam::Vca(vca, VcaParam::Gain), 0.5),
+    ];
+    assert_eq!(changes, expected);
+}
@@ -0,0 +1,72 @@
+//! Inputs are summed and dry/wet mixed the same way whether or not the `simd` feature is enabled.
+
+use dsp::{Graph, Node};
+
+type Stereo = [f32; 2];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+enum Test {
+    /// Outputs a ramp starting at the given frame and rising by `1.0` each frame.
+    Ramp(Stereo),
+    /// Inverts its input, mixing the result with the given amounts of the dry and wet signal.
+    Invert { dry: f32, wet: f32 },
+}
+
+impl Node<Stereo> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Stereo], _sample_hz: f64) {
+        for (i, frame) in buffer.iter_mut().enumerate() {
+            *frame = match *self {
+                Test::Ramp([l, r]) => [l + i as f32, r + i as f32],
+                Test::Invert { .. } => [-frame[0], -frame[1]],
+            };
+        }
+    }
+
+    fn dry(&self) -> f32 {
+        match *self {
+            Test::Invert { dry, .. } => dry,
+            _ => 0.0,
+        }
+    }
+
+    fn wet(&self) -> f32 {
+        match *self {
+            Test::Invert { wet, .. } => wet,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Three ramps summed into an inverting node with the given dry and wet amounts.
+fn graph(dry: f32, wet: f32) -> Graph<Stereo, Test> {
+    let mut graph = Graph::new();
+    let invert = graph.add_node(Test::Invert { dry, wet });
+    for &start in &[[0.0, 1.0], [10.0, 20.0], [100.0, 200.0]] {
+        graph.add_input(Test::Ramp(start), invert);
+    }
+    graph.set_master(Some(invert));
+    graph
+}
+
+#[test]
+fn inputs_are_summed_frame_by_frame() {
+    // An odd length leaves frames over after any whole chunks.
+    let mut buffer = [[0.0; 2]; 19];
+    graph(0.0, 1.0).audio_requested(&mut buffer, SAMPLE_HZ);
+    for (i, frame) in buffer.iter().enumerate() {
+        let i = i as f32;
+        assert_eq!(*frame, [-(110.0 + 3.0 * i), -(221.0 + 3.0 * i)]);
+    }
+}
+
+#[test]
+fn dry_and_wet_signals_are_mixed_frame_by_frame() {
+    let mut buffer = [[0.0; 2]; 19];
+    graph(0.75, 0.25).audio_requested(&mut buffer, SAMPLE_HZ);
+    for (i, frame) in buffer.iter().enumerate() {
+        let i = i as f32;
+        let input = [110.0 + 3.0 * i, 221.0 + 3.0 * i];
+        assert_eq!(*frame, [input[0] * 0.5, input[1] * 0.5]);
+    }
+}
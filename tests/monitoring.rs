@@ -0,0 +1,70 @@
+//! Low-latency monitoring passes around nodes with too much latency while still rendering them.
+
+mod common;
+
+use common::{render, Mono};
+use dsp::{Graph, Node};
+
+enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Doubles its input, reporting the given latency.
+    Double(usize),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = match *self {
+                Test::Dc(value) => [value],
+                Test::Double(_) => [frame[0] * 2.0],
+            };
+        }
+    }
+
+    fn latency(&self) -> usize {
+        match *self {
+            Test::Double(latency) => latency,
+            _ => 0,
+        }
+    }
+}
+
+/// A source passed through a slow node and then a fast one, returning both indices.
+fn graph() -> (Graph<Mono, Test>, dsp::NodeIndex, dsp::NodeIndex) {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Dc(1.0));
+    let (_, slow) = graph.add_output(src, Test::Double(512));
+    let (_, fast) = graph.add_output(slow, Test::Double(16));
+    graph.set_master(Some(fast));
+    (graph, slow, fast)
+}
+
+#[test]
+fn slow_nodes_are_passed_around_while_monitoring() {
+    let (mut graph, slow, fast) = graph();
+    assert_eq!(render(&mut graph), 4.0);
+    assert_eq!(graph.path_latency(fast), Some(528));
+    assert_eq!(graph.full_quality_output(slow), None);
+
+    graph.set_monitoring(Some(64));
+    assert_eq!(graph.monitoring(), Some(64));
+    assert_eq!(render(&mut graph), 2.0);
+    assert!(graph.is_monitor_bypassed(slow));
+    assert!(!graph.is_monitor_bypassed(fast));
+    assert_eq!(graph.path_latency(fast), Some(16));
+
+    // The slow node is still rendered, so its full-quality output may be recorded.
+    assert_eq!(graph.full_quality_output(slow), Some(&[[2.0]; 4][..]));
+}
+
+#[test]
+fn disabling_monitoring_restores_the_full_path() {
+    let (mut graph, slow, fast) = graph();
+    graph.set_monitoring(Some(64));
+    render(&mut graph);
+    graph.set_monitoring(None);
+    assert!(!graph.is_monitor_bypassed(slow));
+    assert_eq!(render(&mut graph), 4.0);
+    assert_eq!(graph.path_latency(fast), Some(528));
+}
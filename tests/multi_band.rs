@@ -0,0 +1,80 @@
+//! The **MultiBand** wrapper processes each frequency band with its own node.
+#![cfg(feature = "filters")]
+
+use dsp::nodes::MultiBand;
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 48_000.0;
+
+/// Scales its input.
+#[derive(Clone)]
+struct Gain(f32);
+
+impl Node<Mono> for Gain {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] *= self.0;
+        }
+    }
+}
+
+/// One second of a sine at the given frequency.
+fn sine(hz: f64) -> Vec<Mono> {
+    (0..SAMPLE_HZ as usize)
+        .map(|i| [(i as f64 * hz * std::f64::consts::TAU / SAMPLE_HZ).sin() as f32])
+        .collect()
+}
+
+/// The energy of the given signal, skipping the first tenth while the filters settle.
+fn energy(signal: &[Mono]) -> f32 {
+    signal[signal.len() / 10..]
+        .iter()
+        .map(|f| f[0] * f[0])
+        .sum()
+}
+
+/// The ratio of the output's energy to that of the input.
+fn gain_of(multi_band: &mut MultiBand<Mono, Gain>, input: &[Mono]) -> f32 {
+    let mut output = input.to_vec();
+    multi_band.audio_requested(&mut output, SAMPLE_HZ);
+    energy(&output) / energy(input)
+}
+
+#[test]
+fn unprocessed_bands_sum_to_a_flat_response() {
+    let mut multi_band = MultiBand::new(Gain(1.0), &[8_000.0, 200.0, 2_000.0]);
+    assert_eq!(multi_band.crossovers(), [200.0, 2_000.0, 8_000.0]);
+    assert_eq!(multi_band.bands().len(), 4);
+    for &hz in &[50.0, 700.0, 5_000.0, 12_000.0] {
+        multi_band.reset();
+        let gain = gain_of(&mut multi_band, &sine(hz));
+        assert!((gain - 1.0).abs() < 0.01, "{}hz: {}", hz, gain);
+    }
+}
+
+#[test]
+fn each_band_is_processed_separately() {
+    let bands = vec![Gain(1.0), Gain(0.0), Gain(0.0)];
+    let mut multi_band = MultiBand::from_bands(bands, &[200.0, 2_000.0]);
+    assert!(gain_of(&mut multi_band, &sine(50.0)) > 0.9);
+    multi_band.reset();
+    assert!(gain_of(&mut multi_band, &sine(10_000.0)) < 0.01);
+
+    // Only the high band passes once the others are muted.
+    multi_band.bands_mut()[0].0 = 0.0;
+    multi_band.bands_mut()[2].0 = 1.0;
+    multi_band.reset();
+    assert!(gain_of(&mut multi_band, &sine(10_000.0)) > 0.9);
+}
+
+#[test]
+fn crossovers_are_clamped_between_their_neighbours() {
+    let mut multi_band: MultiBand<Mono, _> = MultiBand::new(Gain(1.0), &[200.0, 2_000.0]);
+    multi_band.set_crossover(0, 5_000.0);
+    assert_eq!(multi_band.crossovers(), [2_000.0, 2_000.0]);
+    multi_band.set_crossover(1, 100.0);
+    assert_eq!(multi_band.crossovers(), [2_000.0, 2_000.0]);
+    assert_eq!(multi_band.into_bands().len(), 3);
+}
@@ -0,0 +1,69 @@
+//! `#[derive(NodeEnum)]` implements **Node** for an enum by delegating to its variants.
+
+#![cfg(feature = "derive")]
+
+use dsp::{Graph, Node, NodeEnum};
+
+type Mono = [f32; 1];
+
+/// Outputs a constant.
+#[derive(Debug, PartialEq)]
+struct Dc(f32);
+
+/// Scales its input, reporting the given latency.
+#[derive(Debug, PartialEq)]
+struct Gain(f32, usize);
+
+impl Node<Mono> for Dc {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+}
+
+impl Node<Mono> for Gain {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] *= self.0;
+        }
+    }
+
+    fn latency(&self) -> usize {
+        self.1
+    }
+}
+
+#[derive(NodeEnum)]
+enum Test {
+    /// A **Dc** node.
+    Dc(Dc),
+    /// A **Gain** node.
+    Gain(Gain),
+    /// A second kind of **Gain** node, held by a named field.
+    Boost { gain: Gain },
+}
+
+#[test]
+fn nodes_are_delegated_to_the_current_variant() {
+    let mut graph: Graph<Mono, Test> = Graph::new();
+    let dc = graph.add_node(Test::Dc(Dc(1.0)));
+    let (_, gain) = graph.add_output(dc, Test::Gain(Gain(2.0, 8)));
+    let (_, boost) = graph.add_output(gain, Test::Boost { gain: Gain(3.0, 0) });
+    graph.set_master(Some(boost));
+
+    assert_eq!(graph[gain].latency(), 8);
+    assert_eq!(graph[boost].latency(), 0);
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[6.0]; 4]);
+}
+
+#[test]
+fn types_held_by_one_variant_are_typed_variants() {
+    let mut graph: Graph<Mono, Test> = Graph::new();
+    let dc = graph.add_node_typed(Dc(1.0));
+    assert!(matches!(graph[dc.node_index()], Test::Dc(_)));
+    graph.node_as_mut(dc).unwrap().0 = 4.0;
+    assert_eq!(graph.node_as(dc), Some(&Dc(4.0)));
+}
@@ -0,0 +1,56 @@
+//! Each node keeps a unique **NodeId** for as long as it remains within the **Graph**.
+
+use dsp::{Graph, Node, NodeId, NodeIndex};
+
+type Mono = [f32; 1];
+
+/// Outputs a constant.
+#[derive(Debug, PartialEq)]
+struct Dc(f32);
+
+impl Node<Mono> for Dc {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+}
+
+#[test]
+fn ids_follow_nodes_as_their_indices_shift() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Dc(1.0));
+    let b = graph.add_node(Dc(2.0));
+    let c = graph.add_node(Dc(3.0));
+    let (a_id, c_id) = (graph.node_id(a).unwrap(), graph.node_id(c).unwrap());
+    assert!(a_id < graph.node_id(b).unwrap());
+
+    graph.remove_node(a);
+    assert_eq!(graph.index_of(a_id), None);
+    let c = graph.index_of(c_id).unwrap();
+    assert_eq!(c, a);
+    assert_eq!(graph[c], Dc(3.0));
+    assert_eq!(graph.node_id(c), Some(c_id));
+    assert_eq!(graph.node_id(NodeIndex::new(2)), None);
+}
+
+#[test]
+fn ids_are_never_reused() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Dc(1.0));
+    let removed = graph.node_id(a).unwrap();
+    graph.remove_node(a);
+    let b = graph.add_node(Dc(2.0));
+    assert!(graph.node_id(b).unwrap() > removed);
+    graph.clear();
+    let c = graph.add_node(Dc(3.0));
+    assert!(graph.node_id(c).unwrap() > removed);
+}
+
+#[test]
+fn ids_convert_to_and_from_raw_values() {
+    let id = NodeId::new(42);
+    assert_eq!(id.get(), 42);
+    assert_eq!(u64::from(id), 42);
+    assert_eq!(id.to_string(), "#42");
+}
@@ -0,0 +1,75 @@
+//! Notifications reach every subscriber through a bounded queue.
+
+use dsp::{Graph, Node, Notification, ParamChange};
+use std::thread;
+
+type Mono = [f32; 1];
+
+/// Reports a change of its only parameter each time it renders.
+struct Knob(f32);
+
+impl Node<Mono> for Knob {
+    fn audio_requested(&mut self, _buffer: &mut [Mono], _sample_hz: f64) {
+        self.0 += 1.0;
+    }
+
+    fn param_changes(&mut self, changes: &mut Vec<ParamChange>) {
+        changes.push(ParamChange {
+            param: 0,
+            value: self.0,
+        });
+    }
+}
+
+fn knob_graph() -> (Graph<Mono, Knob>, dsp::NodeIndex) {
+    let mut graph = Graph::new();
+    let knob = graph.add_node(Knob(0.0));
+    graph.set_master(Some(knob));
+    (graph, knob)
+}
+
+#[test]
+fn notifications_reach_every_subscriber() {
+    let (mut graph, knob) = knob_graph();
+    let a = graph.subscribe_notifications();
+    let b = graph.subscribe_notifications();
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    graph.audio_requested(&mut buffer, 44_100.0);
+
+    let expected = |value| Notification::ParamChanged(knob, ParamChange { param: 0, value });
+    // Receivers may be read from another thread.
+    let received = thread::spawn(move || a.try_iter().collect::<Vec<_>>())
+        .join()
+        .unwrap();
+    assert_eq!(received, vec![expected(1.0), expected(2.0)]);
+    assert_eq!(b.try_iter().count(), 2);
+    assert_eq!(b.dropped(), 0);
+}
+
+#[test]
+fn full_queues_drop_and_count_notifications() {
+    let (mut graph, _) = knob_graph();
+    let receiver = graph.subscribe_notifications();
+    let mut buffer = [[0.0]; 8];
+    for _ in 0..300 {
+        graph.audio_requested(&mut buffer, 44_100.0);
+    }
+    assert_eq!(receiver.try_iter().count(), 256);
+    assert_eq!(receiver.dropped(), 44);
+
+    // Once the receiver catches up, notifications are queued again.
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(receiver.try_iter().count(), 1);
+}
+
+#[test]
+fn dropped_receivers_do_not_affect_others() {
+    let (mut graph, _) = knob_graph();
+    let dropped = graph.subscribe_notifications();
+    let kept = graph.subscribe_notifications();
+    drop(dropped);
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(kept.try_iter().count(), 1);
+}
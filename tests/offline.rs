@@ -0,0 +1,142 @@
+//! Files are rendered through a fresh node each, compensating for its latency and keeping its
+//! tail.
+
+use dsp::offline::{process_file, process_files, Error, Options};
+use dsp::Node;
+use std::path::PathBuf;
+
+type Mono = [f32; 1];
+
+enum Test {
+    /// Delays its input by the length of its line, reporting that as its latency.
+    Delay(Vec<Mono>),
+    /// Repeats its input once after the given number of frames, reporting that as its tail.
+    Echo(Vec<Mono>),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = match self {
+                Test::Delay(line) => {
+                    line.push(*frame);
+                    line.remove(0)
+                }
+                Test::Echo(line) => {
+                    line.push(*frame);
+                    [frame[0] + line.remove(0)[0]]
+                }
+            };
+        }
+    }
+
+    fn latency(&self) -> usize {
+        match self {
+            Test::Delay(line) => line.len(),
+            Test::Echo(_) => 0,
+        }
+    }
+
+    fn tail_frames(&self) -> usize {
+        match self {
+            Test::Delay(_) => 0,
+            Test::Echo(line) => line.len(),
+        }
+    }
+}
+
+/// A path within the temporary directory that is unique to this test process.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("dsp-chain-offline-{}-{}", std::process::id(), name))
+}
+
+/// Write a mono 16-bit WAVE file of the given samples at 48khz.
+fn write_wav(name: &str, samples: &[i16]) -> PathBuf {
+    let data_len = samples.len() as u32 * 2;
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&48_000u32.to_le_bytes());
+    bytes.extend_from_slice(&96_000u32.to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    let path = temp_path(name);
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+/// Read the samples of a 32-bit float WAVE file as written by the offline processor.
+fn read_wav(path: &PathBuf) -> Vec<f32> {
+    let bytes = std::fs::read(path).unwrap();
+    assert_eq!(&bytes[36..40], b"data");
+    bytes[44..]
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// An impulse of half scale followed by silence.
+fn impulse(len: usize) -> Vec<i16> {
+    let mut samples = vec![0; len];
+    samples[0] = 16_384;
+    samples
+}
+
+#[test]
+fn latency_is_trimmed_from_the_start() {
+    let inputs: Vec<_> = (0..3)
+        .map(|i| write_wav(&format!("delay-in-{}.wav", i), &impulse(100)))
+        .collect();
+    let outputs: Vec<_> = (0..3)
+        .map(|i| temp_path(&format!("delay-out-{}.wav", i)))
+        .collect();
+    let options = Options {
+        block_size: 16,
+        ..Options::default()
+    };
+    let results = process_files(|| Test::Delay(vec![[0.0]; 10]), &inputs, &outputs, &options);
+    assert!(results.iter().all(Result::is_ok));
+    for output in &outputs {
+        let samples = read_wav(output);
+        assert_eq!(samples.len(), 100);
+        assert_eq!(samples[0], 0.5);
+        assert!(samples[1..].iter().all(|&s| s == 0.0));
+    }
+}
+
+#[test]
+fn tails_are_rendered_past_the_end_of_the_input() {
+    let input = write_wav("echo-in.wav", &impulse(10));
+    let output = temp_path("echo-out.wav");
+    let mut echo = Test::Echo(vec![[0.0]; 50]);
+    process_file(&mut echo, &input, &output, &Options::default()).unwrap();
+    let samples = read_wav(&output);
+    // The output ends with the echo, as the silence after it is trimmed.
+    assert_eq!(samples.len(), 51);
+    assert_eq!(samples[50], 0.5);
+}
+
+#[test]
+fn unreadable_inputs_are_reported() {
+    let missing = temp_path("missing.wav");
+    let invalid = temp_path("invalid.wav");
+    std::fs::write(&invalid, b"not a wave file").unwrap();
+    let outputs = [temp_path("missing-out.wav"), temp_path("invalid-out.wav")];
+    let results = process_files(
+        || Test::Delay(Vec::new()),
+        &[missing, invalid],
+        &outputs,
+        &Options::default(),
+    );
+    assert!(matches!(results[0], Err(Error::Io(_))));
+    assert!(matches!(results[1], Err(Error::InvalidWav(_))));
+}
@@ -0,0 +1,63 @@
+//! Nodes that panic while rendering are bypassed according to the **PanicPolicy**.
+
+use dsp::{Graph, Node, Notification, PanicPolicy};
+
+type Mono = [f32; 1];
+
+enum Test {
+    Constant(f32),
+    Boom,
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        match *self {
+            Test::Constant(value) => {
+                for frame in buffer.iter_mut() {
+                    *frame = [value];
+                }
+            }
+            Test::Boom => panic!("boom"),
+        }
+    }
+}
+
+fn quiet_hook() {
+    // Keep the expected panics out of the test output.
+    std::panic::set_hook(Box::new(|_| ()));
+}
+
+#[test]
+fn silence_policy_bypasses_panicking_nodes() {
+    quiet_hook();
+    let mut graph = Graph::new();
+    let boom = graph.add_node(Test::Boom);
+    graph.set_master(Some(boom));
+    graph.set_panic_policy(PanicPolicy::Silence);
+    let notifications = graph.subscribe_notifications();
+    let mut buffer = [[1.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.0]; 4]);
+    assert!(graph.has_panicked(boom));
+    let received: Vec<_> = notifications.try_iter().collect();
+    assert_eq!(received, vec![Notification::NodePanicked(boom)]);
+
+    // The node stays bypassed without panicking again until it is reset.
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(notifications.try_iter().count(), 0);
+    assert!(graph.reset_panicked(boom));
+    assert!(!graph.has_panicked(boom));
+}
+
+#[test]
+fn dry_policy_passes_the_input_through() {
+    quiet_hook();
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Constant(0.5));
+    let (_, boom) = graph.add_output(src, Test::Boom);
+    graph.set_master(Some(boom));
+    graph.set_panic_policy(PanicPolicy::Dry);
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.5]; 4]);
+}
@@ -0,0 +1,114 @@
+//! Parameter handles read, set and watch the parameters of nodes from other threads.
+
+use dsp::event::Event;
+use dsp::{Graph, Node, NodeIndex, ParamChange};
+
+type Mono = [f32; 1];
+
+#[derive(Debug, PartialEq)]
+enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Scales its input by its only parameter.
+    Gain(f32),
+    /// Outputs its only parameter, which it raises by one each time it renders.
+    Ramp(f32),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        if let Test::Ramp(ref mut value) = *self {
+            *value += 1.0;
+        }
+        for frame in buffer.iter_mut() {
+            *frame = match *self {
+                Test::Dc(value) | Test::Ramp(value) => [value],
+                Test::Gain(amp) => [frame[0] * amp],
+            };
+        }
+    }
+
+    fn param_changes(&mut self, changes: &mut Vec<ParamChange>) {
+        if let Test::Ramp(value) = *self {
+            changes.push(ParamChange { param: 0, value });
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let &Event::Param { param: 0, value } = event {
+            match self {
+                Test::Gain(amp) | Test::Ramp(amp) => *amp = value,
+                Test::Dc(_) => (),
+            }
+        }
+    }
+}
+
+/// Render a buffer of `4` frames, returning its first frame.
+fn render(graph: &mut Graph<Mono, Test>) -> f32 {
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    buffer[0][0]
+}
+
+#[test]
+fn values_set_via_handles_reach_the_node() {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(2.0));
+    let (_, gain) = graph.add_output(dc, Test::Gain(1.0));
+    graph.set_master(Some(gain));
+    let handle = graph.param_handle(gain, 0, 1.0).unwrap();
+    assert_eq!((handle.param(), handle.get()), (0, 1.0));
+    assert_eq!(render(&mut graph), 2.0);
+
+    let ui = std::thread::spawn(move || {
+        handle.set(4.0);
+        handle.set(3.0);
+        handle
+    });
+    let handle = ui.join().unwrap();
+    assert_eq!(handle.get(), 3.0);
+    assert_eq!(render(&mut graph), 6.0);
+    assert_eq!(graph[gain], Test::Gain(3.0));
+    assert!(graph.param_handle(NodeIndex::new(7), 0, 0.0).is_err());
+}
+
+#[test]
+fn changes_made_by_nodes_are_watched() {
+    let mut graph = Graph::new();
+    let ramp = graph.add_node(Test::Ramp(0.0));
+    graph.set_master(Some(ramp));
+    let handle = graph.param_handle(ramp, 0, 0.0).unwrap();
+    let mut subscription = handle.subscribe();
+    assert_eq!(subscription.changed(), None);
+
+    render(&mut graph);
+    render(&mut graph);
+    assert_eq!(handle.get(), 2.0);
+    assert_eq!(subscription.changed(), Some(2.0));
+    assert_eq!(subscription.changed(), None);
+    // A second handle to the same parameter shares its value.
+    let shared = graph.param_handle(ramp, 0, 100.0).unwrap();
+    assert_eq!(shared.get(), 2.0);
+    assert_eq!(graph.param_handle_count(), 1);
+}
+
+#[test]
+fn handles_follow_their_node_until_it_is_removed() {
+    let mut graph = Graph::new();
+    let first = graph.add_node(Test::Dc(1.0));
+    let ramp = graph.add_node(Test::Ramp(0.0));
+    let handle = graph.param_handle(ramp, 0, 0.0).unwrap();
+
+    graph.remove_node(first);
+    let ramp = first;
+    graph.set_master(Some(ramp));
+    handle.set(10.0);
+    assert_eq!(render(&mut graph), 11.0);
+    assert!(handle.is_attached());
+
+    graph.remove_node(ramp);
+    assert!(!handle.is_attached());
+    handle.set(20.0);
+    assert_eq!(handle.get(), 20.0);
+}
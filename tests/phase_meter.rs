@@ -0,0 +1,72 @@
+//! The **PhaseMeter** measures stereo correlation and keeps goniometer points.
+
+#![cfg(feature = "analysis")]
+
+use dsp::nodes::PhaseMeter;
+use dsp::{Graph, Node};
+use std::f32::consts::FRAC_1_SQRT_2;
+
+type Stereo = [f32; 2];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// One second of a sine at 100hz, with the right channel scaled by `right`.
+fn sine(right: f32) -> Vec<Stereo> {
+    (0..SAMPLE_HZ as usize)
+        .map(|i| {
+            let s = (i as f64 * 100.0 * std::f64::consts::TAU / SAMPLE_HZ).sin() as f32;
+            [s, s * right]
+        })
+        .collect()
+}
+
+/// The correlation measured by a meter over the given signal.
+fn correlation(signal: &[Stereo]) -> f32 {
+    let mut meter = PhaseMeter::new(0);
+    meter.process(signal, SAMPLE_HZ);
+    meter.correlation()
+}
+
+#[test]
+fn correlation_follows_the_phase_between_channels() {
+    assert!(correlation(&sine(1.0)) > 0.99);
+    assert!(correlation(&sine(-1.0)) < -0.99);
+    let left_only = correlation(&sine(0.0));
+    assert_eq!(left_only, 0.0);
+    assert_eq!(correlation(&[[0.0; 2]; 64]), 0.0);
+}
+
+#[test]
+fn goniometer_points_are_kept_oldest_first() {
+    let mut meter = PhaseMeter::new(2);
+    let mut buffer = [[1.0, 1.0], [1.0, -1.0], [0.0, 1.0]];
+    meter.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer[2], [0.0, 1.0]);
+
+    let mut points = Vec::new();
+    meter.snapshot(&mut points);
+    let expected = [[2.0 * FRAC_1_SQRT_2, 0.0], [-FRAC_1_SQRT_2, FRAC_1_SQRT_2]];
+    assert_eq!(points.len(), 2);
+    for (point, expected) in points.iter().zip(&expected) {
+        assert!((point[0] - expected[0]).abs() < 1e-6);
+        assert!((point[1] - expected[1]).abs() < 1e-6);
+    }
+
+    meter.reset();
+    meter.snapshot(&mut points);
+    assert!(points.is_empty());
+    assert_eq!(meter.correlation(), 0.0);
+}
+
+#[test]
+fn the_correlation_is_published_to_the_analysis_bus() {
+    let mut graph = Graph::new();
+    let meter = graph.add_external_input("in", 2, PhaseMeter::new(0));
+    graph.set_master(Some(meter));
+    let signal = &sine(-1.0)[..1_024];
+    assert!(graph.write_external_input("in", signal));
+    let mut buffer = [[0.0; 2]; 1_024];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    let correlation = graph.analysis_value(meter, "correlation").unwrap();
+    assert!(correlation < -0.99);
+}
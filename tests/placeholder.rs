@@ -0,0 +1,70 @@
+//! Placeholders render silence until the node that they stand in for has loaded.
+
+use dsp::nodes::Placeholder;
+use dsp::{Graph, Node, Notification};
+use std::sync::mpsc;
+
+type Mono = [f32; 1];
+
+/// Outputs a constant, remembering the last channel count it was notified of.
+struct Dc(f32, Option<usize>);
+
+impl Node<Mono> for Dc {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+
+    fn channels_changed(&mut self, channels: usize) {
+        self.1 = Some(channels);
+    }
+}
+
+/// Render a buffer, returning its first frame.
+fn render(graph: &mut Graph<Mono, Placeholder<Dc>>) -> f32 {
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    buffer[0][0]
+}
+
+#[test]
+fn loaded_nodes_replace_the_silence() {
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let mut graph = Graph::new();
+    let node = graph.add_node(Placeholder::from_receiver(receiver));
+    graph.set_master(Some(node));
+    let notifications = graph.subscribe_notifications();
+
+    assert_eq!(render(&mut graph), 0.0);
+    assert!(graph[node].is_loading());
+    graph.set_channels(1);
+
+    sender.send(Dc(1.0, None)).unwrap();
+    assert_eq!(render(&mut graph), 1.0);
+    assert!(!graph[node].is_loading());
+    let received: Vec<_> = notifications.try_iter().collect();
+    assert_eq!(received, vec![Notification::NodeLoaded(node)]);
+
+    // The channel count reported while loading is forwarded to the loaded node.
+    assert_eq!(graph[node].node().unwrap().1, Some(1));
+}
+
+#[test]
+fn nodes_load_on_a_background_thread() {
+    let mut placeholder = Placeholder::spawn(|| Dc(2.0, None));
+    while !Node::<Mono>::finish_loading(&mut placeholder) {
+        std::thread::yield_now();
+    }
+    assert_eq!(placeholder.into_node().map(|dc| dc.0), Some(2.0));
+}
+
+#[test]
+fn failed_loads_remain_silent() {
+    let (sender, receiver) = mpsc::sync_channel::<Dc>(1);
+    drop(sender);
+    let mut placeholder = Placeholder::from_receiver(receiver);
+    assert!(!Node::<Mono>::finish_loading(&mut placeholder));
+    assert!(placeholder.is_loading());
+    assert!(Node::<Mono>::is_silent(&placeholder));
+}
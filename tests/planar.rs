@@ -0,0 +1,63 @@
+//! Nodes that request planar buffers receive their summed input one channel after another.
+
+use dsp::{deinterleave, interleave, BufferFormat, Graph, Node, Planar};
+
+type Stereo = [f32; 2];
+
+enum Test {
+    /// Outputs the given frame.
+    Dc(Stereo),
+    /// Swaps the channels of its input, requesting planar buffers to do so.
+    SwapChannels,
+}
+
+impl Node<Stereo> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Stereo], _sample_hz: f64) {
+        match *self {
+            Test::Dc(value) => {
+                for frame in buffer.iter_mut() {
+                    *frame = value;
+                }
+            }
+            Test::SwapChannels => panic!("planar nodes are not requested interleaved audio"),
+        }
+    }
+
+    fn buffer_format(&self) -> BufferFormat {
+        match *self {
+            Test::Dc(_) => BufferFormat::Interleaved,
+            Test::SwapChannels => BufferFormat::Planar,
+        }
+    }
+
+    fn audio_requested_planar(&mut self, mut buffer: Planar<f32>, _sample_hz: f64) {
+        assert_eq!(buffer.channels(), 2);
+        let left = buffer.channel(0).to_vec();
+        let right = buffer.channel(1).to_vec();
+        buffer.channel_mut(0).copy_from_slice(&right);
+        buffer.channel_mut(1).copy_from_slice(&left);
+    }
+}
+
+#[test]
+fn planar_nodes_process_the_summed_input() {
+    let mut graph = Graph::new();
+    let swap = graph.add_node(Test::SwapChannels);
+    graph.add_input(Test::Dc([1.0, 2.0]), swap);
+    graph.add_input(Test::Dc([0.5, 0.0]), swap);
+    graph.set_master(Some(swap));
+    let mut buffer = [[0.0; 2]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[2.0, 1.5]; 4]);
+}
+
+#[test]
+fn deinterleaving_round_trips() {
+    let frames = [[0.0, 1.0], [2.0, 3.0], [4.0, 5.0]];
+    let mut planar = [0.0; 6];
+    deinterleave(&frames, &mut planar);
+    assert_eq!(planar, [0.0, 2.0, 4.0, 1.0, 3.0, 5.0]);
+    let mut interleaved = [[0.0; 2]; 3];
+    interleave(&planar, &mut interleaved);
+    assert_eq!(interleaved, frames);
+}
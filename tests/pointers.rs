@@ -0,0 +1,69 @@
+//! Boxes, mutable references and shared pointers to nodes are nodes themselves.
+
+use dsp::{BoxedNodeSend, Graph, Node};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type Mono = [f32; 1];
+
+/// Outputs a constant, reporting its level as its latency.
+struct Dc(f32);
+
+impl Node<Mono> for Dc {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+
+    fn latency(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Render a single frame through the given node.
+fn render<N: Node<Mono>>(node: &mut N) -> f32 {
+    let mut buffer = [[0.0]];
+    node.audio_requested(&mut buffer, 44_100.0);
+    buffer[0][0]
+}
+
+#[test]
+fn pointers_delegate_to_their_node() {
+    let mut dc = Dc(2.0);
+    assert_eq!(render(&mut &mut dc), 2.0);
+    let mut boxed: BoxedNodeSend<Mono> = Box::new(Dc(3.0));
+    assert_eq!(render(&mut boxed), 3.0);
+    assert_eq!(boxed.latency(), 3);
+}
+
+#[test]
+fn shared_nodes_may_be_changed_outside_the_graph() {
+    let shared = Rc::new(RefCell::new(Dc(1.0)));
+    let mut graph = Graph::new();
+    let node = graph.add_node(shared.clone());
+    graph.set_master(Some(node));
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[1.0]; 4]);
+
+    shared.borrow_mut().0 = 4.0;
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[4.0]; 4]);
+    assert_eq!(Node::<Mono>::latency(&graph), 4);
+}
+
+#[test]
+fn nodes_shared_between_threads_recover_from_poisoning() {
+    let shared = Arc::new(Mutex::new(Dc(1.0)));
+    let poisoner = shared.clone();
+    let _ = thread::spawn(move || {
+        let _guard = poisoner.lock().unwrap();
+        panic!("poison the lock");
+    })
+    .join();
+    assert!(shared.is_poisoned());
+    assert_eq!(render(&mut shared.clone()), 1.0);
+}
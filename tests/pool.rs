@@ -0,0 +1,90 @@
+//! Connection buffers are drawn from and returned to a pool, so restructuring need not allocate.
+
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// Outputs `1.0`.
+struct One;
+
+impl Node<Mono> for One {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [1.0];
+        }
+    }
+}
+
+#[test]
+fn removed_connections_return_their_buffers_to_the_pool() {
+    let mut graph = Graph::new();
+    let master = graph.add_node(One);
+    let (edge, _) = graph.add_input(One, master);
+    graph.prepare_buffers(16);
+    graph.reserve_buffers(2);
+    assert_eq!(graph.pooled_buffer_count(), 2);
+
+    assert!(graph.remove_edge(edge));
+    assert_eq!(graph.pooled_buffer_count(), 3);
+
+    // New connections draw from the pool.
+    graph.add_input(One, master);
+    assert_eq!(graph.pooled_buffer_count(), 2);
+
+    graph.clear_buffer_pool();
+    assert_eq!(graph.pooled_buffer_count(), 0);
+}
+
+#[test]
+fn rejected_connections_return_their_buffers_to_the_pool() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(One);
+    let b = graph.add_node(One);
+    graph.prepare_buffers(16);
+    graph.reserve_buffers(2);
+
+    // Self-connections are rejected before any buffers are drawn.
+    assert!(graph.add_connections(vec![(a, b), (b, b)]).is_err());
+    assert_eq!(graph.pooled_buffer_count(), 2);
+
+    // Connections that would cycle are only detected once added.
+    assert!(graph.add_connections(vec![(a, b), (b, a)]).is_err());
+    assert_eq!(graph.pooled_buffer_count(), 2);
+    assert_eq!(graph.connection_count(), 0);
+}
+
+#[test]
+fn prepared_graphs_render_without_allocating() {
+    let mut graph = Graph::new();
+    let master = graph.add_node(One);
+    graph.add_input(One, master);
+    graph.set_master(Some(master));
+    graph.prepare_buffers(16);
+    graph.reserve_buffers(1);
+    graph.set_assert_no_alloc(true);
+    let mut buffer = [[0.0]; 16];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer, [[1.0]; 16]);
+
+    // Connections added from the pool take a buffer that is already the right size.
+    graph.add_input(One, master);
+    assert_eq!(graph.pooled_buffer_count(), 0);
+    graph.prepare_buffers(16);
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer, [[1.0]; 16]);
+}
+
+#[test]
+#[should_panic]
+fn changing_the_buffer_size_is_caught() {
+    let mut graph = Graph::new();
+    let master = graph.add_node(One);
+    graph.add_input(One, master);
+    graph.set_master(Some(master));
+    graph.prepare_buffers(16);
+    graph.set_assert_no_alloc(true);
+    let mut buffer = [[0.0]; 32];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+}
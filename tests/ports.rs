@@ -0,0 +1,72 @@
+//! Nodes that render via `Node::process` receive each of their inputs separately.
+
+use dsp::{Graph, Node, NodeIndex};
+
+type Mono = [f32; 1];
+
+enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Outputs the product of its inputs, which requires them to arrive separately.
+    Product,
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        if let Test::Dc(value) = *self {
+            for frame in buffer.iter_mut() {
+                *frame = [value];
+            }
+        }
+    }
+
+    fn separate_io(&self) -> bool {
+        matches!(self, Test::Product)
+    }
+
+    fn process(&mut self, inputs: &[&[Mono]], output: &mut [Mono], _sample_hz: f64) {
+        for (i, frame) in output.iter_mut().enumerate() {
+            *frame = [inputs.iter().map(|input| input[i][0]).product()];
+        }
+    }
+}
+
+/// A **Product** node fed by a source for each of the given values.
+fn product(values: &[f32]) -> (Graph<Mono, Test>, NodeIndex) {
+    let mut graph = Graph::new();
+    let product = graph.add_node(Test::Product);
+    for &value in values {
+        graph.add_input(Test::Dc(value), product);
+    }
+    graph.set_master(Some(product));
+    (graph, product)
+}
+
+#[test]
+fn inputs_arrive_separately() {
+    let (mut graph, _) = product(&[5.0, 1.0, 2.0]);
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[10.0]; 8]);
+}
+
+#[test]
+fn prepared_inputs_do_not_allocate() {
+    let (mut graph, _) = product(&[3.0, 2.0]);
+    graph.prepare_buffers(16);
+    graph.set_assert_no_alloc(true);
+    let mut buffer = [[0.0]; 16];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[6.0]; 16]);
+}
+
+#[test]
+fn many_inputs_are_all_received() {
+    let mut values = vec![1.0; 40];
+    values.push(3.0);
+    let (mut graph, _) = product(&values);
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[3.0]; 4]);
+}
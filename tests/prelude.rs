@@ -0,0 +1,40 @@
+//! The prelude alone is enough to implement a **Node** and build a **Graph**.
+
+use dsp::prelude::*;
+
+/// Scales its input by the given amplitude, for any type of frame.
+struct Gain(f32);
+
+impl<F> Node<F> for Gain
+where
+    F: Frame,
+    F::Sample: DuplexSample<f32>,
+{
+    fn audio_requested(&mut self, buffer: &mut [F], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = frame.map(|s| (s.to_sample::<f32>() * self.0).to_sample());
+        }
+    }
+}
+
+#[test]
+fn graphs_are_built_from_the_prelude() {
+    let mut graph: Graph<[f32; 2], Gain> = Graph::new();
+    let a = graph.add_node(Gain(1.0));
+    let b = graph.add_node(Gain(1.0));
+    let (_, c) = graph.add_output(a, Gain(0.5));
+    graph.add_connection(b, c).unwrap();
+    graph.set_master(Some(c));
+
+    let mut inputs: Vec<NodeIndex> = graph.input_nodes(c).collect();
+    inputs.sort();
+    assert_eq!(inputs, vec![a, b]);
+    assert_eq!(graph.output_nodes(a).collect::<Vec<_>>(), vec![c]);
+    assert_eq!(graph.output_nodes(c).next(), None);
+    let mut walker = graph.inputs(c);
+    assert!(walker.next_node(&graph).is_some());
+
+    let mut buffer = [[1.0, -1.0]; 4];
+    graph[c].audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.5, -0.5]; 4]);
+}
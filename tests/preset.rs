@@ -0,0 +1,145 @@
+//! **Preset**s capture the parameters of a **Graph**, which may then morph between them.
+
+use dsp::event::Event;
+use dsp::{Graph, Node, NodeIndex, ParamSmoothing, Preset, SMOOTHING_INTERVAL};
+
+type Mono = [f32; 1];
+
+/// A sample rate at which one millisecond is one frame.
+const SAMPLE_HZ: f64 = 1_000.0;
+
+/// Outputs the value of its parameters, summed.
+struct Level([f32; 2]);
+
+impl Node<Mono> for Level {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0[0] + self.0[1]];
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { param, value } = *event {
+            self.0[param] = value;
+        }
+    }
+}
+
+/// A change to the given parameter.
+fn param(param: usize, value: f32) -> Event {
+    Event::Param { param, value }
+}
+
+/// A **Level** node as the output with its first parameter at zero.
+fn graph() -> (Graph<Mono, Level>, NodeIndex) {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Level([0.0; 2]));
+    graph.set_master(Some(node));
+    graph.send_event(node, param(0, 0.0)).unwrap();
+    render(&mut graph, 32, 32);
+    (graph, node)
+}
+
+/// Render `frames` frames in buffers of `block` frames, returning the output.
+fn render(graph: &mut Graph<Mono, Level>, frames: usize, block: usize) -> Vec<f32> {
+    let mut output = Vec::new();
+    let mut buffer = vec![[0.0]; block];
+    for _ in 0..frames / block {
+        graph.audio_requested(&mut buffer, SAMPLE_HZ);
+        output.extend(buffer.iter().map(|frame| frame[0]));
+    }
+    output
+}
+
+/// The value at the start of each interval of `SMOOTHING_INTERVAL` frames.
+fn intervals(output: &[f32]) -> Vec<f32> {
+    output.iter().step_by(SMOOTHING_INTERVAL).copied().collect()
+}
+
+/// The number of seconds spanning the given number of smoothing intervals.
+fn seconds(intervals: usize) -> f32 {
+    (intervals * SMOOTHING_INTERVAL) as f32 / SAMPLE_HZ as f32
+}
+
+#[test]
+fn presets_store_values_by_node_id_and_param() {
+    let (mut graph, first) = graph();
+    let node = graph.add_node(Level([0.0; 2]));
+    let id = graph.node_id(node).unwrap();
+    let mut preset = Preset::new().with_value(id, 0, 1.0).with_value(id, 1, 2.0);
+    preset.set(id, 0, 3.0);
+    assert_eq!(preset.len(), 2);
+    assert_eq!(preset.value(id, 0), Some(3.0));
+    assert_eq!(preset.values(), &[(id, 0, 3.0), (id, 1, 2.0)][..]);
+    assert_eq!(preset.remove(id, 1), Some(2.0));
+    assert_eq!(preset.remove(id, 1), None);
+    assert!(!preset.is_empty());
+    assert!(Preset::new().is_empty());
+
+    // Ids stay valid as the indices of nodes shift.
+    graph.remove_node(first);
+    let node = graph.index_of(id).unwrap();
+    assert_eq!(node, first);
+    graph.set_master(Some(node));
+    graph.morph_to(&preset, 0.0);
+    assert_eq!(render(&mut graph, 32, 32), vec![3.0; 32]);
+}
+
+#[test]
+fn every_known_parameter_is_captured_at_its_target() {
+    let (mut graph, node) = graph();
+    let id = graph.node_id(node).unwrap();
+    let _handle = graph.param_handle(node, 1, 0.5).unwrap();
+    graph.set_default_smoothing_ms(1_000.0);
+    graph.send_event(node, param(0, 4.0)).unwrap();
+    render(&mut graph, 32, 32);
+
+    let preset = graph.capture_preset();
+    assert_eq!(preset.len(), 2);
+    assert_eq!(preset.value(id, 0), Some(4.0));
+    assert_eq!(preset.value(id, 1), Some(0.5));
+}
+
+#[test]
+fn morphs_ramp_every_parameter_towards_the_preset() {
+    let morph = |block| {
+        let (mut graph, node) = graph();
+        let id = graph.node_id(node).unwrap();
+        let preset = Preset::new().with_value(id, 0, 4.0);
+        graph.morph_to(&preset, seconds(4));
+        render(&mut graph, 6 * SMOOTHING_INTERVAL, block)
+    };
+    let output = morph(64);
+    assert_eq!(intervals(&output), vec![1.0, 2.0, 3.0, 4.0, 4.0, 4.0]);
+    // Morphs are rendered identically regardless of the buffer size.
+    assert_eq!(morph(1), output);
+    assert_eq!(morph(96), output);
+}
+
+#[test]
+fn discrete_and_unknown_parameters_take_their_value_immediately() {
+    let (mut graph, node) = graph();
+    let id = graph.node_id(node).unwrap();
+    graph
+        .set_param_smoothing(node, 0, ParamSmoothing::Stepped)
+        .unwrap();
+    // The second parameter has never been set, so its current value is unknown.
+    let preset = Preset::new().with_value(id, 0, 2.0).with_value(id, 1, 1.0);
+    graph.morph_to(&preset, 10.0);
+    assert_eq!(render(&mut graph, 32, 32), vec![3.0; 32]);
+
+    // Values for removed nodes are ignored.
+    graph.remove_node(node);
+    graph.morph_to(&preset, 10.0);
+    assert!(graph.capture_preset().is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn presets_round_trip() {
+    let (graph, node) = graph();
+    let id = graph.node_id(node).unwrap();
+    let preset = Preset::new().with_value(id, 0, 0.25);
+    let json = serde_json::to_string(&preset).unwrap();
+    assert_eq!(serde_json::from_str::<Preset>(&json).unwrap(), preset);
+}
@@ -0,0 +1,208 @@
+//! The **Recorder** records its input into takes, punched in and out at exact transport frames.
+
+#![cfg(feature = "sampler")]
+
+use dsp::event::Event;
+use dsp::nodes::{RecordState, Recorder, Take};
+use dsp::{Graph, Node, NodeVariant, TypedNodeIndex};
+
+type Mono = [f32; 1];
+
+enum Test {
+    /// Outputs the number of frames it has rendered so far.
+    Counter(f32),
+    /// A **Recorder** node.
+    Recorder(Recorder),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], sample_hz: f64) {
+        match self {
+            Test::Counter(count) => {
+                for frame in buffer.iter_mut() {
+                    *frame = [*count];
+                    *count += 1.0;
+                }
+            }
+            Test::Recorder(recorder) => recorder.audio_requested(buffer, sample_hz),
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Test::Recorder(recorder) = self {
+            Node::<Mono>::handle_event(recorder, event);
+        }
+    }
+
+    fn always_render(&self) -> bool {
+        matches!(self, Test::Recorder(_))
+    }
+}
+
+impl From<Recorder> for Test {
+    fn from(recorder: Recorder) -> Self {
+        Test::Recorder(recorder)
+    }
+}
+
+impl NodeVariant<Recorder> for Test {
+    fn as_variant(&self) -> Option<&Recorder> {
+        match self {
+            Test::Recorder(recorder) => Some(recorder),
+            _ => None,
+        }
+    }
+
+    fn as_variant_mut(&mut self) -> Option<&mut Recorder> {
+        match self {
+            Test::Recorder(recorder) => Some(recorder),
+            _ => None,
+        }
+    }
+}
+
+/// A recorder of the given capacity fed by a counter, so that each recorded sample is the
+/// transport frame at which it was recorded.
+fn graph(capacity_frames: usize) -> (Graph<Mono, Test>, TypedNodeIndex<Recorder>) {
+    let mut graph = Graph::new();
+    let recorder = graph.add_node_typed(Recorder::new(1, capacity_frames));
+    graph.add_input(Test::Counter(0.0), recorder.node_index());
+    graph.set_master(Some(recorder.node_index()));
+    (graph, recorder)
+}
+
+/// Render `frames` frames in buffers of `4`.
+fn render(graph: &mut Graph<Mono, Test>, frames: usize) {
+    let mut buffer = [[0.0]; 4];
+    for _ in 0..frames / 4 {
+        graph.audio_requested(&mut buffer, 44_100.0);
+    }
+}
+
+/// The samples recorded into a take, as transport frames.
+fn frames(take: &Take) -> Vec<u64> {
+    take.samples().iter().map(|&s| s as u64).collect()
+}
+
+#[test]
+fn scheduled_punches_record_exactly_the_punched_frames() {
+    let (mut graph, recorder) = graph(64);
+    graph.node_as_mut(recorder).unwrap().arm();
+    graph.schedule_punch(recorder, 3..10).unwrap();
+    graph.schedule_punch(recorder, 13..14).unwrap();
+    render(&mut graph, 16);
+
+    let recorder = graph.node_as_mut(recorder).unwrap();
+    assert_eq!(recorder.state(), RecordState::Armed);
+    assert_eq!(recorder.recorded_frames(), 8);
+    let takes = recorder.collect_takes();
+    assert_eq!(takes.len(), 2);
+    assert_eq!(takes[0].start(), Some(3));
+    assert_eq!(frames(&takes[0]), (3..10).collect::<Vec<_>>());
+    assert_eq!(takes[1].start(), Some(13));
+    assert_eq!(frames(&takes[1]), vec![13]);
+    assert_eq!(recorder.recorded_frames(), 0);
+}
+
+#[test]
+fn punches_are_ignored_until_the_recorder_is_armed() {
+    let (mut graph, recorder) = graph(64);
+    graph.schedule_punch(recorder, 0..4).unwrap();
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    // The input passes through unchanged regardless.
+    assert_eq!(buffer, [[0.0], [1.0], [2.0], [3.0]]);
+    assert_eq!(graph.node_as(recorder).unwrap().recorded_frames(), 0);
+
+    // Recorders may also be armed and punched via events.
+    let arm = Event::Param {
+        param: 0,
+        value: 1.0,
+    };
+    graph.schedule_event(recorder.node_index(), 4, arm).unwrap();
+    graph.schedule_punch(recorder, 6..8).unwrap();
+    render(&mut graph, 8);
+    let takes = graph.node_as_mut(recorder).unwrap().collect_takes();
+    assert_eq!(takes.len(), 1);
+    assert_eq!(takes[0].start(), Some(6));
+    assert_eq!(frames(&takes[0]), vec![6, 7]);
+}
+
+#[test]
+fn each_pass_over_the_loop_becomes_a_take() {
+    let (mut graph, recorder) = graph(64);
+    let node = graph.node_as_mut(recorder).unwrap();
+    node.arm();
+    node.set_loop_frames(Some(4));
+    assert_eq!(node.loop_frames(), Some(4));
+    graph.schedule_punch(recorder, 2..12).unwrap();
+    render(&mut graph, 16);
+
+    let takes = graph.node_as_mut(recorder).unwrap().collect_takes();
+    let passes: Vec<_> = takes
+        .iter()
+        .map(|take| (take.pass(), take.frames()))
+        .collect();
+    assert_eq!(passes, vec![(0, 4), (1, 4), (2, 2)]);
+    assert!(takes.iter().all(|take| take.start() == Some(2)));
+    assert_eq!(frames(&takes[2]), vec![10, 11]);
+}
+
+#[test]
+fn recorders_added_while_rendering_are_rendered() {
+    let mut graph = Graph::new();
+    let counter = graph.add_node(Test::Counter(0.0));
+    graph.set_master(Some(counter));
+    render(&mut graph, 4);
+
+    // The recorder does not feed the output, so it is only rendered as it asks to always be.
+    let recorder = graph.add_node_typed(Recorder::new(1, 64));
+    let node = graph.node_as_mut(recorder).unwrap();
+    node.arm();
+    node.punch_in(None);
+    render(&mut graph, 8);
+    assert_eq!(graph.node_as(recorder).unwrap().recorded_frames(), 8);
+}
+
+#[test]
+fn a_full_buffer_ends_the_take_until_it_is_collected() {
+    let mut recorder = Recorder::new(2, 3);
+    assert_eq!(recorder.capacity_frames(), 3);
+    recorder.arm();
+    recorder.punch_in(None);
+    let mut buffer = [[1.0, -1.0]; 4];
+    recorder.audio_requested(&mut buffer, 44_100.0);
+    assert!(recorder.is_full());
+    assert_eq!(recorder.state(), RecordState::Armed);
+    recorder.punch_in(None);
+    assert_eq!(recorder.state(), RecordState::Armed);
+
+    let takes = recorder.collect_takes();
+    assert_eq!(takes.len(), 1);
+    assert_eq!(takes[0].start(), None);
+    assert_eq!(takes[0].channels(), 2);
+    assert_eq!(takes[0].frames(), 3);
+    assert_eq!(takes[0].samples(), &[1.0, -1.0, 1.0, -1.0, 1.0, -1.0][..]);
+    assert!(!recorder.is_full());
+}
+
+#[test]
+fn takes_in_progress_are_kept_when_collecting() {
+    let mut recorder = Recorder::new(1, 16);
+    recorder.arm();
+    recorder.punch_in(None);
+    let mut buffer = [[0.5]; 4];
+    Node::<Mono>::audio_requested(&mut recorder, &mut buffer, 44_100.0);
+    assert!(recorder.collect_takes().is_empty());
+    assert_eq!(recorder.recorded_frames(), 4);
+
+    Node::<Mono>::audio_requested(&mut recorder, &mut buffer, 44_100.0);
+    recorder.punch_out();
+    let takes = recorder.collect_takes();
+    assert_eq!(takes.len(), 1);
+    assert_eq!(takes[0].frames(), 8);
+
+    recorder.disarm();
+    recorder.punch_in(None);
+    assert_eq!(recorder.state(), RecordState::Idle);
+}
@@ -0,0 +1,53 @@
+//! Nodes are removed from a **Graph** in batches, remapping the indices of those that remain.
+
+mod common;
+
+use common::{render, Mono, Test};
+use dsp::{Graph, NodeIndex};
+
+/// A constant feeding a chain of gains of `2`, `3` and `4`, with the last as the master.
+fn chain() -> (Graph<Mono, Test>, [NodeIndex; 4]) {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(1.0));
+    let (_, a) = graph.add_output(dc, Test::Gain(2.0));
+    let (_, b) = graph.add_output(a, Test::Gain(3.0));
+    let (_, c) = graph.add_output(b, Test::Gain(4.0));
+    graph.set_master(Some(c));
+    (graph, [dc, a, b, c])
+}
+
+#[test]
+fn indices_refer_to_the_nodes_before_any_were_removed() {
+    let (mut graph, [dc, a, b, c]) = chain();
+    let ab = graph.find_connection(a, b).unwrap();
+
+    let map = graph.remove_nodes(vec![dc, c, dc, NodeIndex::new(9)]);
+    assert_eq!(graph.node_count(), 2);
+    assert_eq!(graph.connection_count(), 1);
+    assert_eq!(map.node(dc), None);
+    assert_eq!(map.node(c), None);
+    let (a, b) = (map.node(a).unwrap(), map.node(b).unwrap());
+    assert_eq!(graph[a], Test::Gain(2.0));
+    assert_eq!(graph[b], Test::Gain(3.0));
+    assert_eq!(graph.find_connection(a, b), map.connection(ab));
+    assert_eq!(graph.master_index(), None);
+}
+
+#[test]
+fn nodes_are_retained_by_predicate() {
+    let (mut graph, [dc, a, b, c]) = chain();
+    let mut visited = Vec::new();
+    let map = graph.retain_nodes(|idx, node| {
+        visited.push(idx);
+        *node != Test::Gain(3.0)
+    });
+    assert_eq!(visited, vec![dc, a, b, c]);
+    assert_eq!(map.node(b), None);
+    assert_eq!(graph.node_count(), 3);
+    assert_eq!(graph.connection_count(), 1);
+
+    let (a, c) = (map.node(a).unwrap(), map.node(c).unwrap());
+    assert_eq!(graph.master_index(), Some(c));
+    graph.add_connection(a, c).unwrap();
+    assert_eq!(render(&mut graph), 8.0);
+}
@@ -0,0 +1,107 @@
+//! Only the nodes feeding the requested node are rendered, along with those always rendered.
+
+use dsp::{Graph, Node};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+type Mono = [f32; 1];
+
+/// Counts the frames it renders, optionally asking to always be rendered.
+struct Counter {
+    frames: Arc<AtomicUsize>,
+    always: bool,
+}
+
+impl Counter {
+    fn new(always: bool) -> (Self, Arc<AtomicUsize>) {
+        let frames = Arc::new(AtomicUsize::new(0));
+        let counter = Counter {
+            frames: frames.clone(),
+            always,
+        };
+        (counter, frames)
+    }
+}
+
+impl Node<Mono> for Counter {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        self.frames.fetch_add(buffer.len(), Ordering::SeqCst);
+    }
+
+    fn always_render(&self) -> bool {
+        self.always
+    }
+}
+
+#[test]
+fn unheard_branches_are_skipped() {
+    let mut graph = Graph::new();
+    let (src, src_frames) = Counter::new(false);
+    let (out, out_frames) = Counter::new(false);
+    let (other, other_frames) = Counter::new(false);
+    let src = graph.add_node(src);
+    let (_, out) = graph.add_output(src, out);
+    graph.add_node(other);
+    graph.set_master(Some(out));
+
+    let mut buffer = [[0.0]; 16];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(src_frames.load(Ordering::SeqCst), 16);
+    assert_eq!(out_frames.load(Ordering::SeqCst), 16);
+    assert_eq!(other_frames.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn sinks_off_the_output_path_may_opt_in() {
+    let mut graph = Graph::new();
+    let (src, _) = Counter::new(false);
+    let (out, _) = Counter::new(false);
+    let (meter, meter_frames) = Counter::new(true);
+    let (meter_input, meter_input_frames) = Counter::new(false);
+    let (sink, sink_frames) = Counter::new(false);
+    let src = graph.add_node(src);
+    let (_, out) = graph.add_output(src, out);
+    // The meter hangs off the source via a node of its own, which must be rendered for it.
+    let (_, meter_input) = graph.add_output(src, meter_input);
+    graph.add_output(meter_input, meter);
+    graph.add_output(src, sink);
+    graph.set_master(Some(out));
+
+    let mut buffer = [[0.0]; 16];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(meter_frames.load(Ordering::SeqCst), 16);
+    assert_eq!(meter_input_frames.load(Ordering::SeqCst), 16);
+    assert_eq!(sink_frames.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn nested_graphs_with_meters_are_always_rendered() {
+    let mut graph: Graph<Mono, Counter> = Graph::new();
+    assert!(!Node::<Mono>::always_render(&graph));
+    graph.add_node(Counter::new(true).0);
+    assert!(Node::<Mono>::always_render(&graph));
+}
+
+#[cfg(feature = "analysis")]
+#[test]
+fn crate_meters_are_always_rendered() {
+    let tuner = dsp::nodes::Tuner::new(2048);
+    assert!(Node::<Mono>::always_render(&tuner));
+}
+
+#[test]
+fn replacements_that_are_always_rendered_are_rendered() {
+    let mut graph = Graph::new();
+    let (out, _) = Counter::new(false);
+    let (sink, _) = Counter::new(false);
+    let (meter, meter_frames) = Counter::new(true);
+    let out = graph.add_node(out);
+    let (_, sink) = graph.add_output(out, sink);
+    graph.set_master(Some(out));
+    let mut buffer = [[0.0]; 16];
+    graph.audio_requested(&mut buffer, 44_100.0);
+
+    graph.replace_node(sink, meter);
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(meter_frames.load(Ordering::SeqCst), 16);
+}
@@ -0,0 +1,172 @@
+//! Replacing a node swaps it in place, leaving its connections intact, optionally crossfading from
+//! the old node.
+
+mod common;
+
+use common::{render, Mono};
+use dsp::event::Event;
+use dsp::{Graph, Node, Notification, PanicPolicy};
+
+#[derive(Debug, PartialEq)]
+enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Scales its input.
+    Gain(f32),
+    /// Panics when rendered.
+    Boom,
+    /// Outputs a constant while holding any notes.
+    Synth(f32, Vec<u8>),
+    /// Outputs the product of its inputs, scaled by the given amount.
+    Product(f32),
+    /// Scales its input, mixing in the given amount of the dry signal.
+    Blend { gain: f32, dry: f32 },
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = match *self {
+                Test::Dc(value) => [value],
+                Test::Gain(gain) => [frame[0] * gain],
+                Test::Boom => panic!("boom"),
+                Test::Synth(value, ref notes) if !notes.is_empty() => [value],
+                Test::Synth(..) => [0.0],
+                Test::Product(_) => unreachable!("products are rendered via `process`"),
+                Test::Blend { gain, .. } => [frame[0] * gain],
+            };
+        }
+    }
+
+    fn separate_io(&self) -> bool {
+        matches!(self, Test::Product(_))
+    }
+
+    fn process(&mut self, inputs: &[&[Mono]], output: &mut [Mono], _sample_hz: f64) {
+        if let Test::Product(scale) = *self {
+            for (i, frame) in output.iter_mut().enumerate() {
+                *frame = [inputs.iter().map(|input| input[i][0]).product::<f32>() * scale];
+            }
+        }
+    }
+
+    fn dry(&self) -> f32 {
+        match *self {
+            Test::Blend { dry, .. } => dry,
+            _ => 0.0,
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let (Test::Synth(_, notes), Event::NoteOn { note, .. }) = (self, event) {
+            notes.push(*note);
+        }
+    }
+
+    fn held_notes(&self, notes: &mut Vec<Event>) {
+        if let Test::Synth(_, held) = self {
+            notes.extend(held.iter().map(|&note| Event::NoteOn {
+                channel: 0,
+                note,
+                velocity: 1.0,
+            }));
+        }
+    }
+}
+
+#[test]
+fn replaced_nodes_keep_their_connections_and_id() {
+    let mut graph = Graph::new();
+    let gain = graph.add_node(Test::Gain(0.5));
+    graph.add_input(Test::Dc(2.0), gain);
+    graph.set_master(Some(gain));
+    let id = graph.node_id(gain);
+    assert_eq!(render(&mut graph), 1.0);
+
+    assert_eq!(
+        graph.replace_node(gain, Test::Gain(4.0)),
+        Some(Test::Gain(0.5))
+    );
+    assert_eq!(graph.connection_count(), 1);
+    assert_eq!(graph.node_id(gain), id);
+    assert_eq!(render(&mut graph), 8.0);
+
+    let missing = dsp::NodeIndex::new(10);
+    assert_eq!(graph.replace_node(missing, Test::Dc(0.0)), None);
+}
+
+#[test]
+fn replacing_a_panicked_node_stops_bypassing_it() {
+    std::panic::set_hook(Box::new(|_| ()));
+    let mut graph = Graph::new();
+    let boom = graph.add_node(Test::Boom);
+    graph.set_master(Some(boom));
+    graph.set_panic_policy(PanicPolicy::Silence);
+    assert_eq!(render(&mut graph), 0.0);
+    assert!(graph.has_panicked(boom));
+
+    graph.replace_node(boom, Test::Dc(1.0));
+    assert!(!graph.has_panicked(boom));
+    assert_eq!(render(&mut graph), 1.0);
+}
+
+#[test]
+fn crossfaded_replacements_take_over_held_notes() {
+    let mut graph = Graph::new();
+    let synth = graph.add_node(Test::Synth(1.0, vec![60]));
+    graph.set_master(Some(synth));
+    let notifications = graph.subscribe_notifications();
+
+    graph
+        .crossfade_replace_node(synth, Test::Synth(3.0, Vec::new()), 4)
+        .unwrap();
+    assert_eq!(graph[synth], Test::Synth(3.0, vec![60]));
+    assert!(graph.is_crossfading_replacement(synth));
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[1.5], [2.0], [2.5], [3.0]]);
+
+    // Once the crossfade completes, the old node is handed back for collection.
+    assert!(!graph.is_crossfading_replacement(synth));
+    let received: Vec<_> = notifications.try_iter().collect();
+    assert_eq!(received, vec![Notification::NodeReplaced(synth)]);
+    let retired: Vec<_> = graph.drain_replaced_nodes().collect();
+    assert_eq!(retired, vec![Test::Synth(1.0, vec![60])]);
+    assert_eq!(render(&mut graph), 3.0);
+}
+
+#[test]
+fn crossfaded_nodes_that_process_their_inputs_separately_are_rendered_as_usual() {
+    let mut graph = Graph::new();
+    let product = graph.add_node(Test::Product(1.0));
+    graph.add_input(Test::Dc(2.0), product);
+    graph.add_input(Test::Dc(3.0), product);
+    graph.set_master(Some(product));
+    assert_eq!(render(&mut graph), 6.0);
+
+    graph
+        .crossfade_replace_node(product, Test::Product(2.0), 4)
+        .unwrap();
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[7.5], [9.0], [10.5], [12.0]]);
+}
+
+#[test]
+fn crossfaded_nodes_keep_their_dry_signal() {
+    let mut graph = Graph::new();
+    let blend = graph.add_node(Test::Blend {
+        gain: 0.0,
+        dry: 1.0,
+    });
+    graph.add_input(Test::Dc(2.0), blend);
+    graph.set_master(Some(blend));
+    assert_eq!(render(&mut graph), 2.0);
+
+    graph
+        .crossfade_replace_node(blend, Test::Gain(2.0), 4)
+        .unwrap();
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[2.5], [3.0], [3.5], [4.0]]);
+}
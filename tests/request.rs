@@ -0,0 +1,69 @@
+//! Graph methods given stale indices return errors rather than panicking.
+
+use dsp::{Graph, Node, NodeIndex, RequestError};
+
+type Mono = [f32; 1];
+
+struct Constant(f32);
+
+impl Node<Mono> for Constant {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+}
+
+#[test]
+fn stale_indices_are_errors() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Constant(0.25));
+    let b = graph.add_node(Constant(0.5));
+    let stale = NodeIndex::new(2);
+
+    let mut buffer = [[0.0]; 4];
+    assert_eq!(
+        graph.try_audio_requested_from(stale, &mut buffer, 44_100.0),
+        Err(RequestError::NoNode(stale))
+    );
+    assert_eq!(
+        graph.try_add_connection(a, stale),
+        Err(RequestError::NoNode(stale))
+    );
+    assert_eq!(
+        graph
+            .try_add_connections(vec![(a, b), (stale, b)])
+            .map(|_| ()),
+        Err(RequestError::NoNode(stale))
+    );
+    assert_eq!(graph.connection_count(), 0);
+    assert!(graph.try_add_input(Constant(1.0), stale).is_err());
+    assert!(graph.try_add_output(stale, Constant(1.0)).is_err());
+    assert!(graph.node(stale).is_none());
+    assert!(graph.try_index_twice_mut(a, stale).is_none());
+    assert!(graph.try_index_twice_mut(a, a).is_none());
+    assert_eq!(graph.node_count(), 2);
+}
+
+#[test]
+fn valid_indices_succeed() {
+    let mut graph = Graph::new();
+    let a = graph.add_node(Constant(0.25));
+    let b = graph.add_node(Constant(0.5));
+    let edge = graph.try_add_connection(a, b).unwrap();
+    assert_eq!(graph.find_connection(a, b), Some(edge));
+    assert_eq!(
+        graph.try_add_connection(b, a),
+        Err(RequestError::WouldCycle)
+    );
+
+    let (first, second) = graph.try_index_twice_mut(a, b).unwrap();
+    first.0 = second.0;
+    assert_eq!(graph[a].0, 0.5);
+
+    let mut buffer = [[0.0]; 4];
+    graph
+        .try_audio_requested_from(b, &mut buffer, 44_100.0)
+        .unwrap();
+    assert_eq!(buffer, [[0.5]; 4]);
+}
@@ -0,0 +1,103 @@
+//! The **ResonatorBank** rings at the frequency of each partial when struck.
+
+#![cfg(feature = "filters")]
+
+use dsp::event::Event;
+use dsp::nodes::{Partial, ResonatorBank};
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// Strike the bank with a unit impulse, returning `len` frames of its ringing.
+fn strike(bank: &mut ResonatorBank, len: usize) -> Vec<f32> {
+    let mut buffer = vec![[0.0]; len];
+    buffer[0] = [1.0];
+    bank.audio_requested(&mut buffer, SAMPLE_HZ);
+    buffer.iter().map(|frame| frame[0]).collect()
+}
+
+/// The number of times the signal changes sign.
+fn zero_crossings(signal: &[f32]) -> usize {
+    signal
+        .windows(2)
+        .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+        .count()
+}
+
+/// The loudest sample within the given signal.
+fn peak(signal: &[f32]) -> f32 {
+    signal.iter().fold(0.0, |peak, s| f32::max(peak, s.abs()))
+}
+
+/// Send the bank a parameter event.
+fn set_param(bank: &mut ResonatorBank, param: usize, value: f32) {
+    Node::<Mono>::handle_event(bank, &Event::Param { param, value });
+}
+
+#[test]
+fn a_struck_partial_rings_at_its_frequency_and_decays() {
+    let mut bank = ResonatorBank::new(vec![Partial::new(441.0, 0.5, 1.0)]);
+    let ringing = strike(&mut bank, SAMPLE_HZ as usize);
+    // 441hz crosses zero twice per cycle, 88 times over the first tenth of a second.
+    let crossings = zero_crossings(&ringing[..4_410]);
+    assert!((86..=90).contains(&crossings), "{}", crossings);
+
+    let start = peak(&ringing[..200]);
+    assert!((start - 1.0).abs() < 0.01, "{}", start);
+    // After the decay time the ringing has fallen by 60dB.
+    let decayed = peak(&ringing[22_050..22_250]);
+    assert!((decayed / start - 0.001).abs() < 0.0002, "{}", decayed);
+}
+
+#[test]
+fn partials_from_ratios_decay_sooner_and_ring_quieter() {
+    let bank = ResonatorBank::from_ratios(100.0, &[1.0, 2.0, 4.0], 2.0);
+    let expected = [
+        Partial::new(100.0, 2.0, 1.0),
+        Partial::new(200.0, 1.0, 0.5),
+        Partial::new(400.0, 0.5, 0.25),
+    ];
+    assert_eq!(bank.partials(), &expected[..]);
+}
+
+#[test]
+fn partials_are_set_via_events_and_retuned_together() {
+    let mut bank = ResonatorBank::new(vec![
+        Partial::new(441.0, 0.5, 1.0),
+        Partial::new(882.0, 0.5, 0.0),
+    ]);
+    set_param(&mut bank, 3, 220.5);
+    set_param(&mut bank, 5, 1.0);
+    set_param(&mut bank, 2, 0.0);
+    assert_eq!(bank.partials()[1], Partial::new(220.5, 0.5, 1.0));
+    // Parameters of partials that do not exist are ignored.
+    set_param(&mut bank, 6, 1.0);
+    assert_eq!(bank.partials().len(), 2);
+    let crossings = zero_crossings(&strike(&mut bank, 4_410));
+    assert!((42..=46).contains(&crossings), "{}", crossings);
+
+    bank.reset();
+    bank.set_tuning(2.0);
+    assert_eq!(bank.tuning(), 2.0);
+    let crossings = zero_crossings(&strike(&mut bank, 4_410));
+    assert!((86..=90).contains(&crossings), "{}", crossings);
+}
+
+#[test]
+fn the_bank_is_silent_once_the_ringing_dies_away() {
+    let mut bank = ResonatorBank::new(vec![Partial::new(441.0, 0.125, 1.0)]);
+    assert!(Node::<Mono>::is_silent(&bank));
+    strike(&mut bank, 64);
+    assert!(!Node::<Mono>::is_silent(&bank));
+    assert_eq!(Node::<Mono>::tail_frames(&bank), 5_513);
+
+    let mut buffer = [[0.0]; 1_024];
+    for _ in 0..24 {
+        buffer = [[0.0]; 1_024];
+        bank.audio_requested(&mut buffer, SAMPLE_HZ);
+    }
+    assert!(Node::<Mono>::is_silent(&bank));
+    assert_eq!(buffer, [[0.0]; 1_024]);
+}
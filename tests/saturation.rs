@@ -0,0 +1,87 @@
+//! **Saturation** shapes its input through a transfer curve that never exceeds full scale.
+
+#![cfg(feature = "dynamics")]
+
+use dsp::event::Event;
+use dsp::nodes::{Saturation, SaturationCurve};
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+/// Saturate the given samples, returning the output.
+fn saturate(saturation: &mut Saturation, input: &[f32]) -> Vec<f32> {
+    let mut buffer: Vec<Mono> = input.iter().map(|&s| [s]).collect();
+    saturation.audio_requested(&mut buffer, 44_100.0);
+    buffer.iter().map(|frame| frame[0]).collect()
+}
+
+/// Set a parameter of the saturation via an event.
+fn set_param(saturation: &mut Saturation, param: usize, value: f32) {
+    Node::<Mono>::handle_event(saturation, &Event::Param { param, value });
+}
+
+#[test]
+fn every_curve_passes_quiet_signals_and_never_exceeds_full_scale() {
+    for &curve in &SaturationCurve::ALL {
+        let quiet = curve.apply(0.001);
+        assert!((quiet - 0.001).abs() < 1e-5, "{:?}", curve);
+        for &x in &[1.0, 2.0, 10.0, 1_000.0, -1.0, -10.0, -1_000.0] {
+            let y = curve.apply(x);
+            assert!(y.abs() <= 1.0, "{:?} {}", curve, y);
+            assert_eq!(y.signum(), x.signum());
+        }
+    }
+    assert_eq!(SaturationCurve::Cubic.apply(1.5), 1.0);
+    assert_eq!(SaturationCurve::Cubic.apply(-4.0), -1.0);
+}
+
+#[test]
+fn the_diode_curve_saturates_positive_half_cycles_sooner() {
+    let diode = SaturationCurve::Diode;
+    assert!(diode.apply(1.0) < -diode.apply(-1.0));
+    assert_eq!(
+        SaturationCurve::Tanh.apply(1.0),
+        -SaturationCurve::Tanh.apply(-1.0)
+    );
+}
+
+#[test]
+fn the_trims_drive_the_curve_and_restore_the_level() {
+    let mut saturation = Saturation::new(SaturationCurve::Tanh);
+    assert_eq!(saturate(&mut saturation, &[0.5]), vec![0.5f32.tanh()]);
+
+    // 20dB of drive pushes the same signal much further into the curve.
+    let mut driven = Saturation::default()
+        .with_input_db(20.0)
+        .with_output_db(-6.0);
+    let out = saturate(&mut driven, &[0.5])[0];
+    let expected = 5.0f32.tanh() * 10.0f32.powf(-6.0 / 20.0);
+    assert!((out - expected).abs() < 1e-5);
+}
+
+#[test]
+fn the_clipper_keeps_a_hot_signal_below_full_scale() {
+    let mut clipper = Saturation::clipper();
+    assert_eq!(clipper.curve, SaturationCurve::Cubic);
+    let out = saturate(&mut clipper, &[0.1, 0.9, 1.4, 3.0, -8.0]);
+    assert!((out[0] - 0.1).abs() < 0.001);
+    assert!(out.iter().all(|s| s.abs() <= 1.0));
+    assert_eq!(&out[3..], &[1.0, -1.0]);
+}
+
+#[test]
+fn parameters_are_set_via_events() {
+    let mut saturation = Saturation::default();
+    set_param(&mut saturation, 0, 6.0);
+    set_param(&mut saturation, 1, -3.0);
+    set_param(&mut saturation, 2, 3.0);
+    assert_eq!(saturation.input_db, 6.0);
+    assert_eq!(saturation.output_db, -3.0);
+    assert_eq!(saturation.curve, SaturationCurve::Tape);
+
+    // Curves beyond the last are ignored, and others are rounded to the nearest index.
+    set_param(&mut saturation, 2, 4.0);
+    assert_eq!(saturation.curve, SaturationCurve::Tape);
+    set_param(&mut saturation, 2, 1.2);
+    assert_eq!(saturation.curve, SaturationCurve::Cubic);
+}
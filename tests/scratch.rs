@@ -0,0 +1,55 @@
+//! The **Scratch** arena partitions a single allocation into regions, e.g. one per port.
+
+use dsp::Scratch;
+
+type Mono = [f32; 1];
+
+#[test]
+fn regions_are_separate() {
+    let mut scratch: Scratch<Mono> = Scratch::with_capacity(4, 2);
+    let a = scratch.alloc(4);
+    let b = scratch.alloc(4);
+    assert_eq!(scratch.len(), 2);
+    assert_eq!(scratch.get(a), Some(&[[0.0]; 4][..]));
+
+    let (region_b, region_a) = scratch.get_pair_mut(b, a);
+    region_a[0] = [1.0];
+    region_b[0] = [2.0];
+    for region in scratch.iter_mut() {
+        region[1] = [3.0];
+    }
+    let regions: Vec<_> = scratch.iter().collect();
+    assert_eq!(regions[0], &[[1.0], [3.0], [0.0], [0.0]]);
+    assert_eq!(regions[1], &[[2.0], [3.0], [0.0], [0.0]]);
+}
+
+#[test]
+fn reserved_capacity_is_not_exceeded() {
+    let mut scratch: Scratch<Mono> = Scratch::with_capacity(4, 1);
+    assert!(scratch.try_alloc(4).is_some());
+    assert!(scratch.try_alloc(4).is_none());
+    assert_eq!(scratch.len(), 1);
+
+    // Clearing frees the capacity for reuse.
+    scratch.clear();
+    assert!(scratch.is_empty());
+    assert!(scratch.try_alloc(4).is_some());
+}
+
+#[test]
+fn cleared_handles_are_invalidated() {
+    let mut scratch: Scratch<Mono> = Scratch::new();
+    let handle = scratch.alloc(4);
+    scratch.clear();
+    scratch.alloc(4);
+    assert!(scratch.get(handle).is_none());
+    assert!(scratch.get_mut(handle).is_none());
+}
+
+#[test]
+#[should_panic]
+fn a_region_cannot_be_borrowed_twice() {
+    let mut scratch: Scratch<Mono> = Scratch::new();
+    let handle = scratch.alloc(4);
+    scratch.get_pair_mut(handle, handle);
+}
@@ -0,0 +1,36 @@
+//! Any `dasp::Signal` may be used as a source within the **Graph**.
+
+#![cfg(feature = "osc")]
+
+use dsp::nodes::SignalNode;
+use dsp::{signal, Graph, Node};
+
+type Mono = [f32; 1];
+
+#[test]
+fn frames_are_rendered_until_the_signal_is_exhausted() {
+    let frames = vec![[1.0], [2.0], [3.0]];
+    let mut node = SignalNode::from(signal::from_iter(frames));
+    assert!(!Node::<Mono>::is_silent(&node));
+    let mut buffer = [[9.0]; 2];
+    node.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[1.0], [2.0]]);
+    node.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer[0], [3.0]);
+
+    assert!(Node::<Mono>::is_silent(&node));
+    let mut buffer = [[9.0]; 2];
+    node.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.0]; 2]);
+}
+
+#[test]
+fn signals_are_graph_sources() {
+    let mut graph: Graph<Mono, SignalNode<_>> = Graph::new();
+    let source = graph.add_node(SignalNode::new(signal::gen(|| [0.5])));
+    graph.set_master(Some(source));
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.5]; 4]);
+    assert!(!graph.is_silent(source));
+}
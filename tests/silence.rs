@@ -0,0 +1,75 @@
+//! Idle branches of the **Graph** are skipped and their silence propagated downstream.
+
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+/// A voice that adds `1.0` to its input while playing, counting the requests that it renders.
+struct Voice {
+    playing: bool,
+    requests: usize,
+}
+
+impl Voice {
+    fn new(playing: bool) -> Self {
+        Voice {
+            playing,
+            requests: 0,
+        }
+    }
+}
+
+impl Node<Mono> for Voice {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        self.requests += 1;
+        if self.playing {
+            for frame in buffer.iter_mut() {
+                frame[0] += 1.0;
+            }
+        }
+    }
+
+    fn is_silent(&self) -> bool {
+        !self.playing
+    }
+}
+
+/// Render a buffer, returning its first frame.
+fn render(graph: &mut Graph<Mono, Voice>) -> f32 {
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    buffer[0][0]
+}
+
+#[test]
+fn silent_branches_are_skipped() {
+    let mut graph = Graph::new();
+    let master = graph.add_node(Voice::new(true));
+    let (_, effect) = graph.add_input(Voice::new(false), master);
+    let (_, voice) = graph.add_input(Voice::new(false), effect);
+    graph.set_master(Some(master));
+
+    assert_eq!(render(&mut graph), 1.0);
+    assert_eq!(graph[voice].requests + graph[effect].requests, 0);
+    assert!(graph.is_silent(voice) && graph.is_silent(effect));
+    assert!(!graph.is_silent(master));
+
+    // Once the voice plays, the silent effect downstream is rendered as its input is not silent.
+    graph[voice].playing = true;
+    assert_eq!(render(&mut graph), 2.0);
+    assert_eq!(graph[voice].requests, 1);
+    assert_eq!(graph[effect].requests, 1);
+    assert!(!graph.is_silent(effect));
+}
+
+#[test]
+fn nodes_that_may_sound_are_always_rendered() {
+    let mut graph = Graph::new();
+    let master = graph.add_node(Voice::new(true));
+    let (_, voice) = graph.add_input(Voice::new(true), master);
+    graph.set_master(Some(master));
+    render(&mut graph);
+    render(&mut graph);
+    assert_eq!(graph[voice].requests, 2);
+    assert_eq!(graph[master].requests, 2);
+}
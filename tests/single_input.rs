@@ -0,0 +1,79 @@
+//! Nodes with a single input, or that are fully wet, render the same as any other node.
+
+mod common;
+
+use common::Mono;
+use dsp::{Graph, Node};
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Doubles its input, mixing the result with the given amounts of the dry and wet signal.
+    Double { dry: f32, wet: f32 },
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = match *self {
+                Test::Dc(value) => [value],
+                Test::Double { .. } => [frame[0] * 2.0],
+            };
+        }
+    }
+
+    fn dry(&self) -> f32 {
+        match *self {
+            Test::Double { dry, .. } => dry,
+            _ => 0.0,
+        }
+    }
+
+    fn wet(&self) -> f32 {
+        match *self {
+            Test::Double { wet, .. } => wet,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Render a buffer from the node at the given index, returning its first frame.
+fn render_from(graph: &mut Graph<Mono, Test>, idx: dsp::NodeIndex) -> f32 {
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested_from(idx, &mut buffer, SAMPLE_HZ);
+    assert!(buffer.iter().all(|frame| *frame == buffer[0]));
+    buffer[0][0]
+}
+
+#[test]
+fn single_inputs_are_passed_through_to_the_node() {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Dc(1.0));
+    let (_, wet) = graph.add_output(src, Test::Double { dry: 0.0, wet: 1.0 });
+    let (_, mixed) = graph.add_output(
+        wet,
+        Test::Double {
+            dry: 0.5,
+            wet: 0.25,
+        },
+    );
+    assert_eq!(render_from(&mut graph, wet), 2.0);
+    // 2.0 * 0.5 dry plus 4.0 * 0.25 wet.
+    assert_eq!(render_from(&mut graph, mixed), 2.0);
+    // The source's output is left intact for its other outputs.
+    assert_eq!(render_from(&mut graph, src), 1.0);
+}
+
+#[test]
+fn single_and_multiple_inputs_are_mixed_alike() {
+    let mut graph = Graph::new();
+    let single = graph.add_node(Test::Double { dry: 0.5, wet: 0.5 });
+    graph.add_input(Test::Dc(3.0), single);
+    let multiple = graph.add_node(Test::Double { dry: 0.5, wet: 0.5 });
+    graph.add_input(Test::Dc(1.0), multiple);
+    graph.add_input(Test::Dc(2.0), multiple);
+    assert_eq!(render_from(&mut graph, single), 4.5);
+    assert_eq!(render_from(&mut graph, multiple), 4.5);
+}
@@ -0,0 +1,41 @@
+//! Gain ramps and crossfades over slices of frames.
+
+use dsp::slice::{apply_ramp, crossfade_into};
+
+type Mono = [f32; 1];
+
+#[test]
+fn ramps_reach_their_target_one_frame_past_the_end() {
+    let mut buffer = [[1.0]; 4];
+    apply_ramp(&mut buffer, 0.0, 1.0);
+    assert_eq!(buffer, [[0.0], [0.25], [0.5], [0.75]]);
+
+    // Consecutive ramps join seamlessly.
+    let mut next = [[1.0]; 4];
+    apply_ramp(&mut next, 1.0, 0.0);
+    assert_eq!(next, [[1.0], [0.75], [0.5], [0.25]]);
+}
+
+#[test]
+fn flat_ramps_apply_a_constant_gain() {
+    let mut buffer: [Mono; 3] = [[2.0]; 3];
+    apply_ramp(&mut buffer, 0.5, 0.5);
+    assert_eq!(buffer, [[1.0]; 3]);
+    apply_ramp(&mut buffer, 1.0, 1.0);
+    assert_eq!(buffer, [[1.0]; 3]);
+}
+
+#[test]
+fn crossfades_move_from_the_output_to_the_input() {
+    let mut output = [[1.0]; 4];
+    let input = [[0.0]; 4];
+    crossfade_into(&mut output, &input, 0.0, 1.0);
+    assert_eq!(output, [[1.0], [0.75], [0.5], [0.25]]);
+}
+
+#[test]
+#[should_panic]
+fn crossfades_require_buffers_of_equal_length() {
+    let mut output: [Mono; 4] = [[1.0]; 4];
+    crossfade_into(&mut output, &[[0.0]; 3], 0.0, 1.0);
+}
@@ -0,0 +1,147 @@
+//! The **Graph** smooths parameter changes into linear ramps, aligned to transport frames.
+
+use dsp::event::Event;
+use dsp::{Graph, Node, NodeIndex, ParamSmoothing, SMOOTHING_INTERVAL};
+
+type Mono = [f32; 1];
+
+/// A sample rate at which one millisecond is one frame.
+const SAMPLE_HZ: f64 = 1_000.0;
+
+/// Outputs the value of its parameters, summed.
+struct Level([f32; 2]);
+
+impl Node<Mono> for Level {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0[0] + self.0[1]];
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Param { param, value } = *event {
+            self.0[param] = value;
+        }
+    }
+}
+
+/// A change to the given parameter.
+fn param(param: usize, value: f32) -> Event {
+    Event::Param { param, value }
+}
+
+/// A **Level** node as the output, smoothing changes over the given time by default.
+fn graph(ms: f32) -> (Graph<Mono, Level>, NodeIndex) {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Level([0.0; 2]));
+    graph.set_master(Some(node));
+    graph.set_default_smoothing_ms(ms);
+    (graph, node)
+}
+
+/// Render `frames` frames in buffers of `block` frames, returning the output.
+fn render(graph: &mut Graph<Mono, Level>, frames: usize, block: usize) -> Vec<f32> {
+    let mut output = Vec::new();
+    let mut buffer = vec![[0.0]; block];
+    for _ in 0..frames / block {
+        graph.audio_requested(&mut buffer, SAMPLE_HZ);
+        output.extend(buffer.iter().map(|frame| frame[0]));
+    }
+    output
+}
+
+/// The value of each interval of `SMOOTHING_INTERVAL` frames, which must be constant within it.
+fn intervals(output: &[f32]) -> Vec<f32> {
+    output
+        .chunks(SMOOTHING_INTERVAL)
+        .map(|chunk| {
+            assert!(chunk.iter().all(|&s| s == chunk[0]), "{:?}", chunk);
+            chunk[0]
+        })
+        .collect()
+}
+
+#[test]
+fn changes_take_effect_immediately_by_default() {
+    let (mut graph, node) = graph(0.0);
+    graph.set_default_smoothing_ms(-5.0);
+    assert_eq!(graph.default_smoothing_ms(), 0.0);
+    graph.send_event(node, param(0, 1.0)).unwrap();
+    render(&mut graph, 32, 32);
+    graph.send_event(node, param(0, 2.0)).unwrap();
+    assert_eq!(render(&mut graph, 32, 32), vec![2.0; 32]);
+}
+
+#[test]
+fn changes_ramp_from_the_previous_value_over_the_smoothing_time() {
+    let interval = SMOOTHING_INTERVAL as f32;
+    let (mut graph, node) = graph(4.0 * interval);
+    // The first value of a parameter takes effect immediately.
+    graph.send_event(node, param(0, 1.0)).unwrap();
+    assert_eq!(intervals(&render(&mut graph, 64, 64)), vec![1.0, 1.0]);
+
+    // Each interval delivers the value reached by its end.
+    graph.send_event(node, param(0, 5.0)).unwrap();
+    let ramp = intervals(&render(&mut graph, 192, 64));
+    assert_eq!(ramp, vec![2.0, 3.0, 4.0, 5.0, 5.0, 5.0]);
+}
+
+#[test]
+fn ramps_do_not_depend_on_the_buffer_size() {
+    let ramp = |block| {
+        let (mut graph, node) = graph(100.0);
+        graph.send_event(node, param(0, 0.0)).unwrap();
+        render(&mut graph, 16, 16);
+        graph.send_event(node, param(0, 1.0)).unwrap();
+        render(&mut graph, 256, block)
+    };
+    let expected = ramp(256);
+    assert_eq!(ramp(8), expected);
+    assert_eq!(ramp(128), expected);
+}
+
+#[test]
+fn parameters_may_override_the_default_smoothing() {
+    let (mut graph, node) = graph(1_000.0);
+    graph
+        .set_param_smoothing(node, 0, ParamSmoothing::Stepped)
+        .unwrap();
+    let ms = 2.0 * SMOOTHING_INTERVAL as f32;
+    graph
+        .set_param_smoothing(node, 1, ParamSmoothing::Ms(ms))
+        .unwrap();
+    assert_eq!(
+        graph.param_smoothing(node, 0),
+        Some(ParamSmoothing::Stepped)
+    );
+    assert_eq!(graph.param_smoothing(node, 1), Some(ParamSmoothing::Ms(ms)));
+    graph.send_event(node, param(0, 0.0)).unwrap();
+    graph.send_event(node, param(1, 0.0)).unwrap();
+    render(&mut graph, 64, 64);
+
+    graph.send_event(node, param(0, 10.0)).unwrap();
+    graph.send_event(node, param(1, 2.0)).unwrap();
+    let output = intervals(&render(&mut graph, 96, 32));
+    assert_eq!(output, vec![11.0, 12.0, 12.0]);
+
+    graph
+        .set_param_smoothing(node, 0, ParamSmoothing::Default)
+        .unwrap();
+    assert_eq!(
+        graph.param_smoothing(node, 0),
+        Some(ParamSmoothing::Default)
+    );
+    assert_eq!(graph.param_smoothing(NodeIndex::new(7), 0), None);
+    assert!(graph
+        .set_param_smoothing(NodeIndex::new(7), 0, ParamSmoothing::Stepped)
+        .is_err());
+}
+
+#[test]
+fn values_given_to_param_handles_are_smoothed_from() {
+    let (mut graph, node) = graph(2.0 * SMOOTHING_INTERVAL as f32);
+    let handle = graph.param_handle(node, 0, 2.0).unwrap();
+    handle.set(4.0);
+    let output = intervals(&render(&mut graph, 96, 32));
+    assert_eq!(output, vec![3.0, 4.0, 4.0]);
+}
@@ -0,0 +1,98 @@
+//! Muted nodes are silenced, and soloing a node silences every branch parallel to it.
+
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Passes its input through.
+    Pass,
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        if let Test::Dc(value) = *self {
+            for frame in buffer.iter_mut() {
+                *frame = [value];
+            }
+        }
+    }
+}
+
+/// Two sources, each passed through an insert into a bus.
+struct Mix {
+    graph: Graph<Mono, Test>,
+    a: dsp::NodeIndex,
+    b: dsp::NodeIndex,
+    insert_a: dsp::NodeIndex,
+    insert_b: dsp::NodeIndex,
+    bus: dsp::NodeIndex,
+}
+
+impl Mix {
+    fn new() -> Self {
+        let mut graph = Graph::new();
+        let a = graph.add_node(Test::Dc(1.0));
+        let b = graph.add_node(Test::Dc(2.0));
+        let (_, insert_a) = graph.add_output(a, Test::Pass);
+        let (_, insert_b) = graph.add_output(b, Test::Pass);
+        let (_, bus) = graph.add_output(insert_a, Test::Pass);
+        graph.add_connection(insert_b, bus).unwrap();
+        graph.set_master(Some(bus));
+        Mix {
+            graph,
+            a,
+            b,
+            insert_a,
+            insert_b,
+            bus,
+        }
+    }
+
+    /// Render a buffer, returning its first frame.
+    fn render(&mut self) -> f32 {
+        let mut buffer = [[0.0]; 4];
+        self.graph.audio_requested(&mut buffer, 44_100.0);
+        buffer[0][0]
+    }
+}
+
+#[test]
+fn muted_nodes_are_silenced() {
+    let mut mix = Mix::new();
+    assert_eq!(mix.render(), 3.0);
+    mix.graph.set_muted(mix.b, true).unwrap();
+    assert!(mix.graph.is_muted(mix.b));
+    assert!(!mix.graph.is_audible(mix.b));
+    assert_eq!(mix.render(), 1.0);
+    mix.graph.set_muted(mix.b, false).unwrap();
+    assert_eq!(mix.render(), 3.0);
+}
+
+#[test]
+fn soloing_silences_parallel_branches() {
+    let mut mix = Mix::new();
+    mix.graph.set_soloed(mix.insert_a, true).unwrap();
+    assert!(mix.graph.is_soloed(mix.insert_a));
+    assert_eq!(mix.render(), 1.0);
+
+    // Everything feeding the soloed node and every path from it to the output stay audible.
+    assert!(mix.graph.is_audible(mix.a));
+    assert!(mix.graph.is_audible(mix.bus));
+    assert!(!mix.graph.is_audible(mix.b));
+    assert!(!mix.graph.is_audible(mix.insert_b));
+
+    mix.graph.set_soloed(mix.insert_b, true).unwrap();
+    assert_eq!(mix.render(), 3.0);
+}
+
+#[test]
+fn muting_overrides_soloing() {
+    let mut mix = Mix::new();
+    mix.graph.set_soloed(mix.insert_a, true).unwrap();
+    mix.graph.set_muted(mix.insert_a, true).unwrap();
+    assert!(!mix.graph.is_audible(mix.insert_a));
+    assert_eq!(mix.render(), 0.0);
+}
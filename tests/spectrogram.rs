@@ -0,0 +1,71 @@
+//! The **Spectrogram** keeps a rolling history of the magnitude spectrum of its input.
+
+#![cfg(feature = "analysis")]
+
+use dsp::nodes::Spectrogram;
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 6_400.0;
+
+/// `len` frames of a sine at 1khz, the centre of the tenth bin of a `64` frame transform.
+fn sine(len: usize) -> Vec<Mono> {
+    (0..len)
+        .map(|i| [(i as f64 * 1_000.0 * std::f64::consts::TAU / SAMPLE_HZ).sin() as f32])
+        .collect()
+}
+
+/// The index of the loudest bin in the given column.
+fn peak(column: &[f32]) -> usize {
+    (0..column.len())
+        .max_by(|&a, &b| column[a].partial_cmp(&column[b]).unwrap())
+        .unwrap()
+}
+
+#[test]
+fn columns_are_measured_every_hop() {
+    let mut spectrogram = Spectrogram::new(60, 32, 3);
+    assert_eq!(spectrogram.fft_size(), 64);
+    assert_eq!(spectrogram.num_columns(), 3);
+    let mut buffer = sine(96);
+    let input = buffer.clone();
+    spectrogram.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer, input);
+
+    let snapshot = spectrogram.snapshot();
+    assert_eq!(snapshot.num_bins, 33);
+    assert_eq!(snapshot.num_columns, 3);
+    assert_eq!(snapshot.bin_hz, 100.0);
+    assert_eq!(snapshot.hop_secs, 0.005);
+    assert_eq!(peak(&snapshot.magnitudes_db[2 * 33..]), 10);
+}
+
+#[test]
+fn the_history_keeps_the_most_recent_columns() {
+    let mut spectrogram = Spectrogram::new(64, 32, 2);
+    spectrogram.process(&sine(64), SAMPLE_HZ);
+    spectrogram.process(&[[0.0]; 64], SAMPLE_HZ);
+    let snapshot = spectrogram.snapshot();
+    assert_eq!(snapshot.num_columns, 2);
+    // The oldest column still holds half of the sine, while the newest is silent.
+    assert_eq!(peak(&snapshot.magnitudes_db[..33]), 10);
+    let floor = snapshot.magnitudes_db[33..]
+        .iter()
+        .cloned()
+        .fold(f32::MIN, f32::max);
+    assert!(floor < snapshot.magnitudes_db[10] - 60.0);
+}
+
+#[test]
+fn handles_read_the_published_history() {
+    let mut spectrogram = Spectrogram::new(64, 32, 4);
+    let handle = spectrogram.handle();
+    assert_eq!(handle.snapshot().num_columns, 0);
+    spectrogram.process(&sine(128), SAMPLE_HZ);
+    assert_eq!(handle.snapshot(), spectrogram.snapshot());
+
+    spectrogram.reset();
+    assert_eq!(handle.snapshot().num_columns, 0);
+    assert!(spectrogram.snapshot().magnitudes_db.is_empty());
+}
@@ -0,0 +1,53 @@
+//! Splitting nodes off a **Graph** moves them into a standalone graph.
+
+mod common;
+
+use common::{render, Test};
+use dsp::{Graph, Node, NodeIndex};
+
+#[test]
+fn split_nodes_keep_the_connections_between_them() {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(1.0));
+    let (_, a) = graph.add_output(dc, Test::Gain(2.0));
+    let (ab, b) = graph.add_output(a, Test::Gain(3.0));
+    graph.set_connection_enabled(ab, false).unwrap();
+    graph.add_feedback_connection(b, a).unwrap();
+    graph.set_muted(b, true).unwrap();
+
+    let mut split = graph.split_off(&[b, a, b]);
+    assert_eq!(graph.node_count(), 1);
+    assert_eq!(graph.connection_count(), 0);
+    assert!(graph.feedback_connections().is_empty());
+    assert_eq!(graph[dc], Test::Dc(1.0));
+
+    assert_eq!(split.node_count(), 2);
+    let (b, a) = (NodeIndex::new(0), NodeIndex::new(1));
+    assert_eq!(split[b], Test::Gain(3.0));
+    assert_eq!(split[a], Test::Gain(2.0));
+    assert!(split.is_muted(b));
+    let ab = split.find_connection(a, b).unwrap();
+    assert!(!split.is_connection_enabled(ab));
+    assert_eq!(split.feedback_connections().len(), 1);
+    assert_eq!(split.master_index(), None);
+
+    let mut buffer = [[5.0]; 4];
+    split.set_master(Some(a));
+    split.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.0]; 4]);
+}
+
+#[test]
+fn split_graphs_render_on_their_own() {
+    let mut graph = Graph::new();
+    let dc = graph.add_node(Test::Dc(1.0));
+    let (_, gain) = graph.add_output(dc, Test::Gain(4.0));
+    let other = graph.add_node(Test::Dc(7.0));
+    graph.set_master(Some(other));
+
+    let mut split = graph.split_off(&[dc, gain]);
+    assert_eq!(graph.node_count(), 1);
+    assert_eq!(graph[graph.master_index().unwrap()], Test::Dc(7.0));
+    split.set_master(Some(NodeIndex::new(1)));
+    assert_eq!(render(&mut split), 4.0);
+}
@@ -0,0 +1,68 @@
+//! A **Chain** processes audio with each of its statically dispatched stages in order.
+
+use dsp::event::Event;
+use dsp::nodes::Chain;
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+/// Scales its input, reporting the given latency and counting the events it receives.
+#[derive(Debug, PartialEq)]
+struct Gain(f32, usize, usize);
+
+/// Adds a constant to its input.
+#[derive(Debug, PartialEq)]
+struct Offset(f32);
+
+impl Node<Mono> for Gain {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] *= self.0;
+        }
+    }
+
+    fn latency(&self) -> usize {
+        self.1
+    }
+
+    fn handle_event(&mut self, _event: &Event) {
+        self.2 += 1;
+    }
+}
+
+impl Node<Mono> for Offset {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] += self.0;
+        }
+    }
+}
+
+#[test]
+fn stages_process_in_order() {
+    let mut chain = Chain::new((Offset(1.0), Gain(2.0, 3, 0), Offset(0.5), Gain(10.0, 4, 0)));
+    let mut buffer = [[0.0]; 4];
+    chain.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[25.0]; 4]);
+    assert_eq!(chain.latency(), 7);
+
+    chain.handle_event(&Event::Param {
+        param: 0,
+        value: 1.0,
+    });
+    let (_, gain, _, last) = chain.into_stages();
+    assert_eq!((gain.2, last.2), (1, 1));
+}
+
+#[test]
+fn chains_are_nodes_within_a_graph() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Chain::from((Offset(2.0), Gain(3.0, 0, 0))));
+    graph.set_master(Some(node));
+    graph[node].stages_mut().1 .0 = 4.0;
+    assert_eq!(graph[node].stages().0, Offset(2.0));
+
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[8.0]; 4]);
+}
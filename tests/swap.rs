@@ -0,0 +1,78 @@
+//! A **GraphSwap** crossfades from the **Graph** it renders to a replacement sent from elsewhere.
+
+use dsp::{Graph, GraphSwap, Node};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Test {
+    /// Outputs a constant.
+    Dc(f32),
+    /// Outputs a ramp that rises by one each frame, so that its state is audible.
+    Counter(f32),
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = match self {
+                Test::Dc(value) => [*value],
+                Test::Counter(count) => {
+                    *count += 1.0;
+                    [*count]
+                }
+            };
+        }
+    }
+}
+
+/// A graph whose output is the given node.
+fn graph_of(node: Test) -> Graph<Mono, Test> {
+    let mut graph = Graph::new();
+    let idx = graph.add_node(node);
+    graph.set_master(Some(idx));
+    graph
+}
+
+#[test]
+fn replacements_are_crossfaded_in() {
+    let (mut swap, handle) = GraphSwap::new(graph_of(Test::Dc(0.0)), 4);
+    assert!(handle.swap(graph_of(Test::Dc(1.0))));
+    let mut buffer = [[0.0]; 2];
+    swap.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer, [[0.0], [0.25]]);
+    assert!(swap.is_crossfading());
+    swap.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer, [[0.5], [0.75]]);
+    assert!(!swap.is_crossfading());
+    swap.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer, [[1.0], [1.0]]);
+
+    // The replaced graph is handed back rather than dropped on the audio thread.
+    let retired: Vec<_> = handle.retired().collect();
+    assert_eq!(retired.len(), 1);
+    assert_eq!(retired[0].raw_nodes()[0].weight, Test::Dc(0.0));
+}
+
+#[test]
+fn preserved_nodes_carry_their_state_over() {
+    let (mut swap, handle) = GraphSwap::new(graph_of(Test::Counter(0.0)), 0);
+    let mut buffer = [[0.0]; 4];
+    swap.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer[3], [4.0]);
+
+    let replacement = graph_of(Test::Counter(100.0));
+    let idx = replacement.master_index().unwrap();
+    assert!(handle.swap_preserving(replacement, vec![(idx, idx)]));
+    swap.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer[0], [5.0]);
+}
+
+#[test]
+fn swapping_fails_once_the_graph_swap_is_dropped() {
+    let (swap, handle) = GraphSwap::new(graph_of(Test::Dc(0.0)), 4);
+    drop(swap);
+    assert!(!handle.swap(graph_of(Test::Dc(1.0))));
+}
@@ -0,0 +1,123 @@
+//! Nodes ring out for their tail after their inputs fall silent, and flushing renders the
+//! **Graph**'s tail once its input has ended.
+
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+enum Test {
+    /// Outputs a single impulse on the first frame it renders.
+    Impulse(bool),
+    /// Delays its input by the length of its line, reporting that as its latency.
+    Delay(Vec<Mono>),
+    /// Passes its input through with a tail of the given number of frames, counting the requests
+    /// that it renders.
+    Reverb { tail: usize, requests: usize },
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        match self {
+            Test::Impulse(done) => {
+                if !*done {
+                    buffer[0] = [1.0];
+                    *done = true;
+                }
+            }
+            Test::Delay(line) => {
+                for frame in buffer.iter_mut() {
+                    line.push(*frame);
+                    *frame = line.remove(0);
+                }
+            }
+            Test::Reverb { requests, .. } => *requests += 1,
+        }
+    }
+
+    fn is_silent(&self) -> bool {
+        match self {
+            Test::Impulse(done) => *done,
+            Test::Delay(_) => false,
+            Test::Reverb { .. } => true,
+        }
+    }
+
+    fn latency(&self) -> usize {
+        match self {
+            Test::Delay(line) => line.len(),
+            _ => 0,
+        }
+    }
+
+    fn tail_frames(&self) -> usize {
+        match self {
+            Test::Reverb { tail, .. } => *tail,
+            _ => 0,
+        }
+    }
+}
+
+/// A **Reverb** with the given tail.
+fn reverb(tail: usize) -> Test {
+    Test::Reverb { tail, requests: 0 }
+}
+
+/// An impulse delayed by the given number of frames, of which the first `512` are rendered.
+fn delayed_impulse(delay: usize) -> Graph<Mono, Test> {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Impulse(false));
+    let (_, delay) = graph.add_output(src, Test::Delay(vec![[0.0]; delay]));
+    graph.set_master(Some(delay));
+    let mut buffer = vec![[0.0]; 512];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    graph
+}
+
+#[test]
+fn the_tail_ends_with_its_last_sound() {
+    let mut graph = delayed_impulse(3_000);
+    let tail = graph.flush_tail(100_000, SAMPLE_HZ);
+    assert_eq!(tail.len(), 3_000 - 512 + 1);
+    assert_eq!(tail.last(), Some(&[1.0]));
+}
+
+#[test]
+fn the_tail_is_limited_to_the_maximum_length() {
+    let mut graph = delayed_impulse(3_000);
+    let tail = graph.flush_tail(1_000, SAMPLE_HZ);
+    assert!(tail.len() <= 1_000);
+    assert!(tail.iter().all(|frame| frame[0] == 0.0));
+}
+
+#[test]
+fn graphs_without_an_output_have_no_tail() {
+    let mut graph: Graph<Mono, Test> = Graph::new();
+    assert!(graph.flush_tail(1_000, SAMPLE_HZ).is_empty());
+}
+
+#[test]
+fn silent_nodes_are_rendered_until_their_tail_has_rung_out() {
+    let mut graph = Graph::new();
+    let src = graph.add_node(Test::Impulse(false));
+    let (_, reverb) = graph.add_output(src, reverb(8));
+    graph.set_master(Some(reverb));
+    let mut buffer = [[0.0]; 4];
+    for _ in 0..4 {
+        graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    }
+    // One request with the impulse as input, followed by two of ringing out.
+    assert!(matches!(graph[reverb], Test::Reverb { requests: 3, .. }));
+}
+
+#[test]
+fn tails_are_summed_along_each_path() {
+    let mut graph = Graph::new();
+    let master = graph.add_node(reverb(10));
+    let (_, short) = graph.add_input(reverb(5), master);
+    graph.add_input(reverb(20), short);
+    graph.add_input(reverb(30), master);
+    graph.set_master(Some(master));
+    assert_eq!(Node::tail_frames(&graph), 40);
+}
@@ -0,0 +1,116 @@
+//! **TakeLanes** hold the takes recorded over a region and comp them from selected segments.
+
+#![cfg(feature = "sampler")]
+
+use dsp::nodes::{CompSegment, Recorder, Take, TakeLanes};
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+/// A take of `frames` frames of the given constant, punched in without a known start.
+fn take(value: f32, frames: usize) -> Take {
+    let mut recorder = Recorder::new(1, frames);
+    recorder.arm();
+    recorder.punch_in(None);
+    let mut buffer = vec![[value]; frames];
+    Node::<Mono>::audio_requested(&mut recorder, &mut buffer, 44_100.0);
+    recorder.punch_out();
+    recorder.collect_takes().remove(0)
+}
+
+/// Lanes over the frames `0..8` holding an 8 frame take of `1.0` and then one of `2.0`.
+fn lanes() -> TakeLanes {
+    let mut lanes = TakeLanes::new(0..8, 1);
+    assert_eq!(lanes.add_take(take(1.0, 8)), 0);
+    assert_eq!(lanes.add_take(take(2.0, 8)), 1);
+    lanes
+}
+
+/// A segment of the comp.
+fn segment(start: u64, take: usize) -> CompSegment {
+    CompSegment { start, take }
+}
+
+#[test]
+fn each_new_take_is_selected_for_the_whole_region() {
+    let lanes = lanes();
+    assert_eq!(lanes.takes().len(), 2);
+    assert_eq!(lanes.segments(), &[segment(0, 1)][..]);
+    let flat = lanes.flatten();
+    assert_eq!(flat.start(), Some(0));
+    assert_eq!(flat.samples(), &[2.0; 8][..]);
+}
+
+#[test]
+fn selected_segments_are_comped_together() {
+    let mut lanes = lanes();
+    lanes.select(2..4, 0);
+    assert_eq!(
+        lanes.segments(),
+        &[segment(0, 1), segment(2, 0), segment(4, 1)][..]
+    );
+    assert_eq!(lanes.take_at(3), Some(0));
+    assert_eq!(lanes.take_at(8), None);
+    let flat = lanes.flatten();
+    assert_eq!(
+        flat.samples(),
+        &[2.0, 2.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0][..]
+    );
+
+    // Adjacent selections of one take merge, and selections are clipped to the region.
+    lanes.select(4..6, 0);
+    lanes.select(7..20, 0);
+    assert_eq!(
+        lanes.segments(),
+        &[segment(0, 1), segment(2, 0), segment(6, 1), segment(7, 0)][..]
+    );
+    // Selections of missing lanes or empty ranges are ignored.
+    lanes.select(0..8, 2);
+    lanes.select(3..3, 1);
+    assert_eq!(lanes.segments().len(), 4);
+}
+
+#[test]
+fn frames_beyond_the_region_or_the_take_are_silent() {
+    let mut lanes = TakeLanes::new(4..10, 1);
+    lanes.add_take(take(1.0, 4));
+    let mut buffer = [-1.0; 12];
+    lanes.render(&mut buffer, 0);
+    let mut expected = [0.0; 12];
+    expected[4..8].copy_from_slice(&[1.0; 4]);
+    assert_eq!(buffer, expected);
+}
+
+#[test]
+fn removed_takes_hand_their_segments_to_the_take_before() {
+    let mut pair = lanes();
+    pair.select(2..4, 0);
+    let removed = pair.remove_take(1).unwrap();
+    assert_eq!(removed.samples(), &[2.0; 8][..]);
+    assert_eq!(pair.segments(), &[segment(0, 0)][..]);
+
+    let mut stacked = lanes();
+    stacked.add_take(take(3.0, 8));
+    stacked.select(2..4, 1);
+    stacked.remove_take(0);
+    assert_eq!(
+        stacked.segments(),
+        &[segment(0, 1), segment(2, 0), segment(4, 1)][..]
+    );
+    assert_eq!(stacked.remove_take(5), None);
+
+    stacked.remove_take(0);
+    stacked.remove_take(0);
+    assert!(stacked.segments().is_empty());
+    assert_eq!(stacked.take_at(0), None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn lanes_round_trip_with_their_audio() {
+    let mut lanes = lanes();
+    lanes.select(2..4, 0);
+    let json = serde_json::to_string(&lanes).unwrap();
+    let loaded: TakeLanes = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, lanes);
+}
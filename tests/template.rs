@@ -0,0 +1,106 @@
+//! Templates build independent instances of a sub-graph that share their resources.
+
+use dsp::template::{Builder, Resources, Template};
+use dsp::{Graph, Node, WouldCycle};
+use std::sync::Arc;
+
+type Mono = [f32; 1];
+
+enum Test {
+    /// Outputs the first value of a shared table.
+    Table(Arc<Vec<f32>>),
+    /// Passes its summed inputs through.
+    Sum,
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        if let Test::Table(ref table) = *self {
+            for frame in buffer.iter_mut() {
+                *frame = [table[0]];
+            }
+        }
+    }
+}
+
+/// The table read by the voice at the given index.
+fn table(graph: &Graph<Mono, Test>, idx: dsp::NodeIndex) -> &Arc<Vec<f32>> {
+    match graph[idx] {
+        Test::Table(ref table) => table,
+        Test::Sum => panic!("the node is not a voice"),
+    }
+}
+
+/// A voice per argument, each reading the same table, summed into a single output.
+struct Synth;
+
+impl Template<Mono, Test> for Synth {
+    type Args = usize;
+
+    fn build(&self, builder: &mut Builder<Mono, Test>, voices: &usize) -> Result<(), WouldCycle> {
+        let table = builder.resource("table", || vec![0.25]);
+        let output = builder.add_node(Test::Sum);
+        for _ in 0..*voices {
+            let voice = builder.add_node(Test::Table(table.clone()));
+            builder.add_connection(voice, output)?;
+        }
+        builder.set_output(output);
+        Ok(())
+    }
+}
+
+/// A template that connects two nodes in a cycle.
+struct Cycle;
+
+impl Template<Mono, Test> for Cycle {
+    type Args = ();
+
+    fn build(&self, builder: &mut Builder<Mono, Test>, _args: &()) -> Result<(), WouldCycle> {
+        let a = builder.add_node(Test::Sum);
+        let b = builder.add_node(Test::Sum);
+        builder.add_connection(a, b)?;
+        builder.add_connection(b, a)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn instances_share_their_resources() {
+    let mut graph = Graph::new();
+    let mut resources = Resources::new();
+    let a = graph.instantiate(&Synth, &mut resources, &2).unwrap();
+    let b = graph.instantiate(&Synth, &mut resources, &3).unwrap();
+    assert_eq!(a.nodes.len(), 3);
+    assert_eq!(b.nodes.len(), 4);
+    assert_eq!(graph.node_count(), 7);
+
+    let shared = resources.get::<Vec<f32>>("table").unwrap();
+    assert!(Arc::ptr_eq(table(&graph, a.nodes[1]), &shared));
+    assert!(Arc::ptr_eq(table(&graph, b.nodes[1]), &shared));
+    assert_eq!(resources.names().collect::<Vec<_>>(), vec!["table"]);
+}
+
+#[test]
+fn instances_are_rendered_independently() {
+    let mut graph = Graph::new();
+    let mut resources = Resources::new();
+    let instance = graph.instantiate(&Synth, &mut resources, &2).unwrap();
+    graph.instantiate(&Synth, &mut resources, &3).unwrap();
+    graph.set_master(instance.output);
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[0.5]; 4]);
+    assert_eq!(instance.input, None);
+
+    instance.remove_from(&mut graph);
+    assert_eq!(graph.node_count(), 4);
+}
+
+#[test]
+fn failed_instances_are_removed() {
+    let mut graph = Graph::new();
+    graph.add_node(Test::Sum);
+    let mut resources = Resources::new();
+    assert!(graph.instantiate(&Cycle, &mut resources, &()).is_err());
+    assert_eq!(graph.node_count(), 1);
+}
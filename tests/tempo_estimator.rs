@@ -0,0 +1,62 @@
+//! The **TempoEstimator** estimates the tempo and beat phase of its input from its onsets.
+
+#![cfg(feature = "analysis")]
+
+use dsp::nodes::{TempoEstimator, TempoReading};
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// `secs` seconds of short clicks at the given tempo, starting on a beat.
+fn clicks(bpm: f64, secs: f64) -> Vec<Mono> {
+    let period = (60.0 * SAMPLE_HZ / bpm) as usize;
+    (0..(secs * SAMPLE_HZ) as usize)
+        .map(|i| {
+            let since_click = i % period;
+            if since_click < 64 {
+                [(since_click as f32 * 0.7).sin() * 0.8]
+            } else {
+                [0.0]
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn the_tempo_of_a_click_track_is_estimated() {
+    let mut estimator = TempoEstimator::new(1024, 512, 512);
+    assert_eq!(estimator.history_len(), 512);
+    let handle = estimator.handle();
+    let mut buffer = clicks(120.0, 8.0);
+    let input = buffer.clone();
+    estimator.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer, input);
+
+    let reading = estimator.reading().unwrap();
+    assert!((reading.bpm - 120.0).abs() < 2.0, "{:?}", reading);
+    assert!((0.0..1.0).contains(&reading.phase));
+    assert_eq!(handle.reading(), Some(reading));
+
+    estimator.reset();
+    assert_eq!(estimator.reading(), None);
+    assert_eq!(handle.reading(), None);
+}
+
+#[test]
+fn nothing_is_estimated_before_two_periods_of_the_slowest_tempo() {
+    let mut estimator = TempoEstimator::new(1024, 512, 512);
+    estimator.process(&clicks(120.0, 1.5), SAMPLE_HZ);
+    assert_eq!(estimator.reading(), None);
+}
+
+#[test]
+fn readings_convert_to_frames() {
+    let reading = TempoReading {
+        bpm: 120.0,
+        phase: 0.25,
+    };
+    assert_eq!(reading.frames_per_beat(SAMPLE_HZ), 22_050.0);
+    assert_eq!(reading.frames_until_beat(SAMPLE_HZ), 16_537.5);
+}
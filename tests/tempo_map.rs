@@ -0,0 +1,188 @@
+//! The **TempoMap** maps beats to transport frames through steps and ramps, and the **Graph**
+//! passes the tempo to each node before it renders.
+
+use dsp::{Graph, Node, Tempo, TempoMap, TempoPoint, TempoRamp};
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// Records the length of each buffer that it renders and the tempo at its first frame.
+#[derive(Default)]
+struct Follower {
+    tempo: Option<Tempo>,
+    buffers: Vec<(usize, Option<f64>)>,
+}
+
+enum Test {
+    /// A **Follower** node.
+    Follower(Follower),
+    /// A nested graph of **Follower** nodes.
+    Graph(Box<Graph<Mono, Follower>>),
+}
+
+impl Node<Mono> for Follower {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        let bpm = self.tempo.take().map(|tempo| tempo.bpm_at(0));
+        self.buffers.push((buffer.len(), bpm));
+    }
+
+    fn update_tempo(&mut self, tempo: &Tempo) {
+        self.tempo = Some(*tempo);
+    }
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], sample_hz: f64) {
+        match self {
+            Test::Follower(follower) => follower.audio_requested(buffer, sample_hz),
+            Test::Graph(graph) => graph.audio_requested(buffer, sample_hz),
+        }
+    }
+
+    fn update_tempo(&mut self, tempo: &Tempo) {
+        match self {
+            Test::Follower(follower) => follower.update_tempo(tempo),
+            Test::Graph(graph) => graph.update_tempo(tempo),
+        }
+    }
+}
+
+/// A map that ramps along the given ramp from 60bpm to 120bpm over the first second.
+fn ramp(ramp: TempoRamp) -> TempoMap {
+    let mut map = TempoMap::new(60.0);
+    map.insert(SAMPLE_HZ as u64, 120.0, ramp);
+    map
+}
+
+/// Render a buffer of `16` frames.
+fn render(graph: &mut Graph<Mono, Test>) {
+    let mut buffer = [[0.0]; 16];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+}
+
+#[test]
+fn a_constant_tempo_maps_beats_to_frames_evenly() {
+    let map = TempoMap::default();
+    assert_eq!(map.bpm_at(1_000_000), 120.0);
+    assert_eq!(map.beat_at(22_050, SAMPLE_HZ), 1.0);
+    assert_eq!(map.frame_at_beat(4.0, SAMPLE_HZ), 88_200);
+    assert_eq!(map.frame_at_beat(-1.0, SAMPLE_HZ), 0);
+}
+
+#[test]
+fn ramps_move_the_tempo_between_points() {
+    let second = SAMPLE_HZ as u64;
+    let step = ramp(TempoRamp::Step);
+    assert_eq!(step.bpm_at(second - 1), 60.0);
+    assert_eq!(step.bpm_at(second), 120.0);
+    assert!((step.beat_at(second, SAMPLE_HZ) - 1.0).abs() < 1e-9);
+
+    // A linear ramp averages 90bpm, covering one and a half beats.
+    let linear = ramp(TempoRamp::Linear);
+    assert_eq!(linear.bpm_at(second / 2), 90.0);
+    assert!((linear.beat_at(second, SAMPLE_HZ) - 1.5).abs() < 1e-9);
+    // Beyond the last point the tempo holds.
+    assert!((linear.beat_at(2 * second, SAMPLE_HZ) - 3.5).abs() < 1e-9);
+
+    // A squared curve is a quarter of the way there halfway through, covering 1 1/3 beats.
+    let curve = ramp(TempoRamp::Curve(2.0));
+    assert_eq!(curve.bpm_at(second / 2), 75.0);
+    assert!((curve.beat_at(second, SAMPLE_HZ) - 4.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn frames_and_beats_convert_both_ways_along_ramps() {
+    for shape in [TempoRamp::Step, TempoRamp::Linear, TempoRamp::Curve(0.5)] {
+        let map = ramp(shape);
+        for &frame in &[0, 1_234, 30_000, 44_100, 100_000] {
+            let beat = map.beat_at(frame, SAMPLE_HZ);
+            assert_eq!(map.frame_at_beat(beat, SAMPLE_HZ), frame, "{:?}", shape);
+        }
+    }
+}
+
+#[test]
+fn points_are_replaced_and_removed_by_frame() {
+    let mut map = ramp(TempoRamp::Linear);
+    map.insert(44_100, 100.0, TempoRamp::Step);
+    let point = TempoPoint {
+        frame: 44_100,
+        bpm: 100.0,
+        ramp: TempoRamp::Step,
+    };
+    assert_eq!(map.points().len(), 2);
+    assert_eq!(map.points()[1], point);
+
+    assert_eq!(map.remove(1), None);
+    assert_eq!(map.remove(44_100), Some(point));
+    // The last point is kept.
+    assert_eq!(map.remove(0), None);
+    assert_eq!(map.points().len(), 1);
+}
+
+#[test]
+fn buffers_are_split_at_each_point_and_passed_the_tempo() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Test::Follower(Follower::default()));
+    graph.set_master(Some(node));
+    render(&mut graph);
+
+    // The tempo steps down within the second buffer.
+    let mut map = TempoMap::new(120.0);
+    map.insert(26, 60.0, TempoRamp::Step);
+    graph.set_tempo_map(Some(map));
+    assert_eq!(graph.tempo_map().unwrap().points().len(), 2);
+    render(&mut graph);
+    render(&mut graph);
+    graph.tempo_map_mut().unwrap().remove(26);
+    render(&mut graph);
+
+    let expected = vec![
+        (16, None),
+        (10, Some(120.0)),
+        (6, Some(60.0)),
+        (16, Some(60.0)),
+        (16, Some(120.0)),
+    ];
+    match graph[node] {
+        Test::Follower(ref follower) => assert_eq!(follower.buffers, expected),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn nested_graphs_follow_the_tempo_of_their_parent() {
+    let mut inner = Graph::new();
+    let follower = inner.add_node(Follower::default());
+    inner.set_master(Some(follower));
+    let mut graph = Graph::new();
+    let nested = graph.add_node(Test::Graph(Box::new(inner)));
+    graph.set_master(Some(nested));
+    graph.set_tempo_map(Some(TempoMap::new(90.0)));
+    render(&mut graph);
+    graph.set_tempo_map(None);
+    render(&mut graph);
+
+    match graph[nested] {
+        Test::Graph(ref inner) => {
+            let expected = vec![(16, Some(90.0)), (16, None)];
+            assert_eq!(inner[follower].buffers, expected);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn tempos_describe_the_beats_across_a_buffer() {
+    let tempo = Tempo::constant(120.0, 2.0, SAMPLE_HZ);
+    assert_eq!(tempo.beat_at(22_050), 3.0);
+    assert_eq!(tempo.beat_frames_at(0), 22_050.0);
+    assert_eq!(tempo.frames_remaining(), None);
+    assert_eq!(tempo.offset(22_050).beat_at(0), 3.0);
+
+    let tempo = ramp(TempoRamp::Linear).tempo(11_025, SAMPLE_HZ);
+    assert_eq!(tempo.frames_remaining(), Some(33_075));
+    assert_eq!(tempo.bpm_at(11_025), 90.0);
+    assert_eq!(tempo.sample_hz(), SAMPLE_HZ);
+}
@@ -0,0 +1,69 @@
+//! Nodes with an active range are only rendered while the transport is within it.
+
+use dsp::{Graph, Node};
+use std::cell::Cell;
+use std::rc::Rc;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// Outputs `1.0`, counting the requests that it renders.
+struct Counter(Rc<Cell<usize>>);
+
+impl Node<Mono> for Counter {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        self.0.set(self.0.get() + 1);
+        for frame in buffer.iter_mut() {
+            *frame = [1.0];
+        }
+    }
+}
+
+/// Render a buffer of `16` frames, returning its first frame.
+fn render(graph: &mut Graph<Mono, Counter>) -> f32 {
+    let mut buffer = [[0.0]; 16];
+    graph.audio_requested(&mut buffer, SAMPLE_HZ);
+    buffer[0][0]
+}
+
+#[test]
+fn the_position_advances_with_each_request() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Counter(Rc::new(Cell::new(0))));
+    graph.set_master(Some(node));
+    assert_eq!(graph.position(), 0);
+    render(&mut graph);
+    render(&mut graph);
+    assert_eq!(graph.position(), 32);
+    graph.set_position(1_000);
+    render(&mut graph);
+    assert_eq!(graph.position(), 1_016);
+}
+
+#[test]
+fn nodes_are_skipped_outside_of_their_active_range() {
+    let requests = Rc::new(Cell::new(0));
+    let mut graph = Graph::new();
+    let node = graph.add_node(Counter(requests.clone()));
+    graph.set_master(Some(node));
+    graph.set_active_range(node, 20, 40).unwrap();
+    assert_eq!(graph.active_range(node), Some(20..40));
+
+    // Frames 0..16 are before the range, 16..32 and 32..48 overlap it and 48..64 are after it.
+    let outputs: Vec<_> = (0..4).map(|_| render(&mut graph)).collect();
+    assert_eq!(outputs, [0.0, 1.0, 1.0, 0.0]);
+    assert_eq!(requests.get(), 2);
+
+    assert_eq!(graph.clear_active_range(node), Some(20..40));
+    assert_eq!(render(&mut graph), 1.0);
+    assert_eq!(requests.get(), 3);
+}
+
+#[test]
+fn active_ranges_of_missing_nodes_are_rejected() {
+    let mut graph: Graph<Mono, Counter> = Graph::new();
+    let missing = dsp::NodeIndex::new(0);
+    assert!(graph.set_active_range(missing, 0, 10).is_err());
+    assert_eq!(graph.clear_active_range(missing), None);
+}
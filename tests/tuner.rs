@@ -0,0 +1,61 @@
+//! The **Tuner** reports the note nearest to the pitch of its input.
+
+#![cfg(feature = "analysis")]
+
+use dsp::nodes::{Tuner, TunerReading};
+use dsp::Node;
+
+type Mono = [f32; 1];
+
+const SAMPLE_HZ: f64 = 44_100.0;
+
+/// A tenth of a second of a sine at `hz` with the given amplitude.
+fn sine(hz: f64, amp: f32) -> Vec<Mono> {
+    (0..SAMPLE_HZ as usize / 10)
+        .map(|i| [amp * (i as f64 * hz * std::f64::consts::TAU / SAMPLE_HZ).sin() as f32])
+        .collect()
+}
+
+#[test]
+fn notes_are_named_against_the_reference() {
+    let a4 = TunerReading::from_hz(440.0, 440.0).unwrap();
+    assert_eq!((a4.name, a4.octave), ("A", 4));
+    assert!(a4.cents.abs() < 1e-3);
+
+    let middle_c = TunerReading::from_hz(261.626, 440.0).unwrap();
+    assert_eq!((middle_c.name, middle_c.octave), ("C", 4));
+    let sharp = TunerReading::from_hz(452.0, 440.0).unwrap();
+    assert_eq!(sharp.name, "A");
+    assert!((sharp.cents - 46.58).abs() < 0.01);
+    let flat = TunerReading::from_hz(432.0, 432.0 * 1.01).unwrap();
+    assert!(flat.cents < 0.0);
+
+    assert_eq!(TunerReading::from_hz(0.0, 440.0), None);
+    assert_eq!(TunerReading::from_hz(440.0, f32::NAN), None);
+}
+
+#[test]
+fn the_pitch_of_the_input_is_detected() {
+    let mut tuner = Tuner::new(2048);
+    let handle = tuner.handle();
+    let mut buffer = sine(220.0, 0.5);
+    let input = buffer.clone();
+    tuner.audio_requested(&mut buffer, SAMPLE_HZ);
+    assert_eq!(buffer, input);
+
+    let reading = tuner.reading().unwrap();
+    assert_eq!((reading.name, reading.octave), ("A", 3));
+    assert!((reading.hz - 220.0).abs() < 1.0);
+    assert_eq!(handle.reading(), Some(reading));
+
+    tuner.reset();
+    assert_eq!(tuner.reading(), None);
+    assert_eq!(handle.reading(), None);
+}
+
+#[test]
+fn input_below_the_gate_is_ignored() {
+    let mut tuner = Tuner::new(2048);
+    tuner.process(&sine(220.0, 0.0001), SAMPLE_HZ);
+    assert_eq!(tuner.reading(), None);
+}
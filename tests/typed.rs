@@ -0,0 +1,126 @@
+//! Typed node indices access the variants of an enum of nodes as their own types.
+
+use dsp::{Graph, Node, NodeIndex, NodeVariant, TypedNodeIndex};
+
+type Mono = [f32; 1];
+
+/// Outputs a constant.
+#[derive(Debug, PartialEq)]
+struct Dc(f32);
+
+/// Scales its input.
+#[derive(Debug, PartialEq)]
+struct Gain(f32);
+
+#[derive(Debug)]
+enum Test {
+    /// A **Dc** node.
+    Dc(Dc),
+    /// A **Gain** node.
+    Gain(Gain),
+}
+
+impl Node<Mono> for Dc {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+}
+
+impl Node<Mono> for Gain {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            frame[0] *= self.0;
+        }
+    }
+}
+
+impl Node<Mono> for Test {
+    fn audio_requested(&mut self, buffer: &mut [Mono], sample_hz: f64) {
+        match self {
+            Test::Dc(dc) => dc.audio_requested(buffer, sample_hz),
+            Test::Gain(gain) => gain.audio_requested(buffer, sample_hz),
+        }
+    }
+}
+
+impl From<Dc> for Test {
+    fn from(dc: Dc) -> Self {
+        Test::Dc(dc)
+    }
+}
+
+impl From<Gain> for Test {
+    fn from(gain: Gain) -> Self {
+        Test::Gain(gain)
+    }
+}
+
+impl NodeVariant<Dc> for Test {
+    fn as_variant(&self) -> Option<&Dc> {
+        match self {
+            Test::Dc(dc) => Some(dc),
+            _ => None,
+        }
+    }
+
+    fn as_variant_mut(&mut self) -> Option<&mut Dc> {
+        match self {
+            Test::Dc(dc) => Some(dc),
+            _ => None,
+        }
+    }
+}
+
+impl NodeVariant<Gain> for Test {
+    fn as_variant(&self) -> Option<&Gain> {
+        match self {
+            Test::Gain(gain) => Some(gain),
+            _ => None,
+        }
+    }
+
+    fn as_variant_mut(&mut self) -> Option<&mut Gain> {
+        match self {
+            Test::Gain(gain) => Some(gain),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn variants_are_accessed_as_their_own_types() {
+    let mut graph: Graph<Mono, Test> = Graph::new();
+    let dc = graph.add_node_typed(Dc(1.0));
+    let gain = graph.add_node_typed(Gain(2.0));
+    graph.add_connection(dc.node_index(), gain.into()).unwrap();
+    graph.set_master(Some(gain.node_index()));
+
+    assert_eq!(graph.node_as(dc), Some(&Dc(1.0)));
+    graph.node_as_mut(gain).unwrap().0 = 3.0;
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(buffer, [[3.0]; 4]);
+}
+
+#[test]
+fn other_variants_and_missing_nodes_are_none() {
+    let mut graph: Graph<Mono, Test> = Graph::new();
+    let dc = graph.add_node_typed(Dc(1.0));
+    let wrong: TypedNodeIndex<Gain> = TypedNodeIndex::new(dc.node_index());
+    assert_eq!(graph.node_as(wrong), None);
+    graph.remove_node(dc.node_index());
+    assert_eq!(graph.node_as(dc), None);
+    assert_eq!(
+        graph.node_as_mut(TypedNodeIndex::<Dc>::new(NodeIndex::new(3))),
+        None
+    );
+}
+
+#[test]
+fn every_type_is_a_variant_of_itself() {
+    let mut graph: Graph<Mono, Gain> = Graph::new();
+    let gain = graph.add_node_typed(Gain(0.5));
+    assert_eq!(graph.node_as(gain), Some(&Gain(0.5)));
+}
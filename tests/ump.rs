@@ -0,0 +1,90 @@
+//! Universal MIDI Packets are decoded into events, skipping unsupported messages.
+
+use dsp::event::{ump, Event};
+use dsp::{Graph, Node};
+
+type Mono = [f32; 1];
+
+/// Records the events that it handles.
+struct Recorder(Vec<Event>);
+
+impl Node<Mono> for Recorder {
+    fn audio_requested(&mut self, _buffer: &mut [Mono], _sample_hz: f64) {}
+
+    fn handle_event(&mut self, event: &Event) {
+        self.0.push(*event);
+    }
+}
+
+#[test]
+fn midi1_messages_are_decoded() {
+    // A note-on in group 1 on channel 2, then a note-on with a velocity of zero.
+    let events: Vec<_> = ump::decode(&[0x2192_3C7F, 0x2090_3C00]).collect();
+    assert_eq!(
+        events,
+        vec![
+            Event::NoteOn {
+                channel: 18,
+                note: 60,
+                velocity: 1.0,
+            },
+            Event::NoteOff {
+                channel: 0,
+                note: 60,
+                velocity: 0.0,
+            },
+        ]
+    );
+    let bend = ump::decode_packet(&[0x20E0_0040]);
+    assert_eq!(
+        bend,
+        Some(Event::PitchBend {
+            channel: 0,
+            value: 0.0,
+        })
+    );
+}
+
+#[test]
+fn midi2_messages_are_decoded_at_full_resolution() {
+    let note_on = ump::decode_packet(&[0x4090_3C00, 0xFFFF_0000]);
+    assert_eq!(
+        note_on,
+        Some(Event::NoteOn {
+            channel: 0,
+            note: 60,
+            velocity: 1.0,
+        })
+    );
+    let note_bend = ump::decode_packet(&[0x4060_3C00, 0x8000_0000]);
+    assert_eq!(
+        note_bend,
+        Some(Event::NotePitchBend {
+            channel: 0,
+            note: 60,
+            value: 0.0,
+        })
+    );
+}
+
+#[test]
+fn unsupported_and_truncated_packets_are_skipped() {
+    assert_eq!(ump::packet_len(0x4090_3C00), 2);
+    // A system message, a SysEx packet, a note-on and a truncated MIDI 2.0 note-on.
+    let words = [0x10F8_0000, 0x3000_0000, 0, 0x2090_3C7F, 0x4090_3C00];
+    assert_eq!(ump::decode(&words).count(), 1);
+}
+
+#[test]
+fn packets_are_scheduled_for_nodes() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Recorder(Vec::new()));
+    graph.set_master(Some(node));
+    assert_eq!(
+        graph.schedule_ump(node, 0, &[0x2090_3C7F, 0x10F8_0000]),
+        Ok(1)
+    );
+    let mut buffer = [[0.0]; 8];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    assert_eq!(graph[node].0.len(), 1);
+}
@@ -0,0 +1,122 @@
+//! Random sequences of mutations must never break the **Graph**'s invariants.
+
+use dsp::{Frame, Graph, Node, NodeIndex};
+
+type Mono = [f32; 1];
+
+/// A node that adds a constant to its input so that every path through the graph is audible.
+struct Offset(f32);
+
+impl Node<Mono> for Offset {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        for frame in buffer.iter_mut() {
+            *frame = frame.add_amp([self.0]);
+        }
+    }
+}
+
+/// A small xorshift generator so that every run performs the same mutations.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+fn random_node(graph: &Graph<Mono, Offset>, rng: &mut Rng) -> Option<NodeIndex> {
+    match graph.node_count() {
+        0 => None,
+        n => Some(NodeIndex::new(rng.below(n))),
+    }
+}
+
+#[test]
+fn random_mutations_keep_invariants() {
+    for seed in 1..=16u64 {
+        let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let mut graph = Graph::new();
+        graph.set_validate_mutations(true);
+        let mut buffer = vec![[0.0]; 64];
+
+        for step in 0..500 {
+            match rng.below(10) {
+                0..=1 => {
+                    graph.add_node(Offset(step as f32));
+                }
+                2..=4 => {
+                    if let (Some(a), Some(b)) =
+                        (random_node(&graph, &mut rng), random_node(&graph, &mut rng))
+                    {
+                        // Connections that would form a cycle are rejected without any change.
+                        let _ = graph.add_connection(a, b);
+                    }
+                }
+                5 => {
+                    if let (Some(a), Some(b)) =
+                        (random_node(&graph, &mut rng), random_node(&graph, &mut rng))
+                    {
+                        graph.remove_connection(a, b);
+                    }
+                }
+                6 => {
+                    if let Some(n) = random_node(&graph, &mut rng) {
+                        graph.remove_node(n);
+                    }
+                }
+                7 => {
+                    let master = random_node(&graph, &mut rng);
+                    graph.set_master(master);
+                }
+                8 => {
+                    if rng.below(4) == 0 {
+                        graph.clear_disconnected();
+                    } else {
+                        let len = 1 + rng.below(buffer.len());
+                        graph.prepare_buffers(len);
+                    }
+                }
+                _ => {
+                    let len = 1 + rng.below(buffer.len());
+                    graph.audio_requested(&mut buffer[..len], 44_100.0);
+                }
+            }
+
+            let report = graph.validate();
+            assert!(
+                report.is_valid(),
+                "seed {}, step {}: {}",
+                seed,
+                step,
+                report
+            );
+        }
+    }
+}
+
+#[test]
+fn validation_is_enabled_in_debug_builds() {
+    let mut graph: Graph<Mono, Offset> = Graph::new();
+    assert_eq!(graph.validates_mutations(), cfg!(debug_assertions));
+    graph.set_validate_mutations(false);
+    assert!(!graph.validates_mutations());
+    graph.set_validate_mutations(true);
+    assert!(graph.validates_mutations());
+}
+
+#[test]
+fn self_connections_are_rejected() {
+    let mut graph: Graph<Mono, Offset> = Graph::new();
+    let a = graph.add_node(Offset(1.0));
+    assert!(graph.add_connection(a, a).is_err());
+    assert!(graph.add_connections(vec![(a, a)]).is_err());
+    assert_eq!(graph.connection_count(), 0);
+    assert!(graph.validate().is_valid());
+}
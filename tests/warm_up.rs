@@ -0,0 +1,51 @@
+//! Warming up renders the frames leading up to the transport position and no further.
+
+use dsp::{Graph, Node};
+use std::cell::Cell;
+use std::rc::Rc;
+
+type Mono = [f32; 1];
+
+/// Counts the frames that it renders.
+struct Counter(Rc<Cell<usize>>);
+
+impl Node<Mono> for Counter {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        self.0.set(self.0.get() + buffer.len());
+    }
+}
+
+fn counting() -> (Graph<Mono, Counter>, Rc<Cell<usize>>) {
+    let frames = Rc::new(Cell::new(0));
+    let mut graph = Graph::new();
+    let counter = graph.add_node(Counter(frames.clone()));
+    graph.set_master(Some(counter));
+    graph.prepare_buffers(64);
+    (graph, frames)
+}
+
+#[test]
+fn warm_up_is_rounded_to_whole_blocks() {
+    let (mut graph, frames) = counting();
+    graph.set_position(1_000);
+    graph.warm_up(100, 44_100.0);
+    assert_eq!(frames.get(), 128);
+    assert_eq!(graph.position(), 1_000);
+}
+
+#[test]
+fn warm_up_ends_at_the_position_near_the_start() {
+    let (mut graph, frames) = counting();
+    graph.set_position(100);
+    graph.warm_up(512, 44_100.0);
+    assert_eq!(frames.get(), 100);
+    assert_eq!(graph.position(), 100);
+}
+
+#[test]
+fn warm_up_at_the_start_renders_nothing() {
+    let (mut graph, frames) = counting();
+    graph.warm_up(512, 44_100.0);
+    assert_eq!(frames.get(), 0);
+    assert_eq!(graph.position(), 0);
+}
@@ -0,0 +1,80 @@
+//! The watchdog bypasses nodes that repeatedly take too long to render.
+
+use dsp::{Graph, Node, Notification, Watchdog};
+use std::time::Duration;
+
+type Mono = [f32; 1];
+
+/// Outputs a constant after sleeping for the given duration.
+struct Slow(f32, Duration);
+
+impl Node<Mono> for Slow {
+    fn audio_requested(&mut self, buffer: &mut [Mono], _sample_hz: f64) {
+        std::thread::sleep(self.1);
+        for frame in buffer.iter_mut() {
+            *frame = [self.0];
+        }
+    }
+}
+
+/// A node that sleeps for the given duration as the output.
+fn graph(sleep: Duration) -> (Graph<Mono, Slow>, dsp::NodeIndex) {
+    let mut graph = Graph::new();
+    let node = graph.add_node(Slow(1.0, sleep));
+    graph.set_master(Some(node));
+    (graph, node)
+}
+
+/// Render a buffer, returning its first frame.
+fn render(graph: &mut Graph<Mono, Slow>) -> f32 {
+    let mut buffer = [[0.0]; 4];
+    graph.audio_requested(&mut buffer, 44_100.0);
+    buffer[0][0]
+}
+
+#[test]
+fn nodes_that_repeatedly_overrun_are_bypassed() {
+    let (mut graph, node) = graph(Duration::from_millis(2));
+    let watchdog = Watchdog {
+        budget: 0.5,
+        max_overruns: 2,
+    };
+    graph.set_watchdog(Some(watchdog));
+    graph.set_bypass_fade_frames(0);
+    assert_eq!(graph.watchdog(), Some(watchdog));
+    let notifications = graph.subscribe_notifications();
+
+    assert_eq!(render(&mut graph), 1.0);
+    assert_eq!(render(&mut graph), 1.0);
+    assert!(!graph.is_bypassed(node));
+    assert!(graph.render_time(node).unwrap() >= Duration::from_millis(2));
+
+    render(&mut graph);
+    assert!(graph.is_bypassed(node));
+    let received: Vec<_> = notifications.try_iter().collect();
+    assert_eq!(received, vec![Notification::NodeOverBudget(node)]);
+    // Bypassed nodes pass their (silent) input through.
+    assert_eq!(render(&mut graph), 0.0);
+}
+
+#[test]
+fn nodes_within_their_budget_are_left_alone() {
+    let (mut graph, node) = graph(Duration::from_millis(0));
+    let watchdog = Watchdog {
+        budget: 1_000.0,
+        ..Watchdog::default()
+    };
+    graph.set_watchdog(Some(watchdog));
+    for _ in 0..20 {
+        render(&mut graph);
+    }
+    assert!(!graph.is_bypassed(node));
+}
+
+#[test]
+fn nodes_are_not_timed_without_a_watchdog() {
+    let (mut graph, node) = graph(Duration::from_millis(1));
+    assert_eq!(graph.watchdog(), None);
+    render(&mut graph);
+    assert_eq!(graph.render_time(node), Some(Duration::from_secs(0)));
+}